@@ -0,0 +1,213 @@
+//! Benchmarks of an already running process (`--attach`). Instead of spawning a command,
+//! each run observes the process for a fixed duration and records the CPU time that it
+//! consumed, its peak memory usage and its page faults in that window. This is useful for
+//! server processes that are driven by an external load. Only supported on Linux, where
+//! the statistics are read from `/proc/<pid>`.
+
+use std::fs;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::benchmark::benchmark_result::ShellSpawningTime;
+use crate::benchmark::executor::{BenchmarkIteration, Executor, RunContext};
+use crate::benchmark::measurement::Measurement;
+use crate::benchmark::sampler::{self, AuxiliarySampler};
+use crate::command::Command;
+use crate::options::{AttachTarget, CmdFailureAction, CommandOutputPolicy, Options};
+use crate::output::log::log_verbose;
+use crate::quantity::{kibibyte, second, Information, Quantity, Time};
+
+use anyhow::{bail, Context, Result};
+
+/// Time between two samples of the memory usage
+const SAMPLING_INTERVAL: Duration = Duration::from_millis(10);
+
+/// CPU times (in clock ticks) and page faults of a process, from `/proc/<pid>/stat`
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ProcessStats {
+    minor_page_faults: u64,
+    major_page_faults: u64,
+    user_ticks: u64,
+    system_ticks: u64,
+}
+
+impl ProcessStats {
+    fn parse(stat: &str) -> Option<Self> {
+        // The command name (second field) is enclosed in parentheses and may contain spaces
+        let fields: Vec<&str> = stat
+            .get(stat.rfind(')')? + 1..)?
+            .split_whitespace()
+            .collect();
+        let field = |number: usize| fields.get(number - 3)?.parse().ok();
+
+        Some(ProcessStats {
+            minor_page_faults: field(10)?,
+            major_page_faults: field(12)?,
+            user_ticks: field(14)?,
+            system_ticks: field(15)?,
+        })
+    }
+}
+
+/// Parse the resident set size from the contents of `/proc/<pid>/status`
+fn parse_resident_set_size(status: &str) -> Option<Information> {
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kib: f64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(Information::new::<kibibyte>(kib))
+}
+
+/// Runs the benchmark iterations by observing an already running process. Intermediate
+/// commands (e.g. '--setup' or '--prepare') are run by the wrapped executor.
+pub struct AttachExecutor<'a> {
+    target: AttachTarget,
+    options: &'a Options,
+    inner: Box<dyn Executor + 'a>,
+    samplers: Vec<Box<dyn AuxiliarySampler>>,
+}
+
+impl<'a> AttachExecutor<'a> {
+    pub fn new(target: AttachTarget, options: &'a Options, inner: Box<dyn Executor + 'a>) -> Self {
+        AttachExecutor {
+            target,
+            options,
+            inner,
+            samplers: vec![],
+        }
+    }
+
+    fn read_stats(&self) -> Result<ProcessStats> {
+        let path = format!("/proc/{}/stat", self.target.pid);
+        let stat = fs::read_to_string(&path)
+            .with_context(|| format!("The attached process {} is not running", self.target.pid))?;
+        ProcessStats::parse(&stat).with_context(|| format!("Could not parse '{path}'"))
+    }
+
+    fn read_resident_set_size(&self) -> Option<Information> {
+        let status = fs::read_to_string(format!("/proc/{}/status", self.target.pid)).ok()?;
+        parse_resident_set_size(&status)
+    }
+
+    /// Observe the process for the configured duration
+    fn observe(&self) -> Result<Measurement> {
+        let duration = Duration::from_secs_f64(self.target.duration.get::<second>());
+
+        let start_stats = self.read_stats()?;
+        let start_time = SystemTime::now();
+        let start = Instant::now();
+        let mut peak_memory_usage = Information::zero();
+        while start.elapsed() < duration {
+            match self.read_resident_set_size() {
+                Some(rss) if rss > peak_memory_usage => peak_memory_usage = rss,
+                Some(_) => {}
+                None => bail!("The attached process {} has exited", self.target.pid),
+            }
+            thread::sleep(SAMPLING_INTERVAL.min(duration.saturating_sub(start.elapsed())));
+        }
+        let time_wall_clock = Time::new::<second>(start.elapsed().as_secs_f64());
+        let end_stats = self.read_stats()?;
+
+        let ticks = |ticks: u64| Time::new::<second>(ticks as f64 / clock_ticks_per_second());
+        Ok(Measurement {
+            start_time: Some(start_time),
+            time_wall_clock,
+            time_user: ticks(end_stats.user_ticks.saturating_sub(start_stats.user_ticks)),
+            time_system: ticks(
+                end_stats
+                    .system_ticks
+                    .saturating_sub(start_stats.system_ticks),
+            ),
+            peak_memory_usage,
+            minor_page_faults: end_stats
+                .minor_page_faults
+                .saturating_sub(start_stats.minor_page_faults),
+            major_page_faults: end_stats
+                .major_page_faults
+                .saturating_sub(start_stats.major_page_faults),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(unix)]
+fn clock_ticks_per_second() -> f64 {
+    // SAFETY: sysconf has no preconditions
+    match unsafe { libc::sysconf(libc::_SC_CLK_TCK) } {
+        ticks if ticks > 0 => ticks as f64,
+        _ => 100.0,
+    }
+}
+
+#[cfg(not(unix))]
+fn clock_ticks_per_second() -> f64 {
+    100.0
+}
+
+impl Executor for AttachExecutor<'_> {
+    fn run_command_and_measure(
+        &self,
+        command: &Command<'_>,
+        run: RunContext<'_>,
+        command_failure_action: Option<CmdFailureAction>,
+        output_policy: &CommandOutputPolicy,
+    ) -> Result<Measurement> {
+        if run.iteration == BenchmarkIteration::NonBenchmarkRun {
+            return self.inner.run_command_and_measure(
+                command,
+                run,
+                command_failure_action,
+                output_policy,
+            );
+        }
+
+        let (measurement, auxiliary) = sampler::sample_during(&self.samplers, || self.observe());
+        let mut measurement = measurement?;
+        measurement.auxiliary = auxiliary;
+        Ok(measurement)
+    }
+
+    fn calibrate(&mut self) -> Result<()> {
+        if !cfg!(target_os = "linux") {
+            bail!("'--attach' is only supported on Linux");
+        }
+        self.read_stats()?;
+        log_verbose!(
+            "Attached to process {}, observing it for {} per run",
+            self.target.pid,
+            self.target.duration.format_auto()
+        );
+
+        self.samplers = sampler::from_options(self.options)?;
+        Ok(())
+    }
+
+    fn time_overhead(&self) -> Time {
+        Time::zero()
+    }
+
+    fn shell_spawning_time(&self) -> Option<ShellSpawningTime> {
+        None
+    }
+}
+
+#[test]
+fn test_parse_process_stats() {
+    let stat = "1234 (my (weird) server) S 1 1234 1234 0 -1 4194560 812 0 3 0 \
+                150 42 0 0 20 0 4 0 12345 123456789 2048 18446744073709551615";
+    assert_eq!(
+        ProcessStats::parse(stat),
+        Some(ProcessStats {
+            minor_page_faults: 812,
+            major_page_faults: 3,
+            user_ticks: 150,
+            system_ticks: 42,
+        })
+    );
+    assert_eq!(ProcessStats::parse("1234 (truncated) S 1"), None);
+
+    let status = "Name:\tserver\nVmHWM:\t    8192 kB\nVmRSS:\t    4096 kB\n";
+    assert_eq!(
+        parse_resident_set_size(status),
+        Some(Information::new::<kibibyte>(4096.0))
+    );
+    assert_eq!(parse_resident_set_size("Name:\tkthread\n"), None);
+}
@@ -1,17 +1,90 @@
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
 
-use serde::Serialize;
+use serde::{Serialize, Serializer};
 
-use crate::benchmark::measurement::Measurements;
-use crate::quantity::Time;
+use crate::benchmark::measurement::{CapturedOutput, Measurements, RunIndices};
+use crate::output::warnings::RecordedWarning;
+use crate::quantity::{serialize_time, Time};
+use crate::util::git::GitRevision;
 
 /// Parameter value and whether it was used in the command line template
 #[derive(Debug, Default, Clone, Serialize, PartialEq)]
 pub struct Parameter {
     pub value: String,
+
+    /// The value as a JSON number, if it is numeric. Numeric values are compared as numbers
+    /// (2 < 10) instead of as text.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub numeric_value: Option<serde_json::Number>,
+
     pub is_unused: bool,
 }
 
+impl Parameter {
+    pub fn new(value: String, is_unused: bool) -> Self {
+        let numeric_value = match value.parse::<i64>() {
+            Ok(integer) => Some(integer.into()),
+            Err(_) => value
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64),
+        };
+        Parameter {
+            value,
+            numeric_value,
+            is_unused,
+        }
+    }
+
+    /// Compare the values of two parameters, numerically if both of them are numeric
+    pub fn compare_values(&self, other: &Parameter) -> Ordering {
+        match (
+            self.numeric_value
+                .as_ref()
+                .and_then(serde_json::Number::as_f64),
+            other
+                .numeric_value
+                .as_ref()
+                .and_then(serde_json::Number::as_f64),
+        ) {
+            (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+            _ => self.value.cmp(&other.value),
+        }
+    }
+}
+
+/// Time needed to spawn the shell, as measured during the calibration
+#[derive(Debug, Default, Clone, Copy, Serialize, PartialEq)]
+pub struct ShellSpawningTime {
+    #[serde(serialize_with = "serialize_time")]
+    pub time_wall_clock: Time,
+
+    #[serde(serialize_with = "serialize_time")]
+    pub time_user: Time,
+
+    #[serde(serialize_with = "serialize_time")]
+    pub time_system: Time,
+
+    /// Whether the spawning time has been subtracted from the measured times
+    pub subtracted: bool,
+}
+
+fn serialize_times<S>(times: &[Time], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    struct SerializedTime<'a>(&'a Time);
+
+    impl Serialize for SerializedTime<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serialize_time(self.0, serializer)
+        }
+    }
+
+    serializer.collect_seq(times.iter().map(SerializedTime))
+}
+
 /// Meta data and performance metrics for a single benchmark
 #[derive(Debug, Default, Clone, Serialize, PartialEq)]
 pub struct BenchmarkResult {
@@ -25,6 +98,57 @@ pub struct BenchmarkResult {
     /// Parameter values for this benchmark
     #[serde(skip_serializing_if = "BTreeMap::is_empty")]
     pub parameters: BTreeMap<String, Parameter>,
+
+    /// The command (or its name) before the parameters were substituted, which identifies
+    /// the benchmarks of a parameter scan
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command_template: Option<String>,
+
+    /// Shell spawning time, if the commands were run through a shell
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shell_spawning_time: Option<ShellSpawningTime>,
+
+    /// The first part of the output of the first benchmark run (see `--capture-output`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub captured_output: Option<CapturedOutput>,
+
+    /// Locations of the modes of the wall clock times, if their distribution is multimodal
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_times"
+    )]
+    pub modes: Vec<Time>,
+
+    /// Positions of the fastest, the median and the slowest run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_indices: Option<RunIndices>,
+
+    /// Position in which the benchmark was executed, if that differs from the order of the
+    /// results (see `--scan-order`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_order: Option<usize>,
+
+    /// A hyperfine command line that reruns only this benchmark
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reproduce: Option<String>,
+
+    /// The revision of the git repository that contains the benchmarked program (see
+    /// `--git-annotate`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitRevision>,
+
+    /// Whether each run depends on the state left behind by the previous one (see
+    /// `--stateful` and the `{prev_run}` placeholder)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub stateful: bool,
+
+    /// The group of commands that the benchmark is compared with (see `--group`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+
+    /// The warnings about the result, e.g. if statistical outliers were detected
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<RecordedWarning>,
 }
 
 impl BenchmarkResult {
@@ -53,3 +177,74 @@ impl BenchmarkResult {
         format!("{}{}", self.command, parameters)
     }
 }
+
+/// The results of each group of commands (see `--group`), in the order of the first result of
+/// each group. The results without a group form a group of their own.
+pub fn groups(results: &[BenchmarkResult]) -> Vec<(Option<&str>, Vec<BenchmarkResult>)> {
+    let mut groups: Vec<(Option<&str>, Vec<BenchmarkResult>)> = vec![];
+    for result in results {
+        let group = result.group.as_deref();
+        match groups.iter_mut().find(|(name, _)| *name == group) {
+            Some((_, members)) => members.push(result.clone()),
+            None => groups.push((group, vec![result.clone()])),
+        }
+    }
+    groups
+}
+
+#[test]
+fn test_groups() {
+    let result = |command: &str, group: Option<&str>| BenchmarkResult {
+        command: command.into(),
+        group: group.map(String::from),
+        ..Default::default()
+    };
+    let results = [
+        result("a", Some("io")),
+        result("b", Some("cpu")),
+        result("c", Some("io")),
+        result("d", None),
+    ];
+
+    let groups: Vec<_> = groups(&results)
+        .into_iter()
+        .map(|(group, members)| {
+            let commands: Vec<_> = members.into_iter().map(|result| result.command).collect();
+            (group, commands)
+        })
+        .collect();
+    assert_eq!(
+        groups,
+        [
+            (Some("io"), vec!["a".to_string(), "c".to_string()]),
+            (Some("cpu"), vec!["b".to_string()]),
+            (None, vec!["d".to_string()]),
+        ]
+    );
+}
+
+#[test]
+fn test_parameter_values_are_compared_numerically() {
+    let parameter = |value: &str| Parameter::new(value.to_string(), false);
+
+    assert_eq!(parameter("10").numeric_value, Some(10.into()));
+    assert_eq!(
+        parameter("0.5").numeric_value,
+        serde_json::Number::from_f64(0.5)
+    );
+    assert_eq!(parameter("gcc").numeric_value, None);
+    assert_eq!(parameter("NaN").numeric_value, None);
+
+    assert_eq!(
+        parameter("2").compare_values(&parameter("10")),
+        Ordering::Less
+    );
+    assert_eq!(
+        parameter("2").compare_values(&parameter("10a")),
+        Ordering::Greater
+    );
+
+    let json = serde_json::to_value(parameter("10")).unwrap();
+    assert_eq!(json["value"], "10");
+    assert_eq!(json["numeric_value"], 10);
+}
@@ -0,0 +1,476 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+use std::time::UNIX_EPOCH;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::benchmark::measurement::{Measurement, Measurements};
+use crate::command::Command;
+use crate::quantity::{byte, second, Information, Time};
+use crate::util::exit_code::{exit_status_from_code, extract_exit_code};
+use crate::util::hash::{fnv1a, FNV_OFFSET_BASIS};
+use crate::util::program::find_program;
+
+/// Identifies a cached benchmark result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheKey {
+    /// The command line after parameter substitution
+    command_line: String,
+
+    /// Parameter values for this benchmark
+    parameters: BTreeMap<String, String>,
+
+    /// Hash of the program that is being executed, if it could be resolved
+    binary_hash: Option<String>,
+
+    /// Hash of the paths and modification times of the watched files (see
+    /// `--skip-if-unchanged`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    watched_files_hash: Option<String>,
+
+    /// The options which affect the measurements (see `Options::measurement_settings`)
+    #[serde(default)]
+    measurement_settings: String,
+}
+
+impl CacheKey {
+    fn file_name(&self) -> String {
+        let mut hash = FNV_OFFSET_BASIS;
+        hash = fnv1a(hash, self.command_line.as_bytes());
+        for (name, value) in &self.parameters {
+            hash = fnv1a(hash, &[0]);
+            hash = fnv1a(hash, name.as_bytes());
+            hash = fnv1a(hash, &[0]);
+            hash = fnv1a(hash, value.as_bytes());
+        }
+        if let Some(ref binary_hash) = self.binary_hash {
+            hash = fnv1a(hash, &[0]);
+            hash = fnv1a(hash, binary_hash.as_bytes());
+        }
+        hash = fnv1a(hash, &[0]);
+        hash = fnv1a(hash, self.measurement_settings.as_bytes());
+        format!("{hash:016x}.json")
+    }
+}
+
+/// A single run, as stored in the cache
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedRun {
+    /// Wall clock time, in seconds
+    time_wall_clock: f64,
+
+    /// User time, in seconds
+    time_user: f64,
+
+    /// System time, in seconds
+    time_system: f64,
+
+    /// Peak memory usage, in bytes
+    peak_memory_usage: f64,
+
+    #[serde(default)]
+    minor_page_faults: u64,
+
+    #[serde(default)]
+    major_page_faults: u64,
+
+    #[serde(default)]
+    process_count: Option<u64>,
+
+    exit_code: i32,
+
+    #[serde(default)]
+    memory_limit_exceeded: bool,
+
+    #[serde(default)]
+    discarded: bool,
+
+    #[serde(default)]
+    failed_attempt: bool,
+
+    /// Number of bytes written to stdout and stderr, if recorded
+    #[serde(default)]
+    stdout_size: Option<f64>,
+
+    #[serde(default)]
+    stderr_size: Option<f64>,
+
+    /// Change in disk usage in bytes, if recorded
+    #[serde(default)]
+    disk_usage_delta: Option<f64>,
+
+    #[serde(default)]
+    syscall_count: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    key: CacheKey,
+    runs: Vec<CachedRun>,
+
+    /// Numbers of system calls of the extra runs (see `--count-syscalls`)
+    #[serde(default)]
+    syscall_counts: Vec<u64>,
+}
+
+/// Stores benchmark results in a directory, such that they can be reused as long as
+/// neither the command, its parameters, the executed program, the watched files, nor the
+/// options which affect the measurements have changed.
+pub struct ResultCache<'a> {
+    directory: &'a Path,
+
+    /// Cached results for commands matching this pattern are ignored
+    refresh: Option<&'a str>,
+
+    /// Cached results are only reused if no file matching this pattern has been modified
+    watched: Option<&'a str>,
+}
+
+impl<'a> ResultCache<'a> {
+    pub fn new(directory: &'a Path, refresh: Option<&'a str>, watched: Option<&'a str>) -> Self {
+        ResultCache {
+            directory,
+            refresh,
+            watched,
+        }
+    }
+
+    /// Compute the cache key for the given command, measured with the given settings
+    pub fn key(&self, command: &Command, measurement_settings: String) -> Result<CacheKey> {
+        let command_line = command.get_command_line();
+
+        let binary_hash = resolve_program(&command_line)
+            .map(|path| {
+                hash_file(&path)
+                    .with_context(|| format!("Failed to hash program '{}'", path.display()))
+            })
+            .transpose()?;
+
+        let watched_files_hash = self
+            .watched
+            .map(|pattern| {
+                hash_modification_times(pattern)
+                    .with_context(|| format!("Failed to look up the files matching '{pattern}'"))
+            })
+            .transpose()?;
+
+        Ok(CacheKey {
+            command_line,
+            parameters: command
+                .get_parameters()
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            binary_hash,
+            watched_files_hash,
+            measurement_settings,
+        })
+    }
+
+    /// Look up the measurements for the given key. Returns `None` if there is no (valid)
+    /// cached result, or if it should be refreshed.
+    pub fn load(&self, key: &CacheKey) -> Option<Measurements> {
+        if self
+            .refresh
+            .is_some_and(|pattern| matches_glob(pattern, &key.command_line))
+        {
+            return None;
+        }
+
+        let content = fs::read(self.directory.join(key.file_name())).ok()?;
+        let entry: CacheEntry = serde_json::from_slice(&content).ok()?;
+        if entry.key != *key {
+            return None;
+        }
+
+        let mut measurements = Measurements::new(
+            entry
+                .runs
+                .into_iter()
+                .map(|run| Measurement {
+                    time_wall_clock: Time::new::<second>(run.time_wall_clock),
+                    time_user: Time::new::<second>(run.time_user),
+                    time_system: Time::new::<second>(run.time_system),
+                    peak_memory_usage: Information::new::<byte>(run.peak_memory_usage),
+                    minor_page_faults: run.minor_page_faults,
+                    major_page_faults: run.major_page_faults,
+                    process_count: run.process_count,
+                    exit_status: exit_status_from_code(run.exit_code),
+                    memory_limit_exceeded: run.memory_limit_exceeded,
+                    discarded: run.discarded,
+                    failed_attempt: run.failed_attempt,
+                    stdout_size: run.stdout_size.map(Information::new::<byte>),
+                    stderr_size: run.stderr_size.map(Information::new::<byte>),
+                    disk_usage_delta: run.disk_usage_delta.map(Information::new::<byte>),
+                    syscall_count: run.syscall_count,
+                    ..Default::default()
+                })
+                .collect(),
+        );
+        measurements.syscall_counts = entry.syscall_counts;
+        Some(measurements)
+    }
+
+    /// Store the measurements for the given key. Results containing runs without an
+    /// exit code (e.g. killed by a signal) are not cached.
+    pub fn store(&self, key: &CacheKey, measurements: &Measurements) -> Result<()> {
+        let runs = measurements
+            .measurements
+            .iter()
+            .map(|m| {
+                extract_exit_code(m.exit_status).map(|exit_code| CachedRun {
+                    time_wall_clock: m.time_wall_clock.get::<second>(),
+                    time_user: m.time_user.get::<second>(),
+                    time_system: m.time_system.get::<second>(),
+                    peak_memory_usage: m.peak_memory_usage.get::<byte>(),
+                    minor_page_faults: m.minor_page_faults,
+                    major_page_faults: m.major_page_faults,
+                    process_count: m.process_count,
+                    exit_code,
+                    memory_limit_exceeded: m.memory_limit_exceeded,
+                    discarded: m.discarded,
+                    failed_attempt: m.failed_attempt,
+                    stdout_size: m.stdout_size.map(|size| size.get::<byte>()),
+                    stderr_size: m.stderr_size.map(|size| size.get::<byte>()),
+                    disk_usage_delta: m.disk_usage_delta.map(|delta| delta.get::<byte>()),
+                    syscall_count: m.syscall_count,
+                })
+            })
+            .collect::<Option<Vec<_>>>();
+
+        let Some(runs) = runs else {
+            return Ok(());
+        };
+
+        fs::create_dir_all(self.directory).with_context(|| {
+            format!(
+                "Failed to create cache directory '{}'",
+                self.directory.display()
+            )
+        })?;
+
+        let path = self.directory.join(key.file_name());
+        let entry = CacheEntry {
+            key: key.clone(),
+            runs,
+            syscall_counts: measurements.syscall_counts.clone(),
+        };
+        fs::write(&path, serde_json::to_vec_pretty(&entry)?)
+            .with_context(|| format!("Failed to write cache file '{}'", path.display()))
+    }
+}
+
+/// Find the program that is executed by the given command line, by looking at its first
+/// word. Returns `None` for shell builtins or programs that can not be found.
+fn resolve_program(command_line: &str) -> Option<PathBuf> {
+    let words = shell_words::split(command_line).ok()?;
+    let program = Path::new(words.first()?);
+    if program.components().count() > 1 {
+        return Some(program.to_path_buf()).filter(|path| path.is_file());
+    }
+
+    find_program(words.first()?)
+}
+
+fn hash_file(path: &Path) -> Result<String> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hash = FNV_OFFSET_BASIS;
+    let mut buf = [0; 64 << 10];
+    loop {
+        let bytes = reader.read(&mut buf)?;
+        if bytes == 0 {
+            break;
+        }
+        hash = fnv1a(hash, &buf[..bytes]);
+    }
+    Ok(format!("{hash:016x}"))
+}
+
+/// Hash the paths and modification times of all files matching the given pattern, in
+/// which '*' also matches path separators (e.g. 'src/*.rs' matches all Rust files below
+/// 'src'). Only the directory before the first wildcard is searched.
+fn hash_modification_times(pattern: &str) -> Result<String> {
+    let pattern = pattern.replace(MAIN_SEPARATOR, "/");
+    let base = pattern
+        .split('/')
+        .take_while(|component| !component.contains(['*', '?']))
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let mut files = vec![];
+    collect_files(
+        Path::new(if base.is_empty() { "." } else { &base }),
+        &mut files,
+    )?;
+
+    let mut modification_times = files
+        .into_iter()
+        .map(|path| {
+            let path = path.to_string_lossy().replace(MAIN_SEPARATOR, "/");
+            match path.strip_prefix("./") {
+                Some(relative) if base.is_empty() => relative.to_string(),
+                _ => path,
+            }
+        })
+        .filter(|path| matches_glob(&pattern, path))
+        .map(|path| {
+            let modified = fs::metadata(&path)?.modified()?;
+            let nanos = modified
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_nanos());
+            Ok((path, nanos))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    modification_times.sort();
+
+    let mut hash = FNV_OFFSET_BASIS;
+    for (path, nanos) in &modification_times {
+        hash = fnv1a(hash, path.as_bytes());
+        hash = fnv1a(hash, &[0]);
+        hash = fnv1a(hash, &nanos.to_le_bytes());
+    }
+    Ok(format!("{hash:016x}"))
+}
+
+/// All files in the given directory tree (or the given file itself, if it exists).
+/// Symbolic links to directories are not followed.
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return Ok(());
+    };
+    if !metadata.is_dir() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(path)? {
+        collect_files(&entry?.path(), files)?;
+    }
+    Ok(())
+}
+
+/// Match a text against a pattern in which `*` matches any sequence of characters and
+/// `?` matches a single character.
+fn matches_glob(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[test]
+fn test_matches_glob() {
+    assert!(matches_glob("*", "anything"));
+    assert!(matches_glob("sleep *", "sleep 0.1"));
+    assert!(matches_glob("*--release*", "cargo build --release -j 4"));
+    assert!(matches_glob("sleep 0.?", "sleep 0.1"));
+    assert!(matches_glob("a*b*c", "aXbYbZc"));
+    assert!(!matches_glob("sleep *", "echo sleep"));
+    assert!(!matches_glob("sleep 0.?", "sleep 0.12"));
+    assert!(!matches_glob("a*b*c", "aXbYbZ"));
+}
+
+#[test]
+fn test_cache_roundtrip() {
+    use crate::quantity::millisecond;
+
+    let directory = tempfile::tempdir().unwrap();
+    let cache = ResultCache::new(directory.path(), None, None);
+
+    let key = cache
+        .key(&Command::new(None, "sleep 0.01"), "--runs=1".into())
+        .unwrap();
+    assert!(cache.load(&key).is_none());
+
+    let measurements = Measurements::new(vec![Measurement {
+        time_wall_clock: Time::new::<millisecond>(12.5),
+        time_user: Time::new::<millisecond>(1.0),
+        time_system: Time::new::<millisecond>(2.0),
+        peak_memory_usage: Information::new::<byte>(1024.0),
+        minor_page_faults: 42,
+        exit_status: exit_status_from_code(3),
+        ..Default::default()
+    }]);
+    cache.store(&key, &measurements).unwrap();
+    assert_eq!(cache.load(&key), Some(measurements));
+
+    let other_key = cache
+        .key(&Command::new(None, "sleep 0.02"), "--runs=1".into())
+        .unwrap();
+    assert!(cache.load(&other_key).is_none());
+
+    let other_settings_key = cache
+        .key(&Command::new(None, "sleep 0.01"), "--runs=2".into())
+        .unwrap();
+    assert!(cache.load(&other_settings_key).is_none());
+
+    let refreshing_cache = ResultCache::new(directory.path(), Some("sleep *"), None);
+    assert!(refreshing_cache.load(&key).is_none());
+}
+
+#[test]
+fn test_watched_files() {
+    use std::time::{Duration, SystemTime};
+
+    let directory = tempfile::tempdir().unwrap();
+    let sources = directory.path().join("src");
+    fs::create_dir_all(sources.join("nested")).unwrap();
+    let source = sources.join("nested").join("main.c");
+    fs::write(&source, "int main() {}").unwrap();
+    fs::write(sources.join("notes.txt"), "").unwrap();
+
+    let pattern = format!("{}/*.c", sources.display());
+    let cache = ResultCache::new(directory.path(), None, Some(&pattern));
+    let command = Command::new(None, "sleep 0.01");
+    let key = cache.key(&command, String::new()).unwrap();
+    let measurements = Measurements::new(vec![Measurement::default()]);
+    cache.store(&key, &measurements).unwrap();
+    assert_eq!(cache.key(&command, String::new()).unwrap(), key);
+    assert_eq!(cache.load(&key), Some(measurements));
+
+    // Files that do not match the pattern are not watched
+    File::options()
+        .write(true)
+        .open(sources.join("notes.txt"))
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(60))
+        .unwrap();
+    assert_eq!(cache.key(&command, String::new()).unwrap(), key);
+
+    File::options()
+        .write(true)
+        .open(&source)
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(60))
+        .unwrap();
+    let changed_key = cache.key(&command, String::new()).unwrap();
+    assert_ne!(changed_key, key);
+    assert!(cache.load(&changed_key).is_none());
+}
@@ -0,0 +1,140 @@
+//! Metrics that are computed for each run from its measurements and the parameters of the
+//! benchmark with a formula (see `--derived`), e.g. the throughput of a kernel in GFLOPS
+//! for the problem size of a parameter scan.
+
+use crate::benchmark::measurement::Measurement;
+use crate::command::Command;
+use crate::parameter::expression::Formula;
+use crate::quantity::{byte, second};
+
+/// The variables that a formula can refer to, with their meaning
+pub const VARIABLES: &[(&str, &str)] = &[
+    ("time", "wall clock time in seconds"),
+    ("user", "user time in seconds"),
+    ("system", "system time in seconds"),
+    ("memory", "peak memory usage in bytes"),
+];
+
+#[derive(Debug, Clone)]
+pub struct DerivedMetric {
+    pub name: String,
+    formula: Formula,
+}
+
+impl DerivedMetric {
+    /// Parse a definition like 'gflops = 2*{n}^3 / time / 1e9'
+    pub fn parse(definition: &str) -> Result<Self, String> {
+        let (name, formula) = definition
+            .split_once('=')
+            .ok_or("expected a definition like 'gflops = 2*{n}^3 / time / 1e9'")?;
+
+        let name = name.trim();
+        let is_identifier = name.starts_with(|c: char| c.is_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if !is_identifier {
+            return Err(format!("'{name}' is not a valid metric name"));
+        }
+
+        let formula = Formula::parse(formula)?;
+        if let Some(variable) = formula
+            .variables()
+            .into_iter()
+            .find(|variable| VARIABLES.iter().all(|(name, _)| name != variable))
+        {
+            return Err(format!(
+                "unknown variable '{variable}' (available: {})",
+                VARIABLES
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+
+        Ok(DerivedMetric {
+            name: name.to_string(),
+            formula,
+        })
+    }
+
+    /// Check that all parameters of the formula have numeric values for the given command
+    pub fn check_parameters(&self, command: &Command) -> Result<(), String> {
+        match self
+            .formula
+            .parameters()
+            .into_iter()
+            .find(|parameter| parameter_value(command, parameter).is_none())
+        {
+            Some(parameter) => Err(format!(
+                "the formula of '{}' uses the parameter '{{{parameter}}}', which does not have \
+                 a numeric value for '{}'",
+                self.name,
+                command.get_name()
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// The value of the metric for a single run of the given command
+    pub fn value(&self, command: &Command, measurement: &Measurement) -> Result<f64, String> {
+        self.formula.evaluate(
+            |variable| match variable {
+                "time" => Some(measurement.time_wall_clock.get::<second>()),
+                "user" => Some(measurement.time_user.get::<second>()),
+                "system" => Some(measurement.time_system.get::<second>()),
+                "memory" => Some(measurement.peak_memory_usage.get::<byte>()),
+                _ => None,
+            },
+            |parameter| parameter_value(command, parameter),
+        )
+    }
+}
+
+fn parameter_value(command: &Command, name: &str) -> Option<f64> {
+    command
+        .get_parameters()
+        .iter()
+        .find(|(parameter, _)| *parameter == name)
+        .and_then(|(_, value)| value.to_string().parse().ok())
+}
+
+#[test]
+fn test_derived_metric() {
+    use crate::parameter::ParameterValue;
+    use crate::quantity::{Information, Time};
+
+    let metric = DerivedMetric::parse("gflops = 2*{n}^3 / time / 1e9").unwrap();
+    assert_eq!(metric.name, "gflops");
+
+    let command = Command::new_parametrized(
+        None,
+        "matmul {n}",
+        vec![("n", ParameterValue::Text("1000".into()))],
+    );
+    assert!(metric.check_parameters(&command).is_ok());
+    let measurement = Measurement {
+        time_wall_clock: Time::new::<second>(0.5),
+        ..Default::default()
+    };
+    assert_eq!(metric.value(&command, &measurement), Ok(4.0));
+
+    let metric = DerivedMetric::parse("bytes_per_s=memory/time").unwrap();
+    let measurement = Measurement {
+        time_wall_clock: Time::new::<second>(2.0),
+        peak_memory_usage: Information::new::<byte>(4096.0),
+        ..Default::default()
+    };
+    assert_eq!(metric.value(&command, &measurement), Ok(2048.0));
+
+    let metric = DerivedMetric::parse("rate = {size} / time").unwrap();
+    assert!(metric
+        .check_parameters(&command)
+        .unwrap_err()
+        .contains("'{size}'"));
+
+    let error = |definition| DerivedMetric::parse(definition).unwrap_err();
+    assert!(error("2 / time").contains("expected a definition"));
+    assert!(error("my rate = 2 / time").contains("not a valid metric name"));
+    assert!(error("rate = 2 / wall").contains("unknown variable 'wall'"));
+    assert!(error("rate = 2 /").contains("unexpected end"));
+}
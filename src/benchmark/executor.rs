@@ -1,24 +1,35 @@
+use std::fs::{self, File};
+use std::io::{self, Read};
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
-use std::process::ExitStatus;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
 
+use crate::benchmark::benchmark_result::ShellSpawningTime;
 use crate::benchmark::measurement::Measurement;
 use crate::benchmark::measurement::Measurements;
+use crate::benchmark::sampler::{self, AuxiliarySampler};
 use crate::command::Command;
-use crate::options::{
-    CmdFailureAction, CommandInputPolicy, CommandOutputPolicy, Options, OutputStyleOption, Shell,
-};
+use crate::options::{CmdFailureAction, CommandOutputPolicy, Options, Shell, ShellKind};
+use crate::output::log::{log_debug, log_verbose};
 use crate::output::progress_bar::get_progress_bar;
-use crate::quantity::{second, Information, Quantity, Time};
-use crate::timer::execute_and_measure;
-use crate::util::randomized_environment_offset;
+use crate::quantity::{byte, second, Information, Quantity, Time};
+use crate::shim::ShimRecord;
+#[cfg(not(windows))]
+use crate::timer::execute_pipeline_and_measure;
+use crate::timer::{execute_and_measure, timer};
+use crate::util::hash::{fnv1a, FNV_OFFSET_BASIS};
+use crate::util::{disk_usage, pipeline, randomized_environment_offset, syscall_count};
 
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BenchmarkIteration {
     NonBenchmarkRun,
     Warmup(u64),
     Benchmark(u64),
+    Profile(u64),
+    SyscallCount(u64),
 }
 
 impl BenchmarkIteration {
@@ -27,16 +38,104 @@ impl BenchmarkIteration {
             BenchmarkIteration::NonBenchmarkRun => None,
             BenchmarkIteration::Warmup(i) => Some(format!("warmup-{}", i)),
             BenchmarkIteration::Benchmark(i) => Some(format!("{}", i)),
+            BenchmarkIteration::Profile(i) => Some(format!("profile-{}", i)),
+            BenchmarkIteration::SyscallCount(i) => Some(format!("syscalls-{}", i)),
         }
     }
 }
 
-pub trait Executor {
+/// The benchmark and iteration that a command is run for. This is passed to the
+/// benchmarked command and to the preparation/conclusion commands of the same
+/// iteration via environment variables.
+#[derive(Debug, Clone, Copy)]
+pub struct RunContext<'a> {
+    pub benchmark_name: Option<&'a str>,
+    pub iteration: BenchmarkIteration,
+
+    /// Memory limit of the run (see `--limit-memory`), which only applies to the
+    /// benchmarked command itself
+    pub memory_limit: Option<Information>,
+
+    /// Time after which the benchmarked command is killed (see `--auto-timeout`)
+    pub timeout: Option<Time>,
+
+    /// Whether the system calls of the run are counted (see `--count-syscalls`)
+    pub count_syscalls: bool,
+}
+
+impl<'a> RunContext<'a> {
+    pub fn new(benchmark_name: &'a str, iteration: BenchmarkIteration) -> Self {
+        RunContext {
+            benchmark_name: Some(benchmark_name),
+            iteration,
+            memory_limit: None,
+            timeout: None,
+            count_syscalls: false,
+        }
+    }
+
+    /// A run that does not belong to any benchmark (e.g. for calibration)
+    pub fn standalone() -> Self {
+        RunContext {
+            benchmark_name: None,
+            iteration: BenchmarkIteration::NonBenchmarkRun,
+            memory_limit: None,
+            timeout: None,
+            count_syscalls: false,
+        }
+    }
+
+    pub fn with_memory_limit(self, memory_limit: Option<Information>) -> Self {
+        RunContext {
+            memory_limit,
+            ..self
+        }
+    }
+
+    pub fn with_timeout(self, timeout: Option<Time>) -> Self {
+        RunContext { timeout, ..self }
+    }
+
+    pub fn with_syscall_counting(self, count_syscalls: bool) -> Self {
+        RunContext {
+            count_syscalls,
+            ..self
+        }
+    }
+
+    /// A seed for randomized workloads, which only depends on the benchmark name and
+    /// the iteration, such that it is the same across hyperfine invocations.
+    pub fn seed(&self) -> Option<u64> {
+        let name = self.benchmark_name?;
+        let iteration = self.iteration.to_env_var_value()?;
+
+        let hash = fnv1a(FNV_OFFSET_BASIS, name.as_bytes());
+        let hash = fnv1a(hash, &[0]);
+        Some(fnv1a(hash, iteration.as_bytes()))
+    }
+
+    fn set_env_vars(&self, command: &mut std::process::Command) {
+        if let Some(value) = self.iteration.to_env_var_value() {
+            command.env("HYPERFINE_ITERATION", value);
+        }
+        if let BenchmarkIteration::Benchmark(i) = self.iteration {
+            command.env("HYPERFINE_RUN_NUMBER", i.to_string());
+        }
+        if let Some(name) = self.benchmark_name {
+            command.env("HYPERFINE_BENCHMARK_NAME", name);
+        }
+        if let Some(seed) = self.seed() {
+            command.env("HYPERFINE_RUN_SEED", seed.to_string());
+        }
+    }
+}
+
+pub trait Executor: Sync {
     /// Run the given command and measure the execution time
     fn run_command_and_measure(
         &self,
         command: &Command<'_>,
-        iteration: BenchmarkIteration,
+        run: RunContext<'_>,
         command_failure_action: Option<CmdFailureAction>,
         output_policy: &CommandOutputPolicy,
     ) -> Result<Measurement>;
@@ -52,62 +151,292 @@ pub trait Executor {
     /// that is being used in addition to the actual runtime
     /// of the command.
     fn time_overhead(&self) -> Time;
+
+    /// Return the shell spawning time that was measured during
+    /// the calibration, if commands are run through a shell.
+    fn shell_spawning_time(&self) -> Option<ShellSpawningTime>;
 }
 
+/// Parts of the (lowercase) error messages with which common runtimes report a failed
+/// memory allocation
+const OUT_OF_MEMORY_MESSAGES: &[&str] = &[
+    "out of memory",
+    "cannot allocate memory",
+    "memory allocation of",
+    "failed to allocate",
+    "memoryerror",
+    "bad_alloc",
+];
+
+/// Run the given command (or the stages of a pipeline, see '--pipeline-stages') and
+/// measure it
 fn run_command_and_measure_common(
-    mut command: std::process::Command,
-    iteration: BenchmarkIteration,
+    mut stages: Vec<std::process::Command>,
+    run: RunContext<'_>,
     command_failure_action: CmdFailureAction,
-    command_input_policy: &CommandInputPolicy,
+    options: &Options,
     command_output_policy: &CommandOutputPolicy,
     command_name: &str,
+    samplers: &[Box<dyn AuxiliarySampler>],
 ) -> Result<Measurement> {
-    let stdin = command_input_policy.get_stdin()?;
-    let (stdout, stderr) = command_output_policy.get_stdout_stderr()?;
-    command.stdin(stdin).stdout(stdout).stderr(stderr);
+    let exit_codes = &options.exit_codes;
+
+    // The output of the first benchmark run is captured if requested. For this, the output
+    // needs to be piped instead of being redirected to the null device.
+    let capture_size = options
+        .capture_output
+        .filter(|_| run.iteration == BenchmarkIteration::Benchmark(0));
+    let pipe = CommandOutputPolicy::Pipe;
+    let command_output_policy =
+        if capture_size.is_some() && *command_output_policy == CommandOutputPolicy::Null {
+            &pipe
+        } else {
+            command_output_policy
+        };
+
+    // Each command (i.e. each stage of a pipeline) is traced separately, and their counts
+    // are added up
+    let syscall_summaries: Vec<PathBuf> = if run.count_syscalls {
+        stages
+            .iter()
+            .map(|_| syscall_count::summary_path())
+            .collect()
+    } else {
+        vec![]
+    };
+    for (command, summary) in stages.iter_mut().zip(&syscall_summaries) {
+        *command = syscall_count::traced(command, summary);
+    }
+    let stdin = options.command_input_policy.get_stdin()?;
+    let (stdout, stderr, error_output) = command_output_policy.get_stdout_stderr()?;
+    let (last, upstream) = stages
+        .split_last_mut()
+        .expect("at least one command is run");
+    upstream.first_mut().unwrap_or(last).stdin(stdin);
+    last.stdout(stdout).stderr(stderr);
+    for stage in upstream {
+        // The error output of the other stages of a pipeline is shown, but not recorded
+        stage.stderr(if *command_output_policy == CommandOutputPolicy::Inherit {
+            Stdio::inherit()
+        } else {
+            Stdio::null()
+        });
+    }
 
-    command.env(
-        "HYPERFINE_RANDOMIZED_ENVIRONMENT_OFFSET",
-        randomized_environment_offset::value(),
+    let env_offset = randomized_environment_offset::random_offset(
+        options
+            .env_offset_max
+            .unwrap_or(randomized_environment_offset::DEFAULT_MAX_OFFSET),
     );
+    for command in &mut stages {
+        command.env(
+            "HYPERFINE_RANDOMIZED_ENVIRONMENT_OFFSET",
+            randomized_environment_offset::value(env_offset),
+        );
+
+        run.set_env_vars(command);
+
+        log_debug!(
+            "Spawning {:?} with arguments {:?} ({:?}, seed {:?})",
+            command.get_program(),
+            command.get_args().collect::<Vec<_>>(),
+            run.iteration,
+            run.seed(),
+        );
+    }
+
+    let disk_usage_path = options.disk_usage_path.as_deref();
+    let disk_usage = || {
+        disk_usage_path
+            .map(disk_usage::disk_usage)
+            .transpose()
+            .context("Could not determine the disk usage ('--measure-disk')")
+    };
+    let disk_usage_before = disk_usage()?;
+
+    let (measurement, auxiliary) = sampler::sample_during(samplers, || {
+        #[cfg(not(windows))]
+        {
+            if stages.len() > 1 {
+                return execute_pipeline_and_measure(
+                    stages,
+                    error_output,
+                    run.memory_limit,
+                    run.timeout,
+                    timer(options.timer),
+                )
+                .with_context(|| format!("Failed to run pipeline '{command_name}'"));
+            }
+        }
+
+        let command = stages.pop().expect("at least one command is run");
+        execute_and_measure(
+            command,
+            error_output,
+            capture_size,
+            run.memory_limit,
+            run.timeout,
+            options.measure_scheduling_delay,
+            timer(options.timer),
+        )
+        .with_context(|| format!("Failed to run command '{command_name}'"))
+    });
+    let mut measurement = if run.count_syscalls {
+        measurement.context("Could not run the command under strace ('--count-syscalls')")?
+    } else {
+        measurement?
+    };
+    measurement.auxiliary = auxiliary;
+    measurement.env_offset = options.env_offset_max.map(|_| env_offset);
+    if let (Some(before), Some(after)) = (disk_usage_before, disk_usage()?) {
+        measurement.disk_usage_delta = Some(Information::new::<byte>(after as f64 - before as f64));
+    }
+    if !syscall_summaries.is_empty() {
+        measurement.syscall_count = Some(
+            syscall_summaries
+                .iter()
+                .map(|summary| syscall_count::read_summary(summary))
+                .sum::<Result<u64>>()
+                .context("Could not count the system calls ('--count-syscalls')")?,
+        );
+    }
+
+    if options.shim.is_some() {
+        apply_shim_record(&mut measurement).with_context(|| {
+            format!(
+                "The measurement shim ('--shim') did not report a measurement for \
+                 '{command_name}'. Make sure that the prefix runs 'hyperfine-shim'."
+            )
+        })?;
+    }
+
+    if let CommandOutputPolicy::File(path) = command_output_policy {
+        measurement.stdout_size = fs::metadata(path)
+            .ok()
+            .map(|metadata| Information::new::<byte>(metadata.len() as f64));
+
+        if let (Some(size), Some(captured_output)) =
+            (capture_size, measurement.captured_output.as_mut())
+        {
+            captured_output.stdout = read_head(path, size).ok();
+        }
+    }
 
-    if let Some(value) = iteration.to_env_var_value() {
-        command.env("HYPERFINE_ITERATION", value);
+    // A run that fails under a memory limit has only run out of memory if its error output
+    // reports a failed allocation (ENOMEM), or if its peak memory usage has reached the
+    // limit. Other failures (e.g. a segmentation fault) are not attributed to the limit.
+    if let Some(limit) = run.memory_limit {
+        let reports_out_of_memory = measurement.stderr.as_deref().is_some_and(|stderr| {
+            let stderr = stderr.to_lowercase();
+            OUT_OF_MEMORY_MESSAGES
+                .iter()
+                .any(|message| stderr.contains(message))
+        });
+        let peak_memory = measurement
+            .peak_private_bytes
+            .map_or(measurement.peak_memory_usage, |private_bytes| {
+                private_bytes.max(measurement.peak_memory_usage)
+            });
+        measurement.memory_limit_exceeded = !exit_codes.is_ok(measurement.exit_status)
+            && (reports_out_of_memory || peak_memory >= 0.9 * limit);
     }
 
-    let measurement = execute_and_measure(command)
-        .with_context(|| format!("Failed to run command '{command_name}'"))?;
+    // Special exit codes only apply to the benchmarked command, not to intermediate commands
+    let accepted = match run.iteration {
+        BenchmarkIteration::NonBenchmarkRun => measurement.exit_status.success(),
+        BenchmarkIteration::Warmup(_)
+        | BenchmarkIteration::Benchmark(_)
+        | BenchmarkIteration::Profile(_)
+        | BenchmarkIteration::SyscallCount(_) => {
+            exit_codes.is_ok(measurement.exit_status)
+                || exit_codes.is_skip(measurement.exit_status)
+                || measurement.memory_limit_exceeded
+                || measurement.timed_out
+        }
+    };
 
-    if command_failure_action == CmdFailureAction::RaiseError && !measurement.exit_status.success()
-    {
-        let when = match iteration {
+    if command_failure_action == CmdFailureAction::RaiseError && !accepted {
+        let when = match run.iteration {
             BenchmarkIteration::NonBenchmarkRun => "a non-benchmark run".to_string(),
             BenchmarkIteration::Warmup(0) => "the first warmup run".to_string(),
             BenchmarkIteration::Warmup(i) => format!("warmup iteration {i}"),
             BenchmarkIteration::Benchmark(0) => "the first benchmark run".to_string(),
             BenchmarkIteration::Benchmark(i) => format!("benchmark iteration {i}"),
+            BenchmarkIteration::Profile(i) => format!("profiled run {i}"),
+            BenchmarkIteration::SyscallCount(i) => format!("syscall-counting run {i}"),
         };
         bail!(
             "{cause} in {when}. Use the '-i'/'--ignore-failure' option if you want to ignore this. \
-            Alternatively, use the '--show-output' option to debug what went wrong.",
+            Alternatively, use the '--show-output' option to debug what went wrong.{stderr}",
             cause=measurement.exit_status.code().map_or(
                 "The process has been terminated by a signal".into(),
                 |c| format!("Command terminated with non-zero exit code {c}")
 
             ),
+            stderr=measurement
+                .stderr
+                .as_deref()
+                .map(format_stderr_excerpt)
+                .unwrap_or_default(),
         );
     }
 
     Ok(measurement)
 }
 
+/// Replace the times and the peak memory usage of a run by the ones that the measurement shim
+/// has reported (see '--shim'), and remove its record from the error output
+fn apply_shim_record(measurement: &mut Measurement) -> Result<()> {
+    let (record, stderr) = measurement
+        .stderr
+        .as_deref()
+        .and_then(ShimRecord::extract)
+        .map(|(record, stderr)| (record, stderr.to_string()))
+        .ok_or_else(|| anyhow!("No record found in the error output"))?;
+
+    measurement.time_wall_clock = Time::new::<second>(record.wall_clock);
+    measurement.time_user = Time::new::<second>(record.user);
+    measurement.time_system = Time::new::<second>(record.system);
+    measurement.peak_memory_usage = Information::new::<byte>(record.peak_memory as f64);
+    measurement.stderr = Some(stderr).filter(|_| !measurement.exit_status.success());
+    Ok(())
+}
+
+/// Read the first `size` bytes of the given file
+fn read_head(path: &Path, size: usize) -> io::Result<String> {
+    let mut head = Vec::new();
+    File::open(path)?.take(size as u64).read_to_end(&mut head)?;
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
+
+/// Number of lines of the error output that are shown when a command fails
+const STDERR_EXCERPT_LINES: usize = 10;
+
+/// Format the last lines of a failing command's error output for an error message
+fn format_stderr_excerpt(stderr: &str) -> String {
+    let lines: Vec<_> = stderr.trim_end().lines().collect();
+    if lines.is_empty() {
+        return String::new();
+    }
+
+    let excerpt = lines[lines.len().saturating_sub(STDERR_EXCERPT_LINES)..]
+        .iter()
+        .map(|line| format!("\n  {line}"))
+        .collect::<String>();
+    format!("\n\nLast lines of the error output:{excerpt}")
+}
+
 pub struct RawExecutor<'a> {
     options: &'a Options,
+    samplers: Vec<Box<dyn AuxiliarySampler>>,
 }
 
 impl<'a> RawExecutor<'a> {
     pub fn new(options: &'a Options) -> Self {
-        RawExecutor { options }
+        RawExecutor {
+            options,
+            samplers: vec![],
+        }
     }
 }
 
@@ -115,33 +444,40 @@ impl Executor for RawExecutor<'_> {
     fn run_command_and_measure(
         &self,
         command: &Command<'_>,
-        iteration: BenchmarkIteration,
+        run: RunContext<'_>,
         command_failure_action: Option<CmdFailureAction>,
         output_policy: &CommandOutputPolicy,
     ) -> Result<Measurement> {
         run_command_and_measure_common(
-            command.get_command()?,
-            iteration,
+            vec![command.get_command()?],
+            run,
             command_failure_action.unwrap_or(self.options.command_failure_action),
-            &self.options.command_input_policy,
+            self.options,
             output_policy,
             &command.get_command_line(),
+            &self.samplers,
         )
     }
 
     fn calibrate(&mut self) -> Result<()> {
+        self.samplers = sampler::from_options(self.options)?;
         Ok(())
     }
 
     fn time_overhead(&self) -> Time {
         Time::zero()
     }
+
+    fn shell_spawning_time(&self) -> Option<ShellSpawningTime> {
+        None
+    }
 }
 
 pub struct ShellExecutor<'a> {
     options: &'a Options,
     shell: &'a Shell,
     shell_spawning_time: Option<Measurement>,
+    samplers: Vec<Box<dyn AuxiliarySampler>>,
 }
 
 impl<'a> ShellExecutor<'a> {
@@ -150,6 +486,7 @@ impl<'a> ShellExecutor<'a> {
             shell,
             options,
             shell_spawning_time: None,
+            samplers: vec![],
         }
     }
 }
@@ -158,29 +495,60 @@ impl Executor for ShellExecutor<'_> {
     fn run_command_and_measure(
         &self,
         command: &Command<'_>,
-        iteration: BenchmarkIteration,
+        run: RunContext<'_>,
         command_failure_action: Option<CmdFailureAction>,
         output_policy: &CommandOutputPolicy,
     ) -> Result<Measurement> {
-        let on_windows_cmd = cfg!(windows) && *self.shell == Shell::Default("cmd.exe");
-        let mut command_builder = self.shell.command();
-        command_builder.arg(if on_windows_cmd { "/C" } else { "-c" });
-
-        // Windows needs special treatment for its behavior on parsing cmd arguments
-        if on_windows_cmd {
-            #[cfg(windows)]
-            command_builder.raw_arg(command.get_command_line());
+        let shell_kind = self.shell.kind();
+        let on_windows_cmd = cfg!(windows) && shell_kind == ShellKind::Cmd;
+        let shell_command = |command_line: &str| {
+            let mut command_builder = self.shell.command();
+            command_builder.arg(shell_kind.command_flag());
+
+            // The file is sourced in a line of its own, such that the aliases which are
+            // defined in it apply to the command. Sourcing it is part of the calibration
+            // runs (with an empty command) as well.
+            let command_line = match &self.options.source_file {
+                Some(file) => format!(
+                    ". {}\n{command_line}",
+                    shell_words::quote(&file.to_string_lossy())
+                ),
+                None => command_line.to_string(),
+            };
+            // The shim runs the command in a shell of its own where it times it
+            let command_line = match &self.options.shim {
+                Some(shim) => format!("{shim} sh -c {}", shell_words::quote(&command_line)),
+                None => command_line,
+            };
+            let command_line = command_line.as_str();
+
+            // Windows needs special treatment for its behavior on parsing cmd arguments
+            if on_windows_cmd {
+                #[cfg(windows)]
+                command_builder.raw_arg(command_line);
+            } else {
+                command_builder.arg(command_line);
+            }
+            command_builder
+        };
+
+        // The stages of a pipeline are run in a shell of their own, such that their CPU
+        // times can be measured separately
+        let command_line = command.get_command_line();
+        let stages = if self.options.pipeline_stages {
+            pipeline::stages(&command_line)
         } else {
-            command_builder.arg(command.get_command_line());
-        }
+            vec![command_line.as_str()]
+        };
 
         let mut measurement = run_command_and_measure_common(
-            command_builder,
-            iteration,
+            stages.into_iter().map(shell_command).collect(),
+            run,
             command_failure_action.unwrap_or(self.options.command_failure_action),
-            &self.options.command_input_policy,
+            self.options,
             output_policy,
             &command.get_command_line(),
+            &self.samplers,
         )?;
 
         // Subtract shell spawning time
@@ -192,13 +560,37 @@ impl Executor for ShellExecutor<'_> {
             }
         }
 
-        if let Some(ref spawning_time) = self.shell_spawning_time {
+        if let Some(spawning_time) = self
+            .shell_spawning_time
+            .as_ref()
+            .filter(|_| self.options.shell_correction)
+        {
             measurement.time_wall_clock =
                 ensure_non_negative(measurement.time_wall_clock - spawning_time.time_wall_clock);
             measurement.time_user =
                 ensure_non_negative(measurement.time_user - spawning_time.time_user);
             measurement.time_system =
                 ensure_non_negative(measurement.time_system - spawning_time.time_system);
+
+            // Each stage of a pipeline has been run in a shell of its own
+            if !measurement.pipeline_stages.is_empty() {
+                for stage in &mut measurement.pipeline_stages {
+                    stage.time_user =
+                        ensure_non_negative(stage.time_user - spawning_time.time_user);
+                    stage.time_system =
+                        ensure_non_negative(stage.time_system - spawning_time.time_system);
+                }
+                measurement.time_user = measurement
+                    .pipeline_stages
+                    .iter()
+                    .map(|s| s.time_user)
+                    .sum();
+                measurement.time_system = measurement
+                    .pipeline_stages
+                    .iter()
+                    .map(|s| s.time_system)
+                    .sum();
+            }
         }
 
         Ok(measurement)
@@ -207,7 +599,7 @@ impl Executor for ShellExecutor<'_> {
     /// Measure the average shell spawning time
     fn calibrate(&mut self) -> Result<()> {
         const COUNT: u64 = 50;
-        let progress_bar = if self.options.output_style != OutputStyleOption::Disabled {
+        let progress_bar = if self.options.show_benchmark_output() {
             Some(get_progress_bar(
                 COUNT,
                 "Measuring shell spawning time",
@@ -223,17 +615,16 @@ impl Executor for ShellExecutor<'_> {
             // Just run the shell without any command
             let measurement = self.run_command_and_measure(
                 &Command::new(None, ""),
-                BenchmarkIteration::NonBenchmarkRun,
+                RunContext::standalone(),
                 None,
                 &CommandOutputPolicy::Null,
             );
 
             match measurement {
                 Err(_) => {
-                    let shell_cmd = if cfg!(windows) {
-                        format!("{} /C \"\"", self.shell)
-                    } else {
-                        format!("{} -c \"\"", self.shell)
+                    let shell_cmd = match &self.options.shim {
+                        Some(shim) => format!("{shim} sh -c \"\""),
+                        None => format!("{} {} \"\"", self.shell, self.shell.kind().command_flag()),
                     };
 
                     bail!(
@@ -260,14 +651,45 @@ impl Executor for ShellExecutor<'_> {
             time_user: measurements.time_user_mean(),
             time_system: measurements.time_system_mean(),
             peak_memory_usage: measurements.peak_memory_usage_mean(),
-            exit_status: ExitStatus::default(),
+            ..Default::default()
         });
 
+        // The samplers are only set up after the calibration, which does not need them
+        self.samplers = sampler::from_options(self.options)?;
+
+        log_verbose!(
+            "Shell spawning time: {} (user: {}, system: {}){}",
+            measurements.time_wall_clock_mean().format_auto(),
+            measurements.time_user_mean().format_auto(),
+            measurements.time_system_mean().format_auto(),
+            if self.options.shell_correction {
+                ""
+            } else {
+                ", not subtracted"
+            }
+        );
+
         Ok(())
     }
 
     fn time_overhead(&self) -> Time {
-        self.shell_spawning_time.as_ref().unwrap().time_wall_clock
+        if self.options.shell_correction {
+            self.shell_spawning_time.as_ref().unwrap().time_wall_clock
+        } else {
+            // The spawning time is already included in the measured time
+            Time::zero()
+        }
+    }
+
+    fn shell_spawning_time(&self) -> Option<ShellSpawningTime> {
+        self.shell_spawning_time
+            .as_ref()
+            .map(|spawning_time| ShellSpawningTime {
+                time_wall_clock: spawning_time.time_wall_clock,
+                time_user: spawning_time.time_user,
+                time_system: spawning_time.time_system,
+                subtracted: self.options.shell_correction,
+            })
     }
 }
 
@@ -282,6 +704,10 @@ impl MockExecutor {
     }
 
     fn extract_time<S: AsRef<str>>(sleep_command: S) -> Time {
+        // Empty commands are used to measure the noise floor (see '--noise-floor')
+        if sleep_command.as_ref().is_empty() {
+            return Time::zero();
+        }
         assert!(sleep_command.as_ref().starts_with("sleep "));
         Time::new::<second>(
             sleep_command
@@ -297,10 +723,17 @@ impl Executor for MockExecutor {
     fn run_command_and_measure(
         &self,
         command: &Command<'_>,
-        _iteration: BenchmarkIteration,
+        run: RunContext<'_>,
         _command_failure_action: Option<CmdFailureAction>,
         _output_policy: &CommandOutputPolicy,
     ) -> Result<Measurement> {
+        // A run that takes longer than the timeout is stopped at the timeout
+        let time = Self::extract_time(command.get_command_line());
+        let (time, timed_out) = match run.timeout {
+            Some(timeout) if time > timeout => (timeout, true),
+            _ => (time, false),
+        };
+
         #[cfg(unix)]
         let exit_status = {
             use std::os::unix::process::ExitStatusExt;
@@ -314,11 +747,37 @@ impl Executor for MockExecutor {
         };
 
         Ok(Measurement {
-            time_wall_clock: Self::extract_time(command.get_command_line()),
+            start_time: None,
+            time_wall_clock: time,
+            spawn_latency: None,
             time_user: Time::zero(),
             time_system: Time::zero(),
+            time_on_cpu: None,
+            run_queue_delay: None,
+            cpu_migrations: None,
+            numa_node: None,
             peak_memory_usage: Information::zero(),
+            peak_memory_footprint: None,
+            peak_private_bytes: None,
+            peak_working_set: None,
+            minor_page_faults: 0,
+            major_page_faults: 0,
+            process_count: None,
+            memory_limit_exceeded: false,
+            timed_out,
+            discarded: false,
+            failed_attempt: false,
             exit_status,
+            stderr: None,
+            stdout_size: None,
+            stderr_size: None,
+            disk_usage_delta: None,
+            syscall_count: None,
+            env_offset: None,
+            pipeline_stages: vec![],
+            captured_output: None,
+            auxiliary: Default::default(),
+            derived: Default::default(),
         })
     }
 
@@ -332,6 +791,10 @@ impl Executor for MockExecutor {
             Some(shell) => Self::extract_time(shell),
         }
     }
+
+    fn shell_spawning_time(&self) -> Option<ShellSpawningTime> {
+        None
+    }
 }
 
 #[test]
@@ -341,3 +804,43 @@ fn test_mock_executor_extract_time() {
         Time::new::<second>(0.1)
     );
 }
+
+#[test]
+fn test_format_stderr_excerpt() {
+    assert_eq!(format_stderr_excerpt(""), "");
+    assert_eq!(format_stderr_excerpt("\n"), "");
+    assert_eq!(
+        format_stderr_excerpt("error: foo\n"),
+        "\n\nLast lines of the error output:\n  error: foo"
+    );
+
+    let stderr = (1..=12).map(|i| format!("line {i}\n")).collect::<String>();
+    let excerpt = format_stderr_excerpt(&stderr);
+    assert!(!excerpt.contains("line 2\n"));
+    assert!(excerpt.contains("  line 3\n"));
+    assert!(excerpt.ends_with("  line 12"));
+}
+
+#[test]
+fn test_run_context_seed() {
+    let seed = |name, iteration| RunContext::new(name, iteration).seed();
+
+    assert_eq!(
+        seed("sleep 1", BenchmarkIteration::Benchmark(3)),
+        seed("sleep 1", BenchmarkIteration::Benchmark(3))
+    );
+    assert_ne!(
+        seed("sleep 1", BenchmarkIteration::Benchmark(3)),
+        seed("sleep 1", BenchmarkIteration::Benchmark(4))
+    );
+    assert_ne!(
+        seed("sleep 1", BenchmarkIteration::Warmup(3)),
+        seed("sleep 1", BenchmarkIteration::Benchmark(3))
+    );
+    assert_ne!(
+        seed("sleep 1", BenchmarkIteration::Benchmark(3)),
+        seed("sleep 2", BenchmarkIteration::Benchmark(3))
+    );
+    assert_eq!(seed("sleep 1", BenchmarkIteration::NonBenchmarkRun), None);
+    assert_eq!(RunContext::standalone().seed(), None);
+}
@@ -1,10 +1,13 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::process::ExitStatus;
+use std::time::SystemTime;
 
 use serde::Serialize;
 
+use crate::options::RelativeMetric;
 use crate::quantity::{
-    max, mean, median, min, modified_zscores, serialize_information, serialize_time,
-    standard_deviation, Information, Time,
+    byte, max, mean, median, min, modes, modified_zscores, ratio, second, serialize_information,
+    serialize_time, standard_deviation, Information, Time,
 };
 use crate::util::exit_code::extract_exit_code;
 
@@ -18,13 +21,48 @@ where
     }
 }
 
+fn serialize_optional_information<S>(
+    information: &Option<Information>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match information {
+        Some(information) => serialize_information(information, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn serialize_optional_time<S>(time: &Option<Time>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match time {
+        Some(time) => serialize_time(time, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
 /// Performance metric measurements and exit code for a single run
 #[derive(Debug, Clone, Default, Serialize, PartialEq)]
 pub struct Measurement {
+    /// When the run was started, if it has been recorded
+    #[serde(skip)]
+    pub start_time: Option<SystemTime>,
+
     /// Elapsed wall clock time (real time)
     #[serde(serialize_with = "serialize_time")]
     pub time_wall_clock: Time,
 
+    /// Time from the spawn call until the process has been started (on Unix, until the
+    /// program has been executed). This is part of the wall clock time.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_time"
+    )]
+    pub spawn_latency: Option<Time>,
+
     /// Time spent in user mode
     #[serde(serialize_with = "serialize_time")]
     pub time_user: Time,
@@ -33,52 +71,358 @@ pub struct Measurement {
     #[serde(serialize_with = "serialize_time")]
     pub time_system: Time,
 
+    /// Time that the process has spent on a CPU, according to the scheduler (see
+    /// `--scheduling-delay`)
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_time"
+    )]
+    pub time_on_cpu: Option<Time>,
+
+    /// Time that the process has spent waiting in the run queue of the scheduler, i.e.
+    /// ready to run but without a CPU (see `--scheduling-delay`)
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_time"
+    )]
+    pub run_queue_delay: Option<Time>,
+
+    /// Number of times that the process has been moved to another CPU by the scheduler
+    /// (see `--scheduling-delay`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_migrations: Option<u64>,
+
+    /// NUMA node of the CPU on which the process has run last (see `--scheduling-delay`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub numa_node: Option<u32>,
+
     /// Maximum memory usage of the process
     #[serde(serialize_with = "serialize_information")]
     pub peak_memory_usage: Information,
 
+    /// Maximum physical footprint of the process, only available on macOS
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_information"
+    )]
+    pub peak_memory_footprint: Option<Information>,
+
+    /// Maximum private (committed) memory of the process, only available on Windows
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_information"
+    )]
+    pub peak_private_bytes: Option<Information>,
+
+    /// Maximum working set (resident memory) of the process, only available on Windows
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_information"
+    )]
+    pub peak_working_set: Option<Information>,
+
+    /// Number of page faults that were serviced without any I/O. On Windows, this is the
+    /// total number of page faults.
+    pub minor_page_faults: u64,
+
+    /// Number of page faults that required I/O (not available on Windows)
+    pub major_page_faults: u64,
+
+    /// Number of processes that have been created by the command, including the shell
+    /// (Windows only, where it is counted by the job object of the process)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process_count: Option<u64>,
+
+    /// Whether the run has probably failed because it exceeded the memory limit (see
+    /// `--limit-memory`)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub memory_limit_exceeded: bool,
+
+    /// Whether the run has been killed, because it took longer than the timeout (see
+    /// `--auto-timeout`)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+
+    /// Whether this run is excluded from the statistics (see `--discard-first`)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub discarded: bool,
+
+    /// Whether this run has failed and has been repeated, so it is excluded from the
+    /// statistics (see `--successful-runs`)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub failed_attempt: bool,
+
     // The exit status of the process
     #[serde(rename = "exit_code", serialize_with = "serialize_exit_status")]
     pub exit_status: ExitStatus,
+
+    /// The last part of the standard error output, only recorded for failing runs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stderr: Option<String>,
+
+    /// Number of bytes written to stdout. Only available if the output is piped or written
+    /// to a file (see `--output`).
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_information"
+    )]
+    pub stdout_size: Option<Information>,
+
+    /// Number of bytes written to stderr. Not available if the output is shown.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_information"
+    )]
+    pub stderr_size: Option<Information>,
+
+    /// Change in the disk usage of the path given to `--measure-disk`, in bytes. Negative
+    /// if the run has removed more than it has added.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_information"
+    )]
+    pub disk_usage_delta: Option<Information>,
+
+    /// Number of system calls of the command and its child processes, if they have been
+    /// counted in this run (see `--count-syscalls=inline`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub syscall_count: Option<u64>,
+
+    /// Size of the randomized environment variable, if it is recorded (see
+    /// `--randomize-env-offset`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env_offset: Option<usize>,
+
+    /// CPU times of each stage, if the command was run as a pipeline (see
+    /// `--pipeline-stages`). The user and system times of the run are their sums.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pipeline_stages: Vec<StageUsage>,
+
+    /// The first part of the output, if it was captured (see `--capture-output`). This is
+    /// reported once per benchmark, not for each run.
+    #[serde(skip)]
+    pub captured_output: Option<CapturedOutput>,
+
+    /// Average values of the metrics that were sampled during the run (see `--sample-gpu`)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub auxiliary: BTreeMap<String, f64>,
+
+    /// Values of the metrics that are derived from this run with a formula (see `--derived`)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub derived: BTreeMap<String, f64>,
+}
+
+/// The first part of the standard output and error output of a run. The streams are not
+/// available if they are not piped (e.g. if the output is shown).
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct CapturedOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stdout: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stderr: Option<String>,
+}
+
+/// The CPU times of one stage of a pipeline
+#[derive(Debug, Default, Clone, Copy, Serialize, PartialEq)]
+pub struct StageUsage {
+    #[serde(serialize_with = "serialize_time")]
+    pub time_user: Time,
+
+    #[serde(serialize_with = "serialize_time")]
+    pub time_system: Time,
 }
 
 #[derive(Debug, Default, Clone, Serialize, PartialEq)]
 pub struct Measurements {
     pub measurements: Vec<Measurement>,
+
+    /// Fraction of the fastest and of the slowest runs that is excluded from the mean and
+    /// standard deviation of the wall clock time (see `--trim`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trim: Option<f64>,
+
+    /// Whether the runs after the first one were skipped, because it was too slow (see
+    /// `--abort-if-slower-than`)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub too_slow: bool,
+
+    /// Whether the runs were stopped early, because the benchmark took longer than its
+    /// deadline (see `--benchmark-deadline`)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub deadline_exceeded: bool,
+
+    /// Whether the runs were stopped early, because the standard deviation of the wall
+    /// clock times was too large (see `--abort-on-noise`)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub too_noisy: bool,
+
+    /// Time after which each run was killed (see `--auto-timeout`)
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_time"
+    )]
+    pub timeout: Option<Time>,
+
+    /// Whether the runs were stopped early, because a run was killed after the timeout
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+
+    /// Paths of the profiles that were recorded in extra runs (see `--profile-with`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<String>,
+
+    /// Numbers of system calls that were counted in extra runs (see `--count-syscalls`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub syscall_counts: Vec<u64>,
+
+    /// Wall clock times of the empty runs in between the benchmark runs (see
+    /// `--noise-floor`)
+    #[serde(
+        rename = "noise_floor",
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_noise_floor"
+    )]
+    pub noise_floor_probes: Vec<Time>,
+}
+
+/// An estimate of the residual load of the system during a benchmark, from the wall clock
+/// times of empty runs (see `--noise-floor`)
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub struct NoiseFloor {
+    #[serde(serialize_with = "serialize_time")]
+    pub mean: Time,
+
+    /// Not available if only one empty run has been performed
+    #[serde(serialize_with = "serialize_optional_time")]
+    pub stddev: Option<Time>,
+
+    pub runs: usize,
+}
+
+/// Zero-based indices of the fastest, the median and the slowest run among the counted runs.
+/// They show whether the fastest or the slowest runs happened early (e.g. a cold cache) or
+/// late (e.g. thermal throttling) during a benchmark.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub struct RunIndices {
+    pub min: usize,
+    pub median: usize,
+    pub max: usize,
+}
+
+fn serialize_noise_floor<S>(probes: &[Time], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    noise_floor(probes).serialize(serializer)
+}
+
+fn noise_floor(probes: &[Time]) -> Option<NoiseFloor> {
+    (!probes.is_empty()).then(|| NoiseFloor {
+        mean: mean(probes),
+        stddev: (probes.len() >= 2).then(|| standard_deviation(probes)),
+        runs: probes.len(),
+    })
 }
 
 impl Measurements {
     pub fn new(measurements: Vec<Measurement>) -> Self {
-        Self { measurements }
+        Self {
+            measurements,
+            trim: None,
+            too_slow: false,
+            deadline_exceeded: false,
+            too_noisy: false,
+            timeout: None,
+            timed_out: false,
+            profiles: vec![],
+            syscall_counts: vec![],
+            noise_floor_probes: vec![],
+        }
+    }
+
+    /// The measurements that are taken into account for the statistics
+    fn counted(&self) -> impl Iterator<Item = &Measurement> {
+        self.measurements
+            .iter()
+            .filter(|m| !m.discarded && !m.failed_attempt)
     }
 
+    /// The number of runs, excluding discarded ones and failed attempts
     pub fn len(&self) -> usize {
-        self.measurements.len()
+        self.counted().count()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.measurements.is_empty()
+        self.len() == 0
+    }
+
+    /// The number of runs that are discarded (see `--discard-first`)
+    pub fn num_discarded(&self) -> usize {
+        self.measurements.iter().filter(|m| m.discarded).count()
+    }
+
+    /// The number of failed runs that have been repeated (see `--successful-runs`)
+    pub fn num_failed_attempts(&self) -> usize {
+        self.measurements
+            .iter()
+            .filter(|m| m.failed_attempt)
+            .count()
+    }
+
+    /// The number of runs that have exceeded the memory limit (see `--limit-memory`)
+    pub fn num_memory_limit_exceeded(&self) -> usize {
+        self.counted().filter(|m| m.memory_limit_exceeded).count()
     }
 
     pub fn push(&mut self, measurement: Measurement) {
         self.measurements.push(measurement);
     }
 
+    /// Add the runs of another benchmark of the same command
+    pub fn append(&mut self, other: Measurements) {
+        self.measurements.extend(other.measurements);
+        self.too_slow |= other.too_slow;
+        self.deadline_exceeded |= other.deadline_exceeded;
+        self.too_noisy |= other.too_noisy;
+        self.timeout = self.timeout.or(other.timeout);
+        self.timed_out |= other.timed_out;
+        self.profiles.extend(other.profiles);
+        self.syscall_counts.extend(other.syscall_counts);
+        self.noise_floor_probes.extend(other.noise_floor_probes);
+    }
+
     pub fn wall_clock_times(&self) -> Vec<Time> {
-        self.measurements
-            .iter()
-            .map(|m| m.time_wall_clock)
-            .collect()
+        self.counted().map(|m| m.time_wall_clock).collect()
+    }
+
+    /// The number of runs at each end that are excluded from the mean and standard deviation
+    pub fn num_trimmed(&self) -> usize {
+        self.trim
+            .map_or(0, |trim| (self.len() as f64 * trim).floor() as usize)
+    }
+
+    /// The wall clock times without the fastest and slowest runs that are trimmed
+    fn trimmed_wall_clock_times(&self) -> Vec<Time> {
+        let mut times = self.wall_clock_times();
+        let num_trimmed = self.num_trimmed();
+        if num_trimmed == 0 {
+            return times;
+        }
+
+        times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        times.truncate(times.len() - num_trimmed);
+        times.split_off(num_trimmed)
     }
 
-    /// The average wall clock time
+    /// The average wall clock time (possibly trimmed)
     pub fn time_wall_clock_mean(&self) -> Time {
-        mean(&self.wall_clock_times())
+        mean(&self.trimmed_wall_clock_times())
     }
 
-    /// The standard deviation of all wall clock times. Not available if only one run has been performed
+    /// The standard deviation of the wall clock times (possibly trimmed). Not available if only one run has been performed
     pub fn stddev(&self) -> Option<Time> {
-        let times = self.wall_clock_times();
+        let times = self.trimmed_wall_clock_times();
 
         if times.len() < 2 {
             None
@@ -87,6 +431,20 @@ impl Measurements {
         }
     }
 
+    /// The standard deviation of the wall clock times, relative to their mean
+    pub fn relative_stddev(&self) -> Option<f64> {
+        let mean = self.time_wall_clock_mean();
+        self.stddev()
+            .filter(|_| mean > Time::default())
+            .map(|stddev| (stddev / mean).get::<ratio>())
+    }
+
+    /// The residual load of the system, if empty runs have been measured in between the
+    /// benchmark runs
+    pub fn noise_floor(&self) -> Option<NoiseFloor> {
+        noise_floor(&self.noise_floor_probes)
+    }
+
     /// The median wall clock time
     pub fn median(&self) -> Time {
         median(&self.wall_clock_times())
@@ -102,6 +460,29 @@ impl Measurements {
         max(&self.wall_clock_times())
     }
 
+    /// The modes of the distribution of the wall clock times
+    pub fn modes(&self) -> Vec<Time> {
+        modes(&self.wall_clock_times())
+    }
+
+    /// The indices of the fastest, the median and the slowest run. Not available if less than
+    /// two runs have been performed.
+    pub fn run_indices(&self) -> Option<RunIndices> {
+        let times = self.wall_clock_times();
+        if times.len() < 2 {
+            return None;
+        }
+
+        // The sort is stable, such that the first of several equally fast runs is the fastest
+        let mut indices: Vec<usize> = (0..times.len()).collect();
+        indices.sort_by(|&a, &b| times[a].partial_cmp(&times[b]).unwrap());
+        Some(RunIndices {
+            min: indices[0],
+            median: indices[(indices.len() - 1) / 2],
+            max: indices[indices.len() - 1],
+        })
+    }
+
     /// Compute modified Z-scores for the wall clock times
     pub fn modified_zscores(&self) -> Vec<f64> {
         modified_zscores(&self.wall_clock_times())
@@ -109,33 +490,553 @@ impl Measurements {
 
     /// The average user time
     pub fn time_user_mean(&self) -> Time {
-        mean(
-            &self
-                .measurements
-                .iter()
-                .map(|m| m.time_user)
-                .collect::<Vec<_>>(),
-        )
+        mean(&self.counted().map(|m| m.time_user).collect::<Vec<_>>())
     }
 
     /// The average system time
     pub fn time_system_mean(&self) -> Time {
-        mean(
-            &self
-                .measurements
-                .iter()
-                .map(|m| m.time_system)
-                .collect::<Vec<_>>(),
-        )
+        mean(&self.counted().map(|m| m.time_system).collect::<Vec<_>>())
+    }
+
+    /// The average spawn latency, if it has been recorded for all runs
+    pub fn spawn_latency_mean(&self) -> Option<Time> {
+        self.counted()
+            .map(|m| m.spawn_latency)
+            .collect::<Option<Vec<_>>>()
+            .filter(|latencies| !latencies.is_empty())
+            .map(|latencies| mean(&latencies))
+    }
+
+    /// The average run queue delay and time on a CPU, if they have been recorded for all
+    /// runs (see `--scheduling-delay`)
+    pub fn scheduling_means(&self) -> Option<(Time, Time)> {
+        self.counted()
+            .map(|m| m.run_queue_delay.zip(m.time_on_cpu))
+            .collect::<Option<Vec<_>>>()
+            .filter(|times| !times.is_empty())
+            .map(|times| {
+                let (delays, on_cpu): (Vec<_>, Vec<_>) = times.into_iter().unzip();
+                (mean(&delays), mean(&on_cpu))
+            })
+    }
+
+    /// The average CPU times of each stage of a pipeline, if all runs have been split into
+    /// the same number of stages
+    pub fn pipeline_stage_means(&self) -> Vec<StageUsage> {
+        let num_stages = self.counted().next().map_or(0, |m| m.pipeline_stages.len());
+        if self
+            .counted()
+            .any(|m| m.pipeline_stages.len() != num_stages)
+        {
+            return vec![];
+        }
+
+        let stage_mean = |i: usize, time: fn(&StageUsage) -> Time| {
+            mean(
+                &self
+                    .counted()
+                    .map(|m| time(&m.pipeline_stages[i]))
+                    .collect::<Vec<_>>(),
+            )
+        };
+        (0..num_stages)
+            .map(|i| StageUsage {
+                time_user: stage_mean(i, |stage| stage.time_user),
+                time_system: stage_mean(i, |stage| stage.time_system),
+            })
+            .collect()
     }
 
     pub fn peak_memory_usage_mean(&self) -> Information {
         mean(
             &self
-                .measurements
-                .iter()
+                .counted()
                 .map(|m| m.peak_memory_usage)
                 .collect::<Vec<_>>(),
         )
     }
+
+    /// Mean peak private bytes and mean peak working set, if they have been recorded for all
+    /// runs (Windows only)
+    pub fn windows_memory_means(&self) -> (Option<Information>, Option<Information>) {
+        (
+            self.recorded_size_mean(|m| m.peak_private_bytes),
+            self.recorded_size_mean(|m| m.peak_working_set),
+        )
+    }
+
+    /// Mean number of bytes written to stdout and stderr, if it has been recorded for all runs
+    pub fn output_size_means(&self) -> (Option<Information>, Option<Information>) {
+        (
+            self.recorded_size_mean(|m| m.stdout_size),
+            self.recorded_size_mean(|m| m.stderr_size),
+        )
+    }
+
+    /// The mean of a size that is only recorded on some platforms or with some options, if it
+    /// has been recorded for all runs
+    fn recorded_size_mean(
+        &self,
+        size: fn(&Measurement) -> Option<Information>,
+    ) -> Option<Information> {
+        self.counted()
+            .map(size)
+            .collect::<Option<Vec<_>>>()
+            .filter(|sizes| !sizes.is_empty())
+            .map(|sizes| mean(&sizes))
+    }
+
+    /// Mean change in disk usage, if it has been measured for all runs
+    pub fn disk_usage_delta_mean(&self) -> Option<Information> {
+        self.counted()
+            .map(|m| m.disk_usage_delta)
+            .collect::<Option<Vec<_>>>()
+            .filter(|deltas| !deltas.is_empty())
+            .map(|deltas| mean(&deltas))
+    }
+
+    /// Mean of each auxiliary metric over all runs in which it has been sampled
+    pub fn auxiliary_means(&self) -> BTreeMap<String, f64> {
+        let mut values: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+        for measurement in self.counted() {
+            for (metric, &value) in &measurement.auxiliary {
+                values.entry(metric).or_default().push(value);
+            }
+        }
+
+        values
+            .into_iter()
+            .map(|(metric, values)| (metric.to_string(), statistical::mean(&values)))
+            .collect()
+    }
+
+    /// Mean and standard deviation of each derived metric (see `--derived`)
+    pub fn derived_statistics(&self) -> BTreeMap<String, (f64, Option<f64>)> {
+        let mut values: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+        for measurement in self.counted() {
+            for (metric, &value) in &measurement.derived {
+                values.entry(metric).or_default().push(value);
+            }
+        }
+
+        values
+            .into_iter()
+            .map(|(metric, values)| (metric.to_string(), value_statistics(values)))
+            .collect()
+    }
+
+    /// The value of the given metric in each run, in seconds (or in bytes for the peak memory
+    /// usage)
+    pub fn metric_values(&self, metric: RelativeMetric) -> Vec<f64> {
+        self.counted()
+            .map(|m| match metric {
+                RelativeMetric::WallClock => m.time_wall_clock.get::<second>(),
+                RelativeMetric::User => m.time_user.get::<second>(),
+                RelativeMetric::System => m.time_system.get::<second>(),
+                RelativeMetric::Cpu => (m.time_user + m.time_system).get::<second>(),
+                RelativeMetric::Memory => m.peak_memory_usage.get::<byte>(),
+            })
+            .collect()
+    }
+
+    /// The values from which `metric_statistics` is computed, i.e. with the wall clock times
+    /// trimmed like for the other statistics
+    pub fn metric_sample(&self, metric: RelativeMetric) -> Vec<f64> {
+        match metric {
+            RelativeMetric::WallClock => self
+                .trimmed_wall_clock_times()
+                .iter()
+                .map(|time| time.get::<second>())
+                .collect(),
+            _ => self.metric_values(metric),
+        }
+    }
+
+    /// Mean and standard deviation of the given metric, in seconds (or in bytes for the peak
+    /// memory usage). The wall clock times are trimmed like for the other statistics.
+    pub fn metric_statistics(&self, metric: RelativeMetric) -> (f64, Option<f64>) {
+        match metric {
+            RelativeMetric::WallClock => (
+                self.time_wall_clock_mean().get::<second>(),
+                self.stddev().map(|stddev| stddev.get::<second>()),
+            ),
+            _ => value_statistics(self.metric_values(metric)),
+        }
+    }
+
+    /// Mean and standard deviation of the number of minor page faults
+    pub fn minor_page_faults(&self) -> (f64, Option<f64>) {
+        count_statistics(self.counted().map(|m| m.minor_page_faults).collect())
+    }
+
+    /// Mean and standard deviation of the number of major page faults
+    pub fn major_page_faults(&self) -> (f64, Option<f64>) {
+        count_statistics(self.counted().map(|m| m.major_page_faults).collect())
+    }
+
+    /// Mean and standard deviation of the number of processes that have been created
+    /// per run, if it is available
+    pub fn process_count(&self) -> Option<(f64, Option<f64>)> {
+        let counts: Option<Vec<u64>> = self.counted().map(|m| m.process_count).collect();
+        counts
+            .filter(|counts| !counts.is_empty())
+            .map(count_statistics)
+    }
+
+    /// Mean and standard deviation of the number of CPU migrations per run, if they have
+    /// been counted
+    pub fn cpu_migrations(&self) -> Option<(f64, Option<f64>)> {
+        let counts: Option<Vec<u64>> = self.counted().map(|m| m.cpu_migrations).collect();
+        counts
+            .filter(|counts| !counts.is_empty())
+            .map(count_statistics)
+    }
+
+    /// The NUMA nodes on which the runs have ended, in ascending order
+    pub fn numa_nodes(&self) -> Vec<u32> {
+        let nodes: BTreeSet<u32> = self.counted().filter_map(|m| m.numa_node).collect();
+        nodes.into_iter().collect()
+    }
+
+    /// Mean and standard deviation of the number of system calls per run, from the extra
+    /// runs or from the timing runs, if they have been counted
+    pub fn syscall_count(&self) -> Option<(f64, Option<f64>)> {
+        if !self.syscall_counts.is_empty() {
+            return Some(count_statistics(self.syscall_counts.clone()));
+        }
+        let counts: Option<Vec<u64>> = self.counted().map(|m| m.syscall_count).collect();
+        counts
+            .filter(|counts| !counts.is_empty())
+            .map(count_statistics)
+    }
+}
+
+/// Mean and standard deviation (if there are at least two values) of the given counts
+fn count_statistics(counts: Vec<u64>) -> (f64, Option<f64>) {
+    value_statistics(counts.into_iter().map(|count| count as f64).collect())
+}
+
+/// Mean and standard deviation (if there are at least two values)
+fn value_statistics(values: Vec<f64>) -> (f64, Option<f64>) {
+    let mean = statistical::mean(&values);
+    let stddev = if values.len() < 2 {
+        None
+    } else {
+        Some(statistical::standard_deviation(&values, Some(mean)))
+    };
+    (mean, stddev)
+}
+
+#[test]
+fn test_discarded_runs_are_excluded_from_statistics() {
+    use crate::quantity::second;
+
+    let run = |time: f64, discarded: bool| Measurement {
+        time_wall_clock: Time::new::<second>(time),
+        discarded,
+        ..Default::default()
+    };
+    let measurements = Measurements::new(vec![run(10.0, true), run(1.0, false), run(3.0, false)]);
+
+    assert_eq!(measurements.len(), 2);
+    assert_eq!(measurements.num_discarded(), 1);
+    assert_eq!(
+        measurements.time_wall_clock_mean(),
+        Time::new::<second>(2.0)
+    );
+    assert_eq!(measurements.max(), Time::new::<second>(3.0));
+
+    let json = serde_json::to_value(&measurements).unwrap();
+    assert_eq!(json["measurements"][0]["discarded"], true);
+    assert!(json["measurements"][1].get("discarded").is_none());
+}
+
+#[test]
+fn test_page_fault_statistics() {
+    let run = |minor_page_faults, major_page_faults| Measurement {
+        minor_page_faults,
+        major_page_faults,
+        ..Default::default()
+    };
+
+    let measurements = Measurements::new(vec![run(100, 0)]);
+    assert_eq!(measurements.minor_page_faults(), (100.0, None));
+
+    let measurements = Measurements::new(vec![run(100, 2), run(200, 4), run(300, 6)]);
+    assert_eq!(measurements.minor_page_faults(), (200.0, Some(100.0)));
+    assert_eq!(measurements.major_page_faults(), (4.0, Some(2.0)));
+}
+
+#[test]
+fn test_process_count_statistics() {
+    let run = |process_count| Measurement {
+        process_count,
+        ..Default::default()
+    };
+
+    assert_eq!(Measurements::default().process_count(), None);
+    assert_eq!(
+        Measurements::new(vec![run(Some(3)), run(None)]).process_count(),
+        None
+    );
+    assert_eq!(
+        Measurements::new(vec![run(Some(1)), run(Some(3))]).process_count(),
+        Some((2.0, Some(2.0_f64.sqrt())))
+    );
+}
+
+#[test]
+fn test_syscall_count_statistics() {
+    let run = |syscall_count| Measurement {
+        syscall_count,
+        ..Default::default()
+    };
+
+    let mut measurements = Measurements::new(vec![run(Some(10)), run(Some(20))]);
+    assert_eq!(
+        measurements.syscall_count(),
+        Some((15.0, Some(50.0_f64.sqrt())))
+    );
+
+    // Counts of extra runs take precedence
+    measurements.syscall_counts = vec![30];
+    assert_eq!(measurements.syscall_count(), Some((30.0, None)));
+
+    assert_eq!(Measurements::new(vec![run(None)]).syscall_count(), None);
+}
+
+#[test]
+fn test_output_size_means() {
+    use crate::quantity::byte;
+
+    let run = |stdout: Option<f64>, stderr: f64| Measurement {
+        stdout_size: stdout.map(Information::new::<byte>),
+        stderr_size: Some(Information::new::<byte>(stderr)),
+        ..Default::default()
+    };
+
+    let measurements = Measurements::new(vec![run(Some(10.0), 0.0), run(Some(20.0), 4.0)]);
+    assert_eq!(
+        measurements.output_size_means(),
+        (
+            Some(Information::new::<byte>(15.0)),
+            Some(Information::new::<byte>(2.0))
+        )
+    );
+
+    let measurements = Measurements::new(vec![run(None, 0.0), run(Some(20.0), 0.0)]);
+    assert_eq!(measurements.output_size_means().0, None);
+    assert_eq!(Measurements::new(vec![]).output_size_means(), (None, None));
+}
+
+#[test]
+fn test_windows_memory_means() {
+    use crate::quantity::byte;
+
+    let run = |private_bytes: Option<f64>| Measurement {
+        peak_private_bytes: private_bytes.map(Information::new::<byte>),
+        peak_working_set: Some(Information::new::<byte>(8.0)),
+        ..Default::default()
+    };
+
+    let measurements = Measurements::new(vec![run(Some(2.0)), run(Some(4.0))]);
+    assert_eq!(
+        measurements.windows_memory_means(),
+        (
+            Some(Information::new::<byte>(3.0)),
+            Some(Information::new::<byte>(8.0))
+        )
+    );
+
+    let measurements = Measurements::new(vec![run(None), run(Some(4.0))]);
+    assert_eq!(measurements.windows_memory_means().0, None);
+
+    let json = serde_json::to_value(Measurement::default()).unwrap();
+    assert!(json.get("peak_private_bytes").is_none());
+    assert!(json.get("peak_working_set").is_none());
+}
+
+#[test]
+fn test_serialize_peak_memory_footprint() {
+    use crate::quantity::byte;
+
+    let measurement = Measurement::default();
+    let json = serde_json::to_value(&measurement).unwrap();
+    assert!(json.get("peak_memory_footprint").is_none());
+
+    let measurement = Measurement {
+        peak_memory_footprint: Some(Information::new::<byte>(4096.0)),
+        ..Default::default()
+    };
+    let json = serde_json::to_value(&measurement).unwrap();
+    assert_eq!(json["peak_memory_footprint"]["value"], 4096.0);
+    assert_eq!(json["peak_memory_footprint"]["unit"], "byte");
+}
+
+#[test]
+fn test_trimmed_statistics() {
+    use crate::quantity::second;
+
+    let run = |time: f64| Measurement {
+        time_wall_clock: Time::new::<second>(time),
+        ..Default::default()
+    };
+    let mut measurements = Measurements::new(
+        vec![100.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 0.0]
+            .into_iter()
+            .map(run)
+            .collect(),
+    );
+    assert_eq!(measurements.num_trimmed(), 0);
+    assert_eq!(
+        measurements.time_wall_clock_mean(),
+        Time::new::<second>(13.6)
+    );
+
+    measurements.trim = Some(0.15);
+    assert_eq!(measurements.num_trimmed(), 1);
+    assert_eq!(
+        measurements.time_wall_clock_mean(),
+        Time::new::<second>(4.5)
+    );
+    assert_eq!(measurements.min(), Time::new::<second>(0.0));
+    assert_eq!(measurements.max(), Time::new::<second>(100.0));
+
+    let json = serde_json::to_value(&measurements).unwrap();
+    assert_eq!(json["trim"], 0.15);
+}
+
+#[test]
+fn test_relative_stddev() {
+    use crate::quantity::second;
+
+    let run = |time: f64| Measurement {
+        time_wall_clock: Time::new::<second>(time),
+        ..Default::default()
+    };
+
+    let measurements = Measurements::new(vec![run(0.1), run(0.3)]);
+    let relative_stddev = measurements.relative_stddev().unwrap();
+    assert!((relative_stddev - std::f64::consts::FRAC_1_SQRT_2).abs() < 1e-9);
+
+    assert_eq!(Measurements::new(vec![run(0.1)]).relative_stddev(), None);
+    assert_eq!(
+        Measurements::new(vec![run(0.0), run(0.0)]).relative_stddev(),
+        None
+    );
+}
+
+#[test]
+fn test_pipeline_stage_means() {
+    use crate::quantity::second;
+
+    let stage = |time_user: f64, time_system: f64| StageUsage {
+        time_user: Time::new::<second>(time_user),
+        time_system: Time::new::<second>(time_system),
+    };
+    let run = |pipeline_stages: Vec<StageUsage>| Measurement {
+        pipeline_stages,
+        ..Default::default()
+    };
+
+    let measurements = Measurements::new(vec![
+        run(vec![stage(1.0, 0.1), stage(2.0, 0.0)]),
+        run(vec![stage(3.0, 0.3), stage(4.0, 0.0)]),
+    ]);
+    assert_eq!(
+        measurements.pipeline_stage_means(),
+        [stage(2.0, 0.2), stage(3.0, 0.0)]
+    );
+
+    let measurements = Measurements::new(vec![run(vec![stage(1.0, 0.1)]), run(vec![])]);
+    assert_eq!(measurements.pipeline_stage_means(), []);
+}
+
+#[test]
+fn test_spawn_latency_mean() {
+    use crate::quantity::second;
+
+    let run = |spawn_latency: Option<f64>| Measurement {
+        spawn_latency: spawn_latency.map(Time::new::<second>),
+        ..Default::default()
+    };
+
+    let measurements = Measurements::new(vec![run(Some(0.001)), run(Some(0.003))]);
+    assert_eq!(
+        measurements.spawn_latency_mean(),
+        Some(Time::new::<second>(0.002))
+    );
+
+    let json = serde_json::to_value(&measurements).unwrap();
+    assert_eq!(json["measurements"][0]["spawn_latency"]["value"], 0.001);
+
+    let measurements = Measurements::new(vec![run(Some(0.001)), run(None)]);
+    assert_eq!(measurements.spawn_latency_mean(), None);
+    assert_eq!(Measurements::new(vec![]).spawn_latency_mean(), None);
+}
+
+#[test]
+fn test_noise_floor() {
+    let mut measurements = Measurements::default();
+    assert_eq!(measurements.noise_floor(), None);
+
+    measurements.noise_floor_probes = vec![Time::new::<second>(0.001)];
+    let noise_floor = measurements.noise_floor().unwrap();
+    assert_eq!(noise_floor.runs, 1);
+    assert_eq!(noise_floor.stddev, None);
+
+    measurements.append(Measurements {
+        noise_floor_probes: vec![Time::new::<second>(0.003)],
+        ..Default::default()
+    });
+    let noise_floor = measurements.noise_floor().unwrap();
+    assert_eq!(noise_floor.runs, 2);
+    assert!((noise_floor.mean.get::<second>() - 0.002).abs() < 1e-12);
+    assert!(noise_floor.stddev.is_some());
+}
+
+#[test]
+fn test_scheduling_means() {
+    let run = |delay: Option<f64>| Measurement {
+        time_on_cpu: delay.map(|_| Time::new::<second>(1.0)),
+        run_queue_delay: delay.map(Time::new::<second>),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        Measurements::new(vec![run(Some(0.1)), run(Some(0.3))]).scheduling_means(),
+        Some((Time::new::<second>(0.2), Time::new::<second>(1.0)))
+    );
+    assert_eq!(
+        Measurements::new(vec![run(Some(0.1)), run(None)]).scheduling_means(),
+        None
+    );
+}
+
+#[test]
+fn test_run_indices() {
+    use crate::quantity::second;
+
+    let run = |time: f64, discarded: bool| Measurement {
+        time_wall_clock: Time::new::<second>(time),
+        discarded,
+        ..Default::default()
+    };
+    let measurements = Measurements::new(vec![
+        run(9.0, true),
+        run(5.0, false),
+        run(2.0, false),
+        run(3.0, false),
+        run(2.0, false),
+    ]);
+    assert_eq!(
+        measurements.run_indices(),
+        Some(RunIndices {
+            min: 1,
+            median: 3,
+            max: 0
+        })
+    );
+
+    assert_eq!(Measurements::new(vec![run(1.0, false)]).run_indices(), None);
 }
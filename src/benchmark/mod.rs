@@ -1,38 +1,99 @@
+pub mod attach;
 pub mod benchmark_result;
+pub mod cache;
+pub mod derived;
 pub mod executor;
 pub mod measurement;
+pub mod parameter_analysis;
+pub mod power_analysis;
 pub mod relative_speed;
+pub mod sampler;
 pub mod scheduler;
 
-use std::cmp;
+use std::fs;
+use std::sync::Mutex;
+use std::time::Instant;
 
 use crate::benchmark::benchmark_result::Parameter;
-use crate::benchmark::executor::BenchmarkIteration;
-use crate::benchmark::measurement::{Measurement, Measurements};
+use crate::benchmark::executor::{BenchmarkIteration, RunContext};
+use crate::benchmark::measurement::{Measurement, Measurements, StageUsage};
 use crate::command::Command;
+use crate::error::{ExitCode, WithExitCode};
 use crate::options::{
-    CmdFailureAction, CommandOutputPolicy, ExecutorKind, Options, OutputStyleOption,
+    CmdFailureAction, CommandOutputPolicy, ExecutorKind, MemoryUnit, Options, OutputStyleOption,
+    SyscallCounting, PROFILE_OUTPUT_PLACEHOLDER,
 };
 use crate::outlier_detection::OUTLIER_THRESHOLD;
-use crate::output::progress_bar::get_progress_bar;
-use crate::output::warnings::{OutlierWarningOptions, Warnings};
+use crate::output::command_display::{display_command, visible_width};
+use crate::output::confirmation;
+use crate::output::glyphs::glyphs;
+use crate::output::log::{log_verbose, Verbosity};
+use crate::output::progress_bar::{get_progress_bar, SessionProgress};
+use crate::output::warnings::{OutlierWarningOptions, RecordedWarning, Warnings};
 use crate::parameter::ParameterNameAndValue;
-use crate::quantity::{self, const_time_from_seconds, Time, Quantity};
+use crate::quantity::{self, const_time_from_seconds, ratio, second, Information, Quantity, Time};
+use crate::util::duration::format_duration;
+use crate::util::git;
+use crate::util::interrupt;
 use benchmark_result::BenchmarkResult;
+use cache::ResultCache;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, ensure, Context, Result};
 use colored::*;
+use indicatif::ProgressBar;
 
 use self::executor::Executor;
 
+/// Maximum number of consecutive runs that are repeated due to '--exit-code-skip'
+const MAX_SKIPPED_RUNS: usize = 100;
+
+/// Minimum number of runs before a benchmark can be aborted due to '--abort-on-noise'
+const MIN_RUNS_FOR_NOISE_CHECK: usize = 5;
+
+/// Number of extra runs in which the system calls are counted (see '--count-syscalls')
+const SYSCALL_COUNTING_RUNS: u64 = 3;
+
 /// Threshold for warning about fast execution time
 pub const MIN_EXECUTION_TIME: Time = const_time_from_seconds(0.005);
 
+/// Width of the labels in front of the values of a benchmark report
+const REPORT_LABEL_WIDTH: usize = 21;
+
 pub struct Benchmark<'a> {
     number: usize,
     command: &'a Command<'a>,
     options: &'a Options,
     executor: &'a dyn Executor,
+    session_progress: Option<&'a SessionProgress>,
+
+    /// Whether this is the first benchmark of the session, after which the user is asked for
+    /// confirmation if the session takes long (see '--confirm-after')
+    is_first: bool,
+
+    /// The iterations of the benchmarked command that have been started so far
+    run_history: Mutex<RunHistory>,
+
+    /// Time after which each run is killed (see '--auto-timeout')
+    timeout: Option<Time>,
+}
+
+/// The current and the previous iteration of a benchmark, for the `{prev_run}` placeholder
+#[derive(Debug, Default)]
+struct RunHistory {
+    current: Option<String>,
+    previous: Option<String>,
+}
+
+impl RunHistory {
+    /// Record that the given iteration is run (which may already be the current one, for
+    /// its preparation and conclusion commands or if it is repeated), and return the value
+    /// of the previous iteration
+    fn start(&mut self, iteration: &str) -> String {
+        if self.current.as_deref() != Some(iteration) {
+            self.previous = self.current.replace(iteration.to_string());
+        }
+        self.previous.clone().unwrap_or_default()
+    }
 }
 
 impl<'a> Benchmark<'a> {
@@ -47,22 +108,95 @@ impl<'a> Benchmark<'a> {
             command,
             options,
             executor,
+            session_progress: None,
+            is_first: false,
+            run_history: Mutex::default(),
+            timeout: None,
         }
     }
 
-    /// Run setup, cleanup, or preparation commands
+    /// Kill each run after the given time
+    pub fn with_timeout(mut self, timeout: Option<Time>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Show the progress of the benchmark as a part of the progress of the whole session
+    pub fn with_session_progress(mut self, session_progress: Option<&'a SessionProgress>) -> Self {
+        self.session_progress = session_progress;
+        self
+    }
+
+    /// Mark this benchmark as the first one of a sequential session
+    pub fn first_in_session(mut self, is_first: bool) -> Self {
+        self.is_first = is_first;
+        self
+    }
+
+    /// Create a progress bar for the current phase of the benchmark
+    fn progress_bar(&self, show_progress: bool, length: u64, msg: &str) -> Option<ProgressBar> {
+        if !show_progress {
+            return None;
+        }
+        let bar = get_progress_bar(length, msg, self.options.output_style);
+        Some(match self.session_progress {
+            Some(session_progress) => session_progress.add(bar),
+            None => bar,
+        })
+    }
+
+    fn finish_progress_bar(&self, bar: Option<ProgressBar>) {
+        match (bar, self.session_progress) {
+            (Some(bar), Some(session_progress)) => session_progress.remove(&bar),
+            (Some(bar), None) => bar.finish_and_clear(),
+            (None, _) => {}
+        }
+    }
+
+    /// Hide the progress bars while executing `f`, e.g. to print to the terminal
+    fn suspend_progress<F: FnOnce() -> R, R>(&self, bar: Option<&ProgressBar>, f: F) -> R {
+        match (bar, self.session_progress) {
+            (_, Some(session_progress)) => session_progress.suspend(f),
+            (Some(bar), None) => bar.suspend(f),
+            (None, None) => f(),
+        }
+    }
+
+    /// The given command with the `{run}`, `{prev_run}` and `{total_runs}` placeholders
+    /// replaced for the given iteration. Warmup runs are numbered 'warmup-0', 'warmup-1', etc.
+    /// As the runs of a benchmark are strictly sequential, `{prev_run}` always refers to the
+    /// run that was executed right before this one.
+    fn command_for_iteration(
+        &self,
+        command: &Command<'a>,
+        iteration: BenchmarkIteration,
+    ) -> Command<'a> {
+        let run = iteration.to_env_var_value();
+        let prev_run = run
+            .as_deref()
+            .map(|run| self.run_history.lock().unwrap().start(run));
+        command.with_run_placeholders(run, prev_run, self.options.total_runs(self.number))
+    }
+
+    /// Run setup, cleanup, or preparation commands. Their output is handled like the one
+    /// of the benchmarked command, unless '--hook-output' is given.
     fn run_intermediate_command(
         &self,
         command: &Command<'_>,
+        iteration: BenchmarkIteration,
         error_output: &'static str,
         output_policy: &CommandOutputPolicy,
     ) -> Result<Measurement> {
+        let benchmark_name = self.command.get_name();
         self.executor
             .run_command_and_measure(
                 command,
-                executor::BenchmarkIteration::NonBenchmarkRun,
+                RunContext::new(&benchmark_name, iteration),
                 Some(CmdFailureAction::RaiseError),
-                output_policy,
+                self.options
+                    .hook_output_policy
+                    .as_ref()
+                    .unwrap_or(output_policy),
             )
             .map_err(|_| anyhow!(error_output))
     }
@@ -83,7 +217,14 @@ impl<'a> Benchmark<'a> {
                             Append ' || true' to the command if you are sure that this can be ignored.";
 
         Ok(command
-            .map(|cmd| self.run_intermediate_command(&cmd, error_output, output_policy))
+            .map(|cmd| {
+                self.run_intermediate_command(
+                    &cmd,
+                    BenchmarkIteration::NonBenchmarkRun,
+                    error_output,
+                    output_policy,
+                )
+            })
             .transpose()?
             .unwrap_or_default())
     }
@@ -93,18 +234,56 @@ impl<'a> Benchmark<'a> {
         &self,
         parameters: impl IntoIterator<Item = ParameterNameAndValue<'a>>,
         output_policy: &CommandOutputPolicy,
+    ) -> Result<Measurement> {
+        let command = self.options.cleanup_command.as_ref().map(|values| {
+            let cleanup_command = if values.len() == 1 {
+                &values[0]
+            } else {
+                &values[self.number]
+            };
+            Command::new_parametrized(None, cleanup_command, parameters)
+        });
+
+        let error_output = "The cleanup command terminated with a non-zero exit code. \
+                            Append ' || true' to the command if you are sure that this can be ignored.";
+
+        Ok(command
+            .map(|cmd| {
+                self.run_intermediate_command(
+                    &cmd,
+                    BenchmarkIteration::NonBenchmarkRun,
+                    error_output,
+                    output_policy,
+                )
+            })
+            .transpose()?
+            .unwrap_or_default())
+    }
+
+    /// Run the command specified by `--teardown`.
+    fn run_teardown_command(
+        &self,
+        parameters: impl IntoIterator<Item = ParameterNameAndValue<'a>>,
+        output_policy: &CommandOutputPolicy,
     ) -> Result<Measurement> {
         let command = self
             .options
-            .cleanup_command
+            .teardown_command
             .as_ref()
-            .map(|cleanup_command| Command::new_parametrized(None, cleanup_command, parameters));
+            .map(|teardown_command| Command::new_parametrized(None, teardown_command, parameters));
 
-        let error_output = "The cleanup command terminated with a non-zero exit code. \
+        let error_output = "The teardown command terminated with a non-zero exit code. \
                             Append ' || true' to the command if you are sure that this can be ignored.";
 
         Ok(command
-            .map(|cmd| self.run_intermediate_command(&cmd, error_output, output_policy))
+            .map(|cmd| {
+                self.run_intermediate_command(
+                    &cmd,
+                    BenchmarkIteration::NonBenchmarkRun,
+                    error_output,
+                    output_policy,
+                )
+            })
             .transpose()?
             .unwrap_or_default())
     }
@@ -112,40 +291,207 @@ impl<'a> Benchmark<'a> {
     /// Run the command specified by `--prepare`.
     fn run_preparation_command(
         &self,
-        command: &Command<'_>,
+        command: &Command<'a>,
+        iteration: BenchmarkIteration,
         output_policy: &CommandOutputPolicy,
     ) -> Result<Measurement> {
         let error_output = "The preparation command terminated with a non-zero exit code. \
                             Append ' || true' to the command if you are sure that this can be ignored.";
 
-        self.run_intermediate_command(command, error_output, output_policy)
+        self.run_intermediate_command(
+            &self.command_for_iteration(command, iteration),
+            iteration,
+            error_output,
+            output_policy,
+        )
     }
 
     /// Run the command specified by `--conclude`.
     fn run_conclusion_command(
         &self,
-        command: &Command<'_>,
+        command: &Command<'a>,
+        iteration: BenchmarkIteration,
         output_policy: &CommandOutputPolicy,
     ) -> Result<Measurement> {
         let error_output = "The conclusion command terminated with a non-zero exit code. \
                             Append ' || true' to the command if you are sure that this can be ignored.";
 
-        self.run_intermediate_command(command, error_output, output_policy)
+        self.run_intermediate_command(
+            &self.command_for_iteration(command, iteration),
+            iteration,
+            error_output,
+            output_policy,
+        )
     }
 
-    /// Run the benchmark for a single command
+    /// Run the benchmark for a single command and report the results on the terminal
     pub fn run(&self) -> Result<BenchmarkResult> {
-        if self.options.output_style != OutputStyleOption::Disabled {
-            println!(
-                "{}{}: {}",
-                "Benchmark ".bold(),
-                (self.number + 1).to_string().bold(),
-                self.command.get_name_with_unused_parameters(),
-            );
+        let show_output = self.options.show_benchmark_output();
+
+        if show_output {
+            self.suspend_progress(None, || self.print_header());
+        }
+
+        let result = self.measure(show_output)?;
+        self.suspend_progress(None, || self.report(&result));
+
+        Ok(result)
+    }
+
+    /// Print the name of the benchmark
+    pub fn print_header(&self) {
+        Reporter::new(self.options).print_header(self.number, self.command);
+    }
+
+    /// Perform all runs for a single command (or reuse cached results), without reporting
+    /// the results
+    pub fn measure(&self, show_progress: bool) -> Result<BenchmarkResult> {
+        let cache = self.options.cache_directory.as_ref().map(|directory| {
+            ResultCache::new(
+                directory,
+                self.options.cache_refresh.as_deref(),
+                self.options.skip_if_unchanged.as_deref(),
+            )
+        });
+        let cache_key = cache
+            .as_ref()
+            .map(|cache| cache.key(self.command, self.options.measurement_settings(self.number)))
+            .transpose()?;
+
+        let cached_measurements = cache
+            .as_ref()
+            .zip(cache_key.as_ref())
+            .and_then(|(cache, key)| cache.load(key));
+
+        let mut measurements = match cached_measurements {
+            Some(measurements) => {
+                log_verbose!("Using cached result for '{}'", self.command.get_name());
+                if show_progress {
+                    self.suspend_progress(None, || println!("  Using cached result"));
+                }
+                measurements
+            }
+            None => {
+                let measurements = self.measure_runs(show_progress)?;
+                // Results of skipped runs are not cached, as they are incomplete
+                let is_incomplete = measurements.too_slow
+                    || measurements.deadline_exceeded
+                    || measurements.too_noisy
+                    || measurements.timed_out;
+                if let (Some(cache), Some(key), false) = (&cache, &cache_key, is_incomplete) {
+                    cache.store(key, &measurements)?;
+                }
+                measurements
+            }
+        };
+        measurements.trim = self.options.trim;
+        for measurement in &mut measurements.measurements {
+            for metric in &self.options.derived_metrics {
+                let value = metric
+                    .value(self.command, measurement)
+                    .map_err(|e| anyhow!("Could not compute the derived metric: {e}"))?;
+                measurement.derived.insert(metric.name.clone(), value);
+            }
+        }
+        let captured_output = measurements
+            .measurements
+            .iter_mut()
+            .find_map(|measurement| measurement.captured_output.take());
+        let modes = match measurements.modes() {
+            modes if modes.len() > 1 => modes,
+            _ => vec![],
+        };
+        let run_indices = measurements.run_indices();
+
+        // The revision is looked up after the runs, as they may have built the program
+        let git = self
+            .options
+            .git_annotate
+            .then(|| git::revision_in(&self.command.get_command_line()))
+            .flatten();
+        let command = match &git {
+            Some(revision) => format!("{} ({})", self.command.get_name(), revision.annotation()),
+            None => self.command.get_name(),
+        };
+
+        let mut result = BenchmarkResult {
+            command,
+            measurements,
+            parameters: self
+                .command
+                .get_parameters()
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        name.to_string(),
+                        Parameter::new(value.to_string(), self.command.is_parameter_unused(name)),
+                    )
+                })
+                .collect(),
+            command_template: Some(self.command.get_template().to_string())
+                .filter(|_| !self.command.get_parameters().is_empty()),
+            shell_spawning_time: self.executor.shell_spawning_time(),
+            captured_output,
+            modes,
+            run_indices,
+            execution_order: None,
+            reproduce: None,
+            git,
+            stateful: self.options.is_stateful(self.command),
+            group: self.command.get_group().map(String::from),
+            warnings: vec![],
+        };
+        result.warnings = Reporter::new(self.options).recorded_warnings(&result);
+        Ok(result)
+    }
+
+    /// Perform all runs for a single command, including the setup and teardown commands.
+    /// The teardown command is executed even if the benchmark fails.
+    fn measure_runs(&self, show_progress: bool) -> Result<Measurements> {
+        let output_policy = &self.options.command_output_policies[self.number];
+
+        self.run_setup_command(self.command.get_parameters().iter().cloned(), output_policy)
+            .exit_code(ExitCode::PreflightFailed)?;
+
+        let result = self
+            .measure_runs_after_setup(show_progress)
+            .exit_code(ExitCode::MeasurementFailure);
+        let teardown_result =
+            self.run_teardown_command(self.command.get_parameters().iter().cloned(), output_policy);
+
+        let measurements = result?;
+        teardown_result?;
+
+        Ok(measurements)
+    }
+
+    /// Measure an empty run after the first and then after every n-th run (given the number
+    /// of runs so far), to estimate the residual load of the system (see '--noise-floor')
+    fn measure_noise_floor(&self, measurements: &mut Measurements, num_runs: u64) -> Result<()> {
+        if let Some(interval) = self.options.noise_floor_interval {
+            if (num_runs - 1) % interval == 0 {
+                let measurement = self.executor.run_command_and_measure(
+                    &Command::new(None, ""),
+                    RunContext::standalone(),
+                    Some(CmdFailureAction::RaiseError),
+                    &CommandOutputPolicy::Null,
+                )?;
+                measurements
+                    .noise_floor_probes
+                    .push(measurement.time_wall_clock);
+            }
         }
+        Ok(())
+    }
 
-        let mut measurements = Measurements::default();
-        let mut all_succeeded = true;
+    fn measure_runs_after_setup(&self, show_progress: bool) -> Result<Measurements> {
+        let start = Instant::now();
+        let mut measurements = Measurements {
+            timeout: self.timeout,
+            ..Default::default()
+        };
+        let benchmark_name = self.command.get_name();
+        let memory_limit = self.options.memory_limit(self.command)?;
 
         let output_policy = &self.options.command_output_policies[self.number];
 
@@ -162,10 +508,10 @@ impl<'a> Benchmark<'a> {
             )
         });
 
-        let run_preparation_command = || {
+        let run_preparation_command = |iteration| {
             preparation_command
                 .as_ref()
-                .map(|cmd| self.run_preparation_command(cmd, output_policy))
+                .map(|cmd| self.run_preparation_command(cmd, iteration, output_policy))
                 .transpose()
         };
 
@@ -181,146 +527,461 @@ impl<'a> Benchmark<'a> {
                 self.command.get_parameters().iter().cloned(),
             )
         });
-        let run_conclusion_command = || {
+        let run_conclusion_command = |iteration| {
             conclusion_command
                 .as_ref()
-                .map(|cmd| self.run_conclusion_command(cmd, output_policy))
+                .map(|cmd| self.run_conclusion_command(cmd, iteration, output_policy))
                 .transpose()
         };
 
-        self.run_setup_command(self.command.get_parameters().iter().cloned(), output_policy)?;
-
         // Warmup phase
         if self.options.warmup_count > 0 {
-            let progress_bar = if self.options.output_style != OutputStyleOption::Disabled {
-                Some(get_progress_bar(
-                    self.options.warmup_count,
-                    "Performing warmup runs",
-                    self.options.output_style,
-                ))
-            } else {
-                None
-            };
+            let progress_bar = self.progress_bar(
+                show_progress,
+                self.options.warmup_count,
+                "Performing warmup runs",
+            );
 
             for i in 0..self.options.warmup_count {
-                let _ = run_preparation_command()?;
+                let iteration = BenchmarkIteration::Warmup(i);
+                let _ = run_preparation_command(iteration)?;
                 let _ = self.executor.run_command_and_measure(
-                    self.command,
-                    BenchmarkIteration::Warmup(i),
+                    &self.command_for_iteration(self.command, iteration),
+                    RunContext::new(&benchmark_name, iteration)
+                        .with_memory_limit(memory_limit)
+                        .with_timeout(self.timeout),
                     None,
                     output_policy,
                 )?;
-                let _ = run_conclusion_command()?;
+                let _ = run_conclusion_command(iteration)?;
+                ensure!(
+                    !interrupt::was_interrupted(),
+                    "The benchmark was interrupted"
+                );
                 if let Some(bar) = progress_bar.as_ref() {
                     bar.inc(1)
                 }
             }
-            if let Some(bar) = progress_bar.as_ref() {
-                bar.finish_and_clear()
-            }
+            self.finish_progress_bar(progress_bar);
         }
 
+        let run_bounds = &self.options.run_bounds[self.number];
+
         // Set up progress bar (and spinner for initial measurement)
-        let progress_bar = if self.options.output_style != OutputStyleOption::Disabled {
-            Some(get_progress_bar(
-                self.options.run_bounds.min,
-                "Initial time measurement",
-                self.options.output_style,
-            ))
-        } else {
-            None
+        let progress_bar =
+            self.progress_bar(show_progress, run_bounds.min, "Initial time measurement");
+
+        // A single benchmark run, including the preparation and conclusion commands. Runs
+        // that exit with one of the '--exit-code-skip' codes are repeated.
+        let run_benchmark = |iteration| {
+            for _ in 0..MAX_SKIPPED_RUNS {
+                let preparation_result = run_preparation_command(iteration)?;
+                let measurement = self.executor.run_command_and_measure(
+                    &self.command_for_iteration(self.command, iteration),
+                    RunContext::new(&benchmark_name, iteration)
+                        .with_memory_limit(memory_limit)
+                        .with_timeout(self.timeout)
+                        .with_syscall_counting(
+                            self.options.syscall_counting == Some(SyscallCounting::Inline),
+                        ),
+                    None,
+                    output_policy,
+                )?;
+                let conclusion_result = run_conclusion_command(iteration)?;
+                ensure!(
+                    !interrupt::was_interrupted(),
+                    "The benchmark was interrupted"
+                );
+
+                if !self.options.exit_codes.is_skip(measurement.exit_status) {
+                    return Ok((preparation_result, measurement, conclusion_result));
+                }
+                log_verbose!(
+                    "Repeating {iteration:?} of '{benchmark_name}', which exited with a skip code"
+                );
+            }
+            bail!(
+                "The command '{benchmark_name}' exited with one of the '--exit-code-skip' codes \
+                 {MAX_SKIPPED_RUNS} times in a row"
+            );
         };
 
-        let preparation_result = run_preparation_command()?;
+        // Initial timing run
+        let (preparation_result, mut measurement, conclusion_result) =
+            run_benchmark(BenchmarkIteration::Benchmark(0))?;
         let preparation_overhead = preparation_result.map_or(Time::zero(), |res| {
             res.time_wall_clock + self.executor.time_overhead()
         });
-
-        // Initial timing run
-        let measurement = self.executor.run_command_and_measure(
-            self.command,
-            BenchmarkIteration::Benchmark(0),
-            None,
-            output_policy,
-        )?;
-        let success = measurement.exit_status.success();
-
-        let conclusion_result = run_conclusion_command()?;
         let conclusion_overhead = conclusion_result.map_or(Time::zero(), |res| {
             res.time_wall_clock + self.executor.time_overhead()
         });
 
         // Determine number of benchmark runs
-        let runs_in_min_time = (self.options.min_benchmarking_time
-            / (measurement.time_wall_clock
-                + self.executor.time_overhead()
-                + preparation_overhead
-                + conclusion_overhead))
-            .get::<quantity::ratio>() as u64;
-
-        let count = {
-            let min = cmp::max(runs_in_min_time, self.options.run_bounds.min);
-
-            self.options
-                .run_bounds
-                .max
-                .as_ref()
-                .map(|max| cmp::min(min, *max))
-                .unwrap_or(min)
-        };
+        let time_per_run = measurement.time_wall_clock
+            + self.executor.time_overhead()
+            + preparation_overhead
+            + conclusion_overhead;
+        let runs_in_min_time =
+            (self.options.min_benchmarking_time / time_per_run).get::<quantity::ratio>() as u64;
+
+        let count = run_bounds.count(runs_in_min_time);
+
+        log_verbose!(
+            "Performing {count} runs of '{}' ({runs_in_min_time} runs fit into the minimum \
+             benchmarking time of {})",
+            benchmark_name,
+            self.options.min_benchmarking_time.format_auto(),
+        );
+
+        if let Some(threshold) = self
+            .options
+            .abort_threshold
+            .filter(|&threshold| measurement.time_wall_clock > threshold)
+        {
+            log_verbose!(
+                "Skipping the remaining runs of '{benchmark_name}', which is slower than {}",
+                threshold.format(threshold.suitable_unit())
+            );
+            self.finish_progress_bar(progress_bar);
+            self.run_cleanup_command(self.command.get_parameters().iter().cloned(), output_policy)?;
+
+            measurements.push(measurement);
+            measurements.too_slow = true;
+            return Ok(measurements);
+        }
+
+        if measurement.timed_out {
+            log_verbose!(
+                "Skipping the remaining runs of '{benchmark_name}', as its first run timed out"
+            );
+            self.finish_progress_bar(progress_bar);
+            self.run_cleanup_command(self.command.get_parameters().iter().cloned(), output_policy)?;
 
+            measurements.push(measurement);
+            measurements.timed_out = true;
+            return Ok(measurements);
+        }
+
+        if self.is_first {
+            self.suspend_progress(progress_bar.as_ref(), || {
+                self.confirm_session_duration(time_per_run, runs_in_min_time)
+            })?;
+        }
+
+        // Runs that are discarded come on top of the ones used for the statistics
+        let count = count + self.options.discard_first;
         let count_remaining = count - 1;
 
+        if let Some(session_progress) = self.session_progress {
+            session_progress
+                .set_benchmark_duration(time_per_run * (self.options.warmup_count + count) as f64);
+            session_progress.set_remaining_in_benchmark(time_per_run * count_remaining as f64);
+        }
+
+        // Failing runs are repeated (and excluded from the statistics) if
+        // '--successful-runs' is given
+        let is_failure = |measurement: &Measurement| {
+            self.options.max_attempts.is_some()
+                && !self.options.exit_codes.is_ok(measurement.exit_status)
+        };
+
         // Save the first result
+        let mut num_completed = 0;
+        if is_failure(&measurement) {
+            measurement.failed_attempt = true;
+        } else {
+            measurement.discarded = self.options.discard_first > 0;
+            num_completed += 1;
+        }
         measurements.push(measurement);
-
-        all_succeeded = all_succeeded && success;
+        self.measure_noise_floor(&mut measurements, 1)?;
 
         // Re-configure the progress bar
         if let Some(bar) = progress_bar.as_ref() {
-            bar.set_length(count)
-        }
-        if let Some(bar) = progress_bar.as_ref() {
-            bar.inc(1)
+            bar.set_length(count);
+            bar.set_position(num_completed);
         }
 
         // Gather statistics (perform the actual benchmark)
-        for i in 0..count_remaining {
-            run_preparation_command()?;
-
-            let msg = {
-                let t_wall_clock_mean = measurements.time_wall_clock_mean();
-                let time_unit = t_wall_clock_mean.suitable_unit();
-                let mean = t_wall_clock_mean.format(time_unit);
-                format!("Current estimate: {}", mean.to_string().green())
-            };
+        let mut num_attempts = 1;
+        while num_completed < count {
+            // The benchmark is only stopped once it has a run that is not discarded
+            if let Some(deadline) = self.options.benchmark_deadline.filter(|&deadline| {
+                num_completed > self.options.discard_first
+                    && Time::new::<second>(start.elapsed().as_secs_f64()) > deadline
+            }) {
+                log_verbose!(
+                    "Stopping the benchmark of '{benchmark_name}' after {num_attempts} runs, as \
+                     it exceeded its deadline of {}",
+                    deadline.format(deadline.suitable_unit())
+                );
+                measurements.deadline_exceeded = true;
+                break;
+            }
 
-            if let Some(bar) = progress_bar.as_ref() {
-                bar.set_message(msg.to_owned())
+            if let Some(relative_stddev) = measurements.relative_stddev().filter(|&stddev| {
+                measurements.len() >= MIN_RUNS_FOR_NOISE_CHECK
+                    && self
+                        .options
+                        .noise_threshold
+                        .is_some_and(|threshold| stddev > threshold)
+            }) {
+                log_verbose!(
+                    "Stopping the benchmark of '{benchmark_name}' after {} runs, as the standard \
+                     deviation is {:.1}% of the mean",
+                    measurements.len(),
+                    100.0 * relative_stddev
+                );
+                measurements.too_noisy = true;
+                break;
             }
 
-            let measurement = self.executor.run_command_and_measure(
-                self.command,
-                BenchmarkIteration::Benchmark(i + 1),
-                None,
-                output_policy,
-            )?;
-            let success = measurement.exit_status.success();
-            measurements.push(measurement);
+            if !measurements.is_empty() {
+                let msg = {
+                    let t_wall_clock_mean = measurements.time_wall_clock_mean();
+                    let time_unit = t_wall_clock_mean.suitable_unit();
+                    let mean = t_wall_clock_mean.format(time_unit);
+                    format!("Current estimate: {}", mean.to_string().green())
+                };
 
-            all_succeeded = all_succeeded && success;
+                if let Some(bar) = progress_bar.as_ref() {
+                    bar.set_message(msg.to_owned())
+                }
+            }
+
+            if let Some(max_attempts) = self
+                .options
+                .max_attempts
+                .filter(|&max_attempts| num_attempts >= max_attempts)
+            {
+                bail!(
+                    "Only {num_completed} of {count} runs of '{benchmark_name}' succeeded within \
+                     {max_attempts} attempts. Use '--max-attempts' to allow more attempts."
+                );
+            }
+
+            let (_, mut measurement, _) =
+                run_benchmark(BenchmarkIteration::Benchmark(num_attempts))?;
+            num_attempts += 1;
+            self.measure_noise_floor(&mut measurements, num_attempts)?;
+            if measurement.timed_out {
+                log_verbose!(
+                    "Skipping the remaining runs of '{benchmark_name}', as a run timed out"
+                );
+                measurements.push(measurement);
+                measurements.timed_out = true;
+                break;
+            }
+            if is_failure(&measurement) {
+                log_verbose!(
+                    "Repeating a failed run of '{benchmark_name}' ({num_completed} of {count} \
+                     runs have succeeded)"
+                );
+                measurement.failed_attempt = true;
+                measurements.push(measurement);
+                continue;
+            }
+            measurement.discarded = num_completed < self.options.discard_first;
+            measurements.push(measurement);
+            num_completed += 1;
 
             if let Some(bar) = progress_bar.as_ref() {
                 bar.inc(1)
             }
+            if let Some(session_progress) = self.session_progress {
+                session_progress
+                    .set_remaining_in_benchmark(time_per_run * (count - num_completed) as f64);
+            }
+        }
+
+        self.finish_progress_bar(progress_bar);
+
+        // Runs in which the system calls are counted, which are not part of the measurements
+        if self.options.syscall_counting == Some(SyscallCounting::Separate) {
+            let progress_bar = self.progress_bar(
+                show_progress,
+                SYSCALL_COUNTING_RUNS,
+                "Counting system calls",
+            );
+            for i in 0..SYSCALL_COUNTING_RUNS {
+                let iteration = BenchmarkIteration::SyscallCount(i);
+                let _ = run_preparation_command(iteration)?;
+                let measurement = self.executor.run_command_and_measure(
+                    &self.command_for_iteration(self.command, iteration),
+                    RunContext::new(&benchmark_name, iteration)
+                        .with_memory_limit(memory_limit)
+                        .with_syscall_counting(true),
+                    None,
+                    output_policy,
+                )?;
+                let _ = run_conclusion_command(iteration)?;
+                ensure!(
+                    !interrupt::was_interrupted(),
+                    "The benchmark was interrupted"
+                );
 
-            run_conclusion_command()?;
+                measurements
+                    .syscall_counts
+                    .extend(measurement.syscall_count);
+                if let Some(bar) = progress_bar.as_ref() {
+                    bar.inc(1)
+                }
+            }
+            self.finish_progress_bar(progress_bar);
         }
 
-        if let Some(bar) = progress_bar.as_ref() {
-            bar.finish_and_clear()
+        // Profiled runs, which are not part of the measurements
+        if let Some(ref profiling) = self.options.profiling {
+            fs::create_dir_all(&profiling.directory).with_context(|| {
+                format!(
+                    "Could not create the profile directory '{}'",
+                    profiling.directory.display()
+                )
+            })?;
+
+            let progress_bar =
+                self.progress_bar(show_progress, profiling.runs, "Performing profiled runs");
+            for i in 0..profiling.runs {
+                let iteration = BenchmarkIteration::Profile(i);
+                let path = profiling
+                    .directory
+                    .join(format!("benchmark-{}-profile-{i}", self.number + 1))
+                    .to_string_lossy()
+                    .into_owned();
+                let command_line = format!(
+                    "{} {}",
+                    profiling
+                        .command
+                        .replace(PROFILE_OUTPUT_PLACEHOLDER, &shell_words::quote(&path)),
+                    self.command_for_iteration(self.command, iteration)
+                        .get_command_line()
+                );
+
+                let _ = run_preparation_command(iteration)?;
+                let _ = self.executor.run_command_and_measure(
+                    &Command::new(None, &command_line),
+                    RunContext::new(&benchmark_name, iteration),
+                    None,
+                    output_policy,
+                )?;
+                let _ = run_conclusion_command(iteration)?;
+                ensure!(
+                    !interrupt::was_interrupted(),
+                    "The benchmark was interrupted"
+                );
+
+                measurements.profiles.push(path);
+                if let Some(bar) = progress_bar.as_ref() {
+                    bar.inc(1)
+                }
+            }
+            self.finish_progress_bar(progress_bar);
         }
 
+        self.run_cleanup_command(self.command.get_parameters().iter().cloned(), output_policy)?;
+
+        Ok(measurements)
+    }
+
+    /// Ask the user for confirmation if the whole session is estimated to take longer than
+    /// the '--confirm-after' threshold. The estimate assumes that all commands take as long
+    /// as the initial run of the first one.
+    fn confirm_session_duration(&self, time_per_run: Time, runs_in_min_time: u64) -> Result<()> {
+        let Some(threshold) = self.options.confirmation_threshold else {
+            return Ok(());
+        };
+
+        let total_runs: u64 = self
+            .options
+            .run_bounds
+            .iter()
+            .map(|run_bounds| {
+                run_bounds.count(runs_in_min_time)
+                    + self.options.discard_first
+                    + self.options.warmup_count
+            })
+            .sum();
+        let estimate = time_per_run * total_runs as f64;
+        if estimate <= threshold {
+            return Ok(());
+        }
+
+        let num_commands = self.options.run_bounds.len();
+        let question = format!(
+            "The benchmarks ({num_commands} command{}, {total_runs} runs) are estimated to take \
+             about {}. Continue?",
+            if num_commands == 1 { "" } else { "s" },
+            format_duration(estimate).bold(),
+        );
+        if !confirmation::confirm(&question).context("Could not read the confirmation")? {
+            return Err(anyhow!("The benchmark session was cancelled"))
+                .exit_code(ExitCode::Interrupted);
+        }
+        Ok(())
+    }
+
+    /// Print the statistics and warnings for the given result
+    pub fn report(&self, result: &BenchmarkResult) {
+        Reporter::new(self.options).report(result);
+    }
+}
+
+/// Whether the run with the given (zero-based) index was one of the first, the middle or the
+/// last third of the runs
+fn run_position(index: usize, runs: usize) -> &'static str {
+    let last = runs.saturating_sub(1);
+    if 3 * index < last {
+        "early"
+    } else if 3 * index > 2 * last {
+        "late"
+    } else {
+        "middle"
+    }
+}
+
+/// The label of a line in the benchmark report, padded such that the values of all lines are
+/// aligned (independently of the width of the symbols in the label)
+fn report_label(label: String) -> String {
+    let padding = REPORT_LABEL_WIDTH
+        .saturating_sub(visible_width(&label))
+        .max(1);
+    format!("  {label}{:padding$}", "")
+}
+
+/// Prints benchmark results to the terminal, independently of how they have been obtained
+/// (e.g. also for imported run times, see 'hyperfine analyze')
+pub struct Reporter<'a> {
+    options: &'a Options,
+}
+
+impl<'a> Reporter<'a> {
+    pub fn new(options: &'a Options) -> Self {
+        Reporter { options }
+    }
+
+    /// Print the name of the benchmark with the given (zero-based) number
+    pub fn print_header(&self, number: usize, command: &Command<'_>) {
+        let number = (number + 1).to_string();
+        println!(
+            "{}{}: {}",
+            "Benchmark ".bold(),
+            number.bold(),
+            display_command(
+                command,
+                self.options.command_display,
+                "Benchmark : ".len() + number.len()
+            ),
+        );
+    }
+
+    /// Print the statistics and warnings for the given result
+    pub fn report(&self, result: &BenchmarkResult) {
+        if self.options.verbosity == Verbosity::Quiet {
+            return;
+        }
+
+        let measurements = &result.measurements;
+
         // Formatting and console output
         let t_wall_clock_mean = measurements.time_wall_clock_mean();
         let time_unit = self
@@ -330,46 +991,324 @@ impl<'a> Benchmark<'a> {
         let mean_str = t_wall_clock_mean.format(time_unit);
         let min_str = measurements.min().format(time_unit);
         let max_str = measurements.max().format(time_unit);
-        let num_str = format!("{num_runs} runs", num_runs = measurements.len());
+        let mut num_str = format!("{num_runs} runs", num_runs = measurements.len());
+        if measurements.num_discarded() > 0 {
+            num_str += &format!(", {} discarded", measurements.num_discarded());
+        }
+        if measurements.num_failed_attempts() > 0 {
+            num_str += &format!(", {} failed attempts", measurements.num_failed_attempts());
+        }
+        if measurements.num_trimmed() > 0 {
+            num_str += &format!(", {} trimmed at each end", measurements.num_trimmed());
+        }
 
         let user_str = measurements.time_user_mean().format(time_unit);
         let system_str = measurements.time_system_mean().format(time_unit);
 
         if self.options.output_style != OutputStyleOption::Disabled {
-            if measurements.len() == 1 {
+            let glyphs = glyphs();
+            // There is no standard deviation for a single run, or if trimming (see '--trim')
+            // leaves only one
+            if let Some(stddev) = measurements.stddev() {
+                let stddev_str = stddev.format(time_unit);
+
                 println!(
-                    "  Time ({} ≡):        {:>8}  {:>8}     [User: {}, System: {}]",
-                    "abs".green().bold(),
+                    "{}{:>8} {} {:>8}    [User: {}, System: {}]",
+                    report_label(format!(
+                        "Time ({} {} {}):",
+                        "mean".green().bold(),
+                        glyphs.plus_minus,
+                        glyphs.sigma.green()
+                    )),
                     mean_str.green().bold(),
-                    "        ", // alignment
+                    glyphs.plus_minus,
+                    stddev_str.green(),
                     user_str.blue(),
                     system_str.blue()
                 );
             } else {
-                let stddev_str = measurements.stddev().unwrap().format(time_unit);
-
                 println!(
-                    "  Time ({} ± {}):     {:>8} ± {:>8}    [User: {}, System: {}]",
-                    "mean".green().bold(),
-                    "σ".green(),
+                    "{}{:>8}  {:>8}     [User: {}, System: {}]",
+                    report_label(format!(
+                        "Time ({} {}):",
+                        "abs".green().bold(),
+                        glyphs.identical
+                    )),
                     mean_str.green().bold(),
-                    stddev_str.green(),
+                    "        ", // alignment
                     user_str.blue(),
                     system_str.blue()
                 );
+            }
 
+            if measurements.len() > 1 {
                 println!(
-                    "  Range ({} … {}):   {:>8} … {:>8}    {}",
-                    "min".cyan(),
-                    "max".purple(),
+                    "{}{:>8} {} {:>8}    {}",
+                    report_label(format!(
+                        "Range ({} {} {}):",
+                        "min".cyan(),
+                        glyphs.ellipsis,
+                        "max".purple()
+                    )),
                     min_str.cyan(),
+                    glyphs.ellipsis,
                     max_str.purple(),
                     num_str.dimmed()
                 );
             }
+
+            if let Some(memory_unit) = self.options.memory_unit {
+                let peak_memory_mean = measurements.peak_memory_usage_mean();
+                let windows_memory = match measurements.windows_memory_means() {
+                    (Some(private_bytes), Some(working_set)) => format!(
+                        "    [Private bytes: {}, Working set: {}]",
+                        private_bytes
+                            .format(memory_unit.resolve(private_bytes))
+                            .blue(),
+                        working_set.format(memory_unit.resolve(working_set)).blue()
+                    ),
+                    _ => "".into(),
+                };
+                println!(
+                    "  Memory ({}):       {:>8}{windows_memory}",
+                    "peak".yellow(),
+                    peak_memory_mean
+                        .format(memory_unit.resolve(peak_memory_mean))
+                        .yellow()
+                );
+            }
+
+            if self.options.memory_unit.is_some() || self.options.verbosity >= Verbosity::Verbose {
+                let format_count = |(mean, stddev): (f64, Option<f64>)| match stddev {
+                    Some(stddev) => format!("{mean:.0} {} {stddev:.0}", glyphs.plus_minus),
+                    None => format!("{mean:.0}"),
+                };
+                println!(
+                    "  Page faults:         {} minor, {} major",
+                    format_count(measurements.minor_page_faults()).yellow(),
+                    format_count(measurements.major_page_faults()).yellow()
+                );
+            }
+
+            if let Some(delta) = measurements.disk_usage_delta_mean() {
+                let memory_unit = self.options.memory_unit.unwrap_or(MemoryUnit::Auto);
+                let sign = if delta > Information::zero() { "+" } else { "" };
+                println!(
+                    "  Disk ({}):         {:>8}",
+                    "mean".yellow(),
+                    format!("{sign}{}", delta.format(memory_unit.resolve(delta.abs()))).yellow()
+                );
+            }
+
+            if let Some((mean, stddev)) = measurements.syscall_count() {
+                let syscall_count_str = match stddev {
+                    Some(stddev) => format!("{mean:.0} {} {stddev:.0}", glyphs.plus_minus),
+                    None => format!("{mean:.0}"),
+                };
+                println!(
+                    "  Syscalls ({}):     {:>8}",
+                    "mean".yellow(),
+                    syscall_count_str.yellow()
+                );
+            }
+
+            let auxiliary_means = measurements.auxiliary_means();
+            if !auxiliary_means.is_empty() {
+                let metrics = auxiliary_means
+                    .iter()
+                    .map(|(metric, mean)| format!("{metric} = {}", format!("{mean:.1}").yellow()))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                println!("  Sampled ({}):      {}", "mean".yellow(), metrics);
+            }
+
+            let derived_statistics = measurements.derived_statistics();
+            if !derived_statistics.is_empty() {
+                // Values of any magnitude, e.g. GFLOPS or bytes per second
+                let format_value = |value: f64| {
+                    if value == 0.0 || (1e-3..1e6).contains(&value.abs()) {
+                        format!("{value:.3}")
+                    } else {
+                        format!("{value:.3e}")
+                    }
+                };
+                let metrics = derived_statistics
+                    .iter()
+                    .map(|(metric, &(mean, stddev))| {
+                        let value = match stddev {
+                            Some(stddev) => format!(
+                                "{} {} {}",
+                                format_value(mean),
+                                glyphs.plus_minus,
+                                format_value(stddev)
+                            ),
+                            None => format_value(mean),
+                        };
+                        format!("{metric} = {}", value.yellow())
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                println!(
+                    "{}{metrics}",
+                    report_label(format!(
+                        "Derived ({} {} {}):",
+                        "mean".yellow(),
+                        glyphs.plus_minus,
+                        glyphs.sigma.yellow()
+                    ))
+                );
+            }
+
+            if let Some((run_queue_delay, time_on_cpu)) = measurements.scheduling_means() {
+                let migrations = match measurements.cpu_migrations() {
+                    Some((mean, _)) => format!(", CPU migrations: {}", format!("{mean:.1}").blue()),
+                    None => "".into(),
+                };
+                let numa_nodes = match measurements.numa_nodes().as_slice() {
+                    [] => "".into(),
+                    nodes => format!(
+                        ", NUMA {}: {}",
+                        if nodes.len() == 1 { "node" } else { "nodes" },
+                        nodes
+                            .iter()
+                            .map(|node| node.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                            .blue()
+                    ),
+                };
+                println!(
+                    "{}{:>8}    [On CPU: {}{migrations}{numa_nodes}]",
+                    report_label(format!("Run queue ({}):", "mean".yellow())),
+                    run_queue_delay.format(time_unit).yellow(),
+                    time_on_cpu.format(time_unit).blue()
+                );
+            }
+
+            if let Some(noise_floor) = measurements.noise_floor() {
+                let unit = noise_floor.mean.suitable_unit();
+                let stddev_str = match noise_floor.stddev {
+                    Some(stddev) => format!("{} {:>8}", glyphs.plus_minus, stddev.format(unit)),
+                    None => " ".repeat(10), // alignment
+                };
+                println!(
+                    "{}{:>8} {}    {}",
+                    report_label("Noise floor:".to_string()),
+                    noise_floor.mean.format(unit).yellow(),
+                    stddev_str.yellow(),
+                    match noise_floor.runs {
+                        1 => "1 empty run".to_string(),
+                        runs => format!("{runs} empty runs"),
+                    }
+                    .dimmed()
+                );
+            }
+
+            let stage_means = measurements.pipeline_stage_means();
+            let cpu_time = |stage: &StageUsage| stage.time_user + stage.time_system;
+            let total_cpu_time: Time = stage_means.iter().map(cpu_time).sum();
+            for (i, stage) in stage_means.iter().enumerate() {
+                let share = if total_cpu_time > Time::zero() {
+                    format!(
+                        "{:.1}% of the CPU time",
+                        100.0 * (cpu_time(stage) / total_cpu_time).get::<ratio>()
+                    )
+                } else {
+                    "".into()
+                };
+                println!(
+                    "  Stage {} ({}):       [User: {}, System: {}]    {}",
+                    i + 1,
+                    "mean".blue(),
+                    stage.time_user.format(time_unit).blue(),
+                    stage.time_system.format(time_unit).blue(),
+                    share.dimmed()
+                );
+            }
+
+            if self.options.verbosity >= Verbosity::Verbose {
+                let memory_unit = self.options.memory_unit.unwrap_or(MemoryUnit::Auto);
+                let format_size = |size: Option<Information>| match size {
+                    Some(size) => size.format(memory_unit.resolve(size)).yellow(),
+                    None => "n/a".dimmed(),
+                };
+                if let Some(spawn_latency) = measurements.spawn_latency_mean() {
+                    println!(
+                        "  Spawn ({}):        {:>8}    {}",
+                        "mean".yellow(),
+                        spawn_latency.format(spawn_latency.suitable_unit()).yellow(),
+                        format!(
+                            "{:.1}% of the run time",
+                            100.0 * (spawn_latency / t_wall_clock_mean).get::<ratio>()
+                        )
+                        .dimmed()
+                    );
+                }
+
+                let (stdout_size, stderr_size) = measurements.output_size_means();
+                if stdout_size.is_some() || stderr_size.is_some() {
+                    println!(
+                        "  Output ({}):       {} stdout, {} stderr",
+                        "mean".yellow(),
+                        format_size(stdout_size),
+                        format_size(stderr_size)
+                    );
+                }
+
+                if let Some((mean, stddev)) = measurements.process_count() {
+                    let process_count_str = match stddev {
+                        Some(stddev) => format!("{mean:.1} {} {stddev:.1}", glyphs.plus_minus),
+                        None => format!("{mean:.1}"),
+                    };
+                    println!(
+                        "  Processes ({}):    {:>8}",
+                        "mean".yellow(),
+                        process_count_str.yellow()
+                    );
+                }
+
+                if let Some(indices) = result.run_indices {
+                    let runs = measurements.len();
+                    let position = |index: usize| {
+                        format!("#{} ({})", index + 1, run_position(index, runs).dimmed())
+                    };
+                    println!(
+                        "{}fastest {}, median {}, slowest {} of {runs} runs",
+                        report_label("Run order:".to_string()),
+                        position(indices.min),
+                        position(indices.median),
+                        position(indices.max)
+                    );
+                }
+            }
         }
 
-        // Warnings
+        let warnings = self.warnings(result);
+        if !warnings.is_empty() {
+            eprintln!(" ");
+
+            for warning in &warnings {
+                eprintln!("  {}: {}", "Warning".yellow(), warning);
+            }
+        }
+
+        if self.options.output_style != OutputStyleOption::Disabled {
+            println!(" ");
+        }
+    }
+
+    /// The warnings about the given result as they are recorded in the JSON export
+    pub fn recorded_warnings(&self, result: &BenchmarkResult) -> Vec<RecordedWarning> {
+        self.warnings(result).iter().map(Warnings::record).collect()
+    }
+
+    /// The warnings about the given result, e.g. if statistical outliers were detected
+    pub fn warnings(&self, result: &BenchmarkResult) -> Vec<Warnings> {
+        let measurements = &result.measurements;
+        let all_succeeded = measurements.measurements.iter().all(|m| {
+            m.memory_limit_exceeded || m.timed_out || self.options.exit_codes.is_ok(m.exit_status)
+        });
+
         let mut warnings = vec![];
 
         // Check execution time
@@ -410,37 +1349,45 @@ impl<'a> Benchmark<'a> {
             warnings.push(Warnings::OutliersDetected(outlier_warning_options));
         }
 
-        if !warnings.is_empty() {
-            eprintln!(" ");
+        if !result.modes.is_empty() {
+            warnings.push(Warnings::MultimodalDistribution(result.modes.clone()));
+        }
 
-            for warning in &warnings {
-                eprintln!("  {}: {}", "Warning".yellow(), warning);
-            }
+        if let (true, Some(threshold)) = (measurements.too_slow, self.options.abort_threshold) {
+            warnings.push(Warnings::TooSlow(measurements.max(), threshold));
         }
 
-        if self.options.output_style != OutputStyleOption::Disabled {
-            println!(" ");
+        if let (true, Some(deadline)) = (
+            measurements.deadline_exceeded,
+            self.options.benchmark_deadline,
+        ) {
+            warnings.push(Warnings::DeadlineExceeded(deadline, measurements.len()));
         }
 
-        self.run_cleanup_command(self.command.get_parameters().iter().cloned(), output_policy)?;
+        if let (true, Some(timeout)) = (measurements.timed_out, measurements.timeout) {
+            warnings.push(Warnings::TimedOut(timeout, measurements.len()));
+        }
 
-        Ok(BenchmarkResult {
-            command: self.command.get_name(),
-            measurements,
-            parameters: self
-                .command
-                .get_parameters()
-                .iter()
-                .map(|(name, value)| {
-                    (
-                        name.to_string(),
-                        Parameter {
-                            value: value.to_string(),
-                            is_unused: self.command.is_parameter_unused(name),
-                        },
-                    )
-                })
-                .collect(),
-        })
+        let num_memory_limit_exceeded = measurements.num_memory_limit_exceeded();
+        if num_memory_limit_exceeded > 0 {
+            warnings.push(Warnings::MemoryLimitExceeded(
+                num_memory_limit_exceeded,
+                measurements.len(),
+            ));
+        }
+
+        if let (true, Some(threshold), Some(relative_stddev)) = (
+            measurements.too_noisy,
+            self.options.noise_threshold,
+            measurements.relative_stddev(),
+        ) {
+            warnings.push(Warnings::TooNoisy(
+                relative_stddev,
+                threshold,
+                measurements.len(),
+            ));
+        }
+
+        warnings
     }
 }
@@ -0,0 +1,305 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use super::benchmark_result::BenchmarkResult;
+use super::relative_speed::compare_mean_time;
+use crate::quantity::second;
+
+/// Rough classification of how the run time grows with the parameter value
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scaling {
+    Constant,
+    Linear,
+    Quadratic,
+    Other,
+}
+
+impl fmt::Display for Scaling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            Scaling::Constant => "constant",
+            Scaling::Linear => "linear",
+            Scaling::Quadratic => "quadratic",
+            Scaling::Other => "neither linear nor quadratic",
+        };
+        write!(f, "{description}")
+    }
+}
+
+impl Scaling {
+    /// Maximum deviation of the fitted exponent from an integer exponent
+    const TOLERANCE: f64 = 0.25;
+
+    fn from_exponent(exponent: f64) -> Self {
+        if exponent.abs() < Self::TOLERANCE {
+            Scaling::Constant
+        } else if (exponent - 1.0).abs() < Self::TOLERANCE {
+            Scaling::Linear
+        } else if (exponent - 2.0).abs() < Self::TOLERANCE {
+            Scaling::Quadratic
+        } else {
+            Scaling::Other
+        }
+    }
+}
+
+/// Summary of a parameter scan for a single command
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParameterAnalysis {
+    /// The command, with the parameter value replaced by a placeholder
+    pub command: String,
+
+    /// Name of the scanned parameter
+    pub parameter: String,
+
+    /// Parameter value with the lowest mean run time
+    pub fastest_value: String,
+
+    /// Exponent k of a fit of the form `time ~ value^k` (a line in a log-log plot). Not
+    /// available if there are less than two positive parameter values.
+    pub scaling_exponent: Option<f64>,
+
+    /// Classification of the scaling exponent
+    pub scaling: Option<Scaling>,
+}
+
+/// The name and numeric value of the scanned parameter of a result
+fn scan_parameter(result: &BenchmarkResult) -> Option<(&str, f64)> {
+    if result.parameters.len() != 1 {
+        return None;
+    }
+    let (name, parameter) = result.parameters.iter().next()?;
+    Some((name, parameter.numeric_value.as_ref()?.as_f64()?))
+}
+
+/// The command of a result before the scanned parameter was substituted. Results imported
+/// from files without the template are approximated by replacing the parameter value by a
+/// placeholder wherever it appears as a separate word or number.
+fn command_template(result: &BenchmarkResult) -> String {
+    if let Some(template) = &result.command_template {
+        return template.clone();
+    }
+
+    let (name, parameter) = result.parameters.iter().next().unwrap();
+    let command = &result.command;
+    let value = &parameter.value;
+    if value.is_empty() {
+        return command.clone();
+    }
+
+    let is_part_of_word = |c: char| c.is_alphanumeric() || c == '.';
+    let mut template = String::new();
+    let mut end = 0;
+    for (start, _) in command.match_indices(value.as_str()) {
+        let before = command[..start].chars().next_back();
+        let after = command[start + value.len()..].chars().next();
+        if !before.is_some_and(is_part_of_word) && !after.is_some_and(is_part_of_word) {
+            template.push_str(&command[end..start]);
+            template.push_str(&format!("{{{name}}}"));
+            end = start + value.len();
+        }
+    }
+    template.push_str(&command[end..]);
+    template
+}
+
+/// Analyze all parameter scans in the given results. The results of a scan are the ones
+/// with the same command template and a numeric value of the same parameter. They are
+/// analyzed in the order of the (numeric) parameter values, independent of the order in
+/// which the values were given.
+pub fn analyze(results: &[BenchmarkResult]) -> Vec<ParameterAnalysis> {
+    let mut scans: Vec<(String, Vec<&BenchmarkResult>)> = vec![];
+
+    for result in results
+        .iter()
+        .filter(|result| scan_parameter(result).is_some())
+    {
+        let template = command_template(result);
+        match scans.iter_mut().find(|(other, _)| *other == template) {
+            Some((_, scan)) => scan.push(result),
+            None => scans.push((template, vec![result])),
+        }
+    }
+
+    scans
+        .into_iter()
+        .filter(|(_, scan)| scan.len() >= 2)
+        .map(|(_, mut scan)| {
+            scan.sort_by(|a, b| {
+                let (a, b) = (a.parameters.values().next(), b.parameters.values().next());
+                a.unwrap().compare_values(b.unwrap())
+            });
+            analyze_scan(&scan)
+        })
+        .collect()
+}
+
+fn analyze_scan(scan: &[&BenchmarkResult]) -> ParameterAnalysis {
+    let fastest = scan
+        .iter()
+        .min_by(|l, r| compare_mean_time(l, r))
+        .expect("at least one benchmark result");
+    let (name, parameter) = fastest.parameters.iter().next().unwrap();
+
+    let points: Vec<(f64, f64)> = scan
+        .iter()
+        .filter_map(|result| {
+            let (_, value) = scan_parameter(result)?;
+            let time = result.mean_wall_clock_time().get::<second>();
+            if value > 0.0 && time > 0.0 {
+                Some((value.ln(), time.ln()))
+            } else {
+                None
+            }
+        })
+        .collect();
+    let scaling_exponent = fit_slope(&points);
+
+    ParameterAnalysis {
+        command: command_template(fastest),
+        parameter: name.clone(),
+        fastest_value: parameter.value.clone(),
+        scaling_exponent,
+        scaling: scaling_exponent.map(Scaling::from_exponent),
+    }
+}
+
+/// Slope of a least-squares fit of a straight line through the given points
+fn fit_slope(points: &[(f64, f64)]) -> Option<f64> {
+    if points.len() < 2 {
+        return None;
+    }
+
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let covariance: f64 = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+
+    if variance == 0.0 {
+        None
+    } else {
+        Some(covariance / variance)
+    }
+}
+
+#[cfg(test)]
+fn create_result(template: &str, parameter: Option<(&str, &str)>, mean: f64) -> BenchmarkResult {
+    use super::benchmark_result::Parameter;
+    use super::measurement::{Measurement, Measurements};
+    use crate::quantity::Time;
+
+    let command = match parameter {
+        Some((name, value)) => template.replace(&format!("{{{name}}}"), value),
+        None => template.to_string(),
+    };
+    BenchmarkResult {
+        command,
+        measurements: Measurements::new(vec![Measurement {
+            time_wall_clock: Time::new::<second>(mean),
+            ..Default::default()
+        }]),
+        parameters: parameter
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), Parameter::new(value.to_string(), false)))
+            .collect(),
+        command_template: parameter.map(|_| template.to_string()),
+        shell_spawning_time: None,
+        captured_output: None,
+        modes: vec![],
+        run_indices: None,
+        execution_order: None,
+        reproduce: None,
+        git: None,
+        stateful: false,
+        group: None,
+        warnings: vec![],
+    }
+}
+
+#[test]
+fn test_analyze_parameter_scans() {
+    let results = vec![
+        create_result("reference", None, 1.0),
+        create_result("linear {n}", Some(("n", "1")), 2.0),
+        create_result("linear {n}", Some(("n", "2")), 4.0),
+        create_result("linear {n}", Some(("n", "4")), 8.0),
+        create_result("quadratic {n}", Some(("n", "1")), 1.0),
+        create_result("quadratic {n}", Some(("n", "2")), 4.0),
+        create_result("quadratic {n}", Some(("n", "4")), 16.0),
+        create_result("constant {n}", Some(("n", "0")), 3.0),
+        create_result("constant {n}", Some(("n", "1")), 2.0),
+        create_result("constant {n}", Some(("n", "2")), 2.0),
+    ];
+
+    let analysis = analyze(&results);
+    assert_eq!(analysis.len(), 3);
+
+    assert_eq!(analysis[0].command, "linear {n}");
+    assert_eq!(analysis[0].parameter, "n");
+    assert_eq!(analysis[0].fastest_value, "1");
+    approx::assert_relative_eq!(analysis[0].scaling_exponent.unwrap(), 1.0);
+    assert_eq!(analysis[0].scaling, Some(Scaling::Linear));
+
+    approx::assert_relative_eq!(analysis[1].scaling_exponent.unwrap(), 2.0);
+    assert_eq!(analysis[1].scaling, Some(Scaling::Quadratic));
+
+    // The parameter value zero is ignored for the fit
+    assert_eq!(analysis[2].fastest_value, "1");
+    approx::assert_relative_eq!(analysis[2].scaling_exponent.unwrap(), 0.0);
+    assert_eq!(analysis[2].scaling, Some(Scaling::Constant));
+}
+
+#[test]
+fn test_analyze_orders_parameter_values_numerically() {
+    let results = vec![
+        create_result("linear {n}", Some(("n", "10")), 20.0),
+        create_result("other {n}", Some(("n", "1")), 1.0),
+        create_result("linear {n}", Some(("n", "2")), 4.0),
+        create_result("linear {n}", Some(("n", "1")), 2.0),
+    ];
+
+    let analysis = analyze(&results);
+    assert_eq!(analysis.len(), 1);
+    assert_eq!(analysis[0].command, "linear {n}");
+    assert_eq!(analysis[0].fastest_value, "1");
+    approx::assert_relative_eq!(analysis[0].scaling_exponent.unwrap(), 1.0);
+}
+
+#[test]
+fn test_analyze_requires_numeric_scan() {
+    let results = vec![
+        create_result("a", Some(("x", "foo")), 1.0),
+        create_result("b", Some(("x", "bar")), 2.0),
+        create_result("single {n}", Some(("n", "1")), 2.0),
+    ];
+    assert!(analyze(&results).is_empty());
+}
+
+#[test]
+fn test_analyze_groups_by_command_template() {
+    // The value 1 also appears in '--size 10', which must not split the scan
+    let results = vec![
+        create_result("run --threads {n} --size 10", Some(("n", "1")), 3.0),
+        create_result("run --threads {n} --size 10", Some(("n", "2")), 2.0),
+        create_result("run --threads {n} --size 10", Some(("n", "3")), 1.0),
+    ];
+
+    let analysis = analyze(&results);
+    assert_eq!(analysis.len(), 1);
+    assert_eq!(analysis[0].command, "run --threads {n} --size 10");
+    assert_eq!(analysis[0].fastest_value, "3");
+}
+
+#[test]
+fn test_command_template_of_imported_results() {
+    let mut result = create_result("run --threads={n} --size 10 -j1", Some(("n", "1")), 1.0);
+    result.command_template = None;
+    assert_eq!(command_template(&result), "run --threads={n} --size 10 -j1");
+}
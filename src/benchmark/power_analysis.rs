@@ -0,0 +1,131 @@
+//! Statistical power of the comparison of two benchmarks: the smallest difference of the mean
+//! times that can be detected with the observed variance and number of runs, and the number of
+//! runs that would be needed to detect a given difference.
+
+use super::benchmark_result::BenchmarkResult;
+use crate::quantity::second;
+
+/// z-value of a two-sided test at a significance level of 5%
+const Z_ALPHA: f64 = 1.96;
+
+/// z-value of a power of 80%
+const Z_BETA: f64 = 0.8416;
+
+/// Relative difference of the mean times for which the required number of runs is computed
+pub const TARGET_DIFFERENCE: f64 = 0.01;
+
+/// Power analysis of the comparison of a benchmark with the reference benchmark. All
+/// differences are relative to the mean time of the reference.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowerAnalysis {
+    /// Observed difference of the mean times (positive if the benchmark is slower)
+    pub observed_difference: f64,
+
+    /// Smallest difference of the mean times which is detected with a significance level of
+    /// 5% and a power of 80%, given the observed variances and numbers of runs
+    pub minimal_detectable_difference: f64,
+
+    /// Number of runs of each of the two benchmarks which is needed to detect a difference
+    /// of `TARGET_DIFFERENCE`
+    pub required_runs: u64,
+}
+
+impl PowerAnalysis {
+    /// Whether the observed difference is too small to be detected reliably, i.e. whether
+    /// "no significant difference" does not mean that there is no relevant difference
+    pub fn is_underpowered(&self) -> bool {
+        self.observed_difference.abs() < self.minimal_detectable_difference
+    }
+}
+
+/// The power analysis of the comparison of `result` with `reference` (using a two-sample
+/// z-test), or `None` if one of them has less than two runs
+pub fn analyze(reference: &BenchmarkResult, result: &BenchmarkResult) -> Option<PowerAnalysis> {
+    let moments = |result: &BenchmarkResult| {
+        let n = result.measurements.len() as f64;
+        let stddev = result.measurements.stddev()?.get::<second>();
+        Some((
+            result.mean_wall_clock_time().get::<second>(),
+            stddev * stddev,
+            n,
+        ))
+    };
+    let (reference_mean, reference_variance, reference_n) = moments(reference)?;
+    let (mean, variance, n) = moments(result)?;
+    if reference_mean <= 0.0 {
+        return None;
+    }
+
+    let z = Z_ALPHA + Z_BETA;
+    let standard_error = (reference_variance / reference_n + variance / n).sqrt();
+    let target = TARGET_DIFFERENCE * reference_mean;
+    let required_runs = (z * z * (reference_variance + variance) / (target * target)).ceil();
+
+    Some(PowerAnalysis {
+        observed_difference: (mean - reference_mean) / reference_mean,
+        minimal_detectable_difference: z * standard_error / reference_mean,
+        required_runs: (required_runs as u64).max(2),
+    })
+}
+
+#[cfg(test)]
+fn create_result(times: &[f64]) -> BenchmarkResult {
+    use std::collections::BTreeMap;
+
+    use super::measurement::{Measurement, Measurements};
+    use crate::quantity::Time;
+
+    BenchmarkResult {
+        command: "command".into(),
+        measurements: Measurements::new(
+            times
+                .iter()
+                .map(|&time| Measurement {
+                    time_wall_clock: Time::new::<second>(time),
+                    ..Default::default()
+                })
+                .collect(),
+        ),
+        parameters: BTreeMap::new(),
+        command_template: None,
+        shell_spawning_time: None,
+        captured_output: None,
+        modes: vec![],
+        run_indices: None,
+        execution_order: None,
+        reproduce: None,
+        git: None,
+        stateful: false,
+        group: None,
+        warnings: vec![],
+    }
+}
+
+#[test]
+fn test_power_analysis() {
+    use approx::assert_relative_eq;
+
+    // Means of 1.0 s and 1.2 s, with the same spread
+    let reference = create_result(&[0.9, 1.0, 1.1, 0.9, 1.0, 1.1, 0.9, 1.0, 1.1]);
+    let slower = create_result(&[1.1, 1.2, 1.3, 1.1, 1.2, 1.3, 1.1, 1.2, 1.3]);
+
+    let analysis = analyze(&reference, &slower).unwrap();
+    let stddev: f64 = (0.06f64 / 8.0).sqrt();
+    assert_relative_eq!(analysis.observed_difference, 0.2, epsilon = 1e-9);
+    assert_relative_eq!(
+        analysis.minimal_detectable_difference,
+        2.8016 * stddev * (2.0f64 / 9.0).sqrt(),
+        epsilon = 1e-9
+    );
+    assert_eq!(
+        analysis.required_runs,
+        (2.8016f64.powi(2) * 2.0 * stddev * stddev / 0.0001).ceil() as u64
+    );
+    assert!(!analysis.is_underpowered());
+
+    let analysis = analyze(&reference, &reference).unwrap();
+    assert_eq!(analysis.observed_difference, 0.0);
+    assert!(analysis.is_underpowered());
+
+    assert!(analyze(&reference, &create_result(&[1.0])).is_none());
+}
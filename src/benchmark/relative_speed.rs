@@ -1,9 +1,12 @@
 use std::cmp::Ordering;
 
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
 use super::benchmark_result::BenchmarkResult;
 use crate::{
-    options::SortOrder,
-    quantity::{self, Ratio, Time, Quantity},
+    options::{RelativeError, RelativeMetric, SortOrder},
+    quantity::second,
 };
 
 #[derive(Debug)]
@@ -11,21 +14,148 @@ pub struct BenchmarkResultWithRelativeSpeed<'a> {
     pub result: &'a BenchmarkResult,
     pub relative_speed: f64,
     pub relative_speed_stddev: Option<f64>,
+    /// 95% confidence interval of the relative speed
+    pub relative_speed_ci: Option<(f64, f64)>,
     pub is_reference: bool,
     // Less means faster
     pub relative_ordering: Ordering,
+    /// Probability that a random run of the reference is faster than a random run of
+    /// this benchmark (ties count half)
+    pub probability_reference_faster: f64,
 }
 
 pub fn compare_mean_time(l: &BenchmarkResult, r: &BenchmarkResult) -> Ordering {
-    l.mean_wall_clock_time()
-        .partial_cmp(&r.mean_wall_clock_time())
+    compare_mean(l, r, RelativeMetric::WallClock)
+}
+
+/// The mean of the given metric (see `Measurements::metric_statistics`)
+fn metric_mean(result: &BenchmarkResult, metric: RelativeMetric) -> f64 {
+    result.measurements.metric_statistics(metric).0
+}
+
+/// Compare the means of the given metric
+pub fn compare_mean(l: &BenchmarkResult, r: &BenchmarkResult, metric: RelativeMetric) -> Ordering {
+    metric_mean(l, metric)
+        .partial_cmp(&metric_mean(r, metric))
         .unwrap_or(Ordering::Equal)
 }
 
-pub fn fastest_of(results: &[BenchmarkResult]) -> &BenchmarkResult {
+/// Probability that a randomly chosen run of `l` is faster (has a lower value of the given
+/// metric) than a randomly chosen run of `r`, where ties count half. This is also known as
+/// the "common-language effect size" or "probability of superiority". Unlike the ratio of
+/// the means, it takes the overlap of the two distributions into account.
+pub fn probability_of_superiority(
+    l: &BenchmarkResult,
+    r: &BenchmarkResult,
+    metric: RelativeMetric,
+) -> f64 {
+    let l_times = l.measurements.metric_values(metric);
+    let mut r_times = r.measurements.metric_values(metric);
+    if l_times.is_empty() || r_times.is_empty() {
+        return 0.5;
+    }
+
+    r_times.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let wins: f64 = l_times
+        .iter()
+        .map(|l_time| {
+            // Numbers of runs of `r` that are faster than (or as fast as) this run of `l`
+            let faster = r_times.partition_point(|r_time| r_time < l_time);
+            let faster_or_equal = r_times.partition_point(|r_time| r_time <= l_time);
+            (r_times.len() - faster_or_equal) as f64 + 0.5 * (faster_or_equal - faster) as f64
+        })
+        .sum();
+
+    wins / (l_times.len() * r_times.len()) as f64
+}
+
+/// z-value of the two-sided 95% confidence interval of the normal distribution
+const Z_95: f64 = 1.96;
+
+/// Number of resamples for the bootstrap estimate of the uncertainty of a relative speed
+const BOOTSTRAP_RESAMPLES: usize = 1000;
+
+/// The resampling is seeded, such that the reported uncertainties are reproducible
+const BOOTSTRAP_SEED: u64 = 0x6879_7065_7266_696e;
+
+/// Standard deviation and 95% confidence interval of the ratio of the means of the two
+/// samples, estimated by resampling both of them (with replacement). Unlike the propagation
+/// of the standard deviations, this does not assume normally distributed values, which
+/// matters for the skewed distributions of run times.
+fn bootstrap_ratio(numerator: &[f64], denominator: &[f64]) -> Option<(f64, (f64, f64))> {
+    if numerator.len() < 2 || denominator.len() < 2 {
+        return None;
+    }
+
+    let mut rng = StdRng::seed_from_u64(BOOTSTRAP_SEED);
+    let mut resampled_mean = |values: &[f64]| {
+        (0..values.len())
+            .map(|_| values[rng.gen_range(0..values.len())])
+            .sum::<f64>()
+            / values.len() as f64
+    };
+    let mut ratios: Vec<f64> = (0..BOOTSTRAP_RESAMPLES)
+        .map(|_| resampled_mean(numerator) / resampled_mean(denominator))
+        .filter(|ratio| ratio.is_finite())
+        .collect();
+    if ratios.len() < 2 {
+        return None;
+    }
+    ratios.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let n = ratios.len() as f64;
+    let mean = ratios.iter().sum::<f64>() / n;
+    let stddev = (ratios.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+    let percentile = |p: f64| ratios[((n - 1.0) * p).round() as usize];
+    Some((stddev, (percentile(0.025), percentile(0.975))))
+}
+
+/// Half-width of the 95% confidence interval of the mean wall clock time, in seconds
+fn confidence_interval_half_width(result: &BenchmarkResult) -> f64 {
+    let n = result.measurements.len() as f64;
+    result
+        .measurements
+        .stddev()
+        .map_or(0.0, |stddev| Z_95 * stddev.get::<second>() / n.sqrt())
+}
+
+/// Whether two benchmarks are statistically indistinguishable: their mean times differ by
+/// at most `threshold` (relative to the faster one), and the 95% confidence intervals of
+/// the means overlap.
+pub fn is_equivalent(a: &BenchmarkResult, b: &BenchmarkResult, threshold: f64) -> bool {
+    let mean_a = a.mean_wall_clock_time().get::<second>();
+    let mean_b = b.mean_wall_clock_time().get::<second>();
+    let difference = (mean_a - mean_b).abs();
+
+    difference <= threshold * mean_a.min(mean_b)
+        && difference <= confidence_interval_half_width(a) + confidence_interval_half_width(b)
+}
+
+/// Group the results (in the given order) into sets of equivalent benchmarks. Each result
+/// joins the first group whose first member it is equivalent to.
+pub fn equivalence_groups<'a>(
+    results: &[&'a BenchmarkResult],
+    threshold: f64,
+) -> Vec<Vec<&'a BenchmarkResult>> {
+    let mut groups: Vec<Vec<&BenchmarkResult>> = vec![];
+    for &result in results {
+        match groups
+            .iter_mut()
+            .find(|group| is_equivalent(group[0], result, threshold))
+        {
+            Some(group) => group.push(result),
+            None => groups.push(vec![result]),
+        }
+    }
+    groups
+}
+
+/// The result with the lowest mean of the given metric
+pub fn fastest_of(results: &[BenchmarkResult], metric: RelativeMetric) -> &BenchmarkResult {
     results
         .iter()
-        .min_by(|&l, &r| compare_mean_time(l, r))
+        .min_by(|&l, &r| compare_mean(l, r, metric))
         .expect("at least one benchmark result")
 }
 
@@ -33,54 +163,82 @@ fn compute_relative_speeds<'a>(
     results: &'a [BenchmarkResult],
     reference: &'a BenchmarkResult,
     sort_order: SortOrder,
+    metric: RelativeMetric,
+    relative_error: RelativeError,
 ) -> Vec<BenchmarkResultWithRelativeSpeed<'a>> {
+    let (reference_mean, reference_stddev) = reference.measurements.metric_statistics(metric);
+    let reference_sample = match relative_error {
+        RelativeError::Bootstrap => reference.measurements.metric_sample(metric),
+        RelativeError::Naive => vec![],
+    };
+
     let mut results: Vec<_> = results
         .iter()
         .map(|result| {
             let is_reference = result == reference;
-            let relative_ordering = compare_mean_time(result, reference);
+            let relative_ordering = compare_mean(result, reference, metric);
+            let probability_reference_faster =
+                probability_of_superiority(reference, result, metric);
+            let (mean, stddev) = result.measurements.metric_statistics(metric);
 
-            if result.mean_wall_clock_time() == Time::zero() {
+            if mean == 0.0 {
                 return BenchmarkResultWithRelativeSpeed {
                     result,
                     relative_speed: if is_reference { 1.0 } else { f64::INFINITY },
                     relative_speed_stddev: None,
+                    relative_speed_ci: None,
                     is_reference,
                     relative_ordering,
+                    probability_reference_faster,
                 };
             }
 
             let ratio = match relative_ordering {
-                Ordering::Less => reference.mean_wall_clock_time() / result.mean_wall_clock_time(),
-                Ordering::Equal => Ratio::new::<quantity::ratio>(1.0),
-                Ordering::Greater => {
-                    result.mean_wall_clock_time() / reference.mean_wall_clock_time()
-                }
+                Ordering::Less => reference_mean / mean,
+                Ordering::Equal => 1.0,
+                Ordering::Greater => mean / reference_mean,
             };
 
-            // https://en.wikipedia.org/wiki/Propagation_of_uncertainty#Example_formulas
-            // Covariance asssumed to be 0, i.e. variables are assumed to be independent
-            let ratio_stddev = match (
-                result.measurements.stddev(),
-                reference.measurements.stddev(),
-            ) {
-                (Some(result_stddev), Some(fastest_stddev)) => Some(
-                    ratio
-                        * ((result_stddev / result.mean_wall_clock_time())
-                            .powi(uom::typenum::P2::new())
-                            + (fastest_stddev / reference.mean_wall_clock_time())
-                                .powi(uom::typenum::P2::new()))
-                        .sqrt(),
-                ),
-                _ => None,
+            let (ratio_stddev, ratio_ci) = match relative_error {
+                RelativeError::Bootstrap => {
+                    let sample = result.measurements.metric_sample(metric);
+                    let bootstrap = match relative_ordering {
+                        Ordering::Less => bootstrap_ratio(&reference_sample, &sample),
+                        Ordering::Equal | Ordering::Greater => {
+                            bootstrap_ratio(&sample, &reference_sample)
+                        }
+                    };
+                    (
+                        bootstrap.map(|(stddev, _)| stddev),
+                        bootstrap.map(|(_, ci)| ci),
+                    )
+                }
+                RelativeError::Naive => {
+                    // https://en.wikipedia.org/wiki/Propagation_of_uncertainty#Example_formulas
+                    // Covariance asssumed to be 0, i.e. variables are assumed to be independent
+                    let ratio_stddev = match (stddev, reference_stddev) {
+                        (Some(result_stddev), Some(fastest_stddev)) => Some(
+                            ratio
+                                * ((result_stddev / mean).powi(2)
+                                    + (fastest_stddev / reference_mean).powi(2))
+                                .sqrt(),
+                        ),
+                        _ => None,
+                    };
+                    let ratio_ci =
+                        ratio_stddev.map(|stddev| (ratio - Z_95 * stddev, ratio + Z_95 * stddev));
+                    (ratio_stddev, ratio_ci)
+                }
             };
 
             BenchmarkResultWithRelativeSpeed {
                 result,
-                relative_speed: ratio.get::<quantity::ratio>(),
-                relative_speed_stddev: ratio_stddev.map(|r| r.get::<quantity::ratio>()),
+                relative_speed: ratio,
+                relative_speed_stddev: ratio_stddev,
+                relative_speed_ci: ratio_ci,
                 is_reference,
                 relative_ordering,
+                probability_reference_faster,
             }
         })
         .collect();
@@ -88,7 +246,7 @@ fn compute_relative_speeds<'a>(
     match sort_order {
         SortOrder::Command => {}
         SortOrder::MeanTime => {
-            results.sort_unstable_by(|r1, r2| compare_mean_time(r1.result, r2.result));
+            results.sort_unstable_by(|r1, r2| compare_mean(r1.result, r2.result, metric));
         }
     }
 
@@ -99,37 +257,68 @@ pub fn compute_with_check_from_reference<'a>(
     results: &'a [BenchmarkResult],
     reference: &'a BenchmarkResult,
     sort_order: SortOrder,
+    metric: RelativeMetric,
+    relative_error: RelativeError,
 ) -> Option<Vec<BenchmarkResultWithRelativeSpeed<'a>>> {
-    if fastest_of(results).mean_wall_clock_time() == Time::zero()
-        || reference.mean_wall_clock_time() == Time::zero()
+    if metric_mean(fastest_of(results, metric), metric) == 0.0
+        || metric_mean(reference, metric) == 0.0
     {
         return None;
     }
 
-    Some(compute_relative_speeds(results, reference, sort_order))
+    Some(compute_relative_speeds(
+        results,
+        reference,
+        sort_order,
+        metric,
+        relative_error,
+    ))
 }
 
 pub fn compute_with_check(
     results: &[BenchmarkResult],
     sort_order: SortOrder,
-) -> Option<Vec<BenchmarkResultWithRelativeSpeed>> {
-    let fastest = fastest_of(results);
+    metric: RelativeMetric,
+    relative_error: RelativeError,
+) -> Option<Vec<BenchmarkResultWithRelativeSpeed<'_>>> {
+    let fastest = fastest_of(results, metric);
 
-    if fastest.mean_wall_clock_time() == Time::zero() {
+    if metric_mean(fastest, metric) == 0.0 {
         return None;
     }
 
-    Some(compute_relative_speeds(results, fastest, sort_order))
+    Some(compute_relative_speeds(
+        results,
+        fastest,
+        sort_order,
+        metric,
+        relative_error,
+    ))
 }
 
 /// Same as compute_with_check, potentially resulting in relative speeds of infinity
 pub fn compute(
     results: &[BenchmarkResult],
     sort_order: SortOrder,
-) -> Vec<BenchmarkResultWithRelativeSpeed> {
-    let fastest = fastest_of(results);
+    metric: RelativeMetric,
+    relative_error: RelativeError,
+) -> Vec<BenchmarkResultWithRelativeSpeed<'_>> {
+    let fastest = fastest_of(results, metric);
+
+    compute_relative_speeds(results, fastest, sort_order, metric, relative_error)
+}
 
-    compute_relative_speeds(results, fastest, sort_order)
+/// Same as compute, for each group of commands separately (see `--group`)
+pub fn compute_by_group<'a>(
+    groups: &'a [(Option<&str>, Vec<BenchmarkResult>)],
+    sort_order: SortOrder,
+    metric: RelativeMetric,
+    relative_error: RelativeError,
+) -> Vec<BenchmarkResultWithRelativeSpeed<'a>> {
+    groups
+        .iter()
+        .flat_map(|(_, results)| compute(results, sort_order, metric, relative_error))
+        .collect()
 }
 
 #[cfg(test)]
@@ -137,18 +326,27 @@ fn create_result(name: &str, mean: f64) -> BenchmarkResult {
     use std::collections::BTreeMap;
 
     use crate::benchmark::measurement::{Measurement, Measurements};
-    use crate::quantity::second;
+    use crate::quantity::Time;
 
     BenchmarkResult {
         command: name.into(),
-        measurements: Measurements {
-            measurements: vec![Measurement {
-                time_wall_clock: Time::new::<second>(mean),
-                time_user: Time::new::<second>(mean),
-                ..Default::default()
-            }],
-        },
+        measurements: Measurements::new(vec![Measurement {
+            time_wall_clock: Time::new::<second>(mean),
+            time_user: Time::new::<second>(mean),
+            ..Default::default()
+        }]),
         parameters: BTreeMap::new(),
+        command_template: None,
+        shell_spawning_time: None,
+        captured_output: None,
+        modes: vec![],
+        run_indices: None,
+        execution_order: None,
+        reproduce: None,
+        git: None,
+        stateful: false,
+        group: None,
+        warnings: vec![],
     }
 }
 
@@ -162,7 +360,13 @@ fn test_compute_relative_speed() {
         create_result("cmd3", 5.0),
     ];
 
-    let annotated_results = compute_with_check(&results, SortOrder::Command).unwrap();
+    let annotated_results = compute_with_check(
+        &results,
+        SortOrder::Command,
+        RelativeMetric::WallClock,
+        RelativeError::default(),
+    )
+    .unwrap();
 
     assert_relative_eq!(1.5, annotated_results[0].relative_speed);
     assert_relative_eq!(1.0, annotated_results[1].relative_speed);
@@ -176,18 +380,213 @@ fn test_compute_relative_speed_with_reference() {
     let results = vec![create_result("cmd2", 2.0), create_result("cmd3", 5.0)];
     let reference = create_result("cmd2", 4.0);
 
-    let annotated_results =
-        compute_with_check_from_reference(&results, &reference, SortOrder::Command).unwrap();
+    let annotated_results = compute_with_check_from_reference(
+        &results,
+        &reference,
+        SortOrder::Command,
+        RelativeMetric::WallClock,
+        RelativeError::default(),
+    )
+    .unwrap();
 
     assert_relative_eq!(2.0, annotated_results[0].relative_speed);
     assert_relative_eq!(1.25, annotated_results[1].relative_speed);
 }
 
+#[test]
+fn test_compute_relative_speed_by_metric() {
+    use approx::assert_relative_eq;
+
+    use crate::benchmark::measurement::{Measurement, Measurements};
+    use crate::quantity::Time;
+
+    // 'a' is faster, but uses more CPU time than 'b'
+    let result = |name: &str, wall_clock: f64, user: f64, system: f64| BenchmarkResult {
+        command: name.into(),
+        measurements: Measurements::new(vec![Measurement {
+            time_wall_clock: Time::new::<second>(wall_clock),
+            time_user: Time::new::<second>(user),
+            time_system: Time::new::<second>(system),
+            ..Default::default()
+        }]),
+        ..Default::default()
+    };
+    let results = vec![result("a", 1.0, 3.0, 1.0), result("b", 2.0, 1.5, 0.5)];
+
+    assert_eq!(fastest_of(&results, RelativeMetric::WallClock).command, "a");
+    assert_eq!(fastest_of(&results, RelativeMetric::Cpu).command, "b");
+
+    let annotated_results = compute_with_check(
+        &results,
+        SortOrder::MeanTime,
+        RelativeMetric::Cpu,
+        RelativeError::default(),
+    )
+    .unwrap();
+    assert_eq!(annotated_results[0].result.command, "b");
+    assert_relative_eq!(annotated_results[1].relative_speed, 2.0);
+
+    let annotated_results = compute_with_check(
+        &results,
+        SortOrder::Command,
+        RelativeMetric::System,
+        RelativeError::default(),
+    )
+    .unwrap();
+    assert_relative_eq!(annotated_results[0].relative_speed, 2.0);
+    assert_relative_eq!(annotated_results[1].relative_speed, 1.0);
+
+    // Without memory usage measurements, the comparison can not be computed
+    assert!(compute_with_check(
+        &results,
+        SortOrder::Command,
+        RelativeMetric::Memory,
+        RelativeError::default()
+    )
+    .is_none());
+}
+
 #[test]
 fn test_compute_relative_speed_for_zero_times() {
     let results = vec![create_result("cmd1", 1.0), create_result("cmd2", 0.0)];
 
-    let annotated_results = compute_with_check(&results, SortOrder::Command);
+    let annotated_results = compute_with_check(
+        &results,
+        SortOrder::Command,
+        RelativeMetric::WallClock,
+        RelativeError::default(),
+    );
 
     assert!(annotated_results.is_none());
 }
+
+#[test]
+fn test_probability_of_superiority() {
+    use approx::assert_relative_eq;
+
+    use crate::benchmark::measurement::{Measurement, Measurements};
+    use crate::quantity::Time;
+
+    let result = |times: &[f64]| BenchmarkResult {
+        measurements: Measurements::new(
+            times
+                .iter()
+                .map(|&time| Measurement {
+                    time_wall_clock: Time::new::<second>(time),
+                    ..Default::default()
+                })
+                .collect(),
+        ),
+        ..Default::default()
+    };
+
+    let fast = result(&[1.0, 2.0, 3.0]);
+    let slow = result(&[2.0, 4.0, 5.0, 6.0]);
+
+    // 1.0 beats all four, 2.0 beats three and ties one, 3.0 beats three
+    assert_relative_eq!(
+        probability_of_superiority(&fast, &slow, RelativeMetric::WallClock),
+        10.5 / 12.0
+    );
+    assert_relative_eq!(
+        probability_of_superiority(&slow, &fast, RelativeMetric::WallClock),
+        1.5 / 12.0
+    );
+    assert_relative_eq!(
+        probability_of_superiority(&fast, &fast, RelativeMetric::WallClock),
+        0.5
+    );
+}
+
+#[test]
+fn test_equivalence_groups() {
+    use crate::benchmark::measurement::{Measurement, Measurements};
+    use crate::quantity::Time;
+
+    let result = |name: &str, times: &[f64]| BenchmarkResult {
+        command: name.into(),
+        measurements: Measurements::new(
+            times
+                .iter()
+                .map(|&time| Measurement {
+                    time_wall_clock: Time::new::<second>(time),
+                    ..Default::default()
+                })
+                .collect(),
+        ),
+        ..Default::default()
+    };
+
+    let a = result("a", &[1.0, 1.1, 0.9, 1.0]);
+    let b = result("b", &[1.02, 1.12, 0.92, 1.02]);
+    let c = result("c", &[2.0, 2.1, 1.9, 2.0]);
+    let d = result("d", &[2.01, 2.11, 1.91, 2.01]);
+
+    assert!(is_equivalent(&a, &b, 0.05));
+    assert!(!is_equivalent(&a, &b, 0.01));
+    assert!(!is_equivalent(&a, &c, 0.05));
+
+    // Means within the threshold, but without overlapping confidence intervals
+    let e = result("e", &[1.0, 1.0, 1.0]);
+    let f = result("f", &[1.01, 1.01, 1.01]);
+    assert!(!is_equivalent(&e, &f, 0.05));
+
+    let groups = equivalence_groups(&[&a, &b, &c, &d], 0.05);
+    let names: Vec<Vec<&str>> = groups
+        .iter()
+        .map(|group| group.iter().map(|r| r.command.as_str()).collect())
+        .collect();
+    assert_eq!(names, vec![vec!["a", "b"], vec!["c", "d"]]);
+}
+
+#[test]
+fn test_relative_speed_uncertainty() {
+    use crate::benchmark::measurement::{Measurement, Measurements};
+    use crate::quantity::Time;
+
+    // Skewed distributions with a few slow outliers
+    let result = |name: &str, times: &[f64]| BenchmarkResult {
+        command: name.into(),
+        measurements: Measurements::new(
+            times
+                .iter()
+                .map(|&time| Measurement {
+                    time_wall_clock: Time::new::<second>(time),
+                    ..Default::default()
+                })
+                .collect(),
+        ),
+        ..Default::default()
+    };
+    let results = vec![
+        result("fast", &[1.0, 1.0, 1.1, 1.0, 1.2, 1.0, 1.1, 1.0, 3.0, 1.0]),
+        result("slow", &[2.0, 2.1, 2.0, 2.2, 2.0, 2.1, 2.0, 6.0, 2.0, 5.0]),
+    ];
+
+    let compute = |relative_error| {
+        compute(
+            &results,
+            SortOrder::Command,
+            RelativeMetric::WallClock,
+            relative_error,
+        )
+    };
+    let bootstrap = compute(RelativeError::Bootstrap);
+    let (lower, upper) = bootstrap[1].relative_speed_ci.unwrap();
+    assert!(lower < bootstrap[1].relative_speed && bootstrap[1].relative_speed < upper);
+    assert!(bootstrap[1].relative_speed_stddev.unwrap() > 0.0);
+
+    // The resampling is reproducible
+    assert_eq!(
+        compute(RelativeError::Bootstrap)[1].relative_speed_ci,
+        bootstrap[1].relative_speed_ci
+    );
+
+    // The naive estimate is symmetric
+    let naive = compute(RelativeError::Naive);
+    let (lower, upper) = naive[1].relative_speed_ci.unwrap();
+    assert!((naive[1].relative_speed - lower - (upper - naive[1].relative_speed)).abs() < 1e-9);
+    assert_eq!(naive[1].relative_speed, bootstrap[1].relative_speed);
+
+    assert_eq!(bootstrap_ratio(&[1.0], &[1.0, 2.0]), None);
+}
@@ -0,0 +1,145 @@
+//! Auxiliary samplers record metrics that can not be attributed to the benchmarked process
+//! by the operating system (e.g. the GPU utilization). They are polled at a fixed interval
+//! while a command is running, and the average of each metric is stored with the run.
+
+mod nvml;
+
+use std::collections::BTreeMap;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crate::options::Options;
+use crate::output::log::log_verbose;
+
+use anyhow::Result;
+
+/// Time between two samples
+const SAMPLING_INTERVAL: Duration = Duration::from_millis(20);
+
+/// A source of metrics that are sampled while a benchmarked command is running
+pub trait AuxiliarySampler: Send + Sync {
+    /// Names of the sampled metrics, which are used as keys in the exports
+    fn metrics(&self) -> &[String];
+
+    /// Take one sample of each metric, in the order of `metrics()`
+    fn sample(&self) -> Result<Vec<f64>>;
+}
+
+/// Create the samplers that have been requested on the command line
+pub fn from_options(options: &Options) -> Result<Vec<Box<dyn AuxiliarySampler>>> {
+    let mut samplers: Vec<Box<dyn AuxiliarySampler>> = vec![];
+
+    if options.sample_gpu {
+        let sampler = nvml::NvmlSampler::new()?;
+        log_verbose!("Sampling the GPU metrics {}", sampler.metrics().join(", "));
+        samplers.push(Box::new(sampler));
+    }
+
+    Ok(samplers)
+}
+
+/// The number of GPUs whose metrics can be sampled (see `--sample-gpu`), or the reason why
+/// they can not be sampled
+pub fn probe_gpus() -> Result<usize> {
+    let sampler = nvml::NvmlSampler::new()?;
+    // Two metrics are sampled per GPU
+    Ok(sampler.metrics().len() / 2)
+}
+
+/// Run `f` while sampling all metrics in the background. Returns the result of `f` and the
+/// average value of each metric. At least one sample is taken, even for very short runs.
+pub fn sample_during<T>(
+    samplers: &[Box<dyn AuxiliarySampler>],
+    f: impl FnOnce() -> T,
+) -> (T, BTreeMap<String, f64>) {
+    if samplers.is_empty() {
+        return (f(), BTreeMap::new());
+    }
+
+    let (stop_sender, stop_receiver) = mpsc::channel::<()>();
+
+    thread::scope(|scope| {
+        let sampling_thread = scope.spawn(move || {
+            let mut sums: Vec<Vec<f64>> = samplers
+                .iter()
+                .map(|sampler| vec![0.0; sampler.metrics().len()])
+                .collect();
+            let mut counts = vec![0usize; samplers.len()];
+
+            loop {
+                for ((sampler, sums), count) in samplers.iter().zip(&mut sums).zip(&mut counts) {
+                    // A failing sample (e.g. a GPU that is busy resetting) is just skipped
+                    if let Ok(values) = sampler.sample() {
+                        for (sum, value) in sums.iter_mut().zip(values) {
+                            *sum += value;
+                        }
+                        *count += 1;
+                    }
+                }
+
+                if stop_receiver.recv_timeout(SAMPLING_INTERVAL).is_ok() {
+                    break;
+                }
+            }
+
+            averages(samplers, &sums, &counts)
+        });
+
+        let result = f();
+        stop_sender.send(()).ok();
+
+        (result, sampling_thread.join().unwrap_or_default())
+    })
+}
+
+fn averages(
+    samplers: &[Box<dyn AuxiliarySampler>],
+    sums: &[Vec<f64>],
+    counts: &[usize],
+) -> BTreeMap<String, f64> {
+    let mut averages = BTreeMap::new();
+    for ((sampler, sums), &count) in samplers.iter().zip(sums).zip(counts) {
+        if count == 0 {
+            continue;
+        }
+        for (metric, sum) in sampler.metrics().iter().zip(sums) {
+            averages.insert(metric.clone(), sum / count as f64);
+        }
+    }
+    averages
+}
+
+#[test]
+fn test_sample_during() {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    struct CountingSampler {
+        metrics: Vec<String>,
+        counter: AtomicU64,
+    }
+
+    impl AuxiliarySampler for CountingSampler {
+        fn metrics(&self) -> &[String] {
+            &self.metrics
+        }
+
+        fn sample(&self) -> Result<Vec<f64>> {
+            let n = self.counter.fetch_add(1, Ordering::SeqCst) as f64;
+            Ok(vec![1.0, n])
+        }
+    }
+
+    let samplers: Vec<Box<dyn AuxiliarySampler>> = vec![Box::new(CountingSampler {
+        metrics: vec!["constant".into(), "counter".into()],
+        counter: AtomicU64::new(0),
+    })];
+
+    let (result, averages) = sample_during(&samplers, || 42);
+    assert_eq!(result, 42);
+    assert_eq!(averages["constant"], 1.0);
+    assert!(averages["counter"] >= 0.0);
+
+    let (_, averages) = sample_during(&[], || ());
+    assert!(averages.is_empty());
+}
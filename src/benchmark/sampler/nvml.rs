@@ -0,0 +1,208 @@
+//! Sampling of the utilization and memory usage of NVIDIA GPUs through the NVIDIA Management
+//! Library (NVML). The library is shipped with the driver and loaded at runtime, such that
+//! hyperfine does not depend on it at build time.
+
+use std::ffi::{c_int, c_uint, c_void};
+use std::mem;
+use std::ptr;
+
+use super::AuxiliarySampler;
+
+use anyhow::{bail, Result};
+
+type NvmlReturn = c_int;
+type NvmlDevice = *mut c_void;
+
+const NVML_SUCCESS: NvmlReturn = 0;
+
+// Not all fields are read, but they are required for the layout defined by NVML
+#[repr(C)]
+#[derive(Default)]
+#[allow(dead_code)]
+struct NvmlUtilization {
+    gpu: c_uint,
+    memory: c_uint,
+}
+
+#[repr(C)]
+#[derive(Default)]
+#[allow(dead_code)]
+struct NvmlMemory {
+    total: u64,
+    free: u64,
+    used: u64,
+}
+
+type Init = unsafe extern "C" fn() -> NvmlReturn;
+type Shutdown = unsafe extern "C" fn() -> NvmlReturn;
+type GetCount = unsafe extern "C" fn(*mut c_uint) -> NvmlReturn;
+type GetHandle = unsafe extern "C" fn(c_uint, *mut NvmlDevice) -> NvmlReturn;
+type GetUtilizationRates = unsafe extern "C" fn(NvmlDevice, *mut NvmlUtilization) -> NvmlReturn;
+type GetMemoryInfo = unsafe extern "C" fn(NvmlDevice, *mut NvmlMemory) -> NvmlReturn;
+
+struct Functions {
+    shutdown: Shutdown,
+    get_utilization_rates: GetUtilizationRates,
+    get_memory_info: GetMemoryInfo,
+}
+
+/// Samples the utilization (in percent) and the used memory (in MiB) of all GPUs
+pub struct NvmlSampler {
+    functions: Functions,
+    devices: Vec<NvmlDevice>,
+    metrics: Vec<String>,
+}
+
+// SAFETY: NVML is thread-safe, and the device handles stay valid until `nvmlShutdown`
+unsafe impl Send for NvmlSampler {}
+unsafe impl Sync for NvmlSampler {}
+
+impl NvmlSampler {
+    pub fn new() -> Result<Self> {
+        let library = match open_library() {
+            Some(library) => library,
+            None => bail!(
+                "Could not load the NVIDIA Management Library, which is required for \
+                 '--sample-gpu'. Make sure that the NVIDIA driver is installed."
+            ),
+        };
+
+        // SAFETY: The symbols are transmuted to their signatures as documented by NVML
+        unsafe {
+            let symbol = |name| symbol(library, name);
+            let init = mem::transmute::<*mut c_void, Init>(symbol("nvmlInit_v2\0")?);
+            let get_count =
+                mem::transmute::<*mut c_void, GetCount>(symbol("nvmlDeviceGetCount_v2\0")?);
+            let get_handle = mem::transmute::<*mut c_void, GetHandle>(symbol(
+                "nvmlDeviceGetHandleByIndex_v2\0",
+            )?);
+            let functions = Functions {
+                shutdown: mem::transmute::<*mut c_void, Shutdown>(symbol("nvmlShutdown\0")?),
+                get_utilization_rates: mem::transmute::<*mut c_void, GetUtilizationRates>(symbol(
+                    "nvmlDeviceGetUtilizationRates\0",
+                )?),
+                get_memory_info: mem::transmute::<*mut c_void, GetMemoryInfo>(symbol(
+                    "nvmlDeviceGetMemoryInfo\0",
+                )?),
+            };
+
+            check(init(), "nvmlInit")?;
+
+            let mut count = 0;
+            check(get_count(&mut count), "nvmlDeviceGetCount")?;
+            if count == 0 {
+                (functions.shutdown)();
+                bail!("No NVIDIA GPU has been found for '--sample-gpu'");
+            }
+
+            let mut devices = vec![];
+            let mut metrics = vec![];
+            for index in 0..count {
+                let mut device = ptr::null_mut();
+                check(get_handle(index, &mut device), "nvmlDeviceGetHandleByIndex")?;
+                devices.push(device);
+                metrics.push(format!("gpu{index}_utilization_percent"));
+                metrics.push(format!("gpu{index}_memory_used_mib"));
+            }
+
+            Ok(NvmlSampler {
+                functions,
+                devices,
+                metrics,
+            })
+        }
+    }
+}
+
+impl AuxiliarySampler for NvmlSampler {
+    fn metrics(&self) -> &[String] {
+        &self.metrics
+    }
+
+    fn sample(&self) -> Result<Vec<f64>> {
+        let mut values = Vec::with_capacity(self.metrics.len());
+        for &device in &self.devices {
+            let mut utilization = NvmlUtilization::default();
+            let mut memory = NvmlMemory::default();
+
+            // SAFETY: The device handle is valid and the pointers point to structs of the
+            // expected layout
+            unsafe {
+                check(
+                    (self.functions.get_utilization_rates)(device, &mut utilization),
+                    "nvmlDeviceGetUtilizationRates",
+                )?;
+                check(
+                    (self.functions.get_memory_info)(device, &mut memory),
+                    "nvmlDeviceGetMemoryInfo",
+                )?;
+            }
+
+            values.push(utilization.gpu as f64);
+            values.push(memory.used as f64 / (1024.0 * 1024.0));
+        }
+        Ok(values)
+    }
+}
+
+impl Drop for NvmlSampler {
+    fn drop(&mut self) {
+        // SAFETY: NVML has been initialized in `new`
+        unsafe { (self.functions.shutdown)() };
+    }
+}
+
+fn check(status: NvmlReturn, function: &str) -> Result<()> {
+    if status != NVML_SUCCESS {
+        bail!("NVML call '{function}' failed with error code {status}");
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn open_library() -> Option<*mut c_void> {
+    // SAFETY: The library name is nul-terminated
+    let library = unsafe {
+        libc::dlopen(
+            "libnvidia-ml.so.1\0".as_ptr() as *const libc::c_char,
+            libc::RTLD_NOW,
+        )
+    };
+    (!library.is_null()).then_some(library)
+}
+
+#[cfg(not(windows))]
+fn symbol(library: *mut c_void, name: &str) -> Result<*mut c_void> {
+    // SAFETY: The library handle is valid and the name is nul-terminated
+    let symbol = unsafe { libc::dlsym(library, name.as_ptr() as *const libc::c_char) };
+    if symbol.is_null() {
+        bail!(
+            "The NVIDIA Management Library does not provide {}",
+            name.trim_end_matches('\0')
+        );
+    }
+    Ok(symbol)
+}
+
+#[cfg(windows)]
+fn open_library() -> Option<*mut c_void> {
+    use windows_sys::Win32::System::LibraryLoader::LoadLibraryA;
+
+    // SAFETY: The library name is nul-terminated
+    let library = unsafe { LoadLibraryA("nvml.dll\0".as_ptr()) };
+    (!library.is_null()).then_some(library)
+}
+
+#[cfg(windows)]
+fn symbol(library: *mut c_void, name: &str) -> Result<*mut c_void> {
+    use windows_sys::Win32::System::LibraryLoader::GetProcAddress;
+
+    // SAFETY: The library handle is valid and the name is nul-terminated
+    match unsafe { GetProcAddress(library, name.as_ptr()) } {
+        Some(symbol) => Ok(symbol as *mut c_void),
+        None => bail!(
+            "The NVIDIA Management Library does not provide {}",
+            name.trim_end_matches('\0')
+        ),
+    }
+}
@@ -1,16 +1,36 @@
-use super::benchmark_result::BenchmarkResult;
-use super::executor::{self, Executor, MockExecutor, RawExecutor, ShellExecutor};
-use super::{relative_speed, Benchmark};
+use super::attach::AttachExecutor;
+use super::benchmark_result::{self, BenchmarkResult};
+use super::executor::{
+    BenchmarkIteration, Executor, MockExecutor, RawExecutor, RunContext, ShellExecutor,
+};
+use super::relative_speed::{self, BenchmarkResultWithRelativeSpeed};
+use super::{parameter_analysis, power_analysis, Benchmark, Reporter};
 use colored::*;
-use flume::bounded;
-use std::cmp::Ordering;
+use flume::unbounded;
+use std::cmp::{self, Ordering};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 use std::thread;
 
 use crate::command::{Command, Commands};
+use crate::error::{ExitCode, WithExitCode};
 use crate::export::ExportManager;
-use crate::options::{ExecutorKind, Options, OutputStyleOption, SortOrder};
+use crate::options::{
+    CommandDisplay, DuplicateCommands, ExecutorKind, JustRunFormat, Options, OutputStyleOption,
+    RelativeMetric, SortOrder,
+};
+use crate::outlier_detection::OUTLIER_THRESHOLD;
+use crate::output::command_display::{self, visible_width};
+use crate::output::glyphs::glyphs;
+use crate::output::log::{log_debug, log_verbose};
+use crate::output::notification;
+use crate::output::progress_bar::SessionProgress;
+use crate::quantity::second;
+use crate::util::cpu_affinity;
+use crate::util::exit_code::extract_exit_code;
+use crate::util::interrupt;
 
-use anyhow::Result;
+use anyhow::{anyhow, ensure, Context, Result};
+use serde_json::json;
 
 pub struct Scheduler<'a> {
     commands: &'a Commands<'a>,
@@ -33,12 +53,20 @@ impl<'a> Scheduler<'a> {
         }
     }
 
-    pub fn run_benchmarks(&mut self) -> Result<()> {
-        let mut executor: Box<dyn Executor> = match self.options.executor_kind {
+    fn executor(&self) -> Box<dyn Executor + 'a> {
+        let executor: Box<dyn Executor> = match self.options.executor_kind {
             ExecutorKind::Raw => Box::new(RawExecutor::new(self.options)),
             ExecutorKind::Mock(ref shell) => Box::new(MockExecutor::new(shell.clone())),
             ExecutorKind::Shell(ref shell) => Box::new(ShellExecutor::new(shell, self.options)),
         };
+        match self.options.attach {
+            Some(target) => Box::new(AttachExecutor::new(target, self.options, executor)),
+            None => executor,
+        }
+    }
+
+    pub fn run_benchmarks(&mut self) -> Result<()> {
+        let mut executor = self.executor();
 
         let reference = self
             .options
@@ -46,64 +74,371 @@ impl<'a> Scheduler<'a> {
             .as_ref()
             .map(|cmd| Command::new(None, cmd));
 
-        executor.calibrate()?;
+        executor.calibrate().exit_code(ExitCode::PreflightFailed)?;
+
+        let commands: Vec<_> = reference.iter().chain(self.commands.iter()).collect();
+
+        // The reference command is always benchmarked first
+        let offset = commands.len() - self.commands.num_commands(false);
+        let order: Vec<usize> = (0..offset)
+            .chain(
+                self.commands
+                    .execution_order(self.options.scan_order, self.options.shuffle_seed)
+                    .into_iter()
+                    .map(|index| index + offset),
+            )
+            .collect();
+
+        if let Some(seed) = self.options.shuffle_seed {
+            if self.options.show_benchmark_output() {
+                println!(
+                    "{} the order of the benchmarks with seed {seed} (use '--shuffle={seed}' to \
+                     reproduce it)\n",
+                    "Shuffled".bold()
+                );
+            }
+        }
 
-        let mut executor2 = executor.clone();
+        if self.options.jobs > 1 && commands.len() > 1 {
+            return self.run_benchmarks_in_parallel(&commands, &order, &*executor);
+        }
 
-        let (sender, receiver) = bounded(self.options.jobs);
+        let session_progress = if self.options.show_benchmark_output() && commands.len() > 1 {
+            SessionProgress::new(commands.len(), self.options.output_style)
+        } else {
+            None
+        };
 
-        for (number, cmd) in reference.iter().chain(self.commands.iter()).enumerate() {
-            self.results
-                .push(Benchmark::new(number, cmd, self.options, &*executor).run()?);
+        let mut results: Vec<Option<BenchmarkResult>> = vec![None; commands.len()];
+        for (position, &number) in order.iter().enumerate() {
+            // The runs of the remaining commands are killed once they take much longer than
+            // the fastest command so far
+            let timeout = self.options.auto_timeout.and_then(|factor| {
+                results
+                    .iter()
+                    .flatten()
+                    .map(|result| result.mean_wall_clock_time())
+                    .min_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+                    .map(|fastest| fastest * factor)
+            });
+            let mut result = Benchmark::new(number, commands[number], self.options, &*executor)
+                .with_session_progress(session_progress.as_ref())
+                .first_in_session(position == 0)
+                .with_timeout(timeout)
+                .run()?;
+            if let Some(ref session_progress) = session_progress {
+                session_progress.finish_benchmark();
+            }
+            if self.options.is_reordered() {
+                result.execution_order = Some(position);
+            }
+            result.reproduce = Some(self.export_manager.reproduce(number, commands[number]));
+            results[number] = Some(result);
 
             // We export results after each individual benchmark, because
             // we would risk losing them if a later benchmark fails.
-            self.export_manager.write_results(&self.results, true)?;
+            let finished: Vec<_> = results.iter().flatten().cloned().collect();
+            self.export_manager.write_results(&finished, true)?;
+        }
+        self.results = self.merge_duplicates(results);
+
+        Ok(())
+    }
+
+    /// Run each command the requested number of times and print the time of each run,
+    /// without calibration (i.e. without shell spawning time correction) or statistics
+    pub fn just_run(&self, format: JustRunFormat) -> Result<()> {
+        let executor = self.executor();
+        for (number, cmd) in self.commands.iter().enumerate() {
+            let runs = self.options.run_bounds[number].min;
+            let name = cmd.get_name();
+            let output_policy = &self.options.command_output_policies[number];
+            let memory_limit = self.options.memory_limit(cmd)?;
+
+            for run in 0..runs {
+                let iteration = BenchmarkIteration::Benchmark(run);
+                let measurement = executor.run_command_and_measure(
+                    &cmd.with_run_placeholders(
+                        iteration.to_env_var_value(),
+                        Some(
+                            run.checked_sub(1)
+                                .map_or_else(String::new, |r| r.to_string()),
+                        ),
+                        Some(runs),
+                    ),
+                    RunContext::new(&name, iteration).with_memory_limit(memory_limit),
+                    None,
+                    output_policy,
+                )?;
+                ensure!(
+                    !interrupt::was_interrupted(),
+                    "The benchmark was interrupted"
+                );
+
+                match format {
+                    JustRunFormat::Plain => {
+                        println!("{}", measurement.time_wall_clock.get::<second>())
+                    }
+                    JustRunFormat::Jsonl => println!(
+                        "{}",
+                        json!({
+                            "command": name,
+                            "run": run,
+                            "time_wall_clock": measurement.time_wall_clock.get::<second>(),
+                            "time_user": measurement.time_user.get::<second>(),
+                            "time_system": measurement.time_system.get::<second>(),
+                            "exit_code": extract_exit_code(measurement.exit_status),
+                        })
+                    ),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Run several benchmarks at the same time. Each job is pinned to its own set of CPU
+    /// cores (if supported), and the runs within a single benchmark are still sequential.
+    /// Results are reported in the order in which the benchmarks finish.
+    fn run_benchmarks_in_parallel(
+        &mut self,
+        commands: &[&Command],
+        order: &[usize],
+        executor: &dyn Executor,
+    ) -> Result<()> {
+        let mut cpu_sets = cpu_affinity::split_available_cpus(self.options.jobs)
+            .context("Could not determine the available CPUs")?;
+
+        // There can not be more jobs than available CPUs or benchmarks.
+        let mut jobs = cmp::min(self.options.jobs, commands.len());
+        if let Some(ref mut cpu_sets) = cpu_sets {
+            cpu_sets.retain(|cpus| !cpus.is_empty());
+            jobs = cmp::min(jobs, cpu_sets.len());
+        }
+
+        log_verbose!("Running {jobs} benchmarks in parallel");
+        if let Some(ref cpu_sets) = cpu_sets {
+            for (job, cpus) in cpu_sets.iter().take(jobs).enumerate() {
+                log_debug!("Job {job} is pinned to the CPUs {cpus:?}");
+            }
+        }
+
+        let (job_sender, job_receiver) = unbounded();
+        for &number in order {
+            job_sender.send(number)?;
         }
+        drop(job_sender);
+
+        let (result_sender, result_receiver) = unbounded();
+        let abort = AtomicBool::new(false);
+
+        let mut results: Vec<Option<BenchmarkResult>> = vec![None; commands.len()];
+        let options = self.options;
+
+        thread::scope(|scope| -> Result<()> {
+            for job in 0..jobs {
+                let cpus = cpu_sets.as_ref().map(|cpu_sets| &cpu_sets[job]);
+                let job_receiver = job_receiver.clone();
+                let result_sender = result_sender.clone();
+                let abort = &abort;
+
+                scope.spawn(move || {
+                    let pinned = cpus.map(|cpus| cpu_affinity::pin_current_thread(cpus));
+
+                    for number in job_receiver.iter() {
+                        if abort.load(AtomicOrdering::Relaxed) {
+                            break;
+                        }
+
+                        let result = match pinned {
+                            Some(Err(ref e)) => {
+                                Err(anyhow!("Could not pin benchmark job to its CPUs: {e}"))
+                            }
+                            _ => Benchmark::new(number, commands[number], options, executor)
+                                .measure(false),
+                        };
+
+                        if result_sender.send((number, result)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(result_sender);
+
+            for (number, result) in result_receiver.iter() {
+                let mut result = match result {
+                    Ok(result) => result,
+                    Err(e) => {
+                        abort.store(true, AtomicOrdering::Relaxed);
+                        return Err(e);
+                    }
+                };
+
+                let benchmark = Benchmark::new(number, commands[number], options, executor);
+                if options.show_benchmark_output() {
+                    benchmark.print_header();
+                }
+                benchmark.report(&result);
+
+                if options.is_reordered() {
+                    result.execution_order = order.iter().position(|&n| n == number);
+                }
+                result.reproduce = Some(self.export_manager.reproduce(number, commands[number]));
+                results[number] = Some(result);
+
+                let finished: Vec<_> = results.iter().flatten().cloned().collect();
+                if let Err(e) = self.export_manager.write_results(&finished, true) {
+                    abort.store(true, AtomicOrdering::Relaxed);
+                    return Err(e);
+                }
+            }
+
+            Ok(())
+        })?;
+
+        self.results = self.merge_duplicates(results);
 
         Ok(())
     }
 
+    /// Combine the results of benchmarks that appear more than once, if requested with
+    /// '--duplicates=merge'. The results are indexed like the commands, including the
+    /// reference command.
+    fn merge_duplicates(&self, mut results: Vec<Option<BenchmarkResult>>) -> Vec<BenchmarkResult> {
+        if self.options.duplicate_commands == DuplicateCommands::Merge {
+            let offset = usize::from(self.options.reference_command.is_some());
+            for group in self.commands.duplicates() {
+                let (first, copies) = group.split_first().unwrap();
+                for copy in copies {
+                    let Some(copy) = results[offset + copy].take() else {
+                        continue;
+                    };
+                    if let Some(ref mut result) = results[offset + first] {
+                        result.measurements.append(copy.measurements);
+                        result.modes = match result.measurements.modes() {
+                            modes if modes.len() > 1 => modes,
+                            _ => vec![],
+                        };
+                        result.run_indices = result.measurements.run_indices();
+                        result.warnings = Reporter::new(self.options).recorded_warnings(result);
+                    }
+                }
+            }
+        }
+        results.into_iter().flatten().collect()
+    }
+
+    /// The command of the given result, as it is displayed in a line of the terminal output
+    /// of which `used_width` characters are taken by other output
+    fn display_name(&self, result: &BenchmarkResult, used_width: usize) -> String {
+        let reference = self
+            .options
+            .reference_command
+            .as_ref()
+            .map(|cmd| Command::new(None, cmd));
+        let command = reference
+            .iter()
+            .chain(self.commands.iter())
+            .find(|command| command.get_name() == result.command);
+
+        match (command, self.options.command_display) {
+            (Some(command), display) => {
+                command_display::display_command(command, display, used_width)
+            }
+            (None, CommandDisplay::Full) => result.command_with_unused_parameters(),
+            (None, _) => command_display::fit_to_terminal(
+                &result.command_with_unused_parameters(),
+                used_width,
+            ),
+        }
+    }
+
     pub fn print_relative_speed_comparison(&self) {
         if self.options.output_style == OutputStyleOption::Disabled {
             return;
         }
 
-        if self.results.len() < 2 {
-            return;
+        // Commands are only compared with the commands of their group (see '--group')
+        let groups = benchmark_result::groups(&self.results);
+        let mut is_first = true;
+        for (group, results) in groups.iter().filter(|(_, results)| results.len() > 1) {
+            if !is_first {
+                println!();
+            }
+            is_first = false;
+            self.print_relative_speed_comparison_of(results, *group);
         }
+    }
 
+    fn print_relative_speed_comparison_of(&self, results: &[BenchmarkResult], group: Option<&str>) {
+        let metric = self.options.relative_metric;
         let reference = self
             .options
             .reference_command
             .as_ref()
-            .map(|_| &self.results[0])
-            .unwrap_or_else(|| relative_speed::fastest_of(&self.results));
+            .map(|_| &results[0])
+            .unwrap_or_else(|| relative_speed::fastest_of(results, metric));
+
+        // The metric is named in the headline, unless it is the wall clock time
+        let headline = |headline: &str| {
+            let headline = match group {
+                Some(group) => format!("{headline} of group '{group}'"),
+                None => headline.to_string(),
+            };
+            match metric.description() {
+                Some(description) => format!(
+                    "{} {}",
+                    headline.bold(),
+                    format!("(by {description})").dimmed()
+                ),
+                None => headline.bold().to_string(),
+            }
+        };
 
         if let Some(annotated_results) = relative_speed::compute_with_check_from_reference(
-            &self.results,
+            results,
             reference,
             self.options.sort_order_speed_comparison,
+            metric,
+            self.options.relative_error,
         ) {
             match self.options.sort_order_speed_comparison {
                 SortOrder::MeanTime => {
-                    println!("{}", "Summary".bold());
+                    println!("{}", headline("Summary"));
 
                     let reference = annotated_results.iter().find(|r| r.is_reference).unwrap();
                     let others = annotated_results.iter().filter(|r| !r.is_reference);
 
                     println!(
                         "  {} ran",
-                        reference.result.command_with_unused_parameters().cyan()
+                        self.display_name(reference.result, "   ran".len()).cyan()
                     );
 
                     for item in others {
                         let stddev = if let Some(stddev) = item.relative_speed_stddev {
-                            format!(" ± {}", format!("{:.2}", stddev).green())
+                            format!(
+                                " {} {}",
+                                glyphs().plus_minus,
+                                format!("{:.2}", stddev).green()
+                            )
                         } else {
                             "".into()
                         };
+                        let equivalent =
+                            self.options.highlight_threshold.is_some_and(|threshold| {
+                                relative_speed::is_equivalent(
+                                    reference.result,
+                                    item.result,
+                                    threshold,
+                                )
+                            });
                         let comparator = match item.relative_ordering {
+                            _ if equivalent => format!(
+                                "    Equivalent ({}{}) to",
+                                format!("{:.2}", item.relative_speed).bold().green(),
+                                stddev
+                            ),
                             Ordering::Less => format!(
                                 "{}{} times slower than",
                                 format!("{:8.2}", item.relative_speed).bold().green(),
@@ -120,30 +455,51 @@ impl<'a> Scheduler<'a> {
                                 stddev
                             ),
                         };
+                        // Probability that the faster of the two commands wins a
+                        // randomly chosen pair of runs
+                        let probability = match item.relative_ordering {
+                            Ordering::Less => 1.0 - item.probability_reference_faster,
+                            Ordering::Equal | Ordering::Greater => {
+                                item.probability_reference_faster
+                            }
+                        };
+                        let probability =
+                            format!("(faster in {:.0}% of run pairs)", 100.0 * probability);
+                        let used_width =
+                            visible_width(&comparator) + probability.chars().count() + 2;
                         println!(
-                            "{} {}",
+                            "{} {} {}",
                             comparator,
-                            &item.result.command_with_unused_parameters().magenta()
+                            self.display_name(item.result, used_width).magenta(),
+                            probability.dimmed()
                         );
                     }
+
+                    self.print_equivalence_groups(&annotated_results);
                 }
                 SortOrder::Command => {
-                    println!("{}", "Relative speed comparison".bold());
+                    println!("{}", headline("Relative speed comparison"));
 
-                    for item in annotated_results {
+                    for item in &annotated_results {
                         println!(
                             "  {}{}  {}",
                             format!("{:10.2}", item.relative_speed).bold().green(),
                             if item.is_reference {
                                 "        ".into()
                             } else if let Some(stddev) = item.relative_speed_stddev {
-                                format!(" ± {}", format!("{stddev:5.2}").green())
+                                format!(
+                                    " {} {}",
+                                    glyphs().plus_minus,
+                                    format!("{stddev:5.2}").green()
+                                )
                             } else {
                                 "        ".into()
                             },
-                            &item.result.command_with_unused_parameters(),
+                            self.display_name(item.result, "  ".len() + 10 + 8 + "  ".len()),
                         );
                     }
+
+                    self.print_equivalence_groups(&annotated_results);
                 }
             }
         } else {
@@ -159,14 +515,290 @@ impl<'a> Scheduler<'a> {
         }
     }
 
+    /// Print the groups of statistically indistinguishable benchmarks (see
+    /// '--highlight-threshold')
+    fn print_equivalence_groups(&self, annotated_results: &[BenchmarkResultWithRelativeSpeed]) {
+        let Some(threshold) = self.options.highlight_threshold else {
+            return;
+        };
+
+        let results: Vec<_> = annotated_results.iter().map(|item| item.result).collect();
+        for group in relative_speed::equivalence_groups(&results, threshold) {
+            if group.len() < 2 {
+                continue;
+            }
+
+            let names: Vec<_> = group
+                .iter()
+                .map(|result| self.display_name(result, 0).cyan().to_string())
+                .collect();
+            let (last, others) = names.split_last().unwrap();
+            println!(
+                "  {} and {} are equivalent {}",
+                others.join(", "),
+                last,
+                format!(
+                    "(means within {}%, overlapping 95% confidence intervals)",
+                    100.0 * threshold
+                )
+                .dimmed()
+            );
+        }
+    }
+
+    /// Print the statistical power of the comparison of each command with the reference (see
+    /// '--power-analysis'): which differences of the mean times can be detected with the
+    /// observed variance and number of runs, and how many runs are needed to detect a
+    /// difference of 1%.
+    pub fn print_power_analysis(&self) {
+        if self.options.output_style == OutputStyleOption::Disabled || !self.options.power_analysis
+        {
+            return;
+        }
+
+        let groups = benchmark_result::groups(&self.results);
+        for (group, results) in groups.iter().filter(|(_, results)| results.len() > 1) {
+            self.print_power_analysis_of(results, *group);
+        }
+    }
+
+    fn print_power_analysis_of(&self, results: &[BenchmarkResult], group: Option<&str>) {
+        let reference = self
+            .options
+            .reference_command
+            .as_ref()
+            .map(|_| &results[0])
+            .unwrap_or_else(|| relative_speed::fastest_of(results, RelativeMetric::WallClock));
+
+        println!();
+        println!(
+            "{} {}",
+            match group {
+                Some(group) => format!("Statistical power of group '{group}'").bold(),
+                None => "Statistical power".bold(),
+            },
+            "(5% significance level, 80% power)".dimmed()
+        );
+        println!("  Compared to {}:", self.display_name(reference, 14).cyan());
+        for result in results {
+            if std::ptr::eq(result, reference) {
+                continue;
+            }
+
+            let analysis = match power_analysis::analyze(reference, result) {
+                Some(analysis) => format!(
+                    ": difference {}, detectable from {}, {} runs needed to detect {:.0}%{}",
+                    format!("{:+.1}%", 100.0 * analysis.observed_difference).bold(),
+                    format!("{:.1}%", 100.0 * analysis.minimal_detectable_difference).green(),
+                    analysis.required_runs,
+                    100.0 * power_analysis::TARGET_DIFFERENCE,
+                    if analysis.is_underpowered() {
+                        format!(" {}", "(underpowered)".yellow())
+                    } else {
+                        "".into()
+                    }
+                ),
+                None => ": not enough runs".to_string(),
+            };
+            println!(
+                "    {}{}",
+                self.display_name(result, 4 + visible_width(&analysis))
+                    .magenta(),
+                analysis
+            );
+        }
+    }
+
+    /// Print a short analysis of each parameter scan: the fastest parameter value and how
+    /// the run time scales with the parameter.
+    pub fn print_parameter_analysis(&self) {
+        if self.options.output_style == OutputStyleOption::Disabled {
+            return;
+        }
+
+        let analysis = parameter_analysis::analyze(&self.results);
+        if analysis.is_empty() {
+            return;
+        }
+
+        println!();
+        println!("{}", "Parameter analysis".bold());
+        for item in analysis {
+            let scaling = match (item.scaling_exponent, item.scaling) {
+                (Some(exponent), Some(scaling)) => format!(
+                    ", run time grows as {}^{} ({})",
+                    item.parameter,
+                    format!("{exponent:.2}").bold().green(),
+                    scaling
+                ),
+                _ => "".into(),
+            };
+            let analysis = format!(
+                ": fastest for {} = {}{}",
+                item.parameter,
+                item.fastest_value.bold(),
+                scaling
+            );
+            let command = match self.options.command_display {
+                CommandDisplay::Full => item.command.clone(),
+                _ => command_display::fit_to_terminal(&item.command, 2 + visible_width(&analysis)),
+            };
+            println!("  {}{}", command.cyan(), analysis);
+        }
+    }
+
+    /// Report results which have not been measured by this scheduler (see 'hyperfine
+    /// analyze'), such that they can be compared and exported like measured ones
+    pub fn report_results(&mut self, mut results: Vec<BenchmarkResult>) -> Result<()> {
+        let reporter = Reporter::new(self.options);
+        for (number, result) in results.iter_mut().enumerate() {
+            if self.options.show_benchmark_output() {
+                reporter.print_header(number, &Command::new(None, &result.command));
+            }
+            reporter.report(result);
+            result.warnings = reporter.recorded_warnings(result);
+        }
+        self.export_manager.write_results(&results, true)?;
+        self.results = results;
+        Ok(())
+    }
+
     pub fn final_export(&self) -> Result<()> {
         self.export_manager.write_results(&self.results, false)
     }
+
+    /// A description of the commands that are significantly slower than the reference
+    /// command (or the first command of their group, if there is no reference), if there
+    /// are any
+    fn regression(&self) -> Option<String> {
+        let regressions: Vec<_> = benchmark_result::groups(&self.results)
+            .iter()
+            .filter(|(_, results)| results.len() > 1)
+            .filter_map(|(_, results)| self.regression_of(results))
+            .collect();
+
+        if regressions.is_empty() {
+            None
+        } else {
+            Some(format!("Regression detected: {}", regressions.join("; ")))
+        }
+    }
+
+    fn regression_of(&self, results: &[BenchmarkResult]) -> Option<String> {
+        // The reference command (if any) is always the first one
+        let reference = &results[0];
+        let regressions: Vec<_> = relative_speed::compute_with_check_from_reference(
+            results,
+            reference,
+            SortOrder::Command,
+            self.options.relative_metric,
+            self.options.relative_error,
+        )
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|item| {
+            item.relative_ordering == Ordering::Greater
+                && item.relative_speed - item.relative_speed_stddev.unwrap_or(0.0) > 1.0
+        })
+        .map(|item| {
+            format!(
+                "'{}' is {:.2} times slower",
+                item.result.command_with_unused_parameters(),
+                item.relative_speed
+            )
+        })
+        .collect();
+
+        if regressions.is_empty() {
+            None
+        } else {
+            Some(format!(
+                "{} than '{}'",
+                regressions.join(", "),
+                reference.command_with_unused_parameters()
+            ))
+        }
+    }
+
+    /// Signal the end of the session as requested with '--notify'
+    pub fn notify(&self) {
+        let notify = self.options.notify;
+        if !notify.bell && !notify.desktop {
+            return;
+        }
+
+        let message = self
+            .regression()
+            .unwrap_or_else(|| match self.results.len() {
+                1 => "1 benchmark has been performed".to_string(),
+                n => format!("{n} benchmarks have been performed"),
+            });
+        notification::notify(notify, "hyperfine: session finished", &message);
+    }
+
+    /// Check the results for the conditions given by '--fail-on'
+    pub fn check_failure_conditions(&self) -> Result<()> {
+        if self.options.fail_on.regression {
+            if let Some(regression) = self.regression() {
+                return Err(anyhow!(regression)).exit_code(ExitCode::RegressionDetected);
+            }
+        }
+
+        if self.options.fail_on.outliers {
+            let commands_with_outliers: Vec<_> = self
+                .results
+                .iter()
+                .filter(|result| {
+                    result
+                        .measurements
+                        .modified_zscores()
+                        .iter()
+                        .any(|score| score.abs() > OUTLIER_THRESHOLD)
+                })
+                .map(|result| format!("'{}'", result.command_with_unused_parameters()))
+                .collect();
+
+            if !commands_with_outliers.is_empty() {
+                return Err(anyhow!(
+                    "Statistical outliers have been detected in the benchmarks of {}",
+                    commands_with_outliers.join(", ")
+                ))
+                .exit_code(ExitCode::OutliersDetected);
+            }
+        }
+
+        if self.options.fail_on.warnings {
+            let warnings: Vec<_> = self
+                .results
+                .iter()
+                .flat_map(|result| {
+                    result.warnings.iter().map(move |warning| {
+                        format!(
+                            "'{}' ({})",
+                            result.command_with_unused_parameters(),
+                            warning.kind
+                        )
+                    })
+                })
+                .collect();
+
+            if !warnings.is_empty() {
+                return Err(anyhow!(
+                    "Warnings have been reported in strict mode: {}",
+                    warnings.join(", ")
+                ))
+                .exit_code(ExitCode::WarningsReported);
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 fn generate_results(args: &[&'static str]) -> Result<Vec<BenchmarkResult>> {
     use crate::cli::get_cli_arguments;
+    use crate::command::CommandList;
 
     let args = ["hyperfine", "--debug-mode", "--style=none"]
         .iter()
@@ -176,12 +808,9 @@ fn generate_results(args: &[&'static str]) -> Result<Vec<BenchmarkResult>> {
 
     assert_eq!(options.executor_kind, ExecutorKind::Mock(None));
 
-    let commands = Commands::from_cli_arguments(&cli_arguments)?;
-    let export_manager = ExportManager::from_cli_arguments(
-        &cli_arguments,
-        options.time_unit,
-        options.sort_order_exports,
-    )?;
+    let command_list = CommandList::default();
+    let commands = Commands::from_cli_arguments(&cli_arguments, &command_list)?;
+    let export_manager = ExportManager::from_cli_arguments(&cli_arguments, &options)?;
 
     options.validate_against_command_list(&commands)?;
 
@@ -208,6 +837,8 @@ fn scheduler_basic() -> Result<()> {
           peak_memory_usage:
             value: 0
             unit: byte
+          minor_page_faults: 0
+          major_page_faults: 0
           exit_code: 0
         - time_wall_clock:
             value: 0.123
@@ -221,7 +852,14 @@ fn scheduler_basic() -> Result<()> {
           peak_memory_usage:
             value: 0
             unit: byte
+          minor_page_faults: 0
+          major_page_faults: 0
           exit_code: 0
+      run_indices:
+        min: 0
+        median: 0
+        max: 1
+      reproduce: "hyperfine --runs=2 --style=none --debug-mode 'sleep 0.123'"
     - command: sleep 0.456
       measurements:
         - time_wall_clock:
@@ -236,6 +874,8 @@ fn scheduler_basic() -> Result<()> {
           peak_memory_usage:
             value: 0
             unit: byte
+          minor_page_faults: 0
+          major_page_faults: 0
           exit_code: 0
         - time_wall_clock:
             value: 0.456
@@ -249,7 +889,14 @@ fn scheduler_basic() -> Result<()> {
           peak_memory_usage:
             value: 0
             unit: byte
+          minor_page_faults: 0
+          major_page_faults: 0
           exit_code: 0
+      run_indices:
+        min: 0
+        median: 0
+        max: 1
+      reproduce: "hyperfine --runs=2 --style=none --debug-mode 'sleep 0.456'"
     "#);
 
     Ok(())
@@ -0,0 +1,92 @@
+//! A tiny helper that times a command where it runs, e.g. inside of a container or on a remote
+//! host, and prints a record of the measurement that hyperfine reads (see '--shim'):
+//!
+//!     hyperfine --shim 'docker exec bench /usr/local/bin/hyperfine-shim' 'make -j8'
+//!
+//! The record is the last line of the error output. The shim exits with the exit code of the
+//! command, or with 128 plus the number of the signal that has terminated it. It only depends
+//! on the standard library (and libc), such that it can be built as a static binary that is
+//! copied into the container or onto the host, e.g. with
+//! 'cargo build --release --bin hyperfine-shim --target x86_64-unknown-linux-musl'.
+
+#[allow(dead_code)]
+#[path = "../shim.rs"]
+mod shim;
+
+use std::env;
+use std::process::{self, Command, ExitStatus};
+use std::time::Instant;
+
+use shim::ShimRecord;
+
+fn main() {
+    let mut arguments = env::args_os().skip(1);
+    let Some(program) = arguments.next() else {
+        eprintln!("Usage: hyperfine-shim <COMMAND> [ARGUMENTS]...");
+        process::exit(2);
+    };
+
+    let start = Instant::now();
+    let status = match Command::new(&program).args(arguments).status() {
+        Ok(status) => status,
+        Err(e) => {
+            eprintln!("hyperfine-shim: could not run {program:?}: {e}");
+            process::exit(127);
+        }
+    };
+    let wall_clock = start.elapsed().as_secs_f64();
+
+    let (user, system, peak_memory) = children_usage();
+    eprintln!(
+        "{}",
+        ShimRecord {
+            wall_clock,
+            user,
+            system,
+            peak_memory,
+        }
+        .format()
+    );
+    process::exit(exit_code(status));
+}
+
+/// User time, system time (in seconds) and peak memory usage (in bytes) of the command
+#[cfg(unix)]
+fn children_usage() -> (f64, f64, u64) {
+    // SAFETY: getrusage only writes to the given struct
+    let usage = unsafe {
+        let mut usage = std::mem::zeroed::<libc::rusage>();
+        libc::getrusage(libc::RUSAGE_CHILDREN, &mut usage);
+        usage
+    };
+    let seconds = |time: libc::timeval| time.tv_sec as f64 + time.tv_usec as f64 * 1e-6;
+
+    // The maximum resident set size is reported in bytes on macOS, and in KiB elsewhere
+    let peak_memory = if cfg!(target_os = "macos") {
+        usage.ru_maxrss as u64
+    } else {
+        usage.ru_maxrss as u64 * 1024
+    };
+    (
+        seconds(usage.ru_utime),
+        seconds(usage.ru_stime),
+        peak_memory,
+    )
+}
+
+/// Only the wall clock time is measured on other platforms
+#[cfg(not(unix))]
+fn children_usage() -> (f64, f64, u64) {
+    (0.0, 0.0, 0)
+}
+
+fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    status.code().unwrap_or(1)
+}
@@ -0,0 +1,287 @@
+//! Detection of the optional measurement capabilities of the system (`--capabilities`).
+//! Requested measurements that are not available are replaced by the best available
+//! alternative (or left out) with a warning, instead of aborting the session.
+
+use std::time::Instant;
+
+use colored::*;
+
+use crate::benchmark::sampler;
+use crate::export::clipboard;
+use crate::options::{Options, TimerKind};
+use crate::output::notification;
+use crate::util::program::find_program;
+
+/// Whether a capability is available, with details or the reason why it is not
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Availability {
+    Available(String),
+    Unavailable(String),
+}
+
+use Availability::{Available, Unavailable};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capability {
+    pub name: &'static str,
+    pub availability: Availability,
+}
+
+/// Probe all optional capabilities
+pub fn probe() -> Vec<Capability> {
+    let capability = |name, availability| Capability { name, availability };
+    vec![
+        capability("Timer 'monotonic'", Available(monotonic_resolution())),
+        capability("Timer 'monotonic-raw'", monotonic_raw()),
+        capability(
+            "Timer 'task-clock'",
+            Available("CPU time of the process".into()),
+        ),
+        capability("Timer 'tsc'", tsc()),
+        capability("CPU times and peak memory usage", resource_usage()),
+        capability("Physical memory footprint", memory_footprint()),
+        capability("Private bytes and working set", process_memory_counters()),
+        capability("Process count", process_count()),
+        capability("Memory limit (--limit-memory)", memory_limit()),
+        capability("GPU metrics (--sample-gpu)", gpu()),
+        capability("System call counts (--count-syscalls)", syscall_counting()),
+        capability("Turbo boost control (--disable-turbo)", turbo_control()),
+        capability("SMT control (--disable-smt)", smt_control()),
+        capability("Attaching to processes (--attach)", attaching()),
+        capability("Pausing processes (--pause-processes)", pausing()),
+        capability("Desktop notifications (--notify)", desktop_notifications()),
+        capability("Clipboard (--export-clipboard)", clipboard()),
+    ]
+}
+
+pub fn print(capabilities: &[Capability]) {
+    let width = capabilities
+        .iter()
+        .map(|capability| capability.name.len())
+        .max()
+        .unwrap_or(0);
+    for capability in capabilities {
+        let status = match &capability.availability {
+            Available(details) => format!("{} {}", "available".green(), details.dimmed()),
+            Unavailable(reason) => format!("{} {}", "unavailable".red(), reason),
+        };
+        println!("{:width$}  {status}", capability.name);
+    }
+}
+
+/// Replace the requested measurements which are not available on this system by the best
+/// available alternative, or leave them out, with a warning
+pub fn select_available_probes(options: &mut Options) {
+    let warn = |message: String| eprintln!("{} {message}", "Warning:".yellow());
+
+    if options.timer == TimerKind::Tsc {
+        if let Unavailable(reason) = tsc() {
+            warn(format!(
+                "The timer 'tsc' is not available ({reason}), the 'monotonic' timer is used \
+                 instead."
+            ));
+            options.timer = TimerKind::Monotonic;
+        }
+    }
+    if options.sample_gpu {
+        if let Unavailable(reason) = gpu() {
+            warn(format!("GPU metrics are not sampled: {reason}"));
+            options.sample_gpu = false;
+        }
+    }
+    if options.syscall_counting.is_some() {
+        if let Unavailable(reason) = syscall_counting() {
+            warn(format!("System calls are not counted: {reason}"));
+            options.syscall_counting = None;
+        }
+    }
+}
+
+/// The smallest difference between two readings of the monotonic clock
+fn monotonic_resolution() -> String {
+    let mut resolution = u128::MAX;
+    for _ in 0..1000 {
+        let start = Instant::now();
+        let mut now = Instant::now();
+        while now == start {
+            now = Instant::now();
+        }
+        resolution = resolution.min((now - start).as_nanos());
+    }
+    format!("resolution of {resolution} ns")
+}
+
+fn monotonic_raw() -> Availability {
+    if cfg!(any(target_os = "linux", target_os = "android")) {
+        Available("CLOCK_MONOTONIC_RAW".into())
+    } else {
+        Unavailable("only supported on Linux".into())
+    }
+}
+
+fn tsc() -> Availability {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if crate::timer::has_invariant_tsc() {
+            Available("invariant time stamp counter".into())
+        } else {
+            Unavailable("the time stamp counter of the CPU is not invariant".into())
+        }
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        Unavailable("only supported on x86-64".into())
+    }
+}
+
+fn resource_usage() -> Availability {
+    if cfg!(windows) {
+        Available("job objects".into())
+    } else {
+        Available("wait4".into())
+    }
+}
+
+fn memory_footprint() -> Availability {
+    if cfg!(target_os = "macos") {
+        Available("proc_pid_rusage".into())
+    } else {
+        Unavailable("only available on macOS".into())
+    }
+}
+
+fn process_memory_counters() -> Availability {
+    if cfg!(windows) {
+        Available("GetProcessMemoryInfo".into())
+    } else {
+        Unavailable("only available on Windows".into())
+    }
+}
+
+fn process_count() -> Availability {
+    if cfg!(windows) {
+        Available("job objects".into())
+    } else {
+        Unavailable("only available on Windows".into())
+    }
+}
+
+fn memory_limit() -> Availability {
+    if cfg!(windows) {
+        Available("job objects".into())
+    } else {
+        Available("RLIMIT_AS".into())
+    }
+}
+
+fn gpu() -> Availability {
+    match sampler::probe_gpus() {
+        Ok(1) => Available("1 NVIDIA GPU".into()),
+        Ok(count) => Available(format!("{count} NVIDIA GPUs")),
+        Err(e) => Unavailable(format!("{e:#}")),
+    }
+}
+
+fn syscall_counting() -> Availability {
+    if !cfg!(target_os = "linux") {
+        return Unavailable("only supported on Linux".into());
+    }
+    match find_program("strace") {
+        Some(path) => Available(path.display().to_string()),
+        None => Unavailable("'strace' is not installed".into()),
+    }
+}
+
+/// Whether the given sysfs knob (relative to '/sys') can be written
+#[cfg(target_os = "linux")]
+fn sysfs_knob(knob: &str) -> Availability {
+    use std::fs::OpenOptions;
+    use std::io::ErrorKind;
+    use std::path::Path;
+
+    let path = Path::new("/sys").join(knob);
+    match OpenOptions::new().write(true).open(&path) {
+        Ok(_) => Available(path.display().to_string()),
+        Err(e) if e.kind() == ErrorKind::PermissionDenied => {
+            Unavailable("requires root privileges".into())
+        }
+        Err(e) => Unavailable(format!("'{}': {e}", path.display())),
+    }
+}
+
+fn turbo_control() -> Availability {
+    #[cfg(target_os = "linux")]
+    {
+        use crate::util::cpu_state::{CPUFREQ_BOOST, INTEL_NO_TURBO};
+
+        [INTEL_NO_TURBO, CPUFREQ_BOOST]
+            .iter()
+            .find(|knob| std::path::Path::new("/sys").join(knob).exists())
+            .map_or_else(
+                || Unavailable("the CPU frequency driver does not support it".into()),
+                |knob| sysfs_knob(knob),
+            )
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Unavailable("only supported on Linux".into())
+    }
+}
+
+fn smt_control() -> Availability {
+    #[cfg(target_os = "linux")]
+    {
+        sysfs_knob(crate::util::cpu_state::SMT_CONTROL)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Unavailable("only supported on Linux".into())
+    }
+}
+
+fn attaching() -> Availability {
+    if !cfg!(target_os = "linux") {
+        Unavailable("only supported on Linux".into())
+    } else if std::path::Path::new("/proc/self/stat").exists() {
+        Available("/proc".into())
+    } else {
+        Unavailable("'/proc' is not mounted".into())
+    }
+}
+
+fn pausing() -> Availability {
+    if cfg!(unix) {
+        Available("SIGSTOP and SIGCONT".into())
+    } else {
+        Unavailable("only supported on Unix systems".into())
+    }
+}
+
+fn desktop_notifications() -> Availability {
+    let program = notification::desktop_notification_program();
+    match find_program(&program) {
+        Some(_) => Available(program),
+        None => Unavailable(format!("'{program}' is not installed")),
+    }
+}
+
+fn clipboard() -> Availability {
+    let programs: Vec<_> = clipboard::clipboard_programs()
+        .into_iter()
+        .map(|(program, _)| program)
+        .collect();
+    match programs
+        .iter()
+        .find(|program| find_program(program).is_some())
+    {
+        Some(program) => Available(program.to_string()),
+        None => Unavailable(format!(
+            "none of {} is installed",
+            programs
+                .iter()
+                .map(|program| format!("'{program}'"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
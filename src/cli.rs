@@ -1,10 +1,15 @@
 use std::ffi::OsString;
 
 use clap::{
-    builder::NonEmptyStringValueParser, crate_version, Arg, ArgAction, ArgMatches, Command,
-    ValueHint,
+    builder::NonEmptyStringValueParser, crate_version, Arg, ArgAction, ArgGroup, ArgMatches,
+    Command, ValueHint,
 };
 
+/// The formats that can be selected with '--export FORMAT:TARGET'
+const EXPORT_FORMATS: &[&str] = &[
+    "asciidoc", "bencher", "csv", "json", "markdown", "orgmode", "otlp", "runs",
+];
+
 pub fn get_cli_arguments<'a, I, T>(args: I) -> ArgMatches
 where
     I: IntoIterator<Item = T>,
@@ -15,7 +20,7 @@ where
 }
 
 /// Build the clap command for parsing command line arguments
-fn build_command() -> Command {
+pub fn build_command() -> Command {
     Command::new("hyperfine")
         .version(crate_version!())
         .next_line_help(true)
@@ -23,18 +28,122 @@ fn build_command() -> Command {
         .about("A command-line benchmarking tool.")
         .help_expected(true)
         .max_term_width(80)
+        .args_conflicts_with_subcommands(true)
+        .subcommand_negates_reqs(true)
+        .disable_help_subcommand(true)
         .arg(
             Arg::new("command")
                 .help("The command to benchmark. This can be the name of an executable, a command \
                        line like \"grep -i todo\" or a shell command like \"sleep 0.5 && echo test\". \
                        The latter is only available if the shell is not explicitly disabled via \
                        '--shell=none'. If multiple commands are given, hyperfine will show a \
-                       comparison of the respective runtimes.")
-                .required(true)
+                       comparison of the respective runtimes.\nEach run can be identified via \
+                       the environment variables HYPERFINE_BENCHMARK_NAME, HYPERFINE_ITERATION, \
+                       HYPERFINE_RUN_NUMBER and HYPERFINE_RUN_SEED. The latter is a number that \
+                       only depends on the command and the iteration, which can be used to make \
+                       randomized workloads reproducible. The placeholders {run} and \
+                       {total_runs} are replaced by the index of the run ('warmup-0', \
+                       'warmup-1', ... for warmup runs) and the total number of runs, e.g. \
+                       'myprog --out out_{run}.bin'. The latter requires '--runs'. The \
+                       placeholder {prev_run} is replaced by the index of the run that was \
+                       executed right before the current one (empty for the first run), e.g. \
+                       'myprog --in out_{prev_run}.bin --out out_{run}.bin' (see '--stateful').")
+                .required_unless_present_any(["commands-from-file", "script", "attach"])
                 .action(ArgAction::Append)
                 .value_hint(ValueHint::CommandString)
                 .value_parser(NonEmptyStringValueParser::new()),
         )
+        .arg(
+            Arg::new("commands-from-file")
+                .long("commands-from-file")
+                .action(ArgAction::Set)
+                .value_name("FILE")
+                .value_hint(ValueHint::FilePath)
+                .help("Read additional commands to benchmark from FILE, one command per line. \
+                       Use '-' to read the commands from standard input. A line can optionally \
+                       start with a name for the command, separated from the command by a tab \
+                       character. Empty lines and lines starting with '#' are ignored. The \
+                       commands are benchmarked after the ones given on the command line."),
+        )
+        .arg(
+            Arg::new("script")
+                .long("script")
+                .action(ArgAction::Append)
+                .num_args(0..=1)
+                .default_missing_value("-")
+                .value_name("FILE")
+                .value_hint(ValueHint::FilePath)
+                .conflicts_with("no-shell")
+                .help("Benchmark the (multi-line) shell script in FILE, or the one read from \
+                       standard input if FILE is '-' or omitted. The script is passed to the \
+                       shell as a whole (like a single command) and benchmarked as one unit, \
+                       including the correction for the shell spawning time. The option can be \
+                       given multiple times; the scripts are benchmarked after the other \
+                       commands and named after their files."),
+        )
+        .arg(
+            Arg::new("hosts")
+                .long("hosts")
+                .action(ArgAction::Set)
+                .value_name("FILE")
+                .value_hint(ValueHint::FilePath)
+                .conflicts_with_all([
+                    "attach",
+                    "script",
+                    "profile-with",
+                    "show-output",
+                    "export-asciidoc",
+                    "export-bencher",
+                    "export",
+                    "export-clipboard",
+                    "export-csv",
+                    "export-markdown",
+                    "export-orgmode",
+                    "export-otlp",
+                    "export-runs",
+                    "sign-key",
+                    "output-dir",
+                ])
+                .help("Run the benchmarks on several machines. FILE contains one SSH destination \
+                       (e.g. 'user@host') per line; empty lines and lines starting with '#' are \
+                       ignored. hyperfine is run on each host via 'ssh' with the options that \
+                       define the benchmarks and how they are measured (it needs to be \
+                       installed there, and paths refer to files on the hosts, except for the \
+                       file of '--commands-from-file', which is read locally). Options for the \
+                       exports, the output and the session only apply locally. The results \
+                       are shown in a comparison table across hosts, and '--export-json' \
+                       writes a combined export that is keyed by host."),
+        )
+        .arg(
+            Arg::new("attach")
+                .long("attach")
+                .action(ArgAction::Set)
+                .value_name("PID")
+                .conflicts_with_all([
+                    "command",
+                    "commands-from-file",
+                    "script",
+                    "parameter-scan",
+                    "parameter-list",
+                    "parameter-expr",
+                    "cache",
+                ])
+                .help("Benchmark the already running process with the given PID instead of \
+                       running a command. Each run observes the process for the time given by \
+                       '--attach-duration' and records the CPU time that it consumed, its peak \
+                       memory usage and its page faults. This is useful to benchmark server \
+                       processes under an externally driven load. Use '--command-name' to \
+                       label the benchmark. Only supported on Linux."),
+        )
+        .arg(
+            Arg::new("attach-duration")
+                .long("attach-duration")
+                .action(ArgAction::Set)
+                .value_name("DURATION")
+                .requires("attach")
+                .help("For how long the process given to '--attach' is observed in each run, \
+                       e.g. '500ms' or '2s'. Default: 1s."),
+        )
         .arg(
             Arg::new("warmup")
                 .long("warmup")
@@ -46,31 +155,251 @@ fn build_command() -> Command {
                      to fill (disk) caches for I/O-heavy programs.",
                 ),
         )
+        .arg(
+            Arg::new("discard-first")
+                .long("discard-first")
+                .value_name("NUM")
+                .action(ArgAction::Set)
+                .help(
+                    "Perform NUM additional runs at the start of each benchmark that are \
+                     recorded, but excluded from the statistics. Unlike warmup runs, these \
+                     runs show up in the JSON export, where they are marked as discarded. \
+                     This keeps cold-start timings visible without affecting the mean.",
+                ),
+        )
+        .arg(
+            Arg::new("trim")
+                .long("trim")
+                .value_name("PERCENT")
+                .action(ArgAction::Set)
+                .help(
+                    "Exclude the fastest and the slowest PERCENT of the runs (e.g. '--trim 5%') \
+                     from the mean and standard deviation. The trimmed statistics are then used \
+                     for the summary and all exports. This makes the results robust against \
+                     outliers due to a noisy system, without having to repeat the benchmark.",
+                ),
+        )
         .arg(
             Arg::new("min-runs")
                 .long("min-runs")
                 .short('m')
-                .action(ArgAction::Set)
+                .action(ArgAction::Append)
+                .num_args(1)
                 .value_name("NUM")
-                .help("Perform at least NUM runs for each command (default: 10)."),
+                .help("Perform at least NUM runs for each command (default: 10). This option can \
+                       be specified once for all commands or multiple times, once for each \
+                       command (e.g. to run a slow reference command less often)."),
         )
         .arg(
             Arg::new("max-runs")
                 .long("max-runs")
                 .short('M')
+                .action(ArgAction::Append)
+                .num_args(1)
+                .value_name("NUM")
+                .help("Perform at most NUM runs for each command. By default, there is no limit. \
+                       This option can be specified once for all commands or multiple times, \
+                       once for each command."),
+        )
+        .arg(
+            Arg::new("successful-runs")
+                .long("successful-runs")
+                .action(ArgAction::Set)
+                .value_name("NUM")
+                .requires("ignore-failure")
+                .conflicts_with_all(["runs", "min-runs", "max-runs"])
+                .help("Together with '--ignore-failure', repeat failing runs until NUM runs of \
+                       each command have succeeded. The failing runs are marked as failed \
+                       attempts in the exports and excluded from the statistics, such that \
+                       they are always based on NUM runs. See also '--max-attempts'."),
+        )
+        .arg(
+            Arg::new("max-attempts")
+                .long("max-attempts")
                 .action(ArgAction::Set)
                 .value_name("NUM")
-                .help("Perform at most NUM runs for each command. By default, there is no limit."),
+                .requires("successful-runs")
+                .help("The maximum number of runs of each command, including failing ones, \
+                       until the runs given by '--successful-runs' have succeeded. The \
+                       benchmark fails if this is exceeded (default: ten times the number \
+                       of successful runs)."),
         )
         .arg(
             Arg::new("runs")
                 .long("runs")
                 .conflicts_with_all(["max-runs", "min-runs"])
                 .short('r')
-                .action(ArgAction::Set)
+                .action(ArgAction::Append)
+                .num_args(1)
                 .value_name("NUM")
                 .help("Perform exactly NUM runs for each command. If this option is not specified, \
-                       hyperfine automatically determines the number of runs."),
+                       hyperfine automatically determines the number of runs. This option can be \
+                       specified once for all commands or multiple times, once for each command \
+                       (including a potential reference), in the order in which they are given."),
+        )
+        .arg(
+            Arg::new("just-run")
+                .long("just-run")
+                .action(ArgAction::Set)
+                .value_name("NUM")
+                .conflicts_with_all([
+                    "runs",
+                    "min-runs",
+                    "max-runs",
+                    "successful-runs",
+                    "warmup",
+                    "discard-first",
+                    "jobs",
+                    "reference",
+                    "setup",
+                    "prepare",
+                    "conclude",
+                    "cleanup",
+                    "teardown",
+                    "attach",
+                    "hosts",
+                    "profile-with",
+                    "cache",
+                    "export-asciidoc",
+                    "export-bencher",
+                    "export",
+                    "export-clipboard",
+                    "export-csv",
+                    "export-json",
+                    "export-markdown",
+                    "export-orgmode",
+                    "export-otlp",
+                    "export-runs",
+                    "output-dir",
+                ])
+                .help("Just run each command NUM times and print the wall clock time of each run \
+                       (in seconds, one per line), without warmup, shell spawning time \
+                       correction or statistics. This turns hyperfine into a simple process \
+                       launcher whose raw numbers can be processed by other tools."),
+        )
+        .arg(
+            Arg::new("just-run-format")
+                .long("just-run-format")
+                .action(ArgAction::Set)
+                .value_name("FORMAT")
+                .value_parser(["plain", "jsonl"])
+                .requires("just-run")
+                .help("Format of the times printed by '--just-run': 'plain' (the default) prints \
+                       the wall clock time in seconds, 'jsonl' prints a JSON object per run with \
+                       the command, the run number, the wall clock, user and system times and \
+                       the exit code."),
+        )
+        .arg(
+            Arg::new("abort-if-slower-than")
+                .long("abort-if-slower-than")
+                .action(ArgAction::Set)
+                .value_name("DURATION")
+                .help("Skip the remaining runs of a command if its first run takes longer than \
+                       DURATION, e.g. '10s'. The command is reported (and exported) as too slow, \
+                       with the time of the single run. This prevents pathological variants in \
+                       a parameter scan from consuming the whole time budget."),
+        )
+        .arg(
+            Arg::new("benchmark-deadline")
+                .long("benchmark-deadline")
+                .action(ArgAction::Set)
+                .value_name("DURATION")
+                .help("Do not start any more runs of a command once its benchmark has been running \
+                       for longer than DURATION, e.g. '5m'. The statistics are computed from the \
+                       runs performed so far, the result is marked as incomplete in the exports \
+                       and the session continues with the next command."),
+        )
+        .arg(
+            Arg::new("auto-timeout")
+                .long("auto-timeout")
+                .action(ArgAction::Set)
+                .value_name("FACTOR")
+                .conflicts_with_all(["jobs", "attach"])
+                .help("Kill the runs of each command that take longer than FACTOR times the \
+                       mean time of the fastest command benchmarked so far, e.g. '10x'. The \
+                       runs of the first command are not limited. When a run is killed, the \
+                       remaining runs of the command are skipped and the command is reported \
+                       (and exported) as timed out, with a mean that is a lower bound. This \
+                       prevents pathological variants from running forever, without choosing \
+                       an absolute timeout by hand."),
+        )
+        .arg(
+            Arg::new("abort-on-noise")
+                .long("abort-on-noise")
+                .action(ArgAction::Set)
+                .value_name("PERCENT")
+                .help("Skip the remaining runs of a command if the standard deviation of its run \
+                       times exceeds PERCENT of the mean (e.g. '25%') after at least 5 runs. \
+                       The result is reported (and exported) as too noisy, which usually means \
+                       that the measurements were disturbed by other processes."),
+        )
+        .arg(
+            Arg::new("start-at")
+                .long("start-at")
+                .action(ArgAction::Set)
+                .value_name("TIME")
+                .conflicts_with("start-after")
+                .help("Wait until the given local time of day (HH:MM or HH:MM:SS, e.g. '02:00') \
+                       before starting the benchmarks, e.g. to measure at a quiet time on a \
+                       shared machine. If the time has already passed today, the session starts \
+                       at that time tomorrow. A countdown is shown while waiting."),
+        )
+        .arg(
+            Arg::new("start-after")
+                .long("start-after")
+                .action(ArgAction::Set)
+                .value_name("DURATION")
+                .help("Wait for the given DURATION (e.g. '30m' or '2h') before starting the \
+                       benchmarks. A countdown is shown while waiting."),
+        )
+        .arg(
+            Arg::new("lock")
+                .long("lock")
+                .action(ArgAction::Set)
+                .value_name("PATH")
+                .value_hint(ValueHint::FilePath)
+                .help("The lock file that prevents concurrent benchmark sessions on the same \
+                       machine, which would disturb each other's measurements. A session waits \
+                       until the sessions that hold the lock have finished (see \
+                       '--fail-if-locked'). Sessions started by the benchmarked commands do \
+                       not wait for the lock of their parent session. By default, the file \
+                       'hyperfine.lock' in $XDG_RUNTIME_DIR (or in the temporary directory) \
+                       is used."),
+        )
+        .arg(
+            Arg::new("fail-if-locked")
+                .long("fail-if-locked")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("no-lock")
+                .help("Fail right away instead of waiting if another session holds the lock \
+                       file (see '--lock')."),
+        )
+        .arg(
+            Arg::new("no-lock")
+                .long("no-lock")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("lock")
+                .help("Do not take the lock file (see '--lock'), e.g. to deliberately run \
+                       several sessions at the same time."),
+        )
+        .arg(
+            Arg::new("confirm-after")
+                .long("confirm-after")
+                .action(ArgAction::Set)
+                .value_name("DURATION")
+                .help("Ask for confirmation if the whole session is estimated to take longer than \
+                       DURATION (default: 1h), e.g. '30m' or '2h'. The estimate is made after the \
+                       initial run of the first command, assuming that all commands take equally \
+                       long. The question is only asked if stdin is a terminal and the \
+                       benchmarks are not run in parallel."),
+        )
+        .arg(
+            Arg::new("yes")
+                .long("yes")
+                .short('y')
+                .action(ArgAction::SetTrue)
+                .help("Do not ask for confirmation before long benchmark sessions (see \
+                       '--confirm-after')."),
         )
         .arg(
             Arg::new("jobs")
@@ -78,7 +407,12 @@ fn build_command() -> Command {
                 .short('j')
                 .action(ArgAction::Set)
                 .value_name("JOBS")
-                .help("Run JOBS instances simultaneously (default: 1)."),
+                .help(
+                    "Run up to JOBS different benchmarks concurrently (default: 1). On Linux, \
+                     each job is pinned to a disjoint set of CPU cores, and the number of jobs \
+                     is limited to the number of available cores. The runs of a single \
+                     benchmark are always executed sequentially.",
+                ),
         )
         .arg(
             Arg::new("setup")
@@ -117,7 +451,9 @@ fn build_command() -> Command {
                      clearing disk caches, for example.\nThe --prepare option can \
                      be specified once for all commands or multiple times, once for \
                      each command. In the latter case, each preparation command will \
-                     be run prior to the corresponding benchmark command.",
+                     be run prior to the corresponding benchmark command. The preparation \
+                     command receives the same HYPERFINE_* environment variables and \
+                     {run}/{total_runs} placeholders as the benchmark run it prepares.",
                 ),
         )
         .arg(
@@ -134,23 +470,87 @@ fn build_command() -> Command {
                      for example.\nThe --conclude option can be specified once for all \
                      commands or multiple times, once for each command. In the latter case, \
                      each conclude command will be run after the corresponding benchmark \
-                     command.",
+                     command. The {run} and {total_runs} placeholders can be used as in the \
+                     benchmarked command.",
+                ),
+        )
+        .arg(
+            Arg::new("stateful")
+                .long("stateful")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Declare that each run depends on the state left behind by the previous \
+                     one, e.g. to benchmark an incremental compiler intentionally. This is \
+                     implied if the benchmarked command uses the {prev_run} placeholder. The \
+                     runs of stateful benchmarks are executed strictly in order (including \
+                     the warmup runs), stateful benchmarks can not be run in parallel \
+                     ('--jobs'), and they are marked as stateful in the exports.",
                 ),
         )
         .arg(
             Arg::new("cleanup")
                 .long("cleanup")
                 .short('c')
-                .action(ArgAction::Set)
+                .action(ArgAction::Append)
+                .num_args(1)
                 .value_name("CMD")
                 .value_hint(ValueHint::CommandString)
                 .help(
                     "Execute CMD after the completion of all benchmarking \
                      runs for each individual command to be benchmarked. \
                      This is useful if the commands to be benchmarked produce \
-                     artifacts that need to be cleaned up."
+                     artifacts that need to be cleaned up.\nThe --cleanup option can be \
+                     specified once for all commands or multiple times, once for each \
+                     command. In the latter case, each cleanup command will be run after \
+                     all runs of the corresponding benchmark command."
+                ),
+        )
+        .arg(
+            Arg::new("teardown")
+                .long("teardown")
+                .action(ArgAction::Set)
+                .value_name("CMD")
+                .value_hint(ValueHint::CommandString)
+                .help(
+                    "Execute CMD exactly once after all runs of each individual benchmark, \
+                     even if the benchmark failed or was interrupted with Ctrl-C. The \
+                     command is only executed if the '--setup' command (if any) succeeded. \
+                     In contrast to '--cleanup', this can be used to reliably release \
+                     resources such as temporary databases."
+                ),
+        )
+        .arg(
+            Arg::new("profile-with")
+                .long("profile-with")
+                .action(ArgAction::Set)
+                .value_name("CMD")
+                .value_hint(ValueHint::CommandString)
+                .help(
+                    "Perform extra runs of each command under the profiler CMD, after the timing \
+                     runs. The benchmarked command is appended to CMD, and the placeholder \
+                     {output} is replaced by the path of the profile, e.g. \
+                     --profile-with 'perf record -g -o {output} --'. The profiled runs are not \
+                     part of the statistics, and the paths of the profiles are listed in the \
+                     JSON export.",
                 ),
         )
+        .arg(
+            Arg::new("profile-runs")
+                .long("profile-runs")
+                .action(ArgAction::Set)
+                .value_name("NUM")
+                .requires("profile-with")
+                .help("Number of profiled runs of each command (default: 1)."),
+        )
+        .arg(
+            Arg::new("profile-dir")
+                .long("profile-dir")
+                .action(ArgAction::Set)
+                .value_name("DIR")
+                .value_hint(ValueHint::DirPath)
+                .requires("profile-with")
+                .help("Directory in which the profiles are stored (default: 'hyperfine-profiles')."),
+        )
         .arg(
             Arg::new("parameter-scan")
                 .long("parameter-scan")
@@ -183,6 +583,43 @@ fn build_command() -> Command {
                      This performs benchmarks for 'sleep 0.3', 'sleep 0.5' and 'sleep 0.7'.",
                 ),
         )
+        .arg(
+            Arg::new("scan-order")
+                .long("scan-order")
+                .action(ArgAction::Set)
+                .value_name("ORDER")
+                .value_parser(["ascending", "zigzag", "random"])
+                .requires("parameter-scan")
+                .help(
+                    "Order in which the values of '--parameter-scan' are benchmarked: \
+                     'ascending' (the default), 'zigzag' (alternating between the lowest and \
+                     the highest remaining value) or 'random'. The latter two prevent a slow \
+                     drift of the system (e.g. thermal throttling) from looking like a scaling \
+                     trend. The results are still reported in ascending order, and the JSON \
+                     export records the position in which each benchmark was executed.",
+                ),
+        )
+        .arg(
+            Arg::new("shuffle")
+                .long("shuffle")
+                .action(ArgAction::Set)
+                .num_args(0..=1)
+                .require_equals(true)
+                .value_name("SEED")
+                .value_parser(clap::value_parser!(u64))
+                .conflicts_with("scan-order")
+                .help(
+                    "Benchmark the commands (including the values of parameters) in a random \
+                     order, such that the effects of the order (e.g. caches that are warmed \
+                     up by the previous command, or a slow drift of the system) are not \
+                     attributed to the commands. The order only depends on the seed, which \
+                     is chosen randomly if it is not given. It is shown in the terminal \
+                     output and recorded in the JSON export, such that the order can be \
+                     reproduced with '--shuffle=SEED'. The reference command is still \
+                     benchmarked first, and the results are still reported in the order of \
+                     the commands.",
+                ),
+        )
         .arg(
             Arg::new("parameter-list")
                 .long("parameter-list")
@@ -200,6 +637,54 @@ fn build_command() -> Command {
                      possible parameter combinations.\n"
                 ),
         )
+        .arg(
+            Arg::new("parameter-expr")
+                .long("parameter-expr")
+                .action(ArgAction::Append)
+                .allow_hyphen_values(true)
+                .value_names(["VAR", "EXPR"])
+                .conflicts_with_all(["parameter-scan", "parameter-step-size"])
+                .help(
+                    "Perform benchmark runs for each value that is generated by the expression \
+                     EXPR, which has the form '<expression> for <name> in <start>..<end>'. The \
+                     expression may use the operators +, -, *, / and ^ (power). The range \
+                     excludes the end, unless it is written as '<start>..=<end>'. Replaces the \
+                     string '{VAR}' in each command by the current parameter value.\n\n  \
+                     Example:  hyperfine --parameter-expr size '2^k for k in 0..4' 'sleep {size}'\n\n\
+                     This performs benchmarks for 'sleep 1', 'sleep 2', 'sleep 4' and 'sleep 8'.\n\n\
+                     Like '--parameter-list', the option can be specified multiple times (also \
+                     in combination with '--parameter-list') to run benchmarks for all possible \
+                     parameter combinations.\n"
+                ),
+        )
+        .arg(
+            Arg::new("duplicates")
+                .long("duplicates")
+                .action(ArgAction::Set)
+                .value_name("MODE")
+                .value_parser(["error", "merge", "keep"])
+                .help(
+                    "What to do if the same benchmark (the same command line under the same \
+                     name, e.g. because parameter values collapse into identical commands) \
+                     appears more than once: 'error' (the default) aborts before anything is \
+                     run, 'merge' combines the runs of all copies into a single result and \
+                     'keep' reports each copy separately.",
+                ),
+        )
+        .arg(
+            Arg::new("define")
+                .long("define")
+                .action(ArgAction::Append)
+                .value_name("NAME=VALUE")
+                .help(
+                    "Define a variable that is substituted for the string '{NAME}' in all \
+                     other arguments: the commands, the setup, prepare, conclude and cleanup \
+                     commands, export file names, etc. In contrast to parameters, a variable \
+                     has a single value for the whole session. The option can be specified \
+                     multiple times.\n\nExample:  hyperfine --define out=/tmp/bench \
+                     --export-json '{out}/results.json' 'make -C {out}'",
+                ),
+        )
         .arg(
             Arg::new("shell")
                 .long("shell")
@@ -214,7 +699,9 @@ fn build_command() -> Command {
                        the default shell on this platform. Finally, this can also be set to \
                        \"none\" to disable the shell. In this case, commands will be executed \
                        directly. They can still have arguments, but more complex things like \
-                       \"sleep 0.1; sleep 0.2\" are not possible without a shell.")
+                       \"sleep 0.1; sleep 0.2\" are not possible without a shell. Commands are \
+                       passed to the shell via '-c', except for cmd.exe ('/C') and PowerShell \
+                       ('-Command').")
         )
         .arg(
             Arg::new("no-shell")
@@ -223,6 +710,75 @@ fn build_command() -> Command {
                 .conflicts_with_all(["shell", "debug-mode"])
                 .help("An alias for '--shell=none'.")
         )
+        .arg(
+            Arg::new("no-shell-correction")
+                .long("no-shell-correction")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("no-shell")
+                .help("Do not subtract the shell spawning time from the measured times, i.e. \
+                       report the raw times of the shell process. The shell spawning time is \
+                       still measured and included in the JSON export."),
+        )
+        .arg(
+            Arg::new("pipeline-stages")
+                .long("pipeline-stages")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("no-shell")
+                .help("If a benchmarked command is a pipeline like 'a | b | c', run each of \
+                       its stages in a shell of its own and connect them by pipes, such that \
+                       the user and system time of each stage can be measured separately. \
+                       The mean CPU times of the stages are shown after the results, and \
+                       they are listed for each run in the JSON export. Commands which \
+                       contain other control operators (e.g. ';', '&&' or '||') are run as a \
+                       whole. Only the error output of the last stage is recorded. \
+                       Not supported on Windows."),
+        )
+        .arg(
+            Arg::new("source")
+                .long("source")
+                .action(ArgAction::Set)
+                .value_name("FILE")
+                .conflicts_with("no-shell")
+                .help("Source the given file in the shell before each command, such that shell \
+                       functions and aliases that are defined in it can be benchmarked, e.g. \
+                       '--source ~/.bashrc'. The time for sourcing the file is included in the \
+                       measured shell spawning time and therefore subtracted from the results. \
+                       Note that bash only expands aliases in scripts if the file sets \
+                       'shopt -s expand_aliases'. Requires a POSIX shell like sh, bash or zsh."),
+        )
+        .arg(
+            Arg::new("shim")
+                .long("shim")
+                .action(ArgAction::Set)
+                .value_name("PREFIX")
+                .conflicts_with_all(["no-shell", "pipeline-stages", "show-output"])
+                .help("Time each command with the measurement shim 'hyperfine-shim' (which is \
+                       built with hyperfine) where the command runs, e.g. inside of a container \
+                       or on a remote host. The prefix runs the shim, and each command is \
+                       passed to it as 'PREFIX sh -c COMMAND', e.g. \
+                       '--shim \"docker exec bench /usr/local/bin/hyperfine-shim\"' or \
+                       '--shim \"ssh host hyperfine-shim\"'. The wall clock time, the CPU times \
+                       and the peak memory usage are taken from the record that the shim \
+                       prints to the error output, so the time to reach the container or host \
+                       is not measured. The shell spawning time is measured with the shim as \
+                       well. Not supported on Windows."),
+        )
+        .arg(
+            Arg::new("timer")
+                .long("timer")
+                .action(ArgAction::Set)
+                .value_name("TIMER")
+                .value_parser(["monotonic", "monotonic-raw", "task-clock", "tsc"])
+                .help(
+                    "Select the clock that measures the wall clock time of each run:\n  \
+                       * 'monotonic' (default): the monotonic clock of the operating system\n  \
+                       * 'monotonic-raw': CLOCK_MONOTONIC_RAW, which is not subject to NTP\n    \
+                         frequency adjustments (Linux only)\n  \
+                       * 'task-clock': the CPU time (user + system) of the process, like\n    \
+                         'perf stat'; time spent waiting (e.g. for I/O) is not counted\n  \
+                       * 'tsc': the time stamp counter of the CPU (x86-64 only)\n",
+                ),
+        )
         .arg(
             Arg::new("ignore-failure")
                 .long("ignore-failure")
@@ -230,6 +786,68 @@ fn build_command() -> Command {
                 .short('i')
                 .help("Ignore non-zero exit codes of the benchmarked programs."),
         )
+        .arg(
+            Arg::new("exit-code-ok")
+                .long("exit-code-ok")
+                .action(ArgAction::Append)
+                .value_delimiter(',')
+                .value_name("CODES")
+                .help("Comma-separated list of non-zero exit codes of the benchmarked programs \
+                       which are accepted like a successful run (e.g. '--exit-code-ok 1,3')."),
+        )
+        .arg(
+            Arg::new("exit-code-skip")
+                .long("exit-code-skip")
+                .action(ArgAction::Append)
+                .value_delimiter(',')
+                .value_name("CODES")
+                .help("Comma-separated list of exit codes which signal that a run should not \
+                       be counted (e.g. '--exit-code-skip 77', like in automake tests). Such runs \
+                       are excluded from the results and repeated."),
+        )
+        .arg(
+            Arg::new("fail-on")
+                .long("fail-on")
+                .action(ArgAction::Append)
+                .value_delimiter(',')
+                .value_name("CONDITIONS")
+                .value_parser(["regression", "outliers", "warnings"])
+                .help("Comma-separated list of conditions under which hyperfine exits with a \
+                       dedicated non-zero exit code after all benchmarks have been performed \
+                       (and exported). 'regression': a command is significantly slower than \
+                       the reference command (see '--reference'), or than the first command if \
+                       there is no reference (exit code 5). 'outliers': statistical outliers \
+                       have been detected (exit code 6). 'warnings': any warning has been \
+                       reported about the results (exit code 7, see '--strict'). See the man \
+                       page for all exit codes."),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Treat the warnings about the results (e.g. statistical outliers, a slow \
+                       first run, tolerated non-zero exit codes, or run times that are too short \
+                       to be measured accurately) as failures: hyperfine exits with the exit \
+                       code 7 after all benchmarks have been performed (and exported). The \
+                       warnings are always listed in the 'warnings' array of each result in the \
+                       JSON export. Same as '--fail-on warnings'."),
+        )
+        .arg(
+            Arg::new("notify")
+                .long("notify")
+                .action(ArgAction::Set)
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("all")
+                .value_name("KIND")
+                .value_parser(["bell", "desktop", "all"])
+                .help("Signal the end of the session, e.g. of a long session in a background \
+                       terminal: 'bell' rings the terminal bell, 'desktop' shows a desktop \
+                       notification (via notify-send on Linux, osascript on macOS and \
+                       PowerShell on Windows) and 'all' (the default) does both. The \
+                       notification mentions a regression if a command is significantly \
+                       slower than the reference (see '--fail-on')."),
+        )
         .arg(
             Arg::new("style")
                 .long("style")
@@ -245,6 +863,36 @@ fn build_command() -> Command {
                      the output of the tool.",
                 ),
         )
+        .arg(
+            Arg::new("ascii")
+                .long("ascii")
+                .action(ArgAction::SetTrue)
+                .help("Only use ASCII characters in the terminal output and in the Markdown, \
+                       AsciiDoc and org-mode exports, e.g. '+/-' instead of '±' and '...' \
+                       instead of '…'. The spinner and the progress bars are drawn with ASCII \
+                       characters as well."),
+        )
+        .arg(
+            Arg::new("quiet")
+                .long("quiet")
+                .short('q')
+                .action(ArgAction::SetTrue)
+                .conflicts_with("verbose")
+                .help("Only show the final summary, but not the results of the individual \
+                       benchmarks, progress bars or warnings. Useful when embedding hyperfine in \
+                       scripts."),
+        )
+        .arg(
+            Arg::new("verbose")
+                .long("verbose")
+                .short('v')
+                .action(ArgAction::Count)
+                .help("Show diagnostic messages on stderr, such as calibration values and \
+                       scheduling decisions, and report additional statistics such as the \
+                       time needed to spawn the processes, the number of page faults and \
+                       whether the fastest and the slowest runs happened early or late. Specify it twice ('-vv') to also \
+                       show the parameters of every spawned process."),
+        )
         .arg(
             Arg::new("sort")
             .long("sort")
@@ -262,6 +910,73 @@ fn build_command() -> Command {
                    * 'mean-time': order benchmarks by mean runtime\n"
             ),
         )
+        .arg(
+            Arg::new("relative-metric")
+                .long("relative-metric")
+                .action(ArgAction::Set)
+                .value_name("METRIC")
+                .value_parser(["wall", "user", "system", "cpu", "memory"])
+                .default_value("wall")
+                .hide_default_value(true)
+                .help(
+                    "The metric on which the relative speed comparison is based: the mean 'wall' \
+                     clock time (default), 'user' time, 'system' time, 'cpu' time (user and \
+                     system) or peak 'memory' usage. It determines the fastest command, the \
+                     ratios of the summary and of the 'Relative' column of the markup exports, \
+                     and the order with '--sort=mean-time'.",
+                ),
+        )
+        .arg(
+            Arg::new("relative-error")
+                .long("relative-error")
+                .action(ArgAction::Set)
+                .value_name("METHOD")
+                .value_parser(["bootstrap", "naive"])
+                .default_value("bootstrap")
+                .hide_default_value(true)
+                .help(
+                    "How the uncertainty (standard deviation and 95% confidence interval) of \
+                     the relative speeds is estimated: by 'bootstrap' resampling of the runs \
+                     (default), which is also correct for skewed distributions of the times, \
+                     or by 'naive' propagation of the standard deviations of the run times.",
+                ),
+        )
+        .arg(
+            Arg::new("command-display")
+                .long("command-display")
+                .action(ArgAction::Set)
+                .value_name("MODE")
+                .value_parser(["full", "truncate", "name-only"])
+                .help(
+                    "How the commands are shown in the terminal output: 'full', 'truncate' (the \
+                     default, which shortens long commands in the middle to fit into the \
+                     terminal width) or 'name-only' (the '--command-name' or, if there is none, \
+                     the program name and the parameter values). The exports always contain \
+                     the full commands.",
+                ),
+        )
+        .arg(
+            Arg::new("highlight-threshold")
+                .long("highlight-threshold")
+                .action(ArgAction::Set)
+                .value_name("PERCENT")
+                .help("Report commands as equivalent in the speed comparison summary if their \
+                       mean times differ by at most PERCENT (e.g. '--highlight-threshold 5%') \
+                       and the 95% confidence intervals of the means overlap, instead of \
+                       declaring one of them faster."),
+        )
+        .arg(
+            Arg::new("power-analysis")
+                .long("power-analysis")
+                .action(ArgAction::SetTrue)
+                .help("Print the statistical power of the comparison of each command with the \
+                       reference (or the fastest command) at the end of the session: the \
+                       smallest difference of the mean times that can be detected with the \
+                       observed variance and number of runs, and the number of runs that is \
+                       needed to detect a difference of 1%. Comparisons whose observed \
+                       difference is below the detectable difference are marked as \
+                       underpowered."),
+        )
         .arg(
             Arg::new("time-unit")
                 .long("time-unit")
@@ -278,6 +993,183 @@ fn build_command() -> Command {
                          * 'min', 'minute', 'minutes'\n  \
                          * 'h', 'hour', 'hours'"),
         )
+        .arg(
+            Arg::new("memory-unit")
+                .long("memory-unit")
+                .action(ArgAction::Set)
+                .value_name("UNIT")
+                .value_parser(["auto", "B", "KiB", "MiB", "GiB"])
+                .help("Report the peak memory usage of the benchmarked commands in the given UNIT. \
+                       If this option is given, the peak memory usage and the number of page \
+                       faults are shown in the terminal output, and the peak memory usage is \
+                       shown as an additional column in the markup exports (Markdown, \
+                       AsciiDoc, org-mode). Set this to 'auto' to determine the unit automatically.\n\
+                       Possible values: 'auto', 'B', 'KiB', 'MiB', 'GiB'"),
+        )
+        .arg(
+            Arg::new("sample-gpu")
+                .long("sample-gpu")
+                .action(ArgAction::SetTrue)
+                .help("Sample the utilization and the used memory of all NVIDIA GPUs (via the \
+                       NVIDIA Management Library) while the benchmarked commands are running. \
+                       The averages are shown in the terminal output and recorded for each run \
+                       in the JSON export."),
+        )
+        .arg(
+            Arg::new("limit-memory")
+                .long("limit-memory")
+                .action(ArgAction::Set)
+                .value_name("SIZE")
+                .help("Limit the memory of each run of the benchmarked commands to SIZE, e.g. \
+                       '512M' or '2G' (binary multiples). On Linux, this limits the virtual \
+                       address space of each process of the run (RLIMIT_AS), not its resident \
+                       memory, so runtimes which reserve a lot of address space up front (e.g. \
+                       the JVM or Go) may fail far below SIZE. On Windows, it limits the memory \
+                       committed by all processes of the run (Job Object). It is not supported \
+                       on macOS, which does not enforce the limit. The limit may \
+                       contain parameters, e.g. '--limit-memory {mem}' in combination with \
+                       '-L mem 256M,512M,1G' charts the run time against the available memory. \
+                       Failing runs which have probably run out of memory (see the man page) \
+                       do not abort the benchmark, but are recorded with \
+                       'memory_limit_exceeded' in the JSON export."),
+        )
+        .arg(
+            Arg::new("measure-disk")
+                .long("measure-disk")
+                .action(ArgAction::Set)
+                .value_name("PATH")
+                .help("Measure how much the disk usage of PATH (the total size of the files \
+                       in a directory, or the size of a file) changes in each run, e.g. to \
+                       compare the output sizes of compression or build tools. The mean \
+                       change is shown in the terminal output, and the change of each run is \
+                       recorded in the JSON export. The size is determined outside of the \
+                       timed section of the run."),
+        )
+        .arg(
+            Arg::new("count-syscalls")
+                .long("count-syscalls")
+                .action(ArgAction::Set)
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("separate")
+                .value_name("WHEN")
+                .value_parser(["separate", "inline"])
+                .help("Count the system calls of each command (including the shell and all \
+                       child processes) by running it under 'strace -f -c', which needs to be \
+                       installed. As tracing slows down the command considerably, the calls \
+                       are counted in three extra runs after the timing runs by default \
+                       ('separate'), which are not part of the statistics. With 'inline', \
+                       they are counted in the timing runs instead. The mean count is shown in \
+                       the terminal output and recorded in the JSON export. This is only \
+                       supported on Linux."),
+        )
+        .arg(
+            Arg::new("scheduling-delay")
+                .long("scheduling-delay")
+                .action(ArgAction::SetTrue)
+                .help("Measure how long each benchmarked process has been waiting in the run \
+                       queue of the scheduler (ready to run, but without a CPU), and how long \
+                       it has been running on a CPU, to tell a slow command from a busy \
+                       machine. The means are shown in the terminal output, and the times of \
+                       each run are recorded in the JSON export. Only the main thread of the \
+                       benchmarked process is taken into account (with a shell, that is the \
+                       command itself if the shell executes it directly), not its child \
+                       processes. The number of times that it has been moved to another CPU, \
+                       and the NUMA node on which it has run last, are recorded as well. This \
+                       is only supported on Linux."),
+        )
+        .arg(
+            Arg::new("derived")
+                .long("derived")
+                .action(ArgAction::Append)
+                .value_name("NAME=FORMULA")
+                .help("Compute a metric from the measurements of each run, e.g. \
+                       'gflops = 2*{n}^3 / time / 1e9' for a parameter scan over the problem \
+                       size n. The formula can use the operators +, -, *, / and ^, numbers \
+                       like 1e9, the parameters of the benchmark as {name}, and the variables \
+                       'time', 'user' and 'system' (in seconds) and 'memory' (peak memory usage \
+                       in bytes). The mean and standard deviation are shown in the terminal \
+                       output and in the CSV export, and the values of each run are recorded \
+                       in the JSON export. Can be specified multiple times."),
+        )
+        .arg(
+            Arg::new("noise-floor")
+                .long("noise-floor")
+                .action(ArgAction::Set)
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("5")
+                .value_name("EVERY")
+                .help("Measure an empty command after the first and then after every EVERY-th \
+                       benchmark run (default: 5), to estimate the residual load of the \
+                       system during the session. The empty runs are not part of the \
+                       statistics. Their mean time (after subtracting the shell spawning time) \
+                       and standard deviation are shown in the terminal output and recorded \
+                       in the JSON export: a noise floor that is large compared to the \
+                       differences between the commands makes the results unreliable. This \
+                       requires a shell."),
+        )
+        .arg(
+            Arg::new("disable-turbo")
+                .long("disable-turbo")
+                .action(ArgAction::SetTrue)
+                .help("Disable turbo boost (Intel and AMD) while the benchmarks are running, to \
+                       reduce the variance of the measurements. The original state is restored \
+                       when hyperfine exits, even if it is interrupted with Ctrl-C. This is only \
+                       supported on Linux and requires root privileges."),
+        )
+        .arg(
+            Arg::new("disable-smt")
+                .long("disable-smt")
+                .action(ArgAction::SetTrue)
+                .help("Disable simultaneous multithreading (hyper-threading) while the \
+                       benchmarks are running. The original state is restored when hyperfine \
+                       exits, even if it is interrupted with Ctrl-C. This is only supported on \
+                       Linux and requires root privileges."),
+        )
+        .arg(
+            Arg::new("numa-node")
+                .long("numa-node")
+                .action(ArgAction::Set)
+                .value_name("NODE")
+                .value_parser(clap::value_parser!(u32))
+                .help("Run hyperfine and all benchmarked processes on the CPUs of the given \
+                       NUMA node, and only allocate their memory on that node (like \
+                       'numactl --cpunodebind=NODE --membind=NODE'), such that the \
+                       measurements do not depend on where the scheduler places the \
+                       processes. With '--jobs', the CPUs of the node are split among the \
+                       jobs. This is only supported on Linux."),
+        )
+        .arg(
+            Arg::new("pause-processes")
+                .long("pause-processes")
+                .action(ArgAction::Set)
+                .value_name("NAMES")
+                .help("Suspend all processes with one of the given (comma-separated) names, e.g. \
+                       'chrome,slack', while the benchmarks are running, to reduce background \
+                       noise. The processes are stopped with SIGSTOP and continued with SIGCONT \
+                       when hyperfine exits, even if it is interrupted with Ctrl-C. Only \
+                       supported on Unix systems."),
+        )
+        .arg(
+            Arg::new("export")
+                .long("export")
+                .action(ArgAction::Append)
+                .value_name("FORMAT:TARGET")
+                .value_parser(parse_export_target)
+                .help("Export the results in the given FORMAT to TARGET, which is a file or '-' \
+                       for the standard output, e.g. '--export markdown:- --export \
+                       json:results.json'. This is equivalent to the dedicated '--export-FORMAT' \
+                       options, but can be specified multiple times, also with the same format. \
+                       The exports are written in the given order. Possible formats: asciidoc, \
+                       bencher, csv, json, markdown, orgmode, otlp, runs."),
+        )
+        // Options that only apply to a JSON export require one of these
+        .group(
+            ArgGroup::new("json-export")
+                .args(["export-json", "export"])
+                .multiple(true),
+        )
         .arg(
             Arg::new("export-asciidoc")
                 .long("export-asciidoc")
@@ -287,6 +1179,17 @@ fn build_command() -> Command {
                 .help("Export the timing summary statistics as an AsciiDoc table to the given FILE. \
                        The output time unit can be changed using the --time-unit option."),
         )
+        .arg(
+            Arg::new("export-bencher")
+                .long("export-bencher")
+                .action(ArgAction::Set)
+                .value_name("FILE")
+                .value_hint(ValueHint::FilePath)
+                .help("Export the mean wall clock times in the Bencher Metric Format (JSON) to the \
+                       given FILE, for continuous benchmarking tools like Bencher. The 'latency' \
+                       of each command is given in nanoseconds, with one standard deviation \
+                       around the mean as lower and upper bounds."),
+        )
         .arg(
             Arg::new("export-csv")
                 .long("export-csv")
@@ -294,9 +1197,53 @@ fn build_command() -> Command {
                 .value_name("FILE")
                 .value_hint(ValueHint::FilePath)
                 .help("Export the timing summary statistics as CSV to the given FILE. If you need \
-                       the timing results for each individual run, use the JSON export format. \
+                       the timing results for each individual run, use the JSON export format or \
+                       the --export-runs option. \
                        The output time unit is always seconds."),
         )
+        .arg(
+            Arg::new("csv-delimiter")
+                .long("csv-delimiter")
+                .action(ArgAction::Set)
+                .value_name("CHAR")
+                .help("Use CHAR as the field delimiter in the CSV exports ('--export-csv' and \
+                       '--export-runs'). Use '\\t' for tabs. Default: ',' (or ';' if \
+                       '--decimal-comma' is used)."),
+        )
+        .arg(
+            Arg::new("decimal-comma")
+                .long("decimal-comma")
+                .action(ArgAction::SetTrue)
+                .help("Use a comma as the decimal separator in the CSV exports, such that they \
+                       can be opened with spreadsheet applications in locales that use a \
+                       decimal comma."),
+        )
+        .arg(
+            Arg::new("output-dir")
+                .long("output-dir")
+                .action(ArgAction::Set)
+                .value_name("DIR")
+                .value_hint(ValueHint::DirPath)
+                .help("Collect all results of the session in the directory DIR, which is created \
+                       if needed: the exports in all formats, a description of the system \
+                       ('system.json') and of the effective configuration \
+                       ('invocation.json'), the profiles of '--profile-with' (unless \
+                       '--profile-dir' is given) and a 'manifest.json' file which lists them. \
+                       The placeholder {timestamp} in DIR is replaced by the start time of the \
+                       session, e.g. '--output-dir bench-{timestamp}'."),
+        )
+        .arg(
+            Arg::new("precision")
+                .long("precision")
+                .action(ArgAction::Set)
+                .value_name("DIGITS")
+                .help("Number of decimal places of the times, relative speeds and memory sizes \
+                       in the CSV and markup exports (Markdown, AsciiDoc, org-mode), e.g. \
+                       '--precision 4'. The value can be overridden for each kind of number, \
+                       e.g. '--precision 4,relative=2' or '--precision time=5'. By default, \
+                       times have 3 decimal places in seconds and 1 in smaller units (6 in the \
+                       CSV exports, which use seconds), relative speeds 2 and memory sizes 1."),
+        )
         .arg(
             Arg::new("export-json")
                 .long("export-json")
@@ -304,7 +1251,44 @@ fn build_command() -> Command {
                 .value_name("FILE")
                 .value_hint(ValueHint::FilePath)
                 .help("Export the timing summary statistics and timings of individual runs as JSON to the given FILE. \
-                       The output time unit is always seconds"),
+                       The output time unit is always seconds. If the FILE ends with '.gz' or '.zst', \
+                       the export is compressed with the external 'gzip' or 'zstd' program, which \
+                       needs to be installed (as for all other export files)."),
+        )
+        .arg(
+            Arg::new("sign-key")
+                .long("sign-key")
+                .action(ArgAction::Set)
+                .value_name("KEY")
+                .value_hint(ValueHint::FilePath)
+                .requires("json-export")
+                .help("Sign the JSON export with the given private KEY (in PEM format) to make it \
+                       tamper-evident. A fingerprint of the system is embedded in the export, \
+                       and the signature is written to '<FILE>.sig'. It can be verified with \
+                       'openssl dgst -sha256 -verify public.pem -signature <FILE>.sig <FILE>'. \
+                       Requires the 'openssl' command line tool."),
+        )
+        .arg(
+            Arg::new("capabilities")
+                .long("capabilities")
+                .action(ArgAction::SetTrue)
+                .exclusive(true)
+                .help("Print which optional measurement capabilities (timers, GPU metrics, \
+                       system call counts, CPU state control, ...) are available on this \
+                       system, with the reason if one is not, and exit. Requested measurements \
+                       that are not available are replaced by the best available alternative \
+                       (e.g. '--timer=tsc' by the monotonic clock) or left out, with a \
+                       warning."),
+        )
+        .arg(
+            Arg::new("print-json-schema")
+                .long("print-json-schema")
+                .action(ArgAction::SetTrue)
+                .exclusive(true)
+                .help("Print the JSON Schema of the format written by '--export-json' and exit. \
+                       Exports contain a 'schema_version' field. New fields may be added within \
+                       a schema version, but removing or changing existing fields results in a \
+                       new version."),
         )
         .arg(
             Arg::new("export-markdown")
@@ -324,6 +1308,164 @@ fn build_command() -> Command {
                 .help("Export the timing summary statistics as an Emacs org-mode table to the given FILE. \
                        The output time unit can be changed using the --time-unit option."),
         )
+        .arg(
+            Arg::new("orgmode-name")
+                .long("orgmode-name")
+                .action(ArgAction::Set)
+                .value_name("NAME")
+                .requires("export-orgmode")
+                .help("Add a '#+NAME: NAME' line to the org-mode export, such that the table \
+                       can be referenced from other tables and code blocks."),
+        )
+        .arg(
+            Arg::new("orgmode-alignment")
+                .long("orgmode-alignment")
+                .action(ArgAction::SetTrue)
+                .requires("export-orgmode")
+                .help("Add a row with column alignment cookies ('<l>', '<r>') to the org-mode \
+                       export."),
+        )
+        .arg(
+            Arg::new("orgmode-formula")
+                .long("orgmode-formula")
+                .action(ArgAction::SetTrue)
+                .requires("export-orgmode")
+                .help("Add a '#+TBLFM' line to the org-mode export which recomputes the \
+                       'Relative' column from the mean times (relative to the fastest \
+                       command) when the table is recalculated in Emacs."),
+        )
+        .arg(
+            Arg::new("export-columns")
+                .long("export-columns")
+                .action(ArgAction::Set)
+                .value_name("COLUMNS")
+                .value_delimiter(',')
+                .value_parser([
+                    "group", "command", "mean", "median", "min", "max", "user", "system",
+                    "memory", "private-bytes", "working-set", "relative", "relative-ci",
+                ])
+                .hide_possible_values(true)
+                .help("Comma-separated list of the columns of the markup exports (Markdown, \
+                       AsciiDoc, org-mode), in the given order. The default is \
+                       'command,mean,min,max,relative', with an additional 'memory' column if \
+                       '--memory-unit' is given, and an additional 'group' column in front if \
+                       the commands are grouped (see '--group'). The times are shown in the \
+                       same unit as the mean (see '--time-unit').\n\
+                       Possible values: 'group', 'command', 'mean' (± standard deviation), \
+                       'median', \
+                       'min', 'max', 'user', 'system', 'memory' (mean peak memory usage), \
+                       'private-bytes', 'working-set' (mean peak private bytes and working \
+                       set, Windows only), \
+                       'relative' (± standard deviation), 'relative-ci' (95% confidence \
+                       interval of the relative speed, see '--relative-error')"),
+        )
+        .arg(
+            Arg::new("export-otlp")
+                .long("export-otlp")
+                .action(ArgAction::Set)
+                .value_name("TARGET")
+                .value_hint(ValueHint::Url)
+                .help("Export the benchmark session as OpenTelemetry spans (a span for the \
+                       session, for each benchmark and for each run) in the OTLP/JSON format. \
+                       TARGET is either a FILE or an OTLP/HTTP endpoint like \
+                       'http://localhost:4318' (e.g. of an OpenTelemetry Collector), to which \
+                       the spans are sent at the end of the session. The path defaults to \
+                       '/v1/traces'. With the TARGET 'env', the endpoint is taken from \
+                       $OTEL_EXPORTER_OTLP_TRACES_ENDPOINT or $OTEL_EXPORTER_OTLP_ENDPOINT. \
+                       Headers (e.g. API keys) are added from $OTEL_EXPORTER_OTLP_HEADERS and \
+                       $OTEL_EXPORTER_OTLP_TRACES_HEADERS ('key1=value1,key2=value2'). Only \
+                       'http://' endpoints are supported, so backends that require HTTPS \
+                       (e.g. Honeycomb or a hosted Tempo) need a local collector that forwards \
+                       the spans."),
+        )
+        .arg(
+            Arg::new("export-runs")
+                .long("export-runs")
+                .action(ArgAction::Set)
+                .value_name("FILE")
+                .value_hint(ValueHint::FilePath)
+                .help("Export the raw measurements of each individual run as a flat CSV table \
+                       (one row per run) to the given FILE. This is useful for feeding the data \
+                       into statistical tools. Times are given in seconds, memory in bytes."),
+        )
+        .arg(
+            Arg::new("export-plots")
+                .long("export-plots")
+                .action(ArgAction::Set)
+                .value_name("DIR")
+                .value_hint(ValueHint::DirPath)
+                .help("Write ready-to-run Python scripts to the directory DIR which plot the \
+                       wall clock times of the runs: a box and whisker plot (plot_whisker.py), \
+                       a histogram (plot_histogram.py) and the progression of the runs \
+                       (plot_progression.py). The measurements are embedded in the scripts, \
+                       so they do not need the JSON export or the 'scripts' directory of the \
+                       matching version of hyperfine. The scripts need matplotlib, or can be \
+                       run with 'uv run'. They show the plot in a window, or save it to a file \
+                       with '-o FILE'."),
+        )
+        .arg(
+            Arg::new("export-template")
+                .long("export-template")
+                .action(ArgAction::Append)
+                .num_args(2)
+                .value_names(["TEMPLATE", "FILE"])
+                .value_hint(ValueHint::FilePath)
+                .help("Export the results to FILE in a custom text format, which is described by \
+                       the TEMPLATE file (e.g. for wiki markup). In the template, \
+                       '{{ result.mean }}' inserts a value, '{% for result in results %}' ... \
+                       '{% endfor %}' repeats a part for each benchmark and '{% if ... %}' ... \
+                       '{% else %}' ... '{% endif %}' includes a part conditionally. Values can be \
+                       rounded with '{{ result.mean | round(3) }}'. Each result has all fields of \
+                       the JSON export and the summary statistics 'name', 'runs', 'mean', \
+                       'stddev', 'median', 'min', 'max', 'user', 'system' (in seconds), 'memory' \
+                       (in bytes) and 'relative'. This option can be specified multiple times."),
+        )
+        .arg(
+            Arg::new("export-clipboard")
+                .long("export-clipboard")
+                .action(ArgAction::Set)
+                .value_name("FORMAT")
+                .value_parser(["md", "json"])
+                .help("Copy the results to the system clipboard at the end of the session, as a \
+                       Markdown table ('md') or in the JSON format ('json'). This uses the \
+                       clipboard tool of the platform: 'pbcopy' on macOS, 'clip.exe' on Windows \
+                       and 'wl-copy', 'xclip' or 'xsel' on other systems."),
+        )
+        .arg(
+            Arg::new("cache")
+                .long("cache")
+                .action(ArgAction::Set)
+                .value_name("DIR")
+                .value_hint(ValueHint::DirPath)
+                .help("Store benchmark results in the directory DIR and reuse them in later \
+                       invocations instead of measuring again. Results are keyed on the command \
+                       line, the parameter values and a hash of the executed program, so they \
+                       are only reused if none of these changed."),
+        )
+        .arg(
+            Arg::new("cache-refresh")
+                .long("cache-refresh")
+                .action(ArgAction::Set)
+                .value_name("GLOB")
+                .requires("cache")
+                .help("Ignore cached results for commands matching the pattern GLOB, in which \
+                       '*' matches any sequence of characters and '?' matches a single \
+                       character. The commands are measured again and the cache is updated. \
+                       Use '*' to refresh all results."),
+        )
+        .arg(
+            Arg::new("skip-if-unchanged")
+                .long("skip-if-unchanged")
+                .action(ArgAction::Set)
+                .value_name("GLOB")
+                .requires("cache")
+                .help("Skip benchmarks whose cached results (see '--cache') were stored after \
+                       the last modification of the files matching the pattern GLOB, e.g. \
+                       'src/*.rs' in a loop that benchmarks every build. In the pattern, '*' \
+                       also matches path separators, so that files in subdirectories are \
+                       included. If a matching file has been modified, added or removed, the \
+                       benchmark is measured again and the cache is updated."),
+        )
         .arg(
             Arg::new("show-output")
                 .long("show-output")
@@ -336,6 +1478,25 @@ fn build_command() -> Command {
                      when trying to benchmark output speed.",
                 ),
         )
+        .arg(
+            Arg::new("hook-output")
+                .long("hook-output")
+                .action(ArgAction::Set)
+                .value_name("WHERE")
+                .help(
+                    "Control where the output (and error output) of the setup, preparation, \
+                     conclusion, cleanup and teardown commands goes, which would otherwise \
+                     be handled like the output of the benchmarked commands.\n\
+                     \n\
+                     <WHERE> can be:\n\
+                     \n  \
+                       inherit:      Show it on the terminal (without a progress bar).\n\
+                     \n  \
+                       null:         Discard it.\n\
+                     \n  \
+                       file:<PATH>:  Append it to the given file.\n\n",
+                ),
+        )
         .arg(
             Arg::new("output")
                 .long("output")
@@ -363,6 +1524,39 @@ fn build_command() -> Command {
                     hyperfine 'my-command > output-${HYPERFINE_ITERATION}.log'\n\n",
                 ),
         )
+        .arg(
+            Arg::new("capture-output")
+                .long("capture-output")
+                .action(ArgAction::Set)
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("4096")
+                .value_name("BYTES")
+                .conflicts_with("show-output")
+                .requires("json-export")
+                .help("Record the first BYTES bytes (default: 4096) of the standard output and \
+                       error output of the first benchmark run of each command in the JSON \
+                       export, e.g. to keep track of the version banner of the benchmarked \
+                       tool. The output can not be captured if it is shown \
+                       ('--output=inherit')."),
+        )
+        .arg(
+            Arg::new("randomize-env-offset")
+                .long("randomize-env-offset")
+                .action(ArgAction::Set)
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("4096")
+                .value_name("BYTES")
+                .help("Hyperfine sets the inert environment variable \
+                       HYPERFINE_RANDOMIZED_ENVIRONMENT_OFFSET to a random number of bytes for \
+                       each run, such that the memory layout of the benchmarked process (e.g. \
+                       the alignment of its stack) varies between the runs instead of \
+                       systematically biasing the results. This option sets the maximum size \
+                       to BYTES (default: 4096) and records the size of each run as \
+                       'env_offset' in the JSON export, e.g. to correlate it with the run \
+                       times."),
+        )
         .arg(
             Arg::new("input")
                 .long("input")
@@ -387,6 +1581,32 @@ fn build_command() -> Command {
                 .help("Give a meaningful name to a command. This can be specified multiple times \
                        if several commands are benchmarked."),
         )
+        .arg(
+            Arg::new("group")
+                .long("group")
+                .action(ArgAction::Append)
+                .value_name("NAME")
+                .conflicts_with("reference")
+                .help("Start a named group of commands, which contains the commands that \
+                       follow up to the next '--group', e.g. \
+                       '--group io cmd1 cmd2 --group cpu cmd3 cmd4'. After a '--' separator, \
+                       groups are started with '--group NAME --', e.g. \
+                       '--group io -- cmd1 cmd2 --group cpu -- cmd3 cmd4'. Commands are only \
+                       compared with the commands of their own group: the summary, the \
+                       relative speeds of the exports and '--fail-on regression' are computed \
+                       for each group. Commands in front of the first group form a group of \
+                       their own."),
+        )
+        .arg(
+            Arg::new("git-annotate")
+                .long("git-annotate")
+                .action(ArgAction::SetTrue)
+                .help("If a path in a command (e.g. './target/release/app') is inside a git \
+                       repository, append the short hash of the checked-out commit to the name \
+                       of the benchmark, followed by '-dirty' if tracked files have uncommitted \
+                       changes. The repository, the commit and the dirty flag are also recorded \
+                       in the JSON export, so that the results identify the measured code state."),
+        )
         // This option is hidden for now, as it is not yet clear yet if we want to 'stabilize' this,
         // see discussion in https://github.com/sharkdp/hyperfine/issues/527
         .arg(
@@ -394,7 +1614,9 @@ fn build_command() -> Command {
             .long("min-benchmarking-time")
             .action(ArgAction::Set)
             .hide(true)
-            .help("Set the minimum time (in seconds) to run benchmarks. Note that the number of \
+            .value_name("DURATION")
+            .help("Set the minimum time to run benchmarks, either in seconds or as a duration \
+                   like '500ms', '2s' or '1m30s'. Note that the number of \
                    benchmark runs is additionally influenced by the `--min-runs`, `--max-runs`, and \
                    `--runs` option.")
         )
@@ -405,6 +1627,117 @@ fn build_command() -> Command {
             .hide(true)
             .help("Enable debug mode which does not actually run commands, but returns fake times when the command is 'sleep <time>'.")
         )
+        .subcommand(
+            Command::new("merge")
+                .about("Merge several JSON export files (e.g. from sharded benchmark runs) into a single one.")
+                .arg(
+                    Arg::new("files")
+                        .help("The JSON files (as written by '--export-json') to merge.")
+                        .value_name("FILE")
+                        .required(true)
+                        .action(ArgAction::Append)
+                        .value_hint(ValueHint::FilePath),
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .action(ArgAction::Set)
+                        .value_name("FILE")
+                        .value_hint(ValueHint::FilePath)
+                        .help("Write the merged results to FILE instead of standard output."),
+                )
+                .arg(
+                    Arg::new("pool")
+                        .long("pool")
+                        .action(ArgAction::SetTrue)
+                        .help("Pool the runs of benchmarks with the same command and parameter \
+                               values into a single benchmark, instead of simply concatenating \
+                               all benchmarks. The modes, the run indices and the warnings of \
+                               pooled benchmarks are dropped, as they only describe the runs of \
+                               a single file ('hyperfine analyze' computes them for all runs)."),
+                ),
+        )
+        .subcommand(
+            Command::new("analyze")
+                .about("Analyze run times that have been measured elsewhere: compute their \
+                        statistics, detect outliers, compare them and export them like the \
+                        results of a benchmark.")
+                .arg(
+                    Arg::new("arguments")
+                        .help("The files with the run times, followed or preceded by options \
+                               for the output and the exports (e.g. '--export-markdown FILE', \
+                               '--time-unit' or '--sort'). Each file is one of:\n  \
+                               * a JSON export of hyperfine ('--export-json')\n  \
+                               * JSON objects with the 'time_wall_clock' of each run, and \
+                               optionally its 'command', 'time_user', 'time_system' and \
+                               'exit_code' ('--just-run-format=jsonl')\n  \
+                               * a CSV file with a 'time_wall_clock' (or 'time') column, and \
+                               optionally 'command', 'time_user', 'time_system', 'exit_code' \
+                               and 'parameter_<name>' columns ('--export-runs')\n  \
+                               * a list of times, one per line ('--just-run')\n\
+                               All times are given in seconds. Runs without a command are \
+                               named after the file.")
+                        .value_name("FILE")
+                        .required(true)
+                        .num_args(1..)
+                        .trailing_var_arg(true)
+                        .allow_hyphen_values(true)
+                        .value_hint(ValueHint::FilePath),
+                ),
+        )
+        .subcommand(
+            Command::new("compare")
+                .about("Compare the results of two sessions (e.g. before and after a change), \
+                        benchmark by benchmark. For parameter scans, this shows the change of \
+                        the mean time for each combination of parameter values, such that a \
+                        regression can be traced to a region of the parameter space.")
+                .arg(
+                    Arg::new("baseline")
+                        .help("The results to compare against, in any format that \
+                               'hyperfine analyze' can read (e.g. a JSON export).")
+                        .value_name("BASELINE")
+                        .required(true)
+                        .value_hint(ValueHint::FilePath),
+                )
+                .arg(
+                    Arg::new("candidate")
+                        .help("The results to compare, in the same format.")
+                        .value_name("CANDIDATE")
+                        .required(true)
+                        .value_hint(ValueHint::FilePath),
+                )
+                .arg(
+                    Arg::new("export-html")
+                        .long("export-html")
+                        .action(ArgAction::Set)
+                        .value_name("FILE")
+                        .value_hint(ValueHint::FilePath)
+                        .help("Export a heatmap of the changes over the parameter space as a \
+                               web page (which loads Vega-Lite to render it)."),
+                )
+                .arg(
+                    Arg::new("export-vega")
+                        .long("export-vega")
+                        .action(ArgAction::Set)
+                        .value_name("FILE")
+                        .value_hint(ValueHint::FilePath)
+                        .help("Export the heatmap of the changes as a Vega-Lite specification \
+                               (e.g. to render it with other tools)."),
+                ),
+        )
+}
+
+/// Check the format of an '--export FORMAT:TARGET' argument
+fn parse_export_target(value: &str) -> Result<String, String> {
+    match value.split_once(':') {
+        Some((format, _)) if !EXPORT_FORMATS.contains(&format) => Err(format!(
+            "unknown export format '{format}' (possible formats: {})",
+            EXPORT_FORMATS.join(", ")
+        )),
+        Some((_, target)) if !target.is_empty() => Ok(value.to_string()),
+        _ => Err("expected FORMAT:TARGET, e.g. 'markdown:-' or 'json:results.json'".to_string()),
+    }
 }
 
 #[test]
@@ -1,11 +1,16 @@
 use std::collections::BTreeMap;
 use std::fmt;
+use std::fs;
+use std::io::{self, Read};
 use std::str::FromStr;
 
+use crate::parameter::definitions::Definitions;
+use crate::parameter::expression;
 use crate::parameter::tokenize::tokenize;
 use crate::parameter::ParameterValue;
 use crate::{
     error::{OptionsError, ParameterScanError},
+    options::ScanOrder,
     parameter::{
         range_step::{Numeric, RangeStep},
         ParameterNameAndValue,
@@ -13,10 +18,23 @@ use crate::{
 };
 
 use clap::{parser::ValuesRef, ArgMatches};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
 
 use anyhow::{bail, Context, Result};
 use rust_decimal::Decimal;
 
+/// Placeholder for the index of the current run, e.g. `--out out_{run}.bin`
+pub const RUN_PLACEHOLDER: &str = "run";
+
+/// Placeholder for the index of the run that was executed before the current one (of the same
+/// benchmark), e.g. `--in out_{prev_run}.bin`. Empty for the first run.
+pub const PREV_RUN_PLACEHOLDER: &str = "prev_run";
+
+/// Placeholder for the total number of benchmark runs
+pub const TOTAL_RUNS_PLACEHOLDER: &str = "total_runs";
+
 /// A command that should be benchmarked.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Command<'a> {
@@ -28,6 +46,9 @@ pub struct Command<'a> {
 
     /// Zero or more parameter values.
     parameters: Vec<ParameterNameAndValue<'a>>,
+
+    /// The group of commands that it is compared with (see `--group`)
+    group: Option<&'a str>,
 }
 
 impl<'a> Command<'a> {
@@ -36,6 +57,7 @@ impl<'a> Command<'a> {
             name,
             expression,
             parameters: Vec::new(),
+            group: None,
         }
     }
 
@@ -48,9 +70,19 @@ impl<'a> Command<'a> {
             name,
             expression,
             parameters: parameters.into_iter().collect(),
+            group: None,
         }
     }
 
+    pub fn with_group(mut self, group: Option<&'a str>) -> Command<'a> {
+        self.group = group;
+        self
+    }
+
+    pub fn get_group(&self) -> Option<&'a str> {
+        self.group
+    }
+
     pub fn get_name(&self) -> String {
         self.name.map_or_else(
             || self.get_command_line(),
@@ -74,6 +106,38 @@ impl<'a> Command<'a> {
         format!("{}{}", self.get_name(), parameters)
     }
 
+    /// The name of the command if it has been given (see `--command-name`). Otherwise, the
+    /// name of the program, followed by the values of the parameters.
+    pub fn get_short_name(&self) -> String {
+        if self.name.is_some() {
+            return self.get_name_with_unused_parameters();
+        }
+
+        let command_line = self.get_command_line();
+        let program = command_line
+            .split_whitespace()
+            .find(|word| !word.contains('='))
+            .and_then(|program| program.rsplit(['/', '\\']).next())
+            .unwrap_or(&command_line);
+        if self.parameters.is_empty() {
+            return program.to_string();
+        }
+
+        let parameters = self
+            .parameters
+            .iter()
+            .map(|(parameter, value)| format!("{parameter} = {value}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{program} ({parameters})")
+    }
+
+    /// The name of the command if it has been given, otherwise the command line, before the
+    /// parameters are substituted
+    pub fn get_template(&self) -> &'a str {
+        self.name.unwrap_or(self.expression)
+    }
+
     pub fn get_command_line(&self) -> String {
         self.replace_parameters_in(self.expression)
     }
@@ -107,6 +171,38 @@ impl<'a> Command<'a> {
             .filter(move |(parameter, _)| self.is_parameter_unused(parameter))
     }
 
+    /// Whether the given placeholder is used in the command, and not shadowed by a
+    /// parameter with the same name
+    pub fn uses_placeholder(&self, placeholder: &str) -> bool {
+        !self.is_parameter_unused(placeholder)
+            && self.parameters.iter().all(|(name, _)| *name != placeholder)
+    }
+
+    /// The command for a single run, with the `{run}`, `{prev_run}` and `{total_runs}`
+    /// placeholders replaced by the given values. Parameters with the same name take
+    /// precedence.
+    pub fn with_run_placeholders(
+        &self,
+        run: Option<String>,
+        prev_run: Option<String>,
+        total_runs: Option<u64>,
+    ) -> Self {
+        let mut command = self.clone();
+        let placeholders = [
+            (RUN_PLACEHOLDER, run),
+            (PREV_RUN_PLACEHOLDER, prev_run),
+            (TOTAL_RUNS_PLACEHOLDER, total_runs.map(|n| n.to_string())),
+        ];
+        for (placeholder, value) in placeholders {
+            if let Some(value) = value.filter(|_| self.uses_placeholder(placeholder)) {
+                command
+                    .parameters
+                    .push((placeholder, ParameterValue::Text(value)));
+            }
+        }
+        command
+    }
+
     fn replace_parameters_in(&self, original: &str) -> String {
         let mut result = String::new();
         let mut replacements = BTreeMap::<String, String>::new();
@@ -134,17 +230,216 @@ impl<'a> Command<'a> {
     }
 }
 
+/// Read the given file, or the standard input if the path is '-'
+fn read_file_or_stdin(path: &str, what: &str) -> Result<String> {
+    if path == "-" {
+        let mut content = String::new();
+        io::stdin()
+            .read_to_string(&mut content)
+            .with_context(|| format!("Could not read {what} from standard input"))?;
+        Ok(content)
+    } else {
+        fs::read_to_string(path)
+            .with_context(|| format!("Could not read {what} from file '{path}'"))
+    }
+}
+
+/// Commands read from the file given to `--commands-from-file`. Each command can optionally
+/// be preceded by a name (separated by a tab character). Scripts given to `--script` are
+/// added as one command each, named after their file.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CommandList {
+    entries: Vec<(Option<String>, String)>,
+}
+
+impl CommandList {
+    pub fn from_cli_arguments(matches: &ArgMatches) -> Result<Self> {
+        let commands_path = matches.get_one::<String>("commands-from-file");
+        let script_paths: Vec<&String> = matches
+            .get_many::<String>("script")
+            .into_iter()
+            .flatten()
+            .collect();
+        if commands_path.is_none() && script_paths.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let stdin_readers = commands_path
+            .into_iter()
+            .chain(script_paths.iter().copied())
+            .filter(|path| *path == "-")
+            .count();
+        if stdin_readers > 1 {
+            bail!("The standard input can only be read once (by '--commands-from-file' or '--script')");
+        }
+        if !script_paths.is_empty()
+            && matches.get_one::<String>("shell").map(|s| s.as_str()) == Some("none")
+        {
+            bail!("Scripts ('--script') can not be benchmarked without a shell");
+        }
+
+        let definitions = Definitions::from_cli_arguments(matches)?;
+        let mut command_list = match commands_path {
+            Some(path) => {
+                Self::parse(&definitions.substitute(&read_file_or_stdin(path, "commands")?))
+            }
+            None => Self::default(),
+        };
+        for path in script_paths {
+            let name = if path == "-" { "<stdin>" } else { path };
+            let script = read_file_or_stdin(path, "the script")?;
+            command_list.entries.push((
+                Some(name.to_string()),
+                definitions.substitute(script.trim_end()),
+            ));
+        }
+
+        Ok(command_list)
+    }
+
+    fn parse(content: &str) -> Self {
+        let entries = content
+            .lines()
+            .filter(|line| {
+                let line = line.trim();
+                !line.is_empty() && !line.starts_with('#')
+            })
+            .map(|line| match line.split_once('\t') {
+                Some((name, command)) => (Some(name.trim().to_string()), command.to_string()),
+                None => (None, line.to_string()),
+            })
+            .collect();
+
+        Self { entries }
+    }
+
+    pub fn has_names(&self) -> bool {
+        self.entries.iter().any(|(name, _)| name.is_some())
+    }
+
+    /// The commands, each with its name, if it has one
+    pub fn entries(&self) -> impl Iterator<Item = (Option<&str>, &str)> {
+        self.entries
+            .iter()
+            .map(|(name, command)| (name.as_deref(), command.as_str()))
+    }
+}
+
+/// The commands that are given as arguments, each with its group (see `--group`), which is
+/// the one of the last `--group` in front of it. Options are passed on as commands after a
+/// `--` separator, so a group is started by the arguments `--group NAME` (which may be
+/// followed by another `--`) there.
+fn grouped_commands(matches: &ArgMatches) -> Result<Vec<(&str, Option<&str>)>> {
+    let mut group_starts: Vec<(usize, &str)> = matches
+        .indices_of("group")
+        .into_iter()
+        .flatten()
+        .zip(matches.get_many::<String>("group").into_iter().flatten())
+        .map(|(index, group)| (index, group.as_str()))
+        .collect();
+
+    let mut arguments = matches
+        .indices_of("command")
+        .into_iter()
+        .flatten()
+        .zip(matches.get_many::<String>("command").into_iter().flatten())
+        .map(|(index, argument)| (index, argument.as_str()))
+        .peekable();
+    let mut commands = vec![];
+    while let Some((index, argument)) = arguments.next() {
+        if argument != "--group" {
+            commands.push((index, argument));
+            continue;
+        }
+        match arguments.next() {
+            Some((_, group)) if group != "--" => group_starts.push((index, group)),
+            _ => bail!("'--group' requires the name of the group"),
+        }
+        arguments.next_if(|&(_, argument)| argument == "--");
+    }
+
+    Ok(commands
+        .into_iter()
+        .map(|(index, command)| {
+            let group = group_starts
+                .iter()
+                .filter(|&&(start, _)| start < index)
+                .max_by_key(|&&(start, _)| start)
+                .map(|&(_, group)| group);
+            (command, group)
+        })
+        .collect())
+}
+
 /// A collection of commands that should be benchmarked
+#[derive(Default)]
 pub struct Commands<'a>(Vec<Command<'a>>);
 
 impl<'a> Commands<'a> {
-    pub fn from_cli_arguments(matches: &'a ArgMatches) -> Result<Commands<'a>> {
-        let command_names = matches.get_many::<String>("command-name");
-        let command_strings = matches
-            .get_many::<String>("command")
-            .unwrap_or_default()
-            .map(|v| v.as_str())
-            .collect::<Vec<_>>();
+    pub fn from_cli_arguments(
+        matches: &'a ArgMatches,
+        command_list: &'a CommandList,
+    ) -> Result<Commands<'a>> {
+        let grouped_commands = grouped_commands(matches)?;
+        let mut commands = Self::from_command_strings(
+            matches,
+            command_list,
+            grouped_commands
+                .iter()
+                .map(|&(command, _)| command)
+                .collect(),
+        )?;
+
+        // The commands of a parameter scan refer to the command string they are created from
+        for command in &mut commands.0 {
+            command.group = grouped_commands
+                .iter()
+                .find(|(expression, _)| std::ptr::eq(*expression, command.expression))
+                .and_then(|&(_, group)| group);
+        }
+        Ok(commands)
+    }
+
+    fn from_command_strings(
+        matches: &'a ArgMatches,
+        command_list: &'a CommandList,
+        mut command_strings: Vec<&'a str>,
+    ) -> Result<Commands<'a>> {
+        let mut command_names = matches
+            .get_many::<String>("command-name")
+            .map_or(vec![], |names| {
+                names.map(|v| v.as_str()).collect::<Vec<_>>()
+            });
+
+        if command_list.has_names() {
+            if command_names.len() > command_strings.len() {
+                return Err(OptionsError::TooManyCommandNames(command_strings.len()).into());
+            }
+
+            // Names from the command list are only meaningful if they line up with the
+            // commands, so unnamed commands are simply named after themselves.
+            command_names = command_strings
+                .iter()
+                .enumerate()
+                .map(|(i, cmd)| command_names.get(i).copied().unwrap_or(cmd))
+                .chain(
+                    command_list
+                        .entries
+                        .iter()
+                        .map(|(name, cmd)| name.as_deref().unwrap_or(cmd)),
+                )
+                .collect();
+        }
+        command_strings.extend(command_list.entries.iter().map(|(_, cmd)| cmd.as_str()));
+
+        // An attached process is benchmarked under its process ID (see '--attach')
+        if let Some(pid) = matches.get_one::<String>("attach") {
+            command_strings.push(pid);
+        }
+
+        if command_strings.is_empty() {
+            bail!("No commands to benchmark have been specified");
+        }
 
         if let Some(args) = matches.get_many::<String>("parameter-scan") {
             let step_size = matches
@@ -156,12 +451,20 @@ impl<'a> Commands<'a> {
                 args,
                 step_size,
             )?))
-        } else if let Some(args) = matches.get_many::<String>("parameter-list") {
-            let command_names = command_names.map_or(vec![], |names| {
-                names.map(|v| v.as_str()).collect::<Vec<_>>()
-            });
-            let args: Vec<_> = args.map(|v| v.as_str()).collect::<Vec<_>>();
-            let param_names_and_values: Vec<(&str, Vec<String>)> = args
+        } else if matches.contains_id("parameter-list") || matches.contains_id("parameter-expr") {
+            let list_args: Vec<_> = matches
+                .get_many::<String>("parameter-list")
+                .into_iter()
+                .flatten()
+                .map(|v| v.as_str())
+                .collect();
+            let expr_args: Vec<_> = matches
+                .get_many::<String>("parameter-expr")
+                .into_iter()
+                .flatten()
+                .map(|v| v.as_str())
+                .collect();
+            let mut param_names_and_values: Vec<(&str, Vec<String>)> = list_args
                 .chunks_exact(2)
                 .map(|pair| {
                     let name = pair[0];
@@ -169,6 +472,9 @@ impl<'a> Commands<'a> {
                     (name, tokenize(list_str))
                 })
                 .collect();
+            for pair in expr_args.chunks_exact(2) {
+                param_names_and_values.push((pair[0], expression::generate_values(pair[1])?));
+            }
             {
                 let duplicates =
                     Self::find_duplicates(param_names_and_values.iter().map(|(name, _)| *name));
@@ -236,9 +542,6 @@ impl<'a> Commands<'a> {
 
             Ok(Self(commands))
         } else {
-            let command_names = command_names.map_or(vec![], |names| {
-                names.map(|v| v.as_str()).collect::<Vec<_>>()
-            });
             if command_names.len() > command_strings.len() {
                 return Err(OptionsError::TooManyCommandNames(command_strings.len()).into());
             }
@@ -255,10 +558,75 @@ impl<'a> Commands<'a> {
         self.0.iter()
     }
 
+    pub fn get(&self, index: usize) -> &Command<'a> {
+        &self.0[index]
+    }
+
+    /// The indices of the benchmarks that appear more than once, i.e. of the commands with
+    /// the same command line and the same name. Each group is in ascending order.
+    pub fn duplicates(&self) -> Vec<Vec<usize>> {
+        let mut groups = BTreeMap::<(String, String), Vec<usize>>::new();
+        for (index, command) in self.0.iter().enumerate() {
+            groups
+                .entry((
+                    command.get_name_with_unused_parameters(),
+                    command.get_command_line(),
+                ))
+                .or_default()
+                .push(index);
+        }
+        let mut duplicates: Vec<_> = groups
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .collect();
+        duplicates.sort();
+        duplicates
+    }
+
     pub fn num_commands(&self, has_reference_command: bool) -> usize {
         self.0.len() + if has_reference_command { 1 } else { 0 }
     }
 
+    /// The indices of the commands in the order in which they are benchmarked. Commands with
+    /// the same parameter values (i.e. the different commands of a parameter scan) stay
+    /// together, only the order of the values is changed. If a seed is given, all commands
+    /// are shuffled individually instead, in an order that only depends on the seed.
+    pub fn execution_order(&self, scan_order: ScanOrder, shuffle_seed: Option<u64>) -> Vec<usize> {
+        if let Some(seed) = shuffle_seed {
+            let mut order: Vec<usize> = (0..self.0.len()).collect();
+            order.shuffle(&mut StdRng::seed_from_u64(seed));
+            return order;
+        }
+
+        let mut groups: Vec<Vec<usize>> = vec![];
+        for (index, command) in self.0.iter().enumerate() {
+            match groups.last_mut() {
+                Some(group) if self.0[group[0]].parameters == command.parameters => {
+                    group.push(index)
+                }
+                _ => groups.push(vec![index]),
+            }
+        }
+
+        let groups = match scan_order {
+            ScanOrder::Ascending => groups,
+            ScanOrder::Zigzag => {
+                let mut zigzag = Vec::with_capacity(groups.len());
+                let mut remaining = groups.into_iter();
+                while let Some(lowest) = remaining.next() {
+                    zigzag.push(lowest);
+                    zigzag.extend(remaining.next_back());
+                }
+                zigzag
+            }
+            ScanOrder::Random => {
+                groups.shuffle(&mut rand::thread_rng());
+                groups
+            }
+        };
+        groups.into_iter().flatten().collect()
+    }
+
     /// Finds all the strings that appear multiple times in the input iterator, returning them in
     /// sorted order. If no string appears more than once, the result is an empty vector.
     fn find_duplicates<'b, I: IntoIterator<Item = &'b str>>(i: I) -> Vec<&'b str> {
@@ -313,14 +681,11 @@ impl<'a> Commands<'a> {
     }
 
     fn get_parameter_scan_commands<'b>(
-        command_names: Option<ValuesRef<'b, String>>,
+        command_names: Vec<&'b str>,
         command_strings: Vec<&'b str>,
         mut vals: ValuesRef<'b, String>,
         step: Option<&str>,
     ) -> Result<Vec<Command<'b>>, ParameterScanError> {
-        let command_names = command_names.map_or(vec![], |names| {
-            names.map(|v| v.as_str()).collect::<Vec<_>>()
-        });
         let param_name = vals.next().unwrap().as_str();
         let param_min = vals.next().unwrap().as_str();
         let param_max = vals.next().unwrap().as_str();
@@ -374,6 +739,44 @@ fn test_get_command_line_nonoverlapping() {
     assert_eq!(cmd.get_command_line(), "echo {bar} baz quux");
 }
 
+#[test]
+fn test_run_placeholders() {
+    let cmd = Command::new(None, "myprog --out out_{run}.bin --of {total_runs}");
+    assert!(cmd.uses_placeholder(RUN_PLACEHOLDER));
+    assert!(!cmd.uses_placeholder(PREV_RUN_PLACEHOLDER));
+    assert_eq!(
+        cmd.with_run_placeholders(Some("3".into()), Some("2".into()), Some(10))
+            .get_command_line(),
+        "myprog --out out_3.bin --of 10"
+    );
+    assert_eq!(
+        cmd.with_run_placeholders(None, None, None)
+            .get_command_line(),
+        "myprog --out out_{run}.bin --of {total_runs}"
+    );
+
+    let cmd = Command::new(None, "myprog --in out_{prev_run}.bin --out out_{run}.bin");
+    assert!(cmd.uses_placeholder(PREV_RUN_PLACEHOLDER));
+    assert_eq!(
+        cmd.with_run_placeholders(Some("3".into()), Some("2".into()), None)
+            .get_command_line(),
+        "myprog --in out_2.bin --out out_3.bin"
+    );
+
+    // A parameter named 'run' takes precedence
+    let cmd = Command::new_parametrized(
+        None,
+        "echo {run}",
+        vec![("run", ParameterValue::Text("fast".into()))],
+    );
+    assert!(!cmd.uses_placeholder(RUN_PLACEHOLDER));
+    assert_eq!(
+        cmd.with_run_placeholders(Some("3".into()), None, None)
+            .get_command_line(),
+        "echo fast"
+    );
+}
+
 #[test]
 fn test_get_parameterized_command_name() {
     let cmd = Command::new_parametrized(
@@ -408,7 +811,10 @@ fn test_build_commands_cross_product() {
         "echo {par1} {par2}",
         "printf '%s\n' {par1} {par2}",
     ]);
-    let result = Commands::from_cli_arguments(&matches).unwrap().0;
+    let command_list = CommandList::default();
+    let result = Commands::from_cli_arguments(&matches, &command_list)
+        .unwrap()
+        .0;
 
     // Iteration order: command list first, then parameters in listed order (here, "par1" before
     // "par2", which is distinct from their sorted order), with parameter values in listed order.
@@ -444,7 +850,10 @@ fn test_build_parameter_list_commands() {
         "--command-name",
         "name-{foo}",
     ]);
-    let commands = Commands::from_cli_arguments(&matches).unwrap().0;
+    let command_list = CommandList::default();
+    let commands = Commands::from_cli_arguments(&matches, &command_list)
+        .unwrap()
+        .0;
     assert_eq!(commands.len(), 2);
     assert_eq!(commands[0].get_name(), "name-1");
     assert_eq!(commands[1].get_name(), "name-2");
@@ -452,6 +861,31 @@ fn test_build_parameter_list_commands() {
     assert_eq!(commands[1].get_command_line(), "echo 2");
 }
 
+#[test]
+fn test_build_parameter_expr_commands() {
+    use crate::cli::get_cli_arguments;
+
+    let matches = get_cli_arguments(vec![
+        "hyperfine",
+        "echo {mode} {size}",
+        "--parameter-list",
+        "mode",
+        "a,b",
+        "--parameter-expr",
+        "size",
+        "2^k for k in 0..3",
+    ]);
+    let command_list = CommandList::default();
+    let commands = Commands::from_cli_arguments(&matches, &command_list)
+        .unwrap()
+        .0;
+    let command_lines: Vec<_> = commands.iter().map(|c| c.get_command_line()).collect();
+    assert_eq!(
+        command_lines,
+        vec!["echo a 1", "echo b 1", "echo a 2", "echo b 2", "echo a 4", "echo b 4"]
+    );
+}
+
 #[test]
 fn test_build_parameter_scan_commands() {
     use crate::cli::get_cli_arguments;
@@ -467,7 +901,10 @@ fn test_build_parameter_scan_commands() {
         "--command-name",
         "name-{val}",
     ]);
-    let commands = Commands::from_cli_arguments(&matches).unwrap().0;
+    let command_list = CommandList::default();
+    let commands = Commands::from_cli_arguments(&matches, &command_list)
+        .unwrap()
+        .0;
     assert_eq!(commands.len(), 2);
     assert_eq!(commands[0].get_name(), "name-1");
     assert_eq!(commands[1].get_name(), "name-2");
@@ -475,6 +912,65 @@ fn test_build_parameter_scan_commands() {
     assert_eq!(commands[1].get_command_line(), "echo 2");
 }
 
+#[test]
+fn test_execution_order() {
+    let commands = Commands(
+        Commands::build_parameter_scan_commands(
+            "val",
+            1i32,
+            5i32,
+            1i32,
+            vec![],
+            vec!["echo {val}", "printf {val}"],
+        )
+        .unwrap(),
+    );
+
+    assert_eq!(
+        commands.execution_order(ScanOrder::Ascending, None),
+        (0..10).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        commands.execution_order(ScanOrder::Zigzag, None),
+        vec![0, 1, 8, 9, 2, 3, 6, 7, 4, 5]
+    );
+
+    let mut random = commands.execution_order(ScanOrder::Random, None);
+    for pair in random.chunks(2) {
+        assert_eq!(pair[0] % 2, 0);
+        assert_eq!(pair[1], pair[0] + 1);
+    }
+    random.sort();
+    assert_eq!(random, (0..10).collect::<Vec<_>>());
+
+    let mut shuffled = commands.execution_order(ScanOrder::Ascending, Some(42));
+    assert_eq!(
+        shuffled,
+        commands.execution_order(ScanOrder::Ascending, Some(42))
+    );
+    assert_ne!(
+        shuffled,
+        commands.execution_order(ScanOrder::Ascending, Some(43))
+    );
+    shuffled.sort();
+    assert_eq!(shuffled, (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_duplicates() {
+    let text = |value: &str| ParameterValue::Text(value.into());
+    let commands = Commands(vec![
+        Command::new(None, "sleep 1"),
+        Command::new_parametrized(None, "sleep {t}", vec![("t", text("1"))]),
+        Command::new(Some("again"), "sleep 1"),
+        Command::new_parametrized(None, "sleep 1", vec![("t", text("1"))]),
+        Command::new_parametrized(None, "sleep 1", vec![("t", text("2"))]),
+        Command::new(None, "sleep 2"),
+        Command::new(None, "sleep 2"),
+    ]);
+    assert_eq!(commands.duplicates(), vec![vec![0, 1], vec![5, 6]]);
+}
+
 #[test]
 fn test_parameter_scan_commands_int() {
     let commands = Commands::build_parameter_scan_commands(
@@ -564,3 +1060,45 @@ fn test_different_command_name_count_with_parameters() {
         ParameterScanError::UnexpectedCommandNameCount(2, 3)
     ));
 }
+
+#[test]
+fn test_parse_command_list() {
+    let command_list = CommandList::parse("sleep 1\n\n# comment\nfast\tsleep 0.1\n  \n");
+    assert_eq!(
+        command_list.entries,
+        vec![
+            (None, "sleep 1".to_string()),
+            (Some("fast".to_string()), "sleep 0.1".to_string()),
+        ]
+    );
+    assert!(command_list.has_names());
+}
+
+#[test]
+fn test_build_commands_with_command_list() {
+    use crate::cli::get_cli_arguments;
+
+    let matches = get_cli_arguments(vec!["hyperfine", "echo a"]);
+    let command_list = CommandList::parse("b\techo b\necho c\n");
+    let commands = Commands::from_cli_arguments(&matches, &command_list)
+        .unwrap()
+        .0;
+    assert_eq!(commands.len(), 3);
+    assert_eq!(commands[0].get_name(), "echo a");
+    assert_eq!(commands[1].get_name(), "b");
+    assert_eq!(commands[1].get_command_line(), "echo b");
+    assert_eq!(commands[2].get_name(), "echo c");
+}
+
+#[test]
+fn test_too_many_command_names_with_named_command_list() {
+    use crate::cli::get_cli_arguments;
+
+    let matches = get_cli_arguments(vec!["hyperfine", "-n", "a", "-n", "extra", "echo a"]);
+    let command_list = CommandList::parse("b\techo b\n");
+    let result = Commands::from_cli_arguments(&matches, &command_list);
+    assert!(matches!(
+        result.err().unwrap().downcast_ref::<OptionsError>(),
+        Some(OptionsError::TooManyCommandNames(1))
+    ));
+}
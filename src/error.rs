@@ -1,4 +1,5 @@
-use std::num::{self, ParseFloatError, ParseIntError};
+use std::fmt;
+use std::num::{self, ParseIntError};
 
 use rust_decimal::Error as DecimalError;
 use thiserror::Error;
@@ -19,6 +20,8 @@ pub enum ParameterScanError {
     StepRequired,
     #[error("'--command-name' has been specified {0} times. It has to appear exactly once, or exactly {1} times (number of benchmarks)")]
     UnexpectedCommandNameCount(usize, usize),
+    #[error("Invalid parameter expression: {0}. Expected an expression like '2^k for k in 0..10'")]
+    InvalidExpression(String),
 }
 
 impl From<num::ParseIntError> for ParameterScanError {
@@ -39,20 +42,182 @@ pub enum OptionsError<'a> {
         "Conflicting requirements for the number of runs (empty range, min is larger than max)"
     )]
     EmptyRunsRange,
+    #[error("The '--runs', '--min-runs' and '--max-runs' options have to be provided just once or the same number of times (once for each command)")]
+    InconsistentRunCounts,
     #[error("Too many --command-name options: Expected {0} at most")]
     TooManyCommandNames(usize),
     #[error("'--command-name' has been specified {0} times. It has to appear exactly once, or exactly {1} times (number of benchmarks)")]
     UnexpectedCommandNameCount(usize, usize),
     #[error("Could not read numeric integer argument to '--{0}': {1}")]
     IntParsingError(&'a str, ParseIntError),
-    #[error("Could not read numeric floating point argument to '--{0}': {1}")]
-    FloatParsingError(&'a str, ParseFloatError),
+    #[error("Could not read the duration argument to '--{0}': {1}")]
+    InvalidDuration(&'a str, String),
+    #[error("Could not read the argument to '--start-at': {0}")]
+    InvalidTimeOfDay(String),
     #[error("An empty command has been specified for the '--shell <command>' option")]
     EmptyShell,
     #[error("Failed to parse '--shell <command>' expression as command line: {0}")]
     ShellParseError(shell_words::ParseError),
     #[error("Unknown output policy '{0}'. Use './{0}' to output to a file named '{0}'.")]
     UnknownOutputPolicy(String),
+    #[error("Unknown hook output policy '{0}' (use 'inherit', 'null' or 'file:<path>')")]
+    UnknownHookOutputPolicy(String),
     #[error("The file '{0}' specified as '--input' does not exist")]
     StdinDataFileDoesNotExist(String),
+    #[error("The CSV delimiter '{0}' is not a single ASCII character")]
+    InvalidCsvDelimiter(String),
+    #[error(
+        "Invalid value '{0}' for '--precision': expected a number of decimal places, optionally \
+         overridden per kind of number, e.g. '4,relative=2'"
+    )]
+    InvalidPrecision(String),
+    #[error("'--decimal-comma' can not be used with a comma as the CSV delimiter")]
+    AmbiguousDecimalComma,
+    #[error(
+        "The exit code {0} has been specified for both '--exit-code-ok' and '--exit-code-skip'"
+    )]
+    ConflictingExitCodeClasses(i32),
+    #[error("The timer '{0}' is not supported on this platform")]
+    UnsupportedTimer(String),
+    #[error("'--pipeline-stages' requires a shell and is not supported on Windows")]
+    UnsupportedPipelineStages,
+    #[error("'--count-syscalls' is only supported on Linux")]
+    UnsupportedSyscallCounting,
+    #[error("'--scheduling-delay' is only supported on Linux")]
+    UnsupportedSchedulingDelay,
+    #[error("'--numa-node' is only supported on Linux")]
+    UnsupportedNumaNode,
+    #[error("'--limit-memory' is not supported on macOS, which does not enforce limits of the address space")]
+    UnsupportedMemoryLimit,
+    #[error("'--source' requires a POSIX shell like sh, bash or zsh")]
+    UnsupportedSourceFile,
+    #[error("The file '{0}' specified as '--source' does not exist")]
+    SourceFileDoesNotExist(String),
+    #[error(
+        "'--shim' requires a POSIX shell like sh, bash or zsh and is not supported on Windows"
+    )]
+    UnsupportedShim,
+    #[error(
+        "'--shim' can not be combined with output that is shown or written to a file \
+         ('inherit' or 'file:<path>'), as the record of the shim is read from the error output"
+    )]
+    ShimOutputNotRecorded,
+    #[error("Invalid value '{0}' for '--derived': {1}")]
+    InvalidDerivedMetric(String, String),
+    #[error(
+        "Invalid value '{0}' for '--auto-timeout': expected a factor of at least 1, e.g. '10x'"
+    )]
+    InvalidAutoTimeout(String),
+    #[error("The interval of '--noise-floor' must be at least one run")]
+    ZeroNoiseFloorInterval,
+    #[error("'--noise-floor' requires a shell, as it measures empty shell runs")]
+    UnsupportedNoiseFloor,
+    #[error("Invalid value '{0}' for '--trim': expected a percentage below 50%, e.g. '5%'")]
+    InvalidTrim(String),
+    #[error("Invalid value '{0}' for '--highlight-threshold': expected a positive percentage, e.g. '5%'")]
+    InvalidHighlightThreshold(String),
+    #[error(
+        "Invalid value '{0}' for '--abort-on-noise': expected a positive percentage, e.g. '25%'"
+    )]
+    InvalidNoiseThreshold(String),
+    #[error("The '--profile-with' command has to contain the '{{output}}' placeholder for the path of the profile")]
+    MissingProfileOutputPlaceholder,
+}
+
+/// Exit status of hyperfine, such that scripts can tell different kinds of failures apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    /// All benchmarks have been performed successfully
+    Success = 0,
+
+    /// Any other error, e.g. invalid options or a failing export
+    Error = 1,
+
+    /// Invalid command line arguments (as reported by the argument parser)
+    Usage = 2,
+
+    /// A benchmarked command (or one of its '--prepare'/'--conclude' commands) failed
+    MeasurementFailure = 3,
+
+    /// The benchmarks could not be started, e.g. because the shell or the '--setup'
+    /// command failed
+    PreflightFailed = 4,
+
+    /// A command is slower than the reference (with '--fail-on regression')
+    RegressionDetected = 5,
+
+    /// Statistical outliers have been detected (with '--fail-on outliers')
+    OutliersDetected = 6,
+
+    /// Warnings have been reported about the results (with '--strict' or
+    /// '--fail-on warnings')
+    WarningsReported = 7,
+
+    /// The benchmark was interrupted with Ctrl-C
+    Interrupted = 130,
+}
+
+impl ExitCode {
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+}
+
+/// An error that determines the exit status of hyperfine
+#[derive(Debug)]
+pub struct Failure {
+    pub exit_code: ExitCode,
+    error: anyhow::Error,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The wrapped error is not exposed as a source, such that its causes are not
+        // printed twice
+        write!(f, "{:#}", self.error)
+    }
+}
+
+impl std::error::Error for Failure {}
+
+/// Attach an exit code to the error of a result
+pub trait WithExitCode<T> {
+    fn exit_code(self, exit_code: ExitCode) -> anyhow::Result<T>;
+}
+
+impl<T> WithExitCode<T> for anyhow::Result<T> {
+    fn exit_code(self, exit_code: ExitCode) -> anyhow::Result<T> {
+        self.map_err(|error| {
+            // Keep the most specific exit code
+            if error.chain().any(|cause| cause.is::<Failure>()) {
+                error
+            } else {
+                Failure { exit_code, error }.into()
+            }
+        })
+    }
+}
+
+/// The exit code for an error, `ExitCode::Error` unless a more specific one has been attached
+pub fn exit_code_of(error: &anyhow::Error) -> ExitCode {
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<Failure>())
+        .map_or(ExitCode::Error, |failure| failure.exit_code)
+}
+
+#[test]
+fn test_exit_codes() {
+    use anyhow::{anyhow, Context};
+
+    let result: anyhow::Result<()> = Err(anyhow!("command failed"));
+    let error = result
+        .exit_code(ExitCode::MeasurementFailure)
+        .exit_code(ExitCode::PreflightFailed)
+        .context("benchmark failed")
+        .unwrap_err();
+    assert_eq!(exit_code_of(&error), ExitCode::MeasurementFailure);
+    assert_eq!(format!("{error:#}"), "benchmark failed: command failed");
+
+    assert_eq!(exit_code_of(&anyhow!("other")), ExitCode::Error);
 }
@@ -1,8 +1,31 @@
+use super::columns::Column;
 use super::markup::Alignment;
 use crate::export::markup::MarkupExporter;
+use crate::options::{Precision, RelativeError, RelativeMetric};
 
 #[derive(Default)]
-pub struct AsciidocExporter {}
+pub struct AsciidocExporter {
+    columns: Option<Vec<Column>>,
+    precision: Precision,
+    relative_metric: RelativeMetric,
+    relative_error: RelativeError,
+}
+
+impl AsciidocExporter {
+    pub fn new(
+        columns: Option<Vec<Column>>,
+        precision: Precision,
+        relative_metric: RelativeMetric,
+        relative_error: RelativeError,
+    ) -> Self {
+        AsciidocExporter {
+            columns,
+            precision,
+            relative_metric,
+            relative_error,
+        }
+    }
+}
 
 impl MarkupExporter for AsciidocExporter {
     fn table_header(&self, cell_aligmnents: &[Alignment]) -> String {
@@ -19,7 +42,7 @@ impl MarkupExporter for AsciidocExporter {
         )
     }
 
-    fn table_footer(&self, _cell_aligmnents: &[Alignment]) -> String {
+    fn table_footer(&self, _columns: &[Column]) -> String {
         "|===\n".to_string()
     }
 
@@ -34,6 +57,22 @@ impl MarkupExporter for AsciidocExporter {
     fn command(&self, cmd: &str) -> String {
         format!("`{cmd}`")
     }
+
+    fn columns(&self) -> Option<&[Column]> {
+        self.columns.as_deref()
+    }
+
+    fn precision(&self) -> Precision {
+        self.precision
+    }
+
+    fn relative_metric(&self) -> RelativeMetric {
+        self.relative_metric
+    }
+
+    fn relative_error(&self) -> RelativeError {
+        self.relative_error
+    }
 }
 
 /// Check Asciidoc-based data row formatting
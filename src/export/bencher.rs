@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::to_vec_pretty;
+
+use super::Exporter;
+use crate::benchmark::benchmark_result::BenchmarkResult;
+use crate::options::{MemoryUnit, SortOrder};
+use crate::quantity::{nanosecond, Quantity, Time, TimeUnit};
+
+use anyhow::Result;
+
+/// A single value with its bounds, as expected by continuous benchmarking tools
+#[derive(Debug, Serialize, PartialEq)]
+struct Metric {
+    value: f64,
+    lower_value: f64,
+    upper_value: f64,
+}
+
+/// Exports the mean wall clock time of each benchmark in the Bencher Metric Format (BMF),
+/// which can be ingested by Bencher and similar continuous benchmarking dashboards. The
+/// time is given in nanoseconds (the unit of the 'latency' measure), and the bounds are
+/// one standard deviation around the mean.
+#[derive(Default)]
+pub struct BencherExporter {}
+
+impl Exporter for BencherExporter {
+    fn serialize(
+        &self,
+        results: &[BenchmarkResult],
+        _time_unit: Option<TimeUnit>,
+        _memory_unit: Option<MemoryUnit>,
+        _sort_order: SortOrder,
+    ) -> Result<Vec<u8>> {
+        let mut benchmarks = BTreeMap::new();
+        for result in results {
+            let mean = result.mean_wall_clock_time();
+            let stddev = result.measurements.stddev().unwrap_or_else(Time::zero);
+            let metric = Metric {
+                value: mean.get::<nanosecond>(),
+                lower_value: (mean - stddev).get::<nanosecond>().max(0.0),
+                upper_value: (mean + stddev).get::<nanosecond>(),
+            };
+
+            // Benchmark names have to be unique
+            let name = result.command_with_unused_parameters();
+            let mut unique_name = name.clone();
+            for n in 2.. {
+                if !benchmarks.contains_key(&unique_name) {
+                    break;
+                }
+                unique_name = format!("{name} ({n})");
+            }
+            benchmarks.insert(unique_name, BTreeMap::from([("latency", metric)]));
+        }
+
+        let mut output = to_vec_pretty(&benchmarks)?;
+        output.push(b'\n');
+        Ok(output)
+    }
+}
+
+#[test]
+fn test_bencher_export() {
+    use crate::benchmark::measurement::{Measurement, Measurements};
+    use crate::quantity::second;
+
+    let result = |command: &str, times: &[f64]| BenchmarkResult {
+        command: command.into(),
+        measurements: Measurements::new(
+            times
+                .iter()
+                .map(|&time| Measurement {
+                    time_wall_clock: Time::new::<second>(time),
+                    ..Default::default()
+                })
+                .collect(),
+        ),
+        ..Default::default()
+    };
+
+    let results = vec![
+        result("sleep 0.1", &[0.09, 0.11]),
+        result("sleep 2", &[2.0]),
+        result("sleep 2", &[2.0]),
+    ];
+    let output = BencherExporter::default()
+        .serialize(&results, None, None, SortOrder::Command)
+        .unwrap();
+    let output: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    let latency = &output["sleep 0.1"]["latency"];
+    assert!((latency["value"].as_f64().unwrap() - 1e8).abs() < 1e-3);
+    assert!(latency["lower_value"].as_f64().unwrap() < 1e8);
+    assert!(latency["upper_value"].as_f64().unwrap() > 1e8);
+
+    assert_eq!(output["sleep 2"]["latency"]["value"], 2e9);
+    assert_eq!(output["sleep 2"]["latency"]["lower_value"], 2e9);
+    assert_eq!(output["sleep 2 (2)"]["latency"]["value"], 2e9);
+}
@@ -0,0 +1,66 @@
+//! Copying exports to the system clipboard (`--export-clipboard`). hyperfine does not talk to
+//! the clipboard directly, but pipes the content into the clipboard tool of the platform.
+
+use std::io::{ErrorKind, Write};
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+
+/// Programs (with arguments) that copy their standard input to the clipboard, in the order
+/// in which they are tried
+pub fn clipboard_programs() -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        vec![("pbcopy", &[])]
+    } else if cfg!(windows) {
+        vec![("clip.exe", &[])]
+    } else {
+        let mut programs: Vec<(&str, &[&str])> = vec![
+            ("xclip", &["-selection", "clipboard"]),
+            ("xsel", &["--clipboard", "--input"]),
+        ];
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            programs.insert(0, ("wl-copy", &[]));
+        }
+        programs
+    }
+}
+
+/// Copy the given content to the system clipboard
+pub fn copy(content: &[u8]) -> Result<()> {
+    let programs = clipboard_programs();
+    for (program, arguments) in &programs {
+        let mut child = match Command::new(program)
+            .args(*arguments)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(e).with_context(|| format!("Could not run '{program}'")),
+        };
+
+        let error_context =
+            || format!("Could not copy the export to the clipboard via '{program}'");
+        child
+            .stdin
+            .take()
+            .expect("stdin is piped")
+            .write_all(content)
+            .with_context(error_context)?;
+        let status = child.wait().with_context(error_context)?;
+        if !status.success() {
+            bail!("{} ({status})", error_context());
+        }
+        return Ok(());
+    }
+
+    bail!(
+        "Could not copy the export to the clipboard: none of the clipboard tools {} was found",
+        programs
+            .iter()
+            .map(|(program, _)| format!("'{program}'"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+}
@@ -0,0 +1,198 @@
+//! The columns of the Markdown, AsciiDoc and Org-mode tables. Each column is defined once
+//! here, and the exporters render the subset that is selected with `--export-columns`.
+
+use crate::benchmark::relative_speed::BenchmarkResultWithRelativeSpeed;
+use crate::options::Precision;
+use crate::output::glyphs::glyphs;
+use crate::quantity::{Information, InformationUnit, IsUnit, Quantity, Time, TimeUnit};
+
+use super::markup::Alignment;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    /// The group of the command (see `--group`)
+    Group,
+
+    /// The benchmarked command
+    Command,
+
+    /// Mean wall clock time ± standard deviation
+    Mean,
+
+    /// Median wall clock time
+    Median,
+
+    /// Minimum wall clock time
+    Min,
+
+    /// Maximum wall clock time
+    Max,
+
+    /// Mean user time
+    User,
+
+    /// Mean system time
+    System,
+
+    /// Mean peak memory usage
+    Memory,
+
+    /// Mean peak private bytes (Windows only)
+    PrivateBytes,
+
+    /// Mean peak working set (Windows only)
+    WorkingSet,
+
+    /// Mean wall clock time relative to the fastest command
+    Relative,
+
+    /// 95% confidence interval of the relative speed
+    RelativeCi,
+}
+
+/// The columns of a table if none are selected. The memory column is added in front of the
+/// relative speed if a memory unit is given.
+pub const DEFAULT_COLUMNS: &[Column] = &[
+    Column::Command,
+    Column::Mean,
+    Column::Min,
+    Column::Max,
+    Column::Relative,
+];
+
+impl Column {
+    /// The column with the given name, as used on the command line
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "group" => Some(Column::Group),
+            "command" => Some(Column::Command),
+            "mean" => Some(Column::Mean),
+            "median" => Some(Column::Median),
+            "min" => Some(Column::Min),
+            "max" => Some(Column::Max),
+            "user" => Some(Column::User),
+            "system" => Some(Column::System),
+            "memory" => Some(Column::Memory),
+            "private-bytes" => Some(Column::PrivateBytes),
+            "working-set" => Some(Column::WorkingSet),
+            "relative" => Some(Column::Relative),
+            "relative-ci" => Some(Column::RelativeCi),
+            _ => None,
+        }
+    }
+
+    /// The default columns, with or without the memory column
+    pub fn defaults(with_memory: bool) -> Vec<Self> {
+        let mut columns = DEFAULT_COLUMNS.to_vec();
+        if with_memory {
+            columns.insert(columns.len() - 1, Column::Memory);
+        }
+        columns
+    }
+
+    pub fn alignment(self) -> Alignment {
+        match self {
+            Column::Group | Column::Command => Alignment::Left,
+            _ => Alignment::Right,
+        }
+    }
+
+    pub fn header(self, time_unit: TimeUnit, memory_unit: InformationUnit) -> String {
+        let notation = format!("[{}]", time_unit.short_name());
+        match self {
+            Column::Group => "Group".to_string(),
+            Column::Command => "Command".to_string(),
+            Column::Mean => format!("Mean {notation}"),
+            Column::Median => format!("Median {notation}"),
+            Column::Min => format!("Min {notation}"),
+            Column::Max => format!("Max {notation}"),
+            Column::User => format!("User {notation}"),
+            Column::System => format!("System {notation}"),
+            Column::Memory => format!("Memory [{}]", memory_unit.short_name()),
+            Column::PrivateBytes => format!("Private bytes [{}]", memory_unit.short_name()),
+            Column::WorkingSet => format!("Working set [{}]", memory_unit.short_name()),
+            Column::Relative => "Relative".to_string(),
+            Column::RelativeCi => "Relative (95% CI)".to_string(),
+        }
+    }
+
+    /// The content of the cell of this column for the given result. The command is not
+    /// formatted yet, as that depends on the markup language.
+    pub fn cell(
+        self,
+        entry: &BenchmarkResultWithRelativeSpeed,
+        time_unit: TimeUnit,
+        memory_unit: InformationUnit,
+        precision: Precision,
+    ) -> String {
+        let result = &entry.result;
+        let measurements = &result.measurements;
+        let time_precision = precision
+            .time
+            .unwrap_or_else(|| time_unit.preferred_precision());
+        let time = |time: Time| time.format_with_precision(time_unit, time_precision);
+        let memory_precision = precision
+            .memory
+            .unwrap_or_else(|| memory_unit.preferred_precision());
+        let memory = |size: Option<Information>| match size {
+            Some(size) => size.format_with_precision(memory_unit, memory_precision),
+            None => "n/a".into(),
+        };
+        let relative_precision = precision.relative.unwrap_or(2);
+        match self {
+            Column::Group => result.group.clone().unwrap_or_default().replace('|', "\\|"),
+            Column::Command => result.command_with_unused_parameters().replace('|', "\\|"),
+            Column::Mean => {
+                let mean_str = time(result.mean_wall_clock_time());
+                let stddev_str = if let Some(stddev) = measurements.stddev() {
+                    format!(" {} {}", glyphs().plus_minus, time(stddev))
+                } else {
+                    "".into()
+                };
+                format!("{mean_str}{stddev_str}")
+            }
+            Column::Median => time(measurements.median()),
+            Column::Min => time(measurements.min()),
+            Column::Max => time(measurements.max()),
+            Column::User => time(measurements.time_user_mean()),
+            Column::System => time(measurements.time_system_mean()),
+            Column::Memory => memory(Some(measurements.peak_memory_usage_mean())),
+            Column::PrivateBytes => memory(measurements.windows_memory_means().0),
+            Column::WorkingSet => memory(measurements.windows_memory_means().1),
+            Column::Relative => {
+                let rel_str = format!("{:.relative_precision$}", entry.relative_speed);
+                let rel_stddev_str = if entry.is_reference {
+                    "".into()
+                } else if let Some(stddev) = entry.relative_speed_stddev {
+                    format!(" {} {stddev:.relative_precision$}", glyphs().plus_minus)
+                } else {
+                    "".into()
+                };
+                format!("{rel_str}{rel_stddev_str}")
+            }
+            Column::RelativeCi => match entry.relative_speed_ci {
+                Some((lower, upper)) if !entry.is_reference => format!(
+                    "{lower:.relative_precision$} {} {upper:.relative_precision$}",
+                    glyphs().ellipsis
+                ),
+                _ => "".into(),
+            },
+        }
+    }
+}
+
+#[test]
+fn test_default_columns() {
+    assert_eq!(Column::defaults(false), DEFAULT_COLUMNS);
+    assert_eq!(
+        Column::defaults(true),
+        [
+            Column::Command,
+            Column::Mean,
+            Column::Min,
+            Column::Max,
+            Column::Memory,
+            Column::Relative
+        ]
+    );
+}
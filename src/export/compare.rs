@@ -0,0 +1,473 @@
+//! Comparison of the results of two sessions, e.g. before and after a change (see
+//! 'hyperfine compare'). For parameter scans, the change of the mean time is shown for each
+//! combination of parameter values, and can be exported as a heatmap of the parameter space.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+
+use anyhow::{bail, Context, Result};
+use clap::ArgMatches;
+use colored::*;
+use serde_json::{json, Map, Value};
+
+use super::import::read_results;
+use crate::benchmark::benchmark_result::{BenchmarkResult, Parameter};
+use crate::quantity::{second, Quantity, Time};
+
+/// A benchmark that appears in both sessions
+#[derive(Debug, Clone)]
+struct Comparison {
+    command: String,
+    parameters: BTreeMap<String, Parameter>,
+    baseline: Time,
+    candidate: Time,
+}
+
+impl Comparison {
+    /// The relative change of the mean time, e.g. 0.2 if the candidate is 20% slower
+    fn change(&self) -> f64 {
+        self.candidate.get::<second>() / self.baseline.get::<second>() - 1.0
+    }
+
+    /// Order by the parameter values (by name), skipping the first `skip` parameters
+    fn compare_parameters(&self, other: &Comparison, skip: usize) -> Ordering {
+        self.parameters
+            .values()
+            .zip(other.parameters.values())
+            .skip(skip)
+            .map(|(a, b)| a.compare_values(b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+
+    /// 'n = 3, m = 2', optionally followed by the command
+    fn label(&self, skip: usize, with_command: bool) -> String {
+        let mut parts: Vec<_> = self
+            .parameters
+            .iter()
+            .skip(skip)
+            .map(|(name, parameter)| format!("{name} = {}", parameter.value))
+            .collect();
+        if with_command {
+            parts.push(format!("'{}'", self.command));
+        }
+        parts.join(", ")
+    }
+}
+
+fn parameter_values(result: &BenchmarkResult) -> Vec<(&str, &str)> {
+    result
+        .parameters
+        .iter()
+        .map(|(name, parameter)| (name.as_str(), parameter.value.as_str()))
+        .collect()
+}
+
+fn is_same_benchmark(a: &BenchmarkResult, b: &BenchmarkResult) -> bool {
+    a.command == b.command && parameter_values(a) == parameter_values(b)
+}
+
+/// The benchmarks of two sessions, matched by their command and parameter values
+#[derive(Debug)]
+struct SessionComparison {
+    /// The benchmarks that appear in both sessions, ordered by their parameter values
+    comparisons: Vec<Comparison>,
+
+    /// The names of all parameters
+    parameter_names: Vec<String>,
+
+    /// Whether the command is needed to tell the benchmarks apart, i.e. there are no
+    /// parameters or several commands with the same parameter values
+    shows_command: bool,
+
+    only_in_baseline: usize,
+    only_in_candidate: usize,
+}
+
+impl SessionComparison {
+    fn new(baseline: &[BenchmarkResult], candidate: &[BenchmarkResult]) -> Self {
+        let mut comparisons: Vec<_> = baseline
+            .iter()
+            .filter_map(|result| {
+                let other = candidate
+                    .iter()
+                    .find(|other| is_same_benchmark(result, other))?;
+                Some(Comparison {
+                    command: result.command.clone(),
+                    parameters: result.parameters.clone(),
+                    baseline: result.mean_wall_clock_time(),
+                    candidate: other.mean_wall_clock_time(),
+                })
+            })
+            .collect();
+        comparisons.sort_by(|a, b| a.compare_parameters(b, 0));
+
+        let mut parameter_names: Vec<String> = comparisons
+            .iter()
+            .flat_map(|comparison| comparison.parameters.keys().cloned())
+            .collect();
+        parameter_names.sort();
+        parameter_names.dedup();
+
+        let shows_command = parameter_names.is_empty()
+            || comparisons.windows(2).any(|pair| {
+                pair[0].compare_parameters(&pair[1], 0).is_eq()
+                    && pair[0].label(0, false) == pair[1].label(0, false)
+            });
+
+        let only_in = |results: &[BenchmarkResult], others: &[BenchmarkResult]| {
+            results
+                .iter()
+                .filter(|result| !others.iter().any(|other| is_same_benchmark(result, other)))
+                .count()
+        };
+
+        SessionComparison {
+            comparisons,
+            parameter_names,
+            shows_command,
+            only_in_baseline: only_in(baseline, candidate),
+            only_in_candidate: only_in(candidate, baseline),
+        }
+    }
+
+    /// The rows of the delta table (with the header first), and the change of each row
+    fn table(&self) -> Vec<(Vec<String>, Option<f64>)> {
+        let mut header = self.parameter_names.clone();
+        if self.shows_command {
+            header.push("Command".into());
+        }
+        header.extend(["Baseline", "Candidate", "Change"].map(String::from));
+
+        let rows = self.comparisons.iter().map(|comparison| {
+            let mut row: Vec<_> = self
+                .parameter_names
+                .iter()
+                .map(|name| {
+                    comparison
+                        .parameters
+                        .get(name)
+                        .map_or(String::new(), |parameter| parameter.value.clone())
+                })
+                .collect();
+            if self.shows_command {
+                row.push(comparison.command.clone());
+            }
+            for time in [comparison.baseline, comparison.candidate] {
+                row.push(time.format(time.suitable_unit()));
+            }
+            row.push(format!("{:+.1} %", comparison.change() * 100.0));
+            (row, Some(comparison.change()))
+        });
+
+        std::iter::once((header, None)).chain(rows).collect()
+    }
+
+    /// The benchmark with the largest slowdown, if any of them became slower
+    fn largest_regression(&self) -> Option<&Comparison> {
+        self.comparisons
+            .iter()
+            .filter(|comparison| comparison.change() > 0.0)
+            .max_by(|a, b| a.change().total_cmp(&b.change()))
+    }
+
+    fn print(&self, baseline: &str, candidate: &str) {
+        println!(
+            "{} of '{candidate}' with '{baseline}' (mean wall clock time):\n",
+            "Comparison".bold()
+        );
+
+        let table = self.table();
+        let num_labels = table[0].0.len() - 3;
+        let widths: Vec<_> = (0..table[0].0.len())
+            .map(|i| {
+                table
+                    .iter()
+                    .map(|(row, _)| row[i].chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        for (row, change) in &table {
+            let cells: Vec<_> = row
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(i, (cell, &width))| {
+                    let cell = if i < num_labels {
+                        format!("{cell:<width$}")
+                    } else {
+                        format!("{cell:>width$}")
+                    };
+                    match change {
+                        None => cell.bold().to_string(),
+                        Some(change) if i == row.len() - 1 && *change > 0.0 => {
+                            cell.red().to_string()
+                        }
+                        Some(change) if i == row.len() - 1 && *change < 0.0 => {
+                            cell.green().to_string()
+                        }
+                        Some(_) => cell,
+                    }
+                })
+                .collect();
+            println!("  {}", cells.join("   ").trim_end());
+        }
+
+        if let Some(regression) = self.largest_regression() {
+            println!(
+                "\n{}: {} ({})",
+                "Largest regression".bold(),
+                regression.label(0, self.shows_command),
+                format!("{:+.1} %", regression.change() * 100.0).red()
+            );
+        }
+        if self.only_in_baseline > 0 || self.only_in_candidate > 0 {
+            println!(
+                "\n{} of the benchmarks in '{baseline}' and {} of those in '{candidate}' have \
+                 no counterpart in the other file",
+                self.only_in_baseline, self.only_in_candidate,
+            );
+        }
+    }
+
+    /// A Vega-Lite specification of a heatmap of the changes. The first parameter is shown
+    /// on the x axis, all other parameters (and the command, if needed) on the y axis.
+    fn heatmap(&self) -> Value {
+        let field = |name: &str| format!("parameter_{name}");
+        let (x_field, x_title, y_skip) = match self.parameter_names.first() {
+            Some(name) => (field(name), name.clone(), 1),
+            None => ("command".to_string(), "Command".to_string(), 0),
+        };
+        let y_with_command = self.shows_command && y_skip > 0;
+        let has_y = self.parameter_names.len() > 1 || y_with_command;
+
+        let values: Vec<_> = self
+            .comparisons
+            .iter()
+            .map(|comparison| {
+                let mut row = Map::new();
+                row.insert("command".into(), comparison.command.clone().into());
+                for (name, parameter) in &comparison.parameters {
+                    row.insert(field(name), parameter.value.clone().into());
+                }
+                if has_y {
+                    row.insert(
+                        "benchmark".into(),
+                        comparison.label(y_skip, y_with_command).into(),
+                    );
+                }
+                row.insert(
+                    "baseline".into(),
+                    comparison.baseline.get::<second>().into(),
+                );
+                row.insert(
+                    "candidate".into(),
+                    comparison.candidate.get::<second>().into(),
+                );
+                row.insert("change".into(), (comparison.change() * 100.0).into());
+                Value::Object(row)
+            })
+            .collect();
+
+        let distinct = |labels: Vec<String>| {
+            let mut distinct: Vec<String> = vec![];
+            for label in labels {
+                if !distinct.contains(&label) {
+                    distinct.push(label);
+                }
+            }
+            distinct
+        };
+        let x_order = distinct(
+            self.comparisons
+                .iter()
+                .map(|comparison| match self.parameter_names.first() {
+                    Some(name) => comparison
+                        .parameters
+                        .get(name)
+                        .map_or(String::new(), |parameter| parameter.value.clone()),
+                    None => comparison.command.clone(),
+                })
+                .collect(),
+        );
+
+        let mut encoding = json!({
+            "x": { "field": x_field, "type": "ordinal", "title": x_title, "sort": x_order },
+        });
+        if has_y {
+            let mut by_y = self.comparisons.clone();
+            by_y.sort_by(|a, b| a.compare_parameters(b, y_skip));
+            let y_order = distinct(
+                by_y.iter()
+                    .map(|comparison| comparison.label(y_skip, y_with_command))
+                    .collect(),
+            );
+            encoding["y"] = json!({
+                "field": "benchmark", "type": "ordinal", "title": null, "sort": y_order
+            });
+        }
+
+        let mut tooltip = vec![json!({ "field": "command", "title": "Command" })];
+        tooltip.extend(
+            self.parameter_names
+                .iter()
+                .map(|name| json!({ "field": field(name), "title": name })),
+        );
+        tooltip.extend([
+            json!({ "field": "baseline", "title": "Baseline [s]", "format": ".4g" }),
+            json!({ "field": "candidate", "title": "Candidate [s]", "format": ".4g" }),
+            json!({ "field": "change", "title": "Change [%]", "format": "+.1f" }),
+        ]);
+
+        json!({
+            "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+            "title": "Change of the mean wall clock time (candidate vs. baseline)",
+            "data": { "values": values },
+            "encoding": encoding,
+            "layer": [
+                {
+                    "mark": "rect",
+                    "encoding": {
+                        "color": {
+                            "field": "change",
+                            "type": "quantitative",
+                            "title": "Change [%]",
+                            "scale": { "scheme": "redblue", "reverse": true, "domainMid": 0 }
+                        },
+                        "tooltip": tooltip
+                    }
+                },
+                {
+                    "mark": "text",
+                    "encoding": {
+                        "text": { "field": "change", "type": "quantitative", "format": "+.1f" }
+                    }
+                }
+            ]
+        })
+    }
+}
+
+/// A standalone web page that renders the given Vega-Lite specification
+fn heatmap_page(specification: &Value) -> String {
+    // A '</script>' in a command must not end the script element
+    let specification = specification.to_string().replace("</", "<\\/");
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+  <meta charset=\"utf-8\">
+  <title>hyperfine comparison</title>
+  <script src=\"https://cdn.jsdelivr.net/npm/vega@5\"></script>
+  <script src=\"https://cdn.jsdelivr.net/npm/vega-lite@5\"></script>
+  <script src=\"https://cdn.jsdelivr.net/npm/vega-embed@6\"></script>
+</head>
+<body>
+  <div id=\"heatmap\"></div>
+  <script>
+    vegaEmbed(\"#heatmap\", {specification});
+  </script>
+</body>
+</html>
+"
+    )
+}
+
+/// Run the 'compare' subcommand
+pub fn run_compare(matches: &ArgMatches) -> Result<()> {
+    let filename = |name: &str| {
+        matches
+            .get_one::<String>(name)
+            .expect("the files are required")
+            .as_str()
+    };
+    let (baseline, candidate) = (filename("baseline"), filename("candidate"));
+
+    let comparison = SessionComparison::new(&read_results(baseline)?, &read_results(candidate)?);
+    if comparison.comparisons.is_empty() {
+        bail!("None of the benchmarks in '{baseline}' appear in '{candidate}'");
+    }
+    comparison.print(baseline, candidate);
+
+    let specification = comparison.heatmap();
+    if let Some(filename) = matches.get_one::<String>("export-vega") {
+        fs::write(
+            filename,
+            serde_json::to_string_pretty(&specification)? + "\n",
+        )
+        .with_context(|| format!("Could not write the heatmap to '{filename}'"))?;
+    }
+    if let Some(filename) = matches.get_one::<String>("export-html") {
+        fs::write(filename, heatmap_page(&specification))
+            .with_context(|| format!("Could not write the heatmap to '{filename}'"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+fn scan(times: &[(&str, &str, f64)]) -> Vec<BenchmarkResult> {
+    use crate::benchmark::measurement::{Measurement, Measurements};
+
+    times
+        .iter()
+        .map(|(n, m, time)| BenchmarkResult {
+            command: format!("run {n} {m}"),
+            parameters: [("n", n), ("m", m)]
+                .iter()
+                .map(|(name, value)| (name.to_string(), Parameter::new(value.to_string(), false)))
+                .collect(),
+            measurements: Measurements::new(vec![Measurement {
+                time_wall_clock: Time::new::<second>(*time),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        })
+        .collect()
+}
+
+#[test]
+fn test_session_comparison() {
+    let baseline = scan(&[
+        ("10", "1", 1.0),
+        ("2", "1", 1.0),
+        ("2", "2", 2.0),
+        ("4", "1", 1.0),
+    ]);
+    let candidate = scan(&[
+        ("2", "2", 1.0),
+        ("2", "1", 1.5),
+        ("10", "1", 1.1),
+        ("8", "1", 1.0),
+    ]);
+    let comparison = SessionComparison::new(&baseline, &candidate);
+
+    assert_eq!(comparison.parameter_names, ["m", "n"]);
+    assert!(!comparison.shows_command);
+    assert_eq!(comparison.only_in_baseline, 1);
+    assert_eq!(comparison.only_in_candidate, 1);
+
+    // Ordered numerically by the parameter values
+    let table = comparison.table();
+    assert_eq!(table[0].0, ["m", "n", "Baseline", "Candidate", "Change"]);
+    assert_eq!(table[1].0[..2], ["1", "2"]);
+    assert_eq!(table[1].0[4], "+50.0 %");
+    assert_eq!(table[2].0[..2], ["1", "10"]);
+    assert_eq!(table[3].0[..2], ["2", "2"]);
+    assert_eq!(table[3].0[4], "-50.0 %");
+
+    assert_eq!(
+        comparison.largest_regression().unwrap().label(0, false),
+        "m = 1, n = 2"
+    );
+
+    let heatmap = comparison.heatmap();
+    assert_eq!(heatmap["encoding"]["x"]["field"], "parameter_m");
+    assert_eq!(heatmap["encoding"]["x"]["sort"], json!(["1", "2"]));
+    assert_eq!(heatmap["encoding"]["y"]["sort"], json!(["n = 2", "n = 10"]));
+    assert_eq!(heatmap["data"]["values"][0]["benchmark"], "n = 2");
+    assert_eq!(heatmap["data"]["values"][0]["change"], 50.0);
+
+    assert!(heatmap_page(&json!({ "title": "</script>" })).contains("<\\/script>"));
+}
@@ -0,0 +1,105 @@
+//! Compression of export files, based on their file extension (e.g.
+//! `--export-json results.json.gz`). Like the clipboard export, this does not link a
+//! compression library, but pipes the content through the command-line tool of the format,
+//! which therefore needs to be installed.
+
+use std::io::{ErrorKind, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+
+use anyhow::{bail, Context, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// gzip (`.gz`)
+    Gzip,
+
+    /// Zstandard (`.zst`)
+    Zstd,
+}
+
+impl Compression {
+    /// The compression format of a file with the given name, if its extension is known
+    pub fn from_filename(filename: &str) -> Option<Self> {
+        [Compression::Gzip, Compression::Zstd]
+            .iter()
+            .copied()
+            .find(|compression| filename.ends_with(compression.extension()))
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Compression::Gzip => ".gz",
+            Compression::Zstd => ".zst",
+        }
+    }
+
+    fn program(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    pub fn compress(self, content: &[u8]) -> Result<Vec<u8>> {
+        self.pipe(&["-c"], content)
+            .with_context(|| format!("Could not compress the export via '{}'", self.program()))
+    }
+
+    pub fn decompress(self, content: &[u8]) -> Result<Vec<u8>> {
+        self.pipe(&["-d", "-c"], content)
+            .with_context(|| format!("Could not decompress the export via '{}'", self.program()))
+    }
+
+    /// Pipe the content through the compression tool and return its output
+    fn pipe(self, arguments: &[&str], content: &[u8]) -> Result<Vec<u8>> {
+        let program = self.program();
+        let mut child = match Command::new(program)
+            .args(arguments)
+            .arg("-q")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                bail!(
+                    "'{program}' was not found. Files ending with '{}' are (de)compressed with \
+                     the external '{program}' program, which needs to be installed.",
+                    self.extension()
+                )
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        // Write the input in the background, such that a full output pipe can not block it
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let content = content.to_vec();
+        let writer = thread::spawn(move || stdin.write_all(&content));
+
+        let output = child.wait_with_output()?;
+        writer.join().expect("writer thread does not panic")?;
+        if !output.status.success() {
+            bail!(
+                "{} ({})",
+                String::from_utf8_lossy(&output.stderr).trim(),
+                output.status
+            );
+        }
+        Ok(output.stdout)
+    }
+}
+
+#[test]
+fn test_compression_from_filename() {
+    assert_eq!(
+        Compression::from_filename("results.json.gz"),
+        Some(Compression::Gzip)
+    );
+    assert_eq!(
+        Compression::from_filename("results.json.zst"),
+        Some(Compression::Zstd)
+    );
+    assert_eq!(Compression::from_filename("results.json"), None);
+}
@@ -4,25 +4,49 @@ use csv::WriterBuilder;
 
 use super::Exporter;
 use crate::benchmark::benchmark_result::BenchmarkResult;
-use crate::options::SortOrder;
+use crate::options::{CsvFormat, MemoryUnit, Precision, SortOrder};
 use crate::quantity::{Quantity, TimeUnit};
 
 use anyhow::Result;
 
 #[derive(Default)]
-pub struct CsvExporter {}
+pub struct CsvExporter {
+    format: CsvFormat,
+    precision: Precision,
+}
+
+impl CsvExporter {
+    pub fn new(format: CsvFormat, precision: Precision) -> Self {
+        CsvExporter { format, precision }
+    }
+}
 
 impl Exporter for CsvExporter {
     fn serialize(
         &self,
         results: &[BenchmarkResult],
         _time_unit: Option<TimeUnit>,
+        _memory_unit: Option<MemoryUnit>,
         _sort_order: SortOrder,
     ) -> Result<Vec<u8>> {
         const CSV_UNIT: TimeUnit = TimeUnit::Second;
         const CSV_PRECISION: usize = 6;
+        let precision = self.precision.time.unwrap_or(CSV_PRECISION);
 
-        let mut writer = WriterBuilder::new().from_writer(vec![]);
+        let mut writer = WriterBuilder::new()
+            .delimiter(self.format.delimiter)
+            .from_writer(vec![]);
+
+        // The column is only added if needed, to keep the format stable otherwise
+        let any_too_slow = results.iter().any(|res| res.measurements.too_slow);
+        let any_deadline_exceeded = results.iter().any(|res| res.measurements.deadline_exceeded);
+        let any_too_noisy = results.iter().any(|res| res.measurements.too_noisy);
+        let any_timed_out = results.iter().any(|res| res.measurements.timed_out);
+        let any_grouped = results.iter().any(|res| res.group.is_some());
+        let derived_metrics: Vec<String> = results
+            .first()
+            .map(|res| res.measurements.derived_statistics().into_keys().collect())
+            .unwrap_or_default();
 
         {
             let mut headers: Vec<Cow<[u8]>> = [
@@ -37,6 +61,25 @@ impl Exporter for CsvExporter {
                     headers.push(Cow::Owned(format!("parameter_{param_name}").into_bytes()));
                 }
             }
+            for metric in &derived_metrics {
+                headers.push(Cow::Owned(format!("{metric}_mean").into_bytes()));
+                headers.push(Cow::Owned(format!("{metric}_stddev").into_bytes()));
+            }
+            if any_too_slow {
+                headers.push(Cow::Borrowed(b"too_slow"));
+            }
+            if any_deadline_exceeded {
+                headers.push(Cow::Borrowed(b"deadline_exceeded"));
+            }
+            if any_too_noisy {
+                headers.push(Cow::Borrowed(b"too_noisy"));
+            }
+            if any_timed_out {
+                headers.push(Cow::Borrowed(b"timed_out"));
+            }
+            if any_grouped {
+                headers.push(Cow::Borrowed(b"group"));
+            }
             writer.write_record(headers)?;
         }
 
@@ -52,13 +95,61 @@ impl Exporter for CsvExporter {
                 res.measurements.max(),
             ] {
                 fields.push(Cow::Owned(
-                    f.format_with_precision(CSV_UNIT, CSV_PRECISION)
+                    self.format
+                        .number(f.format_with_precision(CSV_UNIT, precision))
                         .into_bytes(),
                 ))
             }
             for v in res.parameters.values() {
                 fields.push(Cow::Borrowed(v.value.as_bytes()))
             }
+            let derived_statistics = res.measurements.derived_statistics();
+            for metric in &derived_metrics {
+                let (mean, stddev) = derived_statistics
+                    .get(metric)
+                    .copied()
+                    .unwrap_or((f64::NAN, None));
+                for value in [mean, stddev.unwrap_or_default()] {
+                    fields.push(Cow::Owned(
+                        self.format
+                            .number(format!("{value:.precision$}"))
+                            .into_bytes(),
+                    ));
+                }
+            }
+            if any_too_slow {
+                fields.push(Cow::Borrowed(if res.measurements.too_slow {
+                    b"true"
+                } else {
+                    b"false"
+                }));
+            }
+            if any_deadline_exceeded {
+                fields.push(Cow::Borrowed(if res.measurements.deadline_exceeded {
+                    b"true"
+                } else {
+                    b"false"
+                }));
+            }
+            if any_too_noisy {
+                fields.push(Cow::Borrowed(if res.measurements.too_noisy {
+                    b"true"
+                } else {
+                    b"false"
+                }));
+            }
+            if any_timed_out {
+                fields.push(Cow::Borrowed(if res.measurements.timed_out {
+                    b"true"
+                } else {
+                    b"false"
+                }));
+            }
+            if any_grouped {
+                fields.push(Cow::Borrowed(
+                    res.group.as_deref().unwrap_or_default().as_bytes(),
+                ));
+            }
             writer.write_record(fields)?;
         }
 
@@ -87,6 +178,7 @@ fn test_csv() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(8.0),
@@ -94,6 +186,7 @@ fn test_csv() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(12.0),
@@ -101,26 +194,26 @@ fn test_csv() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
             ]),
             parameters: {
                 let mut params = BTreeMap::new();
-                params.insert(
-                    "foo".into(),
-                    Parameter {
-                        value: "one".into(),
-                        is_unused: false,
-                    },
-                );
-                params.insert(
-                    "bar".into(),
-                    Parameter {
-                        value: "two".into(),
-                        is_unused: false,
-                    },
-                );
+                params.insert("foo".into(), Parameter::new("one".into(), false));
+                params.insert("bar".into(), Parameter::new("two".into(), false));
                 params
             },
+            command_template: None,
+            shell_spawning_time: None,
+            captured_output: None,
+            modes: vec![],
+            run_indices: None,
+            execution_order: None,
+            reproduce: None,
+            git: None,
+            stateful: false,
+            group: None,
+            warnings: vec![],
         },
         BenchmarkResult {
             command: String::from("command_b"),
@@ -131,6 +224,7 @@ fn test_csv() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(18.0),
@@ -138,6 +232,7 @@ fn test_csv() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(19.0),
@@ -145,32 +240,32 @@ fn test_csv() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
             ]),
             parameters: {
                 let mut params = BTreeMap::new();
-                params.insert(
-                    "foo".into(),
-                    Parameter {
-                        value: "one".into(),
-                        is_unused: false,
-                    },
-                );
-                params.insert(
-                    "bar".into(),
-                    Parameter {
-                        value: "seven".into(),
-                        is_unused: false,
-                    },
-                );
+                params.insert("foo".into(), Parameter::new("one".into(), false));
+                params.insert("bar".into(), Parameter::new("seven".into(), false));
                 params
             },
+            command_template: None,
+            shell_spawning_time: None,
+            captured_output: None,
+            modes: vec![],
+            run_indices: None,
+            execution_order: None,
+            reproduce: None,
+            git: None,
+            stateful: false,
+            group: None,
+            warnings: vec![],
         },
     ];
 
     let actual = String::from_utf8(
         exporter
-            .serialize(&results, Some(TimeUnit::Second), SortOrder::Command)
+            .serialize(&results, Some(TimeUnit::Second), None, SortOrder::Command)
             .unwrap(),
     )
     .unwrap();
@@ -181,3 +276,71 @@ fn test_csv() {
     command_b,18.000000,1.000000,18.000000,18.000000,0.000000,17.000000,19.000000,seven,one
     "#);
 }
+
+#[test]
+fn test_csv_with_decimal_comma() {
+    use crate::benchmark::measurement::{Measurement, Measurements};
+    use crate::quantity::{second, Time};
+
+    let exporter = CsvExporter::new(
+        CsvFormat {
+            delimiter: b';',
+            decimal_comma: true,
+        },
+        Precision::default(),
+    );
+
+    let results = vec![BenchmarkResult {
+        command: String::from("sleep 0.5; echo done"),
+        measurements: Measurements::new(vec![Measurement {
+            time_wall_clock: Time::new::<second>(0.5),
+            ..Default::default()
+        }]),
+        ..Default::default()
+    }];
+
+    let actual = String::from_utf8(
+        exporter
+            .serialize(&results, None, None, SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+
+    insta::assert_snapshot!(actual, @r#"
+    command;mean;stddev;median;user;system;min;max
+    "sleep 0.5; echo done";0,500000;0,000000;0,500000;0,000000;0,000000;0,500000;0,500000
+    "#);
+}
+
+#[test]
+fn test_csv_with_derived_metrics() {
+    use crate::benchmark::measurement::{Measurement, Measurements};
+    use crate::quantity::{second, Time};
+
+    let exporter = CsvExporter::default();
+
+    let run = |time: f64| Measurement {
+        time_wall_clock: Time::new::<second>(time),
+        derived: vec![("gflops".to_string(), 2.0 / time)]
+            .into_iter()
+            .collect(),
+        ..Default::default()
+    };
+    let results = vec![BenchmarkResult {
+        command: String::from("matmul 1000"),
+        measurements: Measurements::new(vec![run(1.0), run(0.5)]),
+        ..Default::default()
+    }];
+
+    let actual = String::from_utf8(
+        exporter
+            .serialize(&results, None, None, SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+
+    insta::assert_snapshot!(actual, @r#"
+    command,mean,stddev,median,user,system,min,max,gflops_mean,gflops_stddev
+    matmul 1000,0.750000,0.353553,0.750000,0.000000,0.000000,0.500000,1.000000,3.000000,1.414214
+    "#);
+}
@@ -0,0 +1,460 @@
+//! Reading of run times that have been measured elsewhere, such that they can be analyzed
+//! like the results of a benchmark (see 'hyperfine analyze')
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::ffi::OsString;
+use std::fs;
+use std::iter;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::ArgMatches;
+use serde_json::Value;
+
+use super::compression::Compression;
+use super::ExportManager;
+use crate::benchmark::benchmark_result::{BenchmarkResult, Parameter};
+use crate::benchmark::measurement::{Measurement, Measurements};
+use crate::benchmark::scheduler::Scheduler;
+use crate::cli::get_cli_arguments;
+use crate::command::Commands;
+use crate::options::Options;
+use crate::output;
+use crate::quantity::{second, Time};
+use crate::util::exit_code::exit_status_from_code;
+
+/// Exit code of runs which have been terminated by a signal (i.e. without an exit code),
+/// such that they are counted as failed runs
+const SIGNALED_EXIT_CODE: i32 = 1;
+
+/// The imported runs, grouped into benchmarks by their command and parameters
+#[derive(Debug, Default)]
+struct ImportedResults(Vec<BenchmarkResult>);
+
+impl ImportedResults {
+    fn add(
+        &mut self,
+        command: &str,
+        parameters: &BTreeMap<String, Parameter>,
+        measurement: Measurement,
+    ) -> &mut BenchmarkResult {
+        match self
+            .0
+            .iter()
+            .position(|result| result.command == command && result.parameters == *parameters)
+        {
+            Some(i) => {
+                self.0[i].measurements.push(measurement);
+                &mut self.0[i]
+            }
+            None => {
+                self.0.push(BenchmarkResult {
+                    command: command.to_string(),
+                    measurements: Measurements::new(vec![measurement]),
+                    parameters: parameters.clone(),
+                    ..Default::default()
+                });
+                self.0.last_mut().unwrap()
+            }
+        }
+    }
+}
+
+/// A time in seconds, either as a plain number or as a quantity of the JSON export
+fn json_seconds(value: &Value) -> Option<f64> {
+    match value {
+        Value::Object(quantity) => quantity.get("value")?.as_f64(),
+        value => value.as_f64(),
+    }
+}
+
+/// The given time, if it is valid, i.e. finite and not negative (which the statistics rely on)
+fn valid_time(time: f64) -> Option<f64> {
+    (time.is_finite() && time >= 0.0).then_some(time)
+}
+
+/// A single run of a JSON export, of a JSON Lines stream, or just its wall clock time
+fn json_run(run: &Value) -> Result<Measurement> {
+    let check = |time: f64| valid_time(time).ok_or_else(|| anyhow!("Invalid time {time}"));
+    let time = |field| run.get(field).and_then(json_seconds).map(check).transpose();
+    let time_wall_clock = match run {
+        Value::Number(_) => json_seconds(run).map(check).transpose()?,
+        _ => time("time_wall_clock")?,
+    }
+    .ok_or_else(|| anyhow!("The run {run} has no wall clock time"))?;
+
+    let exit_code = match run.get("exit_code") {
+        None => 0,
+        Some(Value::Null) => SIGNALED_EXIT_CODE,
+        Some(code) => code
+            .as_i64()
+            .and_then(|code| i32::try_from(code).ok())
+            .ok_or_else(|| anyhow!("Invalid exit code {code}"))?,
+    };
+
+    Ok(Measurement {
+        time_wall_clock: Time::new::<second>(time_wall_clock),
+        time_user: Time::new::<second>(time("time_user")?.unwrap_or(0.0)),
+        time_system: Time::new::<second>(time("time_system")?.unwrap_or(0.0)),
+        exit_status: exit_status_from_code(exit_code),
+        discarded: run.get("discarded").and_then(Value::as_bool) == Some(true),
+        failed_attempt: run.get("failed_attempt").and_then(Value::as_bool) == Some(true),
+        ..Default::default()
+    })
+}
+
+/// Read a JSON export of hyperfine, or an array or a stream of runs (as printed by
+/// '--just-run-format=jsonl')
+fn parse_json(content: &str, default_command: &str) -> Result<Vec<BenchmarkResult>> {
+    let mut results = ImportedResults::default();
+    let no_parameters = BTreeMap::new();
+
+    for value in serde_json::Deserializer::from_str(content).into_iter::<Value>() {
+        let value = value?;
+        let command_of = |value: &Value| {
+            value
+                .get("command")
+                .and_then(Value::as_str)
+                .unwrap_or(default_command)
+                .to_string()
+        };
+
+        if let Some(benchmarks) = value.get("results") {
+            let Some(benchmarks) = benchmarks.as_array() else {
+                bail!("The 'results' of the export are not an array");
+            };
+            for benchmark in benchmarks {
+                let command = command_of(benchmark);
+                let parameters = benchmark
+                    .get("parameters")
+                    .and_then(Value::as_object)
+                    .into_iter()
+                    .flatten()
+                    .map(|(name, parameter)| {
+                        let value = match parameter.get("value").unwrap_or(parameter) {
+                            Value::String(value) => value.clone(),
+                            value => value.to_string(),
+                        };
+                        let is_unused = parameter.get("is_unused").and_then(Value::as_bool);
+                        (name.clone(), Parameter::new(value, is_unused == Some(true)))
+                    })
+                    .collect();
+
+                // Exports of older versions only contain the wall clock times
+                match (benchmark.get("measurements"), benchmark.get("times")) {
+                    (Some(Value::Array(runs)), _) | (None, Some(Value::Array(runs))) => {
+                        let command_template = benchmark
+                            .get("command_template")
+                            .and_then(Value::as_str)
+                            .map(String::from);
+                        for run in runs {
+                            results
+                                .add(&command, &parameters, json_run(run)?)
+                                .command_template = command_template.clone();
+                        }
+                    }
+                    _ => bail!("The benchmark of '{command}' contains no runs"),
+                }
+            }
+        } else if let Value::Array(runs) = value {
+            for run in &runs {
+                results.add(&command_of(run), &no_parameters, json_run(run)?);
+            }
+        } else {
+            results.add(&command_of(&value), &no_parameters, json_run(&value)?);
+        }
+    }
+
+    Ok(results.0)
+}
+
+/// Read a CSV file with a header (like the one written by '--export-runs'), or a plain list
+/// of times in seconds, one per line (as printed by '--just-run')
+fn parse_csv(content: &str, default_command: &str) -> Result<Vec<BenchmarkResult>> {
+    // Exports with '--csv-delimiter' (and '--decimal-comma') are recognized as well
+    let first_line = content.lines().next().unwrap_or_default();
+    let delimiter = [',', ';', '\t']
+        .iter()
+        .copied()
+        .find(|&delimiter| first_line.contains(delimiter))
+        .unwrap_or(',');
+    let number = |field: &str| {
+        let field = if delimiter == ',' {
+            field.into()
+        } else {
+            field.replace(',', ".")
+        };
+        field.parse::<f64>().ok()
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .delimiter(delimiter as u8)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+    let mut records = reader.records();
+    let Some(header) = records.next().transpose()? else {
+        return Ok(vec![]);
+    };
+
+    let mut results = ImportedResults::default();
+    let no_parameters = BTreeMap::new();
+
+    if header.get(0).and_then(number).is_some() {
+        for (line, record) in iter::once(Ok(header)).chain(records).enumerate() {
+            let record = record?;
+            let time = record
+                .get(0)
+                .and_then(number)
+                .and_then(valid_time)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Invalid time '{}' in line {}",
+                        record.get(0).unwrap_or_default(),
+                        line + 1
+                    )
+                })?;
+            results.add(
+                default_command,
+                &no_parameters,
+                Measurement {
+                    time_wall_clock: Time::new::<second>(time),
+                    ..Default::default()
+                },
+            );
+        }
+        return Ok(results.0);
+    }
+
+    let column = |name: &str| header.iter().position(|field| field == name);
+    let time_column = column("time_wall_clock")
+        .or_else(|| column("time"))
+        .ok_or_else(|| anyhow!("There is neither a 'time_wall_clock' nor a 'time' column"))?;
+    let parameter_columns: Vec<_> = header
+        .iter()
+        .enumerate()
+        .filter_map(|(i, field)| Some((i, field.strip_prefix("parameter_")?.to_string())))
+        .collect();
+
+    for (line, record) in records.enumerate() {
+        let record = record?;
+        let field = |column: Option<usize>| column.and_then(|i| record.get(i)).unwrap_or("");
+        // Only the user and system times are optional
+        let time = |column: Option<usize>| {
+            let value = field(column);
+            match value {
+                "" => Ok(0.0),
+                _ => number(value).and_then(valid_time).ok_or_else(|| {
+                    anyhow!("Invalid time '{value}' in line {line}", line = line + 2)
+                }),
+            }
+        };
+
+        if field(Some(time_column)).is_empty() {
+            bail!("Missing time in line {line}", line = line + 2);
+        }
+        let time_wall_clock = time(Some(time_column))?;
+        let exit_code = match field(column("exit_code")) {
+            "" if column("exit_code").is_some() => SIGNALED_EXIT_CODE,
+            "" => 0,
+            code => code
+                .parse()
+                .with_context(|| format!("Invalid exit code '{code}'"))?,
+        };
+        let command = match field(column("command")) {
+            "" => default_command,
+            command => command,
+        };
+        let parameters = parameter_columns
+            .iter()
+            .map(|(i, name)| (name.clone(), Parameter::new(field(Some(*i)).into(), false)))
+            .collect();
+
+        results.add(
+            command,
+            &parameters,
+            Measurement {
+                time_wall_clock: Time::new::<second>(time_wall_clock),
+                time_user: Time::new::<second>(time(column("time_user"))?),
+                time_system: Time::new::<second>(time(column("time_system"))?),
+                exit_status: exit_status_from_code(exit_code),
+                discarded: field(column("discarded")) == "true",
+                failed_attempt: field(column("failed_attempt")) == "true",
+                ..Default::default()
+            },
+        );
+    }
+
+    Ok(results.0)
+}
+
+/// Read the runs in the given file, which may be compressed. Runs without a command are
+/// named after the file.
+pub(crate) fn read_results(filename: &str) -> Result<Vec<BenchmarkResult>> {
+    let mut content =
+        fs::read(filename).with_context(|| format!("Could not read the file '{filename}'"))?;
+    if let Some(compression) = Compression::from_filename(filename) {
+        content = compression.decompress(&content)?;
+    }
+    let content = String::from_utf8_lossy(&content);
+
+    let stem = Path::new(filename.trim_end_matches(".gz").trim_end_matches(".zst"))
+        .file_stem()
+        .map_or(filename.into(), |stem| stem.to_string_lossy());
+    let results = if content.trim_start().starts_with(['{', '[']) {
+        parse_json(&content, &stem)
+    } else {
+        parse_csv(&content, &stem)
+    }
+    .with_context(|| format!("Could not read the runs in '{filename}'"))?;
+
+    if results.is_empty() {
+        bail!("The file '{filename}' does not contain any runs");
+    }
+    Ok(results)
+}
+
+/// Run the 'analyze' subcommand
+pub fn run_analyze(matches: &ArgMatches) -> Result<()> {
+    // The files and options are parsed like the commands and options of a benchmark session
+    let arguments = iter::once(OsString::from("hyperfine")).chain(
+        matches
+            .get_many::<String>("arguments")
+            .into_iter()
+            .flatten()
+            .map(OsString::from),
+    );
+    let cli_arguments = get_cli_arguments(arguments);
+    let options = Options::from_cli_arguments(&cli_arguments)?;
+    output::log::set_verbosity(options.verbosity);
+    output::glyphs::set_ascii(options.ascii);
+    let export_manager = ExportManager::from_cli_arguments(&cli_arguments, &options)?;
+
+    let mut results = vec![];
+    for filename in cli_arguments
+        .get_many::<String>("command")
+        .into_iter()
+        .flatten()
+    {
+        results.extend(read_results(filename)?);
+    }
+    for result in &mut results {
+        result.measurements.trim = options.trim;
+        result.modes = match result.measurements.modes() {
+            modes if modes.len() > 1 => modes,
+            _ => vec![],
+        };
+        result.run_indices = result.measurements.run_indices();
+    }
+
+    let commands = Commands::default();
+    let mut scheduler = Scheduler::new(&commands, &options, &export_manager);
+    scheduler.report_results(results)?;
+    scheduler.print_relative_speed_comparison();
+    scheduler.print_power_analysis();
+    scheduler.print_parameter_analysis();
+    scheduler.final_export()?;
+    scheduler.check_failure_conditions()
+}
+
+#[test]
+fn test_parse_json() {
+    let export = r#"{
+        "results": [
+            {
+                "command": "sleep {t}",
+                "parameters": { "t": { "value": "1", "is_unused": false } },
+                "measurements": [
+                    { "time_wall_clock": { "value": 1.5, "unit": "second" }, "exit_code": 0 },
+                    { "time_wall_clock": { "value": 2.5, "unit": "second" }, "exit_code": null }
+                ]
+            },
+            { "command": "old", "times": [0.5, 1.0] }
+        ]
+    }"#;
+    let results = parse_json(export, "file").unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].parameters["t"].value, "1");
+    assert_eq!(results[0].measurements.len(), 2);
+    assert_eq!(
+        results[0].measurements.time_wall_clock_mean(),
+        Time::new::<second>(2.0)
+    );
+    assert!(!results[0].measurements.measurements[1]
+        .exit_status
+        .success());
+    assert_eq!(results[1].measurements.len(), 2);
+
+    let jsonl = "{\"command\": \"a\", \"time_wall_clock\": 1.0}\n\
+                 {\"command\": \"b\", \"time_wall_clock\": 2.0}\n\
+                 {\"command\": \"a\", \"time_wall_clock\": 3.0, \"exit_code\": 2}\n";
+    let results = parse_json(jsonl, "file").unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].command, "a");
+    assert_eq!(results[0].measurements.len(), 2);
+    assert_eq!(results[1].command, "b");
+
+    let results = parse_json("[0.1, 0.3]", "file").unwrap();
+    assert_eq!(results[0].command, "file");
+    assert_eq!(results[0].measurements.len(), 2);
+
+    assert!(parse_json("{\"results\": [{\"command\": \"a\"}]}", "file").is_err());
+    assert!(parse_json("[{\"time_user\": 1.0}]", "file").is_err());
+    assert!(parse_json("[0.1, -0.3]", "file").is_err());
+    assert!(parse_json("[{\"time_wall_clock\": 1.0, \"time_user\": -1.0}]", "file").is_err());
+}
+
+#[test]
+fn test_parse_csv() {
+    let runs = "command,run,time_wall_clock,time_user,exit_code,parameter_n\n\
+                a,0,1.0,0.5,0,1\n\
+                a,1,2.0,0.5,,1\n\
+                a,0,3.0,0.5,0,2\n";
+    let results = parse_csv(runs, "file").unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].command, "a");
+    assert_eq!(results[0].parameters["n"].value, "1");
+    assert_eq!(results[0].measurements.len(), 2);
+    assert_eq!(
+        results[0].measurements.time_user_mean(),
+        Time::new::<second>(0.5)
+    );
+    assert!(!results[0].measurements.measurements[1]
+        .exit_status
+        .success());
+    assert_eq!(results[1].parameters["n"].value, "2");
+
+    let results = parse_csv("time;command\n0,5;a\n1,5;a\n", "file").unwrap();
+    assert_eq!(
+        results[0].measurements.time_wall_clock_mean(),
+        Time::new::<second>(1.0)
+    );
+
+    let results = parse_csv("0.25\n0.75\n", "file").unwrap();
+    assert_eq!(results[0].command, "file");
+    assert_eq!(
+        results[0].measurements.time_wall_clock_mean(),
+        Time::new::<second>(0.5)
+    );
+
+    assert!(parse_csv("command,mean\na,1.0\n", "file").is_err());
+    assert!(parse_csv("time\nfast\n", "file").is_err());
+
+    let error = |content| parse_csv(content, "file").unwrap_err().to_string();
+    assert_eq!(
+        error("command,time\na,1.0\na,NaN\n"),
+        "Invalid time 'NaN' in line 3"
+    );
+    assert_eq!(error("command,time\na,-1\n"), "Invalid time '-1' in line 2");
+    assert_eq!(
+        error("command,time\na,inf\n"),
+        "Invalid time 'inf' in line 2"
+    );
+    assert_eq!(
+        error("command,time,time_user\na,,1\n"),
+        "Missing time in line 2"
+    );
+    assert_eq!(error("0.5\n-0.5\n"), "Invalid time '-0.5' in line 2");
+}
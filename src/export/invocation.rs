@@ -0,0 +1,292 @@
+use std::collections::BTreeMap;
+
+use clap::parser::ValueSource;
+use clap::{crate_version, ArgAction, ArgMatches};
+use serde::Serialize;
+
+use crate::cli::build_command;
+use crate::command::Command;
+use crate::options::{CmdFailureAction, ExecutorKind, Options};
+use crate::parameter::tokenize::tokenize;
+use crate::quantity::second;
+
+/// A parameter, as defined on the command line
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum ParameterDefinition {
+    Scan {
+        name: String,
+        min: String,
+        max: String,
+        step: Option<String>,
+    },
+    List {
+        name: String,
+        values: Vec<String>,
+    },
+    Expr {
+        name: String,
+        expression: String,
+    },
+}
+
+/// Options which select the benchmarked commands or only apply to the whole session. They
+/// are left out when reproducing a single benchmark.
+const SESSION_OPTIONS: &[&str] = &[
+    "command",
+    "commands-from-file",
+    "script",
+    "command-name",
+    "group",
+    "reference",
+    "scan-order",
+    "sign-key",
+    "hosts",
+    "output-dir",
+];
+
+/// Options which can be given once for each command. When reproducing a single benchmark,
+/// only its own value is used.
+const PER_COMMAND_OPTIONS: &[&str] = &[
+    "runs", "min-runs", "max-runs", "prepare", "conclude", "cleanup", "output",
+];
+
+/// An option that was given on the command line, with the values of each occurrence
+#[derive(Debug, Clone, PartialEq)]
+struct GivenOption {
+    flag: String,
+    occurrences: Vec<Vec<String>>,
+    is_per_command: bool,
+}
+
+/// The effective configuration of a benchmark session, such that exported results are
+/// self-describing and the benchmark can be reproduced.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Invocation {
+    /// Version of hyperfine that produced the results
+    version: &'static str,
+
+    /// All command line options (including defaults), with their raw values
+    options: BTreeMap<String, Vec<String>>,
+
+    /// The shell that was used to run the commands, if any
+    shell: Option<String>,
+
+    warmup_count: u64,
+    min_runs: u64,
+    max_runs: Option<u64>,
+    discard_first: u64,
+
+    /// Minimum benchmarking time, in seconds
+    min_benchmarking_time: f64,
+
+    ignore_failure: bool,
+    parameters: Vec<ParameterDefinition>,
+
+    /// The seed of the random order of the benchmarks (see `--shuffle`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shuffle_seed: Option<u64>,
+
+    /// The options given on the command line, to reproduce single benchmarks
+    #[serde(skip)]
+    given_options: Vec<GivenOption>,
+}
+
+impl Invocation {
+    pub fn new(matches: &ArgMatches, options: &Options) -> Self {
+        let raw_options = matches
+            .ids()
+            .filter_map(|id| {
+                let values = matches.get_raw(id.as_str())?;
+                Some((
+                    id.to_string(),
+                    values
+                        .map(|value| value.to_string_lossy().into_owned())
+                        .collect(),
+                ))
+            })
+            .collect();
+
+        let mut parameters = vec![];
+        if let Some(mut values) = matches.get_many::<String>("parameter-scan") {
+            parameters.push(ParameterDefinition::Scan {
+                name: values.next().cloned().unwrap_or_default(),
+                min: values.next().cloned().unwrap_or_default(),
+                max: values.next().cloned().unwrap_or_default(),
+                step: matches.get_one::<String>("parameter-step-size").cloned(),
+            });
+        }
+        for mut values in matches
+            .get_occurrences::<String>("parameter-list")
+            .into_iter()
+            .flatten()
+        {
+            parameters.push(ParameterDefinition::List {
+                name: values.next().cloned().unwrap_or_default(),
+                values: values.next().map(|v| tokenize(v)).unwrap_or_default(),
+            });
+        }
+        for mut values in matches
+            .get_occurrences::<String>("parameter-expr")
+            .into_iter()
+            .flatten()
+        {
+            parameters.push(ParameterDefinition::Expr {
+                name: values.next().cloned().unwrap_or_default(),
+                expression: values.next().cloned().unwrap_or_default(),
+            });
+        }
+
+        Invocation {
+            version: crate_version!(),
+            options: raw_options,
+            shell: match options.executor_kind {
+                ExecutorKind::Shell(ref shell) => Some(shell.to_string()),
+                ExecutorKind::Raw | ExecutorKind::Mock(_) => None,
+            },
+            warmup_count: options.warmup_count,
+            min_runs: options.run_bounds[0].min,
+            max_runs: options.run_bounds[0].max,
+            discard_first: options.discard_first,
+            min_benchmarking_time: options.min_benchmarking_time.get::<second>(),
+            ignore_failure: options.command_failure_action == CmdFailureAction::Ignore,
+            parameters,
+            shuffle_seed: options.shuffle_seed,
+            given_options: given_options(matches),
+        }
+    }
+
+    /// A hyperfine command line that reruns only the benchmark with the given number (which
+    /// counts the reference command, if any) and the given, fully resolved, command
+    pub fn reproduce(&self, number: usize, command: &Command) -> String {
+        let mut words = vec!["hyperfine".to_string()];
+        for option in &self.given_options {
+            let occurrences = if option.is_per_command && option.occurrences.len() > 1 {
+                &option.occurrences[number..=number]
+            } else {
+                &option.occurrences[..]
+            };
+            for values in occurrences {
+                match values.as_slice() {
+                    [value] if option.flag.starts_with("--") => {
+                        words.push(format!("{}={}", option.flag, shell_words::quote(value)))
+                    }
+                    values => {
+                        words.push(option.flag.clone());
+                        words.extend(values.iter().map(|value| shell_words::quote(value).into()));
+                    }
+                }
+            }
+        }
+        words.push(shell_words::quote(&command.get_command_line()).into());
+        words.join(" ")
+    }
+}
+
+/// The options that were given on the command line, except for the ones which select the
+/// benchmarked commands (including parameters) or configure the exports
+fn given_options(matches: &ArgMatches) -> Vec<GivenOption> {
+    build_command()
+        .get_arguments()
+        .filter(|arg| {
+            let id = arg.get_id().as_str();
+            !SESSION_OPTIONS.contains(&id)
+                && !id.starts_with("parameter-")
+                && id != "export"
+                && !id.starts_with("export-")
+                && matches.value_source(id) == Some(ValueSource::CommandLine)
+        })
+        .filter_map(|arg| {
+            let id = arg.get_id().as_str();
+            let flag = match (arg.get_long(), arg.get_short()) {
+                (Some(long), _) => format!("--{long}"),
+                (None, Some(short)) => format!("-{short}"),
+                (None, None) => return None,
+            };
+            let occurrences = match arg.get_action() {
+                ArgAction::Count => vec![vec![]; usize::from(matches.get_count(id))],
+                action if action.takes_values() => matches
+                    .get_raw_occurrences(id)?
+                    .map(|values| {
+                        let values = values.map(|value| value.to_string_lossy().into_owned());
+                        match arg.get_value_delimiter() {
+                            Some(delimiter) => {
+                                vec![values.collect::<Vec<_>>().join(&delimiter.to_string())]
+                            }
+                            None => values.collect(),
+                        }
+                    })
+                    .collect(),
+                _ => vec![vec![]],
+            };
+            Some(GivenOption {
+                flag,
+                occurrences,
+                is_per_command: PER_COMMAND_OPTIONS.contains(&id),
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn test_invocation() {
+    use crate::cli::get_cli_arguments;
+
+    let matches = get_cli_arguments(vec![
+        "hyperfine",
+        "--warmup=3",
+        "--runs=5",
+        "--shell=bash",
+        "-L",
+        "compiler",
+        "gcc,clang",
+        "{compiler} main.c",
+    ]);
+    let options = Options::from_cli_arguments(&matches).unwrap();
+    let invocation = serde_json::to_value(Invocation::new(&matches, &options)).unwrap();
+
+    assert_eq!(invocation["shell"], "bash");
+    assert_eq!(invocation["warmup_count"], 3);
+    assert_eq!(invocation["min_runs"], 5);
+    assert_eq!(invocation["max_runs"], 5);
+    assert_eq!(invocation["ignore_failure"], false);
+    assert_eq!(invocation["options"]["command"][0], "{compiler} main.c");
+    assert_eq!(invocation["options"]["runs"][0], "5");
+    assert_eq!(
+        invocation["parameters"][0],
+        serde_json::json!({
+            "kind": "list",
+            "name": "compiler",
+            "values": ["gcc", "clang"],
+        })
+    );
+}
+
+#[test]
+fn test_reproduce() {
+    use crate::cli::get_cli_arguments;
+
+    let matches = get_cli_arguments(vec![
+        "hyperfine",
+        "-N",
+        "--warmup=3",
+        "--runs=5",
+        "--runs=10",
+        "--exit-code-ok=1,2",
+        "--prepare",
+        "make clean",
+        "--export-json=results.json",
+        "-L",
+        "compiler",
+        "gcc,clang",
+        "{compiler} main.c",
+    ]);
+    let options = Options::from_cli_arguments(&matches).unwrap();
+    let invocation = Invocation::new(&matches, &options);
+
+    assert_eq!(
+        invocation.reproduce(1, &Command::new(None, "clang main.c")),
+        "hyperfine --warmup=3 --runs=10 --prepare='make clean' -N --exit-code-ok=1,2 \
+         'clang main.c'"
+    );
+}
@@ -1,29 +1,73 @@
 use serde::*;
 use serde_json::to_vec_pretty;
 
+use super::invocation::Invocation;
+use super::signature::SystemFingerprint;
 use super::Exporter;
 use crate::benchmark::benchmark_result::BenchmarkResult;
-use crate::options::SortOrder;
+use crate::benchmark::parameter_analysis::{self, ParameterAnalysis};
+use crate::options::{MemoryUnit, SortOrder};
 use crate::quantity::TimeUnit;
 
 use anyhow::Result;
 
+/// Version of the JSON export format. Fields may be added without changing the version,
+/// but removing, renaming or changing the meaning of a field requires a new version (and
+/// an update of `schema.json`).
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// JSON Schema describing the export format
+pub const SCHEMA: &str = include_str!("schema.json");
+
 #[derive(Serialize, Debug)]
 struct HyperfineSummary<'a> {
+    schema_version: u32,
+
     results: &'a [BenchmarkResult],
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    parameter_analysis: Vec<ParameterAnalysis>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    invocation: Option<&'a Invocation>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system_fingerprint: Option<&'a SystemFingerprint>,
 }
 
 #[derive(Default)]
-pub struct JsonExporter {}
+pub struct JsonExporter {
+    /// The configuration of the benchmark session, which is embedded in the export
+    invocation: Option<Invocation>,
+
+    /// Description of the system, which is embedded in signed exports
+    system_fingerprint: Option<SystemFingerprint>,
+}
+
+impl JsonExporter {
+    pub fn new(invocation: Invocation, system_fingerprint: Option<SystemFingerprint>) -> Self {
+        JsonExporter {
+            invocation: Some(invocation),
+            system_fingerprint,
+        }
+    }
+}
 
 impl Exporter for JsonExporter {
     fn serialize(
         &self,
         results: &[BenchmarkResult],
         _time_unit: Option<TimeUnit>,
+        _memory_unit: Option<MemoryUnit>,
         _sort_order: SortOrder,
     ) -> Result<Vec<u8>> {
-        let mut output = to_vec_pretty(&HyperfineSummary { results });
+        let mut output = to_vec_pretty(&HyperfineSummary {
+            schema_version: SCHEMA_VERSION,
+            results,
+            parameter_analysis: parameter_analysis::analyze(results),
+            invocation: self.invocation.as_ref(),
+            system_fingerprint: self.system_fingerprint.as_ref(),
+        });
         if let Ok(ref mut content) = output {
             content.push(b'\n');
         }
@@ -31,3 +75,151 @@ impl Exporter for JsonExporter {
         Ok(output?)
     }
 }
+
+/// Check that all fields of the given JSON value are described by the schema
+#[cfg(test)]
+fn assert_described_by(value: &serde_json::Value, schema: &serde_json::Value, path: &str) {
+    use serde_json::Value;
+
+    let root: Value = serde_json::from_str(SCHEMA).unwrap();
+    let schema = match schema["$ref"].as_str() {
+        Some(reference) => &root["$defs"][reference.trim_start_matches("#/$defs/")],
+        None => schema,
+    };
+
+    match value {
+        Value::Object(fields) => {
+            for (name, field) in fields {
+                let field_schema = match schema["properties"].get(name) {
+                    Some(field_schema) => field_schema,
+                    None => &schema["additionalProperties"],
+                };
+                assert!(
+                    field_schema.is_object(),
+                    "Field '{}.{}' is not described by the JSON schema",
+                    path,
+                    name
+                );
+                assert_described_by(field, field_schema, &format!("{path}.{name}"));
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                assert_described_by(item, &schema["items"], &format!("{path}[]"));
+            }
+        }
+        _ => {}
+    }
+}
+
+#[test]
+fn test_export_matches_schema() {
+    use crate::benchmark::benchmark_result::{Parameter, ShellSpawningTime};
+    use crate::benchmark::measurement::{CapturedOutput, Measurement, Measurements, RunIndices};
+    use crate::cli::get_cli_arguments;
+    use crate::options::Options;
+    use crate::output::warnings::RecordedWarning;
+    use crate::quantity::{byte, second, Information, Time};
+    use crate::util::git::GitRevision;
+
+    let schema: serde_json::Value = serde_json::from_str(SCHEMA).unwrap();
+    assert_eq!(
+        schema["properties"]["schema_version"]["const"],
+        SCHEMA_VERSION
+    );
+
+    let results: Vec<BenchmarkResult> = (1..=2)
+        .map(|n| BenchmarkResult {
+            command: format!("sleep {n}"),
+            measurements: Measurements {
+                measurements: vec![Measurement {
+                    time_wall_clock: Time::new::<second>(n as f64),
+                    spawn_latency: Some(Time::new::<second>(0.001)),
+                    peak_memory_footprint: Some(Information::new::<byte>(1024.0)),
+                    peak_private_bytes: Some(Information::new::<byte>(2048.0)),
+                    peak_working_set: Some(Information::new::<byte>(4096.0)),
+                    discarded: true,
+                    stderr: Some("error".into()),
+                    stdout_size: Some(Information::new::<byte>(6.0)),
+                    stderr_size: Some(Information::new::<byte>(0.0)),
+                    syscall_count: Some(42),
+                    timed_out: true,
+                    time_on_cpu: Some(Time::new::<second>(0.5)),
+                    run_queue_delay: Some(Time::new::<second>(0.01)),
+                    cpu_migrations: Some(3),
+                    numa_node: Some(0),
+                    auxiliary: vec![("gpu0_utilization_percent".to_string(), 50.0)]
+                        .into_iter()
+                        .collect(),
+                    derived: vec![("gflops".to_string(), 1.5)].into_iter().collect(),
+                    ..Default::default()
+                }],
+                trim: Some(0.05),
+                too_slow: true,
+                deadline_exceeded: true,
+                too_noisy: true,
+                timeout: Some(Time::new::<second>(10.0)),
+                timed_out: true,
+                profiles: vec![format!("hyperfine-profiles/benchmark-{n}-profile-0")],
+                syscall_counts: vec![42],
+                noise_floor_probes: vec![Time::new::<second>(0.001), Time::new::<second>(0.002)],
+            },
+            parameters: vec![("n".to_string(), Parameter::new(n.to_string(), false))]
+                .into_iter()
+                .collect(),
+            command_template: Some("sleep {n}".into()),
+            shell_spawning_time: Some(ShellSpawningTime::default()),
+            captured_output: Some(CapturedOutput {
+                stdout: Some("v1.0".into()),
+                stderr: Some("".into()),
+            }),
+            modes: vec![
+                Time::new::<second>(n as f64),
+                Time::new::<second>(n as f64 + 0.5),
+            ],
+            run_indices: Some(RunIndices {
+                min: 0,
+                median: 0,
+                max: 0,
+            }),
+            execution_order: Some(2 - n),
+            reproduce: Some(format!("hyperfine --runs=2 'sleep {n}'")),
+            git: Some(GitRevision {
+                repository: "/src/project".into(),
+                commit: "a1b2c3d".into(),
+                dirty: n == 2,
+            }),
+            stateful: true,
+            group: Some("io".into()),
+            warnings: vec![RecordedWarning {
+                kind: "outliers_detected".into(),
+                message: "Statistical outliers were detected.".into(),
+            }],
+        })
+        .collect();
+
+    let matches = get_cli_arguments(vec![
+        "hyperfine",
+        "--parameter-scan",
+        "n",
+        "1",
+        "2",
+        "--parameter-step-size",
+        "1",
+        "--shuffle=3",
+        "sleep {n}",
+    ]);
+    let options = Options::from_cli_arguments(&matches).unwrap();
+    let invocation = Invocation::new(&matches, &options);
+
+    let output = JsonExporter::new(invocation, Some(SystemFingerprint::collect()))
+        .serialize(&results, None, None, SortOrder::Command)
+        .unwrap();
+    let output: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(output["schema_version"], SCHEMA_VERSION);
+    assert!(!output["parameter_analysis"].as_array().unwrap().is_empty());
+    assert!(output["invocation"].is_object());
+    assert!(output["system_fingerprint"].is_object());
+    assert_described_by(&output, &schema, "");
+}
@@ -1,9 +1,33 @@
 use crate::export::markup::MarkupExporter;
 
+use super::columns::Column;
+use crate::options::{Precision, RelativeError, RelativeMetric};
+
 use super::markup::Alignment;
 
 #[derive(Default)]
-pub struct MarkdownExporter {}
+pub struct MarkdownExporter {
+    columns: Option<Vec<Column>>,
+    precision: Precision,
+    relative_metric: RelativeMetric,
+    relative_error: RelativeError,
+}
+
+impl MarkdownExporter {
+    pub fn new(
+        columns: Option<Vec<Column>>,
+        precision: Precision,
+        relative_metric: RelativeMetric,
+        relative_error: RelativeError,
+    ) -> Self {
+        MarkdownExporter {
+            columns,
+            precision,
+            relative_metric,
+            relative_error,
+        }
+    }
+}
 
 impl MarkupExporter for MarkdownExporter {
     fn table_row(&self, cells: &[&str]) -> String {
@@ -26,6 +50,22 @@ impl MarkupExporter for MarkdownExporter {
     fn command(&self, cmd: &str) -> String {
         format!("`{cmd}`")
     }
+
+    fn columns(&self) -> Option<&[Column]> {
+        self.columns.as_deref()
+    }
+
+    fn precision(&self) -> Precision {
+        self.precision
+    }
+
+    fn relative_metric(&self) -> RelativeMetric {
+        self.relative_metric
+    }
+
+    fn relative_error(&self) -> RelativeError {
+        self.relative_error
+    }
 }
 
 /// Check Markdown-based data row formatting
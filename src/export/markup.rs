@@ -1,8 +1,10 @@
+use crate::benchmark::benchmark_result::{self, BenchmarkResult};
+use crate::benchmark::relative_speed;
 use crate::benchmark::relative_speed::BenchmarkResultWithRelativeSpeed;
-use crate::benchmark::{benchmark_result::BenchmarkResult, relative_speed};
-use crate::options::SortOrder;
-use crate::quantity::{IsUnit, Quantity, TimeUnit};
+use crate::options::{MemoryUnit, Precision, RelativeError, RelativeMetric, SortOrder};
+use crate::quantity::{Information, InformationUnit, Quantity, TimeUnit};
 
+use super::columns::Column;
 use super::Exporter;
 use anyhow::Result;
 
@@ -15,68 +17,60 @@ pub trait MarkupExporter {
     fn table_results(
         &self,
         entries: &[BenchmarkResultWithRelativeSpeed],
+        columns: &[Column],
         time_unit: TimeUnit,
+        memory_unit: InformationUnit,
     ) -> String {
-        // prepare table header strings
-        let notation = format!("[{}]", time_unit.short_name());
-
         // prepare table cells alignment
-        let cells_alignment = [
-            Alignment::Left,
-            Alignment::Right,
-            Alignment::Right,
-            Alignment::Right,
-            Alignment::Right,
-        ];
+        let cells_alignment: Vec<_> = columns.iter().map(|c| c.alignment()).collect();
 
         // emit table header format
         let mut table = self.table_header(&cells_alignment);
 
         // emit table header data
-        table.push_str(&self.table_row(&[
-            "Command",
-            &format!("Mean {notation}"),
-            &format!("Min {notation}"),
-            &format!("Max {notation}"),
-            "Relative",
-        ]));
+        let header: Vec<_> = columns
+            .iter()
+            .map(|c| c.header(time_unit, memory_unit))
+            .collect();
+        table.push_str(&self.table_row(&header.iter().map(|h| h.as_str()).collect::<Vec<_>>()));
 
         // emit horizontal line
         table.push_str(&self.table_divider(&cells_alignment));
 
         for entry in entries {
             let result = &entry.result;
-            // prepare data row strings
-            let cmd_str = result.command_with_unused_parameters().replace('|', "\\|");
-            let mean_str = result.mean_wall_clock_time().format_value(time_unit);
-            let stddev_str = if let Some(stddev) = result.measurements.stddev() {
-                format!(" ± {}", stddev.format_value(time_unit))
-            } else {
-                "".into()
-            };
-            let min_str = result.measurements.min().format_value(time_unit);
-            let max_str = result.measurements.max().format_value(time_unit);
-            let rel_str = format!("{:.2}", entry.relative_speed);
-            let rel_stddev_str = if entry.is_reference {
-                "".into()
-            } else if let Some(stddev) = entry.relative_speed_stddev {
-                format!(" ± {stddev:.2}")
-            } else {
-                "".into()
-            };
-
-            // prepare table row entries
-            table.push_str(&self.table_row(&[
-                &self.command(&cmd_str),
-                &format!("{mean_str}{stddev_str}"),
-                &min_str,
-                &max_str,
-                &format!("{rel_str}{rel_stddev_str}"),
-            ]))
+            let row: Vec<_> = columns
+                .iter()
+                .map(|&column| {
+                    let cell = column.cell(entry, time_unit, memory_unit, self.precision());
+                    if column != Column::Command {
+                        return cell;
+                    }
+
+                    let mut command = self.command(&cell);
+                    if result.measurements.too_slow {
+                        command.push_str(" (too slow, skipped)");
+                    }
+                    if result.measurements.deadline_exceeded {
+                        command.push_str(" (deadline exceeded)");
+                    }
+                    if result.measurements.too_noisy {
+                        command.push_str(" (too noisy, aborted)");
+                    }
+                    if result.measurements.timed_out {
+                        command.push_str(" (timed out)");
+                    }
+                    if result.stateful {
+                        command.push_str(" (stateful)");
+                    }
+                    command
+                })
+                .collect();
+            table.push_str(&self.table_row(&row.iter().map(|c| c.as_str()).collect::<Vec<_>>()))
         }
 
         // emit table footer format
-        table.push_str(&self.table_footer(&cells_alignment));
+        table.push_str(&self.table_footer(columns));
 
         table
     }
@@ -89,11 +83,32 @@ pub trait MarkupExporter {
         "".to_string()
     }
 
-    fn table_footer(&self, _cell_aligmnents: &[Alignment]) -> String {
+    fn table_footer(&self, _columns: &[Column]) -> String {
         "".to_string()
     }
 
     fn command(&self, size: &str) -> String;
+
+    /// The columns selected with '--export-columns', if any
+    fn columns(&self) -> Option<&[Column]> {
+        None
+    }
+
+    /// The number of decimal places selected with '--precision'
+    fn precision(&self) -> Precision {
+        Precision::default()
+    }
+
+    /// The metric of the 'Relative' column selected with '--relative-metric'
+    fn relative_metric(&self) -> RelativeMetric {
+        RelativeMetric::default()
+    }
+
+    /// The estimate of the uncertainty of the 'Relative' column selected with
+    /// '--relative-error'
+    fn relative_error(&self) -> RelativeError {
+        RelativeError::default()
+    }
 }
 
 fn determine_unit_from_results(results: &[BenchmarkResult]) -> TimeUnit {
@@ -106,17 +121,48 @@ fn determine_unit_from_results(results: &[BenchmarkResult]) -> TimeUnit {
     }
 }
 
+fn determine_memory_unit_from_results(
+    results: &[BenchmarkResult],
+    memory_unit: MemoryUnit,
+) -> InformationUnit {
+    // Use the first BenchmarkResult entry to determine the unit for all entries.
+    let reference = results
+        .first()
+        .map(|r| r.measurements.peak_memory_usage_mean())
+        .unwrap_or_else(Information::zero);
+    memory_unit.resolve(reference)
+}
+
 impl<T: MarkupExporter> Exporter for T {
     fn serialize(
         &self,
         results: &[BenchmarkResult],
         time_unit: Option<TimeUnit>,
+        memory_unit: Option<MemoryUnit>,
         sort_order: SortOrder,
     ) -> Result<Vec<u8>> {
         let unit = time_unit.unwrap_or_else(|| determine_unit_from_results(results));
-        let entries = relative_speed::compute(results, sort_order);
-
-        let table = self.table_results(&entries, unit);
+        let columns = match self.columns() {
+            Some(columns) => columns.to_vec(),
+            None => {
+                let mut columns = Column::defaults(memory_unit.is_some());
+                if results.iter().any(|result| result.group.is_some()) {
+                    columns.insert(0, Column::Group);
+                }
+                columns
+            }
+        };
+        let memory_unit =
+            determine_memory_unit_from_results(results, memory_unit.unwrap_or(MemoryUnit::Auto));
+        let groups = benchmark_result::groups(results);
+        let entries = relative_speed::compute_by_group(
+            &groups,
+            sort_order,
+            self.relative_metric(),
+            self.relative_error(),
+        );
+
+        let table = self.table_results(&entries, &columns, unit, memory_unit);
         Ok(table.as_bytes().to_vec())
     }
 }
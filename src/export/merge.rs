@@ -0,0 +1,199 @@
+use std::fs;
+
+use anyhow::{bail, Context, Result};
+use clap::ArgMatches;
+
+use super::compression::Compression;
+use serde_json::{Map, Value};
+
+/// Fields of a benchmark which are computed from its runs, and which are therefore dropped
+/// when the runs of several benchmarks are pooled
+const FIELDS_COMPUTED_FROM_RUNS: [&str; 3] = ["modes", "run_indices", "warnings"];
+
+/// Merge several JSON exports into a single one. The exports are handled as plain JSON
+/// values, such that all fields of the individual benchmarks are preserved (except for
+/// the ones computed from the runs, if they are pooled).
+pub fn merge_json_exports(exports: Vec<(String, Value)>, pool: bool) -> Result<Value> {
+    let mut schema_version: Option<(String, Value)> = None;
+    let mut merged: Vec<Value> = vec![];
+
+    for (filename, mut export) in exports {
+        if let Some(version) = export.get("schema_version") {
+            match schema_version {
+                Some((ref first_filename, ref first_version)) if first_version != version => {
+                    bail!(
+                        "Can not merge '{filename}' (schema version {version}) with \
+                         '{first_filename}' (schema version {first_version})"
+                    );
+                }
+                Some(_) => {}
+                None => schema_version = Some((filename.clone(), version.clone())),
+            }
+        }
+
+        let results = match export.get_mut("results").map(Value::take) {
+            Some(Value::Array(results)) => results,
+            _ => bail!("'{filename}' is not a valid JSON export: no 'results' array found"),
+        };
+
+        for result in results {
+            let existing = merged.iter_mut().find(|existing| {
+                existing.get("command") == result.get("command")
+                    && existing.get("parameters") == result.get("parameters")
+            });
+
+            match existing {
+                Some(existing) if pool => {
+                    let measurements = match result.get("measurements") {
+                        Some(Value::Array(measurements)) => measurements.clone(),
+                        _ => {
+                            bail!("'{filename}' is not a valid JSON export: no measurements found")
+                        }
+                    };
+                    match existing.get_mut("measurements") {
+                        Some(Value::Array(existing)) => existing.extend(measurements),
+                        _ => bail!("Can not pool benchmarks without measurements"),
+                    }
+                    if let Some(existing) = existing.as_object_mut() {
+                        for field in FIELDS_COMPUTED_FROM_RUNS {
+                            existing.remove(field);
+                        }
+                    }
+                }
+                _ => merged.push(result),
+            }
+        }
+    }
+
+    let mut output = Map::new();
+    if let Some((_, version)) = schema_version {
+        output.insert("schema_version".into(), version);
+    }
+    output.insert("results".into(), Value::Array(merged));
+    Ok(Value::Object(output))
+}
+
+/// Run the 'merge' subcommand
+pub fn run_merge(matches: &ArgMatches) -> Result<()> {
+    let exports = matches
+        .get_many::<String>("files")
+        .into_iter()
+        .flatten()
+        .map(|filename| {
+            let mut content = fs::read(filename)
+                .with_context(|| format!("Could not read export file '{filename}'"))?;
+            if let Some(compression) = Compression::from_filename(filename) {
+                content = compression.decompress(&content)?;
+            }
+            let export = serde_json::from_slice(&content)
+                .with_context(|| format!("'{filename}' is not a valid JSON file"))?;
+            Ok((filename.clone(), export))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let merged = merge_json_exports(exports, matches.get_flag("pool"))?;
+    let mut content = serde_json::to_vec_pretty(&merged)?;
+    content.push(b'\n');
+
+    match matches.get_one::<String>("output") {
+        Some(filename) => {
+            if let Some(compression) = Compression::from_filename(filename) {
+                content = compression.compress(&content)?;
+            }
+            fs::write(filename, content)
+                .with_context(|| format!("Could not write merged results to '{filename}'"))
+        }
+        None => {
+            print!("{}", String::from_utf8_lossy(&content));
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn test_merge_json_exports() {
+    use serde_json::json;
+
+    let result = |command: &str, times: &[f64]| {
+        json!({
+            "command": command,
+            "measurements": times.iter().map(|t| json!({ "time_wall_clock": t })).collect::<Vec<_>>(),
+        })
+    };
+    let exports = || {
+        vec![
+            (
+                "a.json".to_string(),
+                json!({ "results": [result("sleep 1", &[1.0]), result("sleep 2", &[2.0])] }),
+            ),
+            (
+                "b.json".to_string(),
+                json!({ "results": [result("sleep 1", &[1.5])] }),
+            ),
+        ]
+    };
+
+    assert_eq!(
+        merge_json_exports(exports(), false).unwrap(),
+        json!({
+            "results": [result("sleep 1", &[1.0]), result("sleep 2", &[2.0]), result("sleep 1", &[1.5])]
+        })
+    );
+
+    assert_eq!(
+        merge_json_exports(exports(), true).unwrap(),
+        json!({ "results": [result("sleep 1", &[1.0, 1.5]), result("sleep 2", &[2.0])] })
+    );
+}
+
+#[test]
+fn test_merge_drops_fields_computed_from_pooled_runs() {
+    use serde_json::json;
+
+    let result = |time: f64| {
+        json!({
+            "command": "sleep 1",
+            "measurements": [{ "time_wall_clock": time }, { "time_wall_clock": time }],
+            "run_indices": { "min": 0, "median": 0, "max": 1 },
+            "modes": [time],
+            "warnings": [{ "kind": "outliers_detected", "message": "" }],
+            "group": "io",
+        })
+    };
+    let exports = vec![
+        ("a.json".to_string(), json!({ "results": [result(1.0)] })),
+        ("b.json".to_string(), json!({ "results": [result(2.0)] })),
+    ];
+
+    let merged = merge_json_exports(exports, true).unwrap();
+    let pooled = &merged["results"][0];
+    assert_eq!(pooled["measurements"].as_array().unwrap().len(), 4);
+    assert_eq!(pooled["group"], "io");
+    for field in FIELDS_COMPUTED_FROM_RUNS {
+        assert!(pooled.get(field).is_none());
+    }
+}
+
+#[test]
+fn test_merge_validates_exports() {
+    use serde_json::json;
+
+    let merge = |a: Value, b: Value| {
+        merge_json_exports(vec![("a.json".into(), a), ("b.json".into(), b)], false)
+    };
+
+    assert!(merge(json!({ "results": [] }), json!({ "foo": [] })).is_err());
+    assert!(merge(
+        json!({ "schema_version": 1, "results": [] }),
+        json!({ "schema_version": 2, "results": [] })
+    )
+    .is_err());
+    assert_eq!(
+        merge(
+            json!({ "schema_version": 1, "results": [] }),
+            json!({ "schema_version": 1, "results": [] })
+        )
+        .unwrap(),
+        json!({ "schema_version": 1, "results": [] })
+    );
+}
@@ -1,26 +1,54 @@
 use std::fs::{File, OpenOptions};
 use std::io::Write;
+use std::path::Path;
 
 mod asciidoc;
+mod bencher;
+pub mod clipboard;
+mod columns;
+pub mod compare;
+pub mod compression;
 mod csv;
-mod json;
+pub mod import;
+mod invocation;
+pub mod json;
 mod markdown;
 mod markup;
+pub mod merge;
 mod orgmode;
+mod otlp;
+mod output_dir;
+mod plots;
+mod runs;
+mod signature;
+mod template;
 #[cfg(test)]
 mod tests;
 
 use self::asciidoc::AsciidocExporter;
+use self::bencher::BencherExporter;
+use self::columns::Column;
+use self::compression::Compression;
 use self::csv::CsvExporter;
+use self::invocation::Invocation;
 use self::json::JsonExporter;
 use self::markdown::MarkdownExporter;
 use self::orgmode::OrgmodeExporter;
+use self::otlp::OtlpExporter;
+use self::plots::{Plot, PlotExporter};
+use self::runs::RunsExporter;
+use self::signature::{SigningKey, SystemFingerprint};
+use self::template::{Template, TemplateExporter};
 
 use crate::benchmark::benchmark_result::BenchmarkResult;
-use crate::options::SortOrder;
+use crate::command::Command;
+use crate::options::{
+    CsvFormat, MemoryUnit, Options, OrgmodeFormat, Precision, RelativeError, RelativeMetric,
+    SortOrder,
+};
 use crate::quantity::TimeUnit;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use clap::ArgMatches;
 
 /// The desired form of exporter to use for a given file.
@@ -29,6 +57,9 @@ pub enum ExportType {
     /// Asciidoc Table
     Asciidoc,
 
+    /// Bencher Metric Format (JSON), for continuous benchmarking tools
+    Bencher,
+
     /// CSV (comma separated values) format
     Csv,
 
@@ -40,6 +71,35 @@ pub enum ExportType {
 
     /// Emacs org-mode tables
     Orgmode,
+
+    /// OpenTelemetry spans (OTLP/JSON) of the benchmark session
+    Otlp,
+
+    /// Python script which plots the wall clock times of the runs
+    Plot(Plot),
+
+    /// CSV table with the raw measurements of each individual run
+    Runs,
+
+    /// Custom text format, given by a template
+    Template(Template),
+}
+
+impl ExportType {
+    /// The export type with the given name, as used in '--export FORMAT:TARGET'
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "asciidoc" => Some(ExportType::Asciidoc),
+            "bencher" => Some(ExportType::Bencher),
+            "csv" => Some(ExportType::Csv),
+            "json" => Some(ExportType::Json),
+            "markdown" => Some(ExportType::Markdown),
+            "orgmode" => Some(ExportType::Orgmode),
+            "otlp" => Some(ExportType::Otlp),
+            "runs" => Some(ExportType::Runs),
+            _ => None,
+        }
+    }
 }
 
 /// Interface for different exporters.
@@ -49,6 +109,7 @@ trait Exporter {
         &self,
         results: &[BenchmarkResult],
         time_unit: Option<TimeUnit>,
+        memory_unit: Option<MemoryUnit>,
         sort_order: SortOrder,
     ) -> Result<Vec<u8>>;
 }
@@ -56,32 +117,62 @@ trait Exporter {
 pub enum ExportTarget {
     File(String),
     Stdout,
+
+    /// An OTLP/HTTP endpoint to which the spans are sent
+    Url(String),
+
+    /// The system clipboard
+    Clipboard,
 }
 
 struct ExporterWithTarget {
     exporter: Box<dyn Exporter>,
     target: ExportTarget,
+
+    /// Key to write a detached signature next to the exported file
+    signing_key: Option<SigningKey>,
 }
 
 /// Handles the management of multiple file exporters.
 pub struct ExportManager {
     exporters: Vec<ExporterWithTarget>,
     time_unit: Option<TimeUnit>,
+    memory_unit: Option<MemoryUnit>,
     sort_order: SortOrder,
+    relative_metric: RelativeMetric,
+    relative_error: RelativeError,
+    csv_format: CsvFormat,
+    orgmode_format: OrgmodeFormat,
+    columns: Option<Vec<Column>>,
+    precision: Precision,
+    invocation: Invocation,
+    signing_key: Option<SigningKey>,
 }
 
 impl ExportManager {
     /// Build the ExportManager that will export the results specified
     /// in the given ArgMatches
-    pub fn from_cli_arguments(
-        matches: &ArgMatches,
-        time_unit: Option<TimeUnit>,
-        sort_order: SortOrder,
-    ) -> Result<Self> {
+    pub fn from_cli_arguments(matches: &ArgMatches, options: &Options) -> Result<Self> {
         let mut export_manager = Self {
             exporters: vec![],
-            time_unit,
-            sort_order,
+            time_unit: options.time_unit,
+            memory_unit: options.memory_unit,
+            sort_order: options.sort_order_exports,
+            relative_metric: options.relative_metric,
+            relative_error: options.relative_error,
+            csv_format: options.csv_format,
+            orgmode_format: options.orgmode_format.clone(),
+            columns: matches.get_many::<String>("export-columns").map(|names| {
+                names
+                    .map(|name| Column::from_name(name).expect("validated by clap"))
+                    .collect()
+            }),
+            precision: options.precision,
+            invocation: Invocation::new(matches, options),
+            signing_key: matches
+                .get_one::<String>("sign-key")
+                .map(|path| SigningKey::new(path))
+                .transpose()?,
         };
         {
             let mut add_exporter = |flag, exporttype| -> Result<()> {
@@ -91,28 +182,133 @@ impl ExportManager {
                 Ok(())
             };
             add_exporter("export-asciidoc", ExportType::Asciidoc)?;
+            add_exporter("export-bencher", ExportType::Bencher)?;
             add_exporter("export-json", ExportType::Json)?;
             add_exporter("export-csv", ExportType::Csv)?;
             add_exporter("export-markdown", ExportType::Markdown)?;
             add_exporter("export-orgmode", ExportType::Orgmode)?;
+            add_exporter("export-otlp", ExportType::Otlp)?;
+            add_exporter("export-runs", ExportType::Runs)?;
+        }
+        for format_and_target in matches.get_many::<String>("export").into_iter().flatten() {
+            let (format, target) = format_and_target
+                .split_once(':')
+                .expect("validated by clap");
+            let export_type = ExportType::from_name(format).expect("validated by clap");
+            export_manager.add_exporter(export_type, target)?;
+        }
+        let templates: Vec<_> = matches
+            .get_many::<String>("export-template")
+            .into_iter()
+            .flatten()
+            .collect();
+        for template_and_file in templates.chunks(2) {
+            let [template, filename] = template_and_file else {
+                unreachable!("two values per '--export-template'");
+            };
+            let template = Template::from_file(template)?;
+            export_manager.add_exporter(ExportType::Template(template), filename)?;
+        }
+        if let Some(directory) = matches.get_one::<String>("export-plots") {
+            plots::populate(&mut export_manager, Path::new(directory))?;
+        }
+        if let Some(ref directory) = options.output_dir {
+            let invocation = export_manager.invocation.clone();
+            output_dir::populate(
+                &mut export_manager,
+                directory,
+                &invocation,
+                options.profiling.is_some(),
+            )?;
         }
+        if let Some(format) = matches.get_one::<String>("export-clipboard") {
+            let export_type = match format.as_str() {
+                "json" => ExportType::Json,
+                _ => ExportType::Markdown,
+            };
+            export_manager.exporters.push(ExporterWithTarget {
+                exporter: export_manager.exporter(&export_type),
+                target: ExportTarget::Clipboard,
+                signing_key: None,
+            });
+        }
+
+        if export_manager.signing_key.is_some()
+            && !export_manager
+                .exporters
+                .iter()
+                .any(|e| e.signing_key.is_some())
+        {
+            bail!(
+                "'--sign-key' requires a JSON export to a file ('--export-json <FILE>' or \
+                 '--export json:<FILE>')"
+            );
+        }
+
         Ok(export_manager)
     }
 
+    /// Create an exporter of the given type with the settings of this ExportManager
+    fn exporter(&self, export_type: &ExportType) -> Box<dyn Exporter> {
+        match export_type {
+            ExportType::Asciidoc => Box::new(AsciidocExporter::new(
+                self.columns.clone(),
+                self.precision,
+                self.relative_metric,
+                self.relative_error,
+            )),
+            ExportType::Bencher => Box::<BencherExporter>::default(),
+            ExportType::Csv => Box::new(CsvExporter::new(self.csv_format, self.precision)),
+            ExportType::Json => Box::new(JsonExporter::new(
+                self.invocation.clone(),
+                self.signing_key
+                    .as_ref()
+                    .map(|_| SystemFingerprint::collect()),
+            )),
+            ExportType::Markdown => Box::new(MarkdownExporter::new(
+                self.columns.clone(),
+                self.precision,
+                self.relative_metric,
+                self.relative_error,
+            )),
+            ExportType::Orgmode => Box::new(OrgmodeExporter::new(
+                self.orgmode_format.clone(),
+                self.columns.clone(),
+                self.precision,
+                self.relative_metric,
+                self.relative_error,
+            )),
+            ExportType::Otlp => Box::<OtlpExporter>::default(),
+            ExportType::Plot(plot) => Box::new(PlotExporter::new(*plot)),
+            ExportType::Runs => Box::new(RunsExporter::new(self.csv_format, self.precision)),
+            ExportType::Template(template) => Box::new(TemplateExporter::new(
+                template.clone(),
+                self.relative_metric,
+                self.relative_error,
+            )),
+        }
+    }
+
     /// Add an additional exporter to the ExportManager
     pub fn add_exporter(&mut self, export_type: ExportType, filename: &str) -> Result<()> {
-        let exporter: Box<dyn Exporter> = match export_type {
-            ExportType::Asciidoc => Box::<AsciidocExporter>::default(),
-            ExportType::Csv => Box::<CsvExporter>::default(),
-            ExportType::Json => Box::<JsonExporter>::default(),
-            ExportType::Markdown => Box::<MarkdownExporter>::default(),
-            ExportType::Orgmode => Box::<OrgmodeExporter>::default(),
+        let exporter = self.exporter(&export_type);
+
+        let signing_key = match export_type {
+            ExportType::Json if filename != "-" => self.signing_key.clone(),
+            _ => None,
         };
+        let is_otlp = matches!(export_type, ExportType::Otlp);
+        let is_url = is_otlp && filename.contains("://");
 
         self.exporters.push(ExporterWithTarget {
             exporter,
+            signing_key,
             target: if filename == "-" {
                 ExportTarget::Stdout
+            } else if is_otlp && filename == otlp::ENVIRONMENT_TARGET {
+                ExportTarget::Url(otlp::endpoint_from_environment())
+            } else if is_url {
+                ExportTarget::Url(filename.to_string())
             } else {
                 let _ = File::create(filename)
                     .with_context(|| format!("Could not create export file '{filename}'"))?;
@@ -123,6 +319,11 @@ impl ExportManager {
         Ok(())
     }
 
+    /// A hyperfine command line that reruns only the benchmark with the given number
+    pub fn reproduce(&self, number: usize, command: &Command) -> String {
+        self.invocation.reproduce(number, command)
+    }
+
     /// Write the given results to all Exporters. The 'intermediate' flag specifies
     /// whether this is being called while still performing benchmarks, or if this
     /// is the final call after all benchmarks have been finished. In the former case,
@@ -133,13 +334,20 @@ impl ExportManager {
         for e in &self.exporters {
             let content = || {
                 e.exporter
-                    .serialize(results, self.time_unit, self.sort_order)
+                    .serialize(results, self.time_unit, self.memory_unit, self.sort_order)
             };
 
             match e.target {
                 ExportTarget::File(ref filename) => {
                     if intermediate {
-                        write_to_file(filename, &content()?)?
+                        let mut content = content()?;
+                        if let Some(compression) = Compression::from_filename(filename) {
+                            content = compression.compress(&content)?;
+                        }
+                        write_to_file(filename, &content)?;
+                        if let Some(ref key) = e.signing_key {
+                            key.write_signature(filename, &content)?;
+                        }
                     }
                 }
                 ExportTarget::Stdout => {
@@ -148,15 +356,29 @@ impl ExportManager {
                         println!("{}", String::from_utf8(content()?).unwrap());
                     }
                 }
+                ExportTarget::Url(ref endpoint) => {
+                    if !intermediate {
+                        otlp::send(endpoint, &otlp::headers_from_environment()?, &content()?)?;
+                    }
+                }
+                ExportTarget::Clipboard => {
+                    if !intermediate {
+                        clipboard::copy(&content()?)?;
+                    }
+                }
             }
         }
         Ok(())
     }
 }
 
-/// Write the given content to a file with the specified name
+/// Write the given content to a file with the specified name, replacing any earlier
+/// (possibly longer) content
 fn write_to_file(filename: &str, content: &[u8]) -> Result<()> {
-    let mut file = OpenOptions::new().write(true).open(filename)?;
+    let mut file = OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(filename)?;
     file.write_all(content)
         .with_context(|| format!("Failed to export results to '{filename}'"))
 }
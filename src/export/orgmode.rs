@@ -1,8 +1,34 @@
+use super::columns::Column;
 use super::markup::Alignment;
 use crate::export::markup::MarkupExporter;
+use crate::options::{OrgmodeFormat, Precision, RelativeError, RelativeMetric};
 
 #[derive(Default)]
-pub struct OrgmodeExporter {}
+pub struct OrgmodeExporter {
+    format: OrgmodeFormat,
+    columns: Option<Vec<Column>>,
+    precision: Precision,
+    relative_metric: RelativeMetric,
+    relative_error: RelativeError,
+}
+
+impl OrgmodeExporter {
+    pub fn new(
+        format: OrgmodeFormat,
+        columns: Option<Vec<Column>>,
+        precision: Precision,
+        relative_metric: RelativeMetric,
+        relative_error: RelativeError,
+    ) -> Self {
+        OrgmodeExporter {
+            format,
+            columns,
+            precision,
+            relative_metric,
+            relative_error,
+        }
+    }
+}
 
 impl MarkupExporter for OrgmodeExporter {
     fn table_row(&self, cells: &[&str]) -> String {
@@ -17,9 +43,64 @@ impl MarkupExporter for OrgmodeExporter {
         format!("|{}--|\n", "--+".repeat(cell_aligmnents.len() - 1))
     }
 
+    fn table_header(&self, cell_aligmnents: &[Alignment]) -> String {
+        let mut header = String::new();
+        if let Some(ref name) = self.format.name {
+            header.push_str(&format!("#+NAME: {name}\n"));
+        }
+        if self.format.alignment_cookies {
+            let cookies: Vec<_> = cell_aligmnents
+                .iter()
+                .map(|alignment| match alignment {
+                    Alignment::Left => "<l>",
+                    Alignment::Right => "<r>",
+                })
+                .collect();
+            header.push_str(&self.table_row(&cookies));
+        }
+        header
+    }
+
+    fn table_footer(&self, columns: &[Column]) -> String {
+        let position = |column| columns.iter().position(|&c| c == column).map(|i| i + 1);
+        // The formula computes the relative speed from the mean wall clock times of the whole
+        // table, so it does not apply to groups of commands
+        let (true, Some(mean), Some(relative)) = (
+            self.format.formula
+                && self.relative_metric == RelativeMetric::WallClock
+                && position(Column::Group).is_none(),
+            position(Column::Mean),
+            position(Column::Relative),
+        ) else {
+            return "".to_string();
+        };
+
+        // The ';N' flag converts the fields to numbers, which drops the standard deviation
+        // ('± …') of the mean.
+        format!(
+            "#+TBLFM: ${relative}='(format \"%.2f\" (/ ${mean} (apply #'min '(@I${mean}..@>${mean}))));N\n"
+        )
+    }
+
     fn command(&self, cmd: &str) -> String {
         format!("={cmd}=")
     }
+
+    fn columns(&self) -> Option<&[Column]> {
+        self.columns.as_deref()
+    }
+
+    fn precision(&self) -> Precision {
+        self.precision
+    }
+
+    fn relative_metric(&self) -> RelativeMetric {
+        self.relative_metric
+    }
+
+    fn relative_error(&self) -> RelativeError {
+        self.relative_error
+    }
 }
 
 /// Check Emacs org-mode data row formatting
@@ -49,3 +130,58 @@ fn test_orgmode_formatter_table_line() {
 
     assert_eq!(expect, actual);
 }
+
+/// Check the optional table name, alignment cookies and formula
+#[test]
+fn test_orgmode_formatter_table_name_alignment_and_formula() {
+    let alignments = [Alignment::Left, Alignment::Right, Alignment::Right];
+    let columns = [Column::Command, Column::Mean, Column::Relative];
+
+    let exporter = OrgmodeExporter::default();
+    assert_eq!(exporter.table_header(&alignments), "");
+    assert_eq!(exporter.table_footer(&columns), "");
+
+    let exporter = OrgmodeExporter::new(
+        OrgmodeFormat {
+            name: Some("results".into()),
+            alignment_cookies: true,
+            formula: true,
+        },
+        None,
+        Precision::default(),
+        RelativeMetric::WallClock,
+        RelativeError::default(),
+    );
+    assert_eq!(
+        exporter.table_header(&alignments),
+        "#+NAME: results\n| <l>  |  <r> |  <r> |\n"
+    );
+    assert_eq!(
+        exporter.table_footer(&columns),
+        "#+TBLFM: $3='(format \"%.2f\" (/ $2 (apply #'min '(@I$2..@>$2))));N\n"
+    );
+
+    // The formula can only be computed if both the mean and the relative speed are shown
+    assert_eq!(
+        exporter.table_footer(&[Column::Command, Column::Relative]),
+        ""
+    );
+    assert_eq!(
+        exporter.table_footer(&[Column::Relative, Column::Command, Column::Mean]),
+        "#+TBLFM: $1='(format \"%.2f\" (/ $3 (apply #'min '(@I$3..@>$3))));N\n"
+    );
+
+    // ... and if the relative speed is based on the wall clock time
+    let exporter = OrgmodeExporter::new(
+        OrgmodeFormat {
+            name: None,
+            alignment_cookies: false,
+            formula: true,
+        },
+        None,
+        Precision::default(),
+        RelativeMetric::Cpu,
+        RelativeError::default(),
+    );
+    assert_eq!(exporter.table_footer(&columns), "");
+}
@@ -0,0 +1,479 @@
+//! Export of the benchmark session as OpenTelemetry traces, in the JSON encoding of the
+//! OpenTelemetry protocol (OTLP). The session is a root span, with a child span for each
+//! benchmark, which in turn has a child span for each run. The export can be written to a
+//! file or sent to an OTLP/HTTP endpoint (e.g. an OpenTelemetry Collector, Jaeger or Tempo).
+//! Like the OpenTelemetry SDKs, the endpoint and additional headers (e.g. API keys) can be
+//! given by the `OTEL_EXPORTER_OTLP_*` environment variables. Only plain HTTP is supported,
+//! so a collector is needed to forward the spans to backends that require HTTPS.
+
+use std::env;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::crate_version;
+use serde_json::{json, Value};
+
+use super::Exporter;
+use crate::benchmark::benchmark_result::BenchmarkResult;
+use crate::options::{MemoryUnit, SortOrder};
+use crate::quantity::{byte, second, TimeUnit};
+use crate::util::exit_code::extract_exit_code;
+
+use anyhow::{bail, Context, Result};
+
+/// Path of the traces endpoint of an OTLP/HTTP receiver
+const TRACES_PATH: &str = "/v1/traces";
+
+/// Target of '--export-otlp' for the endpoint given by the environment variables
+pub const ENVIRONMENT_TARGET: &str = "env";
+
+/// Endpoint of a local receiver, used if no endpoint is given by the environment variables
+const DEFAULT_ENDPOINT: &str = "http://localhost:4318";
+
+/// Timeout for connecting to and communicating with the OTLP endpoint
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Span kind 'internal', see the OTLP specification
+const SPAN_KIND_INTERNAL: u32 = 1;
+
+/// Span status code 'error', see the OTLP specification
+const STATUS_CODE_ERROR: u32 = 2;
+
+pub struct OtlpExporter {
+    /// Start of the benchmark session
+    session_start: SystemTime,
+}
+
+impl Default for OtlpExporter {
+    fn default() -> Self {
+        OtlpExporter {
+            session_start: SystemTime::now(),
+        }
+    }
+}
+
+/// Nanoseconds since the Unix epoch. 64-bit integers are encoded as strings in OTLP/JSON.
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string()
+}
+
+/// Random identifier with the given number of bytes, encoded in hex
+fn random_id(bytes: usize) -> String {
+    (0..bytes)
+        .map(|_| format!("{:02x}", rand::random::<u8>()))
+        .collect()
+}
+
+fn string_attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+fn int_attribute(key: &str, value: i64) -> Value {
+    json!({ "key": key, "value": { "intValue": value.to_string() } })
+}
+
+fn double_attribute(key: &str, value: f64) -> Value {
+    json!({ "key": key, "value": { "doubleValue": value } })
+}
+
+fn bool_attribute(key: &str, value: bool) -> Value {
+    json!({ "key": key, "value": { "boolValue": value } })
+}
+
+struct Span {
+    span_id: String,
+    parent_span_id: Option<String>,
+    name: String,
+    start: SystemTime,
+    end: SystemTime,
+    attributes: Vec<Value>,
+    failed: bool,
+}
+
+impl Span {
+    fn to_json(&self, trace_id: &str) -> Value {
+        let mut span = json!({
+            "traceId": trace_id,
+            "spanId": self.span_id,
+            "name": self.name,
+            "kind": SPAN_KIND_INTERNAL,
+            "startTimeUnixNano": unix_nanos(self.start),
+            "endTimeUnixNano": unix_nanos(self.end),
+            "attributes": self.attributes,
+        });
+        if let Some(ref parent_span_id) = self.parent_span_id {
+            span["parentSpanId"] = json!(parent_span_id);
+        }
+        if self.failed {
+            span["status"] = json!({ "code": STATUS_CODE_ERROR });
+        }
+        span
+    }
+}
+
+impl Exporter for OtlpExporter {
+    fn serialize(
+        &self,
+        results: &[BenchmarkResult],
+        _time_unit: Option<TimeUnit>,
+        _memory_unit: Option<MemoryUnit>,
+        _sort_order: SortOrder,
+    ) -> Result<Vec<u8>> {
+        let trace_id = random_id(16);
+        let session_span_id = random_id(8);
+        let mut spans = vec![];
+
+        // Runs without a recorded start time are assumed to follow the previous one
+        let mut previous_end = self.session_start;
+        for result in results {
+            let benchmark_span_id = random_id(8);
+            let mut run_spans = vec![];
+            for (run, measurement) in result.measurements.measurements.iter().enumerate() {
+                let start = measurement.start_time.unwrap_or(previous_end);
+                let end = start
+                    + Duration::from_secs_f64(measurement.time_wall_clock.get::<second>().max(0.0));
+                previous_end = end;
+
+                let exit_code = extract_exit_code(measurement.exit_status);
+                let mut attributes = vec![
+                    int_attribute("hyperfine.run", run as i64),
+                    double_attribute(
+                        "hyperfine.time_user_s",
+                        measurement.time_user.get::<second>(),
+                    ),
+                    double_attribute(
+                        "hyperfine.time_system_s",
+                        measurement.time_system.get::<second>(),
+                    ),
+                    int_attribute(
+                        "hyperfine.peak_memory_usage_bytes",
+                        measurement.peak_memory_usage.get::<byte>() as i64,
+                    ),
+                    bool_attribute("hyperfine.discarded", measurement.discarded),
+                    bool_attribute("hyperfine.failed_attempt", measurement.failed_attempt),
+                ];
+                if let Some(code) = exit_code {
+                    attributes.push(int_attribute("process.exit_code", code.into()));
+                }
+
+                run_spans.push(Span {
+                    span_id: random_id(8),
+                    parent_span_id: Some(benchmark_span_id.clone()),
+                    name: format!("run {run}"),
+                    start,
+                    end,
+                    attributes,
+                    failed: exit_code != Some(0),
+                });
+            }
+
+            let mut attributes = vec![
+                string_attribute("hyperfine.command", &result.command),
+                int_attribute("hyperfine.runs", result.measurements.len() as i64),
+                double_attribute(
+                    "hyperfine.mean_s",
+                    result.mean_wall_clock_time().get::<second>(),
+                ),
+            ];
+            if let Some(stddev) = result.measurements.stddev() {
+                attributes.push(double_attribute(
+                    "hyperfine.stddev_s",
+                    stddev.get::<second>(),
+                ));
+            }
+            for (name, parameter) in &result.parameters {
+                attributes.push(string_attribute(
+                    &format!("hyperfine.parameter.{name}"),
+                    &parameter.value,
+                ));
+            }
+
+            spans.push(Span {
+                span_id: benchmark_span_id,
+                parent_span_id: Some(session_span_id.clone()),
+                name: result.command_with_unused_parameters(),
+                start: run_spans.first().map_or(previous_end, |span| span.start),
+                end: run_spans.last().map_or(previous_end, |span| span.end),
+                attributes,
+                failed: run_spans.iter().any(|span| span.failed),
+            });
+            spans.extend(run_spans);
+        }
+        spans.insert(
+            0,
+            Span {
+                span_id: session_span_id,
+                parent_span_id: None,
+                name: "hyperfine".to_string(),
+                start: self.session_start,
+                end: SystemTime::now().max(previous_end),
+                attributes: vec![int_attribute("hyperfine.benchmarks", results.len() as i64)],
+                failed: false,
+            },
+        );
+
+        let request = json!({
+            "resourceSpans": [{
+                "resource": {
+                    "attributes": [string_attribute("service.name", "hyperfine")],
+                },
+                "scopeSpans": [{
+                    "scope": { "name": "hyperfine", "version": crate_version!() },
+                    "spans": spans.iter().map(|span| span.to_json(&trace_id)).collect::<Vec<_>>(),
+                }],
+            }],
+        });
+
+        let mut output = serde_json::to_vec_pretty(&request)?;
+        output.push(b'\n');
+        Ok(output)
+    }
+}
+
+/// The endpoint given by `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` (which is used as is) or by
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` (to which the path of the traces endpoint is appended)
+pub fn endpoint_from_environment() -> String {
+    let variable = |name| env::var(name).ok().filter(|value| !value.is_empty());
+    match variable("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT") {
+        Some(endpoint) => endpoint,
+        None => format!(
+            "{}{TRACES_PATH}",
+            variable("OTEL_EXPORTER_OTLP_ENDPOINT")
+                .as_deref()
+                .unwrap_or(DEFAULT_ENDPOINT)
+                .trim_end_matches('/')
+        ),
+    }
+}
+
+/// The additional headers given by `OTEL_EXPORTER_OTLP_HEADERS` and
+/// `OTEL_EXPORTER_OTLP_TRACES_HEADERS`
+pub fn headers_from_environment() -> Result<Vec<(String, String)>> {
+    let mut headers = vec![];
+    for name in [
+        "OTEL_EXPORTER_OTLP_HEADERS",
+        "OTEL_EXPORTER_OTLP_TRACES_HEADERS",
+    ] {
+        if let Ok(value) = env::var(name) {
+            headers.extend(parse_headers(&value).with_context(|| format!("Invalid ${name}"))?);
+        }
+    }
+    Ok(headers)
+}
+
+/// Parse a list of headers in the format of the OpenTelemetry SDKs ('key1=value1,key2=value2',
+/// with percent-encoded values)
+fn parse_headers(list: &str) -> Result<Vec<(String, String)>> {
+    list.split(',')
+        .filter(|header| !header.trim().is_empty())
+        .map(|header| {
+            let Some((key, value)) = header.split_once('=') else {
+                bail!("The header '{header}' is not of the form 'key=value'");
+            };
+            let key = key.trim();
+            let value = percent_decode(value.trim())
+                .with_context(|| format!("The value of the header '{key}' is not valid"))?;
+            if key.is_empty()
+                || !key
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b"-_".contains(&b))
+            {
+                bail!("Invalid header name '{key}'");
+            }
+            if value.contains(['\r', '\n']) {
+                bail!("The value of the header '{key}' contains a line break");
+            }
+            Ok((key.to_string(), value))
+        })
+        .collect()
+}
+
+fn percent_decode(value: &str) -> Result<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&next, tail)) = rest.split_first() {
+        if next == b'%' {
+            let hex = tail
+                .get(..2)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .context("Invalid percent-encoding")?;
+            bytes.push(hex);
+            rest = &tail[2..];
+        } else {
+            bytes.push(next);
+            rest = tail;
+        }
+    }
+    Ok(String::from_utf8(bytes)?)
+}
+
+/// Send the serialized spans to the given OTLP/HTTP endpoint ('http://host:port', optionally
+/// followed by a path, which defaults to '/v1/traces'), with the given additional headers
+pub fn send(endpoint: &str, headers: &[(String, String)], content: &[u8]) -> Result<()> {
+    let Some(address) = endpoint.strip_prefix("http://") else {
+        bail!(
+            "Unsupported OTLP endpoint '{endpoint}': only 'http://' endpoints are supported \
+             (use an OpenTelemetry Collector to forward the spans via HTTPS)"
+        );
+    };
+    let (authority, path) = match address.find('/') {
+        Some(index) if address.len() > index + 1 => address.split_at(index),
+        Some(index) => (&address[..index], TRACES_PATH),
+        None => (address, TRACES_PATH),
+    };
+    let host_and_port = if authority.contains(':') {
+        authority.to_string()
+    } else {
+        format!("{authority}:80")
+    };
+
+    let error_context = || format!("Could not send the spans to '{endpoint}'");
+    let mut stream = TcpStream::connect(&host_and_port).with_context(error_context)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let mut header = format!(
+        "POST {path} HTTP/1.1\r\nHost: {authority}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n",
+        content.len()
+    );
+    for (key, value) in headers {
+        header += &format!("{key}: {value}\r\n");
+    }
+    header += "\r\n";
+    stream
+        .write_all(header.as_bytes())
+        .and_then(|_| stream.write_all(content))
+        .with_context(error_context)?;
+
+    let mut response = String::new();
+    stream
+        .read_to_string(&mut response)
+        .with_context(error_context)?;
+    let status_line = response.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1) {
+        Some(status) if status.starts_with('2') => Ok(()),
+        _ => bail!("The OTLP endpoint '{endpoint}' responded with '{status_line}'"),
+    }
+}
+
+#[test]
+fn test_otlp_export() {
+    use crate::benchmark::measurement::{Measurement, Measurements};
+    use crate::quantity::Time;
+
+    let result = |command: &str, times: &[f64]| BenchmarkResult {
+        command: command.into(),
+        measurements: Measurements::new(
+            times
+                .iter()
+                .map(|&time| Measurement {
+                    time_wall_clock: Time::new::<second>(time),
+                    ..Default::default()
+                })
+                .collect(),
+        ),
+        ..Default::default()
+    };
+    let results = vec![
+        result("sleep 0.1", &[0.1, 0.1]),
+        result("sleep 0.2", &[0.2]),
+    ];
+
+    let exporter = OtlpExporter {
+        session_start: UNIX_EPOCH + Duration::from_secs(1),
+    };
+    let output = exporter
+        .serialize(&results, None, None, SortOrder::Command)
+        .unwrap();
+    let output: Value = serde_json::from_slice(&output).unwrap();
+    let spans = output["resourceSpans"][0]["scopeSpans"][0]["spans"]
+        .as_array()
+        .unwrap();
+
+    // Session, 2 benchmarks and 3 runs
+    assert_eq!(spans.len(), 6);
+    let session = &spans[0];
+    assert_eq!(session["name"], "hyperfine");
+    assert!(session.get("parentSpanId").is_none());
+    assert_eq!(session["startTimeUnixNano"], "1000000000");
+
+    let benchmark = &spans[1];
+    assert_eq!(benchmark["name"], "sleep 0.1");
+    assert_eq!(benchmark["parentSpanId"], session["spanId"]);
+    assert_eq!(benchmark["endTimeUnixNano"], "1200000000");
+
+    let run = &spans[2];
+    assert_eq!(run["parentSpanId"], benchmark["spanId"]);
+    assert_eq!(run["traceId"], session["traceId"]);
+    assert_eq!(run["startTimeUnixNano"], "1000000000");
+    assert_eq!(run["endTimeUnixNano"], "1100000000");
+
+    assert_eq!(spans[4]["name"], "sleep 0.2");
+    assert_eq!(spans[5]["startTimeUnixNano"], "1200000000");
+}
+
+#[test]
+fn test_send_to_otlp_endpoint() {
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::thread;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+
+        let mut content_length = 0;
+        let mut api_key = None;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line == "\r\n" {
+                break;
+            }
+            if let Some(length) = line.strip_prefix("Content-Length: ") {
+                content_length = length.trim().parse().unwrap();
+            }
+            if let Some(key) = line.strip_prefix("x-api-key: ") {
+                api_key = Some(key.trim().to_string());
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+        (request_line, api_key, body)
+    });
+
+    let headers = parse_headers("x-api-key=secret%3D").unwrap();
+    send(&format!("http://127.0.0.1:{port}"), &headers, b"{}").unwrap();
+    let (request_line, api_key, body) = server.join().unwrap();
+    assert_eq!(request_line, "POST /v1/traces HTTP/1.1\r\n");
+    assert_eq!(api_key.as_deref(), Some("secret="));
+    assert_eq!(body, b"{}");
+
+    assert!(send("https://example.com", &[], b"{}").is_err());
+}
+
+#[test]
+fn test_parse_headers() {
+    assert_eq!(
+        parse_headers(" a=1 , b-c=x%20y,").unwrap(),
+        vec![("a".into(), "1".into()), ("b-c".into(), "x y".into())]
+    );
+    assert!(parse_headers("a").is_err());
+    assert!(parse_headers("a=%0d%0aEvil: 1").is_err());
+    assert!(parse_headers("a=%zz").is_err());
+}
@@ -0,0 +1,123 @@
+//! Collection of all exports and artifacts of a session in one directory (`--output-dir`),
+//! which is described by a `manifest.json` file.
+
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::invocation::Invocation;
+use super::signature::SystemFingerprint;
+use super::{ExportManager, ExportType};
+use crate::util::timestamp::UtcDateTime;
+
+use anyhow::{Context, Result};
+
+/// The exports that are written to the directory, with their file names
+const EXPORTS: &[(&str, ExportType, &str)] = &[
+    (
+        "results.json",
+        ExportType::Json,
+        "Summary statistics and all runs (JSON)",
+    ),
+    ("results.csv", ExportType::Csv, "Summary statistics (CSV)"),
+    (
+        "runs.csv",
+        ExportType::Runs,
+        "Measurements of all runs (CSV)",
+    ),
+    (
+        "results.md",
+        ExportType::Markdown,
+        "Summary table (Markdown)",
+    ),
+    (
+        "results.adoc",
+        ExportType::Asciidoc,
+        "Summary table (AsciiDoc)",
+    ),
+    (
+        "results.org",
+        ExportType::Orgmode,
+        "Summary table (org-mode)",
+    ),
+    (
+        "bencher.json",
+        ExportType::Bencher,
+        "Mean times in the Bencher Metric Format",
+    ),
+    (
+        "otlp.json",
+        ExportType::Otlp,
+        "OpenTelemetry spans (OTLP/JSON)",
+    ),
+];
+
+#[derive(Debug, Serialize)]
+struct ManifestEntry {
+    path: String,
+    description: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct Manifest {
+    hyperfine_version: &'static str,
+
+    /// Start of the session (UTC)
+    created: String,
+
+    files: Vec<ManifestEntry>,
+}
+
+/// Create the directory, add exporters for all export formats to it, and write the
+/// description of the system and of the invocation as well as the manifest. The exports
+/// are written (and updated) along with all other exports. If `has_profiles` is set, the
+/// manifest refers to the 'profiles' subdirectory (see `--profile-with`).
+pub fn populate(
+    export_manager: &mut ExportManager,
+    directory: &Path,
+    invocation: &Invocation,
+    has_profiles: bool,
+) -> Result<()> {
+    fs::create_dir_all(directory)
+        .with_context(|| format!("Could not create the directory '{}'", directory.display()))?;
+
+    let mut files = vec![];
+    let mut add_file = |name: &str, description| {
+        files.push(ManifestEntry {
+            path: name.to_string(),
+            description,
+        });
+        directory.join(name)
+    };
+
+    for (name, export_type, description) in EXPORTS {
+        let path = add_file(name, description);
+        export_manager.add_exporter(export_type.clone(), &path.to_string_lossy())?;
+    }
+
+    write_json(
+        &add_file("system.json", "Description of the system"),
+        &SystemFingerprint::collect(),
+    )?;
+    write_json(
+        &add_file("invocation.json", "Effective configuration of the session"),
+        invocation,
+    )?;
+    if has_profiles {
+        add_file("profiles", "Profiles of the extra runs ('--profile-with')");
+    }
+
+    let manifest = Manifest {
+        hyperfine_version: clap::crate_version!(),
+        created: UtcDateTime::now().to_string(),
+        files,
+    };
+    write_json(&directory.join("manifest.json"), &manifest)
+}
+
+fn write_json(path: &Path, value: &impl Serialize) -> Result<()> {
+    let mut content = serde_json::to_string_pretty(value)?;
+    content.push('\n');
+    fs::write(path, content).with_context(|| format!("Could not write '{}'", path.display()))
+}
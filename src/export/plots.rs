@@ -0,0 +1,149 @@
+//! Ready-to-run plotting scripts (`--export-plots`). They are derived from the scripts in the
+//! 'scripts' directory, but the wall clock times of the runs are embedded in them, so they do
+//! not depend on the format of the JSON export of a particular version of hyperfine.
+
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::{ExportManager, ExportType, Exporter};
+use crate::benchmark::benchmark_result::BenchmarkResult;
+use crate::options::{MemoryUnit, SortOrder};
+use crate::quantity::{second, TimeUnit};
+
+use anyhow::{Context, Result};
+
+/// The placeholder of the data in the scripts
+const RESULTS_PLACEHOLDER: &str = "__RESULTS__";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Plot {
+    /// Box and whisker plot of the wall clock times
+    Whisker,
+
+    /// Histogram of the wall clock times
+    Histogram,
+
+    /// Wall clock time of each run in the order of the runs
+    Progression,
+}
+
+impl Plot {
+    const ALL: [Plot; 3] = [Plot::Whisker, Plot::Histogram, Plot::Progression];
+
+    fn file_name(self) -> &'static str {
+        match self {
+            Plot::Whisker => "plot_whisker.py",
+            Plot::Histogram => "plot_histogram.py",
+            Plot::Progression => "plot_progression.py",
+        }
+    }
+
+    fn script(self) -> &'static str {
+        match self {
+            Plot::Whisker => include_str!("plots/plot_whisker.py"),
+            Plot::Histogram => include_str!("plots/plot_histogram.py"),
+            Plot::Progression => include_str!("plots/plot_progression.py"),
+        }
+    }
+}
+
+/// The data of a benchmark that the scripts use. It is embedded as a JSON value, which is a
+/// valid Python expression as it only contains strings and numbers.
+#[derive(Debug, Serialize)]
+struct PlotData {
+    command: String,
+
+    /// Wall clock times of the runs in seconds
+    times: Vec<f64>,
+}
+
+pub struct PlotExporter {
+    plot: Plot,
+}
+
+impl PlotExporter {
+    pub fn new(plot: Plot) -> Self {
+        PlotExporter { plot }
+    }
+}
+
+impl Exporter for PlotExporter {
+    fn serialize(
+        &self,
+        results: &[BenchmarkResult],
+        _time_unit: Option<TimeUnit>,
+        _memory_unit: Option<MemoryUnit>,
+        _sort_order: SortOrder,
+    ) -> Result<Vec<u8>> {
+        let data: Vec<_> = results
+            .iter()
+            .map(|result| PlotData {
+                command: result.command_with_unused_parameters(),
+                times: result
+                    .measurements
+                    .wall_clock_times()
+                    .into_iter()
+                    .map(|time| time.get::<second>())
+                    .collect(),
+            })
+            .collect();
+        let data = serde_json::to_string_pretty(&data)?;
+
+        Ok(self
+            .plot
+            .script()
+            .replacen(RESULTS_PLACEHOLDER, &data, 1)
+            .into_bytes())
+    }
+}
+
+/// Create the directory and add an exporter for each script to it, such that the scripts
+/// are written (and updated) along with all other exports
+pub fn populate(export_manager: &mut ExportManager, directory: &Path) -> Result<()> {
+    fs::create_dir_all(directory)
+        .with_context(|| format!("Could not create the directory '{}'", directory.display()))?;
+
+    for plot in Plot::ALL {
+        let path = directory.join(plot.file_name());
+        export_manager.add_exporter(ExportType::Plot(plot), &path.to_string_lossy())?;
+
+        // The scripts can be run directly, as they start with a shebang line
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+                .with_context(|| format!("Could not make '{}' executable", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_plot_scripts() {
+    use crate::benchmark::measurement::{Measurement, Measurements};
+    use crate::quantity::Time;
+
+    let measurement = |time| Measurement {
+        time_wall_clock: Time::new::<second>(time),
+        ..Default::default()
+    };
+    let results = [BenchmarkResult {
+        command: "sleep \"0.5\"".into(),
+        measurements: Measurements::new(vec![measurement(0.5), measurement(0.25)]),
+        ..Default::default()
+    }];
+
+    for plot in Plot::ALL {
+        let script = PlotExporter::new(plot)
+            .serialize(&results, None, None, SortOrder::Command)
+            .unwrap();
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.starts_with("#!/usr/bin/env python\n"));
+        assert!(!script.contains(RESULTS_PLACEHOLDER));
+        assert!(script.contains(
+            "RESULTS = [\n  {\n    \"command\": \"sleep \\\"0.5\\\"\",\n    \"times\": [\n      0.5,\n      0.25\n    ]\n  }\n]\n"
+        ));
+    }
+}
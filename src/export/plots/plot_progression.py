@@ -0,0 +1,79 @@
+#!/usr/bin/env python
+# /// script
+# requires-python = ">=3.10"
+# dependencies = [
+#     "matplotlib",
+#     "pyqt6",
+#     "numpy",
+# ]
+# ///
+
+"""This program shows the `hyperfine` benchmark results that are embedded in it in a
+sequential way in order to debug possible background interference, caching effects,
+thermal throttling and similar effects. It has been written by `hyperfine --export-plots`.
+"""
+
+import argparse
+
+import matplotlib.pyplot as plt
+import numpy as np
+
+RESULTS = __RESULTS__
+
+
+def moving_average(times, num_runs):
+    times_padded = np.pad(
+        times, (num_runs // 2, num_runs - 1 - num_runs // 2), mode="edge"
+    )
+    kernel = np.ones(num_runs) / num_runs
+    return np.convolve(times_padded, kernel, mode="valid")
+
+
+parser = argparse.ArgumentParser(description=__doc__)
+parser.add_argument("--title", help="Plot Title")
+parser.add_argument("-o", "--output", help="Save image to the given filename.")
+parser.add_argument(
+    "-w",
+    "--moving-average-width",
+    type=int,
+    metavar="num_runs",
+    help="Width of the moving-average window (default: N/5)",
+)
+parser.add_argument(
+    "--no-moving-average",
+    action="store_true",
+    help="Do not show moving average curve",
+)
+
+args = parser.parse_args()
+
+legend = []
+for result in RESULTS:
+    times = result["times"]
+    num = len(times)
+    nums = range(num)
+
+    plt.scatter(x=nums, y=times, marker=".")
+    plt.ylim([0, None])
+    plt.xlim([-1, num])
+    legend.append(result["command"])
+
+    if not args.no_moving_average:
+        moving_average_width = (
+            num // 5 if args.moving_average_width is None else args.moving_average_width
+        )
+
+        average = moving_average(times, max(moving_average_width, 1))
+        plt.plot(nums, average, "-")
+        legend.append("moving average")
+
+if args.title:
+    plt.title(args.title)
+plt.legend(legend)
+
+plt.ylabel("Time [s]")
+
+if args.output:
+    plt.savefig(args.output)
+else:
+    plt.show()
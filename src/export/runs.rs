@@ -0,0 +1,205 @@
+use std::borrow::Cow;
+
+use csv::WriterBuilder;
+
+use super::Exporter;
+use crate::benchmark::benchmark_result::BenchmarkResult;
+use crate::options::{CsvFormat, MemoryUnit, Precision, SortOrder};
+use crate::quantity::{byte, Quantity, TimeUnit};
+use crate::util::exit_code::extract_exit_code;
+
+use anyhow::Result;
+
+/// Exports the raw measurements of every single run as a flat CSV table
+#[derive(Default)]
+pub struct RunsExporter {
+    format: CsvFormat,
+    precision: Precision,
+}
+
+impl RunsExporter {
+    pub fn new(format: CsvFormat, precision: Precision) -> Self {
+        RunsExporter { format, precision }
+    }
+}
+
+impl Exporter for RunsExporter {
+    fn serialize(
+        &self,
+        results: &[BenchmarkResult],
+        _time_unit: Option<TimeUnit>,
+        _memory_unit: Option<MemoryUnit>,
+        _sort_order: SortOrder,
+    ) -> Result<Vec<u8>> {
+        const CSV_UNIT: TimeUnit = TimeUnit::Second;
+        const CSV_PRECISION: usize = 6;
+        let precision = self.precision.time.unwrap_or(CSV_PRECISION);
+
+        let mut writer = WriterBuilder::new()
+            .delimiter(self.format.delimiter)
+            .from_writer(vec![]);
+
+        {
+            let mut headers: Vec<Cow<[u8]>> = [
+                "command",
+                "run",
+                "time_wall_clock",
+                "time_user",
+                "time_system",
+                "peak_memory_usage",
+                "minor_page_faults",
+                "major_page_faults",
+                "exit_code",
+                "discarded",
+                "failed_attempt",
+            ]
+            .iter()
+            .map(|x| Cow::Borrowed(x.as_bytes()))
+            .collect();
+            if let Some(res) = results.first() {
+                for param_name in res.parameters.keys() {
+                    headers.push(Cow::Owned(format!("parameter_{param_name}").into_bytes()));
+                }
+            }
+            writer.write_record(headers)?;
+        }
+
+        for res in results {
+            for (run, measurement) in res.measurements.measurements.iter().enumerate() {
+                let mut fields = vec![
+                    Cow::Borrowed(res.command.as_bytes()),
+                    Cow::Owned(run.to_string().into_bytes()),
+                ];
+                for t in &[
+                    measurement.time_wall_clock,
+                    measurement.time_user,
+                    measurement.time_system,
+                ] {
+                    fields.push(Cow::Owned(
+                        self.format
+                            .number(t.format_with_precision(CSV_UNIT, precision))
+                            .into_bytes(),
+                    ));
+                }
+                fields.push(Cow::Owned(
+                    self.format
+                        .number(measurement.peak_memory_usage.get::<byte>().to_string())
+                        .into_bytes(),
+                ));
+                for count in &[measurement.minor_page_faults, measurement.major_page_faults] {
+                    fields.push(Cow::Owned(count.to_string().into_bytes()));
+                }
+                fields.push(Cow::Owned(
+                    extract_exit_code(measurement.exit_status)
+                        .map_or_else(String::new, |code| code.to_string())
+                        .into_bytes(),
+                ));
+                for flag in [measurement.discarded, measurement.failed_attempt] {
+                    fields.push(Cow::Borrowed(if flag { b"true" } else { b"false" }));
+                }
+                for v in res.parameters.values() {
+                    fields.push(Cow::Borrowed(v.value.as_bytes()))
+                }
+                writer.write_record(fields)?;
+            }
+        }
+
+        Ok(writer.into_inner()?)
+    }
+}
+
+#[test]
+fn test_runs() {
+    use crate::benchmark::benchmark_result::Parameter;
+    use crate::benchmark::measurement::{Measurement, Measurements};
+    use crate::quantity::{second, Information, Time};
+
+    use std::collections::BTreeMap;
+    use std::process::ExitStatus;
+
+    let exporter = RunsExporter::default();
+
+    let results = vec![
+        BenchmarkResult {
+            command: String::from("command_a"),
+            measurements: Measurements::new(vec![
+                Measurement {
+                    time_wall_clock: Time::new::<second>(7.0),
+                    time_user: Time::new::<second>(6.5),
+                    time_system: Time::new::<second>(0.5),
+                    peak_memory_usage: Information::new::<byte>(1024.),
+                    minor_page_faults: 120,
+                    major_page_faults: 3,
+                    exit_status: ExitStatus::default(),
+                    discarded: true,
+                    ..Default::default()
+                },
+                Measurement {
+                    time_wall_clock: Time::new::<second>(8.0),
+                    time_user: Time::new::<second>(8.0),
+                    time_system: Time::zero(),
+                    peak_memory_usage: Information::new::<byte>(2048.),
+                    exit_status: ExitStatus::default(),
+                    ..Default::default()
+                },
+            ]),
+            parameters: {
+                let mut params = BTreeMap::new();
+                params.insert("foo".into(), Parameter::new("one".into(), false));
+                params
+            },
+            command_template: None,
+            shell_spawning_time: None,
+            captured_output: None,
+            modes: vec![],
+            run_indices: None,
+            execution_order: None,
+            reproduce: None,
+            git: None,
+            stateful: false,
+            group: None,
+            warnings: vec![],
+        },
+        BenchmarkResult {
+            command: String::from("command_b"),
+            measurements: Measurements::new(vec![Measurement {
+                time_wall_clock: Time::new::<second>(17.0),
+                time_user: Time::new::<second>(17.0),
+                time_system: Time::zero(),
+                peak_memory_usage: Information::new::<byte>(512.),
+                exit_status: ExitStatus::default(),
+                ..Default::default()
+            }]),
+            parameters: {
+                let mut params = BTreeMap::new();
+                params.insert("foo".into(), Parameter::new("two".into(), false));
+                params
+            },
+            command_template: None,
+            shell_spawning_time: None,
+            captured_output: None,
+            modes: vec![],
+            run_indices: None,
+            execution_order: None,
+            reproduce: None,
+            git: None,
+            stateful: false,
+            group: None,
+            warnings: vec![],
+        },
+    ];
+
+    let actual = String::from_utf8(
+        exporter
+            .serialize(&results, None, None, SortOrder::Command)
+            .unwrap(),
+    )
+    .unwrap();
+
+    insta::assert_snapshot!(actual, @r#"
+    command,run,time_wall_clock,time_user,time_system,peak_memory_usage,minor_page_faults,major_page_faults,exit_code,discarded,failed_attempt,parameter_foo
+    command_a,0,7.000000,6.500000,0.500000,1024,120,3,0,true,false,one
+    command_a,1,8.000000,8.000000,0.000000,2048,0,0,0,false,false,one
+    command_b,0,17.000000,17.000000,0.000000,512,0,0,0,false,false,two
+    "#);
+}
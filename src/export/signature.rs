@@ -0,0 +1,142 @@
+//! Signing of exported results (`--sign-key`), such that benchmark results can be used as
+//! tamper-evident evidence. The export is signed with OpenSSL, and the signature is written
+//! to a detached `<FILE>.sig` file next to the export. It can be verified with
+//!
+//! ```text
+//! openssl dgst -sha256 -verify public.pem -signature results.json.sig results.json
+//! ```
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde::Serialize;
+
+use crate::util::hash::{fnv1a, FNV_OFFSET_BASIS};
+
+use anyhow::{bail, Context, Result};
+
+/// Description of the system that produced the results, which is embedded in signed exports
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SystemFingerprint {
+    /// Stable hash of all other fields, to quickly compare the systems of two exports
+    id: String,
+
+    os: &'static str,
+    arch: &'static str,
+    kernel: Option<String>,
+    hostname: Option<String>,
+    cpu_model: Option<String>,
+    cpu_count: Option<usize>,
+}
+
+impl SystemFingerprint {
+    pub fn collect() -> Self {
+        let mut fingerprint = SystemFingerprint {
+            id: String::new(),
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            kernel: read_trimmed("/proc/sys/kernel/osrelease"),
+            hostname: read_trimmed("/proc/sys/kernel/hostname")
+                .or_else(|| std::env::var("COMPUTERNAME").ok()),
+            cpu_model: cpu_model(),
+            cpu_count: std::thread::available_parallelism().ok().map(|n| n.get()),
+        };
+
+        let description = format!("{fingerprint:?}");
+        fingerprint.id = format!("{:016x}", fnv1a(FNV_OFFSET_BASIS, description.as_bytes()));
+        fingerprint
+    }
+}
+
+fn read_trimmed(path: &str) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|content| content.trim().to_string())
+}
+
+fn cpu_model() -> Option<String> {
+    let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+    cpuinfo
+        .lines()
+        .find(|line| line.starts_with("model name"))
+        .and_then(|line| line.split_once(':'))
+        .map(|(_, model)| model.trim().to_string())
+}
+
+/// A private key (in PEM format) that is used to sign exports
+#[derive(Debug, Clone)]
+pub struct SigningKey {
+    path: PathBuf,
+}
+
+impl SigningKey {
+    pub fn new(path: &str) -> Result<Self> {
+        let path = PathBuf::from(path);
+        if !path.is_file() {
+            bail!("The signing key '{}' does not exist", path.display());
+        }
+        Ok(SigningKey { path })
+    }
+
+    /// Sign the given content with SHA-256 and return the (binary) signature
+    pub fn sign(&self, content: &[u8]) -> Result<Vec<u8>> {
+        let mut child = Command::new("openssl")
+            .arg("dgst")
+            .arg("-sha256")
+            .arg("-sign")
+            .arg(&self.path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Could not run 'openssl', which is required for '--sign-key'")?;
+
+        child
+            .stdin
+            .take()
+            .expect("stdin is piped")
+            .write_all(content)?;
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            bail!(
+                "Could not sign the export with the key '{}': {}",
+                self.path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(output.stdout)
+    }
+
+    /// Sign the given export and write the signature to `<filename>.sig`
+    pub fn write_signature(&self, filename: &str, content: &[u8]) -> Result<()> {
+        let signature_path = signature_path(filename);
+        fs::write(&signature_path, self.sign(content)?).with_context(|| {
+            format!(
+                "Failed to write the signature to '{}'",
+                signature_path.display()
+            )
+        })
+    }
+}
+
+fn signature_path(filename: &str) -> PathBuf {
+    let mut path = Path::new(filename).as_os_str().to_owned();
+    path.push(".sig");
+    PathBuf::from(path)
+}
+
+#[test]
+fn test_system_fingerprint() {
+    let fingerprint = SystemFingerprint::collect();
+    assert_eq!(fingerprint.os, std::env::consts::OS);
+    assert_eq!(fingerprint.id.len(), 16);
+    assert_eq!(fingerprint, SystemFingerprint::collect());
+
+    assert_eq!(
+        signature_path("out/results.json"),
+        PathBuf::from("out/results.json.sig")
+    );
+}
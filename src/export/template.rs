@@ -0,0 +1,488 @@
+//! Export to a custom text format, which is described by a template (see '--export-template').
+//!
+//! The template language is a small subset of Jinja/Tera:
+//!
+//! * `{{ result.mean }}` inserts a value, given by a path of names and array indices
+//!   separated by dots (e.g. `results.0.command`). Strings are inserted as they are, other
+//!   values as JSON. Numbers can be rounded with `{{ result.mean | round(3) }}`.
+//! * `{% for result in results %}…{% endfor %}` repeats its body for each element of an
+//!   array. In the body, `loop.index` (starting at 1), `loop.first` and `loop.last` are
+//!   available.
+//! * `{% if result.git %}…{% else %}…{% endif %}` checks whether a value is present and not
+//!   `false`, zero or empty (`{% if not … %}` negates the condition).
+//! * `{# … #}` is a comment.
+//!
+//! A `-` at the inner side of a delimiter (`{%-`, `-%}`, `{{-`, `-}}`) removes the
+//! whitespace before or after the tag.
+
+use std::fs;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{json, Map, Value};
+
+use super::Exporter;
+use crate::benchmark::benchmark_result::{self, BenchmarkResult};
+use crate::benchmark::relative_speed;
+use crate::options::{MemoryUnit, RelativeError, RelativeMetric, SortOrder};
+use crate::quantity::{byte, second, TimeUnit};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Value {
+        path: String,
+        round: Option<usize>,
+        line: usize,
+    },
+    For {
+        variable: String,
+        path: String,
+        body: Vec<Node>,
+        line: usize,
+    },
+    If {
+        path: String,
+        negated: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+/// A piece of the template source: literal text, or the content of a tag with its kind
+/// ('{' for values, '%' for statements) and line number
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Text(String),
+    Tag(char, &'a str, usize),
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token<'_>>> {
+    let mut tokens = vec![];
+    let mut rest = source;
+    let mut trim_next = false;
+
+    while let Some(start) = rest.find('{') {
+        let kind = match rest[start + 1..].chars().next() {
+            Some(kind @ ('{' | '%' | '#')) => kind,
+            _ => {
+                // A single '{' is part of the text
+                let (text, remainder) = rest.split_at(start + 1);
+                push_text(&mut tokens, text, &mut trim_next, false);
+                rest = remainder;
+                continue;
+            }
+        };
+        let line = source[..source.len() - rest.len() + start]
+            .matches('\n')
+            .count()
+            + 1;
+        let closing = match kind {
+            '{' => "}}",
+            '%' => "%}",
+            _ => "#}",
+        };
+        let Some(length) = rest[start + 2..].find(closing) else {
+            bail!("Line {line}: the tag is not closed with '{closing}'");
+        };
+        let content = &rest[start + 2..start + 2 + length];
+        let trim_before = content.starts_with('-');
+        push_text(&mut tokens, &rest[..start], &mut trim_next, trim_before);
+        trim_next = content.ends_with('-') && content.len() > 1;
+
+        if kind != '#' {
+            let content = content.strip_prefix('-').unwrap_or(content);
+            let content = if trim_next {
+                &content[..content.len() - 1]
+            } else {
+                content
+            };
+            tokens.push(Token::Tag(kind, content.trim(), line));
+        }
+        rest = &rest[start + 2 + length + closing.len()..];
+    }
+    push_text(&mut tokens, rest, &mut trim_next, false);
+
+    Ok(tokens)
+}
+
+fn push_text(tokens: &mut Vec<Token>, text: &str, trim_start: &mut bool, trim_end: bool) {
+    let mut text = text;
+    if std::mem::take(trim_start) {
+        text = text.trim_start();
+    }
+    if trim_end {
+        text = text.trim_end();
+    }
+    if text.is_empty() {
+        return;
+    }
+    match tokens.last_mut() {
+        Some(Token::Text(previous)) => previous.push_str(text),
+        _ => tokens.push(Token::Text(text.to_string())),
+    }
+}
+
+/// Parse the nodes up to one of the given closing statements (or up to the end if there
+/// are none), and return them with the closing statement
+fn parse<'a>(
+    tokens: &mut impl Iterator<Item = Token<'a>>,
+    closing: &[&str],
+) -> Result<(Vec<Node>, Option<&'a str>)> {
+    let mut nodes = vec![];
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text)),
+            Token::Tag('{', content, line) => {
+                let (path, filter) = match content.split_once('|') {
+                    Some((path, filter)) => (path.trim(), Some(filter.trim())),
+                    None => (content, None),
+                };
+                let round = match filter {
+                    None => None,
+                    Some(filter) => Some(
+                        filter
+                            .strip_prefix("round(")
+                            .and_then(|digits| digits.strip_suffix(')'))
+                            .and_then(|digits| digits.trim().parse().ok())
+                            .ok_or_else(|| {
+                                anyhow!("Line {line}: unknown filter '{filter}' (only 'round(N)' is supported)")
+                            })?,
+                    ),
+                };
+                check_path(path, line)?;
+                nodes.push(Node::Value {
+                    path: path.to_string(),
+                    round,
+                    line,
+                });
+            }
+            Token::Tag(_, content, line) => {
+                let words: Vec<_> = content.split_whitespace().collect();
+                match words.as_slice() {
+                    ["for", variable, "in", path] => {
+                        check_path(path, line)?;
+                        let (body, _) = parse(tokens, &["endfor"])
+                            .with_context(|| format!("Line {line}: in the 'for' loop"))?;
+                        nodes.push(Node::For {
+                            variable: variable.to_string(),
+                            path: path.to_string(),
+                            body,
+                            line,
+                        });
+                    }
+                    ["if", condition @ ..] => {
+                        let (negated, path) = match condition {
+                            ["not", path] => (true, *path),
+                            [path] => (false, *path),
+                            _ => bail!("Line {line}: invalid condition '{content}'"),
+                        };
+                        check_path(path, line)?;
+                        let (then, end) = parse(tokens, &["else", "endif"])
+                            .with_context(|| format!("Line {line}: in the 'if' statement"))?;
+                        let otherwise = if end == Some("else") {
+                            parse(tokens, &["endif"])
+                                .with_context(|| format!("Line {line}: in the 'if' statement"))?
+                                .0
+                        } else {
+                            vec![]
+                        };
+                        nodes.push(Node::If {
+                            path: path.to_string(),
+                            negated,
+                            then,
+                            otherwise,
+                        });
+                    }
+                    [statement] if closing.contains(statement) => {
+                        return Ok((nodes, Some(*statement)));
+                    }
+                    _ => bail!("Line {line}: unexpected statement '{content}'"),
+                }
+            }
+        }
+    }
+
+    match closing.last() {
+        Some(statement) => bail!("'{{% {statement} %}}' is missing"),
+        None => Ok((nodes, None)),
+    }
+}
+
+fn check_path(path: &str, line: usize) -> Result<()> {
+    let is_valid = !path.is_empty()
+        && path.split('.').all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        });
+    if !is_valid {
+        bail!("Line {line}: invalid variable '{path}'");
+    }
+    Ok(())
+}
+
+/// A parsed template
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut tokens = tokenize(source)?.into_iter();
+        let (nodes, _) = parse(&mut tokens, &[])?;
+        Ok(Template { nodes })
+    }
+
+    pub fn from_file(path: &str) -> Result<Self> {
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("Could not read the template '{path}'"))?;
+        Self::parse(&source).with_context(|| format!("Invalid template '{path}'"))
+    }
+
+    /// Render the template with the given (JSON) context
+    pub fn render(&self, context: &Value) -> Result<String> {
+        let mut output = String::new();
+        let mut scopes = vec![];
+        render(&self.nodes, context, &mut scopes, &mut output)?;
+        Ok(output)
+    }
+}
+
+/// Look up a path in the variables of the loops (innermost first) and in the context
+fn lookup<'a>(path: &str, context: &'a Value, scopes: &'a [(String, Value)]) -> Option<&'a Value> {
+    let mut segments = path.split('.');
+    let first = segments.next()?;
+    let mut value = scopes
+        .iter()
+        .rev()
+        .find(|(name, _)| name == first)
+        .map(|(_, value)| value)
+        .or_else(|| context.get(first))?;
+    for segment in segments {
+        value = match value {
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+            Value::Object(fields) => fields.get(segment)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+fn is_truthy(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) | Some(Value::Bool(false)) => false,
+        Some(Value::Number(number)) => number.as_f64() != Some(0.0),
+        Some(Value::String(string)) => !string.is_empty(),
+        Some(Value::Array(items)) => !items.is_empty(),
+        Some(Value::Object(fields)) => !fields.is_empty(),
+        Some(Value::Bool(true)) => true,
+    }
+}
+
+fn render(
+    nodes: &[Node],
+    context: &Value,
+    scopes: &mut Vec<(String, Value)>,
+    output: &mut String,
+) -> Result<()> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Value { path, round, line } => {
+                let value = lookup(path, context, scopes)
+                    .ok_or_else(|| anyhow!("Line {line}: unknown variable '{path}'"))?;
+                match (value, round) {
+                    (Value::Number(number), Some(digits)) => {
+                        let number = number.as_f64().unwrap_or_default();
+                        output.push_str(&format!("{number:.digits$}"));
+                    }
+                    (_, Some(_)) => bail!("Line {line}: '{path}' is not a number"),
+                    (Value::String(string), None) => output.push_str(string),
+                    (Value::Null, None) => {}
+                    (value, None) => output.push_str(&value.to_string()),
+                }
+            }
+            Node::For {
+                variable,
+                path,
+                body,
+                line,
+            } => {
+                let items: Vec<Value> = match lookup(path, context, scopes) {
+                    Some(Value::Array(items)) => items.clone(),
+                    Some(Value::Object(fields)) => fields.values().cloned().collect(),
+                    Some(_) => bail!("Line {line}: '{path}' is not an array"),
+                    None => bail!("Line {line}: unknown variable '{path}'"),
+                };
+                let count = items.len();
+                for (index, item) in items.into_iter().enumerate() {
+                    let state = json!({
+                        "index": index + 1,
+                        "first": index == 0,
+                        "last": index + 1 == count,
+                    });
+                    scopes.push(("loop".to_string(), state));
+                    scopes.push((variable.clone(), item));
+                    let rendered = render(body, context, scopes, output);
+                    scopes.truncate(scopes.len() - 2);
+                    rendered?;
+                }
+            }
+            Node::If {
+                path,
+                negated,
+                then,
+                otherwise,
+            } => {
+                if is_truthy(lookup(path, context, scopes)) != *negated {
+                    render(then, context, scopes, output)?;
+                } else {
+                    render(otherwise, context, scopes, output)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Exports the results in a custom format, given by a template
+pub struct TemplateExporter {
+    template: Template,
+    relative_metric: RelativeMetric,
+    relative_error: RelativeError,
+}
+
+impl TemplateExporter {
+    pub fn new(
+        template: Template,
+        relative_metric: RelativeMetric,
+        relative_error: RelativeError,
+    ) -> Self {
+        TemplateExporter {
+            template,
+            relative_metric,
+            relative_error,
+        }
+    }
+}
+
+/// The context of a template: the 'results' (in the given order), each with all fields of
+/// the JSON export and the summary statistics (times in seconds, memory in bytes)
+fn context(
+    results: &[BenchmarkResult],
+    sort_order: SortOrder,
+    relative_metric: RelativeMetric,
+    relative_error: RelativeError,
+) -> Result<Value> {
+    let groups = benchmark_result::groups(results);
+    let entries = if results.is_empty() {
+        vec![]
+    } else {
+        relative_speed::compute_by_group(&groups, sort_order, relative_metric, relative_error)
+    };
+
+    let mut benchmarks = vec![];
+    for entry in entries {
+        let result = entry.result;
+        let measurements = &result.measurements;
+        let mut fields = match serde_json::to_value(result)? {
+            Value::Object(fields) => fields,
+            _ => Map::new(),
+        };
+        let statistics = json!({
+            "name": result.command_with_unused_parameters(),
+            "runs": measurements.len(),
+            "mean": result.mean_wall_clock_time().get::<second>(),
+            "stddev": measurements.stddev().map(|stddev| stddev.get::<second>()),
+            "median": measurements.median().get::<second>(),
+            "min": measurements.min().get::<second>(),
+            "max": measurements.max().get::<second>(),
+            "user": measurements.time_user_mean().get::<second>(),
+            "system": measurements.time_system_mean().get::<second>(),
+            "memory": measurements.peak_memory_usage_mean().get::<byte>(),
+            "relative": entry.relative_speed,
+            "relative_stddev": entry.relative_speed_stddev,
+            "relative_ci": entry.relative_speed_ci.map(|(lower, upper)| [lower, upper]),
+            "is_reference": entry.is_reference,
+        });
+        if let Value::Object(statistics) = statistics {
+            fields.extend(statistics);
+        }
+        benchmarks.push(Value::Object(fields));
+    }
+
+    Ok(json!({ "results": benchmarks }))
+}
+
+impl Exporter for TemplateExporter {
+    fn serialize(
+        &self,
+        results: &[BenchmarkResult],
+        _time_unit: Option<TimeUnit>,
+        _memory_unit: Option<MemoryUnit>,
+        sort_order: SortOrder,
+    ) -> Result<Vec<u8>> {
+        let context = context(
+            results,
+            sort_order,
+            self.relative_metric,
+            self.relative_error,
+        )?;
+        Ok(self.template.render(&context)?.into_bytes())
+    }
+}
+
+#[test]
+fn test_template() {
+    let context = json!({
+        "title": "Results",
+        "results": [
+            { "command": "sleep 1", "mean": 1.0012, "git": { "commit": "a1b2c3d" } },
+            { "command": "sleep 2", "mean": 2.0034, "git": null },
+        ],
+    });
+    let render = |source: &str| Template::parse(source).unwrap().render(&context).unwrap();
+
+    assert_eq!(
+        render("{{ title }} {{results.1.command}}"),
+        "Results sleep 2"
+    );
+    assert_eq!(
+        render("{% for r in results %}{{ loop.index }}. {{ r.command }}: {{ r.mean | round(2) }}{% if not loop.last %}, {% endif %}{% endfor %}"),
+        "1. sleep 1: 1.00, 2. sleep 2: 2.00"
+    );
+    assert_eq!(
+        render("{% for r in results %}{% if r.git %}{{ r.git.commit }}{% else %}-{% endif %}{% endfor %}"),
+        "a1b2c3d-"
+    );
+    assert_eq!(
+        render("{# comment #}a\n{%- for r in results -%}\n  {{ r.mean }}\n{%- endfor %}\nb"),
+        "a1.00122.0034\nb"
+    );
+    assert_eq!(
+        render("{ x } {{ results.0.git }}"),
+        r#"{ x } {"commit":"a1b2c3d"}"#
+    );
+
+    let error = |source: &str| match Template::parse(source) {
+        Ok(template) => format!("{:#}", template.render(&context).unwrap_err()),
+        Err(e) => format!("{e:#}"),
+    };
+    assert_eq!(error("{{ title"), "Line 1: the tag is not closed with '}}'");
+    assert_eq!(
+        error("\n{% for r in results %}"),
+        "Line 2: in the 'for' loop: '{% endfor %}' is missing"
+    );
+    assert_eq!(error("{% endif %}"), "Line 1: unexpected statement 'endif'");
+    assert_eq!(error("{{ missing }}"), "Line 1: unknown variable 'missing'");
+    assert_eq!(
+        error("{{ title | upper }}"),
+        "Line 1: unknown filter 'upper' (only 'round(N)' is supported)"
+    );
+    assert_eq!(
+        error("{{ title | round(1) }}"),
+        "Line 1: 'title' is not a number"
+    );
+}
@@ -3,8 +3,11 @@ use crate::benchmark::benchmark_result::BenchmarkResult;
 use crate::benchmark::measurement::{Measurement, Measurements};
 use crate::export::asciidoc::AsciidocExporter;
 use crate::export::orgmode::OrgmodeExporter;
-use crate::quantity::{byte, second, Information, Quantity, Time, TimeUnit};
-use crate::{export::markdown::MarkdownExporter, options::SortOrder};
+use crate::quantity::{byte, mebibyte, second, Information, Quantity, Time, TimeUnit};
+use crate::{
+    export::markdown::MarkdownExporter,
+    options::{MemoryUnit, Precision, RelativeError, RelativeMetric, SortOrder},
+};
 use std::collections::BTreeMap;
 use std::process::ExitStatus;
 
@@ -14,7 +17,7 @@ fn get_output<E: Exporter + Default>(
     sort_order: SortOrder,
 ) -> String {
     let exporter = E::default();
-    String::from_utf8(exporter.serialize(results, unit, sort_order).unwrap()).unwrap()
+    String::from_utf8(exporter.serialize(results, unit, None, sort_order).unwrap()).unwrap()
 }
 
 /// Ensure the makrup output includes the table header and the multiple
@@ -35,6 +38,7 @@ fn test_markup_export_auto_ms() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(0.10),
@@ -42,6 +46,7 @@ fn test_markup_export_auto_ms() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(0.14),
@@ -49,9 +54,21 @@ fn test_markup_export_auto_ms() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
             ]),
             parameters: BTreeMap::new(),
+            command_template: None,
+            shell_spawning_time: None,
+            captured_output: None,
+            modes: vec![],
+            run_indices: None,
+            execution_order: None,
+            reproduce: None,
+            git: None,
+            stateful: false,
+            group: None,
+            warnings: vec![],
         },
         BenchmarkResult {
             command: String::from("sleep 2"),
@@ -62,6 +79,7 @@ fn test_markup_export_auto_ms() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(3.0),
@@ -69,6 +87,7 @@ fn test_markup_export_auto_ms() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(4.0),
@@ -76,9 +95,21 @@ fn test_markup_export_auto_ms() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
             ]),
             parameters: BTreeMap::new(),
+            command_template: None,
+            shell_spawning_time: None,
+            captured_output: None,
+            modes: vec![],
+            run_indices: None,
+            execution_order: None,
+            reproduce: None,
+            git: None,
+            stateful: false,
+            group: None,
+            warnings: vec![],
         },
     ];
 
@@ -86,7 +117,7 @@ fn test_markup_export_auto_ms() {
     | Command | Mean [ms] | Min [ms] | Max [ms] | Relative |
     |:---|---:|---:|---:|---:|
     | `sleep 0.1` | 110.0 ± 26.5 | 90.0 | 140.0 | 1.00 |
-    | `sleep 2` | 3000.0 ± 1000.0 | 2000.0 | 4000.0 | 27.27 ± 11.21 |
+    | `sleep 2` | 3000.0 ± 1000.0 | 2000.0 | 4000.0 | 27.27 ± 5.27 |
     "#);
 
     insta::assert_snapshot!(get_output::<AsciidocExporter>(&results, None, SortOrder::Command), @r#"
@@ -108,7 +139,7 @@ fn test_markup_export_auto_ms() {
     | 3000.0 ± 1000.0 
     | 2000.0 
     | 4000.0 
-    | 27.27 ± 11.21 
+    | 27.27 ± 5.27 
     |===
     "#);
 
@@ -116,7 +147,7 @@ fn test_markup_export_auto_ms() {
     | Command  |  Mean [ms] |  Min [ms] |  Max [ms] |  Relative |
     |--+--+--+--+--|
     | =sleep 0.1=  |  110.0 ± 26.5 |  90.0 |  140.0 |  1.00 |
-    | =sleep 2=  |  3000.0 ± 1000.0 |  2000.0 |  4000.0 |  27.27 ± 11.21 |
+    | =sleep 2=  |  3000.0 ± 1000.0 |  2000.0 |  4000.0 |  27.27 ± 5.27 |
     "#);
 }
 
@@ -134,6 +165,7 @@ fn test_markup_export_auto_s() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(2.2),
@@ -141,6 +173,7 @@ fn test_markup_export_auto_s() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(2.3),
@@ -148,9 +181,21 @@ fn test_markup_export_auto_s() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
             ]),
             parameters: BTreeMap::new(),
+            command_template: None,
+            shell_spawning_time: None,
+            captured_output: None,
+            modes: vec![],
+            run_indices: None,
+            execution_order: None,
+            reproduce: None,
+            git: None,
+            stateful: false,
+            group: None,
+            warnings: vec![],
         },
         BenchmarkResult {
             command: String::from("sleep 0.1"),
@@ -161,6 +206,7 @@ fn test_markup_export_auto_s() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(0.2),
@@ -168,6 +214,7 @@ fn test_markup_export_auto_s() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(0.3),
@@ -175,16 +222,28 @@ fn test_markup_export_auto_s() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
             ]),
             parameters: BTreeMap::new(),
+            command_template: None,
+            shell_spawning_time: None,
+            captured_output: None,
+            modes: vec![],
+            run_indices: None,
+            execution_order: None,
+            reproduce: None,
+            git: None,
+            stateful: false,
+            group: None,
+            warnings: vec![],
         },
     ];
 
     insta::assert_snapshot!(get_output::<MarkdownExporter>(&results, None, SortOrder::Command), @r#"
     | Command | Mean [s] | Min [s] | Max [s] | Relative |
     |:---|---:|---:|---:|---:|
-    | `sleep 2` | 2.200 ± 0.100 | 2.100 | 2.300 | 11.00 ± 5.52 |
+    | `sleep 2` | 2.200 ± 0.100 | 2.100 | 2.300 | 11.00 ± 3.23 |
     | `sleep 0.1` | 0.200 ± 0.100 | 0.100 | 0.300 | 1.00 |
     "#);
 
@@ -201,7 +260,7 @@ fn test_markup_export_auto_s() {
     | 2.200 ± 0.100 
     | 2.100 
     | 2.300 
-    | 11.00 ± 5.52 
+    | 11.00 ± 3.23 
 
     | `sleep 0.1` 
     | 0.200 ± 0.100 
@@ -214,7 +273,7 @@ fn test_markup_export_auto_s() {
     insta::assert_snapshot!(get_output::<OrgmodeExporter>(&results, None, SortOrder::Command), @r#"
     | Command  |  Mean [s] |  Min [s] |  Max [s] |  Relative |
     |--+--+--+--+--|
-    | =sleep 2=  |  2.200 ± 0.100 |  2.100 |  2.300 |  11.00 ± 5.52 |
+    | =sleep 2=  |  2.200 ± 0.100 |  2.100 |  2.300 |  11.00 ± 3.23 |
     | =sleep 0.1=  |  0.200 ± 0.100 |  0.100 |  0.300 |  1.00 |
     "#);
 }
@@ -233,6 +292,7 @@ fn test_markup_export_manual_ms() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(2.2),
@@ -240,6 +300,7 @@ fn test_markup_export_manual_ms() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(2.3),
@@ -247,9 +308,21 @@ fn test_markup_export_manual_ms() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
             ]),
             parameters: BTreeMap::new(),
+            command_template: None,
+            shell_spawning_time: None,
+            captured_output: None,
+            modes: vec![],
+            run_indices: None,
+            execution_order: None,
+            reproduce: None,
+            git: None,
+            stateful: false,
+            group: None,
+            warnings: vec![],
         },
         BenchmarkResult {
             command: String::from("sleep 0.1"),
@@ -260,6 +333,7 @@ fn test_markup_export_manual_ms() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(0.2),
@@ -267,6 +341,7 @@ fn test_markup_export_manual_ms() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(0.3),
@@ -274,16 +349,28 @@ fn test_markup_export_manual_ms() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
             ]),
             parameters: BTreeMap::new(),
+            command_template: None,
+            shell_spawning_time: None,
+            captured_output: None,
+            modes: vec![],
+            run_indices: None,
+            execution_order: None,
+            reproduce: None,
+            git: None,
+            stateful: false,
+            group: None,
+            warnings: vec![],
         },
     ];
 
     insta::assert_snapshot!(get_output::<MarkdownExporter>(&timing_results, Some(TimeUnit::MilliSecond), SortOrder::Command), @r#"
     | Command | Mean [ms] | Min [ms] | Max [ms] | Relative |
     |:---|---:|---:|---:|---:|
-    | `sleep 2` | 2200.0 ± 100.0 | 2100.0 | 2300.0 | 11.00 ± 5.52 |
+    | `sleep 2` | 2200.0 ± 100.0 | 2100.0 | 2300.0 | 11.00 ± 3.23 |
     | `sleep 0.1` | 200.0 ± 100.0 | 100.0 | 300.0 | 1.00 |
     "#);
 
@@ -300,7 +387,7 @@ fn test_markup_export_manual_ms() {
     | 2200.0 ± 100.0 
     | 2100.0 
     | 2300.0 
-    | 11.00 ± 5.52 
+    | 11.00 ± 3.23 
 
     | `sleep 0.1` 
     | 200.0 ± 100.0 
@@ -313,7 +400,7 @@ fn test_markup_export_manual_ms() {
     insta::assert_snapshot!(get_output::<OrgmodeExporter>(&timing_results, Some(TimeUnit::MilliSecond), SortOrder::Command), @r#"
     | Command  |  Mean [ms] |  Min [ms] |  Max [ms] |  Relative |
     |--+--+--+--+--|
-    | =sleep 2=  |  2200.0 ± 100.0 |  2100.0 |  2300.0 |  11.00 ± 5.52 |
+    | =sleep 2=  |  2200.0 ± 100.0 |  2100.0 |  2300.0 |  11.00 ± 3.23 |
     | =sleep 0.1=  |  200.0 ± 100.0 |  100.0 |  300.0 |  1.00 |
     "#);
 }
@@ -331,6 +418,7 @@ fn test_markup_export_manual_s() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(2.02),
@@ -338,6 +426,7 @@ fn test_markup_export_manual_s() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(2.03),
@@ -345,9 +434,21 @@ fn test_markup_export_manual_s() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
             ]),
             parameters: BTreeMap::new(),
+            command_template: None,
+            shell_spawning_time: None,
+            captured_output: None,
+            modes: vec![],
+            run_indices: None,
+            execution_order: None,
+            reproduce: None,
+            git: None,
+            stateful: false,
+            group: None,
+            warnings: vec![],
         },
         BenchmarkResult {
             command: String::from("sleep 0.1"),
@@ -358,6 +459,7 @@ fn test_markup_export_manual_s() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(0.12),
@@ -365,6 +467,7 @@ fn test_markup_export_manual_s() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
                 Measurement {
                     time_wall_clock: Time::new::<second>(0.13),
@@ -372,16 +475,28 @@ fn test_markup_export_manual_s() {
                     time_system: Time::zero(),
                     peak_memory_usage: Information::new::<byte>(1024.),
                     exit_status: ExitStatus::default(),
+                    ..Default::default()
                 },
             ]),
             parameters: BTreeMap::new(),
+            command_template: None,
+            shell_spawning_time: None,
+            captured_output: None,
+            modes: vec![],
+            run_indices: None,
+            execution_order: None,
+            reproduce: None,
+            git: None,
+            stateful: false,
+            group: None,
+            warnings: vec![],
         },
     ];
 
     insta::assert_snapshot!(get_output::<MarkdownExporter>(&results, Some(TimeUnit::Second), SortOrder::Command), @r#"
     | Command | Mean [s] | Min [s] | Max [s] | Relative |
     |:---|---:|---:|---:|---:|
-    | `sleep 2` | 2.020 ± 0.010 | 2.010 | 2.030 | 16.83 ± 1.41 |
+    | `sleep 2` | 2.020 ± 0.010 | 2.010 | 2.030 | 16.83 ± 0.67 |
     | `sleep 0.1` | 0.120 ± 0.010 | 0.110 | 0.130 | 1.00 |
     "#);
 
@@ -389,7 +504,7 @@ fn test_markup_export_manual_s() {
     | Command | Mean [s] | Min [s] | Max [s] | Relative |
     |:---|---:|---:|---:|---:|
     | `sleep 0.1` | 0.120 ± 0.010 | 0.110 | 0.130 | 1.00 |
-    | `sleep 2` | 2.020 ± 0.010 | 2.010 | 2.030 | 16.83 ± 1.41 |
+    | `sleep 2` | 2.020 ± 0.010 | 2.010 | 2.030 | 16.83 ± 0.67 |
     "#);
 
     insta::assert_snapshot!(get_output::<AsciidocExporter>(&results, Some(TimeUnit::Second), SortOrder::Command), @r#"
@@ -405,7 +520,7 @@ fn test_markup_export_manual_s() {
     | 2.020 ± 0.010 
     | 2.010 
     | 2.030 
-    | 16.83 ± 1.41 
+    | 16.83 ± 0.67 
 
     | `sleep 0.1` 
     | 0.120 ± 0.010 
@@ -415,3 +530,244 @@ fn test_markup_export_manual_s() {
     |===
     "#);
 }
+
+/// The peak memory usage column is only added if a memory unit is given.
+#[test]
+fn test_markup_export_memory_column() {
+    let result = |command: &str, time: f64, memory: f64| BenchmarkResult {
+        command: String::from(command),
+        measurements: Measurements::new(vec![Measurement {
+            time_wall_clock: Time::new::<second>(time),
+            time_user: Time::new::<second>(time),
+            time_system: Time::zero(),
+            peak_memory_usage: Information::new::<mebibyte>(memory),
+            exit_status: ExitStatus::default(),
+            ..Default::default()
+        }]),
+        parameters: BTreeMap::new(),
+        command_template: None,
+        shell_spawning_time: None,
+        captured_output: None,
+        modes: vec![],
+        run_indices: None,
+        execution_order: None,
+        reproduce: None,
+        git: None,
+        stateful: false,
+        group: None,
+        warnings: vec![],
+    };
+    let results = [result("sleep 1", 1.0, 2.5), result("sleep 2", 2.0, 12.0)];
+
+    let output = |memory_unit| {
+        String::from_utf8(
+            MarkdownExporter::default()
+                .serialize(
+                    &results,
+                    Some(TimeUnit::Second),
+                    memory_unit,
+                    SortOrder::Command,
+                )
+                .unwrap(),
+        )
+        .unwrap()
+    };
+
+    insta::assert_snapshot!(output(Some(MemoryUnit::Auto)), @r#"
+    | Command | Mean [s] | Min [s] | Max [s] | Memory [MiB] | Relative |
+    |:---|---:|---:|---:|---:|---:|
+    | `sleep 1` | 1.000 | 1.000 | 1.000 | 2.5 | 1.00 |
+    | `sleep 2` | 2.000 | 2.000 | 2.000 | 12.0 | 2.00 |
+    "#);
+
+    insta::assert_snapshot!(output(Some(MemoryUnit::Fixed(crate::quantity::InformationUnit::KibiByte))), @r#"
+    | Command | Mean [s] | Min [s] | Max [s] | Memory [KiB] | Relative |
+    |:---|---:|---:|---:|---:|---:|
+    | `sleep 1` | 1.000 | 1.000 | 1.000 | 2560.0 | 1.00 |
+    | `sleep 2` | 2.000 | 2.000 | 2.000 | 12288.0 | 2.00 |
+    "#);
+}
+
+/// The columns can be selected and reordered, and the memory column does not require a
+/// memory unit.
+#[test]
+fn test_markup_export_selected_columns() {
+    use crate::export::columns::Column;
+
+    let result = |command: &str, time: f64| BenchmarkResult {
+        command: String::from(command),
+        measurements: Measurements::new(vec![Measurement {
+            time_wall_clock: Time::new::<second>(time),
+            time_user: Time::new::<second>(0.75 * time),
+            time_system: Time::new::<second>(0.25 * time),
+            peak_memory_usage: Information::new::<mebibyte>(4.0),
+            exit_status: ExitStatus::default(),
+            ..Default::default()
+        }]),
+        parameters: BTreeMap::new(),
+        command_template: None,
+        shell_spawning_time: None,
+        captured_output: None,
+        modes: vec![],
+        run_indices: None,
+        execution_order: None,
+        reproduce: None,
+        git: None,
+        stateful: false,
+        group: None,
+        warnings: vec![],
+    };
+    let results = [result("sleep 1", 1.0), result("sleep 2", 2.0)];
+
+    let exporter = MarkdownExporter::new(
+        Some(vec![
+            Column::Command,
+            Column::User,
+            Column::System,
+            Column::Memory,
+            Column::Median,
+        ]),
+        Precision::default(),
+        RelativeMetric::WallClock,
+        RelativeError::default(),
+    );
+    let output = exporter
+        .serialize(&results, Some(TimeUnit::Second), None, SortOrder::Command)
+        .unwrap();
+
+    insta::assert_snapshot!(String::from_utf8(output).unwrap(), @r#"
+    | Command | User [s] | System [s] | Memory [MiB] | Median [s] |
+    |:---|---:|---:|---:|---:|
+    | `sleep 1` | 0.750 | 0.250 | 4.0 | 1.000 |
+    | `sleep 2` | 1.500 | 0.500 | 4.0 | 2.000 |
+    "#);
+}
+
+/// The private bytes and the working set are only recorded on Windows
+#[test]
+fn test_markup_export_windows_memory_columns() {
+    use crate::export::columns::Column;
+
+    let result = |command: &str, working_set: Option<f64>| BenchmarkResult {
+        command: String::from(command),
+        measurements: Measurements::new(vec![Measurement {
+            time_wall_clock: Time::new::<second>(1.0),
+            peak_memory_usage: Information::new::<mebibyte>(4.0),
+            peak_private_bytes: working_set.map(|_| Information::new::<mebibyte>(3.0)),
+            peak_working_set: working_set.map(Information::new::<mebibyte>),
+            ..Default::default()
+        }]),
+        ..Default::default()
+    };
+    let results = [result("windows", Some(6.0)), result("elsewhere", None)];
+
+    let exporter = MarkdownExporter::new(
+        Some(vec![
+            Column::Command,
+            Column::Memory,
+            Column::PrivateBytes,
+            Column::WorkingSet,
+        ]),
+        Precision::default(),
+        RelativeMetric::WallClock,
+        RelativeError::default(),
+    );
+    let output = exporter
+        .serialize(&results, None, None, SortOrder::Command)
+        .unwrap();
+
+    insta::assert_snapshot!(String::from_utf8(output).unwrap(), @r#"
+    | Command | Memory [MiB] | Private bytes [MiB] | Working set [MiB] |
+    |:---|---:|---:|---:|
+    | `windows` | 4.0 | 3.0 | 6.0 |
+    | `elsewhere` | 4.0 | n/a | n/a |
+    "#);
+}
+
+/// The number of decimal places can be set for each kind of number
+#[test]
+fn test_markup_export_precision() {
+    let result = |command: &str, times: &[f64]| BenchmarkResult {
+        command: String::from(command),
+        measurements: Measurements::new(
+            times
+                .iter()
+                .map(|&time| Measurement {
+                    time_wall_clock: Time::new::<second>(time),
+                    peak_memory_usage: Information::new::<mebibyte>(1.0 / 3.0),
+                    ..Default::default()
+                })
+                .collect(),
+        ),
+        ..Default::default()
+    };
+    let results = [
+        result("sleep 1", &[1.0, 1.0]),
+        result("sleep 1.1", &[1.1, 1.2]),
+    ];
+
+    let exporter = MarkdownExporter::new(
+        None,
+        Precision {
+            time: Some(5),
+            relative: Some(3),
+            memory: Some(0),
+        },
+        RelativeMetric::WallClock,
+        RelativeError::default(),
+    );
+    let output = exporter
+        .serialize(
+            &results,
+            Some(TimeUnit::Second),
+            Some(MemoryUnit::Auto),
+            SortOrder::Command,
+        )
+        .unwrap();
+
+    insta::assert_snapshot!(String::from_utf8(output).unwrap(), @r#"
+    | Command | Mean [s] | Min [s] | Max [s] | Memory [KiB] | Relative |
+    |:---|---:|---:|---:|---:|---:|
+    | `sleep 1` | 1.00000 ± 0.00000 | 1.00000 | 1.00000 | 341 | 1.000 |
+    | `sleep 1.1` | 1.15000 ± 0.07071 | 1.10000 | 1.20000 | 341 | 1.150 ± 0.036 |
+    "#);
+}
+
+/// The 'Relative' column and the order by mean time are based on the selected metric
+#[test]
+fn test_markup_export_relative_metric() {
+    use crate::export::columns::Column;
+
+    let result = |command: &str, wall_clock: f64, user: f64| BenchmarkResult {
+        command: String::from(command),
+        measurements: Measurements::new(vec![Measurement {
+            time_wall_clock: Time::new::<second>(wall_clock),
+            time_user: Time::new::<second>(user),
+            ..Default::default()
+        }]),
+        ..Default::default()
+    };
+    let results = [result("parallel", 1.0, 4.0), result("serial", 2.0, 2.0)];
+
+    let exporter = MarkdownExporter::new(
+        Some(vec![
+            Column::Command,
+            Column::Mean,
+            Column::User,
+            Column::Relative,
+        ]),
+        Precision::default(),
+        RelativeMetric::User,
+        RelativeError::default(),
+    );
+    let output = exporter
+        .serialize(&results, Some(TimeUnit::Second), None, SortOrder::MeanTime)
+        .unwrap();
+
+    insta::assert_snapshot!(String::from_utf8(output).unwrap(), @r#"
+    | Command | Mean [s] | User [s] | Relative |
+    |:---|---:|---:|---:|
+    | `serial` | 2.000 | 2.000 | 1.00 |
+    | `parallel` | 1.000 | 4.000 | 2.00 |
+    "#);
+}
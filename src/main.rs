@@ -7,23 +7,33 @@ use std::env;
 
 use benchmark::scheduler::Scheduler;
 use cli::get_cli_arguments;
-use command::Commands;
+use command::{CommandList, Commands};
+use error::{ExitCode, WithExitCode};
 use export::ExportManager;
 use options::Options;
+use parameter::definitions::Definitions;
+use remote::HostList;
+use util::cpu_state::CpuStateGuard;
+use util::paused_processes::PausedProcesses;
+use util::session_lock::SessionLock;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use colored::*;
 
 pub mod benchmark;
+pub mod capabilities;
 pub mod cli;
 pub mod command;
 pub mod error;
 pub mod export;
+pub mod modality_detection;
 pub mod options;
 pub mod outlier_detection;
 pub mod output;
 pub mod parameter;
 pub mod quantity;
+pub mod remote;
+pub mod shim;
 pub mod timer;
 pub mod util;
 
@@ -33,20 +43,95 @@ fn run() -> Result<()> {
     colored::control::set_virtual_terminal(true).unwrap();
 
     let cli_arguments = get_cli_arguments(env::args_os());
+
+    if cli_arguments.get_flag("print-json-schema") {
+        print!("{}", export::json::SCHEMA);
+        return Ok(());
+    }
+    if cli_arguments.get_flag("capabilities") {
+        capabilities::print(&capabilities::probe());
+        return Ok(());
+    }
+
+    if let Some(("merge", merge_arguments)) = cli_arguments.subcommand() {
+        return export::merge::run_merge(merge_arguments);
+    }
+    if let Some(("analyze", analyze_arguments)) = cli_arguments.subcommand() {
+        return export::import::run_analyze(analyze_arguments);
+    }
+    if let Some(("compare", compare_arguments)) = cli_arguments.subcommand() {
+        return export::compare::run_compare(compare_arguments);
+    }
+
+    // Substitute user-defined variables in all arguments, and parse them again
+    let definitions = Definitions::from_cli_arguments(&cli_arguments)?;
+    let cli_arguments = if definitions.is_empty() {
+        cli_arguments
+    } else {
+        get_cli_arguments(definitions.expand_arguments(env::args_os()))
+    };
+
     let mut options = Options::from_cli_arguments(&cli_arguments)?;
-    let commands = Commands::from_cli_arguments(&cli_arguments)?;
-    let export_manager = ExportManager::from_cli_arguments(
-        &cli_arguments,
-        options.time_unit,
-        options.sort_order_exports,
-    )?;
+    output::log::set_verbosity(options.verbosity);
+    output::glyphs::set_ascii(options.ascii);
+    capabilities::select_available_probes(&mut options);
+    let command_list = CommandList::from_cli_arguments(&cli_arguments)?;
+    let commands = Commands::from_cli_arguments(&cli_arguments, &command_list)?;
+    let export_manager = ExportManager::from_cli_arguments(&cli_arguments, &options)?;
 
     options.validate_against_command_list(&commands)?;
 
+    if let Some(hosts) = HostList::from_cli_arguments(&cli_arguments)? {
+        return hosts.run(
+            &cli_arguments,
+            &command_list,
+            cli_arguments
+                .get_one::<String>("export-json")
+                .map(String::as_str),
+        );
+    }
+
+    if let Some(start_delay) = options.start_delay {
+        start_delay.wait(options.output_style);
+    }
+
+    let _session_lock = options
+        .lock_file
+        .as_deref()
+        .map(|path| SessionLock::acquire(path, options.output_style, options.fail_if_locked))
+        .transpose()
+        .exit_code(ExitCode::PreflightFailed)?;
+
+    // Make sure that the teardown command can run, and the CPU state and paused processes
+    // can be restored if the benchmark is interrupted
+    if options.teardown_command.is_some()
+        || options.disable_turbo
+        || options.disable_smt
+        || !options.pause_processes.is_empty()
+    {
+        util::interrupt::install_handler().context("Could not install a handler for Ctrl-C")?;
+    }
+
+    let _cpu_state = CpuStateGuard::apply(options.disable_turbo, options.disable_smt)
+        .exit_code(ExitCode::PreflightFailed)?;
+    let _paused_processes =
+        PausedProcesses::pause(&options.pause_processes).exit_code(ExitCode::PreflightFailed)?;
+
+    if let Some(node) = options.numa_node {
+        util::numa::bind_to_node(node).exit_code(ExitCode::PreflightFailed)?;
+    }
+
     let mut scheduler = Scheduler::new(&commands, &options, &export_manager);
+    if let Some(format) = options.just_run {
+        return scheduler.just_run(format);
+    }
     scheduler.run_benchmarks()?;
     scheduler.print_relative_speed_comparison();
+    scheduler.print_power_analysis();
+    scheduler.print_parameter_analysis();
     scheduler.final_export()?;
+    scheduler.notify();
+    scheduler.check_failure_conditions()?;
 
     Ok(())
 }
@@ -56,7 +141,13 @@ fn main() {
         Ok(_) => {}
         Err(e) => {
             eprintln!("{} {:#}", "Error:".red(), e);
-            std::process::exit(1);
+
+            let exit_code = if util::interrupt::was_interrupted() {
+                ExitCode::Interrupted
+            } else {
+                error::exit_code_of(&e)
+            };
+            std::process::exit(exit_code.code());
         }
     }
 }
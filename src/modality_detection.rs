@@ -0,0 +1,154 @@
+//! A module for detecting multimodal distributions. Benchmark times with several modes are
+//! usually caused by effects of the memory layout (e.g. address space layout randomization),
+//! which means that the mean and the standard deviation do not describe the results well.
+//!
+//! The modes are the peaks of a kernel density estimate with a Gaussian kernel. The bandwidth
+//! is chosen with Silverman's rule of thumb, which tends to oversmooth multimodal data, such
+//! that only pronounced modes are detected.
+//!
+//! References:
+//! - B. W. Silverman (1986), "Density Estimation for Statistics and Data Analysis",
+//!   Chapman & Hall.
+
+use statistical::{mean, median, standard_deviation};
+
+/// Minimum number of runs for which modes are detected
+pub const MIN_SAMPLE_SIZE: usize = 10;
+
+/// Number of points at which the density is evaluated
+const GRID_SIZE: usize = 512;
+
+/// Minimum height of a mode, relative to the highest one
+const MIN_RELATIVE_HEIGHT: f64 = 0.1;
+
+/// Maximum density between two modes, relative to the lower of them. Modes which are not
+/// separated by such a dip are merged.
+const MAX_RELATIVE_DIP: f64 = 0.75;
+
+/// Minimum distance between two modes, relative to the median. Closer modes are merged, as
+/// such small differences are irrelevant in practice (e.g. 101.0 ms and 101.3 ms).
+const MIN_RELATIVE_DISTANCE: f64 = 0.02;
+
+/// Bandwidth for the kernel density estimate, according to Silverman's rule of thumb
+fn bandwidth(sorted: &[f64]) -> f64 {
+    let n = sorted.len() as f64;
+    let stddev = standard_deviation(sorted, Some(mean(sorted)));
+    let quantile = |q: f64| sorted[((n - 1.0) * q).round() as usize];
+    let iqr = quantile(0.75) - quantile(0.25);
+
+    let spread = if iqr > 0.0 {
+        stddev.min(iqr / 1.34)
+    } else {
+        stddev
+    };
+    0.9 * spread * n.powf(-0.2)
+}
+
+/// Return the locations of the modes of the given sample, in ascending order. Samples that
+/// are too small to tell modes apart have a single mode at their median.
+pub fn modes(xs: &[f64]) -> Vec<f64> {
+    assert!(!xs.is_empty());
+
+    let mut sorted = xs.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let median = median(&sorted);
+    if sorted.len() < MIN_SAMPLE_SIZE {
+        return vec![median];
+    }
+
+    let h = bandwidth(&sorted);
+    if h <= 0.0 || !h.is_finite() {
+        return vec![median];
+    }
+
+    // Evaluate the (unnormalized) density on a grid which covers the tails of the kernels
+    let lower = sorted[0] - 3.0 * h;
+    let upper = sorted[sorted.len() - 1] + 3.0 * h;
+    let step = (upper - lower) / (GRID_SIZE - 1) as f64;
+    let grid: Vec<f64> = (0..GRID_SIZE).map(|i| lower + i as f64 * step).collect();
+    let density: Vec<f64> = grid
+        .iter()
+        .map(|&x| {
+            sorted
+                .iter()
+                .map(|&xi| (-0.5 * ((x - xi) / h).powi(2)).exp())
+                .sum()
+        })
+        .collect();
+
+    let max_density = density.iter().cloned().fold(0.0, f64::max);
+    let peaks = (1..GRID_SIZE - 1).filter(|&i| {
+        density[i] > density[i - 1]
+            && density[i] >= density[i + 1]
+            && density[i] >= MIN_RELATIVE_HEIGHT * max_density
+    });
+
+    // Merge neighbouring peaks which are not separated by a pronounced dip
+    let mut modes: Vec<usize> = vec![];
+    for peak in peaks {
+        if let Some(&previous) = modes.last() {
+            let dip = density[previous..=peak]
+                .iter()
+                .cloned()
+                .fold(f64::INFINITY, f64::min);
+            let too_close = grid[peak] - grid[previous] < MIN_RELATIVE_DISTANCE * median.abs();
+            if too_close || dip > MAX_RELATIVE_DIP * density[previous].min(density[peak]) {
+                if density[peak] > density[previous] {
+                    *modes.last_mut().unwrap() = peak;
+                }
+                continue;
+            }
+        }
+        modes.push(peak);
+    }
+
+    if modes.is_empty() {
+        return vec![median];
+    }
+    modes.into_iter().map(|i| grid[i]).collect()
+}
+
+#[test]
+fn test_unimodal_samples() {
+    // Too small to detect modes
+    assert_eq!(modes(&[1.0, 5.0, 1.0]), vec![1.0]);
+
+    // Constant samples
+    assert_eq!(modes(&[2.0; 20]), vec![2.0]);
+
+    let uniform: Vec<f64> = (0..50).map(|i| 10.0 + 0.01 * i as f64).collect();
+    assert_eq!(modes(&uniform).len(), 1);
+
+    let with_outlier: Vec<f64> = (0..30)
+        .map(|i| 10.0 + 0.01 * (i % 7) as f64)
+        .chain(std::iter::once(15.0))
+        .collect();
+    assert_eq!(modes(&with_outlier).len(), 1);
+
+    // Separate, but very close clusters
+    let close: Vec<f64> = (0..40)
+        .map(|i| if i % 2 == 0 { 101.0 } else { 101.3 } + 0.01 * (i % 5) as f64)
+        .collect();
+    assert_eq!(modes(&close).len(), 1);
+}
+
+#[test]
+fn test_bimodal_samples() {
+    use approx::assert_relative_eq;
+
+    let bimodal: Vec<f64> = (0..40)
+        .map(|i| {
+            let noise = 0.01 * (i % 5) as f64;
+            if i % 2 == 0 {
+                10.0 + noise
+            } else {
+                12.0 + noise
+            }
+        })
+        .collect();
+
+    let modes = modes(&bimodal);
+    assert_eq!(modes.len(), 2);
+    assert_relative_eq!(modes[0], 10.02, epsilon = 0.05);
+    assert_relative_eq!(modes[1], 12.02, epsilon = 0.05);
+}
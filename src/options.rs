@@ -1,15 +1,24 @@
-use std::fs::File;
+use std::fs::{File, OpenOptions};
 use std::io::IsTerminal;
 use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use std::process::{Command, ExitStatus, Stdio};
 use std::{cmp, env, fmt, io};
 
-use anyhow::ensure;
+use anyhow::{anyhow, ensure};
 use clap::ArgMatches;
 
-use crate::command::Commands;
+use crate::benchmark::derived::DerivedMetric;
+use crate::command::{self, Commands, PREV_RUN_PLACEHOLDER, TOTAL_RUNS_PLACEHOLDER};
 use crate::error::OptionsError;
-use crate::quantity::{second, Time, TimeUnit};
+use crate::output::log::Verbosity;
+use crate::quantity::{second, Information, InformationUnit, Quantity, Time, TimeUnit};
+use crate::timer::ErrorOutput;
+use crate::util::duration::parse_duration;
+use crate::util::exit_code::extract_exit_code;
+use crate::util::session_lock;
+use crate::util::size::parse_size;
+use crate::util::start_delay::{StartDelay, TimeOfDay};
+use crate::util::timestamp::UtcDateTime;
 
 use anyhow::Result;
 
@@ -44,6 +53,30 @@ impl fmt::Display for Shell {
     }
 }
 
+/// The family of a shell, which determines how a command is passed to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellKind {
+    /// POSIX shells like sh, bash or zsh, and others which follow their conventions
+    Posix,
+
+    /// The Windows command interpreter, cmd.exe
+    Cmd,
+
+    /// Windows PowerShell or PowerShell Core (pwsh), on any platform
+    PowerShell,
+}
+
+impl ShellKind {
+    /// The argument which precedes the command that is executed by the shell
+    pub fn command_flag(self) -> &'static str {
+        match self {
+            ShellKind::Cmd => "/C",
+            ShellKind::PowerShell => "-Command",
+            ShellKind::Posix => "-c",
+        }
+    }
+}
+
 impl Shell {
     /// Parse given string as shell command line
     pub fn parse_from_str<'a>(s: &str) -> Result<Self, OptionsError<'a>> {
@@ -54,6 +87,26 @@ impl Shell {
         Ok(Shell::Custom(v))
     }
 
+    /// Determine the kind of the shell from the name of its executable
+    pub fn kind(&self) -> ShellKind {
+        let program = match self {
+            Shell::Default(cmd) => cmd,
+            Shell::Custom(cmdline) => cmdline[0].as_str(),
+        };
+        // Windows paths are recognized on all platforms
+        let name = program
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or(program)
+            .to_lowercase();
+
+        match name.strip_suffix(".exe").unwrap_or(&name) {
+            "cmd" => ShellKind::Cmd,
+            "pwsh" | "powershell" => ShellKind::PowerShell,
+            _ => ShellKind::Posix,
+        }
+    }
+
     pub fn command(&self) -> Command {
         match self {
             Shell::Default(cmd) => Command::new(cmd),
@@ -95,13 +148,298 @@ pub enum OutputStyleOption {
     Disabled,
 }
 
+/// Format of the times that are printed in the '--just-run' mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JustRunFormat {
+    /// The wall clock time of each run in seconds, one per line
+    Plain,
+
+    /// A JSON object with the times and the exit code of each run, one per line
+    Jsonl,
+}
+
+/// Extra runs of each command under a profiler (see '--profile-with')
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileOptions {
+    /// The profiler command line, to which the benchmarked command is appended. Contains
+    /// the `{output}` placeholder.
+    pub command: String,
+
+    /// Number of profiled runs of each command
+    pub runs: u64,
+
+    /// Directory in which the profiles are stored
+    pub directory: PathBuf,
+}
+
+/// Placeholder for the path of the profile in the '--profile-with' command
+pub const PROFILE_OUTPUT_PLACEHOLDER: &str = "{output}";
+
+/// Placeholder for the start time of the session in the '--output-dir' path
+pub const TIMESTAMP_PLACEHOLDER: &str = "{timestamp}";
+
+/// Order in which the values of a parameter scan are benchmarked
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ScanOrder {
+    /// In ascending order of the parameter values
+    #[default]
+    Ascending,
+
+    /// Alternating between the lowest and the highest remaining value, such that a slow
+    /// drift of the system (e.g. thermal throttling) is not mistaken for a scaling trend
+    Zigzag,
+
+    /// In random order
+    Random,
+}
+
+/// What to do with benchmarks that appear more than once (see '--duplicates')
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateCommands {
+    /// Abort with an error
+    #[default]
+    Error,
+
+    /// Combine the runs of all copies into a single result
+    Merge,
+
+    /// Benchmark and report each copy separately
+    Keep,
+}
+
+/// How the commands are shown in the terminal output (see '--command-display')
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CommandDisplay {
+    /// The full commands
+    Full,
+
+    /// Long commands are shortened in the middle to fit into the terminal width
+    #[default]
+    Truncate,
+
+    /// The names of the commands or, if they have none, of the programs
+    NameOnly,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SortOrder {
     Command,
     MeanTime,
 }
 
+/// The metric on which the relative speed comparison (and the choice of the fastest
+/// command) is based
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RelativeMetric {
+    /// Wall clock time
+    #[default]
+    WallClock,
+
+    /// User time
+    User,
+
+    /// System time
+    System,
+
+    /// Total CPU time (user and system)
+    Cpu,
+
+    /// Peak memory usage
+    Memory,
+}
+
+impl RelativeMetric {
+    /// Description of the metric in the headline of the comparison, if it is not the
+    /// (default) wall clock time
+    pub fn description(self) -> Option<&'static str> {
+        match self {
+            RelativeMetric::WallClock => None,
+            RelativeMetric::User => Some("user time"),
+            RelativeMetric::System => Some("system time"),
+            RelativeMetric::Cpu => Some("CPU time"),
+            RelativeMetric::Memory => Some("peak memory usage"),
+        }
+    }
+}
+
+/// How the uncertainty of the relative speed (the ratio of two means) is estimated
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RelativeError {
+    /// By resampling the runs of both benchmarks, which does not assume that the times are
+    /// normally distributed
+    #[default]
+    Bootstrap,
+
+    /// By propagating the standard deviations of the run times, assuming independent and
+    /// normally distributed times
+    Naive,
+}
+
+/// Unit in which peak memory usage is reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryUnit {
+    /// Choose a suitable unit based on the reported value
+    Auto,
+
+    /// Always use the given unit
+    Fixed(InformationUnit),
+}
+
+impl MemoryUnit {
+    /// Resolve the unit to use for displaying the given amount of memory
+    pub fn resolve(self, reference: Information) -> InformationUnit {
+        match self {
+            MemoryUnit::Auto => reference.suitable_unit(),
+            MemoryUnit::Fixed(unit) => unit,
+        }
+    }
+}
+
+/// Exit codes of the benchmarked command which get special treatment
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ExitCodeClasses {
+    /// Non-zero exit codes which are accepted like a successful run
+    pub ok: Vec<i32>,
+
+    /// Exit codes which cause the run to be discarded and repeated
+    pub skip: Vec<i32>,
+}
+
+impl ExitCodeClasses {
+    /// Whether the run is considered to be successful
+    pub fn is_ok(&self, status: ExitStatus) -> bool {
+        status.success() || extract_exit_code(status).is_some_and(|code| self.ok.contains(&code))
+    }
+
+    /// Whether the run should be discarded and repeated
+    pub fn is_skip(&self, status: ExitStatus) -> bool {
+        extract_exit_code(status).is_some_and(|code| self.skip.contains(&code))
+    }
+}
+
+/// Conditions under which hyperfine fails after all benchmarks have been performed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FailureConditions {
+    /// Fail if a command is slower than the reference
+    pub regression: bool,
+
+    /// Fail if statistical outliers have been detected
+    pub outliers: bool,
+
+    /// Fail if any warning has been reported about the results (see '--strict')
+    pub warnings: bool,
+}
+
+/// When the system calls of the benchmarked commands are counted (see '--count-syscalls')
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyscallCounting {
+    /// In extra runs after the timing runs, which are not part of the measurements
+    Separate,
+
+    /// In the timing runs, which are slowed down by the tracing
+    Inline,
+}
+
+/// How the end of a session is signaled (see '--notify')
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Notification {
+    /// Ring the terminal bell
+    pub bell: bool,
+
+    /// Show a desktop notification
+    pub desktop: bool,
+}
+
+/// Formatting of the CSV exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvFormat {
+    /// Field delimiter
+    pub delimiter: u8,
+
+    /// Use a comma instead of a dot as the decimal separator
+    pub decimal_comma: bool,
+}
+
+impl Default for CsvFormat {
+    fn default() -> Self {
+        CsvFormat {
+            delimiter: b',',
+            decimal_comma: false,
+        }
+    }
+}
+
+impl CsvFormat {
+    /// Format a number (given in its standard representation) for a CSV field
+    pub fn number(&self, value: String) -> String {
+        if self.decimal_comma {
+            value.replace('.', ",")
+        } else {
+            value
+        }
+    }
+}
+
+/// Number of decimal places of the numbers in the exports (see '--precision'). If a value
+/// is not set, the default of the respective export format is used.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Precision {
+    /// Decimal places of times (in the unit of the export)
+    pub time: Option<usize>,
+
+    /// Decimal places of relative speeds
+    pub relative: Option<usize>,
+
+    /// Decimal places of memory sizes (in the unit of the export)
+    pub memory: Option<usize>,
+}
+
+impl Precision {
+    /// Parse a comma-separated list like '4,relative=2'. A plain number applies to all
+    /// kinds of numbers, and later entries override earlier ones.
+    pub fn parse<'a>(spec: &str) -> Result<Self, OptionsError<'a>> {
+        let invalid = || OptionsError::InvalidPrecision(spec.to_string());
+
+        let mut precision = Precision::default();
+        for entry in spec.split(',') {
+            let (kind, places) = match entry.split_once('=') {
+                Some((kind, places)) => (Some(kind.trim()), places),
+                None => (None, entry),
+            };
+            let places = places.trim().parse::<usize>().map_err(|_| invalid())?;
+            match kind {
+                None => {
+                    precision = Precision {
+                        time: Some(places),
+                        relative: Some(places),
+                        memory: Some(places),
+                    }
+                }
+                Some("time") => precision.time = Some(places),
+                Some("relative") => precision.relative = Some(places),
+                Some("memory") => precision.memory = Some(places),
+                Some(_) => return Err(invalid()),
+            }
+        }
+        Ok(precision)
+    }
+}
+
+/// Formatting of the Emacs org-mode exports
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct OrgmodeFormat {
+    /// Name of the table (`#+NAME:`), such that it can be referenced from other tables
+    pub name: Option<String>,
+
+    /// Emit a row with column alignment cookies (`<l>`, `<r>`)
+    pub alignment_cookies: bool,
+
+    /// Emit a `#+TBLFM` line which recomputes the relative speed
+    pub formula: bool,
+}
+
 /// Bounds for the number of benchmark runs
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RunBounds {
     /// Minimum number of benchmark runs
     pub min: u64,
@@ -110,6 +448,14 @@ pub struct RunBounds {
     pub max: Option<u64>,
 }
 
+impl RunBounds {
+    /// The number of runs, given how many runs fit into the minimum benchmarking time
+    pub fn count(&self, runs_in_min_time: u64) -> u64 {
+        let min = cmp::max(runs_in_min_time, self.min);
+        self.max.map_or(min, |max| cmp::min(min, max))
+    }
+}
+
 impl Default for RunBounds {
     fn default() -> Self {
         RunBounds { min: 10, max: None }
@@ -154,24 +500,43 @@ pub enum CommandOutputPolicy {
     /// Redirect output to a file
     File(PathBuf),
 
+    /// Append output and error output to a file (for the hooks, see '--hook-output')
+    Append(PathBuf),
+
     /// Show command output on the terminal
     Inherit,
 }
 
 impl CommandOutputPolicy {
-    pub fn get_stdout_stderr(&self) -> io::Result<(Stdio, Stdio)> {
+    /// Unless the output is shown on the terminal or appended to a file, stderr is written
+    /// to a temporary file, such that the last part of it can be reported in case the
+    /// command fails.
+    pub fn get_stdout_stderr(&self) -> io::Result<(Stdio, Stdio, Option<ErrorOutput>)> {
         let streams = match self {
-            CommandOutputPolicy::Null => (Stdio::null(), Stdio::null()),
+            CommandOutputPolicy::Null => {
+                let (error_output, stderr) = ErrorOutput::create()?;
+                (Stdio::null(), stderr, Some(error_output))
+            }
 
-            // Typically only stdout is performance-relevant, so just pipe that
-            CommandOutputPolicy::Pipe => (Stdio::piped(), Stdio::null()),
+            // Typically only stdout is performance-relevant, so just discard that through
+            // a pipe
+            CommandOutputPolicy::Pipe => {
+                let (error_output, stderr) = ErrorOutput::create()?;
+                (Stdio::piped(), stderr, Some(error_output))
+            }
 
             CommandOutputPolicy::File(path) => {
                 let file = File::create(path)?;
-                (file.into(), Stdio::null())
+                let (error_output, stderr) = ErrorOutput::create()?;
+                (file.into(), stderr, Some(error_output))
+            }
+
+            CommandOutputPolicy::Append(path) => {
+                let file = OpenOptions::new().create(true).append(true).open(path)?;
+                (file.try_clone()?.into(), file.into(), None)
             }
 
-            CommandOutputPolicy::Inherit => (Stdio::inherit(), Stdio::inherit()),
+            CommandOutputPolicy::Inherit => (Stdio::inherit(), Stdio::inherit(), None),
         };
 
         Ok(streams)
@@ -191,20 +556,99 @@ impl Default for ExecutorKind {
     }
 }
 
+/// Clock for measuring the wall clock time of the benchmarked processes (see '--timer')
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TimerKind {
+    /// The monotonic clock of the operating system
+    #[default]
+    Monotonic,
+
+    /// `CLOCK_MONOTONIC_RAW`, without NTP adjustments (Linux only)
+    MonotonicRaw,
+
+    /// The CPU time (user and system time) of the process
+    TaskClock,
+
+    /// The time stamp counter of the CPU (x86-64 only)
+    Tsc,
+}
+
+impl TimerKind {
+    /// Whether this timer is available on the current platform
+    pub fn is_supported(self) -> bool {
+        match self {
+            TimerKind::Monotonic | TimerKind::TaskClock => true,
+            TimerKind::MonotonicRaw => cfg!(any(target_os = "linux", target_os = "android")),
+            TimerKind::Tsc => cfg!(target_arch = "x86_64"),
+        }
+    }
+}
+
+/// An already running process that is observed instead of running a command (see `--attach`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AttachTarget {
+    /// Process ID
+    pub pid: u32,
+
+    /// For how long the process is observed in each run
+    pub duration: Time,
+}
+
 /// The main settings for a hyperfine benchmark session
 pub struct Options {
-    /// Upper and lower bound for the number of benchmark runs
-    pub run_bounds: RunBounds,
+    /// Upper and lower bound for the number of benchmark runs of each command
+    pub run_bounds: Vec<RunBounds>,
+
+    /// Maximum number of runs of each command, if failing runs are repeated until the
+    /// number of runs given by the run bounds have succeeded (see '--successful-runs')
+    pub max_attempts: Option<u64>,
 
     /// Number of warmup runs
     pub warmup_count: u64,
 
-    /// Number of simultaneous jobs
+    /// Number of initial runs that are recorded, but excluded from the statistics
+    pub discard_first: u64,
+
+    /// Fraction of the fastest and of the slowest runs that is excluded from the statistics
+    pub trim: Option<f64>,
+
+    /// Number of benchmarks that are run concurrently
     pub jobs: usize,
 
     /// Minimum benchmarking time
     pub min_benchmarking_time: Time,
 
+    /// Estimated duration of the session above which the user is asked for confirmation
+    /// (see '--confirm-after'). Not set if the user can not be asked.
+    pub confirmation_threshold: Option<Time>,
+
+    /// When to start the benchmark session (see '--start-at' and '--start-after')
+    pub start_delay: Option<StartDelay>,
+
+    /// The lock file that prevents concurrent sessions (see '--lock' and '--no-lock')
+    pub lock_file: Option<PathBuf>,
+
+    /// Fail instead of waiting if another session holds the lock (see '--fail-if-locked')
+    pub fail_if_locked: bool,
+
+    /// Time of the first run above which the remaining runs of a command are skipped
+    /// (see '--abort-if-slower-than')
+    pub abort_threshold: Option<Time>,
+
+    /// Time after which no more runs of a benchmark are started (see '--benchmark-deadline')
+    pub benchmark_deadline: Option<Time>,
+
+    /// Relative standard deviation above which the remaining runs of a benchmark are
+    /// skipped (see '--abort-on-noise')
+    pub noise_threshold: Option<f64>,
+
+    /// Directory which collects all exports and artifacts of the session (see
+    /// '--output-dir')
+    pub output_dir: Option<PathBuf>,
+
+    /// Extra runs under a profiler, after the timing runs (see '--profile-with')
+    pub profiling: Option<ProfileOptions>,
+
     /// Whether or not to ignore non-zero exit codes
     pub command_failure_action: CmdFailureAction,
 
@@ -220,50 +664,252 @@ pub struct Options {
     /// Command to run before each *batch* of timing runs, i.e. before each individual benchmark
     pub setup_command: Option<String>,
 
-    /// Command to run after each *batch* of timing runs, i.e. after each individual benchmark
-    pub cleanup_command: Option<String>,
+    /// Command(s) to run after each *batch* of timing runs, i.e. after each individual benchmark
+    pub cleanup_command: Option<Vec<String>>,
+
+    /// Command to run once after each individual benchmark, even if it failed
+    pub teardown_command: Option<String>,
 
     /// What color mode to use for the terminal output
     pub output_style: OutputStyleOption,
 
+    /// Whether to write the terminal output and the markup exports in plain ASCII
+    pub ascii: bool,
+
     /// How to order benchmarks in the relative speed comparison
     pub sort_order_speed_comparison: SortOrder,
 
     /// How to order benchmarks in the markup format exports
     pub sort_order_exports: SortOrder,
 
+    /// The metric on which the relative speed comparison is based
+    pub relative_metric: RelativeMetric,
+
+    /// How the uncertainty of the relative speeds is estimated
+    pub relative_error: RelativeError,
+
+    /// Relative difference of the mean times below which benchmarks with overlapping
+    /// confidence intervals are reported as equivalent (see '--highlight-threshold')
+    pub highlight_threshold: Option<f64>,
+
+    /// Whether to print the statistical power of the comparisons (see '--power-analysis')
+    pub power_analysis: bool,
+
     /// Determines how we run commands
     pub executor_kind: ExecutorKind,
 
+    /// Process to observe instead of running the benchmarked commands
+    pub attach: Option<AttachTarget>,
+
     /// Where input to the benchmarked command comes from
     pub command_input_policy: CommandInputPolicy,
 
     /// What to do with the output of the benchmarked commands
     pub command_output_policies: Vec<CommandOutputPolicy>,
 
+    /// What to do with the output of the setup, preparation, conclusion, cleanup and
+    /// teardown commands, if it differs from the one of the benchmarked commands
+    pub hook_output_policy: Option<CommandOutputPolicy>,
+
+    /// Number of bytes of stdout and stderr of the first benchmark run that are recorded
+    pub capture_output: Option<usize>,
+
+    /// Whether the stages of pipelines are run and measured separately (see
+    /// '--pipeline-stages')
+    pub pipeline_stages: bool,
+
+    /// File which is sourced by the shell before each command (see '--source')
+    pub source_file: Option<PathBuf>,
+
+    /// Command prefix that runs the measurement shim, which times each command where it runs
+    /// (see '--shim')
+    pub shim: Option<String>,
+
+    /// Maximum size of the randomized environment variable, if the sizes are recorded
+    /// (see '--randomize-env-offset')
+    pub env_offset_max: Option<usize>,
+
+    /// Print the time of each run instead of computing statistics (see '--just-run')
+    pub just_run: Option<JustRunFormat>,
+
+    /// Order in which the values of a parameter scan are benchmarked
+    pub scan_order: ScanOrder,
+
+    /// The seed of the random order of the benchmarks, if they are shuffled (see '--shuffle')
+    pub shuffle_seed: Option<u64>,
+
+    /// What to do with benchmarks that appear more than once
+    pub duplicate_commands: DuplicateCommands,
+
+    /// How the commands are shown in the terminal output
+    pub command_display: CommandDisplay,
+
+    /// Whether to annotate the benchmarks with the git revision of the benchmarked program
+    pub git_annotate: bool,
+
+    /// Whether each run depends on the state left behind by the previous one (see also the
+    /// `{prev_run}` placeholder)
+    pub stateful: bool,
+
     /// Which time unit to use when displaying results
     pub time_unit: Option<TimeUnit>,
+
+    /// Which unit to use when displaying peak memory usage. Memory usage is not
+    /// displayed if this is not set.
+    pub memory_unit: Option<MemoryUnit>,
+
+    /// Directory in which benchmark results are cached
+    pub cache_directory: Option<PathBuf>,
+
+    /// Glob pattern for commands whose cached results should be ignored
+    pub cache_refresh: Option<String>,
+
+    /// Glob pattern for the files that need to be unchanged for cached results to be
+    /// reused (see '--skip-if-unchanged')
+    pub skip_if_unchanged: Option<String>,
+
+    /// Whether to subtract the shell spawning time from the measured times
+    pub shell_correction: bool,
+
+    /// Clock for measuring the wall clock time
+    pub timer: TimerKind,
+
+    /// Whether to sample GPU metrics while the commands are running
+    pub sample_gpu: bool,
+
+    /// Metrics that are computed from the measurements of each run (see '--derived')
+    pub derived_metrics: Vec<DerivedMetric>,
+
+    /// Kill each run after this multiple of the mean time of the fastest command so far
+    /// (see '--auto-timeout')
+    pub auto_timeout: Option<f64>,
+
+    /// Whether the run queue delay of each run is measured (see '--scheduling-delay')
+    pub measure_scheduling_delay: bool,
+
+    /// Measure an empty run after the first and then after every n-th benchmark run (see
+    /// '--noise-floor')
+    pub noise_floor_interval: Option<u64>,
+
+    /// Memory limit of each run, which may contain parameters (see '--limit-memory')
+    pub memory_limit: Option<String>,
+
+    /// File or directory whose change in disk usage is measured in each run
+    pub disk_usage_path: Option<PathBuf>,
+
+    /// Whether and when the system calls of each run are counted
+    pub syscall_counting: Option<SyscallCounting>,
+
+    /// Whether to disable turbo boost for the duration of the benchmark session
+    pub disable_turbo: bool,
+
+    /// Whether to disable simultaneous multithreading for the duration of the session
+    pub disable_smt: bool,
+
+    /// The NUMA node to which the session is bound (see '--numa-node')
+    pub numa_node: Option<u32>,
+
+    /// Names of the processes that are stopped while the benchmarks are running
+    pub pause_processes: Vec<String>,
+
+    /// How much diagnostic output to show
+    pub verbosity: Verbosity,
+
+    /// Formatting of the CSV exports
+    pub csv_format: CsvFormat,
+
+    /// Formatting of the org-mode exports
+    pub orgmode_format: OrgmodeFormat,
+
+    /// Number of decimal places in the exports
+    pub precision: Precision,
+
+    /// Exit codes which are accepted or cause a run to be repeated
+    pub exit_codes: ExitCodeClasses,
+
+    /// Conditions under which the benchmark session fails (see '--fail-on')
+    pub fail_on: FailureConditions,
+
+    /// How the end of the session is signaled
+    pub notify: Notification,
 }
 
 impl Default for Options {
     fn default() -> Options {
         Options {
-            run_bounds: RunBounds::default(),
+            run_bounds: vec![RunBounds::default()],
+            max_attempts: None,
             warmup_count: 0,
+            discard_first: 0,
+            trim: None,
             jobs: 1,
             min_benchmarking_time: Time::new::<second>(3.0),
+            confirmation_threshold: None,
+            start_delay: None,
+            lock_file: None,
+            fail_if_locked: false,
+            abort_threshold: None,
+            benchmark_deadline: None,
+            noise_threshold: None,
+            output_dir: None,
+            profiling: None,
             command_failure_action: CmdFailureAction::RaiseError,
             reference_command: None,
             preparation_command: None,
             conclusion_command: None,
             setup_command: None,
             cleanup_command: None,
+            teardown_command: None,
             output_style: OutputStyleOption::Full,
             sort_order_speed_comparison: SortOrder::MeanTime,
+            highlight_threshold: None,
+            power_analysis: false,
             sort_order_exports: SortOrder::Command,
+            relative_metric: RelativeMetric::default(),
+            relative_error: RelativeError::default(),
             executor_kind: ExecutorKind::default(),
+            attach: None,
             command_output_policies: vec![CommandOutputPolicy::Null],
+            hook_output_policy: None,
+            capture_output: None,
+            env_offset_max: None,
+            pipeline_stages: false,
+            source_file: None,
+            shim: None,
+            just_run: None,
+            scan_order: ScanOrder::default(),
+            shuffle_seed: None,
+            duplicate_commands: DuplicateCommands::default(),
+            command_display: CommandDisplay::default(),
+            git_annotate: false,
+            stateful: false,
             time_unit: None,
+            memory_unit: None,
+            cache_directory: None,
+            cache_refresh: None,
+            skip_if_unchanged: None,
+            shell_correction: true,
+            timer: TimerKind::default(),
+            sample_gpu: false,
+            derived_metrics: vec![],
+            noise_floor_interval: None,
+            auto_timeout: None,
+            measure_scheduling_delay: false,
+            memory_limit: None,
+            disk_usage_path: None,
+            syscall_counting: None,
+            disable_turbo: false,
+            disable_smt: false,
+            numa_node: None,
+            pause_processes: vec![],
+            verbosity: Verbosity::Normal,
+            ascii: false,
+            csv_format: CsvFormat::default(),
+            orgmode_format: OrgmodeFormat::default(),
+            precision: Precision::default(),
+            exit_codes: ExitCodeClasses::default(),
+            fail_on: FailureConditions::default(),
+            notify: Notification::default(),
             command_input_policy: CommandInputPolicy::Null,
         }
     }
@@ -283,32 +929,128 @@ impl Options {
         };
 
         options.warmup_count = param_to_u64("warmup")?.unwrap_or(options.warmup_count);
+        options.discard_first = param_to_u64("discard-first")?.unwrap_or(options.discard_first);
+
+        if let Some(trim) = matches.get_one::<String>("trim") {
+            let percent = trim
+                .trim_end_matches('%')
+                .parse::<f64>()
+                .ok()
+                .filter(|percent| (0.0..50.0).contains(percent))
+                .ok_or_else(|| OptionsError::InvalidTrim(trim.clone()))?;
+            options.trim = Some(percent / 100.0);
+        }
+
+        // The run counts can be given once for all commands, or once for each command
+        let params_to_u64 = |param| {
+            matches
+                .get_many::<String>(param)
+                .into_iter()
+                .flatten()
+                .map(|n| {
+                    n.parse::<u64>()
+                        .map_err(|e| OptionsError::IntParsingError(param, e))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let mut min_runs = params_to_u64("min-runs")?;
+        let mut max_runs = params_to_u64("max-runs")?;
 
-        let mut min_runs = param_to_u64("min-runs")?;
-        let mut max_runs = param_to_u64("max-runs")?;
+        let runs = params_to_u64("runs")?;
+        if !runs.is_empty() {
+            min_runs = runs.clone();
+            max_runs = runs;
+        }
 
-        if let Some(runs) = param_to_u64("runs")? {
-            min_runs = Some(runs);
-            max_runs = Some(runs);
+        let num_bounds = cmp::max(1, cmp::max(min_runs.len(), max_runs.len()));
+        if [min_runs.len(), max_runs.len()]
+            .iter()
+            .any(|&len| len > 1 && len != num_bounds)
+        {
+            return Err(OptionsError::InconsistentRunCounts);
+        }
+        let nth = |values: &[u64], n: usize| values.get(n).or(values.first()).copied();
+
+        options.run_bounds = (0..num_bounds)
+            .map(|n| {
+                let mut run_bounds = RunBounds::default();
+                match (nth(&min_runs, n), nth(&max_runs, n)) {
+                    (Some(min), None) => {
+                        run_bounds.min = min;
+                    }
+                    (None, Some(max)) => {
+                        // Since the minimum was not explicit we lower it if max is below the default min.
+                        run_bounds.min = cmp::min(run_bounds.min, max);
+                        run_bounds.max = Some(max);
+                    }
+                    (Some(min), Some(max)) if min > max => {
+                        return Err(OptionsError::EmptyRunsRange);
+                    }
+                    (Some(min), Some(max)) => {
+                        run_bounds.min = min;
+                        run_bounds.max = Some(max);
+                    }
+                    (None, None) => {}
+                };
+                Ok(run_bounds)
+            })
+            .collect::<Result<_, _>>()?;
+
+        if let Some(runs) = param_to_u64("successful-runs")? {
+            options.run_bounds = vec![RunBounds {
+                min: runs,
+                max: Some(runs),
+            }];
+            options.max_attempts = Some(param_to_u64("max-attempts")?.unwrap_or(10 * runs));
         }
 
-        match (min_runs, max_runs) {
-            (Some(min), None) => {
-                options.run_bounds.min = min;
-            }
-            (None, Some(max)) => {
-                // Since the minimum was not explicit we lower it if max is below the default min.
-                options.run_bounds.min = cmp::min(options.run_bounds.min, max);
-                options.run_bounds.max = Some(max);
-            }
-            (Some(min), Some(max)) if min > max => {
-                return Err(OptionsError::EmptyRunsRange);
-            }
-            (Some(min), Some(max)) => {
-                options.run_bounds.min = min;
-                options.run_bounds.max = Some(max);
-            }
-            (None, None) => {}
+        // The runs are performed as they are, without warmup or shell spawning time correction
+        if let Some(runs) = param_to_u64("just-run")? {
+            options.run_bounds = vec![RunBounds {
+                min: runs,
+                max: Some(runs),
+            }];
+            options.just_run = Some(
+                match matches
+                    .get_one::<String>("just-run-format")
+                    .map(|s| s.as_str())
+                {
+                    Some("jsonl") => JustRunFormat::Jsonl,
+                    _ => JustRunFormat::Plain,
+                },
+            );
+        }
+
+        options.scan_order = match matches.get_one::<String>("scan-order").map(|s| s.as_str()) {
+            Some("zigzag") => ScanOrder::Zigzag,
+            Some("random") => ScanOrder::Random,
+            _ => ScanOrder::Ascending,
+        };
+        // Without a given seed, a random one is chosen, which is shown and exported such
+        // that the order can be reproduced
+        if matches.contains_id("shuffle") {
+            options.shuffle_seed = Some(
+                matches
+                    .get_one::<u64>("shuffle")
+                    .copied()
+                    .unwrap_or_else(rand::random),
+            );
+        }
+
+        options.duplicate_commands =
+            match matches.get_one::<String>("duplicates").map(|s| s.as_str()) {
+                Some("merge") => DuplicateCommands::Merge,
+                Some("keep") => DuplicateCommands::Keep,
+                _ => DuplicateCommands::Error,
+            };
+
+        options.command_display = match matches
+            .get_one::<String>("command-display")
+            .map(|s| s.as_str())
+        {
+            Some("full") => CommandDisplay::Full,
+            Some("name-only") => CommandDisplay::NameOnly,
+            _ => CommandDisplay::Truncate,
         };
 
         options.jobs = match param_to_u64("jobs")? {
@@ -328,7 +1070,11 @@ impl Options {
             .get_many::<String>("conclude")
             .map(|values| values.map(String::from).collect::<Vec<String>>());
 
-        options.cleanup_command = matches.get_one::<String>("cleanup").map(String::from);
+        options.cleanup_command = matches
+            .get_many::<String>("cleanup")
+            .map(|values| values.map(String::from).collect::<Vec<String>>());
+
+        options.teardown_command = matches.get_one::<String>("teardown").map(String::from);
 
         options.command_output_policies = if matches.get_flag("show-output") {
             vec![CommandOutputPolicy::Inherit]
@@ -354,6 +1100,32 @@ impl Options {
             vec![CommandOutputPolicy::Null]
         };
 
+        options.hook_output_policy = matches
+            .get_one::<String>("hook-output")
+            .map(|value| match value.as_str() {
+                "inherit" => Ok(CommandOutputPolicy::Inherit),
+                "null" => Ok(CommandOutputPolicy::Null),
+                value => match value.strip_prefix("file:") {
+                    Some(path) if !path.is_empty() => {
+                        Ok(CommandOutputPolicy::Append(PathBuf::from(path)))
+                    }
+                    _ => Err(OptionsError::UnknownHookOutputPolicy(value.to_string())),
+                },
+            })
+            .transpose()?;
+
+        options.capture_output = matches
+            .get_one::<String>("capture-output")
+            .map(|size| size.parse::<usize>())
+            .transpose()
+            .map_err(|e| OptionsError::IntParsingError("capture-output", e))?;
+
+        options.env_offset_max = matches
+            .get_one::<String>("randomize-env-offset")
+            .map(|size| size.parse::<usize>())
+            .transpose()
+            .map_err(|e| OptionsError::IntParsingError("randomize-env-offset", e))?;
+
         options.output_style = match matches.get_one::<String>("style").map(|s| s.as_str()) {
             Some("full") => OutputStyleOption::Full,
             Some("basic") => OutputStyleOption::Basic,
@@ -363,8 +1135,8 @@ impl Options {
             _ => {
                 if options
                     .command_output_policies
-                    .iter()
-                    .any(|policy| *policy == CommandOutputPolicy::Inherit)
+                    .contains(&CommandOutputPolicy::Inherit)
+                    || options.hook_output_policy == Some(CommandOutputPolicy::Inherit)
                     || !io::stdout().is_terminal()
                 {
                     OutputStyleOption::Basic
@@ -402,6 +1174,38 @@ impl Options {
             Some(_) => unreachable!("Unknown sort order"),
         };
 
+        options.relative_metric = match matches
+            .get_one::<String>("relative-metric")
+            .map(|s| s.as_str())
+        {
+            None | Some("wall") => RelativeMetric::WallClock,
+            Some("user") => RelativeMetric::User,
+            Some("system") => RelativeMetric::System,
+            Some("cpu") => RelativeMetric::Cpu,
+            Some("memory") => RelativeMetric::Memory,
+            Some(_) => unreachable!("Unknown relative metric"),
+        };
+
+        options.relative_error = match matches
+            .get_one::<String>("relative-error")
+            .map(|s| s.as_str())
+        {
+            None | Some("bootstrap") => RelativeError::Bootstrap,
+            Some("naive") => RelativeError::Naive,
+            Some(_) => unreachable!("Unknown method for the relative error"),
+        };
+
+        if let Some(threshold) = matches.get_one::<String>("highlight-threshold") {
+            let percent = threshold
+                .trim_end_matches('%')
+                .parse::<f64>()
+                .ok()
+                .filter(|percent| percent.is_finite() && *percent > 0.0)
+                .ok_or_else(|| OptionsError::InvalidHighlightThreshold(threshold.clone()))?;
+            options.highlight_threshold = Some(percent / 100.0);
+        }
+        options.power_analysis = matches.get_flag("power-analysis");
+
         options.executor_kind = if matches.get_flag("no-shell") {
             ExecutorKind::Raw
         } else {
@@ -418,6 +1222,242 @@ impl Options {
             }
         };
 
+        // The debug mode does not run the commands, so it does not disturb other sessions. A
+        // session started by the one that holds the lock must not wait for it.
+        options.lock_file = match (&options.executor_kind, matches.get_one::<String>("lock")) {
+            _ if matches.get_flag("no-lock") => None,
+            _ if session_lock::is_held_by_parent() => None,
+            (ExecutorKind::Mock(_), None) => None,
+            (_, Some(path)) => Some(PathBuf::from(path)),
+            (_, None) => Some(session_lock::default_path()),
+        };
+        options.fail_if_locked = matches.get_flag("fail-if-locked");
+
+        if let Some(pid) = matches.get_one::<String>("attach") {
+            options.attach = Some(AttachTarget {
+                pid: pid
+                    .parse()
+                    .map_err(|e| OptionsError::IntParsingError("attach", e))?,
+                duration: parse_duration(
+                    matches
+                        .get_one::<String>("attach-duration")
+                        .map_or("1s", |d| d.as_str()),
+                )
+                .map_err(|e| OptionsError::InvalidDuration("attach-duration", e))?,
+            });
+        }
+
+        options.shell_correction = !matches.get_flag("no-shell-correction");
+
+        options.pipeline_stages = matches.get_flag("pipeline-stages");
+        if options.pipeline_stages && (cfg!(windows) || options.executor_kind == ExecutorKind::Raw)
+        {
+            return Err(OptionsError::UnsupportedPipelineStages);
+        }
+
+        if let Some(path) = matches.get_one::<String>("source") {
+            match &options.executor_kind {
+                ExecutorKind::Shell(shell) if shell.kind() == ShellKind::Posix => {}
+                ExecutorKind::Mock(_) => {}
+                _ => return Err(OptionsError::UnsupportedSourceFile),
+            }
+            // The absolute path, as '.' searches the PATH for file names without a slash
+            options.source_file = Some(
+                std::fs::canonicalize(path)
+                    .ok()
+                    .filter(|path| path.is_file())
+                    .ok_or_else(|| OptionsError::SourceFileDoesNotExist(path.clone()))?,
+            );
+        }
+
+        if let Some(shim) = matches.get_one::<String>("shim") {
+            match &options.executor_kind {
+                _ if cfg!(windows) => return Err(OptionsError::UnsupportedShim),
+                ExecutorKind::Shell(shell) if shell.kind() == ShellKind::Posix => {}
+                ExecutorKind::Mock(_) => {}
+                _ => return Err(OptionsError::UnsupportedShim),
+            }
+            // The shim prints its record to the error output, which therefore needs to be read
+            if options
+                .command_output_policies
+                .iter()
+                .chain(&options.hook_output_policy)
+                .any(|policy| {
+                    matches!(
+                        policy,
+                        CommandOutputPolicy::Inherit | CommandOutputPolicy::Append(_)
+                    )
+                })
+            {
+                return Err(OptionsError::ShimOutputNotRecorded);
+            }
+            options.shim = Some(shim.clone());
+        }
+
+        if let Some(timer) = matches.get_one::<String>("timer") {
+            options.timer = match timer.as_str() {
+                "monotonic-raw" => TimerKind::MonotonicRaw,
+                "task-clock" => TimerKind::TaskClock,
+                "tsc" => TimerKind::Tsc,
+                _ => TimerKind::Monotonic,
+            };
+            if !options.timer.is_supported() {
+                return Err(OptionsError::UnsupportedTimer(timer.clone()));
+            }
+        }
+        options.sample_gpu = matches.get_flag("sample-gpu");
+        options.derived_metrics = matches
+            .get_many::<String>("derived")
+            .into_iter()
+            .flatten()
+            .map(|definition| {
+                DerivedMetric::parse(definition)
+                    .map_err(|e| OptionsError::InvalidDerivedMetric(definition.clone(), e))
+            })
+            .collect::<Result<_, _>>()?;
+        if let Some(factor) = matches.get_one::<String>("auto-timeout") {
+            options.auto_timeout = Some(
+                factor
+                    .trim_end_matches('x')
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|&factor| factor >= 1.0)
+                    .ok_or_else(|| OptionsError::InvalidAutoTimeout(factor.clone()))?,
+            );
+        }
+        options.noise_floor_interval = param_to_u64("noise-floor")?;
+        match options.noise_floor_interval {
+            Some(0) => return Err(OptionsError::ZeroNoiseFloorInterval),
+            Some(_) if options.executor_kind == ExecutorKind::Raw => {
+                return Err(OptionsError::UnsupportedNoiseFloor)
+            }
+            _ => {}
+        }
+        options.memory_limit = matches.get_one::<String>("limit-memory").cloned();
+        if options.memory_limit.is_some() && cfg!(target_os = "macos") {
+            return Err(OptionsError::UnsupportedMemoryLimit);
+        }
+        options.disk_usage_path = matches.get_one::<String>("measure-disk").map(PathBuf::from);
+        options.syscall_counting = match matches
+            .get_one::<String>("count-syscalls")
+            .map(|s| s.as_str())
+        {
+            Some("separate") => Some(SyscallCounting::Separate),
+            Some("inline") => Some(SyscallCounting::Inline),
+            _ => None,
+        };
+        if options.syscall_counting.is_some() && !cfg!(target_os = "linux") {
+            return Err(OptionsError::UnsupportedSyscallCounting);
+        }
+        options.measure_scheduling_delay = matches.get_flag("scheduling-delay");
+        if options.measure_scheduling_delay && !cfg!(target_os = "linux") {
+            return Err(OptionsError::UnsupportedSchedulingDelay);
+        }
+        options.disable_turbo = matches.get_flag("disable-turbo");
+        options.disable_smt = matches.get_flag("disable-smt");
+        options.numa_node = matches.get_one::<u32>("numa-node").copied();
+        if options.numa_node.is_some() && !cfg!(target_os = "linux") {
+            return Err(OptionsError::UnsupportedNumaNode);
+        }
+        options.pause_processes = matches
+            .get_one::<String>("pause-processes")
+            .map(|names| {
+                names
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        options.csv_format.decimal_comma = matches.get_flag("decimal-comma");
+        options.csv_format.delimiter = match matches.get_one::<String>("csv-delimiter") {
+            Some(delimiter) => match delimiter.as_str() {
+                "\\t" => b'\t',
+                d if d.len() == 1 && d.is_ascii() => d.as_bytes()[0],
+                _ => return Err(OptionsError::InvalidCsvDelimiter(delimiter.clone())),
+            },
+            // A comma can not be used for both, so use the common choice in locales
+            // with a decimal comma.
+            None if options.csv_format.decimal_comma => b';',
+            None => b',',
+        };
+        if options.csv_format.decimal_comma && options.csv_format.delimiter == b',' {
+            return Err(OptionsError::AmbiguousDecimalComma);
+        }
+
+        options.orgmode_format = OrgmodeFormat {
+            name: matches.get_one::<String>("orgmode-name").cloned(),
+            alignment_cookies: matches.get_flag("orgmode-alignment"),
+            formula: matches.get_flag("orgmode-formula"),
+        };
+
+        if let Some(spec) = matches.get_one::<String>("precision") {
+            options.precision = Precision::parse(spec)?;
+        }
+
+        options.ascii = matches.get_flag("ascii");
+        options.git_annotate = matches.get_flag("git-annotate");
+        options.stateful = matches.get_flag("stateful");
+
+        options.verbosity = if matches.get_flag("quiet") {
+            Verbosity::Quiet
+        } else {
+            match matches.get_count("verbose") {
+                0 => Verbosity::Normal,
+                1 => Verbosity::Verbose,
+                _ => Verbosity::Debug,
+            }
+        };
+
+        let param_to_exit_codes = |param| {
+            matches
+                .get_many::<String>(param)
+                .into_iter()
+                .flatten()
+                .map(|code| {
+                    code.trim()
+                        .parse::<i32>()
+                        .map_err(|e| OptionsError::IntParsingError(param, e))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        options.exit_codes = ExitCodeClasses {
+            ok: param_to_exit_codes("exit-code-ok")?,
+            skip: param_to_exit_codes("exit-code-skip")?,
+        };
+        if let Some(code) = options
+            .exit_codes
+            .ok
+            .iter()
+            .find(|code| options.exit_codes.skip.contains(code))
+        {
+            return Err(OptionsError::ConflictingExitCodeClasses(*code));
+        }
+
+        for condition in matches.get_many::<String>("fail-on").into_iter().flatten() {
+            match condition.as_str() {
+                "regression" => options.fail_on.regression = true,
+                "outliers" => options.fail_on.outliers = true,
+                "warnings" => options.fail_on.warnings = true,
+                _ => unreachable!("Unknown '--fail-on' condition"),
+            }
+        }
+        if matches.get_flag("strict") {
+            options.fail_on.warnings = true;
+        }
+
+        match matches.get_one::<String>("notify").map(|s| s.as_str()) {
+            Some("bell") => options.notify.bell = true,
+            Some("desktop") => options.notify.desktop = true,
+            Some(_) => {
+                options.notify.bell = true;
+                options.notify.desktop = true;
+            }
+            None => {}
+        }
+
         if matches.get_flag("ignore-failure") {
             options.command_failure_action = CmdFailureAction::Ignore;
         }
@@ -431,13 +1471,92 @@ impl Options {
             _ => None,
         };
 
+        options.memory_unit = match matches.get_one::<String>("memory-unit").map(|s| s.as_str()) {
+            Some("auto") => Some(MemoryUnit::Auto),
+            Some("B") => Some(MemoryUnit::Fixed(InformationUnit::Byte)),
+            Some("KiB") => Some(MemoryUnit::Fixed(InformationUnit::KibiByte)),
+            Some("MiB") => Some(MemoryUnit::Fixed(InformationUnit::MebiByte)),
+            Some("GiB") => Some(MemoryUnit::Fixed(InformationUnit::GibiByte)),
+            _ => None,
+        };
+
+        options.cache_directory = matches.get_one::<String>("cache").map(PathBuf::from);
+        options.cache_refresh = matches.get_one::<String>("cache-refresh").cloned();
+        options.skip_if_unchanged = matches.get_one::<String>("skip-if-unchanged").cloned();
+
         if let Some(time) = matches.get_one::<String>("min-benchmarking-time") {
-            options.min_benchmarking_time = Time::new::<second>(
-                time.parse::<f64>()
-                    .map_err(|e| OptionsError::FloatParsingError("min-benchmarking-time", e))?,
+            options.min_benchmarking_time = parse_duration(time)
+                .map_err(|e| OptionsError::InvalidDuration("min-benchmarking-time", e))?;
+        }
+
+        if let Some(time) = matches.get_one::<String>("abort-if-slower-than") {
+            options.abort_threshold = Some(
+                parse_duration(time)
+                    .map_err(|e| OptionsError::InvalidDuration("abort-if-slower-than", e))?,
+            );
+        }
+
+        if let Some(time) = matches.get_one::<String>("benchmark-deadline") {
+            options.benchmark_deadline = Some(
+                parse_duration(time)
+                    .map_err(|e| OptionsError::InvalidDuration("benchmark-deadline", e))?,
             );
         }
 
+        if let Some(threshold) = matches.get_one::<String>("abort-on-noise") {
+            let percent = threshold
+                .trim_end_matches('%')
+                .parse::<f64>()
+                .ok()
+                .filter(|percent| percent.is_finite() && *percent > 0.0)
+                .ok_or_else(|| OptionsError::InvalidNoiseThreshold(threshold.clone()))?;
+            options.noise_threshold = Some(percent / 100.0);
+        }
+
+        options.output_dir = matches.get_one::<String>("output-dir").map(|dir| {
+            PathBuf::from(dir.replace(TIMESTAMP_PLACEHOLDER, &UtcDateTime::now().to_file_name()))
+        });
+
+        if let Some(command) = matches.get_one::<String>("profile-with") {
+            if !command.contains(PROFILE_OUTPUT_PLACEHOLDER) {
+                return Err(OptionsError::MissingProfileOutputPlaceholder);
+            }
+            options.profiling = Some(ProfileOptions {
+                command: command.clone(),
+                runs: param_to_u64("profile-runs")?.unwrap_or(1),
+                directory: match (
+                    matches.get_one::<String>("profile-dir"),
+                    &options.output_dir,
+                ) {
+                    (Some(dir), _) => PathBuf::from(dir),
+                    (None, Some(output_dir)) => output_dir.join("profiles"),
+                    (None, None) => PathBuf::from("hyperfine-profiles"),
+                },
+            });
+        }
+
+        if let Some(time) = matches.get_one::<String>("start-at") {
+            options.start_delay = Some(StartDelay::At(
+                TimeOfDay::parse(time).map_err(OptionsError::InvalidTimeOfDay)?,
+            ));
+        } else if let Some(duration) = matches.get_one::<String>("start-after") {
+            options.start_delay = Some(StartDelay::After(
+                parse_duration(duration)
+                    .map_err(|e| OptionsError::InvalidDuration("start-after", e))?,
+            ));
+        }
+
+        let confirmation_threshold = parse_duration(
+            matches
+                .get_one::<String>("confirm-after")
+                .map_or("1h", |d| d.as_str()),
+        )
+        .map_err(|e| OptionsError::InvalidDuration("confirm-after", e))?;
+        // Only ask for confirmation if there is someone to answer
+        if !matches.get_flag("yes") && io::stdin().is_terminal() {
+            options.confirmation_threshold = Some(confirmation_threshold);
+        }
+
         options.command_input_policy = if let Some(path_str) = matches.get_one::<String>("input") {
             if path_str == "null" {
                 CommandInputPolicy::Null
@@ -457,10 +1576,173 @@ impl Options {
         Ok(options)
     }
 
+    /// Whether the results of the individual benchmarks (and progress bars) are shown
+    pub fn show_benchmark_output(&self) -> bool {
+        self.output_style != OutputStyleOption::Disabled && self.verbosity != Verbosity::Quiet
+    }
+
+    /// The total number of benchmark runs of the given command (including discarded ones),
+    /// if it is fixed (see `--runs`)
+    pub fn total_runs(&self, number: usize) -> Option<u64> {
+        let run_bounds = &self.run_bounds[number];
+        run_bounds
+            .max
+            .filter(|&max| max == run_bounds.min)
+            .map(|runs| runs + self.discard_first)
+    }
+
+    /// A description of all options which affect the measurements of the given command,
+    /// such that results are only reused for equivalent benchmarks (see `--cache`)
+    pub fn measurement_settings(&self, number: usize) -> String {
+        format!(
+            "{:?}",
+            (
+                (
+                    &self.run_bounds[number],
+                    self.max_attempts,
+                    self.warmup_count,
+                    self.discard_first,
+                    self.min_benchmarking_time.get::<second>(),
+                    &self.executor_kind,
+                    &self.command_input_policy,
+                    &self.command_output_policies[number],
+                ),
+                (
+                    &self.preparation_command,
+                    &self.conclusion_command,
+                    &self.setup_command,
+                    &self.cleanup_command,
+                    &self.teardown_command,
+                ),
+                (
+                    self.shell_correction,
+                    self.timer,
+                    &self.memory_limit,
+                    self.auto_timeout,
+                    self.capture_output,
+                    self.pipeline_stages,
+                    &self.shim,
+                    self.env_offset_max,
+                ),
+                (
+                    self.stateful,
+                    self.measure_scheduling_delay,
+                    self.disable_turbo,
+                    self.disable_smt,
+                    self.numa_node,
+                    &self.exit_codes,
+                ),
+            )
+        )
+    }
+
+    /// The memory limit of the runs of the given command (see `--limit-memory`), after the
+    /// parameters of the command have been substituted
+    pub fn memory_limit(&self, command: &command::Command) -> Result<Option<Information>> {
+        self.memory_limit
+            .as_deref()
+            .map(|limit| {
+                let limit = command::Command::new_parametrized(
+                    None,
+                    limit,
+                    command.get_parameters().iter().cloned(),
+                )
+                .get_command_line();
+                parse_size(&limit).map_err(|e| anyhow!("Invalid value for '--limit-memory': {e}"))
+            })
+            .transpose()
+    }
+
+    /// Whether the benchmarks are not executed in the order of the results (see
+    /// `--scan-order` and `--shuffle`)
+    pub fn is_reordered(&self) -> bool {
+        self.scan_order != ScanOrder::Ascending || self.shuffle_seed.is_some()
+    }
+
+    /// Whether the runs of the given command depend on each other (see `--stateful`)
+    pub fn is_stateful(&self, command: &command::Command) -> bool {
+        self.stateful || command.uses_placeholder(PREV_RUN_PLACEHOLDER)
+    }
+
     pub fn validate_against_command_list(&mut self, commands: &Commands) -> Result<()> {
         let has_reference_command = self.reference_command.is_some();
         let num_commands = commands.num_commands(has_reference_command);
 
+        let reference_command = self
+            .reference_command
+            .as_deref()
+            .map(|cmd| command::Command::new(None, cmd));
+        for cmd in reference_command.iter().chain(commands.iter()) {
+            self.memory_limit(cmd)?;
+            for metric in &self.derived_metrics {
+                metric
+                    .check_parameters(cmd)
+                    .map_err(|e| anyhow!("Invalid value for '--derived': {e}"))?;
+            }
+        }
+
+        if self.duplicate_commands == DuplicateCommands::Error {
+            let duplicates: Vec<_> = commands
+                .duplicates()
+                .iter()
+                .map(|group| {
+                    format!(
+                        "'{}'",
+                        commands.get(group[0]).get_name_with_unused_parameters()
+                    )
+                })
+                .collect();
+            ensure!(
+                duplicates.is_empty(),
+                "The same benchmark has been specified more than once: {}. Use \
+                 '--duplicates=merge' to combine their runs, or '--duplicates=keep' to \
+                 benchmark each copy separately.",
+                duplicates.join(", ")
+            );
+        }
+
+        ensure!(
+            self.jobs == 1 || !commands.iter().any(|cmd| self.is_stateful(cmd)),
+            "Stateful benchmarks ('--stateful' or the '{{{PREV_RUN_PLACEHOLDER}}}' placeholder) \
+             can not be run in parallel ('--jobs')"
+        );
+
+        if self.run_bounds.len() == 1 {
+            self.run_bounds = vec![self.run_bounds[0].clone(); num_commands];
+        } else {
+            ensure!(
+                self.run_bounds.len() == num_commands,
+                "The '--runs', '--min-runs' and '--max-runs' options have to be provided just once or N \
+                 times, where N={num_commands} is the number of benchmark commands (including a \
+                 potential reference)."
+            );
+        }
+
+        // '{total_runs}' may be used in the benchmarked, preparation and conclusion commands
+        let first_command = usize::from(has_reference_command);
+        let uses_total_runs_without_fixed_runs = commands.iter().enumerate().any(|(i, cmd)| {
+            let mut intermediate_expressions = self
+                .preparation_command
+                .iter()
+                .chain(&self.conclusion_command)
+                .flatten();
+            let uses_total_runs = cmd.uses_placeholder(TOTAL_RUNS_PLACEHOLDER)
+                || intermediate_expressions.any(|expression| {
+                    command::Command::new_parametrized(
+                        None,
+                        expression,
+                        cmd.get_parameters().iter().cloned(),
+                    )
+                    .uses_placeholder(TOTAL_RUNS_PLACEHOLDER)
+                });
+            uses_total_runs && self.total_runs(first_command + i).is_none()
+        });
+        ensure!(
+            !uses_total_runs_without_fixed_runs,
+            "The '{{{TOTAL_RUNS_PLACEHOLDER}}}' placeholder can only be used with a fixed number \
+             of runs ('--runs')"
+        );
+
         if let Some(preparation_command) = &self.preparation_command {
             ensure!(
                 preparation_command.len() <= 1 || num_commands == preparation_command.len(),
@@ -477,6 +1759,14 @@ impl Options {
             );
         }
 
+        if let Some(cleanup_command) = &self.cleanup_command {
+            ensure!(
+                cleanup_command.len() <= 1 || num_commands == cleanup_command.len(),
+                "The '--cleanup' option has to be provided just once or N times, where N={num_commands} is the \
+                 number of benchmark commands (including a potential reference)."
+            );
+        }
+
         if self.command_output_policies.len() == 1 {
             self.command_output_policies =
                 vec![self.command_output_policies[0].clone(); num_commands];
@@ -492,6 +1782,40 @@ impl Options {
     }
 }
 
+#[test]
+fn test_parse_precision() {
+    assert_eq!(
+        Precision::parse("4").unwrap(),
+        Precision {
+            time: Some(4),
+            relative: Some(4),
+            memory: Some(4),
+        }
+    );
+    assert_eq!(
+        Precision::parse("4, relative=2").unwrap(),
+        Precision {
+            time: Some(4),
+            relative: Some(2),
+            memory: Some(4),
+        }
+    );
+    assert_eq!(
+        Precision::parse("memory=0").unwrap(),
+        Precision {
+            memory: Some(0),
+            ..Precision::default()
+        }
+    );
+
+    for spec in ["", "-1", "speed=2", "time=", "2.5"] {
+        assert!(matches!(
+            Precision::parse(spec).unwrap_err(),
+            OptionsError::InvalidPrecision(_)
+        ));
+    }
+}
+
 #[test]
 fn test_default_shell() {
     let shell = Shell::default();
@@ -503,6 +1827,21 @@ fn test_default_shell() {
     assert_eq!(cmd.get_program(), DEFAULT_SHELL);
 }
 
+#[test]
+fn test_shell_kind() {
+    let kind = |s| Shell::parse_from_str(s).unwrap().kind();
+
+    assert_eq!(kind("bash --norc"), ShellKind::Posix);
+    assert_eq!(kind("/usr/bin/zsh"), ShellKind::Posix);
+    assert_eq!(kind("cmd.exe"), ShellKind::Cmd);
+    assert_eq!(kind("'C:\\Windows\\System32\\CMD.EXE'"), ShellKind::Cmd);
+    assert_eq!(kind("pwsh -NoProfile"), ShellKind::PowerShell);
+    assert_eq!(kind("powershell.exe"), ShellKind::PowerShell);
+
+    assert_eq!(ShellKind::PowerShell.command_flag(), "-Command");
+    assert_eq!(ShellKind::Posix.command_flag(), "-c");
+}
+
 #[test]
 fn test_can_parse_shell_command_line_from_str() {
     let shell = Shell::parse_from_str("shell -x 'aaa bbb'").unwrap();
@@ -0,0 +1,128 @@
+//! Display of (possibly long) commands in the terminal output, see `--command-display`.
+//! The exports always contain the full commands.
+
+use std::env;
+
+use crate::command::Command;
+use crate::options::CommandDisplay;
+use crate::output::glyphs::glyphs;
+
+/// Commands are never shortened to less than this number of characters
+const MIN_COMMAND_WIDTH: usize = 20;
+
+/// Width of the terminal in columns. The `COLUMNS` environment variable takes precedence,
+/// otherwise the width is only known if the standard output is a terminal.
+pub fn terminal_width() -> Option<usize> {
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .filter(|&columns| columns > 0)
+        .or_else(terminal_columns)
+}
+
+#[cfg(unix)]
+fn terminal_columns() -> Option<usize> {
+    // SAFETY: winsize is a plain C struct, for which all zeroes is a valid value
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    // SAFETY: TIOCGWINSZ only writes to the given winsize struct
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+    (result == 0 && size.ws_col > 0).then_some(usize::from(size.ws_col))
+}
+
+#[cfg(windows)]
+fn terminal_columns() -> Option<usize> {
+    use std::convert::TryFrom;
+    use windows_sys::Win32::System::Console::{
+        GetConsoleScreenBufferInfo, GetStdHandle, CONSOLE_SCREEN_BUFFER_INFO, STD_OUTPUT_HANDLE,
+    };
+
+    // SAFETY: CONSOLE_SCREEN_BUFFER_INFO is a plain C struct, for which all zeroes is a
+    // valid value
+    let mut info: CONSOLE_SCREEN_BUFFER_INFO = unsafe { std::mem::zeroed() };
+    // SAFETY: The call only writes to the given struct, and fails if the standard output
+    // is not a console
+    let success = unsafe { GetConsoleScreenBufferInfo(GetStdHandle(STD_OUTPUT_HANDLE), &mut info) };
+    if success == 0 {
+        return None;
+    }
+    usize::try_from(info.srWindow.Right - info.srWindow.Left + 1).ok()
+}
+
+#[cfg(not(any(unix, windows)))]
+fn terminal_columns() -> Option<usize> {
+    None
+}
+
+/// Number of characters of the given text that take up space in the terminal, i.e.
+/// without ANSI escape sequences
+pub fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape_sequence = false;
+    for c in text.chars() {
+        match c {
+            '\x1b' => in_escape_sequence = true,
+            'm' if in_escape_sequence => in_escape_sequence = false,
+            _ if in_escape_sequence => {}
+            _ => width += 1,
+        }
+    }
+    width
+}
+
+/// Shorten the text to the given number of characters by replacing its middle part with
+/// an ellipsis, such that both the program and the last arguments remain visible
+fn truncate_middle(text: &str, width: usize) -> String {
+    let num_chars = text.chars().count();
+    if num_chars <= width {
+        return text.to_string();
+    }
+
+    let ellipsis = glyphs().ellipsis;
+    let available = width.saturating_sub(ellipsis.chars().count());
+    let head = available * 2 / 3;
+    let tail = available - head;
+    let mut truncated: String = text.chars().take(head).collect();
+    truncated.push_str(ellipsis);
+    truncated.extend(text.chars().skip(num_chars - tail));
+    truncated
+}
+
+/// Shorten the text such that it fits into the rest of a terminal line, of which
+/// `used_width` characters are already taken by other output
+pub fn fit_to_terminal(text: &str, used_width: usize) -> String {
+    match terminal_width() {
+        Some(width) => truncate_middle(
+            text,
+            width.saturating_sub(used_width).max(MIN_COMMAND_WIDTH),
+        ),
+        None => text.to_string(),
+    }
+}
+
+/// The name of the command as it is displayed in a line of the terminal output, of which
+/// `used_width` characters are taken by other output
+pub fn display_command(command: &Command, display: CommandDisplay, used_width: usize) -> String {
+    match display {
+        CommandDisplay::Full => command.get_name_with_unused_parameters(),
+        CommandDisplay::Truncate => {
+            fit_to_terminal(&command.get_name_with_unused_parameters(), used_width)
+        }
+        CommandDisplay::NameOnly => command.get_short_name(),
+    }
+}
+
+#[test]
+fn test_truncate_middle() {
+    assert_eq!(truncate_middle("short", 10), "short");
+    assert_eq!(truncate_middle("0123456789", 10), "0123456789");
+    assert_eq!(
+        truncate_middle("grep -r pattern /some/long/path", 16),
+        "grep -r pa…/path"
+    );
+}
+
+#[test]
+fn test_visible_width() {
+    assert_eq!(visible_width("plain"), 5);
+    assert_eq!(visible_width("\x1b[1;32m1.50\x1b[0m times"), 10);
+}
@@ -0,0 +1,14 @@
+use std::io::{self, BufRead, Write};
+
+use colored::*;
+
+/// Ask the user a yes/no question on the terminal. Anything but 'y' or 'yes' is taken as
+/// a 'no', such that a session is not started by accident.
+pub fn confirm(question: &str) -> io::Result<bool> {
+    eprint!("{} {} ", question, "[y/N]".dimmed());
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
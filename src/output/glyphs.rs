@@ -0,0 +1,87 @@
+//! The non-ASCII characters of the terminal output and the markup exports, which are
+//! replaced by plain ASCII with '--ascii'.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The characters that are used in place of the Unicode symbols
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyphs {
+    /// Between a mean and its standard deviation
+    pub plus_minus: &'static str,
+
+    /// The standard deviation
+    pub sigma: &'static str,
+
+    /// Between the bounds of a range, and in place of the omitted part of a truncated text
+    pub ellipsis: &'static str,
+
+    /// Marks a single (exact) value
+    pub identical: &'static str,
+
+    /// The frames of the spinner
+    pub tick_chars: &'static str,
+
+    /// The filled and the empty part of a progress bar
+    pub progress_chars: &'static str,
+}
+
+#[cfg(not(windows))]
+const UNICODE_TICK_CHARS: &str = "⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏ ";
+
+#[cfg(windows)]
+const UNICODE_TICK_CHARS: &str = r"+-x| ";
+
+const UNICODE: Glyphs = Glyphs {
+    plus_minus: "±",
+    sigma: "σ",
+    ellipsis: "…",
+    identical: "≡",
+    tick_chars: UNICODE_TICK_CHARS,
+    progress_chars: "█░",
+};
+
+const ASCII: Glyphs = Glyphs {
+    plus_minus: "+/-",
+    sigma: "sd",
+    ellipsis: "...",
+    identical: "=",
+    tick_chars: r"|/-\ ",
+    progress_chars: "#-",
+};
+
+static USE_ASCII: AtomicBool = AtomicBool::new(false);
+
+pub fn set_ascii(ascii: bool) {
+    USE_ASCII.store(ascii, Ordering::Relaxed);
+}
+
+/// The characters of the current output mode
+pub fn glyphs() -> &'static Glyphs {
+    if USE_ASCII.load(Ordering::Relaxed) {
+        &ASCII
+    } else {
+        &UNICODE
+    }
+}
+
+/// The name of a unit in the current output mode ('µs' is written as 'us')
+pub fn unit_name(name: &'static str) -> &'static str {
+    match name {
+        "µs" if USE_ASCII.load(Ordering::Relaxed) => "us",
+        _ => name,
+    }
+}
+
+#[test]
+fn test_ascii_glyphs() {
+    for glyph in [
+        ASCII.plus_minus,
+        ASCII.sigma,
+        ASCII.ellipsis,
+        ASCII.identical,
+        ASCII.tick_chars,
+        ASCII.progress_chars,
+    ] {
+        assert!(glyph.is_ascii());
+    }
+}
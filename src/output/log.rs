@@ -0,0 +1,63 @@
+//! Diagnostic messages on stderr, depending on the verbosity level ('-q', '-v', '-vv').
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How much diagnostic output is shown
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Only show the final summary (and exports to stdout)
+    Quiet,
+
+    /// Show the results of each benchmark, warnings and progress bars
+    #[default]
+    Normal,
+
+    /// Additionally show calibration values and scheduling decisions
+    Verbose,
+
+    /// Additionally show the parameters of every spawned process
+    Debug,
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+/// Whether messages of the given verbosity level are shown
+pub fn enabled(verbosity: Verbosity) -> bool {
+    VERBOSITY.load(Ordering::Relaxed) >= verbosity as u8
+}
+
+/// Print a message if hyperfine runs with '-v' (or '-vv')
+macro_rules! log_verbose {
+    ($($arg:tt)*) => {
+        if $crate::output::log::enabled($crate::output::log::Verbosity::Verbose) {
+            eprintln!("{} {}", colored::Colorize::dimmed("[verbose]"), format_args!($($arg)*));
+        }
+    };
+}
+
+/// Print a message if hyperfine runs with '-vv'
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        if $crate::output::log::enabled($crate::output::log::Verbosity::Debug) {
+            eprintln!("{} {}", colored::Colorize::dimmed("[debug]"), format_args!($($arg)*));
+        }
+    };
+}
+
+pub(crate) use log_debug;
+pub(crate) use log_verbose;
+
+#[test]
+fn test_verbosity_levels() {
+    set_verbosity(Verbosity::Verbose);
+    assert!(enabled(Verbosity::Quiet));
+    assert!(enabled(Verbosity::Normal));
+    assert!(enabled(Verbosity::Verbose));
+    assert!(!enabled(Verbosity::Debug));
+
+    set_verbosity(Verbosity::Normal);
+}
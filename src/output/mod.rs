@@ -1,2 +1,7 @@
+pub mod command_display;
+pub mod confirmation;
+pub mod glyphs;
+pub mod log;
+pub mod notification;
 pub mod progress_bar;
 pub mod warnings;
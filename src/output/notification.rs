@@ -0,0 +1,81 @@
+//! Notifications at the end of a session (see `--notify`), for long sessions that run in a
+//! background terminal
+
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use anyhow::{ensure, Context, Result};
+
+use crate::options::Notification;
+
+/// Ring the terminal bell and/or show a desktop notification, as requested. A desktop
+/// notification which can not be shown only leads to a warning.
+pub fn notify(notification: Notification, title: &str, message: &str) {
+    if notification.bell {
+        eprint!("\x07");
+        io::stderr().flush().ok();
+    }
+    if notification.desktop {
+        if let Err(e) = show_desktop_notification(title, message) {
+            eprintln!("Warning: Could not show a desktop notification: {e:#}");
+        }
+    }
+}
+
+/// The program that shows desktop notifications on this platform
+pub fn desktop_notification_program() -> String {
+    desktop_notification_command("", "")
+        .get_program()
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn show_desktop_notification(title: &str, message: &str) -> Result<()> {
+    let mut command = desktop_notification_command(title, message);
+    let program = command.get_program().to_string_lossy().into_owned();
+    let status = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .with_context(|| format!("Could not run '{program}'"))?;
+    ensure!(status.success(), "'{program}' failed ({status})");
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn desktop_notification_command(title: &str, message: &str) -> Command {
+    let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut command = Command::new("osascript");
+    command.arg("-e").arg(format!(
+        "display notification {} with title {}",
+        quote(message),
+        quote(title)
+    ));
+    command
+}
+
+#[cfg(windows)]
+fn desktop_notification_command(title: &str, message: &str) -> Command {
+    let quote = |text: &str| format!("'{}'", text.replace('\'', "''"));
+    let script = format!(
+        "$manager = [Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime]; \
+         $xml = $manager::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02); \
+         $text = $xml.GetElementsByTagName('text'); \
+         $text[0].AppendChild($xml.CreateTextNode({})) > $null; \
+         $text[1].AppendChild($xml.CreateTextNode({})) > $null; \
+         $manager::CreateToastNotifier('hyperfine').Show([Windows.UI.Notifications.ToastNotification]::new($xml))",
+        quote(title),
+        quote(message)
+    );
+    let mut command = Command::new("powershell");
+    command.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+    command
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn desktop_notification_command(title: &str, message: &str) -> Command {
+    let mut command = Command::new("notify-send");
+    command.arg("--app-name=hyperfine").arg(title).arg(message);
+    command
+}
@@ -1,20 +1,25 @@
-use indicatif::{ProgressBar, ProgressStyle};
-use std::time::Duration;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::cmp;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 use crate::options::OutputStyleOption;
+use crate::output::glyphs::glyphs;
+use crate::quantity::{second, Quantity, Time};
 
 #[cfg(not(windows))]
-const TICK_SETTINGS: (&str, u64) = ("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏ ", 80);
+const TICK_INTERVAL: u64 = 80;
 
 #[cfg(windows)]
-const TICK_SETTINGS: (&str, u64) = (r"+-x| ", 200);
+const TICK_INTERVAL: u64 = 200;
 
 /// Return a pre-configured progress bar
 pub fn get_progress_bar(length: u64, msg: &str, option: OutputStyleOption) -> ProgressBar {
     let progressbar_style = match option {
         OutputStyleOption::Basic | OutputStyleOption::Color => ProgressStyle::default_bar(),
         _ => ProgressStyle::default_spinner()
-            .tick_chars(TICK_SETTINGS.0)
+            .tick_chars(glyphs().tick_chars)
+            .progress_chars(glyphs().progress_chars)
             .template(" {spinner} {msg:<30} {wide_bar} ETA {eta_precise} ")
             .expect("no template error"),
     };
@@ -24,8 +29,145 @@ pub fn get_progress_bar(length: u64, msg: &str, option: OutputStyleOption) -> Pr
         _ => ProgressBar::new(length),
     };
     progress_bar.set_style(progressbar_style);
-    progress_bar.enable_steady_tick(Duration::from_millis(TICK_SETTINGS.1));
+    progress_bar.enable_steady_tick(Duration::from_millis(TICK_INTERVAL));
     progress_bar.set_message(msg.to_owned());
 
     progress_bar
 }
+
+/// Estimated durations of the benchmarks in a session
+#[derive(Default)]
+struct SessionEstimate {
+    /// Number of benchmarks that are completed
+    finished: usize,
+
+    /// Estimated total durations of the benchmarks whose initial run is completed
+    benchmark_durations: Vec<Time>,
+
+    /// Estimated remaining duration of the current benchmark, if known
+    remaining_in_current: Option<Time>,
+}
+
+/// A progress bar for the whole benchmark session, shown below the progress bar of the current
+/// benchmark. Its ETA is estimated from the initial runs of the benchmarks: the benchmarks that
+/// have not been started yet are assumed to take as long as the started ones on average.
+pub struct SessionProgress {
+    bars: MultiProgress,
+    bar: ProgressBar,
+    start: Instant,
+    num_benchmarks: usize,
+    estimate: Mutex<SessionEstimate>,
+}
+
+impl SessionProgress {
+    /// Return the progress of a session with the given number of benchmarks, or `None` if
+    /// progress bars are disabled by the output style
+    pub fn new(num_benchmarks: usize, option: OutputStyleOption) -> Option<Self> {
+        if !matches!(option, OutputStyleOption::Full | OutputStyleOption::NoColor) {
+            return None;
+        }
+
+        // The ETA is computed from the estimates, instead of the rate of progress
+        let bar = get_progress_bar(1, "", option);
+        bar.set_style(
+            ProgressStyle::default_spinner()
+                .tick_chars(glyphs().tick_chars)
+                .progress_chars(glyphs().progress_chars)
+                .template(" {spinner} {msg:<30} {wide_bar} ETA {prefix} ")
+                .expect("no template error"),
+        );
+        bar.set_prefix("--:--:--");
+
+        let bars = MultiProgress::new();
+        let bar = bars.add(bar);
+        let session = SessionProgress {
+            bars,
+            bar,
+            start: Instant::now(),
+            num_benchmarks,
+            estimate: Mutex::new(SessionEstimate::default()),
+        };
+        session.update(&SessionEstimate::default());
+        Some(session)
+    }
+
+    /// Show the given (benchmark) progress bar above the session progress bar
+    pub fn add(&self, bar: ProgressBar) -> ProgressBar {
+        self.bars.insert_before(&self.bar, bar)
+    }
+
+    /// Remove a finished progress bar that was shown via `add`
+    pub fn remove(&self, bar: &ProgressBar) {
+        bar.finish_and_clear();
+        self.bars.remove(bar);
+    }
+
+    /// Hide all progress bars while executing `f`, e.g. to print to the terminal
+    pub fn suspend<F: FnOnce() -> R, R>(&self, f: F) -> R {
+        self.bars.suspend(f)
+    }
+
+    /// Set the estimated total duration of the current benchmark (after its initial run)
+    pub fn set_benchmark_duration(&self, duration: Time) {
+        let mut estimate = self.estimate.lock().unwrap();
+        estimate.benchmark_durations.push(duration);
+        self.update(&estimate);
+    }
+
+    /// Set the estimated remaining duration of the current benchmark
+    pub fn set_remaining_in_benchmark(&self, remaining: Time) {
+        let mut estimate = self.estimate.lock().unwrap();
+        estimate.remaining_in_current = Some(remaining);
+        self.update(&estimate);
+    }
+
+    /// Mark the current benchmark as completed
+    pub fn finish_benchmark(&self) {
+        let mut estimate = self.estimate.lock().unwrap();
+        estimate.finished += 1;
+        estimate.remaining_in_current = None;
+        self.update(&estimate);
+    }
+
+    /// The bar shows the elapsed time, relative to the estimated total duration of the session
+    fn update(&self, estimate: &SessionEstimate) {
+        let current = cmp::min(estimate.finished + 1, self.num_benchmarks);
+        self.bar
+            .set_message(format!("Benchmark {current}/{}", self.num_benchmarks));
+
+        if estimate.benchmark_durations.is_empty() {
+            return;
+        }
+        let average_duration = estimate
+            .benchmark_durations
+            .iter()
+            .fold(Time::zero(), |sum, &duration| sum + duration)
+            / estimate.benchmark_durations.len() as f64;
+        let remaining_in_current = estimate.remaining_in_current.unwrap_or(average_duration);
+        let not_started = self.num_benchmarks.saturating_sub(estimate.finished + 1);
+        let remaining = if estimate.finished < self.num_benchmarks {
+            remaining_in_current + average_duration * not_started as f64
+        } else {
+            Time::zero()
+        };
+
+        let remaining = remaining.get::<second>();
+        let eta = remaining.round() as u64;
+        self.bar.set_prefix(format!(
+            "{:02}:{:02}:{:02}",
+            eta / 3600,
+            eta / 60 % 60,
+            eta % 60
+        ));
+
+        let elapsed = self.start.elapsed().as_millis() as u64;
+        self.bar.set_length(elapsed + (remaining * 1e3) as u64);
+        self.bar.set_position(elapsed);
+    }
+}
+
+impl Drop for SessionProgress {
+    fn drop(&mut self) {
+        self.remove(&self.bar);
+    }
+}
@@ -1,7 +1,9 @@
 use std::fmt;
 
+use serde::Serialize;
+
 use crate::benchmark::MIN_EXECUTION_TIME;
-use crate::quantity::{Time, Quantity};
+use crate::quantity::{Quantity, Time};
 
 pub struct OutlierWarningOptions {
     pub warmup_in_use: bool,
@@ -14,6 +16,46 @@ pub enum Warnings {
     NonZeroExitCode,
     SlowInitialRun(Time, OutlierWarningOptions),
     OutliersDetected(OutlierWarningOptions),
+    MultimodalDistribution(Vec<Time>),
+    TooSlow(Time, Time),
+    DeadlineExceeded(Time, usize),
+    TooNoisy(f64, f64, usize),
+    MemoryLimitExceeded(usize, usize),
+    TimedOut(Time, usize),
+}
+
+/// A warning as it is recorded in the JSON export
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RecordedWarning {
+    /// Identifier of the kind of warning, e.g. 'outliers_detected'
+    pub kind: String,
+
+    pub message: String,
+}
+
+impl Warnings {
+    /// Identifier of the kind of warning
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Warnings::FastExecutionTime => "fast_execution_time",
+            Warnings::NonZeroExitCode => "non_zero_exit_code",
+            Warnings::SlowInitialRun(..) => "slow_initial_run",
+            Warnings::OutliersDetected(_) => "outliers_detected",
+            Warnings::MultimodalDistribution(_) => "multimodal_distribution",
+            Warnings::TooSlow(..) => "too_slow",
+            Warnings::DeadlineExceeded(..) => "deadline_exceeded",
+            Warnings::TooNoisy(..) => "too_noisy",
+            Warnings::MemoryLimitExceeded(..) => "memory_limit_exceeded",
+            Warnings::TimedOut(..) => "timed_out",
+        }
+    }
+
+    pub fn record(&self) -> RecordedWarning {
+        RecordedWarning {
+            kind: self.kind().to_string(),
+            message: self.to_string(),
+        }
+    }
 }
 
 impl fmt::Display for Warnings {
@@ -63,6 +105,58 @@ impl fmt::Display for Warnings {
                     " It might help to use the '--warmup' or '--prepare' options."
                 }
             ),
+            Warnings::MultimodalDistribution(ref modes) => write!(
+                f,
+                "The measured times have a multimodal distribution with {count} modes (at \
+                 {locations}). This is typically caused by effects of the memory layout, such as \
+                 address space layout randomization, and means that the mean and standard \
+                 deviation do not describe the results well. Consider looking at the individual \
+                 runs in the JSON export.",
+                count = modes.len(),
+                locations = modes
+                    .iter()
+                    .map(|mode| mode.format(mode.suitable_unit()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Warnings::TooSlow(time, threshold) => write!(
+                f,
+                "The first run took {time}, which exceeds the limit of {threshold} (see \
+                 '--abort-if-slower-than'). The remaining runs were skipped, so the result is \
+                 based on a single run.",
+                time = time.format(time.suitable_unit()),
+                threshold = threshold.format(threshold.suitable_unit()),
+            ),
+            Warnings::DeadlineExceeded(deadline, num_runs) => write!(
+                f,
+                "The benchmark exceeded its deadline of {deadline} (see '--benchmark-deadline') \
+                 after {num_runs} run{s}. The remaining runs were skipped, so the result is based \
+                 on fewer runs than planned.",
+                deadline = deadline.format(deadline.suitable_unit()),
+                s = if num_runs == 1 { "" } else { "s" },
+            ),
+            Warnings::TooNoisy(relative_stddev, threshold, num_runs) => write!(
+                f,
+                "The standard deviation of the first {num_runs} runs is {:.1}% of the mean, \
+                 which exceeds the limit of {}% (see '--abort-on-noise'). The remaining runs \
+                 were skipped. Consider re-running this benchmark on a quiet system.",
+                100.0 * relative_stddev,
+                100.0 * threshold,
+            ),
+            Warnings::MemoryLimitExceeded(num_exceeded, num_runs) => write!(
+                f,
+                "{num_exceeded} of {num_runs} runs have failed, probably because they exceeded \
+                 the memory limit (see '--limit-memory'). They are included in the statistics \
+                 and marked with 'memory_limit_exceeded' in the JSON export."
+            ),
+            Warnings::TimedOut(timeout, num_runs) => write!(
+                f,
+                "A run was killed after the timeout of {timeout} (see '--auto-timeout'). The \
+                 remaining runs were skipped, so the result is based on {num_runs} run{s} and \
+                 its mean is a lower bound.",
+                timeout = timeout.format(timeout.suitable_unit()),
+                s = if num_runs == 1 { "" } else { "s" },
+            ),
         }
     }
 }
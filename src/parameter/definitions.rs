@@ -0,0 +1,143 @@
+use std::ffi::OsString;
+
+use anyhow::{bail, Result};
+use clap::ArgMatches;
+
+/// User-defined substitution variables (`--define NAME=VALUE`). Unlike parameters, they
+/// have a single value for the whole session and are replaced in all arguments (commands,
+/// setup/prepare/cleanup commands, export file names, ...) before these are interpreted.
+#[derive(Debug, Default, PartialEq)]
+pub struct Definitions {
+    variables: Vec<(String, String)>,
+}
+
+impl Definitions {
+    pub fn from_cli_arguments(matches: &ArgMatches) -> Result<Self> {
+        let mut definitions = Definitions::default();
+
+        let parameter_names: Vec<&String> = matches
+            .get_many::<String>("parameter-scan")
+            .into_iter()
+            .flatten()
+            .take(1)
+            .chain(
+                matches
+                    .get_occurrences::<String>("parameter-list")
+                    .into_iter()
+                    .flatten()
+                    .chain(
+                        matches
+                            .get_occurrences::<String>("parameter-expr")
+                            .into_iter()
+                            .flatten(),
+                    )
+                    .filter_map(|mut values| values.next()),
+            )
+            .collect();
+
+        for definition in matches.get_many::<String>("define").into_iter().flatten() {
+            let (name, value) = Self::parse(definition)?;
+
+            if parameter_names.iter().any(|parameter| **parameter == name) {
+                bail!(
+                    "The variable '{name}' defined by '--define' is also used as a parameter name"
+                );
+            }
+            if definitions.variables.iter().any(|(n, _)| *n == name) {
+                bail!("The variable '{name}' has been defined more than once");
+            }
+
+            definitions.variables.push((name, value));
+        }
+
+        Ok(definitions)
+    }
+
+    fn parse(definition: &str) -> Result<(String, String)> {
+        let Some((name, value)) = definition.split_once('=') else {
+            bail!("Invalid variable definition '{definition}', expected 'NAME=VALUE'");
+        };
+
+        if name.is_empty() || name.contains(['{', '}']) {
+            bail!("Invalid variable name '{name}' in definition '{definition}'");
+        }
+
+        Ok((name.to_string(), value.to_string()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.variables.is_empty()
+    }
+
+    /// Replace all occurrences of '{NAME}' by the value of the variable
+    pub fn substitute(&self, text: &str) -> String {
+        self.variables
+            .iter()
+            .fold(text.to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{name}}}"), value)
+            })
+    }
+
+    /// Substitute the variables in all (valid UTF-8) command line arguments
+    pub fn expand_arguments<I>(&self, args: I) -> Vec<OsString>
+    where
+        I: IntoIterator<Item = OsString>,
+    {
+        args.into_iter()
+            .map(|arg| match arg.to_str() {
+                Some(arg) => self.substitute(arg).into(),
+                None => arg,
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_definitions() {
+    use crate::cli::get_cli_arguments;
+
+    let matches = get_cli_arguments(vec![
+        "hyperfine",
+        "--define",
+        "out_dir=/tmp/bench",
+        "--define=opts=-O2 -g",
+        "--define=empty=",
+        "echo",
+    ]);
+    let definitions = Definitions::from_cli_arguments(&matches).unwrap();
+
+    assert_eq!(
+        definitions.substitute("gcc {opts} -o {out_dir}/main{empty} main.c {unknown}"),
+        "gcc -O2 -g -o /tmp/bench/main main.c {unknown}"
+    );
+
+    assert_eq!(
+        definitions.expand_arguments(vec![
+            "--export-json".into(),
+            "{out_dir}/results.json".into()
+        ]),
+        vec![
+            OsString::from("--export-json"),
+            "/tmp/bench/results.json".into()
+        ]
+    );
+}
+
+#[test]
+fn test_invalid_definitions() {
+    use crate::cli::get_cli_arguments;
+
+    let definitions = |args: &[&str]| {
+        let matches = get_cli_arguments(["hyperfine"].iter().chain(args).chain(&["echo"]).copied());
+        Definitions::from_cli_arguments(&matches)
+    };
+
+    assert!(definitions(&["--define=no_value"]).is_err());
+    assert!(definitions(&["--define==value"]).is_err());
+    assert!(definitions(&["--define={x}=value"]).is_err());
+    assert!(definitions(&["--define=x=1", "--define=x=2"]).is_err());
+    assert!(definitions(&["--define=x=1", "--parameter-list", "x", "a,b"]).is_err());
+    assert!(definitions(&["--define=x=1", "--parameter-scan", "x", "1", "2"]).is_err());
+    assert!(definitions(&["--define=x=1", "--parameter-list", "y", "a,b"]).is_ok());
+    assert!(definitions(&["--define=x=1", "--parameter-expr", "x", "k for k in 0..2"]).is_err());
+}
@@ -0,0 +1,426 @@
+//! Generation of parameter values from expressions like `2^k for k in 0..10`
+//! (`--parameter-expr`). The expression supports the operators `+`, `-`, `*`, `/` and `^`
+//! (power) as well as parentheses. The range `a..b` excludes `b`, while `a..=b` includes it.
+//! The same arithmetic is used by the formulas of derived metrics (`--derived`), which can
+//! also refer to parameters with placeholders like `{n}`.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::error::ParameterScanError;
+
+/// Maximum number of values that an expression can generate
+const MAX_VALUES: usize = 100_000;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Identifier(String),
+    Parameter(String),
+    Operator(char),
+    Range { inclusive: bool },
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Number(number) => format!("number {number}"),
+            Token::Identifier(identifier) => format!("'{identifier}'"),
+            Token::Parameter(parameter) => format!("'{{{parameter}}}'"),
+            Token::Operator(operator) => format!("'{operator}'"),
+            Token::Range { inclusive: false } => "'..'".into(),
+            Token::Range { inclusive: true } => "'..='".into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Number(f64),
+    Variable(String),
+    Parameter(String),
+    Negate(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+}
+
+/// Values of the variables and parameters of an expression
+type Lookup<'a> = &'a dyn Fn(&str) -> Option<f64>;
+
+impl Expr {
+    fn evaluate(&self, variables: Lookup, parameters: Lookup) -> Result<f64, String> {
+        Ok(match self {
+            Expr::Number(number) => *number,
+            Expr::Variable(name) => {
+                variables(name).ok_or_else(|| format!("unknown variable '{name}'"))?
+            }
+            Expr::Parameter(name) => {
+                parameters(name).ok_or_else(|| format!("unknown parameter '{name}'"))?
+            }
+            Expr::Negate(expr) => -expr.evaluate(variables, parameters)?,
+            Expr::Binary(operator, lhs, rhs) => {
+                let lhs = lhs.evaluate(variables, parameters)?;
+                let rhs = rhs.evaluate(variables, parameters)?;
+                match operator {
+                    '+' => lhs + rhs,
+                    '-' => lhs - rhs,
+                    '*' => lhs * rhs,
+                    '/' => lhs / rhs,
+                    '^' => lhs.powf(rhs),
+                    _ => unreachable!("unknown operator"),
+                }
+            }
+        })
+    }
+
+    /// Collect the names of the variables (or of the parameters) that are used
+    fn names<'a>(&'a self, parameters: bool, names: &mut Vec<&'a str>) {
+        match self {
+            Expr::Number(_) => {}
+            Expr::Variable(name) | Expr::Parameter(name) => {
+                if matches!(self, Expr::Parameter(_)) == parameters && !names.contains(&&**name) {
+                    names.push(name);
+                }
+            }
+            Expr::Negate(expr) => expr.names(parameters, names),
+            Expr::Binary(_, lhs, rhs) => {
+                lhs.names(parameters, names);
+                rhs.names(parameters, names);
+            }
+        }
+    }
+}
+
+fn invalid(reason: String) -> ParameterScanError {
+    ParameterScanError::InvalidExpression(reason)
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    fn take_while(chars: &mut Peekable<Chars>, predicate: impl Fn(char) -> bool) -> String {
+        let mut taken = String::new();
+        while let Some(&c) = chars.peek().filter(|&&c| predicate(c)) {
+            taken.push(c);
+            chars.next();
+        }
+        taken
+    }
+
+    let mut tokens = vec![];
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() {
+            let mut number = take_while(&mut chars, |c| c.is_ascii_digit());
+            // A dot is only a decimal point if it is not the start of a range
+            let mut lookahead = chars.clone();
+            lookahead.next();
+            if chars.peek() == Some(&'.') && lookahead.peek().is_some_and(|c| c.is_ascii_digit()) {
+                chars.next();
+                number.push('.');
+                number += &take_while(&mut chars, |c| c.is_ascii_digit());
+            }
+            // Scientific notation like '1e9' or '2.5E-3'
+            let mut lookahead = chars.clone();
+            if lookahead.next_if(|&c| c == 'e' || c == 'E').is_some() {
+                let sign = lookahead.next_if(|&c| c == '+' || c == '-');
+                if lookahead.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    number.push(chars.next().unwrap());
+                    if let Some(sign) = sign {
+                        number.push(sign);
+                        chars.next();
+                    }
+                    number += &take_while(&mut chars, |c| c.is_ascii_digit());
+                }
+            }
+            tokens.push(Token::Number(
+                number
+                    .parse()
+                    .map_err(|_| format!("invalid number '{number}'"))?,
+            ));
+        } else if c.is_alphabetic() || c == '_' {
+            let identifier = take_while(&mut chars, |c| c.is_alphanumeric() || c == '_');
+            tokens.push(Token::Identifier(identifier));
+        } else if c == '{' {
+            chars.next();
+            let parameter = take_while(&mut chars, |c| c != '}');
+            if chars.next() != Some('}') {
+                return Err("missing '}'".into());
+            }
+            tokens.push(Token::Parameter(parameter));
+        } else if c == '.' {
+            chars.next();
+            if chars.next() != Some('.') {
+                return Err("expected '..'".into());
+            }
+            let inclusive = chars.next_if_eq(&'=').is_some();
+            tokens.push(Token::Range { inclusive });
+        } else if "+-*/^()".contains(c) {
+            chars.next();
+            tokens.push(Token::Operator(c));
+        } else {
+            return Err(format!("unexpected character '{c}'"));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn next_operator_in(&mut self, operators: &str) -> Option<char> {
+        match self.peek() {
+            Some(&Token::Operator(c)) if operators.contains(c) => {
+                self.position += 1;
+                Some(c)
+            }
+            _ => None,
+        }
+    }
+
+    /// expr := term (('+' | '-') term)*
+    fn expression(&mut self) -> Result<Expr, String> {
+        let mut expr = self.term()?;
+        while let Some(operator) = self.next_operator_in("+-") {
+            expr = Expr::Binary(operator, Box::new(expr), Box::new(self.term()?));
+        }
+        Ok(expr)
+    }
+
+    /// term := power (('*' | '/') power)*
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut expr = self.power()?;
+        while let Some(operator) = self.next_operator_in("*/") {
+            expr = Expr::Binary(operator, Box::new(expr), Box::new(self.power()?));
+        }
+        Ok(expr)
+    }
+
+    /// power := unary ('^' power)?
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.unary()?;
+        if self.next_operator_in("^").is_some() {
+            return Ok(Expr::Binary('^', Box::new(base), Box::new(self.power()?)));
+        }
+        Ok(base)
+    }
+
+    /// unary := '-' unary | number | identifier | '(' expr ')'
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Operator('-')) => Ok(Expr::Negate(Box::new(self.unary()?))),
+            Some(Token::Number(number)) => Ok(Expr::Number(number)),
+            Some(Token::Identifier(name)) => Ok(Expr::Variable(name)),
+            Some(Token::Parameter(name)) => Ok(Expr::Parameter(name)),
+            Some(Token::Operator('(')) => {
+                let expr = self.expression()?;
+                match self.next() {
+                    Some(Token::Operator(')')) => Ok(expr),
+                    _ => Err("missing ')'".into()),
+                }
+            }
+            Some(token) => Err(format!("unexpected {}", token.describe())),
+            None => Err("unexpected end of expression".into()),
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), String> {
+        match self.next() {
+            Some(Token::Identifier(ref identifier)) if identifier == keyword => Ok(()),
+            _ => Err(format!("expected '{keyword}'")),
+        }
+    }
+}
+
+/// Evaluate a range bound, which has to be an integer that does not depend on the variable
+fn integer_bound(expr: &Expr) -> Result<i64, ParameterScanError> {
+    let value = expr.evaluate(&|_| None, &|_| None).map_err(invalid)?;
+    if value.fract() != 0.0 {
+        return Err(invalid(format!(
+            "the range bound {value} is not an integer"
+        )));
+    }
+    if value.abs() >= 1e15 {
+        return Err(ParameterScanError::TooLarge);
+    }
+    Ok(value as i64)
+}
+
+/// Format a value without a fractional part like an integer
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        format!("{value}")
+    }
+}
+
+/// Generate the parameter values for an expression of the form `EXPR for VAR in A..B`
+pub fn generate_values(input: &str) -> Result<Vec<String>, ParameterScanError> {
+    let mut parser = Parser {
+        tokens: tokenize(input).map_err(invalid)?,
+        position: 0,
+    };
+
+    let expr = parser.expression().map_err(invalid)?;
+    parser.expect_keyword("for").map_err(invalid)?;
+    let variable = match parser.next() {
+        Some(Token::Identifier(variable)) => variable,
+        _ => return Err(invalid("expected a variable name after 'for'".into())),
+    };
+    parser.expect_keyword("in").map_err(invalid)?;
+    let start = integer_bound(&parser.expression().map_err(invalid)?)?;
+    let inclusive = match parser.next() {
+        Some(Token::Range { inclusive }) => inclusive,
+        _ => return Err(invalid("expected a range like '0..10'".into())),
+    };
+    let end = integer_bound(&parser.expression().map_err(invalid)?)?;
+    if parser.peek().is_some() {
+        return Err(invalid("unexpected input after the range".into()));
+    }
+
+    let end = if inclusive { end + 1 } else { end };
+    if end <= start {
+        return Err(ParameterScanError::EmptyRange);
+    }
+    if end - start > MAX_VALUES as i64 {
+        return Err(ParameterScanError::TooLarge);
+    }
+
+    (start..end)
+        .map(|i| {
+            let value = expr
+                .evaluate(&|name| (name == variable).then_some(i as f64), &|_| None)
+                .map_err(invalid)?;
+            if !value.is_finite() {
+                return Err(invalid(format!(
+                    "the value for {variable} = {i} is not finite"
+                )));
+            }
+            Ok(format_value(value))
+        })
+        .collect()
+}
+
+/// An arithmetic expression over named variables and parameter placeholders, e.g.
+/// `2*{n}^3 / time / 1e9` (see `--derived`)
+#[derive(Debug, Clone)]
+pub struct Formula(Expr);
+
+impl Formula {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            position: 0,
+        };
+        let expr = parser.expression()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("unexpected {}", token.describe()));
+        }
+        Ok(Formula(expr))
+    }
+
+    /// The names of the variables that are used, without the parameters
+    pub fn variables(&self) -> Vec<&str> {
+        let mut names = vec![];
+        self.0.names(false, &mut names);
+        names
+    }
+
+    /// The names of the parameters that are used
+    pub fn parameters(&self) -> Vec<&str> {
+        let mut names = vec![];
+        self.0.names(true, &mut names);
+        names
+    }
+
+    pub fn evaluate(
+        &self,
+        variables: impl Fn(&str) -> Option<f64>,
+        parameters: impl Fn(&str) -> Option<f64>,
+    ) -> Result<f64, String> {
+        self.0.evaluate(&variables, &parameters)
+    }
+}
+
+#[test]
+fn test_generate_values() {
+    assert_eq!(
+        generate_values("2^k for k in 0..5").unwrap(),
+        vec!["1", "2", "4", "8", "16"]
+    );
+    assert_eq!(
+        generate_values("10 * 2^-(i) for i in 1..=3").unwrap(),
+        vec!["5", "2.5", "1.25"]
+    );
+    assert_eq!(
+        generate_values("(n + 1) * 1.5 for n in -1..2").unwrap(),
+        vec!["0", "1.5", "3"]
+    );
+    assert_eq!(
+        generate_values("2^2^k for k in 0..3").unwrap(),
+        vec!["2", "4", "16"]
+    );
+    assert_eq!(
+        generate_values("k for k in 2*3..2^3").unwrap(),
+        vec!["6", "7"]
+    );
+}
+
+#[test]
+fn test_generate_values_errors() {
+    let error = |input| generate_values(input).unwrap_err().to_string();
+
+    assert!(error("2^k").contains("expected 'for'"));
+    assert!(error("2^j for k in 0..3").contains("unknown variable 'j'"));
+    assert!(error("k for k in 0.5..3").contains("not an integer"));
+    assert!(error("(k for k in 0..3").contains("missing ')'"));
+    assert!(error("k for k in ..3").contains("unexpected '..'"));
+    assert!(error("1/k for k in 0..3").contains("not finite"));
+    assert_eq!(error("k for k in 3..3"), "Empty parameter range");
+    assert_eq!(
+        error("k for k in 0..1000000"),
+        "Parameter range is too large"
+    );
+}
+
+#[test]
+fn test_formula() {
+    let formula = Formula::parse("2*{n}^3 / time / 1e9").unwrap();
+    assert_eq!(formula.variables(), vec!["time"]);
+    assert_eq!(formula.parameters(), vec!["n"]);
+    let value = formula
+        .evaluate(
+            |name| (name == "time").then_some(0.5),
+            |name| (name == "n").then_some(1000.0),
+        )
+        .unwrap();
+    assert!((value - 4.0).abs() < 1e-12);
+
+    let formula = Formula::parse("1.5E-3 * -(a + {b})").unwrap();
+    assert_eq!(formula.evaluate(|_| Some(1.0), |_| Some(1.0)), Ok(-3e-3));
+    assert_eq!(
+        formula.evaluate(|_| Some(1.0), |_| None),
+        Err("unknown parameter 'b'".to_string())
+    );
+
+    assert!(Formula::parse("2 * {n")
+        .unwrap_err()
+        .contains("missing '}'"));
+    assert!(Formula::parse("time time")
+        .unwrap_err()
+        .contains("unexpected 'time'"));
+    assert!(Formula::parse("").unwrap_err().contains("unexpected end"));
+}
@@ -1,6 +1,8 @@
 use crate::util::number::Number;
 use std::fmt::Display;
 
+pub mod definitions;
+pub mod expression;
 pub mod range_step;
 pub mod tokenize;
 
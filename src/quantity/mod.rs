@@ -88,8 +88,14 @@ impl Quantity for Information {
     fn suitable_unit(&self) -> InformationUnit {
         if *self < Information::new::<kibibyte>(1.0) {
             InformationUnit::Byte
-        } else {
+        } else if *self < Information::new::<mebibyte>(1.0) {
             InformationUnit::KibiByte
+        } else if *self < Information::new::<gibibyte>(1.0) {
+            InformationUnit::MebiByte
+        } else if *self < Information::new::<tebibyte>(1.0) {
+            InformationUnit::GibiByte
+        } else {
+            InformationUnit::TebiByte
         }
     }
 
@@ -187,6 +193,14 @@ pub fn modified_zscores<Q: UnsafeRawValue>(values: &[Q]) -> Vec<f64> {
     crate::outlier_detection::modified_zscores(&values)
 }
 
+pub fn modes<Q: UnsafeRawValue>(values: &[Q]) -> Vec<Q> {
+    let values: Vec<_> = values.iter().map(|q| q.unsafe_raw_value()).collect();
+    crate::modality_detection::modes(&values)
+        .into_iter()
+        .map(Q::unsafe_from_raw_value)
+        .collect()
+}
+
 #[test]
 fn test_time() {
     let time = Time::new::<millisecond>(123.4);
@@ -260,6 +274,30 @@ fn test_suiteable_unit() {
     );
 }
 
+#[test]
+fn test_suitable_information_unit() {
+    assert_eq!(
+        Information::new::<byte>(1000.).suitable_unit(),
+        InformationUnit::Byte
+    );
+    assert_eq!(
+        Information::new::<kibibyte>(1.).suitable_unit(),
+        InformationUnit::KibiByte
+    );
+    assert_eq!(
+        Information::new::<kibibyte>(2048.).suitable_unit(),
+        InformationUnit::MebiByte
+    );
+    assert_eq!(
+        Information::new::<mebibyte>(4096.).suitable_unit(),
+        InformationUnit::GibiByte
+    );
+    assert_eq!(
+        Information::new::<tebibyte>(3.).suitable_unit(),
+        InformationUnit::TebiByte
+    );
+}
+
 #[test]
 fn test_format_duration_unit_with_unit() {
     let out = Time::new::<second>(1.3).format(TimeUnit::Second);
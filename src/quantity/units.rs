@@ -2,6 +2,8 @@
 
 use std::marker::PhantomData;
 
+use crate::output::glyphs;
+
 use crate::quantity::{
     byte, gibibyte, hour, kibibyte, mebibyte, microsecond, millisecond, minute, second, tebibyte,
     Information, Time,
@@ -15,7 +17,7 @@ pub trait IsUnit {
     fn preferred_precision(&self) -> usize;
 
     fn short_name(&self) -> &'static str {
-        self.dispatch().short_name()
+        glyphs::unit_name(self.dispatch().short_name())
     }
 
     fn format(&self, value: Self::Quantity, precision: usize) -> String {
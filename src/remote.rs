@@ -0,0 +1,520 @@
+//! Benchmarks on several machines (`--hosts`). hyperfine runs itself on each host via SSH,
+//! with the options and commands that define the benchmarks, and collects the JSON exports
+//! of all hosts. The results are shown in a comparison table across hosts, and can be
+//! exported as a combined JSON file that is keyed by host.
+
+use std::fs;
+use std::process::{Command, Stdio};
+
+use crate::benchmark::measurement::{Measurement, Measurements};
+use crate::cli::build_command;
+use crate::command::CommandList;
+use crate::output::glyphs::glyphs;
+use crate::quantity::{second, Quantity, Time};
+
+use anyhow::{bail, Context, Result};
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use colored::*;
+use serde_json::{Map, Value};
+
+/// Name of the hyperfine executable on the remote hosts
+const REMOTE_PROGRAM: &str = "hyperfine";
+
+/// Options which define the benchmarks and how they are measured, and which are therefore
+/// forwarded to the remote hosts. All other options (e.g. for the exports, the output or
+/// the session) only apply to the local session. The commands from the files given to
+/// '--commands-from-file' and '--script' are read locally and forwarded as arguments.
+const FORWARDED_OPTIONS: &[&str] = &[
+    "warmup",
+    "discard-first",
+    "trim",
+    "min-runs",
+    "max-runs",
+    "successful-runs",
+    "max-attempts",
+    "runs",
+    "abort-if-slower-than",
+    "benchmark-deadline",
+    "auto-timeout",
+    "abort-on-noise",
+    "jobs",
+    "setup",
+    "reference",
+    "prepare",
+    "conclude",
+    "stateful",
+    "cleanup",
+    "teardown",
+    "parameter-scan",
+    "parameter-step-size",
+    "scan-order",
+    "shuffle",
+    "parameter-list",
+    "parameter-expr",
+    "duplicates",
+    "define",
+    "shell",
+    "no-shell",
+    "no-shell-correction",
+    "pipeline-stages",
+    "source",
+    "shim",
+    "timer",
+    "ignore-failure",
+    "exit-code-ok",
+    "exit-code-skip",
+    "sample-gpu",
+    "limit-memory",
+    "measure-disk",
+    "count-syscalls",
+    "scheduling-delay",
+    "derived",
+    "noise-floor",
+    "disable-turbo",
+    "disable-smt",
+    "numa-node",
+    "pause-processes",
+    "output",
+    "capture-output",
+    "randomize-env-offset",
+    "input",
+    "command-name",
+    "git-annotate",
+    "min-benchmarking-time",
+    "debug-mode",
+];
+
+/// The SSH destinations from the file given to `--hosts`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HostList {
+    hosts: Vec<String>,
+}
+
+impl HostList {
+    pub fn from_cli_arguments(matches: &ArgMatches) -> Result<Option<Self>> {
+        let path = match matches.get_one::<String>("hosts") {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Could not read hosts from file '{path}'"))?;
+        let hosts = Self::parse(&content);
+        if hosts.hosts.is_empty() {
+            bail!("The hosts file '{path}' does not contain any hosts");
+        }
+        Ok(Some(hosts))
+    }
+
+    /// One destination (e.g. 'user@host') per line. Empty lines and lines starting with
+    /// '#' are ignored.
+    fn parse(content: &str) -> Self {
+        let hosts = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect();
+        HostList { hosts }
+    }
+
+    /// Run the benchmarks on all hosts, one after the other, and report the results
+    pub fn run(
+        &self,
+        matches: &ArgMatches,
+        command_list: &CommandList,
+        export_json: Option<&str>,
+    ) -> Result<()> {
+        let arguments = forwarded_arguments(matches, command_list);
+
+        let mut exports = Map::new();
+        let mut failed_hosts = vec![];
+        for host in &self.hosts {
+            println!("{}{}", "Host: ".bold(), host.bold());
+            match run_on_host(host, &arguments) {
+                Ok(export) => {
+                    exports.insert(host.clone(), export);
+                }
+                Err(e) => {
+                    eprintln!("{} {:#}", "Error:".red(), e);
+                    failed_hosts.push(host.as_str());
+                }
+            }
+        }
+
+        if !exports.is_empty() {
+            println!();
+            let glyphs = glyphs();
+            println!(
+                "{}",
+                format!(
+                    "Comparison across hosts (mean {} {}):",
+                    glyphs.plus_minus, glyphs.sigma
+                )
+                .bold()
+            );
+            print!("{}", comparison_table(&exports));
+        }
+
+        if let Some(filename) = export_json {
+            let mut output = Map::new();
+            output.insert("hosts".into(), Value::Object(exports));
+            let mut content = serde_json::to_vec_pretty(&output)?;
+            content.push(b'\n');
+            fs::write(filename, content)
+                .with_context(|| format!("Could not write the combined export to '{filename}'"))?;
+        }
+
+        if !failed_hosts.is_empty() {
+            bail!(
+                "The benchmarks failed on {} of {} hosts: {}",
+                failed_hosts.len(),
+                self.hosts.len(),
+                failed_hosts.join(", ")
+            );
+        }
+        Ok(())
+    }
+}
+
+/// The command line arguments for the remote hyperfine: the forwarded options that have
+/// been given on the command line, in their original order, followed by the commands and
+/// the groups they belong to, and by the commands from the command list
+fn forwarded_arguments(matches: &ArgMatches, command_list: &CommandList) -> Vec<String> {
+    let command = build_command();
+    let mut options: Vec<(usize, Vec<String>)> = vec![];
+    let mut commands: Vec<(usize, Vec<String>)> = vec![];
+
+    // If the command list has names, all commands are named (like in
+    // `Commands::from_command_strings`), as the names need to line up with the commands
+    let command_names: Option<Vec<String>> = command_list.has_names().then(|| {
+        let given_names: Vec<&String> = matches
+            .get_many::<String>("command-name")
+            .into_iter()
+            .flatten()
+            .collect();
+        matches
+            .get_many::<String>("command")
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .map(|(i, command)| given_names.get(i).copied().unwrap_or(command))
+            .map(String::as_str)
+            .chain(
+                command_list
+                    .entries()
+                    .map(|(name, command)| name.unwrap_or(command)),
+            )
+            .map(|name| format!("--command-name={name}"))
+            .collect()
+    });
+
+    for arg in command.get_arguments() {
+        let id = arg.get_id().as_str();
+        let is_forwarded =
+            FORWARDED_OPTIONS.contains(&id) && !(id == "command-name" && command_names.is_some());
+        if !(is_forwarded || id == "command" || id == "group")
+            || matches.value_source(id) != Some(ValueSource::CommandLine)
+        {
+            continue;
+        }
+
+        let name = match (arg.get_long(), arg.get_short()) {
+            (Some(long), _) => format!("--{long}"),
+            (None, Some(short)) => format!("-{short}"),
+            (None, None) => String::new(),
+        };
+        let mut indices = matches.indices_of(id).into_iter().flatten();
+        if !arg.get_action().takes_values() {
+            options.extend(indices.map(|index| (index, vec![name.clone()])));
+            continue;
+        }
+
+        for occurrence in matches.get_raw_occurrences(id).into_iter().flatten() {
+            let values: Vec<(usize, String)> = occurrence
+                .map(|value| {
+                    let index = indices.next().unwrap_or_default();
+                    (index, value.to_string_lossy().into_owned())
+                })
+                .collect();
+            let index = values.first().map_or(0, |&(index, _)| index);
+            let values = values.into_iter();
+            match id {
+                "command" => commands.extend(values.map(|(index, value)| (index, vec![value]))),
+                // Groups are started with '--group NAME --' after the '--' separator
+                "group" => commands.extend(
+                    values.map(|(index, name)| (index, vec!["--group".into(), name, "--".into()])),
+                ),
+                _ => {
+                    let values: Vec<String> = values.map(|(_, value)| value).collect();
+                    match values.as_slice() {
+                        [value] => options.push((index, vec![format!("{name}={value}")])),
+                        _ => options
+                            .push((index, std::iter::once(name.clone()).chain(values).collect())),
+                    }
+                }
+            }
+        }
+    }
+    options.sort_by_key(|&(index, _)| index);
+    commands.sort_by_key(|&(index, _)| index);
+
+    // The options for the export come first, and all commands follow a '--', such that
+    // they are not taken as options
+    ["--style=none", "--export-json=-"]
+        .iter()
+        .map(|argument| argument.to_string())
+        .chain(options.into_iter().flat_map(|(_, arguments)| arguments))
+        .chain(command_names.into_iter().flatten())
+        .chain(std::iter::once("--".to_string()))
+        .chain(commands.into_iter().flat_map(|(_, arguments)| arguments))
+        .chain(
+            command_list
+                .entries()
+                .map(|(_, command)| command.to_string()),
+        )
+        .collect()
+}
+
+/// Run hyperfine on the given host, and return its JSON export
+fn run_on_host(host: &str, arguments: &[String]) -> Result<Value> {
+    let remote_command = shell_words::join(
+        std::iter::once(REMOTE_PROGRAM).chain(arguments.iter().map(String::as_str)),
+    );
+    let output = Command::new("ssh")
+        .args(["-o", "BatchMode=yes", host, &remote_command])
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()
+        .context("Could not run 'ssh'")?;
+
+    if !output.status.success() {
+        bail!("The benchmarks on host '{host}' failed ({})", output.status);
+    }
+
+    // Skip anything that is printed before the export (e.g. empty lines)
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let start = stdout.find("{\n").unwrap_or_default();
+    serde_json::from_str(&stdout[start..])
+        .with_context(|| format!("Could not read the results of host '{host}'"))
+}
+
+/// The mean and standard deviation of the wall clock time of a result in a JSON export
+fn summarize(result: &Value) -> Option<(Time, Option<Time>)> {
+    let measurements = result
+        .get("measurements")?
+        .as_array()?
+        .iter()
+        .map(|measurement| {
+            // Times are exported as '{ "value": …, "unit": "second" }'
+            let time_wall_clock = measurement.get("time_wall_clock")?.get("value")?.as_f64()?;
+            Some(Measurement {
+                time_wall_clock: Time::new::<second>(time_wall_clock),
+                discarded: measurement
+                    .get("discarded")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+                failed_attempt: measurement
+                    .get("failed_attempt")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+                ..Default::default()
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut measurements = Measurements::new(measurements);
+    measurements.trim = result.get("trim").and_then(Value::as_f64);
+    if measurements.is_empty() {
+        return None;
+    }
+    Some((measurements.time_wall_clock_mean(), measurements.stddev()))
+}
+
+/// A table with the mean wall clock time of each command (rows) on each host (columns)
+fn comparison_table(exports: &Map<String, Value>) -> String {
+    let mut commands: Vec<&str> = vec![];
+    for export in exports.values() {
+        let results = export.get("results").and_then(Value::as_array);
+        for result in results.into_iter().flatten() {
+            if let Some(command) = result.get("command").and_then(Value::as_str) {
+                if !commands.contains(&command) {
+                    commands.push(command);
+                }
+            }
+        }
+    }
+
+    let mut rows = vec![std::iter::once("Command".to_string())
+        .chain(exports.keys().cloned())
+        .collect::<Vec<_>>()];
+    for command in &commands {
+        let mut row = vec![command.to_string()];
+        for export in exports.values() {
+            let summary = export
+                .get("results")
+                .and_then(Value::as_array)
+                .and_then(|results| {
+                    results.iter().find(|result| {
+                        result.get("command").and_then(Value::as_str) == Some(command)
+                    })
+                })
+                .and_then(summarize);
+            row.push(match summary {
+                Some((mean, stddev)) => {
+                    let unit = mean.suitable_unit();
+                    match stddev {
+                        Some(stddev) => format!(
+                            "{} {} {}",
+                            mean.format(unit),
+                            glyphs().plus_minus,
+                            stddev.format(unit)
+                        ),
+                        None => mean.format(unit),
+                    }
+                }
+                None => "-".to_string(),
+            });
+        }
+        rows.push(row);
+    }
+
+    let num_columns = rows[0].len();
+    let widths: Vec<usize> = (0..num_columns)
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut table = String::new();
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{cell:<width$}"))
+            .collect();
+        table.push_str(&format!("  {}\n", cells.join("   ").trim_end()));
+    }
+    table
+}
+
+#[test]
+fn test_parse_host_list() {
+    let hosts = HostList::parse("# build machines\nalice@host-a\n\n  host-b  \n");
+    assert_eq!(hosts.hosts, vec!["alice@host-a", "host-b"]);
+}
+
+#[test]
+fn test_forwarded_arguments() {
+    use crate::cli::get_cli_arguments;
+
+    let matches = get_cli_arguments(vec![
+        "hyperfine",
+        "--hosts",
+        "hosts.txt",
+        "--runs=5",
+        "--export-json=all.json",
+        "--export-template",
+        "template.txt",
+        "out.txt",
+        "--lock=session.lock",
+        "--notify",
+        "--orgmode-alignment",
+        "-N",
+        "-w",
+        "2",
+        "-L",
+        "n",
+        "1,2",
+        "--group",
+        "io",
+        "cat {n}",
+        "--",
+        "--export-csv",
+    ]);
+
+    assert_eq!(
+        forwarded_arguments(&matches, &CommandList::default()),
+        vec![
+            "--style=none",
+            "--export-json=-",
+            "--runs=5",
+            "-N",
+            "--warmup=2",
+            "--parameter-list",
+            "n",
+            "1,2",
+            "--",
+            "--group",
+            "io",
+            "--",
+            "cat {n}",
+            "--export-csv",
+        ]
+    );
+}
+
+#[test]
+fn test_forwarded_arguments_with_command_list() {
+    use crate::cli::get_cli_arguments;
+
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("commands.txt");
+    fs::write(&path, "fast\tsleep 0.1\nsleep 1\n").unwrap();
+    let path = path.to_str().unwrap();
+
+    let matches = get_cli_arguments(vec![
+        "hyperfine",
+        "--hosts",
+        "hosts.txt",
+        "--commands-from-file",
+        path,
+        "--command-name=first",
+        "echo first",
+    ]);
+    let command_list = CommandList::from_cli_arguments(&matches).unwrap();
+
+    assert_eq!(
+        forwarded_arguments(&matches, &command_list),
+        vec![
+            "--style=none",
+            "--export-json=-",
+            "--command-name=first",
+            "--command-name=fast",
+            "--command-name=sleep 1",
+            "--",
+            "echo first",
+            "sleep 0.1",
+            "sleep 1",
+        ]
+    );
+}
+
+#[test]
+fn test_comparison_table() {
+    use serde_json::json;
+
+    let export = |times: &[f64]| {
+        json!({
+            "results": [{
+                "command": "sleep 1",
+                "measurements": times
+                    .iter()
+                    .map(|t| json!({ "time_wall_clock": { "value": t, "unit": "second" } }))
+                    .collect::<Vec<_>>(),
+            }]
+        })
+    };
+    let mut exports = Map::new();
+    exports.insert("host-a".into(), export(&[1.0, 1.2]));
+    exports.insert("host-b".into(), export(&[2.0]));
+
+    assert_eq!(
+        comparison_table(&exports),
+        "  Command   host-a              host-b\n  sleep 1   1.100 s ± 0.141 s   2.000 s\n"
+    );
+}
@@ -0,0 +1,90 @@
+//! The record that the measurement shim ('hyperfine-shim', see '--shim') prints after it has
+//! timed a command where the command runs, e.g. inside of a container or on a remote host.
+//! This file is also compiled into the shim itself, so it only uses the standard library.
+
+/// Marks the line of the record in the error output of the shim
+pub const RECORD_PREFIX: &str = "hyperfine-shim:";
+
+/// The measurement of a single run of the timed command
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShimRecord {
+    /// Wall clock time in seconds
+    pub wall_clock: f64,
+
+    /// User time in seconds
+    pub user: f64,
+
+    /// System time in seconds
+    pub system: f64,
+
+    /// Peak memory usage in bytes
+    pub peak_memory: u64,
+}
+
+impl ShimRecord {
+    /// The line of the record, e.g.
+    /// 'hyperfine-shim: wall=0.104 user=0.002 system=0.001 memory=2097152'
+    pub fn format(&self) -> String {
+        format!(
+            "{RECORD_PREFIX} wall={:.9} user={:.9} system={:.9} memory={}",
+            self.wall_clock, self.user, self.system, self.peak_memory
+        )
+    }
+
+    /// Find the last record in the error output of the shim. Returns the record and the
+    /// output that precedes it, i.e. the error output of the timed command.
+    pub fn extract(output: &str) -> Option<(ShimRecord, &str)> {
+        let start = output
+            .match_indices(RECORD_PREFIX)
+            .map(|(start, _)| start)
+            .filter(|&start| start == 0 || output[..start].ends_with('\n'))
+            .last()?;
+        let line = output[start + RECORD_PREFIX.len()..].lines().next()?;
+
+        let (mut wall_clock, mut user, mut system, mut peak_memory) = (None, None, None, None);
+        for field in line.split_whitespace() {
+            let (key, value) = field.split_once('=')?;
+            // Unknown fields are ignored, such that the record can be extended
+            match key {
+                "wall" => wall_clock = value.parse().ok(),
+                "user" => user = value.parse().ok(),
+                "system" => system = value.parse().ok(),
+                "memory" => peak_memory = value.parse().ok(),
+                _ => {}
+            }
+        }
+
+        let record = ShimRecord {
+            wall_clock: wall_clock?,
+            user: user.unwrap_or(0.0),
+            system: system.unwrap_or(0.0),
+            peak_memory: peak_memory.unwrap_or(0),
+        };
+        Some((record, &output[..start]))
+    }
+}
+
+#[test]
+fn test_shim_record() {
+    let record = ShimRecord {
+        wall_clock: 0.25,
+        user: 0.125,
+        system: 0.0625,
+        peak_memory: 4096,
+    };
+    let output = format!(
+        "warning: hyperfine-shim: is mentioned\n{}\n",
+        record.format()
+    );
+    assert_eq!(
+        ShimRecord::extract(&output),
+        Some((record, "warning: hyperfine-shim: is mentioned\n"))
+    );
+
+    let (record, _) = ShimRecord::extract("hyperfine-shim: wall=1.5 future=1\n").unwrap();
+    assert_eq!(record.wall_clock, 1.5);
+    assert_eq!(record.peak_memory, 0);
+
+    assert_eq!(ShimRecord::extract("error: not found\n"), None);
+    assert_eq!(ShimRecord::extract("hyperfine-shim: user=1.0\n"), None);
+}
@@ -9,25 +9,120 @@ mod unix_timer;
 #[cfg(target_os = "linux")]
 use nix::fcntl::{splice, SpliceFFlags};
 #[cfg(target_os = "linux")]
-use std::fs::File;
-#[cfg(target_os = "linux")]
 use std::os::fd::AsFd;
 
 #[cfg(target_os = "windows")]
 use windows_sys::Win32::System::Threading::CREATE_SUSPENDED;
 
-use crate::benchmark::measurement::Measurement;
-use wall_clock_timer::WallClockTimer;
+#[cfg(not(windows))]
+use crate::benchmark::measurement::StageUsage;
+use crate::benchmark::measurement::{CapturedOutput, Measurement};
+use crate::options::TimerKind;
+use crate::quantity::{byte, second, Information, Time};
+use crate::shim::RECORD_PREFIX;
+#[cfg(target_arch = "x86_64")]
+pub use wall_clock_timer::has_invariant_tsc;
+pub use wall_clock_timer::Timer;
+use wall_clock_timer::{MonotonicTimer, TaskClockTimer};
 
-use std::io::Read;
-use std::process::{ChildStdout, Command};
+use std::cmp;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read};
+use std::process::Stdio;
+use std::process::{self, ChildStdout, Command};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
 use anyhow::Result;
 
-/// Discard the output of a child process.
-fn discard(output: ChildStdout) {
+/// CPU times and memory usage of a benchmarked process
+#[derive(Debug, Default, Copy, Clone)]
+pub struct ResourceUsage {
+    /// Lifetime of the process, if it can be measured more precisely than the time
+    /// between spawning the process and observing its exit (Windows only)
+    pub time_wall_clock: Option<Time>,
+
+    /// Total amount of time spent executing in user mode
+    pub time_user: Time,
+
+    /// Total amount of time spent executing in kernel mode
+    pub time_system: Time,
+
+    /// Maximum amount of memory used by the process
+    pub peak_memory_usage: Information,
+
+    /// Maximum physical footprint of the process, if available (macOS only)
+    pub peak_memory_footprint: Option<Information>,
+
+    /// Maximum private (committed) memory of the process (Windows only)
+    pub peak_private_bytes: Option<Information>,
+
+    /// Maximum working set of the process (Windows only)
+    pub peak_working_set: Option<Information>,
+
+    /// Number of page faults that were serviced without any I/O
+    pub minor_page_faults: u64,
+
+    /// Number of page faults that required I/O
+    pub major_page_faults: u64,
+
+    /// Number of processes that have been created by the command, including itself
+    /// (Windows only, where it is counted by the job object of the process)
+    pub process_count: Option<u64>,
+
+    /// Time that the process has spent on a CPU, according to the scheduler (Linux only,
+    /// see `--scheduling-delay`)
+    pub time_on_cpu: Option<Time>,
+
+    /// Time that the process has spent waiting in the run queue of the scheduler
+    pub run_queue_delay: Option<Time>,
+
+    /// Number of times that the process has been moved to another CPU (Linux only)
+    pub cpu_migrations: Option<u64>,
+
+    /// NUMA node of the CPU on which the process has run last (Linux only)
+    pub numa_node: Option<u32>,
+}
+
+/// The clock of the given kind. Timers which are not supported on this platform are
+/// rejected when parsing the options.
+pub fn timer(kind: TimerKind) -> &'static dyn Timer {
+    match kind {
+        TimerKind::Monotonic => &MonotonicTimer,
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        TimerKind::MonotonicRaw => &wall_clock_timer::MonotonicRawTimer,
+        TimerKind::TaskClock => &TaskClockTimer,
+        #[cfg(target_arch = "x86_64")]
+        TimerKind::Tsc => &wall_clock_timer::TscTimer,
+        #[allow(unreachable_patterns)]
+        _ => unreachable!("The timer {:?} is not supported on this platform", kind),
+    }
+}
+
+/// Read the first `head_size` bytes of the output of a child process, and discard the rest.
+/// Returns the head and the total number of bytes that were written.
+fn discard(mut output: ChildStdout, head_size: usize) -> (Vec<u8>, u64) {
     const CHUNK_SIZE: usize = 64 << 10;
 
+    let mut head = Vec::new();
+    let mut buf = [0; CHUNK_SIZE];
+    while head.len() < head_size {
+        let remaining = cmp::min(head_size - head.len(), CHUNK_SIZE);
+        match output.read(&mut buf[..remaining]) {
+            Ok(0) | Err(_) => {
+                let size = head.len() as u64;
+                return (head, size);
+            }
+            Ok(bytes) => head.extend_from_slice(&buf[..bytes]),
+        }
+    }
+
+    let mut size = head.len() as u64;
+
     #[cfg(target_os = "linux")]
     {
         if let Ok(file) = File::create("/dev/null") {
@@ -42,23 +137,154 @@ fn discard(output: ChildStdout) {
                 if bytes == 0 {
                     break;
                 }
+                size += bytes as u64;
             }
         }
     }
 
-    let mut output = output;
-    let mut buf = [0; CHUNK_SIZE];
     while let Ok(bytes) = output.read(&mut buf) {
         if bytes == 0 {
             break;
         }
+        size += bytes as u64;
     }
+    (head, size)
 }
 
-/// Execute the given command and return a timing summary
-pub fn execute_and_measure(mut command: Command) -> Result<Measurement> {
+/// Maximum number of bytes of stderr output that are kept for failing runs
+const STDERR_TAIL_SIZE: usize = 4096;
+
+/// The error output of a benchmarked command, which is written to an unlinked temporary
+/// file. Unlike a pipe, it needs no reader while the command runs, and it can be read once
+/// the command has exited, even if background processes of the command still hold it.
+pub struct ErrorOutput {
+    file: File,
+}
+
+impl ErrorOutput {
+    /// Create the file, and return it along with the error output for the child process
+    pub fn create() -> io::Result<(ErrorOutput, Stdio)> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let path = env::temp_dir().join(format!(
+            "hyperfine-stderr-{}-{}",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut options = OpenOptions::new();
+        options.read(true).write(true).create_new(true);
+        #[cfg(windows)]
+        {
+            use std::os::windows::fs::OpenOptionsExt;
+
+            // An open file can not be removed on Windows (FILE_FLAG_DELETE_ON_CLOSE)
+            options.custom_flags(0x0400_0000);
+        }
+        let file = options.open(&path)?;
+        #[cfg(not(windows))]
+        std::fs::remove_file(&path)?;
+
+        let stderr = file.try_clone()?.into();
+        Ok((ErrorOutput { file }, stderr))
+    }
+
+    /// Returns the first `head_size` bytes, the last part and the total number of bytes of
+    /// the error output that has been written so far
+    fn read(self, head_size: usize) -> (Vec<u8>, String, u64) {
+        let size = self.file.metadata().map_or(0, |metadata| metadata.len());
+        let head = read_at(&self.file, 0, cmp::min(head_size as u64, size));
+        let tail_start = size.saturating_sub(STDERR_TAIL_SIZE as u64);
+        let tail = read_at(&self.file, tail_start, size - tail_start);
+        (head, String::from_utf8_lossy(&tail).into_owned(), size)
+    }
+}
+
+/// Read up to `len` bytes from the given offset of the file. On Unix, this does not move the
+/// file position that is shared with the processes that still write to the file.
+fn read_at(file: &File, offset: u64, len: u64) -> Vec<u8> {
+    let mut buf = vec![0; len as usize];
+    let mut bytes_read = 0;
+    while bytes_read < buf.len() {
+        let position = offset + bytes_read as u64;
+        #[cfg(unix)]
+        let result = std::os::unix::fs::FileExt::read_at(file, &mut buf[bytes_read..], position);
+        #[cfg(windows)]
+        let result =
+            std::os::windows::fs::FileExt::seek_read(file, &mut buf[bytes_read..], position);
+        match result {
+            Ok(0) | Err(_) => break,
+            Ok(bytes) => bytes_read += bytes,
+        }
+    }
+    buf.truncate(bytes_read);
+    buf
+}
+
+/// Kills a benchmarked process (on Windows, including its child processes) if it runs for
+/// longer than its timeout (see `--auto-timeout`)
+struct Watchdog {
+    finished: mpsc::Sender<()>,
+    thread: thread::JoinHandle<bool>,
+}
+
+impl Watchdog {
+    fn start(timeout: Time, kill: impl FnOnce() + Send + 'static) -> Self {
+        let (finished, receiver) = mpsc::channel();
+        let timeout = Duration::from_secs_f64(timeout.get::<second>().max(0.0));
+        let thread = thread::spawn(move || {
+            let timed_out = receiver.recv_timeout(timeout) == Err(RecvTimeoutError::Timeout);
+            if timed_out {
+                kill();
+            }
+            timed_out
+        });
+        Watchdog { finished, thread }
+    }
+
+    /// Stop watching the process, which has exited, and return whether it has been killed
+    fn finish(self) -> bool {
+        drop(self.finished);
+        self.thread.join().unwrap_or(false)
+    }
+}
+
+/// A function that kills the processes with the given IDs
+#[cfg(not(windows))]
+fn kill_processes(pids: Vec<u32>) -> impl FnOnce() + Send + 'static {
+    move || {
+        for pid in pids {
+            // SAFETY: Sending a signal has no memory safety implications. The processes
+            // have not been reaped yet (unless they have just exited), so their IDs have
+            // not been reused.
+            unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) };
+        }
+    }
+}
+
+/// Execute the given command and return a timing summary, with the wall clock time measured
+/// by the given timer. The command writes its error output to `error_output`, if given. If
+/// `capture_size` is given, the first bytes of the (piped) stdout and of the error output
+/// are recorded in the measurement. The memory of the process is limited to
+/// `memory_limit`, and it is killed after `timeout`, if given. If `measure_scheduling` is
+/// set, the scheduler statistics of the process are recorded as well (Linux only).
+pub fn execute_and_measure(
+    mut command: Command,
+    error_output: Option<ErrorOutput>,
+    capture_size: Option<usize>,
+    memory_limit: Option<Information>,
+    timeout: Option<Time>,
+    measure_scheduling: bool,
+    timer: &dyn Timer,
+) -> Result<Measurement> {
     #[cfg(not(windows))]
-    let cpu_timer = self::unix_timer::CPUTimer::start();
+    if let Some(limit) = memory_limit {
+        self::unix_timer::limit_memory(&mut command, limit);
+    }
+
+    #[cfg(not(windows))]
+    let cpu_timer = self::unix_timer::CPUTimer::start(measure_scheduling);
+    #[cfg(windows)]
+    let _ = measure_scheduling;
 
     #[cfg(windows)]
     {
@@ -68,28 +294,281 @@ pub fn execute_and_measure(mut command: Command) -> Result<Measurement> {
         command.creation_flags(CREATE_SUSPENDED);
     }
 
-    let wallclock_timer = WallClockTimer::start();
+    let start_time = SystemTime::now();
+    let timer_start = timer.now();
+    let spawn_start = Instant::now();
     let mut child = command.spawn()?;
+    let spawn_latency = Time::new::<second>(spawn_start.elapsed().as_secs_f64());
 
     #[cfg(windows)]
     let cpu_timer = {
         // SAFETY: We created a suspended process
-        unsafe { self::windows_timer::CPUTimer::start_suspended_process(&child) }
+        unsafe { self::windows_timer::CPUTimer::start_suspended_process(&child, memory_limit) }
     };
 
-    if let Some(output) = child.stdout.take() {
-        // Handle CommandOutputPolicy::Pipe
-        discard(output);
+    #[cfg(not(windows))]
+    let watchdog =
+        timeout.map(|timeout| Watchdog::start(timeout, kill_processes(vec![child.id()])));
+    #[cfg(windows)]
+    let watchdog = timeout.map(|timeout| Watchdog::start(timeout, cpu_timer.terminator()));
+
+    let head_size = capture_size.unwrap_or(0);
+    // Handle CommandOutputPolicy::Pipe
+    let stdout = child.stdout.take().map(|stdout| discard(stdout, head_size));
+
+    let (usage, exit_status) = cpu_timer.stop(child)?;
+    let time_wall_clock = timer.elapsed(timer_start, &usage);
+
+    let timed_out = watchdog.is_some_and(Watchdog::finish);
+
+    let stderr = error_output.map(|error_output| error_output.read(head_size));
+    let bytes = |size: u64| Information::new::<byte>(size as f64);
+    let text = |head: &[u8]| String::from_utf8_lossy(head).into_owned();
+
+    Ok(Measurement {
+        start_time: Some(start_time),
+        time_wall_clock,
+        spawn_latency: Some(spawn_latency),
+        time_user: usage.time_user,
+        time_system: usage.time_system,
+        time_on_cpu: usage.time_on_cpu,
+        run_queue_delay: usage.run_queue_delay,
+        cpu_migrations: usage.cpu_migrations,
+        numa_node: usage.numa_node,
+        peak_memory_usage: usage.peak_memory_usage,
+        peak_memory_footprint: usage.peak_memory_footprint,
+        peak_private_bytes: usage.peak_private_bytes,
+        peak_working_set: usage.peak_working_set,
+        minor_page_faults: usage.minor_page_faults,
+        major_page_faults: usage.major_page_faults,
+        process_count: usage.process_count,
+        memory_limit_exceeded: false,
+        timed_out,
+        discarded: false,
+        failed_attempt: false,
+        exit_status,
+        // The record of the measurement shim (see '--shim') is needed for successful runs
+        stderr: stderr
+            .as_ref()
+            .filter(|(_, tail, _)| !exit_status.success() || tail.contains(RECORD_PREFIX))
+            .map(|(_, tail, _)| tail.clone()),
+        stdout_size: stdout.as_ref().map(|&(_, size)| bytes(size)),
+        stderr_size: stderr.as_ref().map(|&(_, _, size)| bytes(size)),
+        disk_usage_delta: None,
+        syscall_count: None,
+        env_offset: None,
+        pipeline_stages: vec![],
+        captured_output: capture_size.map(|_| CapturedOutput {
+            stdout: stdout.as_ref().map(|(head, _)| text(head)),
+            stderr: stderr.as_ref().map(|(head, _, _)| text(head)),
+        }),
+        auxiliary: BTreeMap::new(),
+        derived: BTreeMap::new(),
+    })
+}
+
+/// Execute the stages of a pipeline, with the output of each stage connected to the input
+/// of the next one, and return a timing summary with the CPU times of each stage. The
+/// input of the first and the output of the last stage are taken from the given commands,
+/// and the last stage writes its error output to `error_output`, if given.
+/// Like in a shell (without 'pipefail'), the exit status is the one of the last stage.
+#[cfg(not(windows))]
+pub fn execute_pipeline_and_measure(
+    mut stages: Vec<Command>,
+    error_output: Option<ErrorOutput>,
+    memory_limit: Option<Information>,
+    timeout: Option<Time>,
+    timer: &dyn Timer,
+) -> Result<Measurement> {
+    let num_stages = stages.len();
+    if let Some(limit) = memory_limit {
+        for stage in &mut stages {
+            self::unix_timer::limit_memory(stage, limit);
+        }
+    }
+
+    let start_time = SystemTime::now();
+    let timer_start = timer.now();
+    let mut children = Vec::with_capacity(num_stages);
+    let mut previous_stdout = None;
+    for (i, stage) in stages.iter_mut().enumerate() {
+        if let Some(stdout) = previous_stdout.take() {
+            stage.stdin(Stdio::from(stdout));
+        }
+        let is_last = i + 1 == num_stages;
+        if !is_last {
+            stage.stdout(Stdio::piped());
+        }
+
+        match stage.spawn() {
+            Ok(mut child) => {
+                if !is_last {
+                    previous_stdout = child.stdout.take();
+                }
+                children.push(child);
+            }
+            Err(e) => {
+                for mut child in children {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                }
+                return Err(e.into());
+            }
+        }
     }
 
-    let (time_user, time_system, peak_memory_usage, exit_status) = cpu_timer.stop(child)?;
-    let time_wall_clock = wallclock_timer.stop();
+    let watchdog = timeout.map(|timeout| {
+        Watchdog::start(
+            timeout,
+            kill_processes(children.iter().map(|child| child.id()).collect()),
+        )
+    });
+
+    let mut last = children.pop().expect("a pipeline has at least one stage");
+    let stdout = last.stdout.take().map(|stdout| discard(stdout, 0));
+
+    let mut usage = ResourceUsage::default();
+    let mut pipeline_stages = Vec::with_capacity(num_stages);
+    let mut exit_status = None;
+    for child in children.into_iter().chain(Some(last)) {
+        let (stage_usage, status) = self::unix_timer::CPUTimer::start(false).stop(child)?;
+        usage.time_user += stage_usage.time_user;
+        usage.time_system += stage_usage.time_system;
+        usage.peak_memory_usage += stage_usage.peak_memory_usage;
+        usage.minor_page_faults += stage_usage.minor_page_faults;
+        usage.major_page_faults += stage_usage.major_page_faults;
+        pipeline_stages.push(StageUsage {
+            time_user: stage_usage.time_user,
+            time_system: stage_usage.time_system,
+        });
+        exit_status = Some(status);
+    }
+    let exit_status = exit_status.expect("a pipeline has at least one stage");
+    let time_wall_clock = timer.elapsed(timer_start, &usage);
+
+    let timed_out = watchdog.is_some_and(Watchdog::finish);
+
+    let stderr = error_output.map(|error_output| error_output.read(0));
+    let bytes = |size: u64| Information::new::<byte>(size as f64);
 
     Ok(Measurement {
+        start_time: Some(start_time),
         time_wall_clock,
-        time_user,
-        time_system,
-        peak_memory_usage,
+        time_user: usage.time_user,
+        time_system: usage.time_system,
+        peak_memory_usage: usage.peak_memory_usage,
+        minor_page_faults: usage.minor_page_faults,
+        major_page_faults: usage.major_page_faults,
+        process_count: usage.process_count,
+        timed_out,
         exit_status,
+        stderr: stderr
+            .as_ref()
+            .filter(|_| !exit_status.success())
+            .map(|(_, tail, _)| tail.clone()),
+        stdout_size: stdout.as_ref().map(|&(_, size)| bytes(size)),
+        stderr_size: stderr.as_ref().map(|&(_, _, size)| bytes(size)),
+        pipeline_stages,
+        ..Default::default()
     })
 }
+
+#[cfg(unix)]
+#[test]
+fn test_execute_and_measure_with_custom_timer() {
+    use crate::quantity::{second, Quantity};
+
+    struct FixedTimer;
+
+    impl Timer for FixedTimer {
+        fn now(&self) -> Time {
+            Time::zero()
+        }
+
+        fn elapsed(&self, _start: Time, _usage: &ResourceUsage) -> Time {
+            Time::new::<second>(1.5)
+        }
+    }
+
+    let measurement = execute_and_measure(
+        Command::new("true"),
+        None,
+        None,
+        None,
+        None,
+        false,
+        &FixedTimer,
+    )
+    .unwrap();
+    assert!(measurement.exit_status.success());
+    assert_eq!(measurement.time_wall_clock, Time::new::<second>(1.5));
+    assert!(measurement.spawn_latency.is_some());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_execute_and_measure_reads_the_error_output() {
+    let (error_output, stderr) = ErrorOutput::create().unwrap();
+    let mut command = Command::new("sh");
+    command
+        .args([
+            "-c",
+            "head -c 10000 /dev/zero | tr '\\0' x >&2; echo end >&2; exit 1",
+        ])
+        .stderr(stderr);
+    let measurement = execute_and_measure(
+        command,
+        Some(error_output),
+        Some(3),
+        None,
+        None,
+        false,
+        timer(TimerKind::Monotonic),
+    )
+    .unwrap();
+
+    let stderr = measurement.stderr.unwrap();
+    assert_eq!(stderr.len(), STDERR_TAIL_SIZE);
+    assert!(stderr.ends_with("xxxend\n"));
+    assert_eq!(
+        measurement.stderr_size,
+        Some(Information::new::<byte>(10004.0))
+    );
+    assert_eq!(
+        measurement.captured_output.unwrap().stderr.as_deref(),
+        Some("xxx")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_execute_and_measure_with_timeout() {
+    let mut command = Command::new("sleep");
+    command.arg("10");
+    let start = Instant::now();
+    let measurement = execute_and_measure(
+        command,
+        None,
+        None,
+        None,
+        Some(Time::new::<second>(0.1)),
+        false,
+        timer(TimerKind::Monotonic),
+    )
+    .unwrap();
+    assert!(measurement.timed_out);
+    assert!(!measurement.exit_status.success());
+    assert!(start.elapsed().as_secs() < 5);
+
+    let measurement = execute_and_measure(
+        Command::new("true"),
+        None,
+        None,
+        None,
+        Some(Time::new::<second>(10.0)),
+        false,
+        timer(TimerKind::Monotonic),
+    )
+    .unwrap();
+    assert!(!measurement.timed_out);
+}
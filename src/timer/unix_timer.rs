@@ -2,24 +2,16 @@
 
 use std::io;
 use std::mem::MaybeUninit;
-use std::os::unix::process::ExitStatusExt;
-use std::process::{Child, ExitStatus};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{Child, Command, ExitStatus};
 
 use anyhow::Result;
 
+use super::ResourceUsage;
 use crate::quantity::{byte, kibibyte, microsecond, second, Information, Time};
 
-#[derive(Debug, Copy, Clone)]
-struct ResourceUsage {
-    /// Total amount of time spent executing in user mode
-    pub time_user: Time,
-
-    /// Total amount of time spent executing in kernel mode
-    pub time_system: Time,
-
-    /// Maximum amount of memory used by the process, in bytes
-    pub memory_usage: Information,
-}
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+use crate::quantity::nanosecond;
 
 #[allow(clippy::useless_conversion)]
 fn convert_timeval(tv: libc::timeval) -> Time {
@@ -30,10 +22,32 @@ fn convert_timeval(tv: libc::timeval) -> Time {
 }
 
 #[allow(clippy::useless_conversion)]
-fn wait4(mut child: Child) -> io::Result<(ExitStatus, ResourceUsage)> {
+fn wait4(mut child: Child, measure_scheduling: bool) -> io::Result<(ExitStatus, ResourceUsage)> {
     drop(child.stdin.take());
 
     let pid = child.id() as i32;
+
+    // The scheduler statistics are only available until the child has been reaped
+    #[cfg(target_os = "linux")]
+    let scheduling = measure_scheduling
+        .then(|| {
+            wait_for_exit(pid).ok().map(|_| {
+                (
+                    read_schedstat(pid),
+                    read_cpu_migrations(pid),
+                    read_numa_node(pid),
+                )
+            })
+        })
+        .flatten();
+    #[cfg(not(target_os = "linux"))]
+    let _ = measure_scheduling;
+
+    // On macOS, the accounting information of the (not yet reaped) child is more precise
+    // than what is reported by wait4.
+    #[cfg(target_os = "macos")]
+    let mach_usage = wait_for_exit(pid).and_then(|_| proc_pid_rusage(pid));
+
     let mut status = 0;
     let mut rusage = MaybeUninit::zeroed();
 
@@ -54,31 +68,180 @@ fn wait4(mut child: Child) -> io::Result<(ExitStatus, ResourceUsage)> {
             Information::new::<kibibyte>(rusage.ru_maxrss as f64)
         };
 
-        Ok((
-            ExitStatus::from_raw(status),
-            ResourceUsage {
-                time_user: convert_timeval(rusage.ru_utime),
-                time_system: convert_timeval(rusage.ru_stime),
-                memory_usage: memory_usage_byte.into(),
+        let usage = ResourceUsage {
+            time_user: convert_timeval(rusage.ru_utime),
+            time_system: convert_timeval(rusage.ru_stime),
+            peak_memory_usage: memory_usage_byte,
+            peak_memory_footprint: None,
+            minor_page_faults: rusage.ru_minflt as u64,
+            major_page_faults: rusage.ru_majflt as u64,
+            ..ResourceUsage::default()
+        };
+
+        #[cfg(target_os = "macos")]
+        let usage = match mach_usage {
+            Ok(mach_usage) => ResourceUsage {
+                time_user: mach_usage.time_user,
+                time_system: mach_usage.time_system,
+                peak_memory_footprint: mach_usage.peak_memory_footprint,
+                ..usage
+            },
+            Err(_) => usage,
+        };
+
+        #[cfg(target_os = "linux")]
+        let usage = match scheduling {
+            Some((times, cpu_migrations, numa_node)) => ResourceUsage {
+                time_on_cpu: times.map(|(time_on_cpu, _)| time_on_cpu),
+                run_queue_delay: times.map(|(_, run_queue_delay)| run_queue_delay),
+                cpu_migrations,
+                numa_node,
+                ..usage
             },
-        ))
+            None => usage,
+        };
+
+        Ok((ExitStatus::from_raw(status), usage))
     }
 }
 
-pub struct CPUTimer {}
+/// Wait until the child process has exited, but leave it in a waitable state, such that
+/// its resource usage can still be queried.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn wait_for_exit(pid: i32) -> io::Result<()> {
+    let mut info = MaybeUninit::<libc::siginfo_t>::zeroed();
+
+    loop {
+        // SAFETY: The pointer refers to a valid siginfo_t
+        let result = unsafe {
+            libc::waitid(
+                libc::P_PID,
+                pid as libc::id_t,
+                info.as_mut_ptr(),
+                libc::WEXITED | libc::WNOWAIT,
+            )
+        };
+
+        if result == 0 {
+            return Ok(());
+        }
+
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+}
+
+/// Read the time that the (main thread of the) process has spent on a CPU and waiting in
+/// the run queue of the scheduler. Not available if the kernel does not collect scheduler
+/// statistics.
+#[cfg(target_os = "linux")]
+fn read_schedstat(pid: i32) -> Option<(Time, Time)> {
+    let schedstat = std::fs::read_to_string(format!("/proc/{pid}/schedstat")).ok()?;
+    let mut fields = schedstat
+        .split_whitespace()
+        .map(|field| field.parse::<f64>().ok().map(Time::new::<nanosecond>));
+    Some((fields.next()??, fields.next()??))
+}
+
+/// Read how often the (main thread of the) process has been moved to another CPU. Not
+/// available if the kernel does not provide scheduler debug information.
+#[cfg(target_os = "linux")]
+fn read_cpu_migrations(pid: i32) -> Option<u64> {
+    let sched = std::fs::read_to_string(format!("/proc/{pid}/sched")).ok()?;
+    sched.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim() == "se.nr_migrations")
+            .then(|| value.trim().parse().ok())
+            .flatten()
+    })
+}
+
+/// Read the NUMA node of the CPU on which the (main thread of the) process has run last.
+/// Not available if the kernel has no NUMA support.
+#[cfg(target_os = "linux")]
+fn read_numa_node(pid: i32) -> Option<u32> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // The 39th field is the CPU, counted from the state that follows the command name
+    // (which may contain spaces and parentheses itself)
+    let (_, fields) = stat.rsplit_once(')')?;
+    let cpu: usize = fields.split_whitespace().nth(36)?.parse().ok()?;
+    crate::util::numa::node_of_cpu(cpu)
+}
+
+/// Convert Mach absolute time units into a time
+#[cfg(target_os = "macos")]
+#[allow(deprecated)]
+fn convert_mach_time(ticks: u64) -> Time {
+    let mut timebase = libc::mach_timebase_info { numer: 0, denom: 0 };
+
+    // SAFETY: The pointer refers to a valid mach_timebase_info
+    let result = unsafe { libc::mach_timebase_info(&mut timebase) };
+    if result != 0 || timebase.denom == 0 {
+        timebase = libc::mach_timebase_info { numer: 1, denom: 1 };
+    }
+
+    Time::new::<nanosecond>(ticks as f64 * timebase.numer as f64 / timebase.denom as f64)
+}
+
+/// Query the CPU times of the child process and all of its (reaped) descendants, as well
+/// as its peak physical footprint.
+#[cfg(target_os = "macos")]
+fn proc_pid_rusage(pid: i32) -> io::Result<ResourceUsage> {
+    let mut info = MaybeUninit::<libc::rusage_info_v4>::zeroed();
+
+    // SAFETY: The buffer is large enough to hold the requested rusage_info_v4 flavor
+    let result =
+        unsafe { libc::proc_pid_rusage(pid, libc::RUSAGE_INFO_V4, info.as_mut_ptr().cast()) };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // SAFETY: The struct got initialized by proc_pid_rusage
+    let info = unsafe { info.assume_init() };
+
+    Ok(ResourceUsage {
+        time_user: convert_mach_time(info.ri_user_time + info.ri_child_user_time),
+        time_system: convert_mach_time(info.ri_system_time + info.ri_child_system_time),
+        peak_memory_footprint: Some(Information::new::<byte>(
+            info.ri_lifetime_max_phys_footprint as f64,
+        )),
+        ..ResourceUsage::default()
+    })
+}
+
+/// Limit the address space of the process that is spawned by the given command (and of
+/// its children) to the given size (see `--limit-memory`)
+pub fn limit_memory(command: &mut Command, limit: Information) {
+    let size = limit.get::<byte>() as libc::rlim_t;
+    let set_limit = move || {
+        let rlimit = libc::rlimit {
+            rlim_cur: size,
+            rlim_max: size,
+        };
+        // SAFETY: The pointer is valid, and setrlimit is async-signal-safe
+        if unsafe { libc::setrlimit(libc::RLIMIT_AS, &rlimit) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    };
+    // SAFETY: The closure does not allocate or access any locks
+    unsafe { command.pre_exec(set_limit) };
+}
+
+pub struct CPUTimer {
+    /// Whether the scheduler statistics of the process are read (see `--scheduling-delay`)
+    measure_scheduling: bool,
+}
 
 impl CPUTimer {
-    pub fn start() -> Self {
-        Self {}
+    pub fn start(measure_scheduling: bool) -> Self {
+        Self { measure_scheduling }
     }
 
-    pub fn stop(&self, child: Child) -> Result<(Time, Time, Information, ExitStatus)> {
-        let (status, usage) = wait4(child)?;
-        Ok((
-            usage.time_user,
-            usage.time_system,
-            usage.memory_usage,
-            status,
-        ))
+    pub fn stop(&self, child: Child) -> Result<(ResourceUsage, ExitStatus)> {
+        let (status, usage) = wait4(child, self.measure_scheduling)?;
+        Ok((usage, status))
     }
 }
@@ -1,22 +1,116 @@
+//! Clocks for measuring the wall clock time of the benchmarked processes (see `--timer`)
+
+use std::sync::OnceLock;
 use std::time::Instant;
 
-use crate::quantity::{nanosecond, second, Time};
+use super::ResourceUsage;
+use crate::quantity::{nanosecond, second, Quantity, Time};
 
-pub struct WallClockTimer {
-    start: Instant,
-}
+/// A clock that measures the wall clock time of a benchmark run
+pub trait Timer: Sync {
+    /// The current reading of the clock, relative to an arbitrary (but fixed) point in time
+    fn now(&self) -> Time;
 
-impl WallClockTimer {
-    pub fn start() -> WallClockTimer {
-        WallClockTimer {
-            start: Instant::now(),
-        }
+    /// The time of a run that started when the clock read `start`, and whose process has
+    /// just exited with the given resource usage
+    fn elapsed(&self, start: Time, _usage: &ResourceUsage) -> Time {
+        self.now() - start
     }
+}
+
+/// The monotonic clock of the standard library, i.e. `CLOCK_MONOTONIC` on Linux,
+/// `mach_absolute_time` on macOS and `QueryPerformanceCounter` on Windows
+pub struct MonotonicTimer;
 
-    pub fn stop(&self) -> Time {
-        let duration = self.start.elapsed();
+impl Timer for MonotonicTimer {
+    fn now(&self) -> Time {
+        static EPOCH: OnceLock<Instant> = OnceLock::new();
+        let duration = EPOCH.get_or_init(Instant::now).elapsed();
 
         Time::new::<second>(duration.as_secs() as f64)
             + Time::new::<nanosecond>(duration.subsec_nanos() as f64)
     }
+
+    fn elapsed(&self, start: Time, usage: &ResourceUsage) -> Time {
+        // The lifetime of the process is more precise, if it is available (Windows only)
+        usage.time_wall_clock.unwrap_or_else(|| self.now() - start)
+    }
+}
+
+/// `CLOCK_MONOTONIC_RAW`, which is not subject to NTP frequency adjustments
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub struct MonotonicRawTimer;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Timer for MonotonicRawTimer {
+    fn now(&self) -> Time {
+        let mut ts = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        // SAFETY: The pointer refers to a valid timespec. The call can not fail for this clock.
+        unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC_RAW, &mut ts) };
+
+        Time::new::<second>(ts.tv_sec as f64) + Time::new::<nanosecond>(ts.tv_nsec as f64)
+    }
+}
+
+/// The CPU time of the process (user and system time), like the 'task-clock' event of
+/// `perf stat`. Time in which the process is waiting (e.g. for I/O) is not counted.
+pub struct TaskClockTimer;
+
+impl Timer for TaskClockTimer {
+    fn now(&self) -> Time {
+        Time::zero()
+    }
+
+    fn elapsed(&self, _start: Time, usage: &ResourceUsage) -> Time {
+        usage.time_user + usage.time_system
+    }
+}
+
+/// The time stamp counter of the CPU. Its frequency is calibrated against the monotonic
+/// clock on first use. This requires an invariant TSC, which is the case for all recent
+/// x86-64 processors.
+#[cfg(target_arch = "x86_64")]
+pub struct TscTimer;
+
+#[cfg(target_arch = "x86_64")]
+impl TscTimer {
+    #[allow(unused_unsafe)]
+    fn ticks() -> u64 {
+        // SAFETY: RDTSC is available on all x86-64 processors
+        unsafe { std::arch::x86_64::_rdtsc() }
+    }
+
+    /// Number of ticks per second
+    fn frequency() -> f64 {
+        static FREQUENCY: OnceLock<f64> = OnceLock::new();
+        *FREQUENCY.get_or_init(|| {
+            let start = Instant::now();
+            let start_ticks = Self::ticks();
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            let ticks = Self::ticks() - start_ticks;
+            ticks as f64 / start.elapsed().as_secs_f64()
+        })
+    }
+}
+
+/// Whether the time stamp counter of the CPU runs at a constant rate in all power states, which
+/// is required for the 'tsc' timer
+#[cfg(target_arch = "x86_64")]
+#[allow(unused_unsafe)]
+pub fn has_invariant_tsc() -> bool {
+    use std::arch::x86_64::__cpuid;
+
+    // SAFETY: CPUID is available on all x86-64 processors
+    let max_extended_leaf = unsafe { __cpuid(0x8000_0000) }.eax;
+    max_extended_leaf >= 0x8000_0007 && unsafe { __cpuid(0x8000_0007) }.edx & (1 << 8) != 0
+}
+
+#[cfg(target_arch = "x86_64")]
+impl Timer for TscTimer {
+    fn now(&self) -> Time {
+        Time::new::<second>(Self::ticks() as f64 / Self::frequency())
+    }
 }
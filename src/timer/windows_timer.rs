@@ -2,16 +2,24 @@
 #![warn(unsafe_op_in_unsafe_fn)]
 
 use std::process::{self, Child, ExitStatus};
-use std::{mem, os::windows::io::AsRawHandle, ptr};
+use std::sync::OnceLock;
+use std::{io, mem, os::windows::io::AsRawHandle, ptr};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 
 use windows_sys::Win32::{
-    Foundation::{CloseHandle, HANDLE},
+    Foundation::{CloseHandle, HANDLE, WAIT_OBJECT_0},
     System::JobObjects::{
         AssignProcessToJobObject, CreateJobObjectW, JobObjectBasicAccountingInformation,
-        QueryInformationJobObject, JOBOBJECT_BASIC_ACCOUNTING_INFORMATION,
+        JobObjectExtendedLimitInformation, QueryInformationJobObject, SetInformationJobObject,
+        TerminateJobObject, JOBOBJECT_BASIC_ACCOUNTING_INFORMATION,
+        JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT_JOB_MEMORY,
     },
+    System::Performance::{QueryPerformanceCounter, QueryPerformanceFrequency},
+    System::ProcessStatus::{
+        GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS, PROCESS_MEMORY_COUNTERS_EX,
+    },
+    System::Threading::{CreateEventW, WaitForSingleObject, INFINITE},
 };
 
 #[cfg(feature = "windows_process_extensions_main_thread_handle")]
@@ -30,7 +38,8 @@ use windows_sys::{
     },
 };
 
-use crate::quantity::{nanosecond, Information, InformationQuantity, Time, TimeQuantity};
+use super::ResourceUsage;
+use crate::quantity::{byte, nanosecond, second, Information, Time};
 
 #[cfg(not(feature = "windows_process_extensions_main_thread_handle"))]
 #[allow(non_upper_case_globals)]
@@ -47,12 +56,73 @@ static NtResumeProcess: Lazy<unsafe extern "system" fn(ProcessHandle: HANDLE) ->
         unsafe { mem::transmute(nt_resume_process.unwrap()) }
     });
 
+/// Current value of the performance counter (in ticks)
+fn performance_counter() -> i64 {
+    let mut ticks = 0;
+    // SAFETY: The pointer is valid. The call can not fail on Windows XP and later.
+    unsafe { QueryPerformanceCounter(&mut ticks) };
+    ticks
+}
+
+/// Convert a number of performance counter ticks to a time
+fn ticks_to_time(ticks: i64) -> Time {
+    static FREQUENCY: OnceLock<i64> = OnceLock::new();
+    let frequency = *FREQUENCY.get_or_init(|| {
+        let mut frequency = 0;
+        // SAFETY: The pointer is valid. The call can not fail on Windows XP and later.
+        unsafe { QueryPerformanceFrequency(&mut frequency) };
+        frequency
+    });
+
+    Time::new::<second>(ticks as f64 / frequency as f64)
+}
+
+/// Time that is spent between observing the exit of a process and reading the performance
+/// counter, even if the process has already exited. It is estimated once by waiting for an
+/// object that is already signaled, and subtracted from all measured process lifetimes.
+fn wait_overhead() -> i64 {
+    static OVERHEAD: OnceLock<i64> = OnceLock::new();
+    *OVERHEAD.get_or_init(|| {
+        const ITERATIONS: usize = 100;
+
+        // SAFETY: Creating a new (manual-reset, signaled) event is safe
+        let event = unsafe { CreateEventW(ptr::null(), 1, 1, ptr::null()) };
+        if event.is_null() {
+            return 0;
+        }
+
+        let overhead = (0..ITERATIONS)
+            .map(|_| {
+                let start = performance_counter();
+                // SAFETY: The event handle is valid
+                unsafe { WaitForSingleObject(event, INFINITE) };
+                performance_counter() - start
+            })
+            .min()
+            .unwrap_or(0);
+
+        // SAFETY: The event handle is valid and not used afterwards
+        unsafe { CloseHandle(event) };
+
+        overhead
+    })
+}
+
 pub struct CPUTimer {
     job_object: HANDLE,
+
+    /// Whether the memory of the job is limited (see `--limit-memory`)
+    memory_limited: bool,
+
+    /// Performance counter value right before the suspended process was resumed
+    start: i64,
 }
 
 impl CPUTimer {
-    pub unsafe fn start_suspended_process(child: &process::Child) -> Self {
+    pub unsafe fn start_suspended_process(
+        child: &process::Child,
+        memory_limit: Option<Information>,
+    ) -> Self {
         let child_handle = child.as_raw_handle() as HANDLE;
 
         // SAFETY: Creating a new job object is safe
@@ -62,10 +132,35 @@ impl CPUTimer {
             "CreateJobObjectW failed"
         );
 
+        if let Some(limit) = memory_limit {
+            // SAFETY: The structure consists of integers only, for which zero is valid
+            let mut limit_info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { mem::zeroed() };
+            limit_info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_JOB_MEMORY;
+            limit_info.JobMemoryLimit = limit.get::<byte>() as usize;
+
+            // SAFETY: The job object handle is valid, and the size matches the structure
+            let ret = unsafe {
+                SetInformationJobObject(
+                    job_object,
+                    JobObjectExtendedLimitInformation,
+                    (&limit_info as *const JOBOBJECT_EXTENDED_LIMIT_INFORMATION).cast(),
+                    mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+                )
+            };
+            assert!(ret != 0, "SetInformationJobObject failed");
+        }
+
         // SAFETY: The job object handle is valid
         let ret = unsafe { AssignProcessToJobObject(job_object, child_handle) };
         assert!(ret != 0, "AssignProcessToJobObject failed");
 
+        // Make sure that the calibration does not end up in the measured time
+        wait_overhead();
+
+        // The process does not execute anything before it is resumed, so the overhead of
+        // process creation is not included in the measured lifetime.
+        let start = performance_counter();
+
         #[cfg(feature = "windows_process_extensions_main_thread_handle")]
         {
             // SAFETY: The main thread handle is valid
@@ -84,11 +179,41 @@ impl CPUTimer {
             assert!(ret == STATUS_SUCCESS, "NtResumeProcess failed");
         }
 
-        Self { job_object }
+        Self {
+            job_object,
+            memory_limited: memory_limit.is_some(),
+            start,
+        }
+    }
+
+    /// A function that kills all processes of the job, which must not be called after the
+    /// timer has been dropped (see `--auto-timeout`)
+    pub fn terminator(&self) -> impl FnOnce() + Send + 'static {
+        // Handles can be used from any thread, but raw pointers are not `Send`
+        let job_object = self.job_object as usize;
+        move || {
+            // SAFETY: The job object handle is valid as long as the timer is alive
+            unsafe { TerminateJobObject(job_object as HANDLE, 1) };
+        }
     }
 
-    pub fn stop(&self, mut child: Child) -> Result<(Time, Time, Information, ExitStatus)> {
+    pub fn stop(&self, mut child: Child) -> Result<(ResourceUsage, ExitStatus)> {
+        // Wait for the process handle to be signaled directly, in order to read the
+        // performance counter as soon as possible after the process has exited.
+        // SAFETY: The process handle is valid as long as `child` is alive
+        let ret = unsafe { WaitForSingleObject(child.as_raw_handle() as HANDLE, INFINITE) };
+        let end = performance_counter();
+        if ret != WAIT_OBJECT_0 {
+            bail!(
+                "Could not wait for the benchmarked process: {}",
+                io::Error::last_os_error()
+            );
+        }
+        let time_wall_clock = ticks_to_time((end - self.start - wait_overhead()).max(0));
+
+        // The process has already exited, so this returns immediately
         let status = child.wait()?;
+        let (peak_private_bytes, peak_working_set) = process_memory_peaks(&child);
 
         let mut job_object_info =
             mem::MaybeUninit::<JOBOBJECT_BASIC_ACCOUNTING_INFORMATION>::uninit();
@@ -119,13 +244,96 @@ impl CPUTimer {
             let system_time =
                 Time::new::<nanosecond>((job_object_info.TotalKernelTime as f64) * 100.0);
 
-            Ok((user_time, system_time, Information::zero(), status))
+            Ok((
+                ResourceUsage {
+                    time_wall_clock: Some(time_wall_clock),
+                    time_user: user_time,
+                    time_system: system_time,
+                    // Windows does not distinguish between minor and major page faults
+                    minor_page_faults: u64::from(job_object_info.TotalPageFaultCount),
+                    // All processes which have ever been associated with the job
+                    process_count: Some(u64::from(job_object_info.TotalProcesses)),
+                    peak_memory_usage: self.peak_job_memory_used().unwrap_or_default(),
+                    peak_private_bytes,
+                    peak_working_set,
+                    ..ResourceUsage::default()
+                },
+                status,
+            ))
         } else {
-            Ok((Time::zero(), Time::zero(), Information::zero(), status))
+            Ok((
+                ResourceUsage {
+                    time_wall_clock: Some(time_wall_clock),
+                    ..ResourceUsage::default()
+                },
+                status,
+            ))
+        }
+    }
+}
+
+impl CPUTimer {
+    /// The peak memory that has been committed by the processes of the job, which is only
+    /// recorded if the memory of the job is limited
+    fn peak_job_memory_used(&self) -> Option<Information> {
+        if !self.memory_limited {
+            return None;
+        }
+
+        let mut limit_info = mem::MaybeUninit::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>::uninit();
+
+        // SAFETY: A valid job object got created in `start_suspended_process`
+        let res = unsafe {
+            QueryInformationJobObject(
+                self.job_object,
+                JobObjectExtendedLimitInformation,
+                limit_info.as_mut_ptr().cast(),
+                mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+                ptr::null_mut(),
+            )
+        };
+        if res == 0 {
+            return None;
         }
+
+        // SAFETY: The limit information got correctly initialized
+        let limit_info = unsafe { limit_info.assume_init() };
+        Some(Information::new::<byte>(
+            limit_info.PeakJobMemoryUsed as f64,
+        ))
     }
 }
 
+/// The peak private bytes and the peak working set of the (exited) process. Both are only
+/// known for the process that has been spawned, not for the processes that it has created.
+fn process_memory_peaks(child: &Child) -> (Option<Information>, Option<Information>) {
+    let mut counters = mem::MaybeUninit::<PROCESS_MEMORY_COUNTERS_EX>::uninit();
+
+    // SAFETY: The process handle is valid as long as `child` is alive, and the size matches
+    // the extended structure, which starts with the basic one
+    let res = unsafe {
+        GetProcessMemoryInfo(
+            child.as_raw_handle() as HANDLE,
+            counters.as_mut_ptr().cast::<PROCESS_MEMORY_COUNTERS>(),
+            mem::size_of::<PROCESS_MEMORY_COUNTERS_EX>() as u32,
+        )
+    };
+    if res == 0 {
+        return (None, None);
+    }
+
+    // SAFETY: The counters got correctly initialized
+    let counters = unsafe { counters.assume_init() };
+
+    // The private bytes of an exited process are zero, but the peak of the commit charge
+    // (`PeakPagefileUsage`) is the peak of its private bytes
+    let bytes = |size: usize| Some(Information::new::<byte>(size as f64));
+    (
+        bytes(counters.PeakPagefileUsage),
+        bytes(counters.PeakWorkingSetSize),
+    )
+}
+
 impl Drop for CPUTimer {
     fn drop(&mut self) {
         // SAFETY: A valid job object got created in `start_suspended_process`
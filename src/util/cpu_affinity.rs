@@ -0,0 +1,74 @@
+//! Helpers to pin benchmark jobs to disjoint sets of CPU cores. This is only supported
+//! on Linux. On other platforms, jobs are not pinned.
+
+use std::io;
+
+/// Split the CPUs available to this process into `count` disjoint sets of (roughly)
+/// equal size. Returns `None` if CPU affinity is not supported on this platform.
+pub fn split_available_cpus(count: usize) -> io::Result<Option<Vec<Vec<usize>>>> {
+    Ok(available_cpus()?.map(|cpus| split(&cpus, count)))
+}
+
+fn split(cpus: &[usize], count: usize) -> Vec<Vec<usize>> {
+    (0..count)
+        .map(|i| cpus[i * cpus.len() / count..(i + 1) * cpus.len() / count].to_vec())
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn available_cpus() -> io::Result<Option<Vec<usize>>> {
+    // SAFETY: cpu_set_t is a plain bitmask for which all-zeroes is a valid value
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+
+    // SAFETY: The pointer and size refer to a valid cpu_set_t
+    let result =
+        unsafe { libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(Some(
+        (0..libc::CPU_SETSIZE as usize)
+            // SAFETY: The index is within the bounds of the set
+            .filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, &set) })
+            .collect(),
+    ))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn available_cpus() -> io::Result<Option<Vec<usize>>> {
+    Ok(None)
+}
+
+/// Restrict the calling thread (and all processes spawned from it) to the given CPUs.
+#[cfg(target_os = "linux")]
+pub fn pin_current_thread(cpus: &[usize]) -> io::Result<()> {
+    // SAFETY: cpu_set_t is a plain bitmask for which all-zeroes is a valid value
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    for &cpu in cpus {
+        // SAFETY: The CPU index was obtained from sched_getaffinity
+        unsafe { libc::CPU_SET(cpu, &mut set) };
+    }
+
+    // SAFETY: The pointer and size refer to a valid cpu_set_t
+    let result =
+        unsafe { libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pin_current_thread(_cpus: &[usize]) -> io::Result<()> {
+    Ok(())
+}
+
+#[test]
+fn test_split_cpus() {
+    assert_eq!(split(&[0, 1, 2, 3], 2), vec![vec![0, 1], vec![2, 3]]);
+    assert_eq!(split(&[0, 1, 2, 3, 4], 2), vec![vec![0, 1], vec![2, 3, 4]]);
+    assert_eq!(split(&[0, 2, 4], 3), vec![vec![0], vec![2], vec![4]]);
+    assert_eq!(split(&[0, 1, 2], 1), vec![vec![0, 1, 2]]);
+}
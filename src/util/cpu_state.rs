@@ -0,0 +1,161 @@
+//! Control of the turbo boost and simultaneous multithreading (SMT) state of the CPU for the
+//! duration of a benchmark session (`--disable-turbo`, `--disable-smt`). This is done via the
+//! sysfs knobs of the Linux kernel, which requires root privileges. The original state is
+//! restored when the returned guard is dropped.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::output::log::log_verbose;
+
+use anyhow::{bail, Context, Result};
+
+/// Knob of the `intel_pstate` driver. Writing `1` disables turbo boost.
+pub const INTEL_NO_TURBO: &str = "devices/system/cpu/intel_pstate/no_turbo";
+
+/// Knob of the `acpi-cpufreq` driver (used on AMD CPUs). Writing `0` disables turbo boost.
+pub const CPUFREQ_BOOST: &str = "devices/system/cpu/cpufreq/boost";
+
+/// SMT control of the kernel (`on`, `off`, `forceoff`, `notsupported`, `notimplemented`)
+pub const SMT_CONTROL: &str = "devices/system/cpu/smt/control";
+
+/// Restores the original CPU state when dropped (also when unwinding after a panic)
+#[derive(Debug, Default)]
+pub struct CpuStateGuard {
+    /// Original values of the modified knobs, in the order in which they were changed
+    original_values: Vec<(PathBuf, String)>,
+}
+
+impl CpuStateGuard {
+    /// Disable turbo boost and/or SMT as requested
+    pub fn apply(disable_turbo: bool, disable_smt: bool) -> Result<Self> {
+        let mut guard = CpuStateGuard::default();
+        if !disable_turbo && !disable_smt {
+            return Ok(guard);
+        }
+        if !cfg!(target_os = "linux") {
+            bail!("'--disable-turbo' and '--disable-smt' are only supported on Linux");
+        }
+
+        guard.apply_at(Path::new("/sys"), disable_turbo, disable_smt)?;
+        Ok(guard)
+    }
+
+    fn apply_at(&mut self, sysfs: &Path, disable_turbo: bool, disable_smt: bool) -> Result<()> {
+        if disable_turbo {
+            let intel_no_turbo = sysfs.join(INTEL_NO_TURBO);
+            let cpufreq_boost = sysfs.join(CPUFREQ_BOOST);
+            if intel_no_turbo.exists() {
+                self.set(&intel_no_turbo, "1", "turbo boost")?;
+            } else if cpufreq_boost.exists() {
+                self.set(&cpufreq_boost, "0", "turbo boost")?;
+            } else {
+                bail!(
+                    "Turbo boost can not be disabled on this system: neither '{}' nor '{}' exist",
+                    intel_no_turbo.display(),
+                    cpufreq_boost.display()
+                );
+            }
+        }
+
+        if disable_smt {
+            let smt_control = sysfs.join(SMT_CONTROL);
+            let state = read(&smt_control).with_context(|| {
+                format!(
+                    "SMT can not be disabled on this system: could not read '{}'",
+                    smt_control.display()
+                )
+            })?;
+            match state.as_str() {
+                "off" | "forceoff" => {}
+                "notsupported" | "notimplemented" => {
+                    bail!("SMT can not be disabled on this system (SMT control is '{state}')")
+                }
+                _ => self.set(&smt_control, "off", "SMT")?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write `value` to the knob at `path`, and remember the original value
+    fn set(&mut self, path: &Path, value: &str, feature: &str) -> Result<()> {
+        let original =
+            read(path).with_context(|| format!("Could not read the state of {feature}"))?;
+        if original == value {
+            return Ok(());
+        }
+
+        fs::write(path, value).map_err(|e| {
+            let hint = if e.kind() == io::ErrorKind::PermissionDenied {
+                " (this requires root privileges)"
+            } else {
+                ""
+            };
+            anyhow::anyhow!(
+                "Could not disable {feature} by writing to '{}': {e}{hint}",
+                path.display()
+            )
+        })?;
+        log_verbose!("Disabled {feature} via '{}'", path.display());
+
+        self.original_values.push((path.to_path_buf(), original));
+        Ok(())
+    }
+}
+
+impl Drop for CpuStateGuard {
+    fn drop(&mut self) {
+        for (path, value) in self.original_values.drain(..).rev() {
+            if let Err(e) = fs::write(&path, &value) {
+                eprintln!(
+                    "Warning: Could not restore '{}' to '{value}': {e}",
+                    path.display()
+                );
+            }
+        }
+    }
+}
+
+fn read(path: &Path) -> io::Result<String> {
+    Ok(fs::read_to_string(path)?.trim().to_string())
+}
+
+#[test]
+fn test_cpu_state_guard() {
+    let sysfs = tempfile::tempdir().unwrap();
+    let knob = |path: &str| sysfs.path().join(path);
+    let write = |path: &str, value: &str| {
+        fs::create_dir_all(knob(path).parent().unwrap()).unwrap();
+        fs::write(knob(path), value).unwrap();
+    };
+
+    let mut guard = CpuStateGuard::default();
+    let error = guard.apply_at(sysfs.path(), true, false).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("Turbo boost can not be disabled"));
+
+    write(CPUFREQ_BOOST, "1\n");
+    write(SMT_CONTROL, "on\n");
+    {
+        let mut guard = CpuStateGuard::default();
+        guard.apply_at(sysfs.path(), true, true).unwrap();
+        assert_eq!(read(&knob(CPUFREQ_BOOST)).unwrap(), "0");
+        assert_eq!(read(&knob(SMT_CONTROL)).unwrap(), "off");
+    }
+    assert_eq!(read(&knob(CPUFREQ_BOOST)).unwrap(), "1");
+    assert_eq!(read(&knob(SMT_CONTROL)).unwrap(), "on");
+
+    // Knobs that are already in the desired state are left untouched
+    write(INTEL_NO_TURBO, "1\n");
+    let mut guard = CpuStateGuard::default();
+    guard.apply_at(sysfs.path(), true, false).unwrap();
+    assert!(guard.original_values.is_empty());
+
+    write(SMT_CONTROL, "notsupported\n");
+    let mut guard = CpuStateGuard::default();
+    let error = guard.apply_at(sysfs.path(), false, true).unwrap_err();
+    assert!(error.to_string().contains("SMT can not be disabled"));
+}
@@ -0,0 +1,37 @@
+//! Disk usage of a file or directory tree (see `--measure-disk`)
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The total size of the files in the given directory tree (or of the given file), in
+/// bytes. Symbolic links are not followed, and a path that does not exist (yet) takes up
+/// no space.
+pub fn disk_usage(path: &Path) -> io::Result<u64> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        size += disk_usage(&entry?.path())?;
+    }
+    Ok(size)
+}
+
+#[test]
+fn test_disk_usage() {
+    let directory = tempfile::tempdir().unwrap();
+    assert_eq!(disk_usage(&directory.path().join("missing")).unwrap(), 0);
+
+    fs::write(directory.path().join("a"), [0; 100]).unwrap();
+    fs::create_dir(directory.path().join("sub")).unwrap();
+    fs::write(directory.path().join("sub").join("b"), [0; 20]).unwrap();
+    assert_eq!(disk_usage(&directory.path().join("a")).unwrap(), 100);
+    assert_eq!(disk_usage(directory.path()).unwrap(), 120);
+}
@@ -0,0 +1,110 @@
+//! Parsing of human-friendly durations like `500ms`, `2s` or `1m30s` for time-valued
+//! command line options. A plain number is interpreted as a number of seconds.
+
+use crate::quantity::{hour, microsecond, millisecond, minute, nanosecond, second, Time};
+
+fn unit_to_time(value: f64, unit: &str) -> Option<Time> {
+    Some(match unit {
+        "ns" => Time::new::<nanosecond>(value),
+        "us" | "µs" => Time::new::<microsecond>(value),
+        "ms" => Time::new::<millisecond>(value),
+        "s" => Time::new::<second>(value),
+        "m" | "min" => Time::new::<minute>(value),
+        "h" => Time::new::<hour>(value),
+        _ => return None,
+    })
+}
+
+/// Parse a duration such as `1.5`, `500ms`, `2s` or `1m30s`. Returns a description of the
+/// problem if the input is not a valid, non-negative duration.
+pub fn parse_duration(input: &str) -> Result<Time, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("empty duration".into());
+    }
+
+    if let Ok(seconds) = input.parse::<f64>() {
+        if !seconds.is_finite() || seconds < 0.0 {
+            return Err(format!("'{input}' is not a valid duration"));
+        }
+        return Ok(Time::new::<second>(seconds));
+    }
+
+    let mut total = Time::new::<second>(0.0);
+    let mut rest = input;
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let unit_end = rest[number_end..]
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .map_or(rest.len(), |end| number_end + end);
+
+        let (number, unit) = (&rest[..number_end], &rest[number_end..unit_end]);
+        let value = number
+            .parse::<f64>()
+            .map_err(|_| format!("'{input}' is not a valid duration"))?;
+        total += unit_to_time(value, unit).ok_or_else(|| {
+            if unit.is_empty() {
+                format!("missing unit after '{number}' in '{input}'")
+            } else {
+                format!("unknown unit '{unit}' in '{input}' (use ns, us, ms, s, m or h)")
+            }
+        })?;
+
+        rest = &rest[unit_end..];
+    }
+
+    Ok(total)
+}
+
+/// Format a (long) duration in the same style, e.g. `2h05m`, `12m30s` or `45s`
+pub fn format_duration(time: Time) -> String {
+    let total_seconds = time.get::<second>().max(0.0).round() as u64;
+    let (hours, minutes, seconds) = (
+        total_seconds / 3600,
+        total_seconds % 3600 / 60,
+        total_seconds % 60,
+    );
+
+    if hours > 0 {
+        format!("{hours}h{minutes:02}m")
+    } else if minutes > 0 {
+        format!("{minutes}m{seconds:02}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+#[test]
+fn test_parse_duration() {
+    let seconds = |input| parse_duration(input).unwrap().get::<second>();
+
+    assert_eq!(seconds("3"), 3.0);
+    assert_eq!(seconds("0.25"), 0.25);
+    assert_eq!(seconds("500ms"), 0.5);
+    assert_eq!(seconds("2s"), 2.0);
+    assert_eq!(seconds("1m30s"), 90.0);
+    assert_eq!(seconds("1h"), 3600.0);
+    assert_eq!(seconds(" 1.5min "), 90.0);
+    assert!((seconds("250us") - 250e-6).abs() < 1e-12);
+    assert!((seconds("10ns") - 10e-9).abs() < 1e-15);
+
+    assert!(parse_duration("").is_err());
+    assert!(parse_duration("-1").is_err());
+    assert!(parse_duration("1x")
+        .unwrap_err()
+        .contains("unknown unit 'x'"));
+    assert!(parse_duration("1m30")
+        .unwrap_err()
+        .contains("missing unit after '30'"));
+    assert!(parse_duration("ms").is_err());
+}
+
+#[test]
+fn test_format_duration() {
+    assert_eq!(format_duration(Time::new::<second>(45.3)), "45s");
+    assert_eq!(format_duration(Time::new::<second>(750.0)), "12m30s");
+    assert_eq!(format_duration(Time::new::<hour>(6.2)), "6h12m");
+    assert_eq!(format_duration(parse_duration("2h5m").unwrap()), "2h05m");
+}
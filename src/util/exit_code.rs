@@ -20,3 +20,17 @@ pub fn extract_exit_code(status: ExitStatus) -> Option<i32> {
 pub fn extract_exit_code(status: ExitStatus) -> Option<i32> {
     status.code()
 }
+
+/// The status of a process that exited with the given code, e.g. for results that have
+/// not been measured in this session
+#[cfg(unix)]
+pub fn exit_status_from_code(code: i32) -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    ExitStatus::from_raw((code & 0xff) << 8)
+}
+
+#[cfg(windows)]
+pub fn exit_status_from_code(code: i32) -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    ExitStatus::from_raw(code as u32)
+}
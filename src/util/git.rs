@@ -0,0 +1,91 @@
+//! The state of the git repository that contains a benchmarked program or its input (see
+//! `--git-annotate`)
+
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use serde::Serialize;
+
+/// The checked-out revision of a git repository
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GitRevision {
+    /// The top-level directory of the repository
+    pub repository: String,
+
+    /// The abbreviated hash of the checked-out commit
+    pub commit: String,
+
+    /// Whether tracked files have uncommitted changes
+    pub dirty: bool,
+}
+
+impl GitRevision {
+    /// The revision of the repository that contains the given path, if any
+    pub fn of(path: &Path) -> Option<GitRevision> {
+        let directory = if path.is_dir() { path } else { path.parent()? };
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(directory)
+                .args(args)
+                .stdin(Stdio::null())
+                .stderr(Stdio::null())
+                .output()
+                .ok()?;
+            output
+                .status
+                .success()
+                .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        };
+
+        Some(GitRevision {
+            repository: git(&["rev-parse", "--show-toplevel"])?,
+            commit: git(&["rev-parse", "--short", "HEAD"])?,
+            dirty: !git(&["status", "--porcelain", "--untracked-files=no"])?.is_empty(),
+        })
+    }
+
+    /// The commit, followed by '-dirty' if there are uncommitted changes
+    pub fn annotation(&self) -> String {
+        if self.dirty {
+            format!("{}-dirty", self.commit)
+        } else {
+            self.commit.clone()
+        }
+    }
+}
+
+/// The revision of the first git repository that contains one of the paths in the given
+/// command line. Only words with a path separator (including the values of '--opt=path'
+/// arguments) are considered, so that programs looked up in the PATH do not count.
+pub fn revision_in(command_line: &str) -> Option<GitRevision> {
+    let words = shell_words::split(command_line)
+        .unwrap_or_else(|_| command_line.split_whitespace().map(String::from).collect());
+
+    words
+        .iter()
+        .map(|word| {
+            word.split_once('=')
+                .map_or(word.as_str(), |(_, value)| value)
+        })
+        .filter(|word| word.contains('/') || (cfg!(windows) && word.contains('\\')))
+        .map(Path::new)
+        .filter(|path| path.exists())
+        .find_map(GitRevision::of)
+}
+
+#[test]
+fn test_annotation() {
+    let revision = GitRevision {
+        repository: "/src/project".into(),
+        commit: "a1b2c3d".into(),
+        dirty: false,
+    };
+    assert_eq!(revision.annotation(), "a1b2c3d");
+
+    let revision = GitRevision {
+        dirty: true,
+        ..revision
+    };
+    assert_eq!(revision.annotation(), "a1b2c3d-dirty");
+}
@@ -0,0 +1,61 @@
+//! Handling of Ctrl-C. By default, hyperfine is terminated immediately. If a handler is
+//! installed, the interrupt is only recorded, such that the current benchmark can be
+//! aborted gracefully (e.g. to run the '--teardown' command). On Unix, termination
+//! requests (SIGTERM, SIGHUP) are handled in the same way.
+
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether Ctrl-C has been pressed since the handler was installed
+pub fn was_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+#[cfg(unix)]
+extern "C" fn handle_sigint(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Record Ctrl-C instead of terminating the process
+#[cfg(unix)]
+pub fn install_handler() -> io::Result<()> {
+    let handler = handle_sigint as extern "C" fn(libc::c_int);
+
+    for signal in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP] {
+        // SAFETY: The handler only performs an atomic store, which is async-signal-safe
+        let result = unsafe { libc::signal(signal, handler as libc::sighandler_t) };
+        if result == libc::SIG_ERR {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn handle_ctrl_c(ctrl_type: u32) -> windows_sys::Win32::Foundation::BOOL {
+    use windows_sys::Win32::System::Console::CTRL_C_EVENT;
+
+    if ctrl_type == CTRL_C_EVENT {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+        1
+    } else {
+        0
+    }
+}
+
+/// Record Ctrl-C instead of terminating the process
+#[cfg(windows)]
+pub fn install_handler() -> io::Result<()> {
+    use windows_sys::Win32::System::Console::SetConsoleCtrlHandler;
+
+    // SAFETY: The handler only performs an atomic store
+    let result = unsafe { SetConsoleCtrlHandler(Some(handle_ctrl_c), 1) };
+    if result == 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
@@ -1,4 +1,20 @@
+pub mod cpu_affinity;
+pub mod cpu_state;
+pub mod disk_usage;
+pub mod duration;
 pub mod exit_code;
+pub mod git;
+pub mod hash;
+pub mod interrupt;
 pub mod min_max;
+pub mod numa;
 pub mod number;
+pub mod paused_processes;
+pub mod pipeline;
+pub mod program;
 pub mod randomized_environment_offset;
+pub mod session_lock;
+pub mod size;
+pub mod start_delay;
+pub mod syscall_count;
+pub mod timestamp;
@@ -0,0 +1,127 @@
+//! Binding of a benchmark session to a NUMA node (`--numa-node`), like
+//! 'numactl --cpunodebind=<n> --membind=<n>': the CPUs and the memory of hyperfine are
+//! restricted to the node, and all benchmarked processes inherit these restrictions. The
+//! topology is read from the sysfs of the Linux kernel.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+/// Directory of the NUMA nodes, relative to the sysfs mount point
+const NODE_DIRECTORY: &str = "devices/system/node";
+
+/// Directory of the CPUs, relative to the sysfs mount point
+const CPU_DIRECTORY: &str = "devices/system/cpu";
+
+/// Restrict the calling thread (and all processes spawned from it) to the CPUs and the
+/// memory of the given NUMA node
+pub fn bind_to_node(node: u32) -> Result<()> {
+    let cpus = cpus_of_node(Path::new("/sys"), node)?;
+    super::cpu_affinity::pin_current_thread(&cpus)
+        .with_context(|| format!("Could not bind to the CPUs of NUMA node {node}"))?;
+    bind_memory(node).with_context(|| format!("Could not bind to the memory of NUMA node {node}"))
+}
+
+/// The NUMA node of the given CPU, if the kernel has NUMA support
+pub fn node_of_cpu(cpu: usize) -> Option<u32> {
+    node_of_cpu_at(Path::new("/sys"), cpu)
+}
+
+fn node_of_cpu_at(sysfs: &Path, cpu: usize) -> Option<u32> {
+    // The directory of the CPU contains a link to the directory of its node, e.g. 'node0'
+    fs::read_dir(sysfs.join(CPU_DIRECTORY).join(format!("cpu{cpu}")))
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .find_map(|entry| {
+            entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("node")?
+                .parse()
+                .ok()
+        })
+}
+
+fn cpus_of_node(sysfs: &Path, node: u32) -> Result<Vec<usize>> {
+    let path = sysfs
+        .join(NODE_DIRECTORY)
+        .join(format!("node{node}"))
+        .join("cpulist");
+    let list = fs::read_to_string(&path)
+        .with_context(|| format!("NUMA node {node} does not exist ('{}')", path.display()))?;
+    let cpus = parse_cpu_list(list.trim())
+        .with_context(|| format!("Could not parse the CPUs of NUMA node {node}: '{list}'"))?;
+    if cpus.is_empty() {
+        bail!("NUMA node {node} has no CPUs");
+    }
+    Ok(cpus)
+}
+
+/// Parse a list of CPUs in the format of the kernel, e.g. '0-3,8,10-11'
+fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cpus = vec![];
+    for range in list.split(',').filter(|range| !range.is_empty()) {
+        match range.split_once('-') {
+            Some((first, last)) => cpus.extend(first.parse::<usize>().ok()?..=last.parse().ok()?),
+            None => cpus.push(range.parse().ok()?),
+        }
+    }
+    Some(cpus)
+}
+
+/// Only allocate memory on the given node (the `MPOL_BIND` policy of set_mempolicy)
+#[cfg(target_os = "linux")]
+fn bind_memory(node: u32) -> std::io::Result<()> {
+    const MPOL_BIND: libc::c_long = 2;
+    const MASK_BITS: u32 = libc::c_ulong::BITS;
+
+    let mut mask = vec![0 as libc::c_ulong; (node / MASK_BITS + 1) as usize];
+    mask[(node / MASK_BITS) as usize] |= 1 << (node % MASK_BITS);
+
+    // SAFETY: The mask is valid for the given number of bits. The kernel ignores the bit
+    // after the last one, so the number is one larger than the number of bits in the mask.
+    let result = unsafe {
+        libc::syscall(
+            libc::SYS_set_mempolicy,
+            MPOL_BIND,
+            mask.as_ptr(),
+            mask.len() as libc::c_ulong * MASK_BITS as libc::c_ulong + 1,
+        )
+    };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn bind_memory(_node: u32) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[test]
+fn test_parse_cpu_list() {
+    assert_eq!(
+        parse_cpu_list("0-3,8,10-11"),
+        Some(vec![0, 1, 2, 3, 8, 10, 11])
+    );
+    assert_eq!(parse_cpu_list("5"), Some(vec![5]));
+    assert_eq!(parse_cpu_list(""), Some(vec![]));
+    assert_eq!(parse_cpu_list("0-a"), None);
+}
+
+#[test]
+fn test_numa_topology() {
+    let sysfs = tempfile::tempdir().unwrap();
+    let node = sysfs.path().join(NODE_DIRECTORY).join("node1");
+    fs::create_dir_all(&node).unwrap();
+    fs::write(node.join("cpulist"), "4-5\n").unwrap();
+    fs::create_dir_all(sysfs.path().join(CPU_DIRECTORY).join("cpu4/node1")).unwrap();
+    fs::create_dir_all(sysfs.path().join(CPU_DIRECTORY).join("cpu0")).unwrap();
+
+    assert_eq!(cpus_of_node(sysfs.path(), 1).unwrap(), vec![4, 5]);
+    assert!(cpus_of_node(sysfs.path(), 0).is_err());
+    assert_eq!(node_of_cpu_at(sysfs.path(), 4), Some(1));
+    assert_eq!(node_of_cpu_at(sysfs.path(), 0), None);
+}
@@ -0,0 +1,221 @@
+//! Suspension of background processes for the duration of a benchmark session
+//! (`--pause-processes`), to reduce the noise in the measurements. The processes are stopped
+//! with SIGSTOP and continued with SIGCONT when the returned guard is dropped.
+
+use crate::output::log::log_verbose;
+
+use anyhow::{bail, Result};
+
+/// Continues the paused processes when dropped (also when unwinding after a panic)
+#[derive(Debug, Default)]
+pub struct PausedProcesses {
+    /// Process IDs and names of the processes that have been stopped
+    processes: Vec<(u32, String)>,
+}
+
+impl PausedProcesses {
+    /// Stop all processes with one of the given names. hyperfine itself and its ancestors
+    /// (e.g. the shell, a terminal multiplexer or 'make') are never stopped.
+    pub fn pause(names: &[String]) -> Result<Self> {
+        let mut paused = PausedProcesses::default();
+        if names.is_empty() {
+            return Ok(paused);
+        }
+        if !cfg!(unix) {
+            bail!("'--pause-processes' is only supported on Unix systems");
+        }
+
+        let own_pids = own_pids();
+        for name in names {
+            let pids: Vec<u32> = find_processes(name)?
+                .into_iter()
+                .filter(|pid| !own_pids.contains(pid))
+                .collect();
+            if pids.is_empty() {
+                eprintln!("Warning: No running process matches '{name}'");
+            }
+
+            for pid in pids {
+                match send_signal(pid, Signal::Stop) {
+                    Ok(()) => {
+                        log_verbose!("Paused process {pid} ({name})");
+                        paused.processes.push((pid, name.clone()));
+                    }
+                    Err(e) => eprintln!("Warning: Could not pause process {pid} ({name}): {e}"),
+                }
+            }
+        }
+
+        Ok(paused)
+    }
+}
+
+impl Drop for PausedProcesses {
+    fn drop(&mut self) {
+        for (pid, name) in self.processes.drain(..).rev() {
+            if let Err(e) = send_signal(pid, Signal::Continue) {
+                eprintln!("Warning: Could not continue process {pid} ({name}): {e}");
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Signal {
+    Stop,
+    Continue,
+}
+
+/// The IDs of hyperfine and of all its ancestors, up to the init process
+#[cfg(unix)]
+fn own_pids() -> Vec<u32> {
+    let mut pids = vec![std::process::id()];
+    // SAFETY: getppid has no preconditions and always succeeds
+    let mut pid = unsafe { libc::getppid() } as u32;
+    while pid != 0 && !pids.contains(&pid) {
+        pids.push(pid);
+        match parent_pid(pid) {
+            Some(parent) => pid = parent,
+            None => break,
+        }
+    }
+    pids
+}
+
+/// The ID of the parent of the given process, from the fourth field of `/proc/<pid>/stat`
+/// (after the command name, which may contain spaces and parentheses)
+#[cfg(target_os = "linux")]
+fn parent_pid(pid: u32) -> Option<u32> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// The ID of the parent of the given process, as reported by `ps`
+#[cfg(all(unix, not(target_os = "linux")))]
+fn parent_pid(pid: u32) -> Option<u32> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "ppid=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+#[cfg(not(unix))]
+fn own_pids() -> Vec<u32> {
+    vec![std::process::id()]
+}
+
+#[cfg(unix)]
+fn send_signal(pid: u32, signal: Signal) -> std::io::Result<()> {
+    let signal = match signal {
+        Signal::Stop => libc::SIGSTOP,
+        Signal::Continue => libc::SIGCONT,
+    };
+
+    // SAFETY: kill has no memory safety preconditions
+    match unsafe { libc::kill(pid as libc::pid_t, signal) } {
+        0 => Ok(()),
+        _ => Err(std::io::Error::last_os_error()),
+    }
+}
+
+#[cfg(not(unix))]
+fn send_signal(_pid: u32, _signal: Signal) -> std::io::Result<()> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+/// The IDs of all processes with the given name. On Linux, the name is matched against the
+/// command name (`/proc/<pid>/comm`, which is truncated to 15 characters) and the file name
+/// of the executable.
+#[cfg(target_os = "linux")]
+fn find_processes(name: &str) -> Result<Vec<u32>> {
+    use anyhow::Context;
+    use std::fs;
+    use std::path::Path;
+
+    let mut pids = vec![];
+    for entry in fs::read_dir("/proc").context("Could not list the running processes")? {
+        let entry = entry?;
+        let Some(pid) = entry.file_name().to_str().and_then(|s| s.parse().ok()) else {
+            continue;
+        };
+
+        // The process may have exited in the meantime
+        let comm = fs::read_to_string(entry.path().join("comm")).unwrap_or_default();
+        let executable = fs::read_link(entry.path().join("exe")).ok();
+        let executable_name = executable
+            .as_deref()
+            .and_then(Path::file_name)
+            .and_then(|name| name.to_str());
+
+        if matches_name(comm.trim_end(), executable_name, name) {
+            pids.push(pid);
+        }
+    }
+    pids.sort_unstable();
+    Ok(pids)
+}
+
+/// The IDs of all processes with the given name, as found by `pgrep`
+#[cfg(all(unix, not(target_os = "linux")))]
+fn find_processes(name: &str) -> Result<Vec<u32>> {
+    use anyhow::Context;
+
+    let output = std::process::Command::new("pgrep")
+        .args(["-x", name])
+        .output()
+        .context("Could not run 'pgrep' to find the processes to pause")?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().parse().ok())
+        .collect())
+}
+
+#[cfg(not(unix))]
+fn find_processes(_name: &str) -> Result<Vec<u32>> {
+    Ok(vec![])
+}
+
+/// Maximum length of the command name of a process on Linux
+#[cfg(any(target_os = "linux", test))]
+const COMM_LENGTH: usize = 15;
+
+#[cfg(any(target_os = "linux", test))]
+fn matches_name(comm: &str, executable_name: Option<&str>, name: &str) -> bool {
+    let truncated_name: String = name.chars().take(COMM_LENGTH).collect();
+    comm == truncated_name || executable_name == Some(name)
+}
+
+#[test]
+fn test_matches_name() {
+    assert!(matches_name("slack", Some("slack"), "slack"));
+    assert!(matches_name("chrome", None, "chrome"));
+    assert!(!matches_name("chrome_crashpad", None, "chrome"));
+    assert!(!matches_name("slack", Some("slack"), "chrome"));
+
+    // Long names are truncated in the command name
+    assert!(matches_name(
+        "gnome-software-",
+        None,
+        "gnome-software-service"
+    ));
+    assert!(matches_name(
+        "Web Content",
+        Some("firefox-bin"),
+        "firefox-bin"
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_own_pids_contain_the_ancestors() {
+    let pids = own_pids();
+    assert_eq!(pids[0], std::process::id());
+    // SAFETY: getppid has no preconditions and always succeeds
+    assert_eq!(pids[1], unsafe { libc::getppid() } as u32);
+
+    // The chain ends at a process without a parent (the init process)
+    let last = *pids.last().unwrap();
+    assert_eq!(parent_pid(last).unwrap_or(0), 0);
+}
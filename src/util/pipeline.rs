@@ -0,0 +1,91 @@
+//! Splitting of shell pipelines into their stages (see `--pipeline-stages`)
+
+/// The stages of the given command line, if it is a plain pipeline like `a | b | c`.
+/// Returns a single stage if the command is not a pipeline, or if it can not be split
+/// without changing its meaning, e.g. because it contains other control operators
+/// (`a; b | c`, `a || b`) or pipes stderr as well (`a |& b`).
+pub fn stages(command_line: &str) -> Vec<&str> {
+    let whole = vec![command_line];
+
+    let mut stages = vec![];
+    let mut stage_start = 0;
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut chars = command_line.char_indices().peekable();
+    let mut previous = None;
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (Some('\''), '\'') => quote = None,
+            (Some('\''), _) => {}
+            (_, '\\') => {
+                chars.next();
+            }
+            (Some('"'), '"') => quote = None,
+            (Some('"'), _) => {}
+            (_, '\'' | '"' | '`') if quote.is_none() => quote = Some(c),
+            (Some('`'), '`') => quote = None,
+            (Some('`'), _) => {}
+            (_, '(' | '{') => depth += 1,
+            (_, ')' | '}') => depth = depth.saturating_sub(1),
+            (_, ';' | '\n') if depth == 0 => return whole,
+            (_, '&') if depth == 0 => {
+                // Redirections like '2>&1' or '&>file' are fine
+                let next = chars.peek().map(|&(_, next)| next);
+                if !matches!(previous, Some('>' | '<')) && next != Some('>') {
+                    return whole;
+                }
+            }
+            (_, '|') if depth == 0 => {
+                let next = chars.peek().map(|&(_, next)| next);
+                if matches!(next, Some('|' | '&')) || previous == Some('>') {
+                    return whole;
+                }
+                stages.push(command_line[stage_start..i].trim());
+                stage_start = i + 1;
+            }
+            _ => {}
+        }
+        previous = Some(c);
+    }
+    stages.push(command_line[stage_start..].trim());
+
+    if stages.len() < 2 || stages.iter().any(|stage| stage.is_empty()) {
+        return whole;
+    }
+    stages
+}
+
+#[test]
+fn test_stages() {
+    assert_eq!(stages("sleep 1"), ["sleep 1"]);
+    assert_eq!(
+        stages("cat file | sort -n|uniq -c"),
+        ["cat file", "sort -n", "uniq -c"]
+    );
+
+    // Pipes in quotes, substitutions and subshells
+    assert_eq!(
+        stages("grep 'a|b' file | tr -d \"|\" | wc -l"),
+        ["grep 'a|b' file", "tr -d \"|\"", "wc -l"]
+    );
+    assert_eq!(
+        stages("echo $(ls | head -1) | (cat | cat)"),
+        ["echo $(ls | head -1)", "(cat | cat)"]
+    );
+    assert_eq!(stages("echo a\\|b | cat"), ["echo a\\|b", "cat"]);
+    assert_eq!(stages("make 2>&1 | tee log"), ["make 2>&1", "tee log"]);
+
+    // Commands which are not (only) a pipeline
+    for command in [
+        "a || b",
+        "a; b | c",
+        "a && b | c",
+        "a | b &",
+        "a |& b",
+        "a >| file",
+        "| a",
+        "a |",
+    ] {
+        assert_eq!(stages(command), [command]);
+    }
+}
@@ -0,0 +1,16 @@
+//! Lookup of programs in the directories of the `PATH` environment variable
+
+use std::env;
+use std::path::PathBuf;
+
+/// The path of the program with the given name, if it can be found in one of the directories
+/// of `PATH` (with or without the executable extension of the platform)
+pub fn find_program(name: &str) -> Option<PathBuf> {
+    env::split_paths(&env::var_os("PATH")?)
+        .flat_map(|directory| {
+            let candidate = directory.join(name);
+            let with_extension = candidate.with_extension(env::consts::EXE_EXTENSION);
+            [candidate, with_extension]
+        })
+        .find(|path| path.is_file())
+}
@@ -1,8 +1,25 @@
-/// Returns a string with a random length. This value will be set as an environment
-/// variable to account for offset effects. See [1] for more details.
+/// Maximum length of the randomized environment variable if no other maximum is given via
+/// `--randomize-env-offset`
+pub const DEFAULT_MAX_OFFSET: usize = 4096;
+
+/// Returns a random length between 0 and `max` (inclusive), which is the size of an inert
+/// environment variable that is set for each run, to account for offset effects. See [1]
+/// for more details.
 ///
 /// [1] Mytkowicz, 2009. Producing Wrong Data Without Doing Anything Obviously Wrong!.
 ///     Sigplan Notices - SIGPLAN. 44. 265-276. 10.1145/1508284.1508275.
-pub fn value() -> String {
-    "X".repeat(rand::random::<usize>() % 4096usize)
+pub fn random_offset(max: usize) -> usize {
+    rand::random::<usize>() % (max + 1)
+}
+
+/// The value of the environment variable for the given offset
+pub fn value(offset: usize) -> String {
+    "X".repeat(offset)
+}
+
+#[test]
+fn test_random_offset() {
+    assert_eq!(random_offset(0), 0);
+    assert!((0..100).all(|_| random_offset(10) <= 10));
+    assert_eq!(value(3), "XXX");
 }
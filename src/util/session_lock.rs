@@ -0,0 +1,142 @@
+//! A lock file that prevents concurrent benchmark sessions on the same machine (see '--lock'
+//! and '--no-lock'), which would silently disturb each other's measurements. A session waits
+//! until the lock is free, or fails right away with '--fail-if-locked'. The lock is released
+//! when the guard is dropped, or by the operating system if hyperfine is terminated.
+//!
+//! Sessions that are started by the session holding the lock (e.g. when benchmarking
+//! hyperfine itself) inherit the lock through an environment variable, and do not wait.
+
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use colored::*;
+
+use crate::options::OutputStyleOption;
+
+/// How often a waiting session checks whether the lock has been released
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Set to the path of the lock for the child processes of the session that holds it
+pub const HELD_LOCK_VARIABLE: &str = "HYPERFINE_SESSION_LOCK";
+
+/// Whether a parent session already holds the lock
+pub fn is_held_by_parent() -> bool {
+    env::var_os(HELD_LOCK_VARIABLE).is_some_and(|path| !path.is_empty())
+}
+
+/// The lock file in the runtime directory of the user (or in the temporary directory), such
+/// that all sessions of the user share it
+pub fn default_path() -> PathBuf {
+    env::var_os("XDG_RUNTIME_DIR")
+        .filter(|directory| !directory.is_empty())
+        .map_or_else(env::temp_dir, PathBuf::from)
+        .join("hyperfine.lock")
+}
+
+/// Holds the lock until it is dropped
+#[derive(Debug)]
+pub struct SessionLock {
+    _file: File,
+}
+
+impl SessionLock {
+    /// Acquire the lock, waiting until the sessions that hold it have finished (or failing
+    /// if `fail_if_locked` is set)
+    pub fn acquire(
+        path: &Path,
+        output_style: OutputStyleOption,
+        fail_if_locked: bool,
+    ) -> Result<SessionLock> {
+        let mut is_waiting = false;
+        loop {
+            let file = try_lock(path).with_context(|| {
+                format!(
+                    "Could not lock '{}' (use '--no-lock' to run without the lock)",
+                    path.display()
+                )
+            })?;
+
+            if let Some(mut file) = file {
+                // The process ID tells waiting sessions which session holds the lock
+                file.set_len(0)?;
+                write!(file, "{}", std::process::id())?;
+                env::set_var(HELD_LOCK_VARIABLE, path);
+                return Ok(SessionLock { _file: file });
+            }
+
+            let holder = lock_holder(path).map_or(String::new(), |pid| format!(" (PID {pid})"));
+            if fail_if_locked {
+                bail!(
+                    "Another hyperfine session{holder} holds the lock file '{}' (see '--no-lock')",
+                    path.display()
+                );
+            }
+
+            // The message goes to stderr, as stdout may be an export (e.g. '--just-run')
+            if !is_waiting && output_style != OutputStyleOption::Disabled {
+                eprintln!(
+                    "{} for the other hyperfine session{holder} to finish (lock file '{}', \
+                     see '--no-lock')",
+                    "Waiting".bold(),
+                    path.display()
+                );
+            }
+            is_waiting = true;
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// The process ID of the session that holds the lock, if it can be read
+fn lock_holder(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Open and lock the file, or return `None` if another process holds the lock
+#[cfg(unix)]
+fn try_lock(path: &Path) -> io::Result<Option<File>> {
+    use std::os::unix::io::AsRawFd;
+
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?;
+
+    // SAFETY: the file descriptor is valid for the lifetime of 'file'. The lock belongs to
+    // the open file description, and is released when it is closed.
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        return Ok(Some(file));
+    }
+    let error = io::Error::last_os_error();
+    match error.kind() {
+        io::ErrorKind::WouldBlock => Ok(None),
+        _ => Err(error),
+    }
+}
+
+/// Open the file exclusively, or return `None` if another process has it open
+#[cfg(windows)]
+fn try_lock(path: &Path) -> io::Result<Option<File>> {
+    use std::os::windows::fs::OpenOptionsExt;
+    use windows_sys::Win32::Foundation::ERROR_SHARING_VIOLATION;
+
+    match OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .share_mode(0)
+        .open(path)
+    {
+        Ok(file) => Ok(Some(file)),
+        Err(error) if error.raw_os_error() == Some(ERROR_SHARING_VIOLATION as i32) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
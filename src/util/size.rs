@@ -0,0 +1,48 @@
+//! Parsing of human-friendly sizes like `512M` or `2GiB` for size-valued command line
+//! options. A plain number is interpreted as a number of bytes.
+
+use crate::quantity::{byte, Information};
+
+/// Parse a size such as `1048576`, `512K`, `512M`, `1.5G` or `2GiB`. The suffixes K, M, G
+/// and T denote binary multiples (optionally followed by 'iB' or 'B'), and are not case
+/// sensitive. Returns a description of the problem if the input is not a valid size.
+pub fn parse_size(input: &str) -> Result<Information, String> {
+    let input = input.trim();
+    let number_end = input
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(input.len());
+    let (number, unit) = (&input[..number_end], input[number_end..].trim_start());
+
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("'{input}' is not a valid size"))?;
+    let exponent = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 0,
+        "k" | "kb" | "kib" => 1,
+        "m" | "mb" | "mib" => 2,
+        "g" | "gb" | "gib" => 3,
+        "t" | "tb" | "tib" => 4,
+        _ => {
+            return Err(format!(
+                "unknown unit '{unit}' in '{input}' (use K, M, G or T)"
+            ))
+        }
+    };
+    Ok(Information::new::<byte>(value * 1024f64.powi(exponent)))
+}
+
+#[test]
+fn test_parse_size() {
+    let bytes = |input| parse_size(input).unwrap().get::<byte>();
+
+    assert_eq!(bytes("4096"), 4096.0);
+    assert_eq!(bytes("512K"), 512.0 * 1024.0);
+    assert_eq!(bytes("512M"), 512.0 * 1024.0 * 1024.0);
+    assert_eq!(bytes("1.5g"), 1.5 * 1024.0 * 1024.0 * 1024.0);
+    assert_eq!(bytes(" 2 GiB "), 2.0 * 1024.0 * 1024.0 * 1024.0);
+
+    assert!(parse_size("").is_err());
+    assert!(parse_size("-1M").is_err());
+    assert!(parse_size("M").is_err());
+    assert!(parse_size("1X").unwrap_err().contains("unknown unit 'X'"));
+}
@@ -0,0 +1,181 @@
+//! Delaying the start of the benchmark session until a given time of day or for a given
+//! duration (see '--start-at' and '--start-after'), e.g. to measure at a quiet time on a
+//! shared machine.
+
+use std::fmt;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use colored::*;
+
+use crate::options::OutputStyleOption;
+use crate::output::progress_bar::get_progress_bar;
+use crate::quantity::{second, Time};
+use crate::util::duration::format_duration;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// A time of the (local) day, with a precision of seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeOfDay {
+    hour: u32,
+    minute: u32,
+    second: u32,
+}
+
+impl TimeOfDay {
+    /// Parse a time of day like '02:00' or '23:30:15'
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let invalid = || format!("'{input}' is not a valid time of day (use HH:MM or HH:MM:SS)");
+        let parts = input
+            .trim()
+            .split(':')
+            .map(|part| part.parse::<u32>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (hour, minute, seconds) = match parts.as_slice() {
+            [hour, minute] => (*hour, *minute, 0),
+            [hour, minute, seconds] => (*hour, *minute, *seconds),
+            _ => return Err(invalid()),
+        };
+        if hour > 23 || minute > 59 || seconds > 59 {
+            return Err(invalid());
+        }
+        Ok(TimeOfDay {
+            hour,
+            minute,
+            second: seconds,
+        })
+    }
+
+    fn seconds_since_midnight(&self) -> u64 {
+        u64::from(self.hour * 3600 + self.minute * 60 + self.second)
+    }
+
+    /// The current local time
+    #[cfg(unix)]
+    fn now() -> Self {
+        // SAFETY: 'localtime_r' only writes to the given struct
+        let tm = unsafe {
+            let now = libc::time(std::ptr::null_mut());
+            let mut tm: libc::tm = std::mem::zeroed();
+            libc::localtime_r(&now, &mut tm);
+            tm
+        };
+        TimeOfDay {
+            hour: tm.tm_hour as u32,
+            minute: tm.tm_min as u32,
+            // Leap seconds
+            second: (tm.tm_sec as u32).min(59),
+        }
+    }
+
+    /// The current local time
+    #[cfg(windows)]
+    fn now() -> Self {
+        use windows_sys::Win32::Foundation::SYSTEMTIME;
+        use windows_sys::Win32::System::SystemInformation::GetLocalTime;
+
+        // SAFETY: 'GetLocalTime' only writes to the given struct
+        let time = unsafe {
+            let mut time: SYSTEMTIME = std::mem::zeroed();
+            GetLocalTime(&mut time);
+            time
+        };
+        TimeOfDay {
+            hour: u32::from(time.wHour),
+            minute: u32::from(time.wMinute),
+            second: u32::from(time.wSecond),
+        }
+    }
+
+    /// The time from `now` until the next occurrence of this time of day (today or
+    /// tomorrow)
+    fn until(&self, now: &TimeOfDay) -> Duration {
+        let seconds = (self.seconds_since_midnight() + SECONDS_PER_DAY
+            - now.seconds_since_midnight())
+            % SECONDS_PER_DAY;
+        Duration::from_secs(seconds)
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)?;
+        if self.second > 0 {
+            write!(f, ":{:02}", self.second)?;
+        }
+        Ok(())
+    }
+}
+
+/// When the benchmark session starts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StartDelay {
+    /// At the next occurrence of the given local time of day
+    At(TimeOfDay),
+
+    /// After the given duration
+    After(Time),
+}
+
+impl StartDelay {
+    /// Wait until the session may start, with a countdown
+    pub fn wait(&self, output_style: OutputStyleOption) {
+        let (delay, description) = match self {
+            StartDelay::At(time) => (
+                time.until(&TimeOfDay::now()),
+                format!("the start at {time}"),
+            ),
+            StartDelay::After(time) => (
+                Duration::from_secs_f64(time.get::<second>()),
+                "the start".to_string(),
+            ),
+        };
+        if delay.is_zero() {
+            return;
+        }
+
+        if output_style != OutputStyleOption::Disabled {
+            println!(
+                "{} {} until {description}",
+                "Waiting".bold(),
+                format_duration(Time::new::<second>(delay.as_secs_f64())).bold(),
+            );
+        }
+
+        let progress_bar = get_progress_bar(delay.as_secs(), "Waiting", output_style);
+        let deadline = Instant::now() + delay;
+        let start = Instant::now();
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            if remaining.is_zero() {
+                break;
+            }
+            thread::sleep(remaining.min(Duration::from_secs(1)));
+            progress_bar.set_position(start.elapsed().as_secs());
+        }
+        progress_bar.finish_and_clear();
+    }
+}
+
+#[test]
+fn test_time_of_day() {
+    let time = |input| TimeOfDay::parse(input).unwrap();
+
+    assert_eq!(time("02:00").to_string(), "02:00");
+    assert_eq!(time(" 23:30:15").to_string(), "23:30:15");
+    assert!(TimeOfDay::parse("24:00").is_err());
+    assert!(TimeOfDay::parse("12:60").is_err());
+    assert!(TimeOfDay::parse("12").is_err());
+    assert!(TimeOfDay::parse("12:00:00:00").is_err());
+    assert!(TimeOfDay::parse("2am").is_err());
+
+    assert_eq!(
+        time("02:00").until(&time("01:30")),
+        Duration::from_secs(30 * 60)
+    );
+    assert_eq!(
+        time("02:00").until(&time("23:00")),
+        Duration::from_secs(3 * 3600)
+    );
+    assert_eq!(time("02:00").until(&time("02:00")), Duration::ZERO);
+}
@@ -0,0 +1,83 @@
+//! Counting of the system calls of a command by running it under `strace -f -c` (see
+//! `--count-syscalls`)
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::{Context, Result};
+
+/// A path for the summary of a traced run, which is unique within this process
+pub fn summary_path() -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    env::temp_dir().join(format!("hyperfine-syscalls-{}-{n}", process::id()))
+}
+
+/// The given command, run under strace, which follows all child processes and writes a
+/// summary of their system calls to `summary`
+pub fn traced(command: &Command, summary: &Path) -> Command {
+    let mut traced = Command::new("strace");
+    traced
+        .args(["-f", "-c", "-o"])
+        .arg(summary)
+        .arg("--")
+        .arg(command.get_program())
+        .args(command.get_args());
+    for (key, value) in command.get_envs() {
+        match value {
+            Some(value) => traced.env(key, value),
+            None => traced.env_remove(key),
+        };
+    }
+    if let Some(directory) = command.get_current_dir() {
+        traced.current_dir(directory);
+    }
+    traced
+}
+
+/// Read (and remove) the summary that strace has written, and return the total number of
+/// system calls
+pub fn read_summary(summary: &Path) -> Result<u64> {
+    let content = fs::read_to_string(summary)
+        .with_context(|| format!("Could not read the strace summary '{}'", summary.display()))?;
+    let _ = fs::remove_file(summary);
+    total_calls(&content).with_context(|| {
+        format!(
+            "The strace summary '{}' does not contain the total number of calls",
+            summary.display()
+        )
+    })
+}
+
+/// The number of calls in the 'total' line of a summary, which has the columns '% time',
+/// 'seconds', 'usecs/call', 'calls', 'errors' (empty if there are none) and 'syscall'
+fn total_calls(summary: &str) -> Option<u64> {
+    summary.lines().find_map(|line| {
+        let fields: Vec<_> = line.split_whitespace().collect();
+        match fields.last() {
+            Some(&"total") if fields.len() >= 5 => fields[3].parse().ok(),
+            _ => None,
+        }
+    })
+}
+
+#[test]
+fn test_total_calls() {
+    let summary = "\
+% time     seconds  usecs/call     calls    errors syscall
+------ ----------- ----------- --------- --------- ----------------
+ 40.00    0.000040           4        10           mmap
+ 60.00    0.000060           2        25         3 openat
+------ ----------- ----------- --------- --------- ----------------
+100.00    0.000100           2        35         3 total
+";
+    assert_eq!(total_calls(summary), Some(35));
+
+    let summary = "100.00    0.000100           2        35           total\n";
+    assert_eq!(total_calls(summary), Some(35));
+
+    assert_eq!(total_calls(""), None);
+}
@@ -0,0 +1,87 @@
+//! Formatting of points in time as UTC date and time, without a dependency on a date library
+
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A point in time in UTC, with a precision of seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UtcDateTime {
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+}
+
+impl UtcDateTime {
+    pub fn from_system_time(time: SystemTime) -> Self {
+        let seconds = time
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        let days = (seconds / 86400) as i64;
+        let seconds_of_day = (seconds % 86400) as u32;
+
+        // Convert the days since the epoch to a date in the proleptic Gregorian calendar,
+        // see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        } as u32;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        UtcDateTime {
+            year,
+            month,
+            day,
+            hour: seconds_of_day / 3600,
+            minute: seconds_of_day / 60 % 60,
+            second: seconds_of_day % 60,
+        }
+    }
+
+    pub fn now() -> Self {
+        Self::from_system_time(SystemTime::now())
+    }
+
+    /// The date and time in a form that can be used in file names on all platforms,
+    /// e.g. '2024-06-01_12-30-05'
+    pub fn to_file_name(self) -> String {
+        format!(
+            "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+/// RFC 3339 format, e.g. '2024-06-01T12:30:05Z'
+impl fmt::Display for UtcDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+#[test]
+fn test_utc_date_time() {
+    use std::time::Duration;
+
+    let at = |seconds| UtcDateTime::from_system_time(UNIX_EPOCH + Duration::from_secs(seconds));
+
+    assert_eq!(at(0).to_string(), "1970-01-01T00:00:00Z");
+    assert_eq!(at(951_782_400).to_string(), "2000-02-29T00:00:00Z");
+    assert_eq!(at(1_717_245_005).to_string(), "2024-06-01T12:30:05Z");
+    assert_eq!(at(1_717_245_005).to_file_name(), "2024-06-01_12-30-05");
+}
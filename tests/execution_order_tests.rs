@@ -9,6 +9,7 @@ struct ExecutionOrderTest {
     cmd: assert_cmd::Command,
     expected_content: String,
     logfile_path: PathBuf,
+    expect_success: bool,
     #[allow(dead_code)]
     tempdir: TempDir,
 }
@@ -22,6 +23,7 @@ impl ExecutionOrderTest {
             cmd: hyperfine(),
             expected_content: String::new(),
             logfile_path,
+            expect_success: true,
             tempdir,
         }
     }
@@ -69,6 +71,21 @@ impl ExecutionOrderTest {
         self.command(output)
     }
 
+    fn teardown(&mut self, output: &str) -> &mut Self {
+        self.arg("--teardown");
+        self.command(output)
+    }
+
+    fn failing_command(&mut self, output: &str) -> &mut Self {
+        self.arg(format!("{} && exit 1", self.get_command(output)));
+        self
+    }
+
+    fn expect_failure(&mut self) -> &mut Self {
+        self.expect_success = false;
+        self
+    }
+
     fn expect_output(&mut self, output: &str) -> &mut Self {
         self.expected_content.push_str(output);
 
@@ -82,7 +99,11 @@ impl ExecutionOrderTest {
     }
 
     fn run(&mut self) {
-        self.cmd.assert().success();
+        if self.expect_success {
+            self.cmd.assert().success();
+        } else {
+            self.cmd.assert().failure();
+        }
 
         let mut f = File::open(&self.logfile_path).unwrap();
         let mut content = String::new();
@@ -258,6 +279,60 @@ fn cleanup_commands_are_executed_once_after_each_benchmark() {
         .run();
 }
 
+#[test]
+fn separate_cleanup_commands_are_executed_after_the_corresponding_benchmark() {
+    ExecutionOrderTest::new()
+        .arg("--runs=2")
+        .command("command 1")
+        .cleanup("cleanup 1")
+        .command("command 2")
+        .cleanup("cleanup 2")
+        .expect_output("command 1")
+        .expect_output("command 1")
+        .expect_output("cleanup 1")
+        .expect_output("command 2")
+        .expect_output("command 2")
+        .expect_output("cleanup 2")
+        .run();
+}
+
+#[test]
+fn teardown_commands_are_executed_once_after_each_benchmark() {
+    ExecutionOrderTest::new()
+        .arg("--runs=2")
+        .setup("setup")
+        .command("command 1")
+        .command("command 2")
+        .cleanup("cleanup")
+        .teardown("teardown")
+        .expect_output("setup")
+        .expect_output("command 1")
+        .expect_output("command 1")
+        .expect_output("cleanup")
+        .expect_output("teardown")
+        .expect_output("setup")
+        .expect_output("command 2")
+        .expect_output("command 2")
+        .expect_output("cleanup")
+        .expect_output("teardown")
+        .run();
+}
+
+#[test]
+fn teardown_commands_are_executed_if_the_benchmark_fails() {
+    ExecutionOrderTest::new()
+        .arg("--runs=2")
+        .setup("setup")
+        .failing_command("command")
+        .cleanup("cleanup")
+        .teardown("teardown")
+        .expect_failure()
+        .expect_output("setup")
+        .expect_output("command")
+        .expect_output("teardown")
+        .run();
+}
+
 #[test]
 fn setup_prepare_cleanup_combined() {
     ExecutionOrderTest::new()
@@ -416,6 +491,26 @@ fn reference_is_executed_separately_from_commands() {
         .run();
 }
 
+#[test]
+fn parameter_scan_values_are_executed_in_zigzag_order() {
+    ExecutionOrderTest::new()
+        .arg("--runs=1")
+        .arg("--parameter-scan")
+        .arg("n")
+        .arg("1")
+        .arg("5")
+        .arg("--scan-order=zigzag")
+        .reference("reference")
+        .command("value {n}")
+        .expect_output("reference")
+        .expect_output("value 1")
+        .expect_output("value 5")
+        .expect_output("value 2")
+        .expect_output("value 4")
+        .expect_output("value 3")
+        .run();
+}
+
 #[test]
 fn setup_prepare_reference_conclude_cleanup_combined() {
     ExecutionOrderTest::new()
@@ -561,3 +656,20 @@ fn setup_separate_prepare_reference_separate_conclude_cleanup_combined() {
         .expect_output("cleanup")
         .run();
 }
+
+#[cfg(unix)]
+#[test]
+fn run_information_is_passed_to_commands_and_preparation_commands() {
+    ExecutionOrderTest::new()
+        .arg("--warmup=1")
+        .arg("--runs=2")
+        .prepare("prepare $HYPERFINE_ITERATION")
+        .command("command $HYPERFINE_RUN_NUMBER")
+        .expect_output("prepare warmup-0")
+        .expect_output("command")
+        .expect_output("prepare 0")
+        .expect_output("command 0")
+        .expect_output("prepare 1")
+        .expect_output("command 1")
+        .run();
+}
@@ -1,5 +1,7 @@
 mod common;
 use common::hyperfine;
+#[cfg(unix)]
+use common::hyperfine_raw_command;
 
 use predicates::prelude::*;
 
@@ -154,6 +156,50 @@ fn fails_with_wrong_number_of_conclude_options() {
         ));
 }
 
+#[test]
+fn fails_with_wrong_number_of_cleanup_options() {
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--cleanup=echo a")
+        .arg("--cleanup=echo b")
+        .arg("echo a")
+        .arg("echo b")
+        .assert()
+        .success();
+
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--cleanup=echo a")
+        .arg("--cleanup=echo b")
+        .arg("echo a")
+        .arg("echo b")
+        .arg("echo c")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The '--cleanup' option has to be provided",
+        ));
+}
+
+#[test]
+fn accepts_human_friendly_durations() {
+    hyperfine_debug()
+        .arg("--min-benchmarking-time=1m30s")
+        .arg("--max-runs=2")
+        .arg("sleep 0.1")
+        .assert()
+        .success();
+
+    hyperfine_debug()
+        .arg("--min-benchmarking-time=3 weeks")
+        .arg("sleep 0.1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Could not read the duration argument to '--min-benchmarking-time'",
+        ));
+}
+
 #[test]
 fn fails_with_duplicate_parameter_names() {
     hyperfine()
@@ -258,6 +304,33 @@ fn fails_for_unknown_conclude_command() {
         ));
 }
 
+#[cfg(unix)]
+#[test]
+fn shows_error_output_of_failing_command() {
+    hyperfine()
+        .arg("--runs=2")
+        .arg("echo 'something went wrong' >&2; exit 3")
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("non-zero exit code 3")
+                .and(predicate::str::contains("Last lines of the error output"))
+                .and(predicate::str::contains("  something went wrong")),
+        );
+}
+
+#[cfg(unix)]
+#[test]
+fn does_not_wait_for_background_processes_that_hold_the_error_output() {
+    let start = std::time::Instant::now();
+    hyperfine()
+        .arg("--runs=2")
+        .arg("sleep 5 &")
+        .assert()
+        .success();
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+}
+
 #[cfg(unix)]
 #[test]
 fn can_run_failing_commands_with_ignore_failure_option() {
@@ -289,6 +362,45 @@ fn shows_output_of_benchmarked_command() {
         .stdout(predicate::str::contains("4fd47015").count(2));
 }
 
+#[test]
+fn routes_the_output_of_hooks_separately() {
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--command-name=dummy")
+        .arg("--show-output")
+        .arg("--hook-output=null")
+        .arg("--prepare=echo 9c1b72e4")
+        .arg("echo 4fd47015")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("4fd47015").count(2))
+        .stdout(predicate::str::contains("9c1b72e4").not());
+
+    let tempdir = tempfile::tempdir().unwrap();
+    let log = tempdir.path().join("hooks.log");
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--setup=echo setup")
+        .arg("--prepare=echo prepare")
+        .arg(format!("--hook-output=file:{}", log.display()))
+        .arg("echo benchmark")
+        .assert()
+        .success();
+    let log = std::fs::read_to_string(log).unwrap();
+    assert_eq!(log.matches("setup").count(), 1);
+    assert_eq!(log.matches("prepare").count(), 2);
+    assert!(!log.contains("benchmark"));
+
+    hyperfine()
+        .arg("--hook-output=stdout")
+        .arg("echo benchmark")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Unknown hook output policy 'stdout'",
+        ));
+}
+
 #[test]
 fn runs_commands_using_user_defined_shell() {
     hyperfine()
@@ -467,6 +579,44 @@ fn shows_benchmark_comparison_with_relative_times() {
         );
 }
 
+#[test]
+fn compares_commands_only_within_their_group() {
+    hyperfine_debug()
+        .arg("--export-markdown=-")
+        .arg("--group=io")
+        .arg("--")
+        .arg("sleep 1.0")
+        .arg("sleep 2.0")
+        .arg("--group")
+        .arg("cpu")
+        .arg("--")
+        .arg("sleep 3.0")
+        .arg("sleep 6.0")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Summary of group 'io'")
+                .and(predicate::str::contains("Summary of group 'cpu'"))
+                .and(predicate::str::contains("2.00 ± 0.00 times faster").count(2))
+                .and(predicate::str::contains("3.00 ± 0.00 times faster").not())
+                .and(predicate::str::contains(
+                    "| cpu | `sleep 6.0` | 6.000 ± 0.000 | 6.000 | 6.000 | 2.00 ± 0.00 |",
+                )),
+        );
+
+    hyperfine_debug()
+        .arg("--group=io")
+        .arg("sleep 1.0")
+        .arg("--")
+        .arg("sleep 2.0")
+        .arg("--group")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "'--group' requires the name of the group",
+        ));
+}
+
 #[test]
 fn shows_benchmark_comparison_with_same_time() {
     hyperfine_debug()
@@ -580,6 +730,70 @@ fn exports_intermediate_results_to_file() {
     assert!(contents.contains("true"));
 }
 
+#[test]
+fn reads_commands_from_stdin() {
+    hyperfine_debug()
+        .arg("--commands-from-file=-")
+        .arg("sleep 1")
+        .write_stdin("# some comment\nsleep 2\n\nshort\tsleep 0.5\n")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Benchmark 1: sleep 1")
+                .and(predicate::str::contains("Benchmark 2: sleep 2"))
+                .and(predicate::str::contains("Benchmark 3: short")),
+        );
+}
+
+#[test]
+#[cfg(unix)]
+fn benchmarks_multi_line_scripts() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let log = tempdir.path().join("log");
+    let script = tempdir.path().join("script.sh");
+    std::fs::write(
+        &script,
+        format!(
+            "# A comment\nfor word in a b; do\n  echo $word >> '{0}'\ndone\necho c >> '{0}'\n",
+            log.display()
+        ),
+    )
+    .unwrap();
+
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--script")
+        .arg(&script)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Benchmark 1: {}",
+            script.display()
+        )));
+    assert_eq!(std::fs::read_to_string(&log).unwrap(), "a\nb\nc\n");
+
+    hyperfine_debug()
+        .arg("sleep 1")
+        .arg("--script")
+        .write_stdin("sleep 2\n")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Benchmark 1: sleep 1")
+                .and(predicate::str::contains("Benchmark 2: <stdin>")),
+        );
+
+    hyperfine()
+        .arg("--shell=none")
+        .arg("--script")
+        .arg(&script)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "can not be benchmarked without a shell",
+        ));
+}
+
 #[test]
 fn unused_parameters_are_shown_in_benchmark_name() {
     hyperfine()
@@ -621,20 +835,2526 @@ fn speed_comparison_sort_order() {
         ));
 }
 
-#[cfg(windows)]
 #[test]
-fn windows_quote_args() {
+fn substitutes_defined_variables() {
+    hyperfine_debug()
+        .arg("--define=time=1.5")
+        .arg("--define=name=fast")
+        .arg("--command-name={name}")
+        .arg("--export-markdown=-")
+        .arg("sleep {time}")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Benchmark 1: fast")
+                .and(predicate::str::contains("| `fast` | 1.500 ± 0.000 |")),
+        );
+}
+
+#[test]
+fn discarded_runs_are_excluded_from_statistics() {
+    hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--discard-first=1")
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2 runs, 1 discarded"));
+}
+
+#[test]
+fn shows_output_sizes_in_verbose_mode() {
     hyperfine()
-        .arg("more \"example_input_file.txt\"")
+        .arg("--runs=2")
+        .arg("--output=pipe")
+        .arg("--verbose")
+        .arg("echo hello")
         .assert()
-        .success();
+        .success()
+        .stdout(
+            predicate::str::contains("Output (mean):")
+                .and(predicate::str::contains("B stdout, 0 B stderr")),
+        );
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("echo hello")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Output (mean):").not());
 }
 
-#[cfg(windows)]
 #[test]
-fn windows_quote_before_quote_args() {
+fn shows_spawn_latency_in_verbose_mode() {
     hyperfine()
-        .arg("dir \"..\\src\\\" \"..\\tests\\\"")
+        .arg("--runs=2")
+        .arg("--verbose")
+        .arg("echo hello")
         .assert()
-        .success();
+        .success()
+        .stdout(
+            predicate::str::contains("Spawn (mean):")
+                .and(predicate::str::contains("% of the run time")),
+        );
+}
+
+#[test]
+fn shows_the_order_of_the_runs_in_verbose_mode() {
+    hyperfine_debug()
+        .arg("--runs=4")
+        .arg("--verbose")
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "fastest #1 (early), median #2 (middle), slowest #4 (late) of 4 runs",
+        ));
+
+    hyperfine_debug()
+        .arg("--runs=4")
+        .arg("--export-json=-")
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "\"run_indices\": {\n        \"min\": 0,\n        \"median\": 1,\n        \"max\": 3\n      }",
+        ));
+}
+
+#[test]
+fn shows_page_faults_in_verbose_mode() {
+    hyperfine_debug()
+        .arg("--runs=2")
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Page faults").not());
+
+    hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--verbose")
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Page faults:"));
+}
+
+#[test]
+fn reports_equivalent_commands() {
+    hyperfine_debug()
+        .arg("--highlight-threshold=5%")
+        .arg("--command-name=a")
+        .arg("--command-name=b")
+        .arg("--command-name=c")
+        .arg("sleep 1")
+        .arg("sleep 1")
+        .arg("sleep 2")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Equivalent (1.00 ± 0.00) to b")
+                .and(predicate::str::contains("a and b are equivalent"))
+                .and(predicate::str::contains("2.00 ± 0.00 times faster than c")),
+        );
+
+    hyperfine_debug()
+        .arg("--highlight-threshold=0%")
+        .arg("sleep 1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid value '0%' for '--highlight-threshold'",
+        ));
+}
+
+#[test]
+fn trims_fastest_and_slowest_runs() {
+    hyperfine_debug()
+        .arg("--runs=10")
+        .arg("--trim=10%")
+        .arg("--export-json=-")
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("10 runs, 1 trimmed at each end")
+                .and(predicate::str::contains("\"trim\": 0.1")),
+        );
+
+    hyperfine_debug()
+        .arg("--trim=50%")
+        .arg("sleep 1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid value '50%' for '--trim'"));
+}
+
+#[test]
+fn reports_a_single_run_that_is_left_after_trimming() {
+    hyperfine_debug()
+        .arg("--runs=3")
+        .arg("--trim=40%")
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Time (abs")
+                .and(predicate::str::contains("3 runs, 1 trimmed at each end")),
+        );
+}
+
+#[test]
+fn records_the_execution_order_of_parameter_scans() {
+    let output = hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--parameter-scan")
+        .arg("n")
+        .arg("1")
+        .arg("3")
+        .arg("--scan-order=zigzag")
+        .arg("--export-json=-")
+        .arg("sleep {n}")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json = String::from_utf8(output).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json[json.find("{\n").unwrap()..]).unwrap();
+
+    let results = json["results"].as_array().unwrap();
+    let commands: Vec<_> = results.iter().map(|r| r["command"].clone()).collect();
+    let order: Vec<_> = results
+        .iter()
+        .map(|r| r["execution_order"].clone())
+        .collect();
+    assert_eq!(commands, ["sleep 1", "sleep 2", "sleep 3"]);
+    assert_eq!(order, [0, 2, 1]);
+}
+
+#[test]
+fn shuffles_the_benchmarks_reproducibly() {
+    let export = |shuffle: &str| {
+        let output = hyperfine_debug()
+            .arg("--runs=2")
+            .arg(shuffle)
+            .arg("--export-json=-")
+            .args(["sleep 1", "sleep 2", "sleep 3", "sleep 4", "sleep 5"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "Shuffled the order of the benchmarks with seed",
+            ))
+            .get_output()
+            .stdout
+            .clone();
+        let json = String::from_utf8(output).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&json[json.find("{\n").unwrap()..]).unwrap();
+        let order: Vec<_> = json["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["execution_order"].as_u64().unwrap())
+            .collect();
+        (order, json["invocation"]["shuffle_seed"].as_u64().unwrap())
+    };
+
+    let (order, seed) = export("--shuffle");
+    let mut positions = order.clone();
+    positions.sort();
+    assert_eq!(positions, [0, 1, 2, 3, 4]);
+
+    assert_eq!(export(&format!("--shuffle={seed}")), (order, seed));
+}
+
+#[test]
+fn skips_runs_of_commands_that_are_too_slow() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let json = tempdir.path().join("results.json");
+    let markdown = tempdir.path().join("results.md");
+    let csv = tempdir.path().join("results.csv");
+
+    hyperfine_debug()
+        .arg("--runs=5")
+        .arg("--abort-if-slower-than=2s")
+        .arg(format!("--export-json={}", json.display()))
+        .arg(format!("--export-markdown={}", markdown.display()))
+        .arg(format!("--export-csv={}", csv.display()))
+        .arg("sleep 1")
+        .arg("sleep 3")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "The first run took 3.000 s, which exceeds the limit of 2.000 s",
+        ));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(json).unwrap()).unwrap();
+    assert_eq!(
+        json["results"][0]["measurements"].as_array().unwrap().len(),
+        5
+    );
+    assert!(json["results"][0].get("too_slow").is_none());
+    assert_eq!(
+        json["results"][1]["measurements"].as_array().unwrap().len(),
+        1
+    );
+    assert_eq!(json["results"][1]["too_slow"], true);
+
+    let markdown = std::fs::read_to_string(markdown).unwrap();
+    assert!(markdown.contains("| `sleep 3` (too slow, skipped) |"));
+
+    let csv = std::fs::read_to_string(csv).unwrap();
+    let lines: Vec<_> = csv.lines().collect();
+    assert!(lines[0].ends_with(",too_slow"));
+    assert!(lines[1].ends_with(",false"));
+    assert!(lines[2].ends_with(",true"));
+
+    hyperfine_debug()
+        .arg("--abort-if-slower-than=soon")
+        .arg("sleep 1")
+        .assert()
+        .failure();
+}
+
+#[cfg(unix)]
+#[test]
+fn stops_benchmarks_that_exceed_their_deadline() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let json = tempdir.path().join("results.json");
+    let csv = tempdir.path().join("results.csv");
+
+    hyperfine()
+        .arg("--shell=none")
+        .arg("--runs=1000")
+        .arg("--benchmark-deadline=200ms")
+        .arg(format!("--export-json={}", json.display()))
+        .arg(format!("--export-csv={}", csv.display()))
+        .arg("sleep 0.05")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "The benchmark exceeded its deadline of 200.0 ms",
+        ));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(json).unwrap()).unwrap();
+    let num_runs = json["results"][0]["measurements"].as_array().unwrap().len();
+    assert!((1..1000).contains(&num_runs));
+    assert_eq!(json["results"][0]["deadline_exceeded"], true);
+
+    let csv = std::fs::read_to_string(csv).unwrap();
+    assert!(csv.lines().next().unwrap().ends_with(",deadline_exceeded"));
+}
+
+#[cfg(unix)]
+#[test]
+fn stops_benchmarks_that_are_too_noisy() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let json = tempdir.path().join("results.json");
+    let toggle = tempdir.path().join("toggle");
+
+    // Every other run sleeps, such that the standard deviation is as large as the mean
+    hyperfine()
+        .arg("--runs=100")
+        .arg("--abort-on-noise=25%")
+        .arg(format!("--export-json={}", json.display()))
+        .arg(format!(
+            "if [ -e {0} ]; then rm {0}; sleep 0.05; else touch {0}; fi",
+            toggle.display()
+        ))
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("(see '--abort-on-noise')"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(json).unwrap()).unwrap();
+    let num_runs = json["results"][0]["measurements"].as_array().unwrap().len();
+    assert_eq!(num_runs, 5);
+    assert_eq!(json["results"][0]["too_noisy"], true);
+}
+
+#[cfg(unix)]
+#[test]
+fn repeats_failing_runs_until_enough_runs_succeeded() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let json = tempdir.path().join("results.json");
+    let toggle = tempdir.path().join("toggle");
+
+    // Every other run fails, starting with the first one
+    let command = format!(
+        "if [ -e {0} ]; then rm {0}; else touch {0}; exit 1; fi",
+        toggle.display()
+    );
+
+    hyperfine()
+        .arg("--ignore-failure")
+        .arg("--successful-runs=3")
+        .arg(format!("--export-json={}", json.display()))
+        .arg(&command)
+        .assert()
+        .success();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(json).unwrap()).unwrap();
+    let runs = json["results"][0]["measurements"].as_array().unwrap();
+    assert_eq!(runs.len(), 6);
+    for (i, run) in runs.iter().enumerate() {
+        let failed = i % 2 == 0;
+        assert_eq!(run["exit_code"], if failed { 1 } else { 0 });
+        assert_eq!(run.get("failed_attempt").is_some(), failed);
+        assert!(run.get("discarded").is_none());
+    }
+
+    hyperfine()
+        .arg("--ignore-failure")
+        .arg("--successful-runs=3")
+        .arg("--max-attempts=4")
+        .arg(&command)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Only 2 of 3 runs of 'if [ -e"));
+}
+
+#[test]
+fn rejects_invalid_noise_thresholds() {
+    hyperfine_debug()
+        .arg("--abort-on-noise=-5%")
+        .arg("sleep 0.1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid value '-5%' for '--abort-on-noise'",
+        ));
+}
+
+#[test]
+fn uses_dedicated_exit_codes_for_failures() {
+    hyperfine()
+        .arg("--runs=1")
+        .arg("echo a && exit 1")
+        .assert()
+        .code(3);
+
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--setup=exit 1")
+        .arg("echo a")
+        .assert()
+        .code(4);
+}
+
+#[test]
+fn fails_on_regressions_and_outliers_if_requested() {
+    hyperfine_debug()
+        .arg("--fail-on=regression")
+        .arg("sleep 2")
+        .arg("sleep 1")
+        .assert()
+        .success();
+
+    hyperfine_debug()
+        .arg("--fail-on=regression")
+        .arg("sleep 1")
+        .arg("sleep 2")
+        .assert()
+        .code(5)
+        .stderr(predicate::str::contains(
+            "Regression detected: 'sleep 2' is 2.00 times slower than 'sleep 1'",
+        ));
+
+    hyperfine_debug()
+        .arg("--fail-on=regression,outliers")
+        .arg("--reference=sleep 3")
+        .arg("sleep 1")
+        .arg("sleep 2")
+        .assert()
+        .success();
+}
+
+#[test]
+fn generates_parameter_values_from_expressions() {
+    hyperfine_debug()
+        .arg("--parameter-expr")
+        .arg("n")
+        .arg("2^k for k in 0..3")
+        .arg("sleep {n}")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Benchmark 1: sleep 1")
+                .and(predicate::str::contains("Benchmark 2: sleep 2"))
+                .and(predicate::str::contains("Benchmark 3: sleep 4")),
+        );
+
+    hyperfine_debug()
+        .arg("--parameter-expr")
+        .arg("n")
+        .arg("2^k")
+        .arg("sleep {n}")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid parameter expression: expected 'for'",
+        ));
+}
+
+#[test]
+fn runs_benchmarks_concurrently() {
+    hyperfine_debug()
+        .arg("--jobs=2")
+        .arg("--export-markdown=-")
+        .arg("sleep 2")
+        .arg("sleep 1")
+        .arg("sleep 3")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Benchmark 3: sleep 3")
+                .and(predicate::str::contains(
+                    "| `sleep 2` | 2.000 ± 0.000 | 2.000 | 2.000 | 2.00 ± 0.00 |\n\
+                     | `sleep 1` | 1.000 ± 0.000 | 1.000 | 1.000 | 1.00 |\n\
+                     | `sleep 3` |",
+                ))
+                .and(predicate::str::contains("sleep 1 ran")),
+        );
+}
+
+#[test]
+fn exports_the_selected_markup_columns() {
+    hyperfine_debug()
+        .arg("--export-markdown=-")
+        .arg("--export-columns=relative,command,median,user,system")
+        .arg("sleep 2")
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "| Relative | Command | Median [s] | User [s] | System [s] |\n\
+             |---:|:---|---:|---:|---:|\n\
+             | 2.00 ± 0.00 | `sleep 2` | 2.000 |",
+        ));
+
+    hyperfine_debug()
+        .arg("--export-columns=command,speed")
+        .arg("sleep 1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value 'speed'"));
+}
+
+#[test]
+fn formats_exports_with_the_given_precision() {
+    hyperfine_debug()
+        .arg("--precision=2,relative=1")
+        .arg("--export-csv=-")
+        .arg("--export-markdown=-")
+        .arg("sleep 1")
+        .arg("sleep 2")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("sleep 1,1.00,0.00,1.00,").and(predicate::str::contains(
+                "| `sleep 2` | 2.00 ± 0.00 | 2.00 | 2.00 | 2.0 ± 0.0 |",
+            )),
+        );
+
+    hyperfine_debug()
+        .arg("--precision=speed=2")
+        .arg("sleep 1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid value 'speed=2' for '--precision'",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn sources_a_file_before_each_command() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let rcfile = tempdir.path().join("rcfile.sh");
+    std::fs::write(&rcfile, "greet() { echo \"hello $1\"; }\n").unwrap();
+
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--show-output")
+        .arg(format!("--source={}", rcfile.display()))
+        .arg("greet world")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("hello world"));
+
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--source=does-not-exist.sh")
+        .arg("greet world")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The file 'does-not-exist.sh' specified as '--source' does not exist",
+        ));
+}
+
+#[test]
+fn collects_all_results_in_an_output_directory() {
+    let tempdir = tempfile::tempdir().unwrap();
+
+    hyperfine_debug()
+        .arg(format!(
+            "--output-dir={}",
+            tempdir.path().join("bench-{timestamp}").display()
+        ))
+        .arg("sleep 1")
+        .assert()
+        .success();
+
+    let entries: Vec<_> = std::fs::read_dir(tempdir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(entries.len(), 1);
+    let directory = &entries[0];
+    let name = directory
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .into_owned();
+    assert!(name.starts_with("bench-20") && !name.contains('{'));
+
+    let manifest: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(directory.join("manifest.json")).unwrap())
+            .unwrap();
+    let files = manifest["files"].as_array().unwrap();
+    assert!(files.len() >= 10);
+    for file in files {
+        assert!(directory.join(file["path"].as_str().unwrap()).exists());
+    }
+
+    let markdown = std::fs::read_to_string(directory.join("results.md")).unwrap();
+    assert!(markdown.contains("| `sleep 1` | 1.000 ± 0.000 |"));
+}
+
+#[test]
+fn writes_plotting_scripts_with_the_results() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let plots = tempdir.path().join("plots");
+
+    hyperfine_debug()
+        .arg(format!("--export-plots={}", plots.display()))
+        .arg("--runs=2")
+        .arg("sleep 1")
+        .arg("sleep 2")
+        .assert()
+        .success();
+
+    for name in [
+        "plot_whisker.py",
+        "plot_histogram.py",
+        "plot_progression.py",
+    ] {
+        let script = std::fs::read_to_string(plots.join(name)).unwrap();
+        assert!(script.contains("\"command\": \"sleep 2\""));
+        assert!(script.contains("\"times\": [\n      2.0,\n      2.0\n    ]"));
+    }
+}
+
+#[test]
+fn reuses_cached_results() {
+    let cache = tempfile::tempdir().unwrap();
+    let cache_arg = format!("--cache={}", cache.path().display());
+
+    hyperfine_debug()
+        .arg(&cache_arg)
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Using cached result").not());
+
+    hyperfine_debug()
+        .arg(&cache_arg)
+        .arg("sleep 1")
+        .arg("sleep 2")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Benchmark 1: sleep 1\n  Using cached result")
+                .and(predicate::str::contains("Benchmark 2: sleep 2\n  Time")),
+        );
+
+    // Results measured with other options are not reused
+    hyperfine_debug()
+        .arg(&cache_arg)
+        .arg("--runs=3")
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Using cached result").not());
+
+    hyperfine_debug()
+        .arg(&cache_arg)
+        .arg("--cache-refresh=sleep 1")
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Using cached result").not());
+}
+
+#[test]
+fn skips_benchmarks_if_the_watched_files_are_unchanged() {
+    let cache = tempfile::tempdir().unwrap();
+    let sources = tempfile::tempdir().unwrap();
+    std::fs::write(sources.path().join("main.c"), "int main() {}").unwrap();
+    let benchmark = || {
+        hyperfine_debug()
+            .arg(format!("--cache={}", cache.path().display()))
+            .arg(format!(
+                "--skip-if-unchanged={}/*.c",
+                sources.path().display()
+            ))
+            .arg("sleep 1")
+            .assert()
+            .success()
+    };
+
+    benchmark().stdout(predicate::str::contains("Using cached result").not());
+    benchmark().stdout(predicate::str::contains("Using cached result"));
+
+    std::fs::write(sources.path().join("util.c"), "").unwrap();
+    benchmark().stdout(predicate::str::contains("Using cached result").not());
+    benchmark().stdout(predicate::str::contains("Using cached result"));
+}
+
+#[test]
+fn exports_shell_spawning_time() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let export = |args: &[&str]| {
+        let path = tempdir.path().join("results.json");
+        hyperfine()
+            .arg("--runs=2")
+            .arg(format!("--export-json={}", path.display()))
+            .args(args)
+            .arg("echo")
+            .assert()
+            .success();
+        let content = std::fs::read_to_string(path).unwrap();
+        serde_json::from_str::<serde_json::Value>(&content).unwrap()["results"][0].clone()
+    };
+
+    let result = export(&[]);
+    assert_eq!(result["shell_spawning_time"]["subtracted"], true);
+
+    let result = export(&["--no-shell-correction"]);
+    assert_eq!(result["shell_spawning_time"]["subtracted"], false);
+    assert!(
+        result["shell_spawning_time"]["time_wall_clock"]["value"]
+            .as_f64()
+            .unwrap()
+            > 0.0
+    );
+
+    let result = export(&["--shell=none"]);
+    assert!(result.get("shell_spawning_time").is_none());
+}
+
+#[test]
+fn quiet_mode_only_shows_summary() {
+    hyperfine_debug()
+        .arg("--quiet")
+        .arg("sleep 1")
+        .arg("sleep 2")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Summary")
+                .and(predicate::str::contains("Benchmark 1").not())
+                .and(predicate::str::contains("Time (mean").not()),
+        );
+}
+
+#[test]
+fn verbose_mode_shows_scheduling_decisions() {
+    hyperfine_debug()
+        .arg("--verbose")
+        .arg("--runs=3")
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "[verbose] Performing 3 runs of 'sleep 1'",
+        ))
+        .stderr(predicate::str::contains("[debug]").not());
+}
+
+#[test]
+fn accepts_exit_codes_marked_as_ok() {
+    hyperfine().arg("--runs=2").arg("exit 3").assert().failure();
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--exit-code-ok=1,3")
+        .arg("exit 3")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("non-zero exit code").not());
+}
+
+#[cfg(unix)]
+#[test]
+fn repeats_runs_with_skip_exit_codes() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let marker = tempdir.path().join("marker");
+    let export = tempdir.path().join("results.json");
+
+    // The first run exits with the skip code, all other runs succeed
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--exit-code-skip=77")
+        .arg(format!("--export-json={}", export.display()))
+        .arg(format!(
+            "test -e {marker} || {{ touch {marker}; exit 77; }}",
+            marker = marker.display()
+        ))
+        .assert()
+        .success();
+
+    let results: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(export).unwrap()).unwrap();
+    let measurements = results["results"][0]["measurements"].as_array().unwrap();
+    assert_eq!(measurements.len(), 2);
+    assert!(measurements.iter().all(|m| m["exit_code"] == 0));
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--exit-code-skip=77")
+        .arg("exit 77")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("100 times in a row"));
+}
+
+#[test]
+fn merges_json_exports() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let export = |name: &str, commands: &[&str]| {
+        let path = tempdir.path().join(name);
+        hyperfine_debug()
+            .arg("--runs=2")
+            .arg(format!("--export-json={}", path.display()))
+            .args(commands)
+            .assert()
+            .success();
+        path
+    };
+    let a = export("a.json", &["sleep 1", "sleep 2"]);
+    let b = export("b.json", &["sleep 1"]);
+
+    hyperfine()
+        .arg("merge")
+        .arg(&a)
+        .arg(&b)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"command\": \"sleep 1\"").count(2));
+
+    let merged = tempdir.path().join("merged.json");
+    hyperfine()
+        .arg("merge")
+        .arg("--pool")
+        .arg(&a)
+        .arg(&b)
+        .arg("-o")
+        .arg(&merged)
+        .assert()
+        .success();
+
+    let merged: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(merged).unwrap()).unwrap();
+    let results = merged["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["command"], "sleep 1");
+    assert_eq!(results[0]["measurements"].as_array().unwrap().len(), 4);
+}
+
+#[test]
+fn compares_the_results_of_two_parameter_scans() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let export = |name: &str, times: &[(u32, f64)]| {
+        let results: Vec<_> = times
+            .iter()
+            .map(|(n, time)| {
+                serde_json::json!({
+                    "command": format!("run {n}"),
+                    "parameters": { "n": { "value": n.to_string() } },
+                    "measurements": [{ "time_wall_clock": time }]
+                })
+            })
+            .collect();
+        let path = tempdir.path().join(name);
+        std::fs::write(&path, serde_json::json!({ "results": results }).to_string()).unwrap();
+        path
+    };
+    let baseline = export("baseline.json", &[(1, 1.0), (2, 2.0), (10, 1.0)]);
+    let candidate = export("candidate.json", &[(1, 1.0), (2, 3.0), (20, 1.0)]);
+    let vega = tempdir.path().join("heatmap.json");
+    let html = tempdir.path().join("heatmap.html");
+
+    hyperfine()
+        .arg("compare")
+        .arg(&baseline)
+        .arg(&candidate)
+        .arg("--export-vega")
+        .arg(&vega)
+        .arg("--export-html")
+        .arg(&html)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+50.0 %"))
+        .stdout(predicate::str::contains(
+            "Largest regression: n = 2 (+50.0 %)",
+        ))
+        .stdout(predicate::str::contains("1 of the benchmarks in"));
+
+    let specification: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(vega).unwrap()).unwrap();
+    assert_eq!(
+        specification["encoding"]["x"]["sort"],
+        serde_json::json!(["1", "2"])
+    );
+    assert!(std::fs::read_to_string(html).unwrap().contains("vegaEmbed"));
+
+    hyperfine()
+        .arg("compare")
+        .arg(&baseline)
+        .arg(export("other.json", &[(3, 1.0)]))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("None of the benchmarks"));
+}
+
+#[test]
+fn shortens_long_commands_to_the_terminal_width() {
+    let long_command = "sleep 1.0000000000000000000000000000000000000001";
+
+    hyperfine_debug()
+        .env("COLUMNS", "40")
+        .arg("--runs=2")
+        .arg(long_command)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Benchmark 1: sleep 1.000000000…000000001\n",
+        ));
+
+    hyperfine_debug()
+        .env("COLUMNS", "40")
+        .arg("--runs=2")
+        .arg("--command-display=full")
+        .arg(long_command)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Benchmark 1: {long_command}\n"
+        )));
+
+    hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--command-display=name-only")
+        .arg("--parameter-list")
+        .arg("n")
+        .arg("1,2")
+        .arg("sleep {n}")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Benchmark 2: sleep (n = 2)")
+                .and(predicate::str::contains("sleep (n = 1) ran")),
+        );
+}
+
+#[test]
+fn exports_a_command_line_to_reproduce_each_benchmark() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let json = tempdir.path().join("results.json");
+
+    hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--runs=3")
+        .arg(format!("--export-json={}", json.display()))
+        .arg("--parameter-list")
+        .arg("n")
+        .arg("1,2")
+        .arg("sleep {n}")
+        .assert()
+        .success();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(json).unwrap()).unwrap();
+    assert_eq!(
+        json["results"][1]["reproduce"],
+        "hyperfine --runs=3 --debug-mode 'sleep 2'"
+    );
+
+    let reproduce = json["results"][1]["reproduce"].as_str().unwrap();
+    hyperfine()
+        .args(&shell_words::split(reproduce).unwrap()[1..])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Benchmark 1: sleep 2")
+                .and(predicate::str::contains("3 runs")),
+        );
+}
+
+#[cfg(unix)]
+#[test]
+fn compresses_exports_based_on_their_extension() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let export = tempdir.path().join("results.json.gz");
+    hyperfine_debug()
+        .arg("--runs=2")
+        .arg(format!("--export-json={}", export.display()))
+        .arg("sleep 1")
+        .assert()
+        .success();
+
+    let content = std::fs::read(&export).unwrap();
+    assert_eq!(content[..2], [0x1f, 0x8b]);
+
+    hyperfine()
+        .arg("merge")
+        .arg(&export)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"command\": \"sleep 1\""));
+
+    // The compression tools are not linked, but need to be installed
+    let empty_directory = tempfile::tempdir().unwrap();
+    hyperfine_debug()
+        .env("PATH", empty_directory.path())
+        .arg("--runs=2")
+        .arg(format!("--export-json={}", export.display()))
+        .arg("sleep 1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "'gzip' was not found. Files ending with '.gz' are (de)compressed with the external \
+             'gzip' program, which needs to be installed.",
+        ));
+}
+
+#[cfg(windows)]
+#[test]
+fn windows_quote_args() {
+    hyperfine()
+        .arg("more \"example_input_file.txt\"")
+        .assert()
+        .success();
+}
+
+#[cfg(windows)]
+#[test]
+fn windows_quote_before_quote_args() {
+    hyperfine()
+        .arg("dir \"..\\src\\\" \"..\\tests\\\"")
+        .assert()
+        .success();
+}
+
+#[test]
+fn signs_json_exports() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let key = tempdir.path().join("key.pem");
+    let public_key = tempdir.path().join("public.pem");
+    let export = tempdir.path().join("results.json");
+
+    hyperfine_debug()
+        .arg(format!("--sign-key={}", key.display()))
+        .arg(format!("--export-json={}", export.display()))
+        .arg("sleep 0.1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("does not exist"));
+
+    let openssl = |args: &[&std::ffi::OsStr]| {
+        std::process::Command::new("openssl")
+            .args(args)
+            .output()
+            .is_ok_and(|output| output.status.success())
+    };
+    let generated = openssl(&[
+        "genpkey".as_ref(),
+        "-algorithm".as_ref(),
+        "EC".as_ref(),
+        "-pkeyopt".as_ref(),
+        "ec_paramgen_curve:P-256".as_ref(),
+        "-out".as_ref(),
+        key.as_os_str(),
+    ]) && openssl(&[
+        "pkey".as_ref(),
+        "-in".as_ref(),
+        key.as_os_str(),
+        "-pubout".as_ref(),
+        "-out".as_ref(),
+        public_key.as_os_str(),
+    ]);
+    if !generated {
+        // OpenSSL is not available on this system
+        return;
+    }
+
+    hyperfine_debug()
+        .arg(format!("--sign-key={}", key.display()))
+        .arg("--export-json=-")
+        .arg("sleep 0.1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "'--sign-key' requires a JSON export to a file",
+        ));
+
+    hyperfine_debug()
+        .arg(format!("--sign-key={}", key.display()))
+        .arg(format!("--export-json={}", export.display()))
+        .arg("sleep 0.1")
+        .assert()
+        .success();
+
+    let content = std::fs::read_to_string(&export).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+    assert!(json["system_fingerprint"]["id"].is_string());
+
+    let signature = tempdir.path().join("results.json.sig");
+    assert!(openssl(&[
+        "dgst".as_ref(),
+        "-sha256".as_ref(),
+        "-verify".as_ref(),
+        public_key.as_os_str(),
+        "-signature".as_ref(),
+        signature.as_os_str(),
+        export.as_os_str(),
+    ]));
+
+    std::fs::write(&export, content.replace("sleep 0.1", "sleep 0.2")).unwrap();
+    assert!(!openssl(&[
+        "dgst".as_ref(),
+        "-sha256".as_ref(),
+        "-verify".as_ref(),
+        public_key.as_os_str(),
+        "-signature".as_ref(),
+        signature.as_os_str(),
+        export.as_os_str(),
+    ]));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn benchmarks_an_attached_process() {
+    let mut process = std::process::Command::new("sleep")
+        .arg("30")
+        .spawn()
+        .unwrap();
+
+    hyperfine()
+        .arg(format!("--attach={}", process.id()))
+        .arg("--attach-duration=20ms")
+        .arg("--runs=2")
+        .arg("--command-name=server")
+        .arg("--export-json=-")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Benchmark 1: server")
+                .and(predicate::str::contains("2 runs"))
+                .and(predicate::str::contains("\"command\": \"server\"")),
+        );
+
+    process.kill().unwrap();
+    process.wait().unwrap();
+
+    hyperfine()
+        .arg(format!("--attach={}", process.id()))
+        .arg("--runs=2")
+        .assert()
+        .failure()
+        .code(4)
+        .stderr(predicate::str::contains("is not running"));
+}
+
+#[test]
+fn exports_bencher_metric_format() {
+    hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--export-bencher=-")
+        .arg("sleep 1.5")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "\"sleep 1.5\": {\n    \"latency\": {\n      \"value\": 1499999999.9",
+        ));
+}
+
+#[test]
+fn runs_can_be_specified_for_each_command() {
+    hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--runs=3")
+        .arg("sleep 0.1")
+        .arg("sleep 0.2")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2 runs").and(predicate::str::contains("3 runs")));
+
+    hyperfine_debug()
+        .arg("--min-runs=2")
+        .arg("--max-runs=3")
+        .arg("--max-runs=4")
+        .arg("--reference=sleep 0.1")
+        .arg("sleep 0.2")
+        .assert()
+        .success();
+
+    hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--runs=3")
+        .arg("sleep 0.1")
+        .arg("sleep 0.2")
+        .arg("sleep 0.3")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The '--runs', '--min-runs' and '--max-runs' options have to be provided just once or N times",
+        ));
+}
+
+#[test]
+fn supports_different_timers() {
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--timer=task-clock")
+        .arg("echo dummy benchmark")
+        .assert()
+        .success();
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--timer=unknown")
+        .arg("echo dummy benchmark")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn just_runs_the_commands() {
+    hyperfine_debug()
+        .arg("--just-run=2")
+        .arg("sleep 0.5")
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .stdout("0.5\n0.5\n1\n1\n");
+
+    hyperfine_debug()
+        .arg("--just-run=1")
+        .arg("--just-run-format=jsonl")
+        .arg("sleep 0.5")
+        .assert()
+        .success()
+        .stdout(
+            "{\"command\":\"sleep 0.5\",\"exit_code\":0,\"run\":0,\"time_system\":0.0,\
+             \"time_user\":0.0,\"time_wall_clock\":0.5}\n",
+        );
+}
+
+#[test]
+fn does_not_ask_for_confirmation_without_a_terminal() {
+    hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--confirm-after=1s")
+        .arg("sleep 2")
+        .assert()
+        .success();
+
+    hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--confirm-after=1s")
+        .arg("--yes")
+        .arg("sleep 2")
+        .assert()
+        .success();
+
+    hyperfine_debug()
+        .arg("--confirm-after=soon")
+        .arg("sleep 2")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn exports_opentelemetry_spans() {
+    hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--export-otlp=-")
+        .arg("sleep 1.5")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("\"name\": \"sleep 1.5\"")
+                .and(predicate::str::contains("\"name\": \"run 1\""))
+                .and(predicate::str::contains("\"parentSpanId\"")),
+        );
+}
+
+#[test]
+fn captures_output_of_first_run_in_json_export() {
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--capture-output=5")
+        .arg("--export-json=-")
+        .arg("echo version 1.2.3; echo warning >&2")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("\"stdout\": \"versi\"")
+                .and(predicate::str::contains("\"stderr\": \"warni\"")),
+        );
+}
+
+#[cfg(unix)]
+#[test]
+fn measures_the_stages_of_pipelines_separately() {
+    let output = hyperfine()
+        .arg("--runs=2")
+        .arg("--pipeline-stages")
+        .arg("--export-json=-")
+        .arg("echo 'a|b' | tr '|' ' ' | grep -q 'a b'")
+        .arg("echo a && echo b | cat")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Stage 3 (mean)")
+                .and(predicate::str::contains("Stage 4").not()),
+        )
+        .get_output()
+        .stdout
+        .clone();
+    let json = String::from_utf8(output).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json[json.find("{\n").unwrap()..]).unwrap();
+
+    let num_stages = |result: usize| {
+        json["results"][result]["measurements"][0]["pipeline_stages"]
+            .as_array()
+            .map_or(0, |stages| stages.len())
+    };
+    assert_eq!(num_stages(0), 3);
+    assert_eq!(num_stages(1), 0);
+
+    hyperfine()
+        .arg("--pipeline-stages")
+        .arg("--shell=none")
+        .arg("echo a")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "'--pipeline-stages' requires a shell",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn records_the_randomized_environment_offsets() {
+    let output = hyperfine()
+        .arg("--runs=5")
+        .arg("--randomize-env-offset=10")
+        .arg("--export-json=-")
+        .arg("test ${#HYPERFINE_RANDOMIZED_ENVIRONMENT_OFFSET} -le 10")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json = String::from_utf8(output).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json[json.find("{\n").unwrap()..]).unwrap();
+
+    let measurements = json["results"][0]["measurements"].as_array().unwrap();
+    assert_eq!(measurements.len(), 5);
+    assert!(measurements
+        .iter()
+        .all(|m| m["env_offset"].as_u64().is_some_and(|offset| offset <= 10)));
+}
+
+#[test]
+fn replaces_run_placeholders() {
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--warmup=1")
+        .arg("--prepare=echo prepare {run}/{total_runs}")
+        .arg("--show-output")
+        .arg("echo run {run}")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("prepare warmup-0/2\nrun warmup-0")
+                .and(predicate::str::contains("prepare 0/2\nrun 0"))
+                .and(predicate::str::contains("prepare 1/2\nrun 1")),
+        );
+
+    hyperfine()
+        .arg("sleep 0 {total_runs}")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The '{total_runs}' placeholder can only be used with a fixed number of runs",
+        ));
+}
+
+#[test]
+fn refers_to_the_previous_run_in_stateful_benchmarks() {
+    let output = hyperfine()
+        .arg("--runs=2")
+        .arg("--warmup=1")
+        .arg("--prepare=echo prepare [{prev_run}]")
+        .arg("--show-output")
+        .arg("--export-json=-")
+        .arg("echo run [{prev_run}] {run}")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(stdout.contains("prepare []\nrun [] warmup-0"));
+    assert!(stdout.contains("prepare [warmup-0]\nrun [warmup-0] 0"));
+    assert!(stdout.contains("prepare [0]\nrun [0] 1"));
+
+    let json_start = stdout.find("{\n").unwrap();
+    let export: serde_json::Value = serde_json::from_str(&stdout[json_start..]).unwrap();
+    assert_eq!(export["results"][0]["stateful"], true);
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--stateful")
+        .arg("--export-markdown=-")
+        .arg("echo a")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("`echo a` (stateful)"));
+
+    hyperfine()
+        .arg("--jobs=2")
+        .arg("echo {prev_run}")
+        .arg("echo b")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("can not be run in parallel"));
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn copies_export_to_clipboard() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tempdir = tempfile::tempdir().unwrap();
+
+    // Replace 'xclip' by a script that writes the clipboard content to a file
+    let clipboard = tempdir.path().join("clipboard");
+    let xclip = tempdir.path().join("xclip");
+    std::fs::write(
+        &xclip,
+        format!("#!/bin/sh\ncat > '{}'\n", clipboard.display()),
+    )
+    .unwrap();
+    std::fs::set_permissions(&xclip, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!(
+        "{}:{}",
+        tempdir.path().display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    hyperfine_debug()
+        .env("PATH", &path)
+        .env_remove("WAYLAND_DISPLAY")
+        .arg("--runs=2")
+        .arg("--export-clipboard=md")
+        .arg("sleep 1")
+        .assert()
+        .success();
+
+    let content = std::fs::read_to_string(&clipboard).unwrap();
+    assert!(content.starts_with("| Command | Mean [s] |"));
+    assert!(content.contains("| `sleep 1` | 1.000 ± 0.000 |"));
+}
+
+#[cfg(unix)]
+#[test]
+fn passes_commands_to_powershell_via_command_flag() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tempdir = tempfile::tempdir().unwrap();
+
+    // A fake 'pwsh' which only accepts commands that are passed via '-Command'
+    let pwsh = tempdir.path().join("pwsh");
+    std::fs::write(
+        &pwsh,
+        "#!/bin/sh\n[ \"$1\" = \"-Command\" ] || exit 1\nexec sh -c \"$2\"\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&pwsh, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    hyperfine()
+        .arg(format!("--shell={}", pwsh.display()))
+        .arg("--runs=2")
+        .arg("exit 0")
+        .assert()
+        .success();
+
+    hyperfine()
+        .arg(format!("--shell={}", pwsh.display()))
+        .arg("--runs=2")
+        .arg("exit 1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Command terminated with non-zero exit code",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn records_profiles_in_extra_runs() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tempdir = tempfile::tempdir().unwrap();
+
+    // A fake profiler which writes the profile and runs the given command
+    let profiler = tempdir.path().join("profiler");
+    std::fs::write(
+        &profiler,
+        "#!/bin/sh\n[ \"$1\" = \"-o\" ] || exit 1\necho profile > \"$2\"\nshift 2\nexec \"$@\"\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&profiler, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let profile_dir = tempdir.path().join("profiles");
+    let export = tempdir.path().join("results.json");
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg(format!(
+            "--profile-with={} -o {{output}}",
+            profiler.display()
+        ))
+        .arg("--profile-runs=2")
+        .arg(format!("--profile-dir={}", profile_dir.display()))
+        .arg(format!("--export-json={}", export.display()))
+        .arg("echo a")
+        .arg("echo b")
+        .assert()
+        .success();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(export).unwrap()).unwrap();
+    for (number, result) in json["results"].as_array().unwrap().iter().enumerate() {
+        assert_eq!(result["measurements"].as_array().unwrap().len(), 2);
+        let profiles = result["profiles"].as_array().unwrap();
+        assert_eq!(profiles.len(), 2);
+        for (run, profile) in profiles.iter().enumerate() {
+            let profile = std::path::Path::new(profile.as_str().unwrap());
+            assert_eq!(
+                profile,
+                profile_dir.join(format!("benchmark-{}-profile-{run}", number + 1))
+            );
+            assert_eq!(std::fs::read_to_string(profile).unwrap(), "profile\n");
+        }
+    }
+
+    hyperfine()
+        .arg("--profile-with=perf record")
+        .arg("echo a")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("placeholder"));
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_benchmarks_on_multiple_hosts() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tempdir = tempfile::tempdir().unwrap();
+
+    // Replace 'ssh' by a script that runs the remote command locally
+    let ssh = tempdir.path().join("ssh");
+    std::fs::write(
+        &ssh,
+        "#!/bin/sh\ntest \"$3\" = broken && exit 255\neval \"$4\"\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&ssh, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let hyperfine_binary = assert_cmd::cargo::cargo_bin("hyperfine");
+    let path = format!(
+        "{}:{}:{}",
+        tempdir.path().display(),
+        hyperfine_binary.parent().unwrap().display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    let hosts = tempdir.path().join("hosts");
+    std::fs::write(&hosts, "# build machines\nhost-a\nhost-b\n").unwrap();
+    let export = tempdir.path().join("results.json");
+
+    hyperfine()
+        .env("PATH", &path)
+        .arg("--debug-mode")
+        .arg(format!("--hosts={}", hosts.display()))
+        .arg("--runs=2")
+        .arg(format!("--export-json={}", export.display()))
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "  Command   host-a              host-b\n  sleep 1   1.000 s ± 0.000 s   1.000 s ± 0.000 s\n",
+        ));
+
+    let results: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&export).unwrap()).unwrap();
+    assert_eq!(
+        results["hosts"]["host-a"]["results"][0]["command"],
+        "sleep 1"
+    );
+    assert_eq!(
+        results["hosts"]["host-b"]["results"][0]["command"],
+        "sleep 1"
+    );
+
+    std::fs::write(&hosts, "host-a\nbroken\n").unwrap();
+    hyperfine()
+        .env("PATH", &path)
+        .arg("--debug-mode")
+        .arg(format!("--hosts={}", hosts.display()))
+        .arg("sleep 1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The benchmarks failed on 1 of 2 hosts: broken",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn waits_for_other_sessions_that_hold_the_lock() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let lock = tempdir.path().join("hyperfine.lock");
+    let mut first = hyperfine_raw_command()
+        .arg(format!("--lock={}", lock.display()))
+        .arg("--runs=1")
+        .arg("sleep 2")
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+
+    // The session writes its process ID to the lock file once it holds the lock
+    while std::fs::read_to_string(&lock).map_or(true, |content| content.is_empty()) {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+
+    hyperfine()
+        .arg(format!("--lock={}", lock.display()))
+        .arg("--no-lock")
+        .arg("--runs=1")
+        .arg("echo second")
+        .assert()
+        .failure();
+    hyperfine()
+        .arg("--no-lock")
+        .arg("--runs=1")
+        .arg("echo second")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Waiting").not());
+    hyperfine()
+        .arg(format!("--lock={}", lock.display()))
+        .arg("--fail-if-locked")
+        .arg("--runs=1")
+        .arg("echo second")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "Another hyperfine session (PID {}) holds the lock file",
+            first.id()
+        )));
+    hyperfine()
+        .arg(format!("--lock={}", lock.display()))
+        .arg("--runs=1")
+        .arg("echo second")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(format!(
+            "Waiting for the other hyperfine session (PID {}) to finish",
+            first.id()
+        )));
+
+    assert!(first.wait().unwrap().success());
+}
+
+#[cfg(unix)]
+#[test]
+fn nested_sessions_do_not_wait_for_the_lock_of_their_parent() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let lock = tempdir.path().join("hyperfine.lock");
+    let nested = format!(
+        "{} --lock={} --fail-if-locked --runs=1 true",
+        env!("CARGO_BIN_EXE_hyperfine"),
+        lock.display()
+    );
+
+    hyperfine()
+        .arg(format!("--lock={}", lock.display()))
+        .arg("--runs=1")
+        .arg(nested)
+        .timeout(std::time::Duration::from_secs(30))
+        .assert()
+        .success();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn pauses_processes_during_the_benchmarks() {
+    let tempdir = tempfile::tempdir().unwrap();
+
+    // Use a copy of 'sleep' with a unique name, such that no other processes are affected
+    let sleep = tempdir.path().join("hf-pause-test");
+    std::fs::copy("/bin/sleep", &sleep).unwrap();
+    let mut process = std::process::Command::new(&sleep)
+        .arg("30")
+        .spawn()
+        .unwrap();
+    let state = || {
+        std::fs::read_to_string(format!("/proc/{}/status", process.id()))
+            .unwrap()
+            .lines()
+            .find(|line| line.starts_with("State:"))
+            .unwrap()
+            .to_string()
+    };
+
+    hyperfine()
+        .arg("--runs=1")
+        .arg("--pause-processes=hf-pause-test,hf-not-running")
+        .arg("--show-output")
+        .arg(format!("grep State: /proc/{}/status", process.id()))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(stopped)"))
+        .stderr(predicate::str::contains(
+            "No running process matches 'hf-not-running'",
+        ));
+    assert!(!state().contains("(stopped)"));
+
+    process.kill().unwrap();
+    process.wait().unwrap();
+}
+
+#[test]
+fn estimates_the_uncertainty_of_relative_speeds() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let times = tempdir.path().join("times.csv");
+    std::fs::write(
+        &times,
+        "command,time\nfast,0.10\nfast,0.12\nfast,0.11\nslow,0.20\nslow,0.22\nslow,0.21\n",
+    )
+    .unwrap();
+
+    hyperfine()
+        .arg("analyze")
+        .arg("--relative-error=naive")
+        .arg(&times)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "1.91 ± 0.20 times faster than slow",
+        ));
+
+    hyperfine()
+        .arg("analyze")
+        .arg("--export-markdown=-")
+        .arg("--export-columns=command,relative,relative-ci")
+        .arg(&times)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "| `slow` | 1.91 ± 0.09 | 1.74 … 2.10 |",
+        ));
+}
+
+#[test]
+fn analyzes_run_times_measured_elsewhere() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let times = tempdir.path().join("times.csv");
+    std::fs::write(
+        &times,
+        "command,time\nfast,0.10\nfast,0.12\nfast,0.11\nslow,0.20\nslow,0.22\nslow,0.21\n",
+    )
+    .unwrap();
+    let export = tempdir.path().join("results.json");
+
+    hyperfine()
+        .arg("analyze")
+        .arg(format!("--export-json={}", export.display()))
+        .arg(&times)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Benchmark 1: fast"))
+        .stdout(predicate::str::contains("Benchmark 2: slow"))
+        .stdout(predicate::str::contains("110.0 ms ±  10.0 ms"))
+        .stdout(predicate::str::contains(
+            "1.91 ± 0.09 times faster than slow",
+        ));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(export).unwrap()).unwrap();
+    let results = json["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[1]["command"], "slow");
+    assert_eq!(results[1]["measurements"].as_array().unwrap().len(), 3);
+
+    hyperfine()
+        .arg("analyze")
+        .arg(tempdir.path().join("missing.csv"))
+        .assert()
+        .failure();
+}
+
+#[test]
+fn detects_duplicate_benchmarks() {
+    hyperfine_debug()
+        .arg("--parameter-list")
+        .arg("t")
+        .arg("1,2,1")
+        .arg("sleep {t}")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The same benchmark has been specified more than once: 'sleep 1'",
+        ));
+
+    let output = hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--duplicates=merge")
+        .arg("--export-json=-")
+        .arg("sleep 1")
+        .arg("sleep 2")
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&output[output.find("{\n").unwrap()..]).unwrap();
+    let results = json["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["command"], "sleep 1");
+    assert_eq!(results[0]["measurements"].as_array().unwrap().len(), 4);
+
+    hyperfine_debug()
+        .arg("--duplicates=keep")
+        .arg("sleep 1")
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Benchmark 2: sleep 1"));
+}
+
+#[cfg(unix)]
+#[test]
+fn measures_the_change_in_disk_usage() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let output = tempdir.path().join("output");
+    std::fs::create_dir(&output).unwrap();
+    let export = tempdir.path().join("results.json");
+
+    hyperfine()
+        .arg("--runs=3")
+        .arg(format!("--measure-disk={}", output.display()))
+        .arg(format!("--export-json={}", export.display()))
+        .arg(format!(
+            "head -c 1000 /dev/zero > {}/$HYPERFINE_RUN_NUMBER",
+            output.display()
+        ))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Disk (mean):          +1000 B"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(export).unwrap()).unwrap();
+    for measurement in json["results"][0]["measurements"].as_array().unwrap() {
+        assert_eq!(measurement["disk_usage_delta"]["value"], 1000.0);
+    }
+}
+
+#[test]
+fn rings_the_bell_at_the_end_of_the_session() {
+    hyperfine_debug()
+        .arg("--notify=bell")
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("\x07"));
+
+    hyperfine_debug()
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("\x07").not());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn records_runs_that_exceed_the_memory_limit() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let export = tempdir.path().join("results.json");
+
+    // A process can not even be started with a limit of one megabyte
+    hyperfine()
+        .arg("--runs=2")
+        .arg("-N")
+        .arg("--parameter-list")
+        .arg("limit")
+        .arg("1M,1G")
+        .arg("--limit-memory={limit}")
+        .arg(format!("--export-json={}", export.display()))
+        .arg("true")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "2 of 2 runs have failed, probably because they exceeded the memory limit",
+        ));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(export).unwrap()).unwrap();
+    let exceeded = |result: &serde_json::Value| {
+        result["measurements"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|measurement| measurement["memory_limit_exceeded"] == true)
+            .count()
+    };
+    assert_eq!(exceeded(&json["results"][0]), 2);
+    assert_eq!(exceeded(&json["results"][1]), 0);
+
+    // Crashes are not attributed to the memory limit
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--limit-memory=1G")
+        .arg("kill -SEGV $$")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The process has been terminated by a signal",
+        ));
+
+    hyperfine_debug()
+        .arg("--limit-memory=lots")
+        .arg("sleep 1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid value for '--limit-memory'",
+        ));
+}
+
+#[test]
+fn writes_the_output_in_ascii() {
+    let output = hyperfine_debug()
+        .arg("--ascii")
+        .arg("--time-unit=microsecond")
+        .arg("--export-markdown=-")
+        .arg("sleep 1.0")
+        .arg("sleep 2.0")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.is_ascii());
+    assert!(stdout.contains("Time (mean +/- sd):  1000000.0 us +/-"));
+    assert!(stdout.contains("Range (min ... max): 1000000.0 us ..."));
+    assert!(stdout.contains("| Mean [us] |"));
+    assert!(stdout.contains("2.00 +/- 0.00 times faster"));
+}
+
+#[test]
+fn prints_the_statistical_power_of_the_comparisons() {
+    hyperfine_debug()
+        .arg("--power-analysis")
+        .arg("sleep 1.0")
+        .arg("sleep 2.0")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Statistical power")
+                .and(predicate::str::contains("Compared to sleep 1.0:"))
+                .and(predicate::str::contains(
+                    "sleep 2.0: difference +100.0%, detectable from 0.0%, 2 runs needed to detect 1%",
+                )),
+        );
+
+    hyperfine_debug()
+        .arg("sleep 1.0")
+        .arg("sleep 2.0")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Statistical power").not());
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn counts_the_system_calls_of_each_command() {
+    use std::os::unix::fs::PermissionsExt;
+
+    // A stand-in for strace, which writes a summary with a fixed number of calls
+    let tempdir = tempfile::tempdir().unwrap();
+    let strace = tempdir.path().join("strace");
+    std::fs::write(
+        &strace,
+        "#!/bin/sh\n\
+         while [ \"$1\" != \"--\" ]; do [ \"$1\" = \"-o\" ] && out=\"$2\"; shift; done\n\
+         shift\n\
+         echo '100.00    0.000100           2        42           total' > \"$out\"\n\
+         exec \"$@\"\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&strace, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!(
+        "{}:{}",
+        tempdir.path().display(),
+        std::env::var("PATH").unwrap()
+    );
+
+    let export = tempdir.path().join("results.json");
+    hyperfine()
+        .env("PATH", &path)
+        .arg("--runs=2")
+        .arg("--count-syscalls")
+        .arg(format!("--export-json={}", export.display()))
+        .arg("echo a")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Syscalls (mean):       42 ± 0"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&export).unwrap()).unwrap();
+    assert_eq!(
+        json["results"][0]["syscall_counts"],
+        serde_json::json!([42, 42, 42])
+    );
+    assert!(json["results"][0]["measurements"][0]["syscall_count"].is_null());
+
+    hyperfine()
+        .env("PATH", &path)
+        .arg("--runs=2")
+        .arg("--count-syscalls=inline")
+        .arg(format!("--export-json={}", export.display()))
+        .arg("echo a")
+        .assert()
+        .success();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&export).unwrap()).unwrap();
+    assert!(json["results"][0]["syscall_counts"].is_null());
+    assert_eq!(json["results"][0]["measurements"][1]["syscall_count"], 42);
+}
+
+#[test]
+fn prints_the_capabilities_of_the_system() {
+    hyperfine().arg("--capabilities").assert().success().stdout(
+        predicate::str::contains("Timer 'monotonic'")
+            .and(predicate::str::contains("GPU metrics (--sample-gpu)")),
+    );
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn leaves_out_measurements_that_are_not_available() {
+    let empty_path = tempfile::tempdir().unwrap();
+    hyperfine()
+        .env("PATH", empty_path.path())
+        .arg("--runs=2")
+        .arg("--count-syscalls")
+        .arg("--shell=none")
+        .arg("/bin/true")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Warning: System calls are not counted: 'strace' is not installed",
+        ))
+        .stdout(predicate::str::contains("Syscalls").not());
+}
+
+#[test]
+fn bases_the_relative_speed_comparison_on_the_selected_metric() {
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--relative-metric=memory")
+        .arg("echo a")
+        .arg("echo b")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Summary (by peak memory usage)"));
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--relative-metric=memory")
+        .arg("--sort=command")
+        .arg("echo a")
+        .arg("echo b")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Relative speed comparison (by peak memory usage)",
+        ));
+}
+
+#[test]
+#[cfg(unix)]
+fn annotates_the_benchmarks_with_the_git_revision() {
+    let repository = tempfile::tempdir().unwrap();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(repository.path())
+            .args([
+                "-c",
+                "user.name=hyperfine",
+                "-c",
+                "user.email=hyperfine@example.com",
+            ])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success());
+    };
+    let script = repository.path().join("app.sh");
+    std::fs::write(&script, "true\n").unwrap();
+    git(&["init", "--quiet"]);
+    git(&["add", "app.sh"]);
+    git(&["commit", "--quiet", "--message", "Initial commit"]);
+    std::fs::write(&script, "true\ntrue\n").unwrap();
+
+    let output = hyperfine()
+        .arg("--runs=2")
+        .arg("--git-annotate")
+        .arg("--export-json=-")
+        .arg(format!("sh {}", script.display()))
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    let json_start = stdout.find("{\n").unwrap();
+    let export: serde_json::Value = serde_json::from_str(&stdout[json_start..]).unwrap();
+    let result = &export["results"][0];
+
+    let commit = result["git"]["commit"].as_str().unwrap();
+    assert!(!commit.is_empty());
+    assert_eq!(result["git"]["dirty"], true);
+    assert_eq!(
+        result["command"],
+        format!("sh {} ({commit}-dirty)", script.display())
+    );
+}
+
+#[test]
+fn exports_the_results_with_a_custom_template() {
+    let directory = tempfile::tempdir().unwrap();
+    let template = directory.path().join("results.tpl");
+    std::fs::write(
+        &template,
+        "{% for result in results -%}\n\
+         {{ loop.index }}. {{ result.name }}: {{ result.mean | round(3) }} s\n\
+         {% endfor %}",
+    )
+    .unwrap();
+
+    hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--export-template")
+        .arg(&template)
+        .arg("-")
+        .arg("sleep 0.5")
+        .arg("sleep 1.25")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "1. sleep 0.5: 0.500 s\n2. sleep 1.25: 1.250 s\n",
+        ));
+
+    std::fs::write(&template, "{% if result %}").unwrap();
+    hyperfine_debug()
+        .arg("--export-template")
+        .arg(&template)
+        .arg("-")
+        .arg("sleep 1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Line 1: in the 'if' statement: '{% endif %}' is missing",
+        ));
+}
+
+#[test]
+fn overwrites_longer_intermediate_exports() {
+    let directory = tempfile::tempdir().unwrap();
+    let template = directory.path().join("fastest.tpl");
+    let export = directory.path().join("fastest.txt");
+    std::fs::write(&template, "fastest: {{ results.0.command }}\n").unwrap();
+
+    // The intermediate export after the first benchmark is longer than the final one
+    hyperfine_debug()
+        .arg("--sort=mean-time")
+        .arg("--export-template")
+        .arg(&template)
+        .arg(&export)
+        .arg("sleep 1.000")
+        .arg("sleep 0.5")
+        .assert()
+        .success();
+    assert_eq!(
+        std::fs::read_to_string(&export).unwrap(),
+        "fastest: sleep 0.5\n"
+    );
+}
+
+#[test]
+fn delays_the_start_of_the_session() {
+    let start = std::time::Instant::now();
+    hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--start-after=1.2s")
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Waiting 1s until the start"));
+    assert!(start.elapsed() >= std::time::Duration::from_millis(1200));
+
+    hyperfine_debug()
+        .arg("--start-at=25:00")
+        .arg("sleep 1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "'25:00' is not a valid time of day (use HH:MM or HH:MM:SS)",
+        ));
+
+    hyperfine_debug()
+        .arg("--start-at=02:00")
+        .arg("--start-after=1h")
+        .arg("sleep 1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn computes_derived_metrics_for_each_run() {
+    let output = hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--parameter-scan")
+        .arg("n")
+        .arg("1")
+        .arg("2")
+        .arg("--derived=speed = 2*{n} / time")
+        .arg("--export-json=-")
+        .arg("sleep {n}")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Derived (mean ± σ):  speed = 2.000 ± 0.000",
+        ))
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    let json_start = stdout.find("{\n").unwrap();
+    let export: serde_json::Value = serde_json::from_str(&stdout[json_start..]).unwrap();
+    for result in export["results"].as_array().unwrap() {
+        for measurement in result["measurements"].as_array().unwrap() {
+            assert_eq!(measurement["derived"]["speed"], 2.0);
+        }
+    }
+
+    hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--derived=speed = 2*{n} / time")
+        .arg("--export-csv=-")
+        .arg("--parameter-list")
+        .arg("n")
+        .arg("4")
+        .arg("sleep 2")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "parameter_n,speed_mean,speed_stddev\nsleep 2,2.000000,0.000000,2.000000,0.000000,0.000000,2.000000,2.000000,4,4.000000,0.000000",
+        ));
+
+    hyperfine_debug()
+        .arg("--derived=speed = 2*{n} / time")
+        .arg("sleep 1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "uses the parameter '{n}', which does not have a numeric value for 'sleep 1'",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn fails_on_warnings_in_strict_mode() {
+    let output = hyperfine()
+        .arg("--runs=2")
+        .arg("--shell=none")
+        .arg("--ignore-failure")
+        .arg("--strict")
+        .arg("--export-json=-")
+        .arg("false")
+        .assert()
+        .code(7)
+        .stderr(predicate::str::contains(
+            "Warnings have been reported in strict mode: 'false' (non_zero_exit_code)",
+        ))
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    let json_start = stdout.find("{\n").unwrap();
+    let export: serde_json::Value = serde_json::from_str(&stdout[json_start..]).unwrap();
+    let warnings = export["results"][0]["warnings"].as_array().unwrap();
+    assert!(warnings.iter().any(|warning| {
+        warning["kind"] == "non_zero_exit_code"
+            && warning["message"] == "Ignoring non-zero exit code."
+    }));
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--shell=none")
+        .arg("--ignore-failure")
+        .arg("false")
+        .assert()
+        .success();
+
+    hyperfine_debug()
+        .arg("--strict")
+        .arg("sleep 1")
+        .arg("sleep 2")
+        .assert()
+        .success();
+}
+
+#[test]
+fn exports_to_multiple_targets_in_the_given_order() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let json = tempdir.path().join("results.json");
+
+    let output = hyperfine_debug()
+        .arg("--runs=2")
+        .arg("--style=none")
+        .arg("--export")
+        .arg("csv:-")
+        .arg(format!("--export=json:{}", json.display()))
+        .arg("--export=markdown:-")
+        .arg("sleep 1")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    let csv = stdout.find("command,mean,stddev").unwrap();
+    let markdown = stdout.find("| Command | Mean [s]").unwrap();
+    assert!(csv < markdown);
+
+    let export: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&json).unwrap()).unwrap();
+    assert_eq!(export["results"][0]["command"], "sleep 1");
+
+    hyperfine_debug()
+        .arg("--export=yaml:-")
+        .arg("sleep 1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown export format 'yaml'"));
+}
+
+#[test]
+fn kills_runs_that_take_much_longer_than_the_fastest_command() {
+    let output = hyperfine_debug()
+        .arg("--runs=3")
+        .arg("--auto-timeout=3x")
+        .arg("--export-json=-")
+        .arg("sleep 1")
+        .arg("sleep 5")
+        .arg("sleep 2")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "A run was killed after the timeout of 3.000 s",
+        ))
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    let json_start = stdout.find("{\n").unwrap();
+    let export: serde_json::Value = serde_json::from_str(&stdout[json_start..]).unwrap();
+    let results = export["results"].as_array().unwrap();
+
+    assert_eq!(results[0].get("timeout"), None);
+    assert_eq!(results[1]["timeout"]["value"], 3.0);
+    assert_eq!(results[1]["timed_out"], true);
+    assert_eq!(results[1]["measurements"][0]["timed_out"], true);
+    assert_eq!(
+        results[1]["measurements"][0]["time_wall_clock"]["value"],
+        3.0
+    );
+    assert_eq!(results[1]["measurements"].as_array().unwrap().len(), 1);
+    assert_eq!(results[2].get("timed_out"), None);
+    assert_eq!(results[2]["measurements"].as_array().unwrap().len(), 3);
+
+    hyperfine_debug()
+        .arg("--auto-timeout=0.5x")
+        .arg("sleep 1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid value '0.5x' for '--auto-timeout'",
+        ));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn measures_the_run_queue_delay() {
+    let output = hyperfine()
+        .arg("--runs=2")
+        .arg("--scheduling-delay")
+        .arg("--export-json=-")
+        .arg("echo a")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Run queue (mean):"))
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    let json_start = stdout.find("{\n").unwrap();
+    let export: serde_json::Value = serde_json::from_str(&stdout[json_start..]).unwrap();
+    for measurement in export["results"][0]["measurements"].as_array().unwrap() {
+        assert!(measurement["run_queue_delay"]["value"].as_f64().unwrap() >= 0.0);
+        assert!(measurement["time_on_cpu"]["value"].as_f64().unwrap() > 0.0);
+        // Only available if the kernel provides scheduler debug information
+        if std::path::Path::new("/proc/self/sched").exists() {
+            assert!(measurement["cpu_migrations"].as_u64().is_some());
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn binds_the_session_to_a_numa_node() {
+    if std::path::Path::new("/sys/devices/system/node/node0").exists() {
+        hyperfine()
+            .arg("--runs=2")
+            .arg("--numa-node=0")
+            .arg("echo a")
+            .assert()
+            .success();
+    }
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--numa-node=4095")
+        .arg("echo a")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("NUMA node 4095 does not exist"));
+}
+
+#[test]
+fn measures_the_noise_floor_in_between_runs() {
+    let output = hyperfine_debug()
+        .arg("--runs=7")
+        .arg("--noise-floor=3")
+        .arg("--export-json=-")
+        .arg("sleep 0.1")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match("Noise floor: .* 3 empty runs").unwrap())
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    let json_start = stdout.find("{\n").unwrap();
+    let export: serde_json::Value = serde_json::from_str(&stdout[json_start..]).unwrap();
+    let result = &export["results"][0];
+    assert_eq!(result["measurements"].as_array().unwrap().len(), 7);
+    assert_eq!(result["noise_floor"]["runs"], 3);
+    assert_eq!(result["noise_floor"]["mean"]["value"], 0.0);
+
+    hyperfine()
+        .arg("--runs=2")
+        .arg("--noise-floor")
+        .arg("--export-json=-")
+        .arg("echo a")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"noise_floor\""));
+
+    hyperfine()
+        .arg("--noise-floor")
+        .arg("-N")
+        .arg("echo a")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'--noise-floor' requires a shell"));
+}
+
+#[cfg(unix)]
+#[test]
+fn takes_the_measurements_from_the_shim() {
+    // The prefix takes 0.1 seconds to reach the shim, like a slow connection to a remote host
+    let prefix = format!(
+        "sh -c 'sleep 0.1; exec \"$@\"' - {}",
+        env!("CARGO_BIN_EXE_hyperfine-shim")
+    );
+    let output = hyperfine()
+        .arg(format!("--shim={prefix}"))
+        .arg("--runs=2")
+        .arg("--export-json=-")
+        .arg("--style=none")
+        .arg("echo output; echo error >&2")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    for measurement in json["results"][0]["measurements"].as_array().unwrap() {
+        assert!(measurement["time_wall_clock"]["value"].as_f64().unwrap() < 0.08);
+        assert!(measurement["peak_memory_usage"]["value"].as_f64().unwrap() > 0.0);
+        assert!(measurement.get("stderr").is_none());
+    }
+
+    hyperfine()
+        .arg("--shim=true")
+        .arg("--runs=1")
+        .arg("echo test")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Make sure you can run 'true sh -c \"\"'",
+        ));
 }